@@ -144,7 +144,9 @@ class ChadSelect:
                 first match that passes *valid*. ``>= 0`` returns the
                 match at that position (or ``""`` if out of bounds).
             query_str: Prefixed query string (e.g. ``"css:.price"``).
-            valid: Receives each candidate string, returns ``True`` to accept.
+            valid: Any callable; receives each candidate string and returns
+                ``True`` to accept. Exceptions it raises propagate to the
+                caller.
         """
         result = self.query(index, query_str)
         if result and valid(result[0]):
@@ -166,7 +168,11 @@ class ChadSelect:
         queries: Sequence[Tuple[int, str]],
         valid: Callable[[str], bool],
     ) -> List[str]:
-        """Like :meth:`select_first` but with a custom validity check."""
+        """Like :meth:`select_first` but with a custom validity check.
+
+        *valid* is called once per candidate of each query's result set; a
+        query wins only when every candidate passes.
+        """
         for index, query_str in queries:
             result = self.query(index, query_str)
             if result and all(valid(r) for r in result):
@@ -184,7 +190,11 @@ class ChadSelect:
         queries: Sequence[Tuple[int, str]],
         valid: Callable[[str], bool],
     ) -> List[str]:
-        """Like :meth:`select_many` but with a custom validity check."""
+        """Like :meth:`select_many` but with a custom validity check.
+
+        *valid* is called once per candidate; rejected candidates are
+        dropped before de-duplication.
+        """
         seen: set[str] = set()
         out: List[str] = []
         for index, query_str in queries:
@@ -683,6 +683,43 @@ class TestSelectManyWhere:
         assert "42" in r
         assert "7" in r
 
+    def test_validator_called_per_candidate(self):
+        cs = ChadSelect()
+        cs.add_text("1 0 42 0 7")
+        seen = []
+
+        def valid(s):
+            seen.append(s)
+            return s != "0"
+
+        r = cs.select_many_where([(-1, r"(\d+)")], valid)
+        assert seen == ["1", "0", "42", "0", "7"]
+        assert r == ["1", "42", "7"]
+
+
+class TestValidatorCallables:
+    def test_accepts_callable_object(self):
+        class MinLength:
+            def __init__(self, n):
+                self.n = n
+
+            def __call__(self, s):
+                return len(s) >= self.n
+
+        cs = ChadSelect()
+        cs.add_text("a: 12\nb: 12345")
+        r = cs.select_first_where(
+            [(0, r"a: (\d+)"), (0, r"b: (\d+)")],
+            MinLength(3),
+        )
+        assert r == ["12345"]
+
+    def test_validator_exception_propagates(self):
+        cs = ChadSelect()
+        cs.add_text("price: abc")
+        with pytest.raises(ValueError):
+            cs.select_where(0, r"price: (\w+)", lambda s: float(s) > 0)
+
 
 # ═══════════════════════════════════════════════════════════════════════════════
 #  query_batch
@@ -227,6 +227,18 @@ fn bench_regex(c: &mut Criterion) {
         b.iter(|| black_box(cs.query(-1, r"regex:[+-]\d+\.\d+%")));
     });
 
+    g.bench_function("dollar_prices_borrowed", |b| {
+        let mut cs = ChadSelect::new();
+        cs.add_html(ecom.clone());
+        b.iter(|| black_box(cs.query_borrowed(-1, r"regex:\$[\d,]+\.\d{2}")));
+    });
+
+    g.bench_function("dollar_prices_arc", |b| {
+        let mut cs = ChadSelect::new();
+        cs.add_html(ecom.clone());
+        b.iter(|| black_box(cs.query_arc(-1, r"regex:\$[\d,]+\.\d{2}")));
+    });
+
     g.finish();
 }
 
@@ -0,0 +1,186 @@
+//! Query deadlines and cancellation.
+//!
+//! A [`CancellationToken`] bounds how long `chadselect`'s
+//! `ChadSelect::query_cancellable` may run. While such a query runs its token is installed for the current
+//! thread, and the engines poll it between content items and between matches
+//! (the XPath engine also polls while walking the tree). Once it trips, they
+//! stop early and the query returns what it found so far, marked with a
+//! [`QueryStatus`].
+//!
+//! Tokens need the `std` feature (for the clock and the thread-local that
+//! holds the running query's token); without it queries run to completion.
+
+#[cfg(feature = "std")]
+use std::cell::{Cell, RefCell};
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// How many polls pass between reads of the clock. Checking the cancel flag
+/// is cheap; `Instant::now` is not free on the XPath engine's per-node path.
+#[cfg(feature = "std")]
+const CLOCK_INTERVAL: u32 = 64;
+
+/// A deadline and/or cancel flag for a query.
+///
+/// Clones share the flag, so a clone handed to another thread can
+/// [`cancel`](CancellationToken::cancel) a query running here.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use chadselect_core::CancellationToken;
+///
+/// let token = CancellationToken::with_timeout(Duration::from_secs(5));
+/// assert!(!token.is_expired());
+///
+/// token.clone().cancel();
+/// assert!(token.is_cancelled());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+/// Whether a query ran to completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueryStatus {
+    /// Every alternative and content item that needed running ran.
+    #[default]
+    Complete,
+    /// The token's deadline passed; the results are partial.
+    TimedOut,
+    /// [`CancellationToken::cancel`] was called; the results are partial.
+    Cancelled,
+}
+
+#[cfg(feature = "std")]
+impl CancellationToken {
+    /// A token with no deadline, tripped only by [`cancel`](Self::cancel).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A token that trips `timeout` from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// A token that trips at `deadline`.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Cancel every query using this token (or a clone of it).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether the deadline, if any, has passed.
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// The status a query polling this token now would report.
+    fn poll(&self, read_clock: bool) -> QueryStatus {
+        if self.is_cancelled() {
+            QueryStatus::Cancelled
+        } else if read_clock && self.is_expired() {
+            QueryStatus::TimedOut
+        } else {
+            QueryStatus::Complete
+        }
+    }
+}
+
+/// The token of the query running on this thread, and what it has reported.
+#[cfg(feature = "std")]
+struct Active {
+    token: CancellationToken,
+    polls: Cell<u32>,
+    tripped: Cell<QueryStatus>,
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static ACTIVE: RefCell<Option<Active>> = const { RefCell::new(None) };
+}
+
+/// Installs a token for the current thread until dropped.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub struct Scope {
+    previous: Option<Active>,
+}
+
+#[cfg(feature = "std")]
+impl Scope {
+    pub fn enter(token: &CancellationToken) -> Self {
+        let active = Active {
+            token: token.clone(),
+            polls: Cell::new(0),
+            tripped: Cell::new(QueryStatus::Complete),
+        };
+        let previous = ACTIVE.with(|a| a.borrow_mut().replace(active));
+        Self { previous }
+    }
+
+    /// How the query ended: the first trip any engine saw, if any.
+    pub fn status(&self) -> QueryStatus {
+        ACTIVE.with(|a| {
+            a.borrow()
+                .as_ref()
+                .map_or(QueryStatus::Complete, |active| active.tripped.get())
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        ACTIVE.with(|a| *a.borrow_mut() = previous);
+    }
+}
+
+/// Whether the query running on this thread should stop. Always `false`
+/// outside `ChadSelect::query_cancellable`.
+/// Once it returns `true` it keeps doing so for the rest of the query.
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub fn interrupted() -> bool {
+    ACTIVE.with(|a| {
+        let active = a.borrow();
+        let Some(active) = active.as_ref() else {
+            return false;
+        };
+        if active.tripped.get() != QueryStatus::Complete {
+            return true;
+        }
+        let polls = active.polls.get().wrapping_add(1);
+        active.polls.set(polls);
+        let status = active.token.poll(polls % CLOCK_INTERVAL == 1);
+        active.tripped.set(status);
+        status != QueryStatus::Complete
+    })
+}
+
+/// Without `std` there is no token to poll.
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+pub fn interrupted() -> bool {
+    false
+}
@@ -0,0 +1,62 @@
+//! Email address and phone number harvesting, shared by the
+//! `extract-emails()` / `extract-phones('US')` pipe functions and
+//! `chadselect`'s `ChadSelect::extract_contacts`.
+//!
+//! Phone numbers are parsed and validated with libphonenumber's metadata,
+//! behind the `phone` feature.
+
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::engine::regex::compiled;
+use crate::patterns::EMAIL;
+
+/// A run that may be a phone number: an optional `+`, then digits with the
+/// usual separators, at least seven characters long.
+#[cfg(feature = "phone")]
+const PHONE_PATTERN: &str = r"\+?\(?\d[\d\s().\-/]{5,}\d";
+
+/// The email addresses in `text`, in order, without case-insensitive
+/// duplicates.
+pub fn emails(text: &str) -> Vec<String> {
+    let re = compiled(EMAIL).expect("valid regex");
+    let mut seen = BTreeSet::new();
+    re.find_iter(text)
+        .map(|m| m.as_str())
+        .filter(|email| seen.insert(email.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The two-letter region `region` names (`US`, `gb`), for numbers written
+/// without a country code.
+#[cfg(feature = "phone")]
+pub fn region(region: &str) -> Result<phonenumber::country::Id, String> {
+    region
+        .trim()
+        .to_uppercase()
+        .parse()
+        .map_err(|_| format!("unknown region '{}'", region))
+}
+
+/// The valid phone numbers in `text` as E.164, in order, without duplicates.
+/// Numbers without a country code are read as numbers of `region`; with no
+/// region only international numbers (`+44 20 …`) are found.
+#[cfg(feature = "phone")]
+pub fn phones(text: &str, region: Option<phonenumber::country::Id>) -> Vec<String> {
+    let re = compiled(PHONE_PATTERN).expect("valid regex");
+    let mut seen = BTreeSet::new();
+    re.find_iter(text)
+        .filter_map(|m| phone(m.as_str(), region))
+        .filter(|number| seen.insert(number.clone()))
+        .collect()
+}
+
+/// `candidate` as an E.164 number, when it parses and is valid.
+#[cfg(feature = "phone")]
+fn phone(candidate: &str, region: Option<phonenumber::country::Id>) -> Option<String> {
+    let number = phonenumber::parse(region, candidate).ok()?;
+    phonenumber::is_valid(&number)
+        .then(|| number.format().mode(phonenumber::Mode::E164).to_string())
+}
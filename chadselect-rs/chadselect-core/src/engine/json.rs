@@ -0,0 +1,172 @@
+//! JMESPath extraction engine.
+//!
+//! Evaluates JMESPath expressions against a JMESPath value tree
+//! (`Arc<Variable>`). Compiled expressions are cached per thread; `chadselect`
+//! also caches the converted tree per document, so its queries evaluate
+//! without re-converting the JSON. [`process`] converts on every call.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use jmespath::Expression;
+
+use crate::functions;
+use crate::warnings::warning;
+
+thread_local! {
+    /// Cache of compiled JMESPath expressions, keyed by the expression string.
+    ///
+    /// `jmespath::compile` parses the expression on every call; the converted
+    /// document tree is already cached per document, but the *expression* was
+    /// recompiled per query (a flat slice of fleet CPU). The crawler runs the
+    /// same expressions across many documents, so compiling once per distinct
+    /// expression (per thread) removes that. `Expression<'static>` is `Clone`
+    /// (cheap — it shares the parsed AST), so we clone out of the cache. Invalid
+    /// expressions cache as `None` to avoid re-parsing / re-warning each call.
+    static COMPILED: RefCell<HashMap<String, Option<Expression<'static>>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Compile `path` (or fetch the cached `Expression`). Returns `None` for an
+/// invalid expression, warning once on first compile.
+#[doc(hidden)]
+pub fn compiled(path: &str) -> Option<Expression<'static>> {
+    COMPILED.with(|c| {
+        if let Some(e) = c.borrow().get(path) {
+            return e.clone();
+        }
+        let compiled = match jmespath::compile(path) {
+            Ok(expr) => Some(expr),
+            Err(e) => {
+                warning!(
+                    InvalidQuery,
+                    "Invalid JMESPath expression '{}': {}",
+                    path,
+                    e
+                );
+                None
+            }
+        };
+        c.borrow_mut().insert(path.to_string(), compiled.clone());
+        compiled
+    })
+}
+
+/// Process a JMESPath expression (potentially with a `>>` function chain)
+/// against a JSON document, returning matches.
+///
+/// - Scalars are returned as strings, arrays flattened, objects as compact
+///   JSON; `null` yields nothing.
+/// - Invalid expressions and invalid JSON return an empty vector (never
+///   panics).
+pub fn process(path_with_functions: &str, json: &str) -> Vec<String> {
+    let (path, text_functions) = functions::split_functions(path_with_functions);
+
+    let expression = match compiled(&path) {
+        Some(expr) => expr,
+        None => return vec![],
+    };
+
+    let results = match jmespath::Variable::from_json(json) {
+        Ok(data) => search(&expression, &jmespath::Rcvar::new(data)),
+        Err(e) => {
+            warning!(InvalidContent, "Failed to parse JSON content: {}", e);
+            return vec![];
+        }
+    };
+
+    if text_functions.is_empty() {
+        results
+    } else {
+        functions::apply_text_functions(results, &text_functions)
+    }
+}
+
+/// Evaluate an already-compiled expression against a converted value tree.
+#[doc(hidden)]
+pub fn search(expression: &Expression<'static>, data: &jmespath::Rcvar) -> Vec<String> {
+    let result = match expression.search_cached(data) {
+        Ok(value) => value,
+        Err(e) => {
+            warning!(EvaluationFailed, "JMESPath execution failed: {}", e);
+            return vec![];
+        }
+    };
+
+    jmespath_value_to_strings(&result)
+}
+
+/// Recursively convert a JMESPath result into a flat `Vec<String>`.
+fn jmespath_value_to_strings(value: &jmespath::Variable) -> Vec<String> {
+    match value {
+        jmespath::Variable::Null => vec![],
+        jmespath::Variable::Bool(b) => vec![b.to_string()],
+        jmespath::Variable::Number(n) => vec![n.to_string()],
+        jmespath::Variable::String(s) => vec![s.clone()],
+        jmespath::Variable::Array(arr) => arr
+            .iter()
+            .flat_map(|item| jmespath_value_to_strings(item))
+            .collect(),
+        jmespath::Variable::Object(obj) => {
+            let mut json_parts = Vec::new();
+            json_parts.push("{".to_string());
+            for (i, (key, value)) in obj.iter().enumerate() {
+                if i > 0 {
+                    json_parts.push(",".to_string());
+                }
+                let value_strings = jmespath_value_to_strings(value);
+                let value_str = if value_strings.len() == 1 {
+                    value_strings[0].clone()
+                } else {
+                    format!("[{}]", value_strings.join(","))
+                };
+                json_parts.push(format!(
+                    "\"{}\":{}",
+                    key,
+                    if matches!(&**value, jmespath::Variable::String(_)) {
+                        format!("\"{}\"", value_str)
+                    } else {
+                        value_str
+                    }
+                ));
+            }
+            json_parts.push("}".to_string());
+            vec![json_parts.join("")]
+        }
+        jmespath::Variable::Expref(_) => {
+            vec!["<expression>".to_string()]
+        }
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+
+    /// Guard: JMESPath expressions compile once and are cached per distinct
+    /// expression. Fails (or stops compiling) if the compile cache is removed.
+    #[test]
+    fn expressions_are_compiled_once_and_cached() {
+        COMPILED.with(|c| c.borrow_mut().clear());
+        let _ = compiled("foo.bar");
+        let _ = compiled("foo.bar");
+        assert_eq!(
+            COMPILED.with(|c| c.borrow().len()),
+            1,
+            "repeated expr must compile once"
+        );
+        let _ = compiled("baz[0]");
+        assert_eq!(
+            COMPILED.with(|c| c.borrow().len()),
+            2,
+            "distinct expr adds one entry"
+        );
+    }
+
+    #[test]
+    fn process_parses_the_document() {
+        let json = r#"{"listings": [{"price": 28500}, {"price": 31000}]}"#;
+        assert_eq!(process("listings[].price", json), ["28500", "31000"]);
+        assert!(process("listings", "{not json").is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+//! Extraction engines that need no HTML document.
+//!
+//! The engines that walk one (CSS, XPath) live in `chadselect`.
+
+#[cfg(feature = "json")]
+pub mod json;
+
+// Always built: contact harvesting, `lines:`, and the pipe functions run
+// regexes too. The feature only decides whether `regex:` queries parse.
+pub mod regex;
@@ -0,0 +1,281 @@
+//! Regex extraction engine.
+//!
+//! Processes regex patterns against any content type. Capture groups are
+//! extracted automatically — if the pattern contains groups, only group
+//! values are returned; otherwise full matches are returned. A trailing `>>`
+//! function chain is applied to the extracted values.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::cancel;
+use crate::functions::{self, ContextWindow, ValueSource};
+use crate::warnings::warning;
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// Cache of compiled regexes, keyed by the pattern string.
+    ///
+    /// `Regex::new` is the dominant per-call cost (regex compilation —
+    /// `build_many_from_hir` — measured ~28% of fleet CPU when recompiled per
+    /// page), and a crawler runs the same handful of patterns across many
+    /// documents. Compiling once per distinct pattern (per thread) makes it a
+    /// one-time cost; `Regex` is internally `Arc`-backed, so cloning out of the
+    /// cache is a cheap refcount bump. Invalid patterns cache as `None` so we
+    /// neither recompile nor re-warn on every call.
+    static COMPILED: RefCell<HashMap<String, Option<Regex>>> = RefCell::new(HashMap::new());
+}
+
+/// Compile `pattern` (or fetch the cached `Regex`). Returns `None` for an
+/// invalid pattern, warning once on first compile. Without `std` there is
+/// no cache, and every call compiles.
+#[doc(hidden)]
+pub fn compiled(pattern: &str) -> Option<Regex> {
+    #[cfg(feature = "std")]
+    return COMPILED.with(|c| {
+        if let Some(r) = c.borrow().get(pattern) {
+            return r.clone();
+        }
+        let compiled = compile(pattern);
+        c.borrow_mut().insert(pattern.to_string(), compiled.clone());
+        compiled
+    });
+    #[cfg(not(feature = "std"))]
+    compile(pattern)
+}
+
+fn compile(pattern: &str) -> Option<Regex> {
+    match Regex::new(pattern) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            warning!(InvalidQuery, "Invalid regex pattern '{}': {}", pattern, e);
+            None
+        }
+    }
+}
+
+/// Process a regex pattern (potentially with a `>>` function chain) against
+/// content, returning all matches.
+///
+/// - If the regex contains capture groups, captured values are returned.
+/// - If no capture groups, full match strings are returned.
+/// - Invalid patterns return an empty vector (never panics).
+pub fn process(pattern_with_functions: &str, content: &str) -> Vec<String> {
+    let (pattern, text_functions) = functions::split_pattern_functions(pattern_with_functions);
+
+    let regex = match compiled(&pattern) {
+        Some(r) => r,
+        None => return vec![],
+    };
+
+    if text_functions.is_empty() {
+        return extract(&regex, content, usize::MAX);
+    }
+    let entries = extract_located(&regex, content, usize::MAX)
+        .into_iter()
+        .map(|(span, text)| (Some((span.start, span.end)), text))
+        .collect();
+    functions::apply_chain(entries, &text_functions, &Spans(content))
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect()
+}
+
+/// Run an already-compiled regex over `content` (no function chain),
+/// stopping once `limit` values are collected.
+#[doc(hidden)]
+pub fn extract(regex: &Regex, content: &str, limit: usize) -> Vec<String> {
+    extract_borrowed(regex, content, limit)
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Like [`extract`], borrowing each value from `content`.
+#[doc(hidden)]
+pub fn extract_borrowed<'a>(regex: &Regex, content: &'a str, limit: usize) -> Vec<&'a str> {
+    let mut results = Vec::new();
+
+    if regex.captures_len() > 1 {
+        // Has capture groups — extract group values
+        for capture in regex.captures_iter(content) {
+            if cancel::interrupted() {
+                break;
+            }
+            for i in 1..capture.len() {
+                if let Some(matched) = capture.get(i) {
+                    results.push(matched.as_str());
+                }
+                if results.len() >= limit {
+                    return results;
+                }
+            }
+        }
+    } else {
+        // No capture groups — return full matches
+        for mat in regex.find_iter(content) {
+            if cancel::interrupted() || results.len() >= limit {
+                break;
+            }
+            results.push(mat.as_str());
+        }
+    }
+
+    results
+}
+
+/// Like [`extract`], pairing each value with its byte range in `content`.
+#[doc(hidden)]
+pub fn extract_located(
+    regex: &Regex,
+    content: &str,
+    limit: usize,
+) -> Vec<(Range<usize>, String)> {
+    extract_located_from(regex, content, 0, limit)
+}
+
+/// Like [`extract_located`], for the matches starting at or after byte
+/// `start`. The search still sees the content before `start`, so anchors
+/// and word boundaries there behave as they do for the whole content.
+#[doc(hidden)]
+pub fn extract_located_from(
+    regex: &Regex,
+    content: &str,
+    start: usize,
+    limit: usize,
+) -> Vec<(Range<usize>, String)> {
+    let mut results = Vec::new();
+    let grouped = regex.captures_len() > 1;
+    // Past an empty match, the search resumes a character later.
+    let after = |end: usize| content[end..].chars().next().map(|c| end + c.len_utf8());
+    let mut at = Some(start);
+    // The end of the last match: an empty match there is skipped, as
+    // `captures_iter` and `find_iter` skip it.
+    let mut last_end = None;
+
+    while let Some(from) = at.filter(|&at| at <= content.len()) {
+        if cancel::interrupted() || results.len() >= limit {
+            break;
+        }
+        let (whole, groups) = if grouped {
+            let Some(capture) = regex.captures_at(content, from) else {
+                break;
+            };
+            let groups: Vec<_> = capture.iter().skip(1).flatten().collect();
+            (capture.get(0).expect("group 0 always matches"), groups)
+        } else {
+            let Some(found) = regex.find_at(content, from) else {
+                break;
+            };
+            (found, vec![found])
+        };
+        if whole.is_empty() && last_end == Some(whole.end()) {
+            at = after(whole.end());
+            continue;
+        }
+        for m in groups {
+            results.push((m.range(), m.as_str().to_string()));
+            if results.len() >= limit {
+                return results;
+            }
+        }
+        last_end = Some(whole.end());
+        at = if whole.is_empty() { after(whole.end()) } else { Some(whole.end()) };
+    }
+
+    results
+}
+
+/// Whether a match of `regex` can contain a newline, and so start on an
+/// earlier line than it ends.
+#[doc(hidden)]
+pub fn spans_lines(regex: &Regex) -> bool {
+    use regex_syntax::hir::{Class, Hir, HirKind};
+
+    fn matches_newline(hir: &Hir) -> bool {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => false,
+            HirKind::Literal(literal) => literal.0.contains(&b'\n'),
+            HirKind::Class(Class::Unicode(class)) => class
+                .ranges()
+                .iter()
+                .any(|r| r.start() <= '\n' && '\n' <= r.end()),
+            HirKind::Class(Class::Bytes(class)) => class
+                .ranges()
+                .iter()
+                .any(|r| r.start() <= b'\n' && b'\n' <= r.end()),
+            HirKind::Repetition(repetition) => matches_newline(&repetition.sub),
+            HirKind::Capture(capture) => matches_newline(&capture.sub),
+            HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+                hirs.iter().any(matches_newline)
+            }
+        }
+    }
+
+    // A pattern that compiled parses; if it somehow does not, assume the
+    // worst.
+    regex_syntax::parse(regex.as_str()).map_or(true, |hir| matches_newline(&hir))
+}
+
+/// Regex matches, as byte ranges of `content`, as the source of chain
+/// values: `context` widens a match to the content around it.
+#[doc(hidden)]
+pub struct Spans<'a>(pub &'a str);
+
+impl ValueSource<(usize, usize)> for Spans<'_> {
+    fn context(&self, (start, end): (usize, usize), window: ContextWindow) -> Option<String> {
+        let content = self.0;
+        let wider = match window {
+            ContextWindow::Chars(0) => &content[start..end],
+            ContextWindow::Chars(n) => {
+                let from = content[..start]
+                    .char_indices()
+                    .rev()
+                    .nth(n - 1)
+                    .map_or(0, |(i, _)| i);
+                let to = content[end..]
+                    .char_indices()
+                    .nth(n)
+                    .map_or(content.len(), |(i, _)| end + i);
+                &content[from..to]
+            }
+            ContextWindow::Line => {
+                let from = content[..start].rfind('\n').map_or(0, |i| i + 1);
+                let to = content[end..].find('\n').map_or(content.len(), |i| end + i);
+                content[from..to].trim_end_matches('\r')
+            }
+        };
+        Some(wider.to_string())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    /// Guard: the same pattern compiles once and is reused, and the cache keys
+    /// per distinct pattern. If the compile cache is ever removed, this fails
+    /// (or stops compiling) — an uncached engine can't silently come back.
+    #[test]
+    fn patterns_are_compiled_once_and_cached() {
+        COMPILED.with(|c| c.borrow_mut().clear());
+        let _ = compiled(r"a(b)c");
+        let _ = compiled(r"a(b)c");
+        let _ = compiled(r"a(b)c");
+        assert_eq!(COMPILED.with(|c| c.borrow().len()), 1, "repeated pattern must compile once");
+        let _ = compiled(r"x(y)z");
+        assert_eq!(COMPILED.with(|c| c.borrow().len()), 2, "distinct pattern adds one entry");
+        // Invalid patterns are cached (as None) so they don't recompile/re-warn.
+        let _ = compiled(r"(unclosed");
+        let _ = compiled(r"(unclosed");
+        assert_eq!(COMPILED.with(|c| c.borrow().len()), 3, "invalid pattern cached once");
+    }
+}
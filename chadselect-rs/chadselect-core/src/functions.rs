@@ -0,0 +1,1550 @@
+//! Post-processing text functions — shared by every extraction engine.
+//!
+//! Functions are chained using the `>>` delimiter after a selector expression:
+//! ```text
+//! css:.price >> normalize-space() >> uppercase()
+//! xpath://div/text() >> substring-after('VIN: ') >> substring(0, 3)
+//! json:products[].name >> uppercase()
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+#[cfg(feature = "digest")]
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+#[cfg(feature = "digest")]
+use base64::Engine;
+#[cfg(feature = "digest")]
+use md5::{Digest, Md5};
+use regex::{Regex, RegexBuilder};
+#[cfg(feature = "digest")]
+use sha2::Sha256;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::contacts;
+use crate::markup::strip_tags;
+use crate::query::FUNCTION_PIPE;
+use crate::units;
+use crate::warnings::warning;
+
+/// A backslash-escaped pipe: a literal `>>` in the expression, not a split point.
+const ESCAPED_PIPE: &str = "\\>>";
+
+/// Separates the values `get-attrs` reads from one element.
+const ATTRIBUTE_SEPARATOR: &str = "|";
+
+/// Post-processing text function variants.
+#[derive(Debug, Clone)]
+pub enum TextFunction {
+    /// Trim and collapse internal whitespace (XPath-style `normalize-space`).
+    NormalizeSpace,
+    /// Trim leading and trailing whitespace.
+    Trim,
+    /// Convert to uppercase.
+    Uppercase,
+    /// Convert to lowercase.
+    Lowercase,
+    /// Extract a substring by start index (0-based) and length. A negative
+    /// `start` counts back from the end; a `None` length runs to the end.
+    Substring { start: isize, length: Option<usize> },
+    /// Return everything after the first occurrence of the delimiter.
+    SubstringAfter { delimiter: String },
+    /// Return everything before the first occurrence of the delimiter.
+    SubstringBefore { delimiter: String },
+    /// Replace all occurrences of `find` with `replace`.
+    Replace { find: String, replace: String },
+    /// Extract an HTML element attribute by name (CSS only). Works at any
+    /// position in the chain; the value is empty when the attribute is absent.
+    GetAttribute { attribute: String },
+    /// Extract several attributes of an element as one value, joined with
+    /// `|` in argument order (`href|title`); an absent attribute is an empty
+    /// field (CSS only).
+    GetAttributes { attributes: Vec<String> },
+    /// Move from the matched element to a related one — an ancestor, a
+    /// sibling, or the nearest element before or after it — whose text (and
+    /// attributes, for a later `get-attr`) the rest of the chain sees. A
+    /// value with no such element is dropped (CSS only).
+    Navigate { step: Navigation },
+    /// Widen a regex match to `window` of the raw content around it — a
+    /// number of characters on each side, or the whole line. Values of
+    /// other engines are unchanged (regex only).
+    Context { window: ContextWindow },
+    /// Join **all** results in the chain into a single string with `separator`.
+    /// Unlike the other functions (which map element-wise), this folds the
+    /// whole result list into one value. Spelled `join('sep')` or `concat('sep')`.
+    Join { separator: String },
+    /// XPath-style `translate(from, to)`: per-character map; a character in
+    /// `from` with no counterpart in `to` is deleted.
+    Translate { from: String, to: String },
+    /// Extract the first regex capture group (or the whole match if the pattern
+    /// has no groups); empty string if it doesn't match.
+    RegexExtract { re: Regex },
+    /// Regex search-and-replace (replacement uses Rust regex `$1` group refs).
+    RegexReplace { re: Regex, replace: String },
+    /// Return everything after the **last** occurrence of the delimiter.
+    SubstringAfterLast { delimiter: String },
+    /// Return everything before the **last** occurrence of the delimiter.
+    SubstringBeforeLast { delimiter: String },
+    /// Replace an **empty** result list with the single value `default`.
+    /// Like `join`, this acts on the whole list (per content item).
+    IfEmpty { default: String },
+    /// Map a value containing `needle` to `then`; any other value becomes
+    /// `otherwise`, or is kept unchanged when no `otherwise` is given.
+    IfContains {
+        needle: String,
+        then: String,
+        otherwise: Option<String>,
+    },
+    /// Keep only values the regex matches (anywhere in the value).
+    Matches { re: Regex },
+    /// Drop values the regex matches (anywhere in the value).
+    NotMatches { re: Regex },
+    /// Reduce a value to the first number in it (`"$1,299.00"` → `"1299"`);
+    /// empty when it holds no number.
+    ToNumber,
+    /// Sort the result list ascending — numerically when every value is a
+    /// number, otherwise as strings. Acts on the whole list (per content item).
+    Sort,
+    /// Drop repeated values, keeping the first occurrence of each.
+    Unique,
+    /// Drop values whose key — `key` applied to the value — repeats an
+    /// earlier one, keeping the first value as written. A value with an
+    /// empty key is its own key.
+    UniqueBy { key: Box<TextFunction> },
+    /// Reverse the result list.
+    Reverse,
+    /// Keep only the first result.
+    First,
+    /// Keep only the last result.
+    Last,
+    /// Substitute the value into a template: `{}` is the value, `{{` / `}}`
+    /// are literal braces (`format('SKU={}')`).
+    Format { template: String },
+    /// Uppercase the first letter of every word and lowercase the rest.
+    TitleCase,
+    /// Uppercase the first character and lowercase the rest.
+    Capitalize,
+    /// Pad on the left with `fill` up to `width` characters.
+    PadLeft { width: usize, fill: char },
+    /// Pad on the right with `fill` up to `width` characters.
+    PadRight { width: usize, fill: char },
+    /// Cut a value longer than `max` characters to `max`, ending with
+    /// `suffix` (which counts toward `max`).
+    Truncate { max: usize, suffix: String },
+    /// Remove HTML markup, keeping the text: tags and comments are dropped,
+    /// numeric and common named entities decoded, and `<script>`/`<style>`
+    /// contents removed (see [`markup::strip_tags`](crate::markup::strip_tags)).
+    StripTags,
+    /// Lowercase hex SHA-256 digest of the value's UTF-8 bytes.
+    #[cfg(feature = "digest")]
+    Sha256,
+    /// Lowercase hex MD5 digest of the value's UTF-8 bytes.
+    #[cfg(feature = "digest")]
+    Md5,
+    /// Standard (padded) base64 encoding of the value.
+    #[cfg(feature = "digest")]
+    Base64Encode,
+    /// Decode standard or URL-safe base64, padded or not, ignoring embedded
+    /// whitespace. Invalid input or non-UTF-8 output yields empty.
+    #[cfg(feature = "digest")]
+    Base64Decode,
+    /// Fold the list into one value: the number of results, numeric or not
+    /// (`"0"` for an empty list).
+    Count,
+    /// Fold the numbers in the list (see `to-number`) into one value with
+    /// `aggregate`. Non-numeric values are skipped; no numbers yields no result.
+    Aggregate { aggregate: Aggregate },
+    /// Keep only values at least `min` characters long.
+    MinLength { min: usize },
+    /// Keep only values at most `max` characters long.
+    MaxLength { max: usize },
+    /// The number of words in the value (Unicode UAX #29 word boundaries).
+    WordCount,
+    /// Normalize a money value to `amount|CODE` (`"$1,299"` →
+    /// `"1299.00|USD"`); empty when it has no amount or no recognisable
+    /// currency symbol or ISO code.
+    ParseCurrency,
+    /// Convert the first number in the value from unit `from` to unit `to`
+    /// (`convert-unit('mi', 'km')`); empty when it holds no number.
+    ConvertUnit { from: String, to: String },
+    /// Replace each value with the email addresses found in it.
+    ExtractEmails,
+    /// Replace each value with the valid phone numbers found in it, as
+    /// E.164 (`+12125550100`); numbers without a country code are read as
+    /// numbers of `region`.
+    #[cfg(feature = "phone")]
+    ExtractPhones { region: phonenumber::country::Id },
+    /// Split each value into its sentences (Unicode UAX #29 boundaries),
+    /// trimmed.
+    Sentences,
+    /// Split each value into its paragraphs — runs of text separated by a
+    /// blank line or a U+2029 PARAGRAPH SEPARATOR — trimmed.
+    Paragraphs,
+}
+
+/// The DOM step taken by [`TextFunction::Navigate`]. A CSS selector narrows
+/// the step to elements it matches; without one, any element qualifies.
+/// Selectors are kept as written: the core parses no HTML or CSS, so
+/// `chadselect` compiles them, rejecting an invalid one when the query is
+/// prepared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Navigation {
+    /// The nearest ancestor matching the selector, or the element itself
+    /// (`closest('.product')`).
+    Closest(String),
+    /// The parent element (`parent()`).
+    Parent,
+    /// The nearest element after this one in document order, outside its
+    /// subtree (`following('.value')`).
+    Following(Option<String>),
+    /// The nearest element before this one in document order, not an
+    /// ancestor (`preceding('h2')`).
+    Preceding(Option<String>),
+    /// The nearest later sibling (`following-sibling()`).
+    FollowingSibling(Option<String>),
+    /// The nearest earlier sibling (`preceding-sibling()`).
+    PrecedingSibling(Option<String>),
+}
+
+impl Navigation {
+    /// The selector narrowing the step, if any.
+    pub fn selector(&self) -> Option<&str> {
+        match self {
+            Navigation::Closest(selector) => Some(selector),
+            Navigation::Parent => None,
+            Navigation::Following(selector)
+            | Navigation::Preceding(selector)
+            | Navigation::FollowingSibling(selector)
+            | Navigation::PrecedingSibling(selector) => selector.as_deref(),
+        }
+    }
+}
+
+/// How much surrounding content [`TextFunction::Context`] adds to a match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextWindow {
+    /// Up to this many characters before and after the match
+    /// (`context(80)`).
+    Chars(usize),
+    /// The whole line(s) the match is on (`context('line')` or `context()`).
+    Line,
+}
+
+/// Numeric fold applied by [`TextFunction::Aggregate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregate {
+    /// Fold `numbers`; `None` for an empty slice.
+    fn apply(self, numbers: &[f64]) -> Option<f64> {
+        if numbers.is_empty() {
+            return None;
+        }
+        let sum: f64 = numbers.iter().sum();
+        Some(match self {
+            Aggregate::Sum => sum,
+            Aggregate::Min => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregate::Avg => sum / numbers.len() as f64,
+        })
+    }
+}
+
+/// Returns the list of all supported text function signatures.
+pub fn supported_text_functions() -> Vec<&'static str> {
+    vec![
+        "normalize-space()",
+        "trim()",
+        "uppercase()",
+        "lowercase()",
+        "substring(start, length)",
+        "substring-after('delimiter')",
+        "substring-before('delimiter')",
+        "replace('find', 'replace')",
+        "get-attr('attribute')",
+        "get-attrs('attribute', ...)",
+        "closest('selector')",
+        "parent()",
+        "following('selector')",
+        "preceding('selector')",
+        "following-sibling('selector')",
+        "preceding-sibling('selector')",
+        "context(n | 'line')",
+        "join('separator')",
+        "translate('from', 'to')",
+        "remove-chars('chars')",
+        "regex-extract('pattern')",
+        "regex-replace('pattern', 'replacement')",
+        "replace-regex('pattern', 'replacement')",
+        "replace-i('find', 'replace')",
+        "substring-after-last('delimiter')",
+        "substring-before-last('delimiter')",
+        "if-empty('default')",
+        "if-contains('needle', 'then', 'else')",
+        "matches('pattern')",
+        "not-matches('pattern')",
+        "to-number()",
+        "sort()",
+        "unique()",
+        "unique-by(key)",
+        "reverse()",
+        "first()",
+        "last()",
+        "format('template {}')",
+        "title-case()",
+        "capitalize()",
+        "pad-left(width, 'fill')",
+        "pad-right(width, 'fill')",
+        "truncate(max, 'suffix')",
+        "strip-tags()",
+        #[cfg(feature = "digest")]
+        "sha256()",
+        #[cfg(feature = "digest")]
+        "md5()",
+        #[cfg(feature = "digest")]
+        "base64-encode()",
+        #[cfg(feature = "digest")]
+        "base64-decode()",
+        "count()",
+        "sum()",
+        "min()",
+        "max()",
+        "avg()",
+        "sentences()",
+        "paragraphs()",
+        "min-length(n)",
+        "max-length(n)",
+        "word-count()",
+        "parse-currency()",
+        "convert-unit('from', 'to')",
+        "extract-emails()",
+        #[cfg(feature = "phone")]
+        "extract-phones('region')",
+    ]
+}
+
+/// Split an input string on the function-pipe delimiter (`>>`).
+///
+/// Returns `(expression, functions)` where `expression` is the selector/query
+/// portion and `functions` is the parsed chain of [`TextFunction`]s.
+///
+/// The split is quote-aware: a `>>` inside a `'…'`, `"…"`, or `` `…` ``
+/// string literal of the expression (e.g. an XPath
+/// `contains(., 'a >> b')` argument) is not treated as the pipe. A literal
+/// `>>` outside a string can be written as `\>>`; the escape is removed
+/// from the returned expression.
+///
+/// If no `>>` is present, the entire input is treated as the expression with
+/// an empty function chain.
+pub fn split_functions(input: &str) -> (Cow<'_, str>, Vec<TextFunction>) {
+    split_at_pipe(input, true)
+}
+
+/// Like [`split_functions`], but for regex patterns, whose grammar has no
+/// string literals — an apostrophe in `it's (\d+)` is just a character, so
+/// quotes are not tracked. Only the `\>>` escape is honoured.
+pub fn split_pattern_functions(input: &str) -> (Cow<'_, str>, Vec<TextFunction>) {
+    split_at_pipe(input, false)
+}
+
+/// Like [`split_functions`], but fails on a malformed or unrecognised
+/// function instead of skipping it (see [`try_parse_text_functions`]).
+pub fn try_split_functions(input: &str) -> Result<(Cow<'_, str>, Vec<TextFunction>), String> {
+    try_split_at_pipe(input, true)
+}
+
+/// Like [`split_pattern_functions`], but fails on a malformed or
+/// unrecognised function instead of skipping it.
+pub fn try_split_pattern_functions(
+    input: &str,
+) -> Result<(Cow<'_, str>, Vec<TextFunction>), String> {
+    try_split_at_pipe(input, false)
+}
+
+fn split_at_pipe(input: &str, quote_aware: bool) -> (Cow<'_, str>, Vec<TextFunction>) {
+    let (expression, chain) = split_chain(input, quote_aware);
+    (expression, chain.map(parse_text_functions).unwrap_or_default())
+}
+
+fn try_split_at_pipe(
+    input: &str,
+    quote_aware: bool,
+) -> Result<(Cow<'_, str>, Vec<TextFunction>), String> {
+    let (expression, chain) = split_chain(input, quote_aware);
+    let functions = match chain {
+        Some(chain) => try_parse_text_functions(chain)?,
+        None => vec![],
+    };
+    Ok((expression, functions))
+}
+
+/// Split `input` into its (unescaped) expression and the function chain
+/// after the first top-level `>>`, if any.
+#[doc(hidden)]
+pub fn split_chain(input: &str, quote_aware: bool) -> (Cow<'_, str>, Option<&str>) {
+    match pipe_positions(input, quote_aware).first() {
+        Some(&pipe_pos) => (
+            unescape_pipe(input[..pipe_pos].trim()),
+            Some(&input[pipe_pos + FUNCTION_PIPE.len()..]),
+        ),
+        None => (unescape_pipe(input), None),
+    }
+}
+
+/// The expression portion of `input`, before the first top-level `>>` (escapes
+/// left intact). Used where only the selector matters, e.g. engine detection.
+#[doc(hidden)]
+pub fn strip_functions(input: &str) -> &str {
+    match pipe_positions(input, true).first() {
+        Some(&pipe_pos) => &input[..pipe_pos],
+        None => input,
+    }
+}
+
+/// Split `input` at the first top-level `>>` whose stage starts with
+/// `prefix`, returning the part before it and the stage onwards (beginning at
+/// the prefix). `None` when no stage starts with `prefix`.
+#[cfg(feature = "json")]
+#[doc(hidden)]
+pub fn split_at_stage<'a>(input: &'a str, prefix: &str) -> Option<(&'a str, &'a str)> {
+    pipe_positions(input, true).into_iter().find_map(|pos| {
+        let stage = input[pos + FUNCTION_PIPE.len()..].trim_start();
+        stage
+            .starts_with(prefix)
+            .then(|| (input[..pos].trim_end(), stage))
+    })
+}
+
+/// Byte offsets of every top-level `>>` in `input`.
+///
+/// An escaped `\>>` is skipped. With `quote_aware`, so is any `>>` inside a
+/// quoted string literal; a quote with no closing partner is an ordinary
+/// character, so a stray apostrophe can't swallow the rest of the query.
+fn pipe_positions(input: &str, quote_aware: bool) -> Vec<usize> {
+    let bytes = input.as_bytes();
+    let pipe = FUNCTION_PIPE.as_bytes();
+    let mut positions = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // `\>>` escape (or any other backslash escape): skip both.
+            b'\\' if bytes[i + 1..].starts_with(pipe) => i += 1 + pipe.len(),
+            b'\\' => i += 2,
+            b'\'' | b'"' | b'`' if quote_aware => match closing_quote(bytes, i) {
+                Some(end) => i = end + 1,
+                None => i += 1,
+            },
+            _ if bytes[i..].starts_with(pipe) => {
+                positions.push(i);
+                i += pipe.len();
+            }
+            _ => i += 1,
+        }
+    }
+    positions
+}
+
+/// Index of the quote closing the literal opened at `open`, skipping
+/// backslash-escaped characters.
+#[doc(hidden)]
+pub fn closing_quote(bytes: &[u8], open: usize) -> Option<usize> {
+    let quote = bytes[open];
+    let mut i = open + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return Some(i),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Replace each `\>>` escape with a literal `>>`.
+fn unescape_pipe(expression: &str) -> Cow<'_, str> {
+    if expression.contains(ESCAPED_PIPE) {
+        Cow::Owned(expression.replace(ESCAPED_PIPE, FUNCTION_PIPE))
+    } else {
+        Cow::Borrowed(expression)
+    }
+}
+
+/// Parse a function chain string like `"normalize-space() >> uppercase()"`.
+///
+/// Individual function strings that are malformed or unrecognised are
+/// skipped (with a `log::warn`), keeping the rest of the chain intact. A `>>`
+/// inside a quoted argument (`replace('>>', '/')`) does not split the chain.
+/// Use [`try_parse_text_functions`] to reject a malformed chain instead.
+pub fn parse_text_functions(functions_str: &str) -> Vec<TextFunction> {
+    function_segments(functions_str)
+        .filter_map(|segment| {
+            parse_function(segment)
+                .map_err(|e| warning!(InvalidQuery, "Skipping text function: {}", e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Like [`parse_text_functions`], but fails on the first malformed or
+/// unrecognised function, naming it in the error.
+///
+/// ```rust
+/// use chadselect_core::functions::try_parse_text_functions;
+///
+/// assert_eq!(try_parse_text_functions("trim() >> replace(',', ';')").unwrap().len(), 2);
+/// assert!(try_parse_text_functions("replace('only-one')").is_err());
+/// assert!(try_parse_text_functions("no-such-fn()").is_err());
+/// ```
+pub fn try_parse_text_functions(functions_str: &str) -> Result<Vec<TextFunction>, String> {
+    function_segments(functions_str).map(parse_function).collect()
+}
+
+/// The non-empty, trimmed `>>` segments of a function chain.
+#[doc(hidden)]
+pub fn function_segments(functions_str: &str) -> impl Iterator<Item = &str> {
+    let mut start = 0;
+    let mut segments = Vec::new();
+    for pos in pipe_positions(functions_str, true) {
+        segments.push(&functions_str[start..pos]);
+        start = pos + FUNCTION_PIPE.len();
+    }
+    segments.push(&functions_str[start..]);
+
+    segments
+        .into_iter()
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+}
+
+// ─── Argument tokenizer ─────────────────────────────────────────────────────
+
+/// One argument of a function call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Arg {
+    /// The argument text, unquoted and unescaped.
+    value: String,
+    /// Whether it was written as a quoted string.
+    quoted: bool,
+}
+
+/// Split a call like `replace(',', ';')` into its name and arguments.
+///
+/// Arguments are comma-separated. A quoted argument (`'…'` or `"…"`) may
+/// contain commas, parentheses, and `>>`; inside it `\'`, `\"`, `\\`, `\n`,
+/// and `\t` are escapes, and any other backslash is kept as written (so
+/// regex classes like `\d` need no doubling). A bare argument runs to the
+/// next comma or `)` outside brackets, so `regex-extract((\d{1,3}))` works.
+fn tokenize_call(call: &str) -> Result<(&str, Vec<Arg>), String> {
+    let open = call
+        .find('(')
+        .ok_or_else(|| format!("'{}' is not a function call (missing '(')", call))?;
+    let name = call[..open].trim();
+    if name.is_empty() {
+        return Err(format!("missing function name in '{}'", call));
+    }
+
+    let bytes = call.as_bytes();
+    let unclosed = || format!("missing ')' in '{}'", call);
+    let mut args = Vec::new();
+    let mut i = skip_spaces(bytes, open + 1);
+
+    if bytes.get(i) == Some(&b')') {
+        i += 1;
+    } else {
+        loop {
+            i = skip_spaces(bytes, i);
+            match bytes.get(i) {
+                None => return Err(unclosed()),
+                Some(b'\'' | b'"') => {
+                    let close = closing_quote(bytes, i)
+                        .ok_or_else(|| format!("unterminated string in '{}'", call))?;
+                    args.push(Arg {
+                        value: unescape_argument(&call[i + 1..close]),
+                        quoted: true,
+                    });
+                    i = close + 1;
+                }
+                Some(_) => {
+                    let end = bare_argument_end(bytes, i);
+                    let value = call[i..end].trim();
+                    if value.is_empty() {
+                        return Err(format!("empty argument {} in '{}'", args.len() + 1, call));
+                    }
+                    args.push(Arg {
+                        value: value.to_string(),
+                        quoted: false,
+                    });
+                    i = end;
+                }
+            }
+
+            i = skip_spaces(bytes, i);
+            match bytes.get(i) {
+                Some(b',') => i += 1,
+                Some(b')') => {
+                    i += 1;
+                    break;
+                }
+                Some(_) => {
+                    return Err(format!(
+                        "expected ',' or ')' after argument {} in '{}'",
+                        args.len(),
+                        call
+                    ))
+                }
+                None => return Err(unclosed()),
+            }
+        }
+    }
+
+    let trailing = call[i..].trim();
+    if !trailing.is_empty() {
+        return Err(format!("unexpected '{}' after '{}(…)'", trailing, name));
+    }
+    Ok((name, args))
+}
+
+/// The name and argument values of a call like `contains('ERROR')`, for
+/// expressions that share the function-call syntax.
+pub(crate) fn call_arguments(call: &str) -> Result<(&str, Vec<String>), String> {
+    let (name, args) = tokenize_call(call)?;
+    Ok((name, args.into_iter().map(|arg| arg.value).collect()))
+}
+
+fn skip_spaces(bytes: &[u8], mut i: usize) -> usize {
+    while bytes.get(i).is_some_and(u8::is_ascii_whitespace) {
+        i += 1;
+    }
+    i
+}
+
+/// End of a bare argument: the first `,` or `)` not nested in brackets or
+/// inside a quoted part (`regex:'(\d+)'`).
+fn bare_argument_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' if i > start => {
+                if let Some(close) = closing_quote(bytes, i) {
+                    i = close;
+                }
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b',' if depth == 0 => return i,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Resolve the escapes of a quoted argument's body.
+#[doc(hidden)]
+pub fn unescape_argument(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(escaped @ ('\\' | '\'' | '"')) => out.push(escaped),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// ─── Function parsing ───────────────────────────────────────────────────────
+
+/// Parse one `name(args)` segment of a chain.
+fn parse_function(call: &str) -> Result<TextFunction, String> {
+    let (name, args) = tokenize_call(call)?;
+    let call_args = CallArgs { name, args };
+
+    let function = match name {
+        "normalize-space" => call_args.none(TextFunction::NormalizeSpace)?,
+        "trim" => call_args.none(TextFunction::Trim)?,
+        "uppercase" => call_args.none(TextFunction::Uppercase)?,
+        "lowercase" => call_args.none(TextFunction::Lowercase)?,
+        "title-case" => call_args.none(TextFunction::TitleCase)?,
+        "capitalize" => call_args.none(TextFunction::Capitalize)?,
+        "strip-tags" => call_args.none(TextFunction::StripTags)?,
+        #[cfg(feature = "digest")]
+        "sha256" => call_args.none(TextFunction::Sha256)?,
+        #[cfg(feature = "digest")]
+        "md5" => call_args.none(TextFunction::Md5)?,
+        #[cfg(feature = "digest")]
+        "base64-encode" => call_args.none(TextFunction::Base64Encode)?,
+        #[cfg(feature = "digest")]
+        "base64-decode" => call_args.none(TextFunction::Base64Decode)?,
+        #[cfg(not(feature = "digest"))]
+        "sha256" | "md5" | "base64-encode" | "base64-decode" => {
+            return Err(call_args.error("requires the `digest` feature"))
+        }
+        "to-number" => call_args.none(TextFunction::ToNumber)?,
+        "sort" => call_args.none(TextFunction::Sort)?,
+        "unique" => call_args.none(TextFunction::Unique)?,
+        "unique-by" => TextFunction::UniqueBy {
+            key: Box::new(unique_key(&call_args.non_empty()?).map_err(|e| call_args.error(&e))?),
+        },
+        "reverse" => call_args.none(TextFunction::Reverse)?,
+        "first" => call_args.none(TextFunction::First)?,
+        "last" => call_args.none(TextFunction::Last)?,
+        "count" => call_args.none(TextFunction::Count)?,
+        "sentences" => call_args.none(TextFunction::Sentences)?,
+        "paragraphs" => call_args.none(TextFunction::Paragraphs)?,
+        "word-count" => call_args.none(TextFunction::WordCount)?,
+        "parse-currency" => call_args.none(TextFunction::ParseCurrency)?,
+        "convert-unit" => {
+            let (from, to) = call_args.two()?;
+            units::check_conversion(&from, &to).map_err(|e| call_args.error(&e))?;
+            TextFunction::ConvertUnit { from, to }
+        }
+        "extract-emails" => call_args.none(TextFunction::ExtractEmails)?,
+        #[cfg(feature = "phone")]
+        "extract-phones" => TextFunction::ExtractPhones {
+            region: contacts::region(&call_args.one()?).map_err(|e| call_args.error(&e))?,
+        },
+        #[cfg(not(feature = "phone"))]
+        "extract-phones" => return Err(call_args.error("requires the `phone` feature")),
+        "min-length" => {
+            call_args.arity(1, 1)?;
+            TextFunction::MinLength {
+                min: call_args.number::<usize>(0)?,
+            }
+        }
+        "max-length" => {
+            call_args.arity(1, 1)?;
+            TextFunction::MaxLength {
+                max: call_args.number::<usize>(0)?,
+            }
+        }
+        "sum" | "min" | "max" | "avg" => {
+            let aggregate = match name {
+                "sum" => Aggregate::Sum,
+                "min" => Aggregate::Min,
+                "max" => Aggregate::Max,
+                _ => Aggregate::Avg,
+            };
+            call_args.none(TextFunction::Aggregate { aggregate })?
+        }
+        // `substring(start)` / `substring(start, length)`; a negative
+        // start counts from the end, an omitted or `-1` length runs to it.
+        "substring" => {
+            call_args.arity(1, 2)?;
+            let start = call_args.number::<isize>(0)?;
+            let length = match call_args.args.get(1) {
+                None => None,
+                Some(arg) if arg.value == "-1" => None,
+                Some(_) => Some(call_args.number::<usize>(1)?),
+            };
+            TextFunction::Substring { start, length }
+        }
+        "substring-after" => TextFunction::SubstringAfter {
+            delimiter: call_args.non_empty()?,
+        },
+        "substring-before" => TextFunction::SubstringBefore {
+            delimiter: call_args.non_empty()?,
+        },
+        "substring-after-last" => TextFunction::SubstringAfterLast {
+            delimiter: call_args.non_empty()?,
+        },
+        "substring-before-last" => TextFunction::SubstringBeforeLast {
+            delimiter: call_args.non_empty()?,
+        },
+        "get-attr" => TextFunction::GetAttribute {
+            attribute: call_args.non_empty()?,
+        },
+        "get-attrs" => {
+            call_args.arity(1, usize::MAX)?;
+            if call_args.args.iter().any(|arg| arg.value.is_empty()) {
+                return Err(call_args.error("attribute names must not be empty"));
+            }
+            TextFunction::GetAttributes {
+                attributes: call_args.args.iter().map(|arg| arg.value.clone()).collect(),
+            }
+        }
+        #[cfg(not(feature = "css"))]
+        "closest" | "parent" | "following" | "preceding" | "following-sibling"
+        | "preceding-sibling" => {
+            return Err(call_args.error("needs an HTML document (enable the `css` feature)"));
+        }
+        #[cfg(feature = "css")]
+        "closest" => {
+            call_args.arity(1, 1)?;
+            TextFunction::Navigate {
+                step: Navigation::Closest(call_args.selector(0)?),
+            }
+        }
+        #[cfg(feature = "css")]
+        "parent" => call_args.none(TextFunction::Navigate {
+            step: Navigation::Parent,
+        })?,
+        #[cfg(feature = "css")]
+        "following" | "preceding" | "following-sibling" | "preceding-sibling" => {
+            call_args.arity(0, 1)?;
+            let selector = if call_args.args.is_empty() {
+                None
+            } else {
+                Some(call_args.selector(0)?)
+            };
+            let step = match name {
+                "following" => Navigation::Following(selector),
+                "preceding" => Navigation::Preceding(selector),
+                "following-sibling" => Navigation::FollowingSibling(selector),
+                _ => Navigation::PrecedingSibling(selector),
+            };
+            TextFunction::Navigate { step }
+        }
+        "context" => {
+            call_args.arity(0, 1)?;
+            let window = match call_args.args.first() {
+                None => ContextWindow::Line,
+                Some(arg) if arg.value == "line" => ContextWindow::Line,
+                Some(_) => ContextWindow::Chars(call_args.number::<usize>(0)?),
+            };
+            TextFunction::Context { window }
+        }
+        "replace" => {
+            let (find, replace) = call_args.two()?;
+            TextFunction::Replace { find, replace }
+        }
+        // Literal, case-insensitive replace: an escaped pattern with a
+        // `$`-escaped replacement, run as a regex replace.
+        "replace-i" => {
+            let (find, replace) = call_args.two()?;
+            let re = RegexBuilder::new(&regex::escape(&find))
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| call_args.error(&e.to_string()))?;
+            TextFunction::RegexReplace {
+                re,
+                replace: replace.replace('$', "$$"),
+            }
+        }
+        "translate" => {
+            let (from, to) = call_args.two()?;
+            TextFunction::Translate { from, to }
+        }
+        // `remove-chars('$,')` is `translate('$,', '')`.
+        "remove-chars" => TextFunction::Translate {
+            from: call_args.one()?,
+            to: String::new(),
+        },
+        "regex-extract" => TextFunction::RegexExtract {
+            re: call_args.one_regex()?,
+        },
+        "regex-replace" | "replace-regex" => {
+            call_args.arity(2, 2)?;
+            TextFunction::RegexReplace {
+                re: call_args.regex(0)?,
+                replace: call_args.args[1].value.clone(),
+            }
+        }
+        "matches" => TextFunction::Matches {
+            re: call_args.one_regex()?,
+        },
+        "not-matches" => TextFunction::NotMatches {
+            re: call_args.one_regex()?,
+        },
+        "if-empty" => TextFunction::IfEmpty {
+            default: call_args.one()?,
+        },
+        "if-contains" => {
+            call_args.arity(2, 3)?;
+            let mut values = call_args.args.iter().map(|arg| arg.value.clone());
+            TextFunction::IfContains {
+                needle: values.next().unwrap_or_default(),
+                then: values.next().unwrap_or_default(),
+                otherwise: values.next(),
+            }
+        }
+        // `join('sep')` / `concat('sep')` — fold the result list into one
+        // string. An empty/absent argument joins with no separator.
+        "join" | "concat" => {
+            call_args.arity(0, 1)?;
+            TextFunction::Join {
+                separator: call_args.args.first().map(|a| a.value.clone()).unwrap_or_default(),
+            }
+        }
+        "format" => TextFunction::Format {
+            template: call_args.one()?,
+        },
+        "pad-left" | "pad-right" => {
+            call_args.arity(1, 2)?;
+            let width = call_args.number::<usize>(0)?;
+            let fill = match call_args.args.get(1) {
+                None => ' ',
+                Some(arg) => arg
+                    .value
+                    .chars()
+                    .next()
+                    .ok_or_else(|| call_args.error("fill must not be empty"))?,
+            };
+            if name == "pad-left" {
+                TextFunction::PadLeft { width, fill }
+            } else {
+                TextFunction::PadRight { width, fill }
+            }
+        }
+        "truncate" => {
+            call_args.arity(1, 2)?;
+            TextFunction::Truncate {
+                max: call_args.number::<usize>(0)?,
+                suffix: call_args.args.get(1).map(|a| a.value.clone()).unwrap_or_default(),
+            }
+        }
+        _ => return Err(format!("unknown text function '{}'", name)),
+    };
+
+    Ok(function)
+}
+
+/// The key function of `unique-by(key)`: `regex:'pattern'` (the first
+/// capture group, or the whole match), or a function that maps each value,
+/// with or without its parentheses (`lowercase`, `substring(0, 3)`).
+fn unique_key(key: &str) -> Result<TextFunction, String> {
+    if let Some(pattern) = key.strip_prefix("regex:") {
+        let pattern = pattern.trim();
+        let bytes = pattern.as_bytes();
+        let pattern = match bytes.first() {
+            Some(b'\'' | b'"') if closing_quote(bytes, 0) == Some(bytes.len() - 1) => {
+                unescape_argument(&pattern[1..pattern.len() - 1])
+            }
+            _ => pattern.to_string(),
+        };
+        let re = Regex::new(&pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+        return Ok(TextFunction::RegexExtract { re });
+    }
+
+    let function = if key.contains('(') {
+        parse_function(key)?
+    } else {
+        parse_function(&format!("{}()", key))?
+    };
+    match function {
+        TextFunction::GetAttribute { .. }
+        | TextFunction::GetAttributes { .. }
+        | TextFunction::Navigate { .. }
+        | TextFunction::Context { .. }
+        | TextFunction::Join { .. }
+        | TextFunction::IfEmpty { .. }
+        | TextFunction::Sort
+        | TextFunction::Unique
+        | TextFunction::UniqueBy { .. }
+        | TextFunction::Reverse
+        | TextFunction::First
+        | TextFunction::Last
+        | TextFunction::Count
+        | TextFunction::Aggregate { .. }
+        | TextFunction::Matches { .. }
+        | TextFunction::NotMatches { .. }
+        | TextFunction::MinLength { .. }
+        | TextFunction::MaxLength { .. }
+        | TextFunction::Sentences
+        | TextFunction::Paragraphs
+        | TextFunction::ExtractEmails => Err(format!("'{}' does not map a value to a key", key)),
+        #[cfg(feature = "phone")]
+        TextFunction::ExtractPhones { .. } => {
+            Err(format!("'{}' does not map a value to a key", key))
+        }
+        function => Ok(function),
+    }
+}
+
+/// A tokenized call, with argument checks that name the function on failure.
+struct CallArgs<'a> {
+    name: &'a str,
+    args: Vec<Arg>,
+}
+
+impl CallArgs<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{}(): {}", self.name, message)
+    }
+
+    fn arity(&self, min: usize, max: usize) -> Result<(), String> {
+        let n = self.args.len();
+        if (min..=max).contains(&n) {
+            return Ok(());
+        }
+        let expected = match (min, max) {
+            (min, max) if min == max => format!("{}", min),
+            (min, usize::MAX) => format!("at least {}", min),
+            (min, max) => format!("{} to {}", min, max),
+        };
+        Err(self.error(&format!("expected {} argument(s), got {}", expected, n)))
+    }
+
+    /// A function taking no arguments.
+    fn none(&self, function: TextFunction) -> Result<TextFunction, String> {
+        self.arity(0, 0)?;
+        Ok(function)
+    }
+
+    fn one(&self) -> Result<String, String> {
+        self.arity(1, 1)?;
+        Ok(self.args[0].value.clone())
+    }
+
+    fn non_empty(&self) -> Result<String, String> {
+        let value = self.one()?;
+        if value.is_empty() {
+            return Err(self.error("argument must not be empty"));
+        }
+        Ok(value)
+    }
+
+    fn two(&self) -> Result<(String, String), String> {
+        self.arity(2, 2)?;
+        Ok((self.args[0].value.clone(), self.args[1].value.clone()))
+    }
+
+    fn number<T: core::str::FromStr>(&self, index: usize) -> Result<T, String> {
+        let arg = &self.args[index];
+        match arg.value.parse() {
+            Ok(n) if !arg.quoted => Ok(n),
+            _ => Err(self.error(&format!("'{}' is not a valid number", arg.value))),
+        }
+    }
+
+    /// The argument at `index`, a CSS selector (see [`Navigation`]).
+    #[cfg(feature = "css")]
+    fn selector(&self, index: usize) -> Result<String, String> {
+        match self.args[index].value.trim() {
+            "" => Err(self.error("the selector must not be empty")),
+            selector => Ok(selector.to_string()),
+        }
+    }
+
+    /// The single argument, compiled as a regex.
+    fn one_regex(&self) -> Result<Regex, String> {
+        self.arity(1, 1)?;
+        self.regex(0)
+    }
+
+    /// The argument at `index`, compiled as a regex.
+    fn regex(&self, index: usize) -> Result<Regex, String> {
+        let pattern = &self.args[index].value;
+        Regex::new(pattern).map_err(|e| self.error(&format!("invalid regex '{}': {}", pattern, e)))
+    }
+}
+
+/// Apply a chain of text functions to a vector of results.
+///
+/// Each function is applied to every element; elements that become empty after
+/// a function are filtered out. List-level functions (`join`, `if-empty`,
+/// `sort`, `unique`, `unique-by`, `reverse`, `first`, `last`, and the
+/// `count`/`sum`/`min`/`max`/`avg` folds) instead act on the whole vector,
+/// filters (`matches`, `not-matches`, `min-length`, `max-length`) keep or
+/// drop elements unchanged, and `sentences`/`paragraphs`/`extract-emails`/
+/// `extract-phones` split each element into several. `get-attr`/`get-attrs`
+/// and the navigation functions (`closest`, `parent`, …) need an element,
+/// and `context` a regex match; they leave plain strings unchanged.
+pub fn apply_text_functions(results: Vec<String>, functions: &[TextFunction]) -> Vec<String> {
+    let entries = results.into_iter().map(|text| (None, text)).collect();
+    apply_chain::<()>(entries, functions, &Detached)
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect()
+}
+
+/// What a function chain can ask of the source a value came from: a
+/// matched element's attributes and neighbours (CSS), or the content
+/// around a match (regex). Each answer defaults to "not available".
+#[doc(hidden)]
+pub trait ValueSource<T> {
+    /// Whether the sources are elements. Values from other sources pass
+    /// `get-attr`/`get-attrs` and navigation unchanged.
+    fn elements(&self) -> bool {
+        false
+    }
+
+    /// The value of attribute `name`, for `get-attr`/`get-attrs`.
+    fn attribute(&self, _source: T, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// The element `step` reaches, with its text.
+    fn navigate(&self, _source: T, _step: &Navigation) -> Option<(T, String)> {
+        None
+    }
+
+    /// The match widened to `window`, for `context`.
+    fn context(&self, _source: T, _window: ContextWindow) -> Option<String> {
+        None
+    }
+}
+
+/// Sources that answer nothing: values with no element or match behind
+/// them.
+#[doc(hidden)]
+pub struct Detached;
+
+impl<T> ValueSource<T> for Detached {}
+
+/// Run a function chain over `(source, text)` entries, keeping each text
+/// paired with the source it came from (a matched element, for CSS; a
+/// match's byte range, for regex) so that `get-attr`, navigation, and
+/// `context` can consult `sources` at any point in the chain.
+///
+/// Filters and reordering functions keep the pairing; a fold (`join`,
+/// `count`, …) produces a value with no source, which `get-attr` and the
+/// navigation functions leave unchanged. Empty values are dropped before
+/// every function except `get-attr`/`get-attrs` and navigation — so an
+/// element without text can still yield an attribute or lead elsewhere —
+/// and from the final result.
+#[doc(hidden)]
+pub fn apply_chain<T: Copy>(
+    mut entries: Vec<(Option<T>, String)>,
+    functions: &[TextFunction],
+    sources: &impl ValueSource<T>,
+) -> Vec<(Option<T>, String)> {
+    for function in functions {
+        if !matches!(
+            function,
+            TextFunction::GetAttribute { .. }
+                | TextFunction::GetAttributes { .. }
+                | TextFunction::Navigate { .. }
+        ) {
+            entries.retain(|(_, text)| !text.is_empty());
+        }
+
+        match function {
+            TextFunction::GetAttribute { .. }
+            | TextFunction::GetAttributes { .. }
+            | TextFunction::Navigate { .. }
+                if !sources.elements() => {}
+            TextFunction::GetAttribute { attribute: name } => {
+                for (source, text) in &mut entries {
+                    if let Some(source) = source {
+                        *text = sources.attribute(*source, name).unwrap_or_default();
+                    }
+                }
+            }
+            TextFunction::GetAttributes { attributes } => {
+                for (source, text) in &mut entries {
+                    if let Some(source) = source {
+                        let values: Vec<String> = attributes
+                            .iter()
+                            .map(|name| sources.attribute(*source, name).unwrap_or_default())
+                            .collect();
+                        *text = if values.iter().all(String::is_empty) {
+                            String::new()
+                        } else {
+                            values.join(ATTRIBUTE_SEPARATOR)
+                        };
+                    }
+                }
+            }
+            TextFunction::Navigate { step } => {
+                for (source, text) in &mut entries {
+                    if let Some(from) = *source {
+                        let (to, to_text) = match sources.navigate(from, step) {
+                            Some((to, to_text)) => (Some(to), to_text),
+                            None => (None, String::new()),
+                        };
+                        *source = to;
+                        *text = to_text;
+                    }
+                }
+            }
+            TextFunction::Context { window } => {
+                for (source, text) in &mut entries {
+                    if let Some(wider) = source.and_then(|s| sources.context(s, *window)) {
+                        *text = wider;
+                    }
+                }
+            }
+            // Fold: join the whole list into a single result.
+            TextFunction::Join { separator } => {
+                let texts: Vec<&str> = entries.iter().map(|(_, text)| text.as_str()).collect();
+                let joined = texts.join(separator.as_str());
+                entries = if joined.is_empty() { vec![] } else { vec![(None, joined)] };
+            }
+            TextFunction::IfEmpty { default } => {
+                if entries.is_empty() && !default.is_empty() {
+                    entries = vec![(None, default.clone())];
+                }
+            }
+            TextFunction::Sort => {
+                let numbers: Option<Vec<f64>> =
+                    entries.iter().map(|(_, text)| text.trim().parse().ok()).collect();
+                match numbers {
+                    Some(numbers) => {
+                        let mut keyed: Vec<_> = numbers.into_iter().zip(entries).collect();
+                        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+                        entries = keyed.into_iter().map(|(_, entry)| entry).collect();
+                    }
+                    None => entries.sort_by(|a, b| a.1.cmp(&b.1)),
+                }
+            }
+            TextFunction::Unique => {
+                let mut seen = BTreeSet::new();
+                entries.retain(|(_, text)| seen.insert(text.clone()));
+            }
+            TextFunction::UniqueBy { key } => {
+                let mut seen = BTreeSet::new();
+                entries.retain(|(_, text)| {
+                    let derived = apply_single_text_function(text, key);
+                    seen.insert(if derived.is_empty() {
+                        text.clone()
+                    } else {
+                        derived
+                    })
+                });
+            }
+            TextFunction::Reverse => entries.reverse(),
+            TextFunction::Count => entries = vec![(None, entries.len().to_string())],
+            TextFunction::Aggregate { aggregate } => {
+                let numbers: Vec<f64> =
+                    entries.iter().filter_map(|(_, text)| parse_number(text)).collect();
+                entries = aggregate
+                    .apply(&numbers)
+                    .map(|n| (None, n.to_string()))
+                    .into_iter()
+                    .collect();
+            }
+            TextFunction::First => entries.truncate(1),
+            TextFunction::Last => {
+                let last = entries.pop();
+                entries = last.into_iter().collect();
+            }
+            // Expand: split each element into several, keeping its source.
+            TextFunction::Sentences | TextFunction::Paragraphs | TextFunction::ExtractEmails => {
+                entries = expand(entries, |text| match function {
+                    TextFunction::Sentences => sentences(text),
+                    TextFunction::Paragraphs => paragraphs(text),
+                    _ => contacts::emails(text),
+                });
+            }
+            #[cfg(feature = "phone")]
+            TextFunction::ExtractPhones { region } => {
+                entries = expand(entries, |text| contacts::phones(text, Some(*region)));
+            }
+            // Filter: keep or drop each element as-is.
+            TextFunction::Matches { re } => entries.retain(|(_, text)| re.is_match(text)),
+            TextFunction::NotMatches { re } => entries.retain(|(_, text)| !re.is_match(text)),
+            TextFunction::MinLength { min } => {
+                entries.retain(|(_, text)| text.chars().count() >= *min)
+            }
+            TextFunction::MaxLength { max } => {
+                entries.retain(|(_, text)| text.chars().count() <= *max)
+            }
+            // Map: transform each element, dropping any that become empty.
+            _ => {
+                for (_, text) in &mut entries {
+                    *text = apply_single_text_function(text, function);
+                }
+            }
+        }
+    }
+    entries.retain(|(_, text)| !text.is_empty());
+    entries
+}
+
+/// Replace each entry with the parts `split` makes of its text, keeping its
+/// source.
+fn expand<T: Copy>(
+    entries: Vec<(Option<T>, String)>,
+    split: impl Fn(&str) -> Vec<String>,
+) -> Vec<(Option<T>, String)> {
+    entries
+        .into_iter()
+        .flat_map(|(source, text)| split(&text).into_iter().map(move |part| (source, part)))
+        .collect()
+}
+
+/// Apply a single text function to a string.
+pub fn apply_single_text_function(text: &str, function: &TextFunction) -> String {
+    match function {
+        TextFunction::NormalizeSpace => {
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        }
+        TextFunction::Trim => text.trim().to_string(),
+        TextFunction::Uppercase => text.to_uppercase(),
+        TextFunction::Lowercase => text.to_lowercase(),
+        TextFunction::Substring { start, length } => {
+            let chars: Vec<char> = text.chars().collect();
+            let start = if *start < 0 {
+                chars.len().saturating_sub(start.unsigned_abs())
+            } else {
+                *start as usize
+            };
+            if start < chars.len() {
+                let end = match length {
+                    Some(length) => start.saturating_add(*length).min(chars.len()),
+                    None => chars.len(),
+                };
+                chars[start..end].iter().collect()
+            } else {
+                String::new()
+            }
+        }
+        TextFunction::SubstringAfter { delimiter } => {
+            if let Some(pos) = text.find(delimiter.as_str()) {
+                text[pos + delimiter.len()..].to_string()
+            } else {
+                String::new()
+            }
+        }
+        TextFunction::SubstringBefore { delimiter } => {
+            if let Some(pos) = text.find(delimiter.as_str()) {
+                text[..pos].to_string()
+            } else {
+                text.to_string()
+            }
+        }
+        TextFunction::Replace { find, replace } => text.replace(find.as_str(), replace.as_str()),
+        TextFunction::GetAttribute { .. }
+        | TextFunction::GetAttributes { .. }
+        | TextFunction::Navigate { .. }
+        | TextFunction::Context { .. } => {
+            // Reads the value's source; handled in `apply_chain`, not here.
+            text.to_string()
+        }
+        TextFunction::Join { .. }
+        | TextFunction::IfEmpty { .. }
+        | TextFunction::Sort
+        | TextFunction::Unique
+        | TextFunction::UniqueBy { .. }
+        | TextFunction::Reverse
+        | TextFunction::First
+        | TextFunction::Last
+        | TextFunction::Count
+        | TextFunction::Aggregate { .. } => {
+            // Act on the whole list; handled in `apply_text_functions`, not here.
+            text.to_string()
+        }
+        TextFunction::Sentences | TextFunction::Paragraphs | TextFunction::ExtractEmails => {
+            // Produce several values; handled in `apply_chain`, not here.
+            text.to_string()
+        }
+        #[cfg(feature = "phone")]
+        TextFunction::ExtractPhones { .. } => text.to_string(),
+        TextFunction::WordCount => text.unicode_words().count().to_string(),
+        TextFunction::ParseCurrency => units::parse_currency(text).unwrap_or_default(),
+        TextFunction::ConvertUnit { from, to } => parse_number(text)
+            .and_then(|n| units::convert(n, from, to))
+            .unwrap_or_default(),
+        TextFunction::Format { template } => format_value(template, text),
+        TextFunction::TitleCase => {
+            let mut out = String::with_capacity(text.len());
+            let mut word_start = true;
+            for c in text.chars() {
+                if word_start {
+                    out.extend(c.to_uppercase());
+                } else {
+                    out.extend(c.to_lowercase());
+                }
+                word_start = !c.is_alphanumeric() && c != '\'';
+            }
+            out
+        }
+        TextFunction::Capitalize => {
+            let mut chars = text.chars();
+            match chars.next() {
+                Some(first) => {
+                    let rest = chars.as_str().to_lowercase();
+                    first.to_uppercase().chain(rest.chars()).collect()
+                }
+                None => String::new(),
+            }
+        }
+        TextFunction::PadLeft { width, fill } => {
+            let padding = width.saturating_sub(text.chars().count());
+            core::iter::repeat_n(*fill, padding).chain(text.chars()).collect()
+        }
+        TextFunction::PadRight { width, fill } => {
+            let padding = width.saturating_sub(text.chars().count());
+            text.chars().chain(core::iter::repeat_n(*fill, padding)).collect()
+        }
+        TextFunction::Truncate { max, suffix } => {
+            if text.chars().count() <= *max {
+                text.to_string()
+            } else {
+                let keep = max.saturating_sub(suffix.chars().count());
+                text.chars().take(keep).chain(suffix.chars()).collect()
+            }
+        }
+        TextFunction::StripTags => strip_tags(text),
+        #[cfg(feature = "digest")]
+        TextFunction::Sha256 => hex(&Sha256::digest(text.as_bytes())),
+        #[cfg(feature = "digest")]
+        TextFunction::Md5 => hex(&Md5::digest(text.as_bytes())),
+        #[cfg(feature = "digest")]
+        TextFunction::Base64Encode => STANDARD.encode(text),
+        #[cfg(feature = "digest")]
+        TextFunction::Base64Decode => base64_decode(text).unwrap_or_default(),
+        TextFunction::ToNumber => parse_number(text).map_or_else(String::new, |n| n.to_string()),
+        // Filters: a rejected value maps to empty, which the chain drops.
+        TextFunction::Matches { re } if re.is_match(text) => text.to_string(),
+        TextFunction::NotMatches { re } if !re.is_match(text) => text.to_string(),
+        TextFunction::Matches { .. } | TextFunction::NotMatches { .. } => String::new(),
+        TextFunction::MinLength { min } if text.chars().count() >= *min => text.to_string(),
+        TextFunction::MaxLength { max } if text.chars().count() <= *max => text.to_string(),
+        TextFunction::MinLength { .. } | TextFunction::MaxLength { .. } => String::new(),
+        TextFunction::IfContains {
+            needle,
+            then,
+            otherwise,
+        } => {
+            if text.contains(needle.as_str()) {
+                then.clone()
+            } else {
+                otherwise.clone().unwrap_or_else(|| text.to_string())
+            }
+        }
+        TextFunction::Translate { from, to } => {
+            let to_chars: Vec<char> = to.chars().collect();
+            text.chars()
+                .filter_map(|c| match from.chars().position(|fc| fc == c) {
+                    // Mapped char, or deleted when `to` has no counterpart.
+                    Some(idx) => to_chars.get(idx).copied(),
+                    None => Some(c),
+                })
+                .collect()
+        }
+        TextFunction::RegexExtract { re } => match re.captures(text) {
+            Some(caps) => caps
+                .get(1)
+                .or_else(|| caps.get(0))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default(),
+            None => String::new(),
+        },
+        TextFunction::RegexReplace { re, replace } => {
+            re.replace_all(text, replace.as_str()).into_owned()
+        }
+        TextFunction::SubstringAfterLast { delimiter } => match text.rfind(delimiter.as_str()) {
+            Some(pos) => text[pos + delimiter.len()..].to_string(),
+            None => String::new(),
+        },
+        TextFunction::SubstringBeforeLast { delimiter } => match text.rfind(delimiter.as_str()) {
+            Some(pos) => text[..pos].to_string(),
+            None => text.to_string(),
+        },
+    }
+}
+
+/// The sentences of `text` (UAX #29), trimmed; whitespace-only ones are
+/// dropped.
+fn sentences(text: &str) -> Vec<String> {
+    text.split_sentence_bounds()
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The paragraphs of `text`: split at lines holding only whitespace and at
+/// U+2029, trimmed; empty ones are dropped.
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    let mut flush = |current: &mut String| {
+        let paragraph = current.trim();
+        if !paragraph.is_empty() {
+            paragraphs.push(paragraph.to_string());
+        }
+        current.clear();
+    };
+    for block in text.split('\u{2029}') {
+        for line in block.lines() {
+            if line.trim().is_empty() {
+                flush(&mut current);
+            } else {
+                current.push_str(line);
+                current.push('\n');
+            }
+        }
+        flush(&mut current);
+    }
+    paragraphs
+}
+
+/// Lowercase hex encoding of a digest.
+#[doc(hidden)]
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Decode base64 in any common alphabet/padding to UTF-8 text.
+#[cfg(feature = "digest")]
+fn base64_decode(text: &str) -> Option<String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let unpadded = compact.trim_end_matches('=');
+    let bytes = if unpadded.contains(['-', '_']) {
+        URL_SAFE_NO_PAD.decode(unpadded)
+    } else {
+        STANDARD_NO_PAD.decode(unpadded)
+    }
+    .ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Fill a `format` template: `{}` becomes `value`, `{{` and `}}` a literal brace.
+fn format_value(template: &str, value: &str) -> String {
+    let mut out = String::with_capacity(template.len() + value.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('}')) => {
+                chars.next();
+                out.push_str(value);
+            }
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The first number in `text`: an optional `-`, digits with optional `,`
+/// thousands separators, and an optional `.` fraction.
+#[doc(hidden)]
+pub fn parse_number(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let start = bytes.iter().position(u8::is_ascii_digit)?;
+    let negative = start > 0 && bytes[start - 1] == b'-';
+
+    let mut number = String::new();
+    let mut seen_point = false;
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        let next_is_digit = bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        match b {
+            b'0'..=b'9' => number.push(b as char),
+            b',' if !seen_point && next_is_digit => {}
+            b'.' if !seen_point && next_is_digit => {
+                seen_point = true;
+                number.push('.');
+            }
+            _ => break,
+        }
+    }
+
+    let value: f64 = number.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
@@ -0,0 +1,67 @@
+//! # ChadSelect core
+//!
+//! The parts of [ChadSelect](https://docs.rs/chadselect) that do not need a
+//! parsed HTML document: query-prefix parsing, the `>>` function pipeline,
+//! and the regex and JMESPath engines. It parses no HTML, so it builds on
+//! `regex` and a few small encoding crates only (plus JMESPath with the
+//! `json` feature), for edge workers and other constrained targets that run
+//! regex or JSON queries over text.
+//!
+//! ```rust
+//! use chadselect_core::engine::regex;
+//! use chadselect_core::query::{parse_query, QueryType};
+//!
+//! let QueryType::Regex(pattern) = parse_query(r"regex:\$(\d+) >> to-number()").unwrap() else {
+//!     unreachable!()
+//! };
+//! assert_eq!(regex::process(&pattern, "from $49 to $99"), ["49", "99"]);
+//! ```
+//!
+//! Without the default `std` feature the crate is `#![no_std]` and needs
+//! only `alloc`, building with `default-features = false, features =
+//! ["regex"]`. What `std` adds is thread-local state: the compiled-regex
+//! cache, warning capture, and cancellation. Without it regexes compile on
+//! every call, warnings are only logged, and queries run to completion. The
+//! `json` and `phone` features need `std`. The crate has no I/O and no
+//! global state beyond those thread-locals.
+//!
+//! The default `digest` feature adds the `sha256()`, `md5()`, and
+//! `base64-*()` pipe functions and the hashing and encoding crates behind
+//! them.
+//!
+//! Most users want `chadselect`, which re-exports these modules and adds the
+//! content store and the CSS and XPath engines.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "css", feature = "json", feature = "regex")))]
+compile_error!(
+    "chadselect-core needs at least one engine feature: `css`, `xpath`, `json`, or `regex`"
+);
+
+pub mod cancel;
+#[doc(hidden)]
+pub mod contacts;
+pub mod engine;
+pub mod functions;
+pub mod markup;
+pub mod patterns;
+pub mod query;
+mod units;
+pub mod warnings;
+
+#[cfg(feature = "std")]
+pub use cancel::CancellationToken;
+pub use cancel::QueryStatus;
+pub use functions::supported_text_functions;
+pub use query::{PrefixMode, QueryType, FALLBACK_OPERATOR, FUNCTION_PIPE};
+pub use warnings::{Warning, WarningKind};
+
+/// What the exported macros need from `alloc`, reachable from crates that
+/// do not name it.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::format;
+}
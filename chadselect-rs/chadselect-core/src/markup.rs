@@ -0,0 +1,178 @@
+//! HTML markup without a parser: the tag stripping behind `strip-tags()` and
+//! character-reference decoding.
+//!
+//! ```text
+//! <p>Low <b>miles</b> &amp; one owner</p><script>track()</script>   →   Low miles & one owner
+//! ```
+
+use alloc::string::{String, ToString};
+
+/// Elements whose bodies are code, not text: they are dropped whole.
+const CODE_ELEMENTS: [&str; 2] = ["script", "style"];
+
+/// The text of an HTML fragment: tags and comments removed, `<script>` and
+/// `<style>` bodies dropped, and character references decoded. A `<` that
+/// does not start a tag (`1 < 2`) is text.
+pub fn strip_tags(html: &str) -> String {
+    if !html.contains(['<', '&']) {
+        return html.to_string();
+    }
+    let starts_name = |s: &str| s.starts_with(|c: char| c.is_ascii_alphabetic());
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        rest = if let Some(comment) = after.strip_prefix("!--") {
+            comment.find("-->").map_or("", |end| &comment[end + 3..])
+        } else if after.starts_with(['!', '?']) || after.strip_prefix('/').is_some_and(starts_name)
+        {
+            skip_tag(after)
+        } else if starts_name(after) {
+            let name_len = after
+                .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+                .unwrap_or(after.len());
+            let name = &after[..name_len];
+            let body = skip_tag(after);
+            match CODE_ELEMENTS
+                .iter()
+                .find(|code| code.eq_ignore_ascii_case(name))
+            {
+                Some(code) => skip_body(body, code),
+                None => body,
+            }
+        } else {
+            text.push('<');
+            after
+        };
+    }
+    text.push_str(rest);
+    decode_entities(&text)
+}
+
+/// What follows the `>` closing the tag `after` starts, skipping quoted
+/// attribute values.
+fn skip_tag(after: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in after.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return &after[i + 1..],
+            (None, _) => {}
+        }
+    }
+    ""
+}
+
+/// What follows the `</name>` that ends the code element whose body starts
+/// `body`.
+fn skip_body<'a>(body: &'a str, name: &str) -> &'a str {
+    let mut rest = body;
+    while let Some(close) = rest.find("</") {
+        let tag = &rest[close + 2..];
+        if tag.len() >= name.len()
+            && tag.is_char_boundary(name.len())
+            && tag[..name.len()].eq_ignore_ascii_case(name)
+        {
+            return skip_tag(tag);
+        }
+        rest = tag;
+    }
+    ""
+}
+
+/// Decode character references: numeric ones and the common named ones.
+/// Other named references stay as written.
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let name = &rest[1..end];
+            let c = match name.strip_prefix('#') {
+                Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16)
+                    .ok()
+                    .and_then(char::from_u32),
+                Some(decimal) => decimal.parse().ok().and_then(char::from_u32),
+                None => named_entity(name),
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The character a common named reference (`amp`, `nbsp`, `eacute`, …)
+/// stands for.
+fn named_entity(name: &str) -> Option<char> {
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        "deg" => '°',
+        "middot" => '·',
+        "bull" => '•',
+        "hellip" => '…',
+        "ndash" => '–',
+        "mdash" => '—',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "times" => '×',
+        "divide" => '÷',
+        "euro" => '€',
+        "pound" => '£',
+        "yen" => '¥',
+        "cent" => '¢',
+        "sect" => '§',
+        "para" => '¶',
+        "frac12" => '½',
+        "frac14" => '¼',
+        "frac34" => '¾',
+        "aacute" => 'á',
+        "eacute" => 'é',
+        "iacute" => 'í',
+        "oacute" => 'ó',
+        "uacute" => 'ú',
+        "agrave" => 'à',
+        "egrave" => 'è',
+        "auml" => 'ä',
+        "ouml" => 'ö',
+        "uuml" => 'ü',
+        "Auml" => 'Ä',
+        "Ouml" => 'Ö',
+        "Uuml" => 'Ü',
+        "szlig" => 'ß',
+        "ntilde" => 'ñ',
+        "ccedil" => 'ç',
+        _ => return None,
+    };
+    Some(c)
+}
@@ -0,0 +1,303 @@
+//! Regex front-ends: built-in named patterns (`pat:name` runs a vetted
+//! regex), shell-style globs (`glob:*error*` matches whole lines), line
+//! filters (`lines:contains('ERROR')`), and multiline regexes (`regexm:`).
+//!
+//! ```text
+//! pat:email                 →   regex:(?i)\b[a-z0-9][a-z0-9._%+-]*@…
+//! pat:iso-date >> first()   →   regex:\b\d{4}-(?:0[1-9]|1[0-2])-… >> first()
+//! glob:*error*              →   regex:(?mR)^.*error.*$
+//! lines:contains('ERROR')   →   regex:(?mR)^.*ERROR.*$
+//! lines:matches('5\d\d')    →   regex:(?mR)^.+$ >> matches('5\d\d')
+//! regexm:^ERROR: (.*)$      →   regex:(?mR)^ERROR: (.*)$
+//! ```
+//!
+//! Apart from `regexm:`, the regexes use no capture groups, so each match is
+//! returned whole. Hex byte signatures (`hex:de ad ?? ef`) translate to byte
+//! regexes instead — see [`hex_to_regex`].
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::format;
+
+use crate::functions;
+use crate::query::FUNCTION_PIPE;
+
+/// The prefix that runs a named pattern.
+pub const PAT_PREFIX: &str = "pat:";
+
+/// An email address: a dot-atom local part, `@`, and a domain ending in an
+/// alphabetic top-level label.
+pub(crate) const EMAIL: &str = r"(?i)\b[a-z0-9][a-z0-9._%+-]*@[a-z0-9](?:[a-z0-9-]*[a-z0-9])?(?:\.[a-z0-9](?:[a-z0-9-]*[a-z0-9])?)*\.[a-z]{2,}\b";
+
+/// The built-in patterns, by name.
+pub const BUILTIN_PATTERNS: [(&str, &str); 6] = [
+    ("email", EMAIL),
+    // An `http`/`https` URL up to whitespace, quotes, or angle brackets,
+    // without trailing sentence punctuation or a closing bracket.
+    ("url", r#"(?i)\bhttps?://[^\s<>"'`]*[^\s<>"'`.,;:!?)\]}]"#),
+    // Four dotted octets, each 0–255 without leading zeros.
+    (
+        "ipv4",
+        r"\b(?:(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(?:25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\b",
+    ),
+    // 8-4-4-4-12 hex digits, either case.
+    (
+        "uuid",
+        r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b",
+    ),
+    // `YYYY-MM-DD`, optionally with a `T`/space-separated time, fractional
+    // seconds, and a `Z` or `±hh:mm` offset.
+    (
+        "iso-date",
+        r"\b\d{4}-(?:0[1-9]|1[0-2])-(?:0[1-9]|[12]\d|3[01])(?:[T ](?:[01]\d|2[0-3]):[0-5]\d(?::[0-5]\d(?:\.\d+)?)?(?:Z|[+-](?:[01]\d|2[0-3]):?[0-5]\d)?)?\b",
+    ),
+    // 17 characters of the VIN alphabet (digits and capitals except I, O,
+    // and Q).
+    ("vin", r"\b[A-HJ-NPR-Z0-9]{17}\b"),
+];
+
+/// The regex of the built-in pattern `name`, if there is one.
+pub fn builtin(name: &str) -> Option<&'static str> {
+    BUILTIN_PATTERNS
+        .iter()
+        .find(|(pattern, _)| *pattern == name)
+        .map(|(_, regex)| *regex)
+}
+
+/// The regex (with any `>>` chain) a `pat:name` expression, without the
+/// prefix, runs.
+pub(crate) fn expand(expression: &str) -> Result<String, String> {
+    let name_part = functions::strip_functions(expression);
+    let name = name_part.trim();
+    let chain = &expression[name_part.len()..];
+    let Some(regex) = builtin(name) else {
+        let names: Vec<&str> = BUILTIN_PATTERNS.iter().map(|(name, _)| *name).collect();
+        return Err(format!(
+            "unknown pattern '{}{}' (built-in: {})",
+            PAT_PREFIX,
+            name,
+            names.join(", ")
+        ));
+    };
+    Ok(format!("{}{}", regex, chain))
+}
+
+/// Translate a glob to a regex matching whole lines (`\n` or `\r\n`
+/// terminated): `*` is any run of characters, `?` any one character,
+/// `[abc]`, `[a-z]`, and `[!abc]` (or `[^abc]`) character classes, and `\`
+/// makes the next character literal. Everything else matches itself.
+///
+/// ```rust
+/// use chadselect_core::patterns::glob_to_regex;
+///
+/// assert_eq!(glob_to_regex("*error*"), "(?mR)^.*error.*$");
+/// assert_eq!(glob_to_regex("v?.[0-9]*"), r"(?mR)^v.\.[0-9].*$");
+/// ```
+pub fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::from("(?mR)^");
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                push_literal(&mut regex, chars[i]);
+            }
+            '[' => match class_end(&chars, i) {
+                Some(end) => {
+                    push_class(&mut regex, &chars[i + 1..end]);
+                    i = end;
+                }
+                None => push_literal(&mut regex, '['),
+            },
+            c => push_literal(&mut regex, c),
+        }
+        i += 1;
+    }
+    regex.push('$');
+    regex
+}
+
+/// The index of the `]` closing the class opened at `start`; a `]` first in
+/// the class (after any `!`/`^`) is a member, not the end.
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if matches!(chars.get(i), Some('!' | '^')) {
+        i += 1;
+    }
+    if chars.get(i) == Some(&']') {
+        i += 1;
+    }
+    chars[i.min(chars.len())..]
+        .iter()
+        .position(|c| *c == ']')
+        .map(|offset| i + offset)
+}
+
+/// A glob character class body as a regex class: a leading `!` negates, `-`
+/// between members is a range, and every other member is literal
+/// (punctuation is escaped, including the regex class operators `&&`, `--`,
+/// and `~~`).
+fn push_class(regex: &mut String, members: &[char]) {
+    regex.push('[');
+    let members = match members.first() {
+        Some('!' | '^') => {
+            regex.push('^');
+            &members[1..]
+        }
+        _ => members,
+    };
+    for (i, c) in members.iter().enumerate() {
+        let range = *c == '-' && i > 0 && i + 1 < members.len();
+        if *c == '>' {
+            regex.push_str(r"\x3E");
+            continue;
+        }
+        if c.is_ascii_punctuation() && !range {
+            regex.push('\\');
+        }
+        regex.push(*c);
+    }
+    regex.push(']');
+}
+
+/// Append `c` as a literal. `>` is written as a hex escape here and in
+/// classes, so the regex never holds a `>>` function pipe.
+fn push_literal(regex: &mut String, c: char) {
+    match c {
+        '>' => regex.push_str(r"\x3E"),
+        c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+    }
+}
+
+/// The inline flags a `regexm:` query starts with: `^`/`$` match at line
+/// boundaries, with `\r\n` as one break. Dot-all stays off, so `(.*)$`
+/// still stops at the end of its line; add `(?s)` for blocks spanning lines.
+pub const MULTILINE_FLAGS: &str = "(?mR)";
+
+/// The regex (with any `>>` chain) a `regexm:` expression, without the
+/// prefix, runs. Flags in the pattern itself still apply on top.
+pub(crate) fn multiline_query(expression: &str) -> String {
+    format!("{}{}", MULTILINE_FLAGS, expression)
+}
+
+/// Translate a hex byte signature to a byte regex (run with Unicode off and
+/// dot-all on, as `bytes:` patterns are): each pair of hex digits is one
+/// byte, `??` is any byte, and parentheses capture the bytes between them.
+/// Whitespace is ignored.
+///
+/// ```rust
+/// use chadselect_core::patterns::hex_to_regex;
+///
+/// assert_eq!(hex_to_regex("DE AD ?? ef").unwrap(), r"\xde\xad.\xef");
+/// assert_eq!(hex_to_regex("7f45 (????)").unwrap(), r"\x7f\x45(..)");
+/// assert!(hex_to_regex("de a").is_err());
+/// ```
+///
+/// # Errors
+///
+/// On a lone hex digit, unbalanced parentheses, or any other character.
+pub fn hex_to_regex(signature: &str) -> Result<String, String> {
+    let tokens: Vec<char> = signature.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut regex = String::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i] {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ')' => return Err("unbalanced ')' in hex signature".to_string()),
+            _ => {
+                let pair = tokens.get(i..i + 2).unwrap_or(&tokens[i..]);
+                match pair {
+                    ['?', '?'] => regex.push('.'),
+                    [high, low] if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => {
+                        regex.push_str(r"\x");
+                        regex.push(high.to_ascii_lowercase());
+                        regex.push(low.to_ascii_lowercase());
+                    }
+                    _ => {
+                        return Err(format!(
+                            "expected a hex byte or ?? at '{}' in hex signature",
+                            pair.iter().collect::<String>()
+                        ))
+                    }
+                }
+                i += 2;
+                continue;
+            }
+        }
+        regex.push(tokens[i]);
+        i += 1;
+    }
+    if depth > 0 {
+        return Err("unclosed '(' in hex signature".to_string());
+    }
+    Ok(regex)
+}
+
+/// The regex (with any `>>` chain) a `glob:` expression, without the prefix,
+/// runs. Quotes in the glob are literal characters.
+pub(crate) fn glob_query(expression: &str) -> String {
+    match functions::split_chain(expression, false) {
+        (glob, Some(chain)) => format!("{} {}{}", glob_to_regex(&glob), FUNCTION_PIPE, chain),
+        (glob, None) => glob_to_regex(&glob),
+    }
+}
+
+/// The regex (with any `>>` chain) a `lines:` expression, without the
+/// prefix, runs. The expression is an optional line filter — `contains`,
+/// `starts-with`, `ends-with`, or `equals` with a literal, or `matches` /
+/// `not-matches` with a regex — and every line it keeps is a result; with
+/// no filter, every non-empty line is.
+pub(crate) fn lines_query(expression: &str) -> Result<String, String> {
+    let (filter, chain) = functions::split_chain(expression, true);
+    let filter = filter.trim();
+    let (regex, stage) = if filter.is_empty() {
+        (String::from("(?mR)^.+$"), None)
+    } else {
+        let (name, args) = functions::call_arguments(filter)?;
+        let [literal] = args.as_slice() else {
+            return Err(format!(
+                "lines:{}(): expected 1 argument, got {}",
+                name,
+                args.len()
+            ));
+        };
+        let mut escaped = String::new();
+        for c in literal.chars() {
+            push_literal(&mut escaped, c);
+        }
+        match name {
+            "contains" => (format!("(?mR)^.*{}.*$", escaped), None),
+            "starts-with" => (format!("(?mR)^{}.*$", escaped), None),
+            "ends-with" => (format!("(?mR)^.*{}$", escaped), None),
+            "equals" => (format!("(?mR)^{}$", escaped), None),
+            // Line-level regex filters run as the pipe functions of the
+            // same name, so the pattern's own groups can't pick parts of
+            // the line.
+            "matches" | "not-matches" => (String::from("(?mR)^.+$"), Some(filter)),
+            _ => {
+                return Err(format!(
+                    "unknown line filter '{}' (expected contains, starts-with, ends-with, \
+                     equals, matches, or not-matches)",
+                    name
+                ))
+            }
+        }
+    };
+
+    let stages: Vec<&str> = stage.into_iter().chain(chain).collect();
+    if stages.is_empty() {
+        Ok(regex)
+    } else {
+        Ok(format!(
+            "{} {} {}",
+            regex,
+            FUNCTION_PIPE,
+            stages.join(&format!(" {} ", FUNCTION_PIPE))
+        ))
+    }
+}
@@ -0,0 +1,328 @@
+//! Query type parsing — prefix-based routing to the correct extraction engine.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::functions;
+use crate::patterns;
+
+/// The function-pipe delimiter used to separate a selector expression from its
+/// post-processing function chain.
+///
+/// We use `>>` instead of `|` because `|` is a union operator in XPath 1.0
+/// and a pipe operator in JMESPath, which would create ambiguity.
+///
+/// ```text
+/// css:.price >> normalize-space() >> uppercase()
+/// xpath://div[@class='vin']/text() >> substring-after('VIN: ')
+/// ```
+pub const FUNCTION_PIPE: &str = ">>";
+
+/// The fallback operator separating alternative queries in one string.
+///
+/// ```text
+/// css:.sale-price || css:.price || regex:\$(\d+)
+/// ```
+///
+/// Every alternative after the first must carry an engine prefix; a `||` not
+/// followed by one is left alone, so JMESPath's own `a || b` still works.
+pub const FALLBACK_OPERATOR: &str = "||";
+
+/// Prefixes that may start a fallback alternative: every
+/// [`QueryType::prefix`], its aliases, `auto:`, and `ref:`.
+const ALTERNATIVE_PREFIXES: [&str; 15] = [
+    "regex:", "regexm:", "xpath:", "json:", "css:", "auto:", "ref:", "embedded-json:",
+    "structured:", "text:", "pat:", "glob:", "lines:", "bytes:", "hex:",
+];
+
+/// Parsed query type with the engine-specific expression.
+#[derive(Debug, Clone)]
+pub enum QueryType {
+    /// Regex pattern — works on all content types.
+    Regex(String),
+    /// XPath 1.0 expression — works on HTML and Text.
+    XPath(String),
+    /// JMESPath expression — works on JSON.
+    JsonPath(String),
+    /// CSS selector — works on HTML.
+    CssSelector(String),
+    /// A source query selecting JSON blobs (script bodies, attributes), then
+    /// an optional `>> json:` stage run against each blob — works wherever
+    /// the source query does.
+    EmbeddedJson(String),
+    /// JMESPath expression over the page's structured data (JSON-LD,
+    /// microdata, RDFa, OpenGraph) — works on HTML.
+    Structured(String),
+    /// Full-text search (words, phrases, `AND`/`OR`, `NEAR`) returning
+    /// matching sentences — works on HTML, Text, and Markdown.
+    FullText(String),
+    /// Byte regex (see [`regex::bytes`](::regex::bytes)) returning matches as
+    /// lossy UTF-8 — works on all content types, and on the original bytes
+    /// of Binary content.
+    Bytes(String),
+    /// Hex byte signature (`de ad ?? ef`) returning matches hex-encoded —
+    /// works wherever [`QueryType::Bytes`] does.
+    Hex(String),
+}
+
+impl QueryType {
+    /// The query prefix (without the colon) naming this query's engine.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            QueryType::Regex(_) => "regex",
+            QueryType::XPath(_) => "xpath",
+            QueryType::JsonPath(_) => "json",
+            QueryType::CssSelector(_) => "css",
+            QueryType::EmbeddedJson(_) => "embedded-json",
+            QueryType::Structured(_) => "structured",
+            QueryType::FullText(_) => "text",
+            QueryType::Bytes(_) => "bytes",
+            QueryType::Hex(_) => "hex",
+        }
+    }
+
+    /// The Cargo feature this query's engine needs that this build leaves
+    /// out, if any. Such a query fails to prepare, and the lenient query
+    /// methods skip it with an
+    /// [`UnsupportedEngine`](crate::WarningKind::UnsupportedEngine) warning.
+    ///
+    /// `chadselect` turns on the features here that it is built with, so
+    /// the answer matches its engines.
+    ///
+    /// ```rust
+    /// use chadselect_core::QueryType;
+    ///
+    /// assert_eq!(QueryType::Regex(r"\d+".to_string()).missing_feature(), None);
+    /// ```
+    pub fn missing_feature(&self) -> Option<&'static str> {
+        let needs: &[&'static str] = match self {
+            QueryType::Regex(_) | QueryType::Bytes(_) | QueryType::Hex(_) => &["regex"],
+            QueryType::XPath(_) => &["xpath"],
+            QueryType::JsonPath(_) | QueryType::EmbeddedJson(_) => &["json"],
+            QueryType::CssSelector(_) | QueryType::FullText(_) => &["css"],
+            QueryType::Structured(_) => &["css", "json"],
+        };
+        needs.iter().copied().find(|&feature| match feature {
+            "regex" => !cfg!(feature = "regex"),
+            "xpath" => !cfg!(feature = "xpath"),
+            "json" => !cfg!(feature = "json"),
+            _ => !cfg!(feature = "css"),
+        })
+    }
+
+    /// The engine-specific expression (including any `>>` function chain).
+    pub fn expression(&self) -> &str {
+        match self {
+            QueryType::Regex(e)
+            | QueryType::XPath(e)
+            | QueryType::JsonPath(e)
+            | QueryType::CssSelector(e)
+            | QueryType::EmbeddedJson(e)
+            | QueryType::Structured(e)
+            | QueryType::FullText(e)
+            | QueryType::Bytes(e)
+            | QueryType::Hex(e) => e,
+        }
+    }
+}
+
+/// How a query without a recognised engine prefix is routed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrefixMode {
+    /// Treat the whole query as a regex (the historical behaviour).
+    #[default]
+    Regex,
+    /// Reject the query. A typo'd prefix such as `xpat://div` fails to parse
+    /// instead of silently compiling as a regex.
+    Strict,
+    /// Pick the engine heuristically, exactly as the `auto:` prefix does.
+    Auto,
+}
+
+/// Parse a prefixed query string into its typed representation.
+///
+/// Supported prefixes:
+/// - `regex:` → [`QueryType::Regex`]
+/// - `regexm:` → [`QueryType::Regex`] with `^`/`$` matching per line (see
+///   [`patterns::MULTILINE_FLAGS`])
+/// - `xpath:` → [`QueryType::XPath`]
+/// - `json:`  → [`QueryType::JsonPath`]
+/// - `css:`   → [`QueryType::CssSelector`]
+/// - `auto:`  → whichever of the above [`detect_query_type`] picks
+/// - `embedded-json:` → [`QueryType::EmbeddedJson`]
+/// - `structured:` → [`QueryType::Structured`]
+/// - `text:` → [`QueryType::FullText`]
+/// - `pat:` → [`QueryType::Regex`] running the named built-in pattern (see
+///   [`patterns`])
+/// - `glob:` → [`QueryType::Regex`] matching whole lines against the glob
+///   (see [`patterns::glob_to_regex`])
+/// - `lines:` → [`QueryType::Regex`] returning the lines a filter such as
+///   `contains('ERROR')` keeps
+/// - `bytes:` → [`QueryType::Bytes`]
+/// - `hex:` → [`QueryType::Hex`]
+///
+/// If no prefix is provided, the query defaults to Regex.
+pub fn parse_query(query: &str) -> Result<QueryType, String> {
+    parse_query_with(query, PrefixMode::Regex)
+}
+
+/// Like [`parse_query`], but routes an unprefixed query according to `mode`.
+pub fn parse_query_with(query: &str, mode: PrefixMode) -> Result<QueryType, String> {
+    if let Some(pattern) = query.strip_prefix("regex:") {
+        Ok(QueryType::Regex(pattern.to_string()))
+    } else if let Some(pattern) = query.strip_prefix("regexm:") {
+        Ok(QueryType::Regex(patterns::multiline_query(pattern)))
+    } else if let Some(path) = query.strip_prefix("json:") {
+        Ok(QueryType::JsonPath(path.to_string()))
+    } else if let Some(xpath) = query.strip_prefix("xpath:") {
+        Ok(QueryType::XPath(xpath.to_string()))
+    } else if let Some(selector) = query.strip_prefix("css:") {
+        Ok(QueryType::CssSelector(selector.to_string()))
+    } else if let Some(expression) = query.strip_prefix("embedded-json:") {
+        Ok(QueryType::EmbeddedJson(expression.to_string()))
+    } else if let Some(path) = query.strip_prefix("structured:") {
+        Ok(QueryType::Structured(path.to_string()))
+    } else if let Some(expression) = query.strip_prefix("text:") {
+        Ok(QueryType::FullText(expression.to_string()))
+    } else if let Some(expression) = query.strip_prefix("pat:") {
+        Ok(QueryType::Regex(patterns::expand(expression)?))
+    } else if let Some(expression) = query.strip_prefix("glob:") {
+        Ok(QueryType::Regex(patterns::glob_query(expression)))
+    } else if let Some(expression) = query.strip_prefix("lines:") {
+        Ok(QueryType::Regex(patterns::lines_query(expression)?))
+    } else if let Some(pattern) = query.strip_prefix("bytes:") {
+        Ok(QueryType::Bytes(pattern.to_string()))
+    } else if let Some(signature) = query.strip_prefix("hex:") {
+        Ok(QueryType::Hex(signature.to_string()))
+    } else if let Some(expression) = query.strip_prefix("auto:") {
+        Ok(detect_query_type(expression))
+    } else {
+        match mode {
+            PrefixMode::Regex => Ok(QueryType::Regex(query.to_string())),
+            PrefixMode::Auto => Ok(detect_query_type(query)),
+            PrefixMode::Strict => Err(format!(
+                "query '{}' has no recognised engine prefix (expected one of {})",
+                query,
+                ALTERNATIVE_PREFIXES.join(", ")
+            )),
+        }
+    }
+}
+
+/// HTML element names that mark a bare leading identifier as a CSS type
+/// selector (`div.price`, `ul > li`) rather than a JMESPath field.
+const HTML_TAGS: &[&str] = &[
+    "a", "abbr", "address", "article", "aside", "b", "blockquote", "body", "button",
+    "caption", "code", "dd", "div", "dl", "dt", "em", "fieldset", "figcaption", "figure",
+    "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "head", "header", "html", "i",
+    "iframe", "img", "input", "label", "legend", "li", "link", "main", "meta", "nav",
+    "noscript", "ol", "option", "p", "picture", "pre", "script", "section", "select",
+    "small", "source", "span", "strong", "style", "sup", "sub", "svg", "table", "tbody",
+    "td", "template", "textarea", "tfoot", "th", "thead", "time", "title", "tr", "u", "ul",
+    "video",
+];
+
+/// Whether `query` starts with an engine (or `ref:`) prefix.
+#[doc(hidden)]
+pub fn has_prefix(query: &str) -> bool {
+    ALTERNATIVE_PREFIXES.iter().any(|p| query.starts_with(p))
+}
+
+/// Heuristically classify an unprefixed expression (any `>>` function chain is
+/// ignored for classification and kept on the returned expression):
+///
+/// - leading `/`, `(`, or `./` → XPath
+/// - leading `.`, `#`, `*`, a `[attr]` test, or an HTML tag name → CSS
+/// - JMESPath-ish syntax (`a.b`, `a[0]`, `a[]`, `a[?x]`, backtick literals) → JSON
+/// - anything else → Regex
+pub fn detect_query_type(expression: &str) -> QueryType {
+    let owned = expression.to_string();
+    let expr = functions::strip_functions(expression).trim();
+
+    if expr.starts_with('/') || expr.starts_with('(') || expr.starts_with("./") {
+        return QueryType::XPath(owned);
+    }
+    if expr.starts_with('.') || expr.starts_with('#') || expr.starts_with('*') {
+        return QueryType::CssSelector(owned);
+    }
+    if expr.starts_with('[') && is_css_attribute_test(expr) {
+        return QueryType::CssSelector(owned);
+    }
+
+    let ident_len = expr
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(expr.len());
+    let ident = &expr[..ident_len];
+    let rest = &expr[ident_len..];
+    if HTML_TAGS.contains(&ident)
+        && (rest.is_empty() || rest.starts_with(['.', '#', '[', ':', ' ', '>', '+', '~', ',']))
+    {
+        return QueryType::CssSelector(owned);
+    }
+    if looks_like_jmespath(expr) {
+        return QueryType::JsonPath(owned);
+    }
+    QueryType::Regex(owned)
+}
+
+/// `[name]`, `[name=…]`, `[name^=…]`, … — a CSS attribute test rather than a
+/// JMESPath multiselect list.
+fn is_css_attribute_test(expr: &str) -> bool {
+    let inner = &expr[1..];
+    let name_len = inner
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(inner.len());
+    name_len > 0 && inner[name_len..].starts_with([']', '=', '^', '$', '*', '~', '|'])
+}
+
+/// Identifier paths (`a.b[0].c`, `items[].name`, `items[?in_stock]`) and
+/// backtick literals are JMESPath; regex escapes and whitespace-only words are
+/// not.
+fn looks_like_jmespath(expr: &str) -> bool {
+    if expr.contains('`') || expr.contains("[?") || expr.contains("[]") || expr.contains("[*]") {
+        return true;
+    }
+    if expr.contains('\\') || expr.contains(char::is_whitespace) {
+        return false;
+    }
+    let starts_ident = expr
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '@');
+    let path_chars = expr
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '[' | ']' | '-' | '@'));
+    starts_ident && path_chars && (expr.contains('.') || expr.contains('['))
+}
+
+/// Split a query string on the [`FALLBACK_OPERATOR`] into its alternatives,
+/// trimmed. A `||` inside a quoted string literal, or not followed by an
+/// engine prefix, does not split. A query without `||` yields itself.
+pub fn split_alternatives(query: &str) -> Vec<&str> {
+    let bytes = query.as_bytes();
+    let op = FALLBACK_OPERATOR.as_bytes();
+    let mut alternatives = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\'' | b'"' | b'`' => match functions::closing_quote(bytes, i) {
+                Some(end) => i = end + 1,
+                None => i += 1,
+            },
+            _ if bytes[i..].starts_with(op) => {
+                let rest = query[i + op.len()..].trim_start();
+                if ALTERNATIVE_PREFIXES.iter().any(|p| rest.starts_with(p)) {
+                    alternatives.push(query[start..i].trim());
+                    start = i + op.len();
+                }
+                i += op.len();
+            }
+            _ => i += 1,
+        }
+    }
+    alternatives.push(query[start..].trim());
+    alternatives
+}
@@ -0,0 +1,235 @@
+//! Money and measurement normalization for the `parse-currency()` and
+//! `convert-unit()` pipe functions.
+//!
+//! ```text
+//! "$1,299.00"     parse-currency()            → "1299.00|USD"
+//! "1.299,00 €"    parse-currency()            → "1299.00|EUR"
+//! "12,000 mi"     convert-unit('mi', 'km')    → "19312.128"
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Currency symbols and the ISO 4217 code each stands for, longest first so
+/// `US$` wins over `$`.
+const CURRENCY_SYMBOLS: [(&str, &str); 27] = [
+    ("US$", "USD"),
+    ("CA$", "CAD"),
+    ("AU$", "AUD"),
+    ("NZ$", "NZD"),
+    ("HK$", "HKD"),
+    ("MX$", "MXN"),
+    ("CN¥", "CNY"),
+    ("C$", "CAD"),
+    ("A$", "AUD"),
+    ("S$", "SGD"),
+    ("R$", "BRL"),
+    ("zł", "PLN"),
+    ("Kč", "CZK"),
+    ("€", "EUR"),
+    ("£", "GBP"),
+    ("¥", "JPY"),
+    ("₹", "INR"),
+    ("₩", "KRW"),
+    ("₽", "RUB"),
+    ("₺", "TRY"),
+    ("₪", "ILS"),
+    ("₫", "VND"),
+    ("฿", "THB"),
+    ("₱", "PHP"),
+    ("₴", "UAH"),
+    ("元", "CNY"),
+    ("$", "USD"),
+];
+
+/// ISO 4217 codes recognised when written out (`49.99 EUR`, `CHF 12`).
+const CURRENCY_CODES: [&str; 36] = [
+    "AED", "ARS", "AUD", "BRL", "CAD", "CHF", "CLP", "CNY", "COP", "CZK", "DKK", "EUR", "GBP",
+    "HKD", "HUF", "IDR", "ILS", "INR", "ISK", "JPY", "KRW", "MXN", "MYR", "NOK", "NZD", "PHP",
+    "PLN", "RON", "RUB", "SAR", "SEK", "SGD", "THB", "TRY", "USD", "ZAR",
+];
+
+/// Currencies without minor units, formatted with no decimals.
+const ZERO_DECIMAL_CURRENCIES: [&str; 6] = ["CLP", "ISK", "JPY", "KRW", "VND", "IDR"];
+
+/// The currency `text` names: a written ISO code, else a symbol.
+fn currency_code(text: &str) -> Option<&'static str> {
+    let words = text.split(|c: char| !c.is_ascii_alphabetic());
+    for word in words {
+        if let Some(code) = CURRENCY_CODES.iter().find(|code| **code == word) {
+            return Some(code);
+        }
+    }
+    CURRENCY_SYMBOLS
+        .iter()
+        .find(|(symbol, _)| text.contains(symbol))
+        .map(|(_, code)| *code)
+}
+
+/// A space that may group thousands: plain, no-break, or narrow no-break.
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\u{a0}' | '\u{202f}')
+}
+
+/// The first amount in `text`, reading `,` and `.` as either the decimal
+/// or the thousands separator. When both appear, the last one is the
+/// decimal point; when only one does, it is a thousands separator if every
+/// group after it has exactly three digits (`1.299` is 1299).
+fn amount(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let negative = text[..start].trim_end().ends_with('-');
+    let digits: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, ',' | '.' | '\'') || is_space(*c))
+        .collect();
+    let digits = digits.trim_end_matches(|c: char| !c.is_ascii_digit());
+    // Spaces and apostrophes only ever group thousands (`1 299`, `1'299`).
+    let digits: String = digits
+        .chars()
+        .filter(|c| *c != '\'' && !is_space(*c))
+        .collect();
+
+    let last_comma = digits.rfind(',');
+    let last_point = digits.rfind('.');
+    let decimal = match (last_comma, last_point) {
+        (Some(comma), Some(point)) => Some(if comma > point { ',' } else { '.' }),
+        (Some(_), None) | (None, Some(_)) => {
+            let separator = if last_comma.is_some() { ',' } else { '.' };
+            let groups: Vec<&str> = digits.split(separator).skip(1).collect();
+            let thousands = groups.iter().all(|group| group.len() == 3);
+            (!thousands).then_some(separator)
+        }
+        (None, None) => None,
+    };
+
+    let number: String = digits
+        .chars()
+        .filter_map(|c| match c {
+            '0'..='9' => Some(c),
+            c if Some(c) == decimal => Some('.'),
+            _ => None,
+        })
+        .collect();
+    let value: f64 = number.parse().ok()?;
+    Some(if negative { -value } else { value })
+}
+
+/// `text` as `amount|CODE`, the amount with the currency's usual number of
+/// decimals. `None` when it has no amount or no recognisable currency.
+pub(crate) fn parse_currency(text: &str) -> Option<String> {
+    let code = currency_code(text)?;
+    let amount = amount(text)?;
+    let decimals = if ZERO_DECIMAL_CURRENCIES.contains(&code) {
+        0
+    } else {
+        2
+    };
+    Some(format!("{:.*}|{}", decimals, amount, code))
+}
+
+/// What a unit measures; only units of the same kind convert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Length,
+    Mass,
+    Volume,
+    Area,
+    Speed,
+    Temperature,
+}
+
+/// A unit: `value_in_base = (value + offset) * factor`, where the base unit
+/// is the metre, kilogram, litre, square metre, metre per second, or kelvin.
+struct Unit {
+    names: &'static [&'static str],
+    kind: Kind,
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(names: &'static [&'static str], kind: Kind, factor: f64) -> Unit {
+    Unit {
+        names,
+        kind,
+        factor,
+        offset: 0.0,
+    }
+}
+
+static UNITS: [Unit; 29] = [
+    unit(&["mm"], Kind::Length, 0.001),
+    unit(&["cm"], Kind::Length, 0.01),
+    unit(&["m"], Kind::Length, 1.0),
+    unit(&["km"], Kind::Length, 1000.0),
+    unit(&["in"], Kind::Length, 0.0254),
+    unit(&["ft"], Kind::Length, 0.3048),
+    unit(&["yd"], Kind::Length, 0.9144),
+    unit(&["mi"], Kind::Length, 1609.344),
+    unit(&["mg"], Kind::Mass, 0.000_001),
+    unit(&["g"], Kind::Mass, 0.001),
+    unit(&["kg"], Kind::Mass, 1.0),
+    unit(&["t"], Kind::Mass, 1000.0),
+    unit(&["oz"], Kind::Mass, 0.028_349_523_125),
+    unit(&["lb", "lbs"], Kind::Mass, 0.453_592_37),
+    unit(&["ml"], Kind::Volume, 0.001),
+    unit(&["l"], Kind::Volume, 1.0),
+    unit(&["floz", "fl oz"], Kind::Volume, 0.029_573_529_562_5),
+    unit(&["qt"], Kind::Volume, 0.946_352_946),
+    unit(&["gal"], Kind::Volume, 3.785_411_784),
+    unit(&["m2", "sqm"], Kind::Area, 1.0),
+    unit(&["ft2", "sqft"], Kind::Area, 0.092_903_04),
+    unit(&["acre"], Kind::Area, 4_046.856_422_4),
+    unit(&["ha"], Kind::Area, 10_000.0),
+    unit(&["m/s"], Kind::Speed, 1.0),
+    unit(&["km/h", "kph"], Kind::Speed, 1.0 / 3.6),
+    unit(&["mph"], Kind::Speed, 0.447_04),
+    Unit {
+        names: &["c", "°c"],
+        kind: Kind::Temperature,
+        factor: 1.0,
+        offset: 273.15,
+    },
+    Unit {
+        names: &["f", "°f"],
+        kind: Kind::Temperature,
+        factor: 5.0 / 9.0,
+        offset: 459.67,
+    },
+    unit(&["k"], Kind::Temperature, 1.0),
+];
+
+fn lookup(name: &str) -> Option<&'static Unit> {
+    let name = name.trim().to_lowercase();
+    UNITS
+        .iter()
+        .find(|unit| unit.names.contains(&name.as_str()))
+}
+
+/// Check that `from` and `to` are known units of the same kind.
+pub(crate) fn check_conversion(from: &str, to: &str) -> Result<(), String> {
+    let unit = |name: &str| lookup(name).ok_or_else(|| format!("unknown unit '{}'", name));
+    let (from_unit, to_unit) = (unit(from)?, unit(to)?);
+    if from_unit.kind != to_unit.kind {
+        return Err(format!("cannot convert '{}' to '{}'", from, to));
+    }
+    Ok(())
+}
+
+/// `value` converted from unit `from` to unit `to`, rounded to six decimal
+/// places with trailing zeros dropped. `None` for unknown or mismatched
+/// units.
+pub(crate) fn convert(value: f64, from: &str, to: &str) -> Option<String> {
+    let (from, to) = (lookup(from)?, lookup(to)?);
+    if from.kind != to.kind {
+        return None;
+    }
+    let base = (value + from.offset) * from.factor;
+    let converted = base / to.factor - to.offset;
+    let formatted = format!("{:.6}", converted);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    Some(match formatted {
+        "-0" => "0".to_string(),
+        other => other.to_string(),
+    })
+}
@@ -0,0 +1,136 @@
+//! Structured warnings.
+//!
+//! Everything the crate logs with `log::warn!` — an invalid selector, JSON
+//! that fails to parse, an index out of range — is also recorded as a
+//! [`Warning`], so a caller can react to it without scraping log output.
+//! In `chadselect`, `ChadSelect::take_warnings` drains the warnings an
+//! instance has gathered, and `QueryResult::warnings` holds those of one
+//! detailed query.
+//!
+//! Recording needs the `std` feature (captures are thread-local); without it
+//! warnings are only logged.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+
+/// What a [`Warning`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// A query, selector, pattern, text function, named query, or template
+    /// that could not be parsed or compiled. The query (or the offending
+    /// part) was skipped.
+    InvalidQuery,
+    /// A query compiled but failed while running (a JMESPath type error).
+    EvaluationFailed,
+    /// Loaded content that could not be parsed as its declared type.
+    InvalidContent,
+    /// Content that was not added: over a size limit, or unreadable.
+    ContentRejected,
+    /// A result or content index outside the available range.
+    IndexOutOfRange,
+    /// A query for an engine this build leaves out (see the `css`, `xpath`,
+    /// `json`, and `regex` Cargo features). The query was skipped.
+    UnsupportedEngine,
+}
+
+/// One recorded warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// What the warning is about.
+    pub kind: WarningKind,
+    /// The message that was logged.
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// One frame per [`Capture`] open on this thread, innermost last.
+    static FRAMES: RefCell<Vec<Vec<Warning>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Log a warning with `log::warn!` (under the calling module's target) and
+/// record it in the innermost open [`Capture`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! warning {
+    ($kind:ident, $($arg:tt)+) => {{
+        let message = $crate::__private::format!($($arg)+);
+        log::warn!("{}", message);
+        $crate::warnings::record($crate::warnings::WarningKind::$kind, message);
+    }};
+}
+#[doc(hidden)]
+pub use crate::warning;
+
+/// Record a warning in the innermost open [`Capture`]. Outside any capture
+/// (a free function called directly) the warning is only logged.
+#[doc(hidden)]
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
+pub fn record(kind: WarningKind, message: String) {
+    #[cfg(feature = "std")]
+    FRAMES.with(|frames| {
+        if let Some(frame) = frames.borrow_mut().last_mut() {
+            frame.push(Warning { kind, message });
+        }
+    });
+}
+
+/// Collects the warnings recorded on this thread while it is open. Captures
+/// nest: a finished capture hands its warnings on to the one around it.
+#[doc(hidden)]
+pub struct Capture {
+    finished: bool,
+}
+
+impl Capture {
+    pub fn begin() -> Self {
+        #[cfg(feature = "std")]
+        FRAMES.with(|frames| frames.borrow_mut().push(Vec::new()));
+        Capture { finished: false }
+    }
+
+    /// The warnings recorded since [`begin`](Capture::begin), and whether
+    /// this was the outermost capture.
+    pub fn finish(mut self) -> (Vec<Warning>, bool) {
+        self.finished = true;
+        pop_frame()
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        if !self.finished {
+            pop_frame();
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn pop_frame() -> (Vec<Warning>, bool) {
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let warnings = frames.pop().unwrap_or_default();
+        match frames.last_mut() {
+            Some(parent) => {
+                parent.extend(warnings.iter().cloned());
+                (warnings, false)
+            }
+            None => (warnings, true),
+        }
+    })
+}
+
+/// Without `std` nothing is captured.
+#[cfg(not(feature = "std"))]
+fn pop_frame() -> (Vec<Warning>, bool) {
+    (Vec::new(), true)
+}
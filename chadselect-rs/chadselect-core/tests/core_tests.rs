@@ -0,0 +1,58 @@
+use chadselect_core::engine::regex;
+use chadselect_core::functions::{apply_text_functions, try_parse_text_functions};
+use chadselect_core::query::{parse_query, split_alternatives, QueryType};
+use chadselect_core::warnings::Capture;
+use chadselect_core::WarningKind;
+
+#[test]
+fn prefixed_queries_parse_without_their_engines() {
+    assert!(matches!(parse_query("css:.price"), Ok(QueryType::CssSelector(s)) if s == ".price"));
+    assert!(matches!(
+        parse_query("json:items[0]"),
+        Ok(QueryType::JsonPath(_))
+    ));
+    assert!(matches!(
+        parse_query("glob:*error*"),
+        Ok(QueryType::Regex(_))
+    ));
+    assert_eq!(
+        split_alternatives("css:.sale || regex:\\$(\\d+)"),
+        ["css:.sale", "regex:\\$(\\d+)"]
+    );
+}
+
+#[test]
+fn regex_engine_runs_function_chains() {
+    let content = "total: $1,299.00\ntax: $104.00";
+    assert_eq!(
+        regex::process(r"\$([\d,.]+) >> to-number() >> sum()", content),
+        ["1403"]
+    );
+    assert_eq!(
+        regex::process(r"tax >> context('line') >> uppercase()", content),
+        ["TAX: $104.00"]
+    );
+}
+
+#[test]
+fn pipeline_runs_on_plain_strings() {
+    let functions = try_parse_text_functions("trim() >> unique() >> join(', ')").unwrap();
+    let values = vec![" a ".to_string(), "b".to_string(), "a".to_string()];
+    assert_eq!(apply_text_functions(values, &functions), ["a, b"]);
+}
+
+#[test]
+fn invalid_patterns_are_recorded_as_warnings() {
+    let capture = Capture::begin();
+    assert!(regex::process("(unclosed-core-test", "text").is_empty());
+    let (warnings, _) = capture.finish();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::InvalidQuery);
+}
+
+#[cfg(not(feature = "css"))]
+#[test]
+fn element_functions_need_the_css_feature() {
+    let err = try_parse_text_functions("closest('.card')").unwrap_err();
+    assert!(err.contains("css"), "{}", err);
+}
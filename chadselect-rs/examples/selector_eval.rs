@@ -17,14 +17,12 @@
 //!
 //! ## Data inputs (regenerate with `python3 eval/extract_selectors.py`)
 //!   * `eval/selectors.json`     — every unique css:/xpath: selector pulled
-//!                                  from `hermes_rust/src/crawlers/*.rs`
-//!                                  (string-literal lexing + `>>` pipe stripped).
+//!     from `hermes_rust/src/crawlers/*.rs` (string-literal lexing + `>>` pipe
+//!     stripped).
 //!   * `eval/corpus_vocab.json`  — the tag / class-token / data-attr / id /
-//!                                  label-text vocabulary mined from those
-//!                                  selectors, so the synthetic HTML "speaks
-//!                                  the same language" and selectors actually
-//!                                  match nodes (which is what triggers the
-//!                                  O(n²) predicate / following-sibling blowups).
+//!     label-text vocabulary mined from those selectors, so the synthetic HTML
+//!     "speaks the same language" and selectors actually match nodes (which is
+//!     what triggers the O(n²) predicate / following-sibling blowups).
 //!
 //! ## What it measures
 //!   1. **Warm eval** — doc parsed once, each selector run via the public
@@ -384,7 +382,7 @@ fn main() {
     }
     let suite_elapsed = suite_start.elapsed();
 
-    rows.sort_by(|a, b| b.1.per_eval.cmp(&a.1.per_eval));
+    rows.sort_by_key(|r| std::cmp::Reverse(r.1.per_eval));
 
     // ── slowest selectors ──
     println!("──────────────────────────────────────────────────────────────────────────");
@@ -417,7 +415,7 @@ fn main() {
         }
     }
     let mut feat_rows: Vec<_> = feat_total.into_iter().collect();
-    feat_rows.sort_by(|a, b| (b.1 .0 / b.1 .1 as u32).cmp(&(a.1 .0 / a.1 .1 as u32)));
+    feat_rows.sort_by_key(|r| std::cmp::Reverse(r.1 .0 / r.1 .1 as u32));
     println!("\n──────────────────────────────────────────────────────────────────────────");
     println!(" COST BY STRUCTURAL FEATURE (mean warm per-eval, selectors carrying it)");
     println!("──────────────────────────────────────────────────────────────────────────");
@@ -0,0 +1,141 @@
+//! Columnar output: an [`ExtractionSchema`]'s rows as an Arrow
+//! [`RecordBatch`], and (with the `parquet` feature) as a Parquet file.
+
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+
+use crate::schema::{ColumnType, ExtractionSchema};
+use crate::ChadSelect;
+
+/// The Arrow crates `record_batch` is built against, so callers can use the
+/// batch without adding matching dependencies of their own.
+pub use arrow_array;
+pub use arrow_schema;
+/// The Parquet crate `write_parquet` is built against.
+#[cfg(feature = "parquet")]
+pub use parquet;
+
+impl ColumnType {
+    fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Text => DataType::Utf8,
+            ColumnType::Integer => DataType::Int64,
+            ColumnType::Real => DataType::Float64,
+        }
+    }
+
+    /// An Arrow array of `values`, null where one is empty or has no number.
+    fn array<'a>(self, values: impl Iterator<Item = &'a str>) -> ArrayRef {
+        let present = values.map(|text| Some(text).filter(|text| !text.is_empty()));
+        match self {
+            ColumnType::Text => Arc::new(present.collect::<StringArray>()),
+            ColumnType::Integer => Arc::new(
+                present
+                    .map(|text| text.and_then(ColumnType::integer))
+                    .collect::<Int64Array>(),
+            ),
+            ColumnType::Real => Arc::new(
+                present
+                    .map(|text| text.and_then(ColumnType::real))
+                    .collect::<Float64Array>(),
+            ),
+        }
+    }
+}
+
+impl ExtractionSchema {
+    /// The Arrow schema of the batches built from this schema: one nullable
+    /// field per column, `Utf8`, `Int64`, or `Float64`.
+    pub fn arrow_schema(&self) -> Schema {
+        Schema::new(
+            self.columns
+                .iter()
+                .map(|c| Field::new(&c.name, c.column_type.data_type(), true))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl ChadSelect {
+    /// Extract `schema` from every content item into an Arrow
+    /// [`RecordBatch`], one row per item, ready for DataFusion, Polars, or
+    /// any other Arrow consumer.
+    ///
+    /// Rows are built as [`ExtractionSchema`] describes. An empty value, or
+    /// an `Integer`/`Real` one with no number in it, is null.
+    ///
+    /// Errors when the schema is empty.
+    ///
+    /// ```rust
+    /// use chadselect::arrow::arrow_array::{Array, Int64Array};
+    /// use chadselect::{ChadSelect, ColumnType, ExtractionSchema};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<h1>Civic</h1><span class="price">$28,500</span>"#.to_string());
+    /// cs.add_html(r#"<h1>Accord</h1>"#.to_string());
+    ///
+    /// let schema = ExtractionSchema::new()
+    ///     .column("title", ColumnType::Text, "css:h1")
+    ///     .column("price", ColumnType::Integer, "css:.price");
+    ///
+    /// let batch = cs.record_batch(&schema).unwrap();
+    /// assert_eq!(batch.num_rows(), 2);
+    ///
+    /// let prices = batch.column_by_name("price").unwrap();
+    /// let prices = prices.as_any().downcast_ref::<Int64Array>().unwrap();
+    /// assert_eq!(prices.value(0), 28500);
+    /// assert!(prices.is_null(1));
+    /// ```
+    pub fn record_batch(&self, schema: &ExtractionSchema) -> Result<RecordBatch, String> {
+        if schema.is_empty() {
+            return Err("extraction schema has no columns".to_string());
+        }
+        let rows = self.extract_rows(schema);
+        let arrays = schema
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                column
+                    .column_type
+                    .array(rows.iter().map(|(_, row)| row[i].as_str()))
+            })
+            .collect();
+        RecordBatch::try_new(Arc::new(schema.arrow_schema()), arrays)
+            .map_err(|e| format!("arrow: {}", e))
+    }
+
+    /// Extract `schema` as [`record_batch`](ChadSelect::record_batch) does
+    /// and write it to `writer` as a Parquet file. Returns the number of rows
+    /// written.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, ColumnType, ExtractionSchema};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html("<h1>Civic</h1>".to_string());
+    ///
+    /// let schema = ExtractionSchema::new().column("title", ColumnType::Text, "css:h1");
+    /// let mut file = Vec::new();
+    /// assert_eq!(cs.write_parquet(&mut file, &schema), Ok(1));
+    /// assert!(file.starts_with(b"PAR1"));
+    /// ```
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: std::io::Write + Send>(
+        &self,
+        writer: W,
+        schema: &ExtractionSchema,
+    ) -> Result<usize, String> {
+        use parquet::arrow::ArrowWriter;
+
+        let batch = self.record_batch(schema)?;
+        let parquet_error = |e: parquet::errors::ParquetError| format!("parquet: {}", e);
+        let mut writer =
+            ArrowWriter::try_new(writer, batch.schema(), None).map_err(parquet_error)?;
+        writer.write(&batch).map_err(parquet_error)?;
+        writer.close().map_err(parquet_error)?;
+        Ok(batch.num_rows())
+    }
+}
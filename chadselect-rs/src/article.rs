@@ -0,0 +1,323 @@
+//! Readability-style article extraction — see
+//! [`ChadSelect::extract_article`](crate::ChadSelect::extract_article).
+//!
+//! Paragraph-like blocks are scored by length and comma count; each score
+//! is credited to the block's parent in full and its grandparent in half.
+//! Containers start from a tag weight and a class/id weight (`article`,
+//! `content`, … up; `sidebar`, `comment`, … down), and the final score is
+//! discounted by the share of the container's text that sits in links.
+//! The best container, plus any sibling scoring close to it, is the body.
+
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+
+use crate::ChadSelect;
+
+/// Paragraph-like blocks whose text is scored.
+const SCORED_BLOCKS: &str = "p, pre, td, blockquote";
+
+/// Blocks whose text makes up the article's plain text.
+const TEXT_BLOCKS: [&str; 9] = ["p", "pre", "blockquote", "li", "h2", "h3", "h4", "h5", "h6"];
+
+/// Elements never part of an article body.
+const NON_CONTENT_TAGS: [&str; 10] = [
+    "script", "style", "noscript", "nav", "aside", "footer", "header", "form", "iframe", "button",
+];
+
+/// Blocks shorter than this (in characters) are not scored.
+const MIN_BLOCK_CHARS: usize = 25;
+
+/// The main article of a page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Article {
+    /// `og:title`, else the first `<h1>`, else `<title>`.
+    pub title: Option<String>,
+    /// `<meta name="author">`, else a `rel="author"` / `.byline` / `.author`
+    /// element's text.
+    pub byline: Option<String>,
+    /// `article:published_time`, else the first `<time datetime>`, as
+    /// written.
+    pub published: Option<String>,
+    /// The body's paragraphs, headings, and list items, one per paragraph
+    /// separated by blank lines.
+    pub text: String,
+    /// The outer HTML of the body's container elements.
+    pub html: String,
+}
+
+impl ChadSelect {
+    /// The main article of the HTML item at content `index`, found with a
+    /// readability-style content score rather than site-specific
+    /// selectors. `None` when `index` is out of range, the item is not HTML,
+    /// or no block of body text is found.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<html><head><title>Civic review | Cars</title>
+    ///   <meta name="author" content="Jane Doe"></head>
+    /// <body><nav><a href="/">Home</a> <a href="/reviews">Reviews</a></nav>
+    ///   <article><h1>2021 Civic review</h1>
+    ///     <p>The new Civic is quieter, roomier, and better built than before, and it still drives well.</p>
+    ///     <p>Fuel economy is excellent, with the base engine returning well over forty on the highway.</p>
+    ///   </article>
+    ///   <aside class="sidebar"><p>Subscribe to our newsletter for weekly deals and the latest reviews.</p></aside>
+    /// </body></html>"#.to_string());
+    ///
+    /// let article = cs.extract_article(0).unwrap();
+    /// assert_eq!(article.title.as_deref(), Some("2021 Civic review"));
+    /// assert_eq!(article.byline.as_deref(), Some("Jane Doe"));
+    /// assert!(article.text.starts_with("The new Civic is quieter"));
+    /// assert!(!article.text.contains("newsletter"));
+    /// ```
+    pub fn extract_article(&self, index: usize) -> Option<Article> {
+        let item = self.html_item(index)?;
+        let html = item.html();
+        let weights = ClassWeights::new();
+
+        let body = best_containers(&html, &weights)?;
+        let text = body_text(&body, &weights);
+        if text.is_empty() {
+            return None;
+        }
+        Some(Article {
+            title: title(&html),
+            byline: byline(&html),
+            published: published(&html),
+            text,
+            html: body.iter().map(|e| e.html()).collect::<Vec<_>>().join("\n"),
+        })
+    }
+}
+
+/// Class and id patterns for likely and unlikely content.
+struct ClassWeights {
+    positive: Regex,
+    negative: Regex,
+}
+
+impl ClassWeights {
+    fn new() -> Self {
+        ClassWeights {
+            positive: Regex::new(
+                r"(?i)article|body|content|entry|hentry|main|page|post|story|text|blog",
+            )
+            .expect("valid regex"),
+            negative: Regex::new(concat!(
+                r"(?i)comment|footer|footnote|sidebar|widget|sponsor|promo|related|share|",
+                r"social|banner|nav|menu|masthead|combx|\bads?\b|cookie|newsletter|subscribe",
+            ))
+            .expect("valid regex"),
+        }
+    }
+
+    /// +25 for a likely-content class or id, −25 for an unlikely one.
+    fn of(&self, element: &ElementRef) -> f64 {
+        let names = [element.value().attr("class"), element.value().attr("id")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut weight = 0.0;
+        if self.negative.is_match(&names) {
+            weight -= 25.0;
+        }
+        if self.positive.is_match(&names) {
+            weight += 25.0;
+        }
+        weight
+    }
+
+    /// Whether `element` or an ancestor is non-content by tag or class.
+    fn excluded(&self, element: &ElementRef) -> bool {
+        std::iter::once(*element)
+            .chain(element.ancestors().filter_map(ElementRef::wrap))
+            .any(|e| {
+                NON_CONTENT_TAGS.contains(&e.value().name())
+                    || (self.of(&e) < 0.0 && !matches!(e.value().name(), "body" | "html"))
+            })
+    }
+}
+
+/// The starting score of a container by tag.
+fn tag_weight(element: &ElementRef) -> f64 {
+    match element.value().name() {
+        "div" | "article" | "main" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    }
+}
+
+/// The highest-scoring container and its qualifying siblings, in document
+/// order.
+fn best_containers<'a>(html: &'a Html, weights: &ClassWeights) -> Option<Vec<ElementRef<'a>>> {
+    let blocks = Selector::parse(SCORED_BLOCKS).expect("valid selector");
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    // Scored containers in first-seen order, so ties go to the earliest.
+    let mut candidates: Vec<NodeId> = Vec::new();
+
+    for block in html.select(&blocks) {
+        if weights.excluded(&block) {
+            continue;
+        }
+        let text = collapsed_text(&block);
+        let length = text.chars().count();
+        if length < MIN_BLOCK_CHARS {
+            continue;
+        }
+        let score = 1.0 + text.matches(',').count() as f64 + (length as f64 / 100.0).min(3.0);
+
+        let ancestors = block.ancestors().filter_map(ElementRef::wrap).take(2);
+        for (level, ancestor) in ancestors.enumerate() {
+            let entry = scores.entry(ancestor.id()).or_insert_with(|| {
+                candidates.push(ancestor.id());
+                tag_weight(&ancestor) + weights.of(&ancestor)
+            });
+            *entry += if level == 0 { score } else { score / 2.0 };
+        }
+    }
+
+    let final_score = |id: NodeId| -> Option<(ElementRef<'a>, f64)> {
+        let element = ElementRef::wrap(html.tree.get(id)?)?;
+        Some((element, scores[&id] * (1.0 - link_density(&element))))
+    };
+    let (top, top_score) = candidates
+        .iter()
+        .filter_map(|id| final_score(*id))
+        .reduce(|best, next| if next.1 > best.1 { next } else { best })?;
+
+    // Siblings scoring close to the top container belong to the article
+    // too (a story split across several wrappers).
+    let threshold = (top_score * 0.2).max(10.0);
+    let Some(parent) = top.parent() else {
+        return Some(vec![top]);
+    };
+    let containers = parent
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|sibling| {
+            sibling.id() == top.id()
+                || (scores.contains_key(&sibling.id())
+                    && final_score(sibling.id()).is_some_and(|(_, s)| s >= threshold))
+        })
+        .collect();
+    Some(containers)
+}
+
+/// The share (0–1) of `element`'s text inside links.
+fn link_density(element: &ElementRef) -> f64 {
+    let total = collapsed_text(element).chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let links = Selector::parse("a").expect("valid selector");
+    let linked: usize = element
+        .select(&links)
+        .map(|a| collapsed_text(&a).chars().count())
+        .sum();
+    (linked as f64 / total as f64).min(1.0)
+}
+
+/// The text blocks of the body containers, one per paragraph.
+fn body_text(containers: &[ElementRef], weights: &ClassWeights) -> String {
+    let blocks = Selector::parse(&TEXT_BLOCKS.join(", ")).expect("valid selector");
+    let mut paragraphs = Vec::new();
+    for container in containers {
+        for block in container.select(&blocks) {
+            // Nested blocks (a <p> in a <li>) are covered by their outermost.
+            let nested = block
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .take_while(|a| a.id() != container.id())
+                .any(|a| TEXT_BLOCKS.contains(&a.value().name()));
+            if nested || weights.excluded(&block) {
+                continue;
+            }
+            let text = collapsed_text(&block);
+            if !text.is_empty() {
+                paragraphs.push(text);
+            }
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+fn collapsed_text(element: &ElementRef) -> String {
+    element
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The first non-empty of `selectors`, reading `attribute` (or the text
+/// when `None`).
+fn first_of(html: &Html, selectors: &[(&str, Option<&str>)]) -> Option<String> {
+    selectors.iter().find_map(|(selector, attribute)| {
+        let selector = Selector::parse(selector).expect("valid selector");
+        html.select(&selector).find_map(|element| {
+            let value = match attribute {
+                Some(name) => element
+                    .value()
+                    .attr(name)?
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                None => collapsed_text(&element),
+            };
+            (!value.is_empty()).then_some(value)
+        })
+    })
+}
+
+fn title(html: &Html) -> Option<String> {
+    first_of(
+        html,
+        &[
+            (r#"meta[property="og:title"]"#, Some("content")),
+            ("h1", None),
+            ("title", None),
+        ],
+    )
+}
+
+fn byline(html: &Html) -> Option<String> {
+    first_of(
+        html,
+        &[
+            (r#"meta[name="author"]"#, Some("content")),
+            (r#"meta[property="article:author"]"#, Some("content")),
+            (r#"[rel="author"]"#, None),
+            (r#"[itemprop="author"]"#, None),
+            (".byline", None),
+            (".author", None),
+        ],
+    )
+    .map(|byline| match byline.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("by ") => byline[3..].trim().to_string(),
+        _ => byline,
+    })
+}
+
+fn published(html: &Html) -> Option<String> {
+    first_of(
+        html,
+        &[
+            (
+                r#"meta[property="article:published_time"]"#,
+                Some("content"),
+            ),
+            (r#"meta[itemprop="datePublished"]"#, Some("content")),
+            ("time[datetime]", Some("datetime")),
+            (r#"meta[name="date"]"#, Some("content")),
+        ],
+    )
+}
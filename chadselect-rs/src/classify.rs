@@ -0,0 +1,196 @@
+//! Page-type classification from weighted indicator queries — see
+//! [`ChadSelect::classify`](crate::ChadSelect::classify).
+//!
+//! ```text
+//! Classifier                                       page with a captcha form
+//!   captcha  css:#challenge-form          0.9  ✓
+//!   captcha  regex:verify you are human   0.6  ✓   →  captcha  1 - 0.1 × 0.4 = 0.96
+//!   error    regex:Access Denied          0.8
+//!   error    json:error.message           0.9
+//! ```
+
+use crate::content::ContentItem;
+use crate::prepared::PreparedQuery;
+use crate::warnings::Capture;
+use crate::{default_valid, ChadSelect};
+
+/// Labels, each with the queries that indicate it and how strongly.
+///
+/// An indicator is present in a content item when its query yields a
+/// non-empty result there — or, for a `css:` alternative without a `>>`
+/// chain, when the selector matches any element, so `css:.captcha` finds an
+/// empty `<div class="captcha">`. A label's confidence combines the
+/// weights of its present indicators as independent evidence:
+/// `1 − Π(1 − weight)`. One indicator of weight `0.9` gives `0.9`; a second
+/// of `0.6` raises it to `0.96`.
+///
+/// ```rust
+/// use chadselect::Classifier;
+///
+/// let classifier = Classifier::new()
+///     .indicator("captcha", "css:.g-recaptcha, #challenge-form", 0.9)
+///     .indicator("captcha", "regex:(?i)verify you are human", 0.6)
+///     .indicator("error", "regex:Access Denied|503 Service Unavailable", 0.8)
+///     .indicator("error", "json:error.message", 0.9)
+///     .min_confidence(0.5);
+/// assert_eq!(classifier.labels(), ["captcha", "error"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Classifier {
+    indicators: Vec<Indicator>,
+    min_confidence: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Indicator {
+    label: String,
+    query: String,
+    weight: f64,
+}
+
+impl Classifier {
+    /// A classifier with no indicators.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `query` as evidence for `label` with `weight`, from `0.0`
+    /// (none) to `1.0` (conclusive); other weights are clamped.
+    pub fn indicator(mut self, label: &str, query: &str, weight: f64) -> Self {
+        self.indicators.push(Indicator {
+            label: label.to_string(),
+            query: query.to_string(),
+            weight: weight.clamp(0.0, 1.0),
+        });
+        self
+    }
+
+    /// Report no label unless the best one reaches `confidence`. Defaults
+    /// to `0.0`: any present indicator is enough.
+    pub fn min_confidence(mut self, confidence: f64) -> Self {
+        self.min_confidence = confidence;
+        self
+    }
+
+    /// The labels, in the order their first indicator was added.
+    pub fn labels(&self) -> Vec<&str> {
+        let mut labels: Vec<&str> = Vec::new();
+        for indicator in &self.indicators {
+            if !labels.contains(&indicator.label.as_str()) {
+                labels.push(&indicator.label);
+            }
+        }
+        labels
+    }
+}
+
+/// The label [`classify`](ChadSelect::classify) chose for a content item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    /// The label with the highest confidence; on a tie, the one added
+    /// first.
+    pub label: String,
+    /// The label's confidence, 0–1.
+    pub confidence: f64,
+    /// The queries of the label's indicators that were present, in the
+    /// order they were added.
+    pub indicators: Vec<String>,
+}
+
+impl ChadSelect {
+    /// Classify the content item at `index` — as a captcha, error, or
+    /// product page, say — by which of `classifier`'s indicator queries are
+    /// present in it (see [`Classifier`]).
+    ///
+    /// `None` when `index` is out of range, when no indicator is present,
+    /// or when the best label falls short of the classifier's
+    /// [`min_confidence`](Classifier::min_confidence). Indicator queries that
+    /// fail to parse are skipped with a warning.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, Classifier};
+    ///
+    /// let classifier = Classifier::new()
+    ///     .indicator("captcha", "css:#challenge-form", 0.9)
+    ///     .indicator("captcha", "regex:(?i)verify you are human", 0.6)
+    ///     .indicator("error", "regex:Access Denied", 0.8)
+    ///     .indicator("product", "css:.price", 0.7);
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<form id="challenge-form"></form><p>Verify you are human</p>"#.to_string());
+    /// cs.add_html(r#"<span class="price">$28,500</span>"#.to_string());
+    /// cs.add_html("<p>Nothing here</p>".to_string());
+    ///
+    /// let page = cs.classify(0, &classifier).unwrap();
+    /// assert_eq!(page.label, "captcha");
+    /// assert!((page.confidence - 0.96).abs() < 1e-9);
+    /// assert_eq!(cs.classify(1, &classifier).unwrap().label, "product");
+    /// assert!(cs.classify(2, &classifier).is_none());
+    /// ```
+    pub fn classify(&self, index: usize, classifier: &Classifier) -> Option<Classification> {
+        let item = self.content_list.get(index)?;
+        let capture = Capture::begin();
+
+        // (label, Π(1 − weight) over present indicators, their queries)
+        let mut scores: Vec<(&str, f64, Vec<String>)> = Vec::new();
+        for indicator in &classifier.indicators {
+            let position = match scores
+                .iter()
+                .position(|(label, ..)| *label == indicator.label)
+            {
+                Some(position) => position,
+                None => {
+                    scores.push((&indicator.label, 1.0, vec![]));
+                    scores.len() - 1
+                }
+            };
+            let present = self
+                .build_lenient(&indicator.query)
+                .is_some_and(|prepared| self.presence(&prepared, item).is_some());
+            if present {
+                let (_, absent, queries) = &mut scores[position];
+                *absent *= 1.0 - indicator.weight;
+                queries.push(indicator.query.clone());
+            }
+        }
+
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        let mut best: Option<Classification> = None;
+        for (label, absent, indicators) in scores {
+            let confidence = 1.0 - absent;
+            if indicators.is_empty() || best.as_ref().is_some_and(|b| b.confidence >= confidence) {
+                continue;
+            }
+            best = Some(Classification {
+                label: label.to_string(),
+                confidence,
+                indicators,
+            });
+        }
+        best.filter(|b| b.confidence >= classifier.min_confidence)
+    }
+
+    /// Whether `prepared` is present in `item`, and the value that shows it:
+    /// the first alternative that yields a non-empty result, or that is a
+    /// `css:` selector without a function chain matching an element. The
+    /// value is the first non-empty result, or empty for an element without
+    /// text. Shared by [`Classifier`] indicators and [`rule`](crate::RuleSet::rule)s.
+    pub(crate) fn presence(&self, prepared: &PreparedQuery, item: &ContentItem) -> Option<String> {
+        prepared.alternatives().iter().find_map(|alternative| {
+            let value = alternative
+                .run(item, &self.config)
+                .into_iter()
+                .find(|result| default_valid(result));
+            #[cfg(feature = "css")]
+            if value.is_none() && alternative.css().is_some() && alternative.functions().is_empty()
+            {
+                return alternative
+                    .matched_nodes(item, &self.config)
+                    .filter(|nodes| !nodes.is_empty())
+                    .map(|_| String::new());
+            }
+            value
+        })
+    }
+}
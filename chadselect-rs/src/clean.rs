@@ -0,0 +1,151 @@
+//! Boilerplate removal for article-like pages — see [`clean_html`] and
+//! [`ChadSelect::add_html_cleaned`](crate::ChadSelect::add_html_cleaned).
+
+use ego_tree::NodeId;
+use scraper::{Html, Node};
+
+use crate::content::ContentType;
+use crate::ChadSelect;
+
+/// Elements that are never page content.
+const BOILERPLATE_TAGS: [&str; 9] = [
+    "nav", "footer", "aside", "script", "style", "noscript", "iframe", "template", "dialog",
+];
+
+/// ARIA landmark roles for navigation and page chrome.
+const BOILERPLATE_ROLES: [&str; 6] = [
+    "navigation",
+    "banner",
+    "contentinfo",
+    "complementary",
+    "search",
+    "dialog",
+];
+
+/// Class and id words (split on `-`, `_`, and spaces) that mark a block as
+/// boilerplate: `site-nav`, `comment_list`, `ad-slot`, `share-buttons`, ….
+const BOILERPLATE_WORDS: [&str; 27] = [
+    "nav",
+    "navbar",
+    "navigation",
+    "menu",
+    "breadcrumb",
+    "breadcrumbs",
+    "footer",
+    "sidebar",
+    "comment",
+    "comments",
+    "ad",
+    "ads",
+    "advert",
+    "advertisement",
+    "sponsor",
+    "sponsored",
+    "promo",
+    "banner",
+    "cookie",
+    "cookies",
+    "consent",
+    "newsletter",
+    "subscribe",
+    "share",
+    "social",
+    "related",
+    "popup",
+];
+
+/// Elements kept even when their class looks like boilerplate (a `<body
+/// class="has-sidebar">` is still the page).
+const STRUCTURAL_TAGS: [&str; 5] = ["html", "head", "body", "main", "article"];
+
+/// Strip boilerplate from an HTML document and return it re-serialized.
+///
+/// Removed: `nav`, `footer`, `aside`, scripts (except JSON data such as
+/// JSON-LD), styles, `noscript`, frames, templates, and dialogs; elements
+/// with a navigation or page-chrome ARIA role; elements whose class or id
+/// names a menu, sidebar, comment section, ad, share bar, cookie banner, or
+/// similar; and HTML comments.
+///
+/// ```rust
+/// use chadselect::clean::clean_html;
+///
+/// let cleaned = clean_html(r#"<nav>Home | Cars</nav>
+///     <div class="post"><p>Civic review</p><div class="share-buttons">Tweet</div></div>
+///     <!-- tracking --><footer>© 2024</footer>"#);
+/// assert!(cleaned.contains("<p>Civic review</p>"));
+/// assert!(!cleaned.contains("Home"));
+/// assert!(!cleaned.contains("Tweet"));
+/// assert!(!cleaned.contains("tracking"));
+/// assert!(!cleaned.contains("2024"));
+/// ```
+pub fn clean_html(html: &str) -> String {
+    let mut document = Html::parse_document(html);
+    let doomed: Vec<NodeId> = document
+        .tree
+        .root()
+        .descendants()
+        .filter(|node| match node.value() {
+            Node::Comment(_) => true,
+            Node::Element(element) => is_boilerplate(element),
+            _ => false,
+        })
+        .map(|node| node.id())
+        .collect();
+
+    for id in doomed {
+        if let Some(mut node) = document.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+    document.html()
+}
+
+fn is_boilerplate(element: &scraper::node::Element) -> bool {
+    let name = element.name();
+    if name == "script" {
+        let kind = element
+            .attr("type")
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        return !kind.contains("json");
+    }
+    if BOILERPLATE_TAGS.contains(&name) {
+        return true;
+    }
+    if STRUCTURAL_TAGS.contains(&name) {
+        return false;
+    }
+    let role = element
+        .attr("role")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if BOILERPLATE_ROLES.contains(&role.trim()) {
+        return true;
+    }
+    [element.attr("class"), element.attr("id")]
+        .into_iter()
+        .flatten()
+        .flat_map(|names| names.split(|c: char| !c.is_ascii_alphanumeric()))
+        .any(|word| BOILERPLATE_WORDS.contains(&word.to_ascii_lowercase().as_str()))
+}
+
+impl ChadSelect {
+    /// Add HTML content with boilerplate (navigation, footers, sidebars,
+    /// comments, ads, share bars, …) stripped first, so broad regex and
+    /// text queries see mostly the page's own content. See [`clean_html`]
+    /// for what is removed; Regex runs on the cleaned markup too.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html_cleaned(r#"<nav><a href="/deals">$99 deals</a></nav>
+    ///     <article><p>Price: $28,500</p></article>
+    ///     <aside class="ad">Only $199/mo</aside>"#.to_string());
+    ///
+    /// assert_eq!(cs.query(-1, r"regex:\$[\d,]+"), vec!["$28,500"]);
+    /// ```
+    pub fn add_html_cleaned(&mut self, content: String) {
+        self.push(clean_html(&content), ContentType::Html);
+    }
+}
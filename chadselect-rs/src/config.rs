@@ -0,0 +1,362 @@
+//! Instance configuration and the [`ChadSelectBuilder`].
+
+use crate::library::QueryLibrary;
+use crate::query::PrefixMode;
+use crate::rewrite::RewriteRule;
+use crate::rules::RuleSet;
+use crate::ChadSelect;
+
+/// Options fixed when a [`ChadSelect`] is built.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Config {
+    /// Routing for queries without a recognised engine prefix.
+    pub(crate) prefix_mode: PrefixMode,
+    /// How CSS/XPath matches are flattened into text.
+    pub(crate) text: TextOptions,
+    /// Largest accepted content item, in bytes.
+    pub(crate) max_item_bytes: Option<usize>,
+    /// Largest total of all content items, in bytes.
+    pub(crate) max_total_bytes: Option<usize>,
+    /// What happens to content that exceeds a limit.
+    pub(crate) limit_policy: LimitPolicy,
+    /// Estimated bytes of parsed caches kept across all items.
+    pub(crate) cache_budget_bytes: Option<usize>,
+    /// Register the XPath 2.0 function shims when evaluating XPath.
+    pub(crate) xpath_shims: bool,
+    /// Engine rerouting applied to queries before they compile.
+    pub(crate) translation: Translation,
+    /// House-convention rewrites applied to queries before they parse.
+    pub(crate) rewrite_rules: Vec<RewriteRule>,
+    /// Most values a query returns before it is cut short.
+    pub(crate) max_results: Option<usize>,
+    /// Whether a query's `index` counts across all items or within each.
+    pub(crate) index_mode: IndexMode,
+    /// Parse `<template>` contents into the element's subtree.
+    pub(crate) hoist_templates: bool,
+}
+
+/// Which engine, if any, queries are rerouted to before they compile (see
+/// [`ChadSelectBuilder::translation`](ChadSelectBuilder::translation)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Translation {
+    /// Run every query on the engine its prefix names.
+    #[default]
+    Off,
+    /// Run `css:` queries on the XPath engine.
+    CssToXPath,
+    /// Run `xpath:` queries on the CSS engine.
+    XPathToCss,
+}
+
+/// What the `index` argument of [`query`](ChadSelect::query) and
+/// [`select`](ChadSelect::select) counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexMode {
+    /// Across the concatenated results of all content items: `0` is the
+    /// first match overall.
+    #[default]
+    Global,
+    /// Within each content item: `0` is the first match of every item, so a
+    /// query returns up to one result per item (items without that match
+    /// contribute nothing). `-1` still returns everything.
+    PerContent,
+}
+
+/// What happens to added content that exceeds a size limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitPolicy {
+    /// Refuse the new item (the `add_*` methods log a warning;
+    /// [`ChadSelect::try_add`] returns the error).
+    #[default]
+    Reject,
+    /// Cut the new item down to what fits, at a char boundary. Truncated
+    /// JSON will no longer parse; HTML parses leniently.
+    Truncate,
+    /// Remove the oldest items until the new one fits the total limit
+    /// (indices shift down). An item over the per-item limit is refused.
+    EvictOldest,
+}
+
+/// How a matched element is flattened into its result text.
+///
+/// The defaults join the element's text nodes with a single space and trim
+/// the result. XPath string values are a plain concatenation, so only
+/// `exclude_script_text` applies to them. For `<pre>`/code blocks use an
+/// empty separator and no trim to keep the original whitespace.
+///
+/// ```rust
+/// use chadselect::{ChadSelect, TextOptions};
+///
+/// let opts = TextOptions { separator: String::new(), trim: false, ..TextOptions::default() };
+/// let mut cs = ChadSelect::builder().text_options(opts).build();
+/// cs.add_html("<pre>  let x = <b>1</b>;\n</pre>".to_string());
+///
+/// assert_eq!(cs.select(0, "css:pre"), "  let x = 1;\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextOptions {
+    /// Inserted between an element's text nodes. Defaults to `" "`.
+    pub separator: String,
+    /// Trim surrounding whitespace from the joined text. Defaults to `true`.
+    pub trim: bool,
+    /// Include the `alt` (or, failing that, `title`) text of `<img>`,
+    /// `<area>`, and `<input>` elements inside the match. Defaults to `false`.
+    pub include_alt_text: bool,
+    /// Leave out the contents of `<script>`, `<style>`, `<noscript>`, and
+    /// `<template>` elements inside the match (a match that *is* one of those
+    /// elements still yields its text). Defaults to `false`.
+    pub exclude_script_text: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            separator: " ".to_string(),
+            trim: true,
+            include_alt_text: false,
+            exclude_script_text: false,
+        }
+    }
+}
+
+/// Builder for a configured [`ChadSelect`].
+///
+/// [`ChadSelect::new`] is equivalent to `ChadSelect::builder().build()`.
+///
+/// ```rust
+/// use chadselect::{ChadSelect, PrefixMode};
+///
+/// let mut cs = ChadSelect::builder().prefix_mode(PrefixMode::Strict).build();
+/// cs.add_html("<div>hello</div>".to_string());
+///
+/// // A typo'd prefix is rejected instead of running as a regex.
+/// assert!(cs.query(-1, "xpat://div").is_empty());
+/// assert_eq!(cs.select(0, "xpath://div"), "hello");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChadSelectBuilder {
+    config: Config,
+    library: QueryLibrary,
+    rules: RuleSet,
+}
+
+impl ChadSelectBuilder {
+    /// Create a builder with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how queries without a recognised engine prefix are routed.
+    /// Defaults to [`PrefixMode::Regex`]; [`PrefixMode::Auto`] classifies them
+    /// like the `auto:` prefix.
+    pub fn prefix_mode(mut self, mode: PrefixMode) -> Self {
+        self.config.prefix_mode = mode;
+        self
+    }
+
+    /// Set how CSS/XPath matches are flattened into text (see [`TextOptions`]).
+    /// Text pseudo-selectors always match against the default flattening.
+    pub fn text_options(mut self, options: TextOptions) -> Self {
+        self.config.text = options;
+        self
+    }
+
+    /// Refuse (or, per [`limit_policy`](Self::limit_policy), truncate) any
+    /// content item larger than `bytes`.
+    pub fn max_item_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_item_bytes = Some(bytes);
+        self
+    }
+
+    /// Cap the total size of all loaded content at `bytes`; what happens to
+    /// an item that would exceed it is set by
+    /// [`limit_policy`](Self::limit_policy).
+    pub fn max_total_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_total_bytes = Some(bytes);
+        self
+    }
+
+    /// Set what happens to content over a size limit. Defaults to
+    /// [`LimitPolicy::Reject`].
+    pub fn limit_policy(mut self, policy: LimitPolicy) -> Self {
+        self.config.limit_policy = policy;
+        self
+    }
+
+    /// Bound the parsed caches (HTML documents, JSON value trees) across all
+    /// items to an estimated `bytes`. After each query the least recently
+    /// used items' caches are dropped until the estimate fits; they re-parse
+    /// on next use. A budget below one document's parse re-parses every query.
+    pub fn cache_budget_bytes(mut self, bytes: usize) -> Self {
+        self.config.cache_budget_bytes = Some(bytes);
+        self
+    }
+
+    /// Make the XPath 2.0 functions `matches`, `replace`, `tokenize`,
+    /// `lower-case`, `upper-case`, and `ends-with` available to `xpath:`
+    /// queries, so selectors written for lxml or Saxon port over with fewer
+    /// edits. Off by default.
+    ///
+    /// The regex functions take plain-text patterns only: a string literal
+    /// with escaped metacharacters, optionally anchored with `^` and `$`,
+    /// with the `i` (case-insensitive) and `q` (literal) flags; `tokenize`
+    /// also splits on `\s+`. Any other pattern is refused when the query is
+    /// compiled. Case mapping covers ASCII and Latin-1 letters.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::builder().xpath_shims(true).build();
+    /// cs.add_html(r#"<a href="/spec.PDF">Spec</a> <a href="/faq.html">FAQ</a>
+    ///     <p class="price  sale">Price: $28,500</p>"#.to_string());
+    ///
+    /// assert_eq!(cs.query(-1, r"xpath://a[matches(@href, '\.pdf$', 'i')]"), vec!["Spec"]);
+    /// assert_eq!(cs.select(0, "xpath:replace(//p, '^Price: ', '')"), "$28,500");
+    /// assert_eq!(cs.select(0, r"xpath://p[tokenize(@class, '\s+') = 'sale']/@class"), "price  sale");
+    /// assert_eq!(cs.select(0, "xpath:upper-case(//a[ends-with(@href, '.html')])"), "FAQ");
+    /// ```
+    pub fn xpath_shims(mut self, enabled: bool) -> Self {
+        self.config.xpath_shims = enabled;
+        self
+    }
+
+    /// Run `css:` queries on the XPath engine or `xpath:` queries on the CSS
+    /// engine, translated with [`css_to_xpath`](crate::translate::css_to_xpath)
+    /// or [`xpath_to_css`](crate::translate::xpath_to_css) — for a construct
+    /// only the other engine supports, or to keep one engine's caches warm.
+    /// A query that does not translate runs on its own engine. Off by
+    /// default.
+    ///
+    /// A CSS `>> get-attr('x')` directly after the selector becomes an
+    /// XPath `/@x` step and back; a chain that reads attributes later keeps
+    /// its query on CSS.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, Translation};
+    ///
+    /// let mut cs = ChadSelect::builder().translation(Translation::CssToXPath).build();
+    /// cs.add_html(r#"<a class="next" href="/p/2">Next</a>"#.to_string());
+    ///
+    /// let r = cs.query_detailed(0, "css:a.next >> get-attr('href')");
+    /// assert_eq!(r.results, vec!["/p/2"]);
+    /// assert_eq!(r.engine(), Some("xpath"));
+    /// ```
+    pub fn translation(mut self, translation: Translation) -> Self {
+        self.config.translation = translation;
+        self
+    }
+
+    /// Add a [`RewriteRule`] applied to every query before it is parsed —
+    /// e.g. append `>> normalize-space()` to all CSS and XPath queries, or
+    /// route unprefixed queries to `json:` while only JSON is loaded. Rules
+    /// accumulate across calls.
+    pub fn rewrite_rule(mut self, rule: RewriteRule) -> Self {
+        self.config.rewrite_rules.push(rule);
+        self
+    }
+
+    /// Cut every query off at `max` results, so a runaway query
+    /// (`regex:.` over a large document) cannot exhaust memory.
+    /// [`QueryResult::truncated`](crate::QueryResult::truncated) reports
+    /// when it happened. Unlimited by default.
+    ///
+    /// The cap applies before `index` selection, across all content items.
+    /// Without a `>>` chain the engines stop collecting at the cap; a chain
+    /// sees every match of an item (it may `count()` or `join()` them), and
+    /// only its output is cut.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::builder().max_results(3).build();
+    /// cs.add_text("abcdefgh".to_string());
+    ///
+    /// let r = cs.query_detailed(-1, "regex:.");
+    /// assert_eq!(r.results, vec!["a", "b", "c"]);
+    /// assert!(r.truncated);
+    /// assert_eq!(cs.select(0, "regex:. >> count()"), "8");
+    /// ```
+    pub fn max_results(mut self, max: usize) -> Self {
+        self.config.max_results = Some(max);
+        self
+    }
+
+    /// Set what `index` counts. Defaults to [`IndexMode::Global`];
+    /// [`IndexMode::PerContent`] picks the match at `index` from each
+    /// content item — "the first price on every page" for batch extraction.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, IndexMode};
+    ///
+    /// let mut cs = ChadSelect::builder().index_mode(IndexMode::PerContent).build();
+    /// cs.add_html("<h1>Civic</h1><h1>ad</h1>".to_string());
+    /// cs.add_html("<h1>Accord</h1>".to_string());
+    ///
+    /// assert_eq!(cs.query(0, "css:h1"), vec!["Civic", "Accord"]);
+    /// assert_eq!(cs.query(1, "css:h1"), vec!["ad"]);
+    /// assert_eq!(cs.query(-1, "css:h1").len(), 3);
+    /// ```
+    pub fn index_mode(mut self, mode: IndexMode) -> Self {
+        self.config.index_mode = mode;
+        self
+    }
+
+    /// Parse the contents of `<template>` elements into the queryable tree.
+    /// HTML keeps a template's contents in a separate document fragment, so
+    /// `css:template .price`, `xpath://template//span`, and any selector
+    /// that crosses the template boundary find nothing; component-rendered
+    /// pages often ship their data that way. With hoisting the contents are
+    /// ordinary descendants of the `<template>` element. Off by default.
+    ///
+    /// Declarative shadow roots (`<template shadowrootmode="open">`) are
+    /// parsed into their host element either way.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::builder().hoist_templates(true).build();
+    /// cs.add_html(r#"<template id="car"><span class="price">$28,500</span></template>"#
+    ///     .to_string());
+    ///
+    /// assert_eq!(cs.select(0, "css:#car .price"), "$28,500");
+    /// assert_eq!(cs.select(0, "xpath://template[@id='car']/span/text()"), "$28,500");
+    /// ```
+    pub fn hoist_templates(mut self, enabled: bool) -> Self {
+        self.config.hoist_templates = enabled;
+        self
+    }
+
+    /// Start with the named queries in `library` (see [`QueryLibrary`]).
+    pub fn library(mut self, library: QueryLibrary) -> Self {
+        self.library = library;
+        self
+    }
+
+    /// Start with the rules [`evaluate_rules`](ChadSelect::evaluate_rules)
+    /// runs (see [`RuleSet`]).
+    pub fn rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Register the regex `pattern` under `name`, to be run as `pat:name`
+    /// (see [`QueryLibrary::define_pattern`]). Call after
+    /// [`library`](ChadSelectBuilder::library), which replaces the library.
+    pub fn pattern(mut self, name: &str, pattern: &str) -> Self {
+        self.library.define_pattern(name, pattern);
+        self
+    }
+
+    /// Build the configured, empty [`ChadSelect`].
+    pub fn build(self) -> ChadSelect {
+        ChadSelect {
+            content_list: Vec::new(),
+            config: self.config,
+            library: self.library,
+            rules: self.rules,
+            clock: Default::default(),
+            warnings: Default::default(),
+            watches: Default::default(),
+        }
+    }
+}
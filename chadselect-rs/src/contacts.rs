@@ -0,0 +1,83 @@
+//! Email address and phone number harvesting — see
+//! [`ChadSelect::extract_contacts`](crate::ChadSelect::extract_contacts) and
+//! the `extract-emails()` / `extract-phones('US')` pipe functions.
+//!
+//! Phone numbers are parsed and validated with libphonenumber's metadata,
+//! behind the `phone` feature.
+
+#[cfg(feature = "css")]
+use scraper::Selector;
+
+use chadselect_core::contacts::emails;
+#[cfg(feature = "phone")]
+use chadselect_core::contacts::phones;
+
+#[cfg(feature = "css")]
+use crate::content::{visible_text, ContentType};
+use crate::ChadSelect;
+
+/// The contact details found in a content item.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Contacts {
+    /// Email addresses, in order of first appearance, without duplicates
+    /// (compared case-insensitively).
+    pub emails: Vec<String>,
+    /// Valid phone numbers in E.164 form (`+12125550100`), in order of
+    /// first appearance, without duplicates.
+    #[cfg(feature = "phone")]
+    pub phones: Vec<String>,
+}
+
+impl ChadSelect {
+    /// The email addresses and phone numbers in the content item at
+    /// `index`. Empty when `index` is out of range.
+    ///
+    /// HTML and Markdown are searched in their visible text and in their
+    /// `mailto:` and `tel:` links; other content in its raw text. Phone
+    /// numbers (with the `phone` feature) are only found in international
+    /// form (`+1 212 555 0100`); use `>> extract-phones('US')` to read the
+    /// national numbers of a region.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<p>Questions? Write to Sales@Example.com or
+    ///     <a href="mailto:sales@example.com">sales@example.com</a>.</p>"#.to_string());
+    ///
+    /// let contacts = cs.extract_contacts(0);
+    /// assert_eq!(contacts.emails, ["Sales@Example.com"]);
+    /// ```
+    pub fn extract_contacts(&self, index: usize) -> Contacts {
+        let Some(item) = self.content_list.get(index) else {
+            return Contacts::default();
+        };
+
+        let text = match item.content_type {
+            #[cfg(feature = "css")]
+            ContentType::Html | ContentType::Markdown => {
+                let html = item.html();
+                let mut text = visible_text(&html, usize::MAX);
+                let links = Selector::parse("a[href], area[href]").expect("valid selector");
+                for element in html.select(&links) {
+                    let href = element.value().attr("href").unwrap_or_default().trim();
+                    let Some((scheme, address)) = href.split_once(':') else {
+                        continue;
+                    };
+                    if scheme.eq_ignore_ascii_case("mailto") || scheme.eq_ignore_ascii_case("tel") {
+                        text.push('\n');
+                        text.push_str(address);
+                    }
+                }
+                text
+            }
+            _ => item.content.clone(),
+        };
+
+        Contacts {
+            emails: emails(&text),
+            #[cfg(feature = "phone")]
+            phones: phones(&text, None),
+        }
+    }
+}
@@ -1,11 +1,33 @@
 //! Content types and content item storage with lazy-parsed caching.
 
-use scraper::Html;
+#[cfg(feature = "css")]
+use ego_tree::NodeId;
+#[cfg(feature = "css")]
+use html5ever::driver;
+#[cfg(feature = "css")]
+use html5ever::tendril::{StrTendril, TendrilSink};
+#[cfg(feature = "css")]
+use pulldown_cmark::{Options, Parser};
+#[cfg(feature = "css")]
+use scraper::{Html, HtmlTreeSink, Node};
+#[cfg(any(feature = "css", feature = "regex"))]
+use std::borrow::Cow;
+use std::cell::Cell;
+#[cfg(any(feature = "css", feature = "json"))]
 use std::cell::RefCell;
+#[cfg(feature = "css")]
 use std::collections::HashMap;
+#[cfg(feature = "css")]
+use std::ops::ControlFlow;
+#[cfg(feature = "css")]
 use std::rc::Rc;
 
-use crate::engine::xnode::{build_order, OrderMap};
+#[cfg(feature = "css")]
+use crate::dom::{DomElement, Selections};
+#[cfg(feature = "css")]
+use crate::engine::{build_order, OrderMap};
+#[cfg(feature = "json")]
+use crate::formats;
 
 /// Content type enumeration for explicit content specification.
 #[derive(Debug, Clone, PartialEq)]
@@ -16,8 +38,134 @@ pub enum ContentType {
     Html,
     /// JSON content — compatible with Regex and JMESPath.
     Json,
+    /// Markdown source — rendered to HTML for CSS and XPath; Regex runs on
+    /// the raw source.
+    Markdown,
+    /// TOML source — converted to JSON for JMESPath; Regex runs on the raw
+    /// source.
+    #[cfg(feature = "toml")]
+    Toml,
+    /// INI source — converted to JSON for JMESPath; Regex runs on the raw
+    /// source.
+    Ini,
+    /// Raw bytes (see [`add_bytes`](crate::ChadSelect::add_bytes)), held one
+    /// character per byte so Regex sees ASCII runs as text. `bytes:` and
+    /// `hex:` queries match the original bytes.
+    Binary,
 }
 
+impl ContentType {
+    /// The content type for a `Content-Type` header value, sniffing the body
+    /// when the header is missing or says nothing useful.
+    #[cfg(any(feature = "fetch", feature = "har", feature = "warc"))]
+    pub(crate) fn from_mime(header: Option<&str>, body: &[u8]) -> ContentType {
+        let mime = header
+            .and_then(|h| h.split(';').next())
+            .map(|m| m.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match mime.as_str() {
+            "text/html" | "application/xhtml+xml" => ContentType::Html,
+            "application/json" | "text/json" => ContentType::Json,
+            m if m.ends_with("+json") => ContentType::Json,
+            "text/markdown" | "text/x-markdown" => ContentType::Markdown,
+            #[cfg(feature = "toml")]
+            "application/toml" => ContentType::Toml,
+            "" | "application/octet-stream" => {
+                let start = body.iter().find(|b| !b.is_ascii_whitespace());
+                match start {
+                    Some(b'{' | b'[') => ContentType::Json,
+                    Some(b'<') => ContentType::Html,
+                    _ => ContentType::Text,
+                }
+            }
+            _ => ContentType::Text,
+        }
+    }
+
+    /// Whether `json:` queries run against this type, natively or converted.
+    pub(crate) fn is_json_like(&self) -> bool {
+        match self {
+            ContentType::Json | ContentType::Ini => true,
+            #[cfg(feature = "toml")]
+            ContentType::Toml => true,
+            _ => false,
+        }
+    }
+}
+
+/// Bytes of markup fed to the HTML parser between progress reports.
+#[cfg(feature = "css")]
+const PARSE_CHUNK_BYTES: usize = 1 << 20;
+
+/// How far a parse has got, reported by
+/// [`add_html_with_progress`](crate::ChadSelect::add_html_with_progress) and
+/// [`parse_all`](crate::ChadSelect::parse_all).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseProgress {
+    /// Source bytes parsed so far.
+    pub bytes_parsed: usize,
+    /// Source bytes to parse in all.
+    pub total_bytes: usize,
+}
+
+impl ParseProgress {
+    /// The fraction parsed, from `0.0` to `1.0` (`1.0` when there is
+    /// nothing to parse).
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes == 0 {
+            1.0
+        } else {
+            self.bytes_parsed as f64 / self.total_bytes as f64
+        }
+    }
+}
+
+/// Snapshot of a [`ChadSelect`](crate::ChadSelect)'s memory usage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    /// Number of loaded content items.
+    pub items: usize,
+    /// Total bytes of raw content.
+    pub content_bytes: usize,
+    /// Items holding a parsed document or value tree.
+    pub parsed_items: usize,
+    /// Estimated bytes held by parsed caches.
+    pub cached_bytes: usize,
+}
+
+/// Whether a MIME type names binary content no engine can query.
+#[cfg(any(feature = "fetch", feature = "har", feature = "warc"))]
+pub(crate) fn is_binary_mime(mime: &str) -> bool {
+    let mime = mime
+        .split(';')
+        .next()
+        .unwrap_or(mime)
+        .trim()
+        .to_ascii_lowercase();
+    let (kind, subtype) = mime.split_once('/').unwrap_or((&mime, ""));
+    match kind {
+        "image" => subtype != "svg+xml",
+        "audio" | "video" | "font" => true,
+        "application" => {
+            matches!(
+                subtype,
+                "pdf" | "zip" | "gzip" | "wasm" | "font-woff" | "x-font-woff" | "vnd.ms-fontobject"
+            ) || subtype.starts_with("x-protobuf")
+        }
+        _ => false,
+    }
+}
+
+/// Estimated size of a parsed HTML document (DOM plus order map) per byte of
+/// source.
+#[cfg(feature = "css")]
+const DOCUMENT_BYTES_PER_SOURCE_BYTE: usize = 4;
+
+/// Estimated size of a JMESPath value tree per byte of source.
+#[cfg(feature = "json")]
+const VALUE_BYTES_PER_SOURCE_BYTE: usize = 3;
+
 /// A single content item with its type and lazily-cached parsed representations.
 ///
 /// Parsed documents are created on first access and reused for subsequent queries,
@@ -28,25 +176,60 @@ pub struct ContentItem {
     pub content: String,
     /// Declared content type.
     pub content_type: ContentType,
-    /// Lazily built JMESPath value tree (`Rc<jmespath::Variable>`), cached **per
+    /// Where the content came from (the path for
+    /// [`add_file`](crate::ChadSelect::add_file), the URL for `add_url`),
+    /// when known.
+    pub source: Option<String>,
+    /// The character encoding the content was decoded from (set by
+    /// `add_url`), when known. Content added as a `String` is UTF-8.
+    pub charset: Option<String>,
+    /// Whether HTML content is a fragment (added with
+    /// [`add_html_fragment`](crate::ChadSelect::add_html_fragment)) rather
+    /// than a document: it is parsed without the implied `<html>`/`<body>`
+    /// wrapper, so its top-level nodes are children of the root.
+    pub fragment: bool,
+    /// The 1-based page this item holds, for content split from a paged
+    /// document (set by `add_pdf`).
+    pub page: Option<usize>,
+    /// The workbook sheet this item holds (set by `add_xlsx`).
+    pub sheet: Option<String>,
+    /// Whether `<template>` contents are parsed into the element's subtree
+    /// (see [`hoist_templates`](crate::ChadSelectBuilder::hoist_templates)).
+    pub(crate) hoist_templates: bool,
+    /// Lazily built JMESPath value tree (`Arc<jmespath::Variable>`), cached **per
     /// document**. `jmespath::Expression::search` converts its input into this
     /// tree on *every* call; the conversion is a full serde walk of the whole
     /// document (one `Rc` + `String` + BTree node per value). Building it once
     /// here and evaluating every `json:` selector against the cached tree (via
     /// `search_cached`) removes that per-query whole-document conversion — the
     /// dominant allocation source on JSON-heavy pages.
+    #[cfg(feature = "json")]
     pub(crate) jmespath_value: RefCell<Option<jmespath::Rcvar>>,
     /// Lazily parsed HTML document (via `scraper`/html5ever), **shared** by
     /// both the CSS and XPath engines — the HTML is parsed exactly once.
+    #[cfg(feature = "css")]
     pub(crate) html_document: RefCell<Option<Rc<Html>>>,
     /// Lazily built document-order map for the XPath engine, cached **per
     /// document**. The map depends only on the parsed tree, not the query, so
     /// building it once here — rather than once per `evaluate()` call — removes
     /// an O(n) whole-document pass from every one of the hundreds of XPath
     /// selectors the fleet runs against a single page.
+    #[cfg(feature = "css")]
     pub(crate) html_order: RefCell<Option<Rc<OrderMap>>>,
+    /// Lazily built structured-data document (JSON-LD, microdata, RDFa,
+    /// OpenGraph) for the `structured:` engine, as a JMESPath value tree.
+    #[cfg(all(feature = "css", feature = "json"))]
+    pub(crate) structured_value: RefCell<Option<jmespath::Rcvar>>,
     /// Element-text cache for CSS text pseudo-selectors: selector → Vec<(element_index, text)>.
+    #[cfg(feature = "css")]
     pub(crate) element_text_cache: RefCell<HashMap<String, Vec<(usize, String)>>>,
+    /// Elements matched by [`query_dom`](crate::ChadSelect::query_dom), by
+    /// backend and selector. A backend's document may borrow the
+    /// markup, so the selections are cached rather than the document.
+    #[cfg(feature = "css")]
+    pub(crate) dom_selections: RefCell<Selections>,
+    /// Query tick at which a cache of this item was last used (cache LRU).
+    pub(crate) last_used: Cell<u64>,
 }
 
 impl ContentItem {
@@ -55,10 +238,247 @@ impl ContentItem {
         Self {
             content,
             content_type,
+            source: None,
+            charset: None,
+            fragment: false,
+            page: None,
+            sheet: None,
+            hoist_templates: false,
+            #[cfg(feature = "json")]
             jmespath_value: RefCell::new(None),
+            #[cfg(feature = "css")]
             html_document: RefCell::new(None),
+            #[cfg(feature = "css")]
             html_order: RefCell::new(None),
+            #[cfg(all(feature = "css", feature = "json"))]
+            structured_value: RefCell::new(None),
+            #[cfg(feature = "css")]
             element_text_cache: RefCell::new(HashMap::new()),
+            #[cfg(feature = "css")]
+            dom_selections: RefCell::new(HashMap::new()),
+            last_used: Cell::new(0),
+        }
+    }
+
+    /// The size of the content in bytes, as loaded: the original byte count
+    /// of binary content (held one character per byte), the UTF-8 length of
+    /// anything else. This is what the size limits count.
+    pub fn size(&self) -> usize {
+        size_of(&self.content, &self.content_type)
+    }
+
+    /// The content as bytes: the original bytes of binary content, the UTF-8
+    /// encoding of anything else.
+    #[cfg(feature = "regex")]
+    pub(crate) fn bytes(&self) -> Cow<'_, [u8]> {
+        match self.content_type {
+            ContentType::Binary => Cow::Owned(self.content.chars().map(|c| c as u8).collect()),
+            _ => Cow::Borrowed(self.content.as_bytes()),
+        }
+    }
+
+    /// Whether any lazily parsed representation is currently cached.
+    pub(crate) fn is_parsed(&self) -> bool {
+        #[cfg(feature = "css")]
+        if self.html_document.borrow().is_some() {
+            return true;
+        }
+        #[cfg(feature = "json")]
+        if self.jmespath_value.borrow().is_some() {
+            return true;
+        }
+        #[cfg(all(feature = "css", feature = "json"))]
+        if self.structured_value.borrow().is_some() {
+            return true;
+        }
+        #[cfg(feature = "css")]
+        if !self.dom_selections.borrow().is_empty() {
+            return true;
+        }
+        false
+    }
+
+    /// Estimated bytes held by the cached parsed representations.
+    pub(crate) fn cached_bytes(&self) -> usize {
+        // Without the `css` and `json` features there is nothing to cache.
+        #[allow(unused_mut, unused_variables)]
+        let (source, mut bytes) = (self.content.len(), 0);
+        #[cfg(feature = "css")]
+        if self.html_document.borrow().is_some() {
+            bytes += source * DOCUMENT_BYTES_PER_SOURCE_BYTE;
+        }
+        #[cfg(feature = "json")]
+        if self.jmespath_value.borrow().is_some() {
+            bytes += source * VALUE_BYTES_PER_SOURCE_BYTE;
+        }
+        #[cfg(feature = "css")]
+        {
+            let texts = self.element_text_cache.borrow();
+            bytes += texts.values().flatten().map(|(_, text)| text.len()).sum::<usize>();
+            let selections = self.dom_selections.borrow();
+            bytes += selections
+                .values()
+                .flat_map(|elements| elements.iter())
+                .map(DomElement::heap_bytes)
+                .sum::<usize>();
+        }
+        bytes
+    }
+
+    /// Release every cached parsed representation.
+    pub(crate) fn drop_caches(&self) {
+        #[cfg(feature = "json")]
+        self.jmespath_value.take();
+        #[cfg(feature = "css")]
+        {
+            self.html_document.take();
+            self.html_order.take();
+            self.element_text_cache.borrow_mut().clear();
+            self.dom_selections.borrow_mut().clear();
+        }
+        #[cfg(all(feature = "css", feature = "json"))]
+        self.structured_value.take();
+    }
+
+    /// Extend the content with `more`. Caches built from the old text are
+    /// dropped — the HTML document, XPath order map, element texts, and
+    /// `query_dom` selections of HTML and Markdown, the value tree of JSON,
+    /// TOML, and INI — and rebuild on the next query; text has none.
+    pub(crate) fn append(&mut self, more: &str) {
+        self.content.push_str(more);
+        self.drop_caches();
+    }
+
+    /// Append the JSON value `line` (one line of a JSON Lines stream) as the
+    /// last element of this item, a JSON array. The text and any cached
+    /// value tree are extended in place, so each line costs its own size
+    /// rather than the whole array's.
+    pub(crate) fn append_json_element(&mut self, line: &str) -> Result<(), String> {
+        if self.content_type != ContentType::Json {
+            return Err(format!(
+                "{:?} content is not a JSON array",
+                self.content_type
+            ));
+        }
+        let value: serde_json::Value =
+            serde_json::from_str(line).map_err(|e| format!("invalid JSON line: {}", e))?;
+        let end = self.content.trim_end().len();
+        if !self.content.trim_start().starts_with('[') || !self.content[..end].ends_with(']') {
+            return Err("JSON content is not an array".to_string());
+        }
+        let empty = self.content[..end - 1].trim() == "[";
+        self.content.truncate(end - 1);
+        if !empty {
+            self.content.push(',');
+        }
+        self.content.push_str(line.trim());
+        self.content.push(']');
+
+        #[cfg(feature = "json")]
+        {
+            let mut cached = self.jmespath_value.borrow_mut();
+            let extended = match cached.as_mut().map(jmespath::Rcvar::make_mut) {
+                Some(jmespath::Variable::Array(items)) => jmespath::Variable::try_from(value)
+                    .map(|element| items.push(jmespath::Rcvar::new(element)))
+                    .is_ok(),
+                _ => false,
+            };
+            if !extended {
+                cached.take();
+            }
+        }
+        #[cfg(not(feature = "json"))]
+        drop(value);
+        Ok(())
+    }
+
+    /// Convert the content into a JMESPath value tree: JSON is parsed
+    /// directly, TOML and INI through their JSON form (see [`formats`]).
+    #[cfg(feature = "json")]
+    pub(crate) fn to_jmespath(&self) -> Result<jmespath::Variable, String> {
+        let converted = match self.content_type {
+            #[cfg(feature = "toml")]
+            ContentType::Toml => formats::toml_to_json(&self.content)?,
+            ContentType::Ini => formats::ini_to_json(&self.content)?,
+            _ => return jmespath::Variable::from_json(&self.content),
+        };
+        jmespath::Variable::from_serializable(converted).map_err(|e| e.to_string())
+    }
+
+    /// Get the cached JMESPath value tree, converting the content on first
+    /// use.
+    #[cfg(feature = "json")]
+    pub(crate) fn jmespath(&self) -> Result<jmespath::Rcvar, String> {
+        let mut value = self.jmespath_value.borrow_mut();
+        if value.is_none() {
+            // Parse the raw content straight into the JMESPath tree (one serde
+            // pass for JSON, skipping the intermediate `serde_json::Value`).
+            *value = Some(jmespath::Rcvar::new(self.to_jmespath()?));
+        }
+        Ok(value.as_ref().unwrap().clone())
+    }
+
+    /// Build the parsed representation its engines query — the HTML document
+    /// and XPath order map for HTML and Markdown, the JMESPath value tree for
+    /// JSON, TOML, and INI. Plain text and bytes have nothing to parse.
+    pub(crate) fn warm(&self) -> Result<(), String> {
+        #[cfg(feature = "css")]
+        if matches!(self.content_type, ContentType::Html | ContentType::Markdown) {
+            self.html_with_order();
+        }
+        #[cfg(feature = "json")]
+        if self.content_type.is_json_like() {
+            self.jmespath()?;
+        }
+        Ok(())
+    }
+
+    /// Parse the HTML document (rendering Markdown first) in chunks, calling
+    /// `on_chunk` with the source bytes parsed after each one. A `Break`
+    /// abandons the parse and caches nothing.
+    #[cfg(feature = "css")]
+    pub(crate) fn parse_html_chunked(
+        &self,
+        mut on_chunk: impl FnMut(usize) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        if self.fragment || self.html_document.borrow().is_some() {
+            // Fragments are snippets; they are parsed in one go.
+            self.html();
+            return on_chunk(self.content.len());
+        }
+        let markup = self.html_source();
+
+        let mut parser =
+            driver::parse_document(HtmlTreeSink::new(Html::new_document()), Default::default());
+        let mut start = 0;
+        loop {
+            let mut end = (start + PARSE_CHUNK_BYTES).min(markup.len());
+            while !markup.is_char_boundary(end) {
+                end += 1;
+            }
+            parser.process(StrTendril::from_slice(&markup[start..end]));
+            start = end;
+            // Rendered Markdown is longer than its source; report in source bytes.
+            on_chunk(start * self.content.len() / markup.len().max(1))?;
+            if start == markup.len() {
+                break;
+            }
+        }
+        let mut parsed = parser.finish();
+        if self.hoist_templates {
+            hoist_templates(&mut parsed);
+        }
+        *self.html_document.borrow_mut() = Some(Rc::new(parsed));
+        ControlFlow::Continue(())
+    }
+
+    /// The markup an HTML parser reads: the content itself, or rendered
+    /// Markdown.
+    #[cfg(feature = "css")]
+    pub(crate) fn html_source(&self) -> Cow<'_, str> {
+        match self.content_type {
+            ContentType::Markdown => Cow::Owned(render_markdown(&self.content)),
+            _ => Cow::Borrowed(self.content.as_str()),
         }
     }
 
@@ -67,10 +487,19 @@ impl ContentItem {
     /// Both the CSS engine (`scraper` selectors) and the XPath engine (via the
     /// `chadpath` adapter) call this, so a document is parsed by html5ever **once**
     /// regardless of how many or which kinds of queries run against it.
+    #[cfg(feature = "css")]
     pub(crate) fn html(&self) -> Rc<Html> {
         let mut doc = self.html_document.borrow_mut();
         if doc.is_none() {
-            *doc = Some(Rc::new(Html::parse_document(&self.content)));
+            let mut parsed = match self.content_type {
+                ContentType::Markdown => Html::parse_document(&render_markdown(&self.content)),
+                _ if self.fragment => parse_fragment(&self.content),
+                _ => Html::parse_document(&self.content),
+            };
+            if self.hoist_templates {
+                hoist_templates(&mut parsed);
+            }
+            *doc = Some(Rc::new(parsed));
         }
         doc.as_ref().unwrap().clone()
     }
@@ -79,6 +508,7 @@ impl ContentItem {
     /// map, building either on first use. The XPath engine uses this so the
     /// O(n) order pass is amortised across all queries on the document instead
     /// of repeated per query.
+    #[cfg(feature = "css")]
     pub(crate) fn html_with_order(&self) -> (Rc<Html>, Rc<OrderMap>) {
         let doc = self.html();
         let mut order = self.html_order.borrow_mut();
@@ -89,9 +519,118 @@ impl ContentItem {
     }
 }
 
+/// Parse an HTML fragment in a `<body>` context, then lift its nodes out of
+/// the `<html>` element html5ever wraps them in, so they sit directly under
+/// the root: `/div[1]` is the fragment's first top-level `<div>`.
+#[cfg(feature = "css")]
+fn parse_fragment(source: &str) -> Html {
+    let mut html = Html::parse_fragment(source);
+    let wrapper = html.tree.root().first_child().map(|wrapper| wrapper.id());
+    if let Some(wrapper) = wrapper {
+        html.tree.root_mut().reparent_from_id_append(wrapper);
+        html.tree.get_mut(wrapper).expect("wrapper exists").detach();
+    }
+    html
+}
+
+/// Move the contents of every `<template>` out of its document fragment and
+/// into the template element itself, so selectors see them as ordinary
+/// descendants. Nested templates are hoisted too.
+#[cfg(feature = "css")]
+fn hoist_templates(html: &mut Html) {
+    let fragments: Vec<NodeId> = html
+        .tree
+        .root()
+        .descendants()
+        .filter(|node| matches!(node.value(), Node::Fragment) && node.parent().is_some())
+        .map(|node| node.id())
+        .collect();
+    for fragment in fragments {
+        let Some(template) = html.tree.get(fragment).and_then(|node| node.parent()) else {
+            continue;
+        };
+        let template = template.id();
+        html.tree.get_mut(template).expect("template exists").reparent_from_id_append(fragment);
+        html.tree.get_mut(fragment).expect("fragment exists").detach();
+    }
+}
+
+/// Render Markdown (CommonMark plus tables, strikethrough, and task lists) to
+/// HTML.
+#[cfg(feature = "css")]
+fn render_markdown(source: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut rendered = String::with_capacity(source.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut rendered, Parser::new_ext(source, options));
+    rendered
+}
+
+/// The size in bytes of `content` loaded as `content_type` (see
+/// [`ContentItem::size`]).
+pub(crate) fn size_of(content: &str, content_type: &ContentType) -> usize {
+    match content_type {
+        ContentType::Binary => content.chars().count(),
+        _ => content.len(),
+    }
+}
+
+/// The charset named by a `<meta charset="…">`, a `<meta http-equiv
+/// content="…; charset=…">`, or an `<?xml … encoding="…"?>` in `head`.
+#[cfg(any(feature = "info", feature = "fetch"))]
+pub(crate) fn declared_charset(head: &str) -> Option<String> {
+    let lowercase = head.to_ascii_lowercase();
+    let (start, key) = if lowercase.trim_start().starts_with("<?xml") {
+        (lowercase.find("<?xml")?, "encoding=")
+    } else {
+        (lowercase.find("<meta")?, "charset=")
+    };
+    let at = lowercase[start..].find(key)? + start + key.len();
+    let label: String = lowercase[at..]
+        .trim_start_matches(['"', '\''])
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect();
+    (!label.is_empty()).then_some(label)
+}
+
+/// The document's text outside scripts and styles, stopping once it reaches
+/// `limit` bytes.
+#[cfg(feature = "css")]
+pub(crate) fn visible_text(html: &Html, limit: usize) -> String {
+    let hidden = ["script", "style", "noscript", "template", "head"];
+    let mut text = String::new();
+    for node in html.tree.root().descendants() {
+        let Some(chunk) = node.value().as_text() else {
+            continue;
+        };
+        let in_hidden = node.ancestors().any(|a| {
+            a.value()
+                .as_element()
+                .is_some_and(|e| hidden.contains(&e.name()))
+        });
+        if in_hidden {
+            continue;
+        }
+        text.push_str(chunk);
+        text.push(' ');
+        if text.len() >= limit {
+            break;
+        }
+    }
+    text
+}
+
 impl Clone for ContentItem {
     fn clone(&self) -> Self {
         // Don't clone cached documents — they will be lazily re-parsed if needed.
-        ContentItem::new(self.content.clone(), self.content_type.clone())
+        let mut item = ContentItem::new(self.content.clone(), self.content_type.clone());
+        item.source = self.source.clone();
+        item.charset = self.charset.clone();
+        item.fragment = self.fragment;
+        item.page = self.page;
+        item.sheet = self.sheet.clone();
+        item.hoist_templates = self.hoist_templates;
+        item
     }
 }
@@ -0,0 +1,275 @@
+//! Change detection between extraction runs: capture an
+//! [`ExtractionSchema`]'s rows as an [`ExtractionSnapshot`], then diff two
+//! snapshots by record key.
+//!
+//! ```text
+//! yesterday                      today                        diff
+//!   Civic   price 28500            Civic   price 27900   →     changed  Civic  price 28500 → 27900
+//!   Accord  price 31000            Prius   price 30500         added    Prius
+//!                                                              removed  Accord
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+use crate::functions::hex;
+use crate::schema::{ColumnType, ExtractionSchema};
+use crate::ChadSelect;
+
+impl ColumnType {
+    /// `text` in canonical form for comparison; `None` when it is empty or
+    /// has no number.
+    fn canonical(self, text: String) -> Option<String> {
+        if text.is_empty() {
+            return None;
+        }
+        match self {
+            ColumnType::Text => Some(text),
+            ColumnType::Integer => ColumnType::integer(&text).map(|n| n.to_string()),
+            ColumnType::Real => ColumnType::real(&text).map(|n| n.to_string()),
+        }
+    }
+}
+
+/// One extracted row of an [`ExtractionSnapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotRecord {
+    /// The key the row is matched on when diffing: the schema's
+    /// [`key`](ExtractionSchema::key) column, or else the content item's
+    /// source, or else its index.
+    pub key: String,
+    /// The column values in the snapshot's column order. Integer and real
+    /// values are canonical numbers (`"$28,500"` is `"28500"`); empty and
+    /// non-numeric values are `None`.
+    pub values: Vec<Option<String>>,
+    /// Lowercase hex SHA-256 of the content item the row came from.
+    pub content_hash: String,
+}
+
+/// The results of one extraction run, for comparing against another with
+/// [`diff`](ExtractionSnapshot::diff).
+///
+/// Unlike [`Snapshot`](crate::Snapshot), which captures the loaded content
+/// itself, this holds only the extracted values and a hash of each item.
+/// When two rows share a key, the first is kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractionSnapshot {
+    columns: Vec<String>,
+    records: Vec<SnapshotRecord>,
+}
+
+/// A column whose value differs between two snapshots of a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// Column name.
+    pub column: String,
+    /// The value in the older snapshot.
+    pub old: Option<String>,
+    /// The value in the newer snapshot.
+    pub new: Option<String>,
+}
+
+/// A record present in both snapshots whose values differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordChange {
+    /// The record key.
+    pub key: String,
+    /// The columns that changed, in column order.
+    pub fields: Vec<FieldChange>,
+}
+
+/// The difference between two [`ExtractionSnapshot`]s.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Records only in the newer snapshot, in its order.
+    pub added: Vec<SnapshotRecord>,
+    /// Records only in the older snapshot, in its order.
+    pub removed: Vec<SnapshotRecord>,
+    /// Records in both whose values differ, in the newer snapshot's order.
+    pub changed: Vec<RecordChange>,
+    /// Keys of records in both whose values are equal but whose content
+    /// changed — often a layout change the schema's queries no longer
+    /// follow.
+    pub content_changed: Vec<String>,
+}
+
+impl SnapshotDiff {
+    /// Whether no record was added, removed, or changed. Content-only
+    /// changes do not count.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl ExtractionSnapshot {
+    /// Column names, in schema order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// The captured records, in content order.
+    pub fn records(&self) -> &[SnapshotRecord] {
+        &self.records
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether no record was captured.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The record with key `key`.
+    pub fn get(&self, key: &str) -> Option<&SnapshotRecord> {
+        self.records.iter().find(|record| record.key == key)
+    }
+
+    /// The value of `column` in the record with key `key`.
+    pub fn value(&self, key: &str, column: &str) -> Option<&str> {
+        let index = self.columns.iter().position(|c| c == column)?;
+        self.get(key)?.values[index].as_deref()
+    }
+
+    /// What changed from this (older) snapshot to `newer`.
+    ///
+    /// Records are matched by key. Columns are matched by name; one missing
+    /// from either snapshot counts as `None` there.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, ColumnType, ExtractionSchema};
+    ///
+    /// let schema = ExtractionSchema::new()
+    ///     .column("model", ColumnType::Text, "css:h1")
+    ///     .column("price", ColumnType::Integer, "css:.price")
+    ///     .key("model");
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<h1>Civic</h1><span class="price">$28,500</span>"#.to_string());
+    /// cs.add_html(r#"<h1>Accord</h1><span class="price">$31,000</span>"#.to_string());
+    /// let yesterday = cs.extraction_snapshot(&schema);
+    ///
+    /// cs.clear();
+    /// cs.add_html(r#"<h1>Civic</h1><span class="price">$27,900</span>"#.to_string());
+    /// let today = cs.extraction_snapshot(&schema);
+    ///
+    /// let diff = yesterday.diff(&today);
+    /// assert_eq!(diff.removed[0].key, "Accord");
+    /// assert_eq!(diff.changed[0].key, "Civic");
+    /// assert_eq!(diff.changed[0].fields[0].new.as_deref(), Some("27900"));
+    /// ```
+    pub fn diff(&self, newer: &ExtractionSnapshot) -> SnapshotDiff {
+        let mut columns = self.columns.clone();
+        for column in &newer.columns {
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+        let old_records: HashMap<&str, &SnapshotRecord> =
+            self.records.iter().map(|r| (r.key.as_str(), r)).collect();
+        let new_keys: HashSet<&str> = newer.records.iter().map(|r| r.key.as_str()).collect();
+
+        let mut diff = SnapshotDiff::default();
+        for new in &newer.records {
+            let Some(old) = old_records.get(new.key.as_str()) else {
+                diff.added.push(new.clone());
+                continue;
+            };
+            let fields: Vec<FieldChange> = columns
+                .iter()
+                .filter_map(|column| {
+                    let old_value = self.field(old, column);
+                    let new_value = newer.field(new, column);
+                    (old_value != new_value).then(|| FieldChange {
+                        column: column.clone(),
+                        old: old_value,
+                        new: new_value,
+                    })
+                })
+                .collect();
+            if !fields.is_empty() {
+                diff.changed.push(RecordChange {
+                    key: new.key.clone(),
+                    fields,
+                });
+            } else if old.content_hash != new.content_hash {
+                diff.content_changed.push(new.key.clone());
+            }
+        }
+        diff.removed = self
+            .records
+            .iter()
+            .filter(|r| !new_keys.contains(r.key.as_str()))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    /// `record`'s value of `column`; `None` when the column is not in this
+    /// snapshot.
+    fn field(&self, record: &SnapshotRecord, column: &str) -> Option<String> {
+        let index = self.columns.iter().position(|c| c == column)?;
+        record.values[index].clone()
+    }
+}
+
+impl ChadSelect {
+    /// Extract `schema` from every content item into an
+    /// [`ExtractionSnapshot`] for change detection.
+    ///
+    /// Rows are built as [`ExtractionSchema`] describes. Each is keyed by
+    /// the schema's [`key`](ExtractionSchema::key) column when it has a
+    /// value, or else by the content item's source, or else by its index.
+    /// An empty schema yields an empty snapshot.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, ColumnType, ExtractionSchema};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<h1>Civic</h1><span class="price">$28,500</span>"#.to_string());
+    ///
+    /// let schema = ExtractionSchema::new()
+    ///     .column("title", ColumnType::Text, "css:h1")
+    ///     .column("price", ColumnType::Integer, "css:.price");
+    /// let snapshot = cs.extraction_snapshot(&schema);
+    /// assert_eq!(snapshot.value("0", "price"), Some("28500"));
+    /// ```
+    pub fn extraction_snapshot(&self, schema: &ExtractionSchema) -> ExtractionSnapshot {
+        let key_index = schema
+            .key
+            .as_ref()
+            .and_then(|key| schema.columns.iter().position(|c| &c.name == key));
+
+        let mut seen = HashSet::new();
+        let mut records = Vec::new();
+        for (item, row) in self.extract_rows(schema) {
+            let values: Vec<Option<String>> = schema
+                .columns
+                .iter()
+                .zip(row)
+                .map(|(column, text)| column.column_type.canonical(text))
+                .collect();
+            let content = &self.content_list[item];
+            let key = key_index
+                .and_then(|i| values[i].clone())
+                .or_else(|| content.source.clone())
+                .unwrap_or_else(|| item.to_string());
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+            records.push(SnapshotRecord {
+                key,
+                values,
+                content_hash: hex(&Sha256::digest(content.content.as_bytes())),
+            });
+        }
+
+        ExtractionSnapshot {
+            columns: schema.columns.iter().map(|c| c.name.clone()).collect(),
+            records,
+        }
+    }
+}
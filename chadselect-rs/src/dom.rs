@@ -0,0 +1,535 @@
+//! Swappable HTML parsers for CSS queries — see [`DomBackend`] and
+//! [`ChadSelect::query_dom`](crate::ChadSelect::query_dom).
+//!
+//! Every other HTML feature runs on the cached `scraper` document. A
+//! [`DomBackend`] lets a build run plain `css:` queries with a different
+//! parser instead, picked by type:
+//!
+//! | Backend | Feature | Parser |
+//! |---------|---------|--------|
+//! | [`Scraper`] | `css` | html5ever, the parser behind every other query |
+//! | [`Tl`] | `tl` | `tl`: a zero-copy, non-spec-compliant DOM |
+//! | [`LolHtml`] | `lol-html` | `lol_html`: a streaming rewriter, no DOM at all |
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[cfg(any(feature = "tl", feature = "lol-html"))]
+use chadselect_core::markup::decode_entities;
+
+use crate::config::TextOptions;
+use crate::functions::{self, Navigation, TextFunction, ValueSource};
+use crate::query;
+use crate::warnings::{warning, Capture};
+use crate::{cancel, ChadSelect};
+
+/// An HTML parser that can run a CSS selector.
+///
+/// A backend only has to find elements and report their text nodes and
+/// attributes; the `>>` function chain, `index` semantics, and text
+/// flattening are shared.
+pub trait DomBackend: 'static {
+    /// A parsed document, which may borrow the markup it was parsed from.
+    type Document<'a>;
+
+    /// Parse `html`.
+    fn parse(html: &str) -> Result<Self::Document<'_>, String>;
+
+    /// The elements `selector` matches in `document`, in document order.
+    /// An error names a selector the backend cannot parse.
+    fn select(document: &Self::Document<'_>, selector: &str) -> Result<Vec<DomElement>, String>;
+}
+
+/// One element a [`DomBackend`] matched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DomElement {
+    /// The text nodes inside the element, in document order, with
+    /// character references decoded.
+    pub text_nodes: Vec<String>,
+    /// The element's attributes in source order, names lowercased and
+    /// values decoded.
+    pub attributes: Vec<(String, String)>,
+}
+
+impl DomElement {
+    /// The value of attribute `name`, compared case-insensitively.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The element's text, flattened the way the CSS engine flattens a
+    /// match: text nodes joined with `options.separator`, then trimmed if
+    /// `options.trim`. The alt-text and script options need a DOM and are
+    /// not applied.
+    pub fn text(&self, options: &TextOptions) -> String {
+        let joined = self.text_nodes.join(&options.separator);
+        if options.trim {
+            joined.trim().to_string()
+        } else {
+            joined
+        }
+    }
+
+    /// Approximate heap bytes held, for the cache budget.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let text: usize = self.text_nodes.iter().map(String::len).sum();
+        let attributes: usize = self
+            .attributes
+            .iter()
+            .map(|(name, value)| name.len() + value.len())
+            .sum();
+        text + attributes
+    }
+}
+
+/// The elements [`query_dom`](ChadSelect::query_dom) matched in a content
+/// item, by backend and selector.
+pub(crate) type Selections = HashMap<(TypeId, String), Rc<Vec<DomElement>>>;
+
+/// `scraper` and html5ever: spec-compliant, and what every other query
+/// uses. Parsing here does not share the document cached for `query`.
+#[derive(Debug, Clone, Copy)]
+pub struct Scraper;
+
+impl DomBackend for Scraper {
+    type Document<'a> = scraper::Html;
+
+    fn parse(html: &str) -> Result<scraper::Html, String> {
+        Ok(scraper::Html::parse_document(html))
+    }
+
+    fn select(document: &scraper::Html, selector: &str) -> Result<Vec<DomElement>, String> {
+        let selector = scraper::Selector::parse(selector).map_err(|e| e.to_string())?;
+        Ok(document
+            .select(&selector)
+            .map(|element| DomElement {
+                text_nodes: element.text().map(str::to_string).collect(),
+                attributes: element
+                    .value()
+                    .attrs()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// `tl`: parses several times faster than html5ever by not building a
+/// spec-compliant tree — misnested or unclosed markup may nest differently
+/// than a browser would. Supports type, `#id`, `.class`, and attribute
+/// selectors, combined with the descendant (` `) and child (`>`)
+/// combinators and `,`; pseudo-classes and sibling combinators are
+/// rejected.
+#[cfg(feature = "tl")]
+#[derive(Debug, Clone, Copy)]
+pub struct Tl;
+
+#[cfg(feature = "tl")]
+impl DomBackend for Tl {
+    type Document<'a> = tl::VDom<'a>;
+
+    fn parse(html: &str) -> Result<tl::VDom<'_>, String> {
+        tl::parse(html, tl::ParserOptions::default()).map_err(|e| e.to_string())
+    }
+
+    fn select(document: &tl::VDom<'_>, selector: &str) -> Result<Vec<DomElement>, String> {
+        let parser = document.parser();
+        let nodes = document.nodes();
+        Ok(tl_matches(document, selector)?
+            .into_iter()
+            .filter_map(|index| nodes[index as usize].as_tag())
+            .map(|tag| DomElement {
+                text_nodes: tag
+                    .children()
+                    .all(parser)
+                    .iter()
+                    .filter_map(|node| match node {
+                        tl::Node::Raw(text) => Some(decode_entities(&text.as_utf8_str())),
+                        _ => None,
+                    })
+                    .collect(),
+                attributes: tag
+                    .attributes()
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = value.as_deref().map(decode_entities).unwrap_or_default();
+                        (name.to_ascii_lowercase(), value)
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// The node indices `selector` matches, in document order.
+///
+/// `tl` parses combinators but only matches compound selectors (`li.car`),
+/// so the selector is split into compounds here and the combinators are
+/// walked over `tl`'s flat node table, where an element's descendants are
+/// the contiguous run of nodes after it.
+#[cfg(feature = "tl")]
+fn tl_matches(document: &tl::VDom<'_>, selector: &str) -> Result<Vec<u32>, String> {
+    use std::collections::BTreeSet;
+
+    let parser = document.parser();
+    let nodes = document.nodes();
+    let mut found = BTreeSet::new();
+    for group in split_selector(selector)? {
+        let mut current: BTreeSet<u32> = BTreeSet::new();
+        for (step, (child, compound)) in group.iter().enumerate() {
+            let compound = tl::parse_query_selector(compound)
+                .ok_or_else(|| format!("tl cannot parse selector '{}'", compound))?;
+            let candidates: Vec<u32> = if step == 0 {
+                (0..nodes.len() as u32).collect()
+            } else {
+                current
+                    .iter()
+                    .filter_map(|&index| nodes[index as usize].as_tag())
+                    .flat_map(|tag| -> Vec<u32> {
+                        let children = tag.children();
+                        if *child {
+                            children.top().iter().map(|h| h.get_inner()).collect()
+                        } else {
+                            children
+                                .boundaries(parser)
+                                .map(|(start, end)| (start..=end).collect())
+                                .unwrap_or_default()
+                        }
+                    })
+                    .collect()
+            };
+            current = candidates
+                .into_iter()
+                .filter(|&index| compound.matches(&nodes[index as usize]))
+                .collect();
+        }
+        found.extend(current);
+    }
+    Ok(found.into_iter().collect())
+}
+
+/// `selector` as `,`-separated groups of compound selectors, each with
+/// whether it is joined to the previous one by `>` (rather than by
+/// whitespace). Brackets and quotes are kept whole.
+#[cfg(feature = "tl")]
+fn split_selector(selector: &str) -> Result<Vec<Vec<(bool, &str)>>, String> {
+    let mut groups = vec![];
+    let mut group = vec![];
+    let mut child = false;
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in selector.char_indices() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        let boundary = depth == 0 && (c.is_whitespace() || c == '>' || c == ',');
+        if boundary {
+            if let Some(s) = start.take() {
+                group.push((child, &selector[s..i]));
+                child = false;
+            }
+            match c {
+                '>' if group.is_empty() || child => {
+                    return Err(format!("misplaced '>' in selector '{}'", selector));
+                }
+                '>' => child = true,
+                ',' if group.is_empty() || child => {
+                    return Err(format!("empty selector in '{}'", selector));
+                }
+                ',' => groups.push(std::mem::take(&mut group)),
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            '"' | '\'' => quote = Some(c),
+            ':' | '+' | '~' if depth == 0 => {
+                return Err(format!(
+                    "tl does not support '{}' in selector '{}'",
+                    c, selector
+                ));
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(s) = start {
+        group.push((child, &selector[s..]));
+    } else if child || group.is_empty() {
+        return Err(format!("incomplete selector '{}'", selector));
+    }
+    groups.push(group);
+    Ok(groups)
+}
+
+/// `lol_html`: selects while streaming the markup, without building a
+/// tree, so it uses the least memory. An element's text is the text
+/// between its start tag and its end tag; an element whose end tag is left
+/// implicit (`<li>` without `</li>`) gathers text to the end of the
+/// document, and a void element (`<img>`) has none.
+#[cfg(feature = "lol-html")]
+#[derive(Debug, Clone, Copy)]
+pub struct LolHtml;
+
+#[cfg(feature = "lol-html")]
+impl DomBackend for LolHtml {
+    type Document<'a> = &'a str;
+
+    fn parse(html: &str) -> Result<&str, String> {
+        Ok(html)
+    }
+
+    fn select(document: &&str, selector: &str) -> Result<Vec<DomElement>, String> {
+        use std::borrow::Cow;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        use lol_html::html_content::{Element, TextChunk, TextType};
+        use lol_html::{DocumentContentHandlers, ElementContentHandlers, HtmlRewriter, Settings};
+
+        /// Matched elements, the indices of those still open, and the text
+        /// node being assembled from chunks.
+        #[derive(Default)]
+        struct Stream {
+            elements: Vec<DomElement>,
+            open: Vec<usize>,
+            text: String,
+        }
+
+        let parsed: lol_html::Selector = selector.parse().map_err(|e| format!("{}", e))?;
+        let stream = Rc::new(RefCell::new(Stream::default()));
+
+        let on_element = {
+            let stream = Rc::clone(&stream);
+            move |element: &mut Element<'_, '_>| {
+                let mut state = stream.borrow_mut();
+                let index = state.elements.len();
+                state.elements.push(DomElement {
+                    text_nodes: vec![],
+                    attributes: element
+                        .attributes()
+                        .iter()
+                        .map(|attribute| (attribute.name(), decode_entities(&attribute.value())))
+                        .collect(),
+                });
+                if let Some(handlers) = element.end_tag_handlers() {
+                    state.open.push(index);
+                    let stream = Rc::clone(&stream);
+                    handlers.push(Box::new(move |_| {
+                        stream.borrow_mut().open.retain(|&open| open != index);
+                        Ok(())
+                    }));
+                }
+                Ok(())
+            }
+        };
+        let on_text = {
+            let stream = Rc::clone(&stream);
+            move |chunk: &mut TextChunk<'_>| {
+                let mut state = stream.borrow_mut();
+                state.text.push_str(chunk.as_str());
+                if chunk.last_in_text_node() {
+                    let raw = std::mem::take(&mut state.text);
+                    if !raw.is_empty() && !state.open.is_empty() {
+                        let text = match chunk.text_type() {
+                            TextType::Data | TextType::RCData => decode_entities(&raw),
+                            _ => raw,
+                        };
+                        let Stream { elements, open, .. } = &mut *state;
+                        for &index in open.iter() {
+                            elements[index].text_nodes.push(text.clone());
+                        }
+                    }
+                }
+                Ok(())
+            }
+        };
+
+        let settings = Settings {
+            element_content_handlers: vec![(
+                Cow::Owned(parsed),
+                ElementContentHandlers::default().element(on_element),
+            )],
+            document_content_handlers: vec![DocumentContentHandlers::default().text(on_text)],
+            ..Settings::new()
+        };
+        let mut rewriter = HtmlRewriter::new(settings, |_: &[u8]| {});
+        rewriter
+            .write(document.as_bytes())
+            .and_then(|()| rewriter.end())
+            .map_err(|e| e.to_string())?;
+
+        let elements = std::mem::take(&mut stream.borrow_mut().elements);
+        Ok(elements)
+    }
+}
+
+/// Matched [`DomElement`]s, by position, as the source of chain values:
+/// `get-attr` reads their attributes. Navigation needs a tree, so
+/// `closest()` and the other steps find nothing.
+struct Elements<'e>(&'e [DomElement]);
+
+impl ValueSource<usize> for Elements<'_> {
+    fn elements(&self) -> bool {
+        true
+    }
+
+    fn attribute(&self, element: usize, name: &str) -> Option<String> {
+        self.0[element].attr(name).map(str::to_string)
+    }
+}
+
+impl ChadSelect {
+    /// Run a `css:` query with the HTML parser of backend `B`, with the
+    /// same `index` semantics as [`query`](ChadSelect::query).
+    ///
+    /// Each HTML and Markdown item is parsed by `B` the first time a
+    /// selector runs on it; the matched elements are cached on the item per
+    /// backend and selector, so running it again parses nothing. The item's
+    /// `scraper` document is neither used nor built. Only plain selectors
+    /// apply: alternatives with a text pseudo-selector (`:has-text()`, …) or
+    /// another engine are skipped with a warning. The `>>` chain runs as
+    /// usual, except that navigation functions (`closest()`, …) have no tree
+    /// to walk: they find nothing, with a warning. With `||` fallbacks, the
+    /// first alternative whose results are all non-empty wins.
+    ///
+    /// ```rust
+    /// use chadselect::dom::Scraper;
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<a class="sku" href="/p/1">Civic</a><a class="sku" href="/p/2">Accord</a>"#.to_string());
+    ///
+    /// assert_eq!(cs.query_dom::<Scraper>(-1, "css:a.sku"), ["Civic", "Accord"]);
+    /// assert_eq!(cs.query_dom::<Scraper>(1, "css:a.sku >> get-attr('href')"), ["/p/2"]);
+    /// ```
+    pub fn query_dom<B: DomBackend>(&self, index: i32, query_str: &str) -> Vec<String> {
+        let Some(prepared) = self.build_lenient(query_str) else {
+            return vec![];
+        };
+        let capture = Capture::begin();
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let alternatives = prepared.alternatives();
+        let mut results = vec![];
+        for alternative in alternatives {
+            let (Some((_, chain)), query::QueryType::CssSelector(expression)) =
+                (alternative.plain_css(), alternative.query_type())
+            else {
+                warning!(
+                    InvalidQuery,
+                    "query_dom runs plain css: selectors, skipping {}: alternative '{}'",
+                    alternative.query_type().prefix(),
+                    alternative.query_type().expression()
+                );
+                continue;
+            };
+            let (selector, _) = functions::split_chain(expression, true);
+            if let Some(step) = chain.iter().find_map(|function| match function {
+                TextFunction::Navigate { step } => Some(step),
+                _ => None,
+            }) {
+                warning!(
+                    InvalidQuery,
+                    "query_dom has no element tree to walk: {}() in '{}' finds nothing",
+                    navigation_name(step),
+                    alternative.query_type().expression()
+                );
+            }
+
+            let mut found = vec![];
+            for content_item in &self.content_list {
+                if cancel::interrupted() {
+                    break;
+                }
+                if !query::is_query_compatible(alternative.query_type(), &content_item.content_type)
+                {
+                    continue;
+                }
+                let key = (TypeId::of::<B>(), selector.to_string());
+                let cached = content_item.dom_selections.borrow().get(&key).cloned();
+                let elements = match cached {
+                    Some(elements) => elements,
+                    None => {
+                        let source = content_item.html_source();
+                        let document = match B::parse(&source) {
+                            Ok(document) => document,
+                            Err(e) => {
+                                warning!(InvalidContent, "Failed to parse HTML: {}", e);
+                                continue;
+                            }
+                        };
+                        let elements = match B::select(&document, &selector) {
+                            Ok(elements) => Rc::new(elements),
+                            Err(e) => {
+                                warning!(
+                                    InvalidQuery,
+                                    "Invalid CSS selector '{}': {}",
+                                    selector,
+                                    e
+                                );
+                                break;
+                            }
+                        };
+                        content_item
+                            .dom_selections
+                            .borrow_mut()
+                            .insert(key, Rc::clone(&elements));
+                        elements
+                    }
+                };
+                content_item.last_used.set(tick);
+                let entries = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(i, element)| (Some(i), element.text(&self.config.text)))
+                    .collect();
+                let item_results: Vec<String> =
+                    functions::apply_chain(entries, chain, &Elements(&elements))
+                        .into_iter()
+                        .map(|(_, text)| text)
+                        .collect();
+                found.extend(self.pick_in_item(item_results, index));
+                if self.over_max_results(&mut found) {
+                    break;
+                }
+            }
+            results = self.pick_across_items(found, index);
+
+            if alternatives.len() == 1
+                || cancel::interrupted()
+                || (!results.is_empty() && results.iter().all(|r| crate::default_valid(r)))
+            {
+                break;
+            }
+            results.clear();
+        }
+
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        results
+    }
+}
+
+/// The pipe function a navigation step is written as.
+fn navigation_name(step: &Navigation) -> &'static str {
+    match step {
+        Navigation::Closest(_) => "closest",
+        Navigation::Parent => "parent",
+        Navigation::Following(_) => "following",
+        Navigation::Preceding(_) => "preceding",
+        Navigation::FollowingSibling(_) => "following-sibling",
+        Navigation::PrecedingSibling(_) => "preceding-sibling",
+    }
+}
@@ -0,0 +1,150 @@
+//! Byte-pattern extraction engine.
+//!
+//! Runs [`regex::bytes`] patterns over the bytes of a content item — the
+//! original bytes of Binary content, the UTF-8 encoding of anything else.
+//! Patterns compile with Unicode off and dot-all on, so `\xDE` is one byte
+//! and `.` any byte. Capture groups work as in the regex engine: with groups,
+//! only group values are returned. `bytes:` returns each value as lossy
+//! UTF-8, `hex:` as lowercase hex; a trailing `>>` function chain is applied
+//! to those strings.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use regex::bytes::Regex;
+
+use crate::cancel;
+use crate::content::ContentItem;
+use crate::functions;
+use crate::patterns;
+use crate::warnings::warning;
+
+/// The inline flags every byte pattern starts with.
+const FLAGS: &str = "(?s-u)";
+
+thread_local! {
+    /// Cache of compiled byte regexes, keyed by the pattern string — see the
+    /// regex engine's cache. Invalid patterns cache as `None`.
+    static COMPILED: RefCell<HashMap<String, Option<Regex>>> = RefCell::new(HashMap::new());
+}
+
+/// How matched bytes become result values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// Lossy UTF-8, invalid sequences as U+FFFD.
+    Text,
+    /// Lowercase hex, two digits per byte.
+    Hex,
+}
+
+impl Encoding {
+    /// `bytes` as a result value.
+    pub(crate) fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Encoding::Text => String::from_utf8_lossy(bytes).into_owned(),
+            Encoding::Hex => functions::hex(bytes),
+        }
+    }
+}
+
+/// Compile the byte regex `pattern` (or fetch it from the cache). Returns
+/// `None` for an invalid pattern, warning once on first compile.
+pub(crate) fn compiled(pattern: &str) -> Option<Regex> {
+    COMPILED.with(|c| {
+        if let Some(r) = c.borrow().get(pattern) {
+            return r.clone();
+        }
+        let compiled = match Regex::new(&format!("{}{}", FLAGS, pattern)) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                warning!(InvalidQuery, "Invalid byte pattern '{}': {}", pattern, e);
+                None
+            }
+        };
+        c.borrow_mut().insert(pattern.to_string(), compiled.clone());
+        compiled
+    })
+}
+
+/// The matches (or capture group values) of `regex` in `bytes`, as byte
+/// ranges, stopping once `limit` are collected.
+pub(crate) fn extract_located(regex: &Regex, bytes: &[u8], limit: usize) -> Vec<Range<usize>> {
+    let mut results = Vec::new();
+
+    if regex.captures_len() > 1 {
+        for capture in regex.captures_iter(bytes) {
+            if cancel::interrupted() {
+                break;
+            }
+            for matched in capture.iter().skip(1).flatten() {
+                results.push(matched.range());
+                if results.len() >= limit {
+                    return results;
+                }
+            }
+        }
+    } else {
+        for mat in regex.find_iter(bytes) {
+            if cancel::interrupted() || results.len() >= limit {
+                break;
+            }
+            results.push(mat.range());
+        }
+    }
+
+    results
+}
+
+/// Run `regex` over the item's bytes, encoding each value.
+pub(crate) fn evaluate(
+    regex: &Regex,
+    encoding: Encoding,
+    content_item: &ContentItem,
+    limit: usize,
+) -> Vec<String> {
+    let bytes = content_item.bytes();
+    extract_located(regex, &bytes, limit)
+        .into_iter()
+        .map(|range| encoding.encode(&bytes[range]))
+        .collect()
+}
+
+/// Process a byte regex (potentially with a `>>` function chain) against a
+/// content item, returning matches as lossy UTF-8.
+pub fn process(pattern_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
+    let (pattern, text_functions) = functions::split_pattern_functions(pattern_with_functions);
+    run(&pattern, &text_functions, Encoding::Text, content_item)
+}
+
+/// Process a hex byte signature (see [`patterns::hex_to_regex`], potentially
+/// with a `>>` function chain) against a content item, returning matches
+/// hex-encoded.
+pub fn process_hex(signature_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
+    let (signature, text_functions) = functions::split_functions(signature_with_functions);
+    match patterns::hex_to_regex(&signature) {
+        Ok(pattern) => run(&pattern, &text_functions, Encoding::Hex, content_item),
+        Err(e) => {
+            warning!(InvalidQuery, "Invalid hex signature '{}': {}", signature, e);
+            vec![]
+        }
+    }
+}
+
+fn run(
+    pattern: &str,
+    text_functions: &[functions::TextFunction],
+    encoding: Encoding,
+    content_item: &ContentItem,
+) -> Vec<String> {
+    let Some(regex) = compiled(pattern) else {
+        return vec![];
+    };
+    let results = evaluate(&regex, encoding, content_item, usize::MAX);
+
+    if text_functions.is_empty() {
+        results
+    } else {
+        functions::apply_text_functions(results, text_functions)
+    }
+}
@@ -7,11 +7,16 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 
-use log::warn;
-use scraper::{ElementRef, Html, Selector};
+use ego_tree::iter::Edge;
+use ego_tree::NodeId;
+use scraper::{CaseSensitivity::CaseSensitive, ElementRef, Html, Node, Selector};
 
+use crate::cancel;
+use crate::config::TextOptions;
 use crate::content::ContentItem;
-use crate::functions::{self, TextFunction};
+use crate::engine::NON_CONTENT_TAGS;
+use crate::functions::{self, Navigation, TextFunction, ValueSource};
+use crate::warnings::warning;
 
 thread_local! {
     /// Cache of parsed CSS selectors, keyed by the selector string.
@@ -26,7 +31,7 @@ thread_local! {
 
 /// Parse `selector` (or fetch the cached `Selector`). Returns `None` for an
 /// invalid selector; the caller logs context-specific warnings.
-fn cached_selector(selector: &str) -> Option<Selector> {
+pub(crate) fn cached_selector(selector: &str) -> Option<Selector> {
     COMPILED.with(|c| {
         if let Some(s) = c.borrow().get(selector) {
             return s.clone();
@@ -52,129 +57,609 @@ enum TextPseudoSelector {
     TextStarts(String),
     /// Element's text content ends with the text.
     TextEnds(String),
+    /// Element's text content is at least `threshold` similar to the text,
+    /// ignoring case, punctuation, and spacing (see [`fuzzy_similarity`]).
+    TextFuzzy { text: String, threshold: f64 },
 }
 
-/// Parsed CSS selector with optional text pseudo-selector and function chain.
+impl TextPseudoSelector {
+    /// Whether an element's [`match_text`] passes this filter.
+    fn matches(&self, text_content: &str) -> bool {
+        match self {
+            TextPseudoSelector::HasText(t) => text_content.contains(t.as_str()),
+            TextPseudoSelector::ContainsText(t) => text_content.contains(t.as_str()),
+            TextPseudoSelector::TextEquals(t) => text_content == t,
+            TextPseudoSelector::TextStarts(t) => text_content.starts_with(t.as_str()),
+            TextPseudoSelector::TextEnds(t) => text_content.ends_with(t.as_str()),
+            TextPseudoSelector::TextFuzzy { text, threshold } => {
+                fuzzy_similarity(text_content, text, *threshold).is_some()
+            }
+        }
+    }
+}
+
+/// Default `:text-fuzzy()` threshold.
+const DEFAULT_FUZZY_THRESHOLD: f64 = 0.8;
+
+/// `text` lowercased, with punctuation treated as spacing and runs of
+/// spacing collapsed to one space.
+fn fuzzy_key(text: &str) -> Vec<char> {
+    let lowercase = text.to_lowercase();
+    let words = lowercase.split(|c: char| !c.is_alphanumeric());
+    let words: Vec<&str> = words.filter(|word| !word.is_empty()).collect();
+    words.join(" ").chars().collect()
+}
+
+/// The similarity of `a` and `b` after [`fuzzy_key`] normalization — one
+/// minus their Levenshtein distance over the longer length, from 0 to 1 —
+/// when it reaches `threshold`; `None` below it. Pairs whose lengths alone
+/// rule out the threshold are rejected without computing the distance.
+fn fuzzy_similarity(a: &str, b: &str, threshold: f64) -> Option<f64> {
+    let (a, b) = (fuzzy_key(a), fuzzy_key(b));
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return Some(1.0);
+    }
+    let similarity = |distance: usize| 1.0 - distance as f64 / longest as f64;
+    if similarity(a.len().abs_diff(b.len())) < threshold {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(similarity(previous[b.len()])).filter(|s| *s >= threshold)
+}
+
+/// Parsed CSS selector with an optional text pseudo-selector.
 #[derive(Debug, Clone)]
-struct ParsedCssSelector {
+pub(crate) struct ParsedCssSelector {
     /// The main CSS selector before any text pseudo-selector.
     base_selector: String,
     /// Optional text-based filter.
     text_pseudo: Option<TextPseudoSelector>,
     /// CSS selector to apply to descendants of filtered elements.
     post_selector: String,
-    /// Post-processing function chain.
-    functions: Vec<TextFunction>,
+}
+
+/// A CSS expression (without its function chain) compiled for repeated runs.
+#[derive(Debug, Clone)]
+pub(crate) enum CompiledCss {
+    /// A plain selector `scraper` evaluates directly.
+    Standard(Selector),
+    /// A selector with a text pseudo-selector, evaluated in stages.
+    Pseudo(ParsedCssSelector),
+    /// A selector `scraper` cannot evaluate — a nested `:has()`, or a text
+    /// pseudo-selector inside `:has()`/`:is()`/`:where()`/`:not()` —
+    /// matched by the fallback layer.
+    Fallback(SelectorList),
 }
 
 /// All recognised text pseudo-selector prefixes.
-const PSEUDO_PATTERNS: [&str; 5] = [
+const PSEUDO_PATTERNS: [&str; 6] = [
     ":has-text(",
     ":contains-text(",
     ":text-equals(",
     ":text-starts(",
     ":text-ends(",
+    ":text-fuzzy(",
 ];
 
+/// Functional pseudo-classes the fallback layer evaluates itself.
+const FUNCTIONAL_PSEUDO_CLASSES: [&str; 4] = [":has(", ":is(", ":where(", ":not("];
+
 // ─── Public entry point ─────────────────────────────────────────────────────
 
 /// Process a CSS selector expression (potentially with `>>` function chain
 /// and text pseudo-selectors) against a content item, returning matches.
 pub fn process(selector_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
-    // Route to text-pseudo path if any pseudo-selector is present.
-    if PSEUDO_PATTERNS
-        .iter()
-        .any(|p| selector_with_functions.contains(p))
+    let (selector, text_functions) = functions::split_functions(selector_with_functions);
+    match compile(&selector) {
+        Some(compiled) => run(
+            &compiled,
+            &text_functions,
+            content_item,
+            &TextOptions::default(),
+            usize::MAX,
+        ),
+        None => {
+            warning!(InvalidQuery, "Invalid CSS selector '{}'", selector);
+            vec![]
+        }
+    }
+}
+
+/// Compile a selector (without its function chain). Returns `None` for an
+/// invalid plain selector; the caller logs context-specific warnings.
+pub(crate) fn compile(selector: &str) -> Option<CompiledCss> {
+    let text_pseudo = PSEUDO_PATTERNS.iter().any(|p| selector.contains(p));
+
+    // Functional pseudo-classes `scraper` rejects (a nested `:has()`) or
+    // whose arguments hold text pseudo-selectors degrade to the fallback
+    // layer rather than failing.
+    if FUNCTIONAL_PSEUDO_CLASSES.iter().any(|p| selector.contains(p))
+        && (text_pseudo || cached_selector(selector).is_none())
     {
-        return process_with_text_selectors(selector_with_functions, content_item);
+        return SelectorList::parse(selector).map(CompiledCss::Fallback);
+    }
+
+    // Route to the text-pseudo path if any pseudo-selector is present.
+    if text_pseudo {
+        return parse_with_text_selectors(selector).map(CompiledCss::Pseudo);
     }
 
-    process_standard(selector_with_functions, content_item)
+    cached_selector(selector).map(CompiledCss::Standard)
 }
 
-// ─── Standard CSS selector processing ───────────────────────────────────────
+/// Run a compiled selector and its function chain against a content item.
+/// Without a chain, text extraction stops once `limit` results are in.
+pub(crate) fn run(
+    compiled: &CompiledCss,
+    text_functions: &[TextFunction],
+    content_item: &ContentItem,
+    text_options: &TextOptions,
+    limit: usize,
+) -> Vec<String> {
+    match compiled {
+        CompiledCss::Standard(selector) => {
+            process_standard(selector, text_functions, content_item, text_options, limit)
+        }
+        CompiledCss::Pseudo(parsed) => {
+            process_with_text_selectors(parsed, text_functions, content_item, text_options, limit)
+        }
+        CompiledCss::Fallback(list) => {
+            let html_doc = content_item.html();
+            element_results(&list.select(&html_doc), text_functions, text_options, limit)
+        }
+    }
+}
 
-/// Standard CSS selector processing (no text pseudo-selectors).
-fn process_standard(selector_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
-    let (css_selector_str, text_functions) = functions::split_functions(selector_with_functions);
+/// The full attribute map of every element a compiled selector matches in a
+/// content item, in document order.
+pub(crate) fn attribute_maps(
+    compiled: &CompiledCss,
+    content_item: &ContentItem,
+) -> Vec<HashMap<String, String>> {
+    let html_doc = content_item.html();
+    matched_elements(compiled, content_item, &html_doc)
+        .iter()
+        .map(|element| {
+            element
+                .value()
+                .attrs()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        })
+        .collect()
+}
 
-    // Use the shared, already-parsed HTML document.
+/// Run a compiled selector and its function chain, pairing each result with
+/// the matched element's pre-order rank and selector path. Results that no
+/// longer stem from one element (folds, `if-empty`) have no location.
+pub(crate) fn run_located(
+    compiled: &CompiledCss,
+    text_functions: &[TextFunction],
+    content_item: &ContentItem,
+    text_options: &TextOptions,
+    limit: usize,
+) -> Vec<(Option<(usize, String)>, String)> {
+    let (html_doc, order) = content_item.html_with_order();
+    let elements = matched_elements(compiled, content_item, &html_doc);
+
+    element_entries(&elements, text_functions, text_options, limit)
+        .into_iter()
+        .map(|(element, text)| {
+            let location = element.map(|element| {
+                let position = order.get(&element.id()).map_or(0, |rank| *rank as usize);
+                (position, css_path(&element))
+            });
+            (location, text)
+        })
+        .collect()
+}
+
+/// The ids of the elements a compiled selector matches in the item's cached
+/// document, for DOM edits.
+pub(crate) fn matched_nodes(compiled: &CompiledCss, content_item: &ContentItem) -> Vec<NodeId> {
     let html_doc = content_item.html();
+    matched_elements(compiled, content_item, &html_doc)
+        .iter()
+        .map(|element| element.id())
+        .collect()
+}
 
-    let css_selector = match cached_selector(css_selector_str) {
-        Some(s) => s,
-        None => {
-            warn!("Invalid CSS selector '{}'", css_selector_str);
-            return vec![];
+/// The elements a compiled selector matches in `html_doc`, the content
+/// item's parsed document.
+fn matched_elements<'a>(
+    compiled: &CompiledCss,
+    content_item: &ContentItem,
+    html_doc: &'a Html,
+) -> Vec<ElementRef<'a>> {
+    match compiled {
+        CompiledCss::Standard(selector) => html_doc
+            .select(selector)
+            .take_while(|_| !cancel::interrupted())
+            .collect(),
+        CompiledCss::Pseudo(parsed) => pseudo_elements(parsed, content_item, html_doc),
+        CompiledCss::Fallback(list) => list.select(html_doc),
+    }
+}
+
+// ─── Element paths ──────────────────────────────────────────────────────────
+
+/// A selector path that matches `element`: one `>`-joined step per ancestor,
+/// starting at the root element or at the nearest ancestor with an `id`.
+///
+/// A step is the tag plus its classes (`div.products`) when that is enough to
+/// tell the element apart from its siblings, `tag:nth-child(n)` otherwise.
+pub(crate) fn css_path(element: &ElementRef) -> String {
+    let mut steps = Vec::new();
+    let mut current = Some(*element);
+
+    while let Some(element) = current {
+        let value = element.value();
+        if value.id().is_some() {
+            steps.push(css_step(&element));
+            break;
         }
-    };
 
-    let selected_elements: Vec<_> = html_doc.select(&css_selector).collect();
+        let parent = element.parent().and_then(ElementRef::wrap);
+        let classes = source_classes(&element);
+        let ambiguous = parent.is_some_and(|parent| {
+            parent
+                .children()
+                .filter_map(ElementRef::wrap)
+                .filter(|sibling| {
+                    sibling.value().name() == value.name()
+                        && classes
+                            .iter()
+                            .all(|class| sibling.value().has_class(class, CaseSensitive))
+                })
+                .count()
+                > 1
+        });
 
-    let has_get_attr = text_functions
-        .iter()
-        .any(|f| matches!(f, TextFunction::GetAttribute { .. }));
+        if ambiguous {
+            let nth = element.prev_siblings().filter(|node| node.value().is_element()).count() + 1;
+            steps.push(format!("{}:nth-child({})", value.name(), nth));
+        } else {
+            steps.push(css_step(&element));
+        }
+        current = parent;
+    }
 
-    let mut results: Vec<String> = if has_get_attr {
-        selected_elements
-            .iter()
-            .flat_map(|element| {
-                for function in &text_functions {
-                    if let TextFunction::GetAttribute { attribute } = function {
-                        return vec![element
-                            .value()
-                            .attr(attribute)
-                            .unwrap_or("")
-                            .to_string()];
+    steps.reverse();
+    steps.join(" > ")
+}
+
+/// The tag of `element` with its id (`div#main`) or, without one, its
+/// classes (`span.price.old`).
+pub(crate) fn css_step(element: &ElementRef) -> String {
+    let value = element.value();
+    let mut step = value.name().to_string();
+    if let Some(id) = value.id() {
+        step.push('#');
+        step.push_str(&css_identifier(id));
+    } else {
+        for class in source_classes(element) {
+            step.push('.');
+            step.push_str(&css_identifier(class));
+        }
+    }
+    step
+}
+
+/// The classes of `element` in source order (`classes()` order is
+/// unspecified), without duplicates.
+fn source_classes<'a>(element: &ElementRef<'a>) -> Vec<&'a str> {
+    let mut classes: Vec<&str> = Vec::new();
+    for class in element.value().attr("class").unwrap_or_default().split_ascii_whitespace() {
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    classes
+}
+
+/// Escape `name` for use as a CSS identifier (class name or id).
+pub(crate) fn css_identifier(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for (i, c) in name.chars().enumerate() {
+        let leading_digit = c.is_ascii_digit()
+            && (i == 0 || (i == 1 && name.starts_with('-')));
+        if leading_digit {
+            escaped.push_str(&format!("\\{:x} ", c as u32));
+        } else if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+            escaped.push(c);
+        } else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+// ─── Element text ───────────────────────────────────────────────────────────
+
+/// Flatten a matched element into its result text per `options`.
+fn element_text(element: &ElementRef, options: &TextOptions) -> String {
+    let parts: Vec<&str> = if options.include_alt_text || options.exclude_script_text {
+        let mut parts = Vec::new();
+        // Id of the excluded element whose subtree is being skipped.
+        let mut skipping = None;
+        for edge in element.traverse() {
+            match edge {
+                Edge::Open(node) if skipping.is_none() => match node.value() {
+                    Node::Text(text) => parts.push(&**text),
+                    Node::Element(el)
+                        if options.exclude_script_text
+                            && node.id() != element.id()
+                            && NON_CONTENT_TAGS.contains(&el.name()) =>
+                    {
+                        skipping = Some(node.id());
                     }
-                }
-                vec![String::new()]
-            })
-            .filter(|text| !text.is_empty())
-            .collect()
+                    Node::Element(el)
+                        if options.include_alt_text
+                            && matches!(el.name(), "img" | "area" | "input") =>
+                    {
+                        parts.extend(el.attr("alt").or_else(|| el.attr("title")));
+                    }
+                    _ => {}
+                },
+                Edge::Close(node) if skipping == Some(node.id()) => skipping = None,
+                _ => {}
+            }
+        }
+        parts
     } else {
-        selected_elements
-            .iter()
-            .map(|element| {
-                element
-                    .text()
-                    .collect::<Vec<_>>()
-                    .join(" ")
-                    .trim()
-                    .to_string()
-            })
-            .filter(|text| !text.is_empty())
-            .collect()
+        element.text().collect()
     };
 
-    // Apply non-get-attr functions.
-    if !text_functions.is_empty() {
-        let fns_to_apply: Vec<_> = if has_get_attr {
-            text_functions
-                .iter()
-                .filter(|f| !matches!(f, TextFunction::GetAttribute { .. }))
-                .cloned()
-                .collect()
-        } else {
-            text_functions
-        };
-        if !fns_to_apply.is_empty() {
-            results = functions::apply_text_functions(results, &fns_to_apply);
+    let joined = parts.join(&options.separator);
+    if options.trim {
+        joined.trim().to_string()
+    } else {
+        joined
+    }
+}
+
+/// Default flattening used for text pseudo-selector matching, independent of
+/// the configured [`TextOptions`] so selectors match the same everywhere.
+pub(crate) fn match_text(element: &ElementRef) -> String {
+    element.text().collect::<Vec<_>>().join(" ").trim().to_string()
+}
+
+// ─── Standard CSS selector processing ───────────────────────────────────────
+
+/// Standard CSS selector processing (no text pseudo-selectors).
+fn process_standard(
+    css_selector: &Selector,
+    text_functions: &[TextFunction],
+    content_item: &ContentItem,
+    text_options: &TextOptions,
+    limit: usize,
+) -> Vec<String> {
+    // Use the shared, already-parsed HTML document.
+    let html_doc = content_item.html();
+
+    let selected_elements: Vec<_> = html_doc
+        .select(css_selector)
+        .take_while(|_| !cancel::interrupted())
+        .collect();
+    element_results(&selected_elements, text_functions, text_options, limit)
+}
+
+/// Run several plain selectors against one content item in a single walk of
+/// its document, returning one result set per selector, in order.
+///
+/// Each result set is identical to running its selector on its own; the
+/// document is visited once instead of once per selector.
+pub(crate) fn run_batch(
+    selectors: &[(&Selector, &[TextFunction])],
+    content_item: &ContentItem,
+    text_options: &TextOptions,
+) -> Vec<Vec<String>> {
+    let html_doc = content_item.html();
+
+    // Same visiting order and root exclusion as `Html::select`.
+    let mut matched: Vec<Vec<ElementRef>> = vec![Vec::new(); selectors.len()];
+    for element in html_doc.tree.nodes().filter_map(ElementRef::wrap) {
+        if element.parent().is_none() {
+            continue;
+        }
+        for ((selector, _), elements) in selectors.iter().zip(&mut matched) {
+            if selector.matches(&element) {
+                elements.push(element);
+            }
         }
     }
 
-    results
+    selectors
+        .iter()
+        .zip(&matched)
+        .map(|((_, text_functions), elements)| {
+            element_results(elements, text_functions, text_options, usize::MAX)
+        })
+        .collect()
+}
+
+/// Turn selected elements into results: each element's text, run through
+/// the function chain with the element kept alongside for `get-attr`.
+fn element_results(
+    selected_elements: &[ElementRef],
+    text_functions: &[TextFunction],
+    text_options: &TextOptions,
+    limit: usize,
+) -> Vec<String> {
+    element_entries(selected_elements, text_functions, text_options, limit)
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect()
+}
+
+/// Like [`element_results`], keeping each result's source element (`None`
+/// once a fold or `if-empty` has produced it).
+///
+/// `limit` caps the results of an empty chain; a chain may fold or filter,
+/// so it always sees every element.
+fn element_entries<'a>(
+    selected_elements: &[ElementRef<'a>],
+    text_functions: &[TextFunction],
+    text_options: &TextOptions,
+    limit: usize,
+) -> Vec<(Option<ElementRef<'a>>, String)> {
+    if text_functions.is_empty() {
+        return selected_elements
+            .iter()
+            .take_while(|_| !cancel::interrupted())
+            .map(|element| (Some(*element), element_text(element, text_options)))
+            .filter(|(_, text)| !text.is_empty())
+            .take(limit)
+            .collect();
+    }
+
+    // A leading `get-attr` replaces the text before anything reads it.
+    let reads_attribute_first = matches!(
+        text_functions.first(),
+        Some(TextFunction::GetAttribute { .. } | TextFunction::GetAttributes { .. })
+    );
+    let entries = selected_elements
+        .iter()
+        .take_while(|_| !cancel::interrupted())
+        .map(|element| {
+            let text = if reads_attribute_first {
+                String::new()
+            } else {
+                element_text(element, text_options)
+            };
+            (Some(*element), text)
+        })
+        .collect();
+
+    functions::apply_chain(entries, text_functions, &Elements(text_options))
+}
+
+/// Matched elements as the source of chain values: `get-attr` reads their
+/// attributes, and navigation moves to a related element and its text.
+struct Elements<'o>(&'o TextOptions);
+
+impl<'a> ValueSource<ElementRef<'a>> for Elements<'_> {
+    fn elements(&self) -> bool {
+        true
+    }
+
+    fn attribute(&self, element: ElementRef<'a>, name: &str) -> Option<String> {
+        attribute(&element, name).map(str::to_string)
+    }
+
+    fn navigate(
+        &self,
+        element: ElementRef<'a>,
+        step: &Navigation,
+    ) -> Option<(ElementRef<'a>, String)> {
+        navigate(&element, step).map(|to| (to, element_text(&to, self.0)))
+    }
+}
+
+/// The value of `element`'s attribute `name`, for `get-attr`. Embedded SVG
+/// needs two allowances: a namespaced attribute (`xlink:href`) matches by
+/// its local name as well, and camelCase names (`viewBox`) match in any
+/// case.
+fn attribute<'a>(element: &ElementRef<'a>, name: &str) -> Option<&'a str> {
+    let element = element.value();
+    if let Some(value) = element.attr(name) {
+        return Some(value);
+    }
+    let local = name.rsplit(':').next().unwrap_or(name);
+    element
+        .attrs()
+        .find(|(key, _)| *key == local)
+        .or_else(|| element.attrs().find(|(key, _)| key.eq_ignore_ascii_case(local)))
+        .map(|(_, value)| value)
+}
+
+// ─── Navigation ─────────────────────────────────────────────────────────────
+
+/// The element a navigation step (`closest`, `following`, …) reaches from
+/// `element`, if any.
+fn navigate<'a>(element: &ElementRef<'a>, step: &Navigation) -> Option<ElementRef<'a>> {
+    // Selectors were checked when the query was prepared; one that does not
+    // compile reaches no element.
+    let selector = match step.selector() {
+        Some(selector) => Some(cached_selector(selector)?),
+        None => None,
+    };
+    let qualifies = |candidate: &ElementRef| {
+        selector
+            .as_ref()
+            .is_none_or(|selector| selector.matches(candidate))
+    };
+    match step {
+        Navigation::Closest(_) => std::iter::once(*element)
+            .chain(element.ancestors().filter_map(ElementRef::wrap))
+            .find(|candidate| qualifies(candidate)),
+        Navigation::Parent => element.parent().and_then(ElementRef::wrap),
+        Navigation::FollowingSibling(_) => element
+            .next_siblings()
+            .filter_map(ElementRef::wrap)
+            .find(|candidate| qualifies(candidate)),
+        Navigation::PrecedingSibling(_) => element
+            .prev_siblings()
+            .filter_map(ElementRef::wrap)
+            .find(|candidate| qualifies(candidate)),
+        // Siblings of the element and of each ancestor, nearest first; each
+        // sibling's subtree is searched in document order.
+        Navigation::Following(_) => std::iter::once(**element)
+            .chain(element.ancestors())
+            .flat_map(|node| node.next_siblings())
+            .flat_map(|sibling| sibling.descendants())
+            .filter_map(ElementRef::wrap)
+            .find(|candidate| qualifies(candidate)),
+        // The same walk backwards: earlier siblings, each subtree last
+        // element first.
+        Navigation::Preceding(_) => std::iter::once(**element)
+            .chain(element.ancestors())
+            .flat_map(|node| node.prev_siblings())
+            .find_map(|sibling| {
+                sibling
+                    .descendants()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|candidate| qualifies(candidate))
+                    .last()
+            }),
+    }
 }
 
 // ─── Text pseudo-selector processing ────────────────────────────────────────
 
 /// Process CSS selectors with text pseudo-selectors (two-stage approach).
 fn process_with_text_selectors(
-    selector_with_functions: &str,
+    parsed: &ParsedCssSelector,
+    text_functions: &[TextFunction],
     content_item: &ContentItem,
+    text_options: &TextOptions,
+    limit: usize,
 ) -> Vec<String> {
-    let parsed = parse_with_text_selectors(selector_with_functions);
+    // Use the shared, already-parsed HTML document.
+    let html_doc = content_item.html();
+    let final_elements = pseudo_elements(parsed, content_item, &html_doc);
+    element_results(&final_elements, text_functions, text_options, limit)
+}
 
+/// The elements a text pseudo-selector matches in `html_doc`, the content
+/// item's parsed document.
+fn pseudo_elements<'a>(
+    parsed: &ParsedCssSelector,
+    content_item: &ContentItem,
+    html_doc: &'a Html,
+) -> Vec<ElementRef<'a>> {
     // Get cached element data (populates cache as a side-effect).
     let cached_data = if !parsed.base_selector.is_empty() {
         get_cached_elements_data(&parsed.base_selector, content_item)
@@ -182,16 +667,13 @@ fn process_with_text_selectors(
         Vec::new()
     };
 
-    // Use the shared, already-parsed HTML document.
-    let html_doc = content_item.html();
-
     // Stage 1: Resolve base elements.
     let (base_elements, element_texts): (Vec<_>, Vec<_>) = if parsed.base_selector.is_empty() {
         let star = cached_selector("*").expect("'*' is a valid selector");
         let elements: Vec<_> = html_doc.select(&star).collect();
         let texts: Vec<_> = elements
             .iter()
-            .map(|e| e.text().collect::<Vec<_>>().join(" ").trim().to_string())
+            .map(match_text)
             .collect();
         (elements, texts)
     } else if !cached_data.is_empty() {
@@ -209,7 +691,7 @@ fn process_with_text_selectors(
                 (elements, texts)
             }
             None => {
-                warn!("Invalid base CSS selector '{}'", parsed.base_selector);
+                warning!(InvalidQuery, "Invalid base CSS selector '{}'", parsed.base_selector);
                 return vec![];
             }
         }
@@ -219,12 +701,12 @@ fn process_with_text_selectors(
                 let elements: Vec<_> = html_doc.select(&selector).collect();
                 let texts: Vec<_> = elements
                     .iter()
-                    .map(|e| e.text().collect::<Vec<_>>().join(" ").trim().to_string())
+                    .map(match_text)
                     .collect();
                 (elements, texts)
             }
             None => {
-                warn!("Invalid base CSS selector '{}'", parsed.base_selector);
+                warning!(InvalidQuery, "Invalid base CSS selector '{}'", parsed.base_selector);
                 return vec![];
             }
         }
@@ -235,13 +717,7 @@ fn process_with_text_selectors(
         base_elements
             .into_iter()
             .zip(element_texts)
-            .filter(|(_, text_content)| match text_pseudo {
-                TextPseudoSelector::HasText(t) => text_content.contains(t.as_str()),
-                TextPseudoSelector::ContainsText(t) => text_content.contains(t.as_str()),
-                TextPseudoSelector::TextEquals(t) => text_content == t,
-                TextPseudoSelector::TextStarts(t) => text_content.starts_with(t.as_str()),
-                TextPseudoSelector::TextEnds(t) => text_content.ends_with(t.as_str()),
-            })
+            .filter(|(_, text_content)| text_pseudo.matches(text_content))
             .map(|(element, _)| element)
             .collect()
     } else {
@@ -253,27 +729,7 @@ fn process_with_text_selectors(
     // combinators — so idioms like `span:text-equals(Label) + span` work
     // (previously the post was descendant-only and a leading combinator was
     // silently dropped, returning nothing).
-    let final_elements = apply_post_selector(&html_doc, filtered_elements, &parsed.post_selector);
-
-    // Extract text from final elements.
-    let mut results: Vec<String> = final_elements
-        .iter()
-        .map(|element| {
-            element
-                .text()
-                .collect::<Vec<_>>()
-                .join(" ")
-                .trim()
-                .to_string()
-        })
-        .filter(|text| !text.is_empty())
-        .collect();
-
-    if !parsed.functions.is_empty() {
-        results = functions::apply_text_functions(results, &parsed.functions);
-    }
-
-    results
+    apply_post_selector(html_doc, filtered_elements, &parsed.post_selector)
 }
 
 /// Apply a post-selector to the text-matched elements, honouring a leading
@@ -304,7 +760,7 @@ fn apply_post_selector<'a>(
     let selector = match cached_selector(rest) {
         Some(s) => s,
         None => {
-            warn!("Invalid post CSS selector '{}'", rest);
+            warning!(InvalidQuery, "Invalid post CSS selector '{}'", rest);
             return Vec::new();
         }
     };
@@ -340,6 +796,292 @@ fn apply_post_selector<'a>(
         .collect()
 }
 
+// ─── Functional pseudo-class fallback ───────────────────────────────────────
+
+/// A selector list matched element by element, for selectors `scraper`
+/// cannot evaluate. Each compound's plain part (`div.row`, `a[href]`,
+/// `li:first-child`) is still matched by `scraper`; `:has()`, `:is()`,
+/// `:where()`, `:not()`, and the text pseudo-selectors are evaluated here,
+/// to any nesting depth.
+#[derive(Debug, Clone)]
+pub(crate) struct SelectorList(Vec<ComplexSelector>);
+
+/// Compounds joined by combinators (`' '`, `>`, `+`, `~`), left to right.
+/// The first combinator only means something in a `:has()` argument, where
+/// it relates the first compound to the `:has()` subject.
+#[derive(Debug, Clone)]
+struct ComplexSelector(Vec<(char, Compound)>);
+
+#[derive(Debug, Clone)]
+struct Compound {
+    /// The plain part, `None` when the compound is only pseudo-classes
+    /// evaluated here.
+    plain: Option<Selector>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    /// `:is()` and `:where()` (specificity plays no part in matching).
+    Is(SelectorList),
+    Not(SelectorList),
+    Has(SelectorList),
+    Text(TextPseudoSelector),
+}
+
+impl SelectorList {
+    /// Parse a selector list. `None` when a part is invalid.
+    fn parse(input: &str) -> Option<Self> {
+        Self::parse_list(input, false)
+    }
+
+    /// With `relative`, each selector may start with a combinator, as in
+    /// `:has(> img)`.
+    fn parse_list(input: &str, relative: bool) -> Option<Self> {
+        split_top_level(input, |c| c == ',')
+            .into_iter()
+            .map(|part| ComplexSelector::parse(part, relative))
+            .collect::<Option<Vec<_>>>()
+            .filter(|complexes| !complexes.is_empty())
+            .map(SelectorList)
+    }
+
+    /// The elements of `document` the list matches, in document order,
+    /// visited like `Html::select`.
+    fn select<'a>(&self, document: &'a Html) -> Vec<ElementRef<'a>> {
+        document
+            .tree
+            .nodes()
+            .filter_map(ElementRef::wrap)
+            .filter(|element| element.parent().is_some() && self.matches(element))
+            .collect()
+    }
+
+    fn matches(&self, element: &ElementRef) -> bool {
+        self.0.iter().any(|complex| complex.matches(element, complex.0.len() - 1))
+    }
+
+    /// Whether some element related to `anchor` matches one of the
+    /// (relative) selectors.
+    fn has(&self, anchor: &ElementRef) -> bool {
+        self.0.iter().any(|complex| complex.reaches(anchor, 0))
+    }
+}
+
+impl ComplexSelector {
+    fn parse(input: &str, relative: bool) -> Option<Self> {
+        let mut steps = Vec::new();
+        let mut combinator = None;
+        for token in split_top_level(input, |c| c.is_whitespace() || matches!(c, '>' | '+' | '~'))
+        {
+            match token {
+                ">" | "+" | "~" => {
+                    // Two explicit combinators in a row, or one leading a
+                    // selector outside `:has()`.
+                    if matches!(combinator, Some('>' | '+' | '~'))
+                        || (steps.is_empty() && !relative)
+                    {
+                        return None;
+                    }
+                    combinator = token.chars().next();
+                }
+                _ => {
+                    steps.push((combinator.unwrap_or(' '), Compound::parse(token)?));
+                    combinator = None;
+                }
+            }
+        }
+        // A trailing combinator has nothing to apply to.
+        (combinator.is_none() && !steps.is_empty()).then_some(ComplexSelector(steps))
+    }
+
+    /// Whether `element` matches the compound at `step`, with the compounds
+    /// before it matched by the elements the combinators lead back to.
+    fn matches(&self, element: &ElementRef, step: usize) -> bool {
+        let (_, compound) = &self.0[step];
+        if !compound.matches(element) {
+            return false;
+        }
+        if step == 0 {
+            return true;
+        }
+        let combinator = self.0[step].0;
+        let mut candidates: Box<dyn Iterator<Item = ElementRef>> = match combinator {
+            '>' => Box::new(element.parent().and_then(ElementRef::wrap).into_iter()),
+            '+' => Box::new(element.prev_siblings().filter_map(ElementRef::wrap).take(1)),
+            '~' => Box::new(element.prev_siblings().filter_map(ElementRef::wrap)),
+            _ => Box::new(element.ancestors().filter_map(ElementRef::wrap)),
+        };
+        candidates.any(|candidate| self.matches(&candidate, step - 1))
+    }
+
+    /// Whether an element reached from `anchor` through the combinator at
+    /// `step` matches that compound and, from there, the rest.
+    fn reaches(&self, anchor: &ElementRef, step: usize) -> bool {
+        let (combinator, compound) = &self.0[step];
+        let mut candidates: Box<dyn Iterator<Item = ElementRef>> = match combinator {
+            '>' => Box::new(anchor.children().filter_map(ElementRef::wrap)),
+            '+' => Box::new(anchor.next_siblings().filter_map(ElementRef::wrap).take(1)),
+            '~' => Box::new(anchor.next_siblings().filter_map(ElementRef::wrap)),
+            _ => Box::new(anchor.descendants().skip(1).filter_map(ElementRef::wrap)),
+        };
+        candidates.any(|candidate| {
+            compound.matches(&candidate)
+                && (step + 1 == self.0.len() || self.reaches(&candidate, step + 1))
+        })
+    }
+}
+
+impl Compound {
+    /// Split the functional pseudo-classes and text pseudo-selectors out of
+    /// a compound; what remains must be a selector `scraper` accepts.
+    fn parse(input: &str) -> Option<Self> {
+        let mut plain = String::new();
+        let mut filters = Vec::new();
+        let mut rest = input;
+        while let Some(start) = rest.find(':') {
+            let name = FUNCTIONAL_PSEUDO_CLASSES
+                .iter()
+                .chain(PSEUDO_PATTERNS.iter())
+                .find(|name| rest[start..].starts_with(**name));
+            let Some(name) = name else {
+                // A plain pseudo-class (`:first-child`, `:nth-child(2)`).
+                let end = start + 1 + rest[start + 1..].find([':', '(']).unwrap_or(
+                    rest.len() - start - 1,
+                );
+                let end = if rest[end..].starts_with('(') {
+                    closing_paren(rest, end)? + 1
+                } else {
+                    end
+                };
+                plain.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            };
+            let open = start + name.len() - 1;
+            let close = closing_paren(rest, open)?;
+            let argument = rest[open + 1..close].trim();
+            filters.push(match *name {
+                ":has(" => Filter::Has(SelectorList::parse_list(argument, true)?),
+                ":is(" | ":where(" => Filter::Is(SelectorList::parse(argument)?),
+                ":not(" => Filter::Not(SelectorList::parse(argument)?),
+                _ => Filter::Text(text_pseudo_selector(name, argument)?),
+            });
+            plain.push_str(&rest[..start]);
+            rest = &rest[close + 1..];
+        }
+        plain.push_str(rest);
+
+        let plain = match plain.trim() {
+            "" if !filters.is_empty() => None,
+            "" => return None,
+            plain => Some(cached_selector(plain)?),
+        };
+        Some(Compound { plain, filters })
+    }
+
+    fn matches(&self, element: &ElementRef) -> bool {
+        self.plain.as_ref().is_none_or(|plain| plain.matches(element))
+            && self.filters.iter().all(|filter| match filter {
+                Filter::Is(list) => list.matches(element),
+                Filter::Not(list) => !list.matches(element),
+                Filter::Has(list) => list.has(element),
+                Filter::Text(pseudo) => pseudo.matches(&match_text(element)),
+            })
+    }
+}
+
+/// The text pseudo-selector `name` (`":text-equals("` …) with `argument`.
+/// `None` for an unknown name or a malformed `:text-fuzzy()` argument list.
+fn text_pseudo_selector(name: &str, argument: &str) -> Option<TextPseudoSelector> {
+    if name == ":text-fuzzy(" {
+        return text_fuzzy(argument);
+    }
+    let text = pseudo_argument(argument);
+    Some(match name {
+        ":has-text(" => TextPseudoSelector::HasText(text),
+        ":contains-text(" => TextPseudoSelector::ContainsText(text),
+        ":text-equals(" => TextPseudoSelector::TextEquals(text),
+        ":text-starts(" => TextPseudoSelector::TextStarts(text),
+        ":text-ends(" => TextPseudoSelector::TextEnds(text),
+        _ => return None,
+    })
+}
+
+/// `:text-fuzzy('text')` or `:text-fuzzy('text', threshold)`, the threshold
+/// a number from 0 to 1.
+fn text_fuzzy(argument: &str) -> Option<TextPseudoSelector> {
+    let (text, threshold) = match split_top_level(argument, |c| c == ',')[..] {
+        [text] => (text, DEFAULT_FUZZY_THRESHOLD),
+        [text, threshold] => (text, threshold.parse().ok()?),
+        _ => return None,
+    };
+    (0.0..=1.0)
+        .contains(&threshold)
+        .then(|| TextPseudoSelector::TextFuzzy {
+            text: pseudo_argument(text),
+            threshold,
+        })
+}
+
+/// The index of the `)` closing the `(` at `open`, skipping quoted text.
+pub(crate) fn closing_paren(input: &str, open: usize) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' => i = functions::closing_quote(bytes, i)?,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Split `input` at the characters `is_separator` accepts outside
+/// parentheses, brackets, and quotes. Separators other than whitespace and
+/// `,` are kept as their own tokens; empty tokens are dropped.
+pub(crate) fn split_top_level(input: &str, is_separator: impl Fn(char) -> bool) -> Vec<&str> {
+    let bytes = input.as_bytes();
+    let mut tokens = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        match c {
+            '\'' | '"' => {
+                i = functions::closing_quote(bytes, i).unwrap_or(bytes.len() - 1);
+            }
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && c.is_ascii() && is_separator(c) => {
+                tokens.push(&input[start..i]);
+                if !c.is_whitespace() && c != ',' {
+                    tokens.push(&input[i..=i]);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    tokens.push(&input[start..]);
+    tokens
+        .into_iter()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
 // ─── Element-text caching ───────────────────────────────────────────────────
 
 /// Retrieve or populate the element-text cache for a base selector.
@@ -362,15 +1104,7 @@ fn get_cached_elements_data(
         let cache_data: Vec<_> = elements
             .iter()
             .enumerate()
-            .map(|(index, element)| {
-                let text = element
-                    .text()
-                    .collect::<Vec<_>>()
-                    .join(" ")
-                    .trim()
-                    .to_string();
-                (index, text)
-            })
+            .map(|(index, element)| (index, match_text(element)))
             .collect();
 
         let mut cache = content_item.element_text_cache.borrow_mut();
@@ -383,11 +1117,23 @@ fn get_cached_elements_data(
 
 // ─── Selector parsing ───────────────────────────────────────────────────────
 
-/// Parse a CSS selector string that may contain text pseudo-selectors and
-/// a `>>` function chain into a structured representation.
-fn parse_with_text_selectors(input: &str) -> ParsedCssSelector {
-    let (base_input, text_functions) = functions::split_functions(input);
+/// The text of a pseudo-selector argument: a quoted string is unquoted and
+/// unescaped (so `'O\'Brien'` and `"O'Brien"` both match `O'Brien`); bare text
+/// is used as written.
+pub(crate) fn pseudo_argument(argument: &str) -> String {
+    let bytes = argument.as_bytes();
+    match bytes.first() {
+        Some(b'\'' | b'"') if functions::closing_quote(bytes, 0) == Some(bytes.len() - 1) => {
+            functions::unescape_argument(&argument[1..argument.len() - 1])
+        }
+        _ => argument.to_string(),
+    }
+}
 
+/// Parse a CSS selector string (without its function chain) that may contain
+/// text pseudo-selectors into a structured representation. `None` when the
+/// pseudo-selector's arguments are malformed.
+fn parse_with_text_selectors(base_input: &str) -> Option<ParsedCssSelector> {
     let mut text_pseudo = None;
     let mut base_selector = String::new();
     let mut post_selector = String::new();
@@ -396,38 +1142,35 @@ fn parse_with_text_selectors(input: &str) -> ParsedCssSelector {
     for pattern in PSEUDO_PATTERNS.iter() {
         if let Some(start_pos) = base_input.find(pattern) {
             let content_start = start_pos + pattern.len();
+            let bytes = base_input.as_bytes();
             let mut paren_count = 1;
             let mut end_pos = content_start;
 
-            for (i, ch) in base_input[content_start..].char_indices() {
-                match ch {
-                    '(' => paren_count += 1,
-                    ')' => {
+            // Quoted text (with `\'` / `\"` / `\\` escapes) may hold parentheses.
+            let mut i = content_start;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\'' | b'"' => {
+                        if let Some(close) = functions::closing_quote(bytes, i) {
+                            i = close;
+                        }
+                    }
+                    b'(' => paren_count += 1,
+                    b')' => {
                         paren_count -= 1;
                         if paren_count == 0 {
-                            end_pos = content_start + i;
+                            end_pos = i;
                             break;
                         }
                     }
                     _ => {}
                 }
+                i += 1;
             }
 
             if paren_count == 0 {
-                let text_content = base_input[content_start..end_pos].trim();
-                let clean_text = text_content
-                    .trim_matches('"')
-                    .trim_matches('\'')
-                    .to_string();
-
-                text_pseudo = Some(match *pattern {
-                    ":has-text(" => TextPseudoSelector::HasText(clean_text),
-                    ":contains-text(" => TextPseudoSelector::ContainsText(clean_text),
-                    ":text-equals(" => TextPseudoSelector::TextEquals(clean_text),
-                    ":text-starts(" => TextPseudoSelector::TextStarts(clean_text),
-                    ":text-ends(" => TextPseudoSelector::TextEnds(clean_text),
-                    _ => continue,
-                });
+                let argument = base_input[content_start..end_pos].trim();
+                text_pseudo = Some(text_pseudo_selector(pattern, argument)?);
 
                 base_selector = base_input[..start_pos].trim().to_string();
                 post_selector = base_input[end_pos + 1..].to_string();
@@ -441,12 +1184,11 @@ fn parse_with_text_selectors(input: &str) -> ParsedCssSelector {
         base_selector = base_input.to_string();
     }
 
-    ParsedCssSelector {
+    Some(ParsedCssSelector {
         base_selector,
         text_pseudo,
         post_selector,
-        functions: text_functions,
-    }
+    })
 }
 
 #[cfg(test)]
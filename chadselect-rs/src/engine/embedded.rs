@@ -0,0 +1,79 @@
+//! Embedded-JSON bridge.
+//!
+//! Modern pages carry their best data as JSON inside the HTML — a
+//! `<script type="application/ld+json">` block or a `window.__DATA__ = {…};`
+//! assignment. An `embedded-json:` query selects those blobs with any other
+//! engine, parses each one, and runs a `json:` stage against it:
+//!
+//! ```text
+//! embedded-json:css:script[type='application/ld+json'] >> json:offers.price
+//! embedded-json:css:div.product >> get-attr('data-json') >> json:sku
+//! ```
+//!
+//! Functions before the `json:` stage belong to the source query; functions
+//! after it apply to the JMESPath results. Without a `json:` stage each blob
+//! is returned whole. Blobs that hold no parseable JSON are skipped.
+
+use jmespath::Expression;
+
+use crate::config::Config;
+use crate::content::ContentItem;
+use crate::engine::json;
+use crate::functions;
+use crate::prepared::PreparedQuery;
+use crate::query::QueryType;
+use crate::warnings::warning;
+
+/// The stage prefix separating the source query from the JMESPath path.
+const JSON_STAGE: &str = "json:";
+
+/// Process an embedded-json expression (`<source query> >> json:<path>`)
+/// against a content item, returning matches.
+pub fn process(expression: &str, content_item: &ContentItem) -> Vec<String> {
+    match PreparedQuery::from_query_type(QueryType::EmbeddedJson(expression.to_string())) {
+        Ok(prepared) => prepared
+            .alternatives()
+            .iter()
+            .flat_map(|alternative| alternative.run(content_item, &Config::default()))
+            .collect(),
+        Err(e) => {
+            warning!(InvalidQuery, "Invalid embedded-json query '{}': {}", expression, e);
+            vec![]
+        }
+    }
+}
+
+/// Split an expression into its source query and the `json:` stage (path plus
+/// its function chain, without the prefix), if any.
+pub(crate) fn split(expression: &str) -> (&str, Option<&str>) {
+    match functions::split_at_stage(expression, JSON_STAGE) {
+        Some((source, stage)) => (source, Some(&stage[JSON_STAGE.len()..])),
+        None => (expression.trim(), None),
+    }
+}
+
+/// Run `path` against every blob that holds JSON, in order.
+pub(crate) fn evaluate(path: &Expression<'static>, blobs: &[String]) -> Vec<String> {
+    blobs
+        .iter()
+        .filter_map(|blob| parse_blob(blob))
+        .flat_map(|data| json::search(path, &data))
+        .collect()
+}
+
+/// Parse the JSON in a blob: the whole blob when it starts with `{` or `[`,
+/// otherwise the first object/array after an `=` (a script assignment).
+/// Trailing text such as `;` is ignored.
+fn parse_blob(blob: &str) -> Option<jmespath::Rcvar> {
+    let blob = blob.trim();
+    let start = if blob.starts_with(['{', '[']) {
+        0
+    } else {
+        let after_assign = blob.find('=').map_or(0, |i| i + 1);
+        after_assign + blob[after_assign..].find(['{', '['])?
+    };
+
+    let mut stream =
+        serde_json::Deserializer::from_str(&blob[start..]).into_iter::<jmespath::Variable>();
+    stream.next()?.ok().map(jmespath::Rcvar::new)
+}
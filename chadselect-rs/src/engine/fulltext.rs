@@ -0,0 +1,430 @@
+//! Full-text search engine.
+//!
+//! Matches words and phrases in a document's text, sentence by sentence, and
+//! returns the matching sentences:
+//!
+//! ```text
+//! text:warranty                         a word
+//! text:"one owner"                      a phrase
+//! text:leather sunroof                  both (AND is implied; `AND` may be written)
+//! text:hybrid OR electric               either (binds looser than AND)
+//! text:civic NEAR/3 miles               within 3 words of each other
+//! ```
+//!
+//! Matching ignores case and punctuation: the text and each term are split
+//! into words of letters and digits. `NEAR` without a distance allows 10
+//! words. Operators are only recognised in upper case; a lower-case `or` is
+//! a search word.
+//!
+//! HTML (and rendered Markdown) is searched in its visible text, block
+//! element by block element (`<p>`, `<li>`, `<td>`, …), so a sentence never
+//! spans two blocks. Plain text is split at blank lines.
+
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use scraper::node::Node;
+use scraper::ElementRef;
+
+use crate::cancel;
+use crate::content::{ContentItem, ContentType};
+use crate::engine::css::css_path;
+use crate::engine::NON_CONTENT_TAGS;
+use crate::functions;
+use crate::result::ResultLocation;
+use crate::warnings::warning;
+
+/// Words `NEAR` allows between its terms when no distance is given.
+const DEFAULT_NEAR_DISTANCE: usize = 10;
+
+/// Elements that start a new block of text.
+const BLOCK_TAGS: [&str; 36] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "caption",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "td",
+    "th",
+    "ul",
+];
+
+/// A compiled full-text query: any of its groups matching a sentence is a
+/// match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FullTextQuery {
+    /// `OR`-separated groups, each a list of clauses that must all match.
+    groups: Vec<Vec<Clause>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Clause {
+    /// A word or phrase, as lowercase words.
+    Term(Vec<String>),
+    /// Two terms at most `distance` words apart, in either order.
+    Near(Vec<String>, Vec<String>, usize),
+}
+
+enum Token {
+    Term(Vec<String>),
+    And,
+    Or,
+    Near(usize),
+}
+
+/// Lowercase words of letters and digits in `text`.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn term(text: &str) -> Result<Vec<String>, String> {
+    let term = words(text);
+    if term.is_empty() {
+        return Err(format!("'{}' has no words to search for", text));
+    }
+    Ok(term)
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+    while !rest.is_empty() {
+        let (token, after) = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| format!("unterminated phrase in '{}'", expression))?;
+            (Token::Term(term(&quoted[..end])?), &quoted[end + 1..])
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == '"')
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let token = match word {
+                "AND" => Token::And,
+                "OR" => Token::Or,
+                "NEAR" => Token::Near(DEFAULT_NEAR_DISTANCE),
+                _ => match word.strip_prefix("NEAR/") {
+                    Some(distance) => Token::Near(
+                        distance
+                            .parse()
+                            .map_err(|_| format!("invalid NEAR distance '{}'", distance))?,
+                    ),
+                    None => Token::Term(term(word)?),
+                },
+            };
+            (token, &rest[end..])
+        };
+        tokens.push(token);
+        rest = after.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Parse a full-text expression (without any function chain).
+pub(crate) fn compile(expression: &str) -> Result<FullTextQuery, String> {
+    let dangling = |operator: &str| format!("'{}' needs a term on each side", operator);
+
+    let mut groups = Vec::new();
+    let mut group: Vec<Clause> = Vec::new();
+    let mut last: Option<Vec<String>> = None;
+    let mut near = None;
+    let mut pending: Option<&str> = None;
+
+    for token in tokenize(expression)? {
+        match token {
+            Token::Term(words) => {
+                match near.take() {
+                    // `a NEAR b NEAR c` keeps `a NEAR b` and adds `b NEAR c`.
+                    Some(distance) => {
+                        let previous = last.take().expect("NEAR follows a term");
+                        if group.last() == Some(&Clause::Term(previous.clone())) {
+                            group.pop();
+                        }
+                        group.push(Clause::Near(previous, words.clone(), distance));
+                    }
+                    None => group.push(Clause::Term(words.clone())),
+                }
+                last = Some(words);
+                pending = None;
+            }
+            Token::And | Token::Or | Token::Near(_) => {
+                let operator = match token {
+                    Token::And => "AND",
+                    Token::Or => "OR",
+                    _ => "NEAR",
+                };
+                if last.is_none() || pending.is_some() {
+                    return Err(dangling(operator));
+                }
+                match token {
+                    Token::Or => {
+                        groups.push(std::mem::take(&mut group));
+                        last = None;
+                    }
+                    Token::Near(distance) => near = Some(distance),
+                    _ => {}
+                }
+                pending = Some(operator);
+            }
+        }
+    }
+
+    if let Some(operator) = pending {
+        return Err(dangling(operator));
+    }
+    if group.is_empty() {
+        return Err("empty full-text query".to_string());
+    }
+    groups.push(group);
+    Ok(FullTextQuery { groups })
+}
+
+/// Where `term` starts in `words`.
+fn positions<'a>(term: &'a [String], words: &'a [String]) -> impl Iterator<Item = usize> + 'a {
+    words
+        .windows(term.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == term)
+        .map(|(at, _)| at)
+}
+
+impl Clause {
+    fn matches(&self, words: &[String]) -> bool {
+        match self {
+            Clause::Term(term) => positions(term, words).next().is_some(),
+            Clause::Near(a, b, distance) => positions(a, words).any(|at_a| {
+                positions(b, words).any(|at_b| {
+                    let gap = if at_a <= at_b {
+                        at_b.saturating_sub(at_a + a.len())
+                    } else {
+                        at_a.saturating_sub(at_b + b.len())
+                    };
+                    gap <= *distance
+                })
+            }),
+        }
+    }
+}
+
+impl FullTextQuery {
+    /// Whether `sentence` matches.
+    fn matches(&self, sentence: &str) -> bool {
+        let words = words(sentence);
+        self.groups
+            .iter()
+            .any(|group| group.iter().all(|clause| clause.matches(&words)))
+    }
+}
+
+/// The sentences of `text`, trimmed, with the byte offset each starts at.
+/// A sentence ends after `.`, `!`, or `?` followed by whitespace, and at a
+/// blank line.
+fn sentences(text: &str) -> Vec<(usize, &str)> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((at, c)) = chars.next() {
+        let end = match (c, chars.peek()) {
+            ('.' | '!' | '?', Some((_, next))) if next.is_whitespace() => at + c.len_utf8(),
+            ('\n', _)
+                if text[at + 1..]
+                    .trim_start_matches([' ', '\t', '\r'])
+                    .starts_with('\n') =>
+            {
+                at
+            }
+            _ => continue,
+        };
+        sentences.push((start, &text[start..end]));
+        start = end;
+    }
+    sentences.push((start, &text[start..]));
+
+    sentences
+        .into_iter()
+        .filter_map(|(offset, sentence)| {
+            let trimmed = sentence.trim_start();
+            let offset = offset + sentence.len() - trimmed.len();
+            let trimmed = trimmed.trim_end();
+            (!trimmed.is_empty()).then_some((offset, trimmed))
+        })
+        .collect()
+}
+
+/// `text` with runs of whitespace collapsed to one space.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The visible text of an HTML document, one entry per block element (in
+/// order of first text), paired with the element's pre-order rank and
+/// selector path.
+fn html_blocks(content_item: &ContentItem) -> Vec<(Option<(usize, String)>, String)> {
+    let (html, order) = content_item.html_with_order();
+    let mut blocks: Vec<(Option<ElementRef>, String)> = Vec::new();
+    let mut indices: HashMap<Option<NodeId>, usize> = HashMap::new();
+
+    for node in html.tree.root().descendants() {
+        let Node::Text(text) = node.value() else {
+            continue;
+        };
+        let mut block = None;
+        let mut hidden = false;
+        for ancestor in node.ancestors().filter_map(ElementRef::wrap) {
+            let name = ancestor.value().name();
+            if name == "head" || NON_CONTENT_TAGS.contains(&name) {
+                hidden = true;
+                break;
+            }
+            if block.is_none() && BLOCK_TAGS.contains(&name) {
+                block = Some(ancestor);
+            }
+        }
+        if hidden {
+            continue;
+        }
+        let index = *indices
+            .entry(block.map(|element| element.id()))
+            .or_insert_with(|| {
+                blocks.push((block, String::new()));
+                blocks.len() - 1
+            });
+        blocks[index].1.push_str(text);
+    }
+
+    blocks
+        .into_iter()
+        .map(|(block, text)| {
+            let location = block.map(|element| {
+                let position = order.get(&element.id()).map_or(0, |rank| *rank as usize);
+                (position, css_path(&element))
+            });
+            (location, collapse_whitespace(&text))
+        })
+        .collect()
+}
+
+/// Run a compiled query against a content item (no function chain),
+/// returning each matching sentence with its location, at most `limit` of
+/// them.
+pub(crate) fn evaluate_located(
+    query: &FullTextQuery,
+    content_item: &ContentItem,
+    content_index: usize,
+    limit: usize,
+) -> Vec<(ResultLocation, String)> {
+    let mut results = Vec::new();
+    match content_item.content_type {
+        ContentType::Html | ContentType::Markdown => {
+            for (element, block) in html_blocks(content_item) {
+                for (_, sentence) in sentences(&block) {
+                    if cancel::interrupted() || results.len() >= limit {
+                        return results;
+                    }
+                    if query.matches(sentence) {
+                        let location = match &element {
+                            Some((position, css_path)) => ResultLocation {
+                                content_index,
+                                position: Some(*position),
+                                css_path: Some(css_path.clone()),
+                                ..Default::default()
+                            },
+                            None => ResultLocation {
+                                content_index,
+                                ..Default::default()
+                            },
+                        };
+                        results.push((location, sentence.to_string()));
+                    }
+                }
+            }
+        }
+        _ => {
+            let content = &content_item.content;
+            for (offset, sentence) in sentences(content) {
+                if cancel::interrupted() || results.len() >= limit {
+                    break;
+                }
+                if query.matches(sentence) {
+                    let location = ResultLocation::at_span(
+                        content_index,
+                        content,
+                        offset..offset + sentence.len(),
+                    );
+                    results.push((location, collapse_whitespace(sentence)));
+                }
+            }
+        }
+    }
+    results
+}
+
+/// Like [`evaluate_located`], without locations.
+pub(crate) fn evaluate(
+    query: &FullTextQuery,
+    content_item: &ContentItem,
+    limit: usize,
+) -> Vec<String> {
+    evaluate_located(query, content_item, 0, limit)
+        .into_iter()
+        .map(|(_, sentence)| sentence)
+        .collect()
+}
+
+/// Process a full-text expression (potentially with a `>>` function chain)
+/// against a content item, returning the matching sentences.
+pub fn process(expression_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
+    let (expression, text_functions) = functions::split_functions(expression_with_functions);
+
+    let query = match compile(&expression) {
+        Ok(query) => query,
+        Err(e) => {
+            warning!(
+                InvalidQuery,
+                "Invalid full-text query '{}': {}",
+                expression,
+                e
+            );
+            return vec![];
+        }
+    };
+
+    let results = evaluate(&query, content_item, usize::MAX);
+
+    if text_functions.is_empty() {
+        results
+    } else {
+        functions::apply_text_functions(results, &text_functions)
+    }
+}
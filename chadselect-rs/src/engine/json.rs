@@ -1,152 +1,55 @@
 //! JMESPath extraction engine.
 //!
 //! Processes JMESPath expressions against JSON content. The document is parsed
-//! into a JMESPath value tree (`Rc<Variable>`) once and cached per document
-//! (see [`ContentItem::jmespath_value`]); compiled expressions are cached per
-//! thread. Every query then evaluates against the cached tree without
-//! re-converting the document.
-
-use std::cell::RefCell;
-use std::collections::HashMap;
+//! into a JMESPath value tree (`Arc<Variable>`) once and cached per document
+//! (see [`ContentItem::jmespath_value`]); expressions compile and evaluate
+//! through `chadselect_core::engine::json`, which caches them per thread.
+//! Every query then evaluates against the cached tree without re-converting
+//! the document.
 
 use jmespath::Expression;
-use log::warn;
-
-use crate::content::ContentItem;
 
-thread_local! {
-    /// Cache of compiled JMESPath expressions, keyed by the expression string.
-    ///
-    /// `jmespath::compile` parses the expression on every call; the converted
-    /// document tree is already cached per document, but the *expression* was
-    /// recompiled per query (a flat slice of fleet CPU). The crawler runs the
-    /// same expressions across many documents, so compiling once per distinct
-    /// expression (per thread) removes that. `Expression<'static>` is `Clone`
-    /// (cheap — it shares the parsed AST), so we clone out of the cache. Invalid
-    /// expressions cache as `None` to avoid re-parsing / re-warning each call.
-    static COMPILED: RefCell<HashMap<String, Option<Expression<'static>>>> =
-        RefCell::new(HashMap::new());
-}
+pub(crate) use chadselect_core::engine::json::{compiled, search};
 
-/// Compile `path` (or fetch the cached `Expression`). Returns `None` for an
-/// invalid expression, warning once on first compile.
-fn compiled(path: &str) -> Option<Expression<'static>> {
-    COMPILED.with(|c| {
-        if let Some(e) = c.borrow().get(path) {
-            return e.clone();
-        }
-        let compiled = match jmespath::compile(path) {
-            Ok(expr) => Some(expr),
-            Err(e) => {
-                warn!("Invalid JMESPath expression '{}': {}", path, e);
-                None
-            }
-        };
-        c.borrow_mut().insert(path.to_string(), compiled.clone());
-        compiled
-    })
-}
+use crate::content::ContentItem;
+use crate::functions;
+use crate::warnings::warning;
 
-/// Process a JMESPath expression against a content item, returning matches.
+/// Process a JMESPath expression (potentially with a `>>` function chain)
+/// against a content item, returning matches.
 ///
-/// The document is parsed into a JMESPath value tree (`Rc<Variable>`) **once**
+/// The document is parsed into a JMESPath value tree (`Arc<Variable>`) **once**
 /// and cached on the [`ContentItem`]; every query then evaluates against the
 /// cached tree via [`Expression::search_cached`]. This is the key allocation
 /// win: upstream `Expression::search` re-converts the whole document into a
-/// fresh `Rc<Variable>` tree on *every* call (a full serde walk — the dominant
+/// fresh `Arc<Variable>` tree on *every* call (a full serde walk — the dominant
 /// allocation source on JSON-heavy pages). Caching the converted tree collapses
 /// that to a single conversion per document, shared by reference across queries.
-pub fn process(path: &str, content_item: &ContentItem) -> Vec<String> {
-    let mut jmespath_ref = content_item.jmespath_value.borrow_mut();
+pub fn process(path_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
+    let (path, text_functions) = functions::split_functions(path_with_functions);
 
-    if jmespath_ref.is_none() {
-        // Parse the raw content straight into the JMESPath tree (one serde pass),
-        // skipping the intermediate `serde_json::Value` entirely.
-        match jmespath::Variable::from_json(&content_item.content) {
-            Ok(var) => *jmespath_ref = Some(jmespath::Rcvar::new(var)),
-            Err(e) => {
-                warn!("Failed to parse JSON content: {}", e);
-                return vec![];
-            }
-        }
-    }
-
-    let Some(data) = jmespath_ref.as_ref() else {
-        warn!("Failed to access cached JMESPath value");
-        return vec![];
-    };
-
-    let expression = match compiled(path) {
+    let expression = match compiled(&path) {
         Some(expr) => expr,
         None => return vec![],
     };
 
-    let result = match expression.search_cached(data) {
-        Ok(value) => value,
-        Err(e) => {
-            warn!("JMESPath execution failed: {}", e);
-            return vec![];
-        }
-    };
-
-    jmespath_value_to_strings(&result)
-}
-
-/// Recursively convert a JMESPath result into a flat `Vec<String>`.
-#[cfg(test)]
-mod cache_tests {
-    use super::*;
+    let results = evaluate(&expression, content_item);
 
-    /// Guard: JMESPath expressions compile once and are cached per distinct
-    /// expression. Fails (or stops compiling) if the compile cache is removed.
-    #[test]
-    fn expressions_are_compiled_once_and_cached() {
-        COMPILED.with(|c| c.borrow_mut().clear());
-        let _ = compiled("foo.bar");
-        let _ = compiled("foo.bar");
-        assert_eq!(COMPILED.with(|c| c.borrow().len()), 1, "repeated expr must compile once");
-        let _ = compiled("baz[0]");
-        assert_eq!(COMPILED.with(|c| c.borrow().len()), 2, "distinct expr adds one entry");
+    if text_functions.is_empty() {
+        results
+    } else {
+        functions::apply_text_functions(results, &text_functions)
     }
 }
 
-fn jmespath_value_to_strings(value: &jmespath::Variable) -> Vec<String> {
-    match value {
-        jmespath::Variable::Null => vec![],
-        jmespath::Variable::Bool(b) => vec![b.to_string()],
-        jmespath::Variable::Number(n) => vec![n.to_string()],
-        jmespath::Variable::String(s) => vec![s.clone()],
-        jmespath::Variable::Array(arr) => {
-            arr.iter().flat_map(|item| jmespath_value_to_strings(item)).collect()
-        }
-        jmespath::Variable::Object(obj) => {
-            let mut json_parts = Vec::new();
-            json_parts.push("{".to_string());
-            for (i, (key, value)) in obj.iter().enumerate() {
-                if i > 0 {
-                    json_parts.push(",".to_string());
-                }
-                let value_strings = jmespath_value_to_strings(value);
-                let value_str = if value_strings.len() == 1 {
-                    value_strings[0].clone()
-                } else {
-                    format!("[{}]", value_strings.join(","))
-                };
-                json_parts.push(format!(
-                    "\"{}\":{}",
-                    key,
-                    if matches!(&**value, jmespath::Variable::String(_)) {
-                        format!("\"{}\"", value_str)
-                    } else {
-                        value_str
-                    }
-                ));
-            }
-            json_parts.push("}".to_string());
-            vec![json_parts.join("")]
-        }
-        jmespath::Variable::Expref(_) => {
-            vec!["<expression>".to_string()]
+/// Evaluate an already-compiled expression against the item's cached value
+/// tree (no function chain), building the tree on first use.
+pub(crate) fn evaluate(expression: &Expression<'static>, content_item: &ContentItem) -> Vec<String> {
+    match content_item.jmespath() {
+        Ok(data) => search(expression, &data),
+        Err(e) => {
+            warning!(InvalidContent, "Failed to parse JSON content: {}", e);
+            vec![]
         }
     }
 }
@@ -2,11 +2,68 @@
 //!
 //! Each sub-module handles one query engine and exposes a single `process`
 //! function that accepts the engine-specific expression and a [`ContentItem`].
+//!
+//! Each engine is built only with its Cargo feature; see
+//! [`QueryType::missing_feature`](crate::QueryType::missing_feature).
 
+#[cfg(feature = "regex")]
+pub mod bytes;
+#[cfg(feature = "css")]
 pub mod css;
+#[cfg(feature = "json")]
+pub mod embedded;
+#[cfg(feature = "css")]
+pub mod fulltext;
+#[cfg(feature = "json")]
 pub mod json;
+// Always built: contact harvesting, `lines:`, and the pipe functions run
+// regexes too. The feature only decides whether `regex:` queries parse.
+#[cfg_attr(not(feature = "regex"), allow(dead_code))]
 pub mod regex;
+#[cfg(all(feature = "css", feature = "json"))]
+pub mod structured;
+#[cfg(feature = "xpath")]
 pub mod xnode;
+#[cfg(feature = "xpath")]
 pub mod xpath;
+#[cfg(feature = "xpath")]
 pub mod xpath_eval;
+#[cfg(feature = "xpath")]
 pub mod xpath_rewrite;
+#[cfg(feature = "xpath")]
+pub mod xpath_shims;
+
+#[cfg(feature = "css")]
+use std::collections::HashMap;
+
+#[cfg(feature = "css")]
+use ego_tree::NodeId;
+#[cfg(feature = "css")]
+use scraper::Html;
+
+/// Elements whose contents are not page text: skipped by
+/// [`TextOptions::exclude_script_text`](crate::TextOptions::exclude_script_text).
+#[cfg(feature = "css")]
+pub(crate) const NON_CONTENT_TAGS: [&str; 4] = ["script", "style", "noscript", "template"];
+
+/// Pre-order rank of every node, so document-order comparisons are O(1).
+/// Without this, `cmp_document_order` would re-walk to the root per call and
+/// chadpath's per-step nodeset sort would become O(n²).
+///
+/// This map depends only on the document, not the query, so it is built once
+/// per parsed document and cached on the [`ContentItem`](crate::content::ContentItem)
+/// alongside the `Html`. (Rebuilding it per query — the fleet runs hundreds of
+/// selectors per page — was a dominant slice of the post-0.3.x CPU
+/// regression.)
+#[cfg(feature = "css")]
+pub type OrderMap = HashMap<NodeId, u32>;
+
+/// Assign each node a pre-order (document-order) rank in a single pass.
+#[cfg(feature = "css")]
+pub fn build_order(html: &Html) -> OrderMap {
+    let mut map = HashMap::new();
+    for (rank, n) in html.tree.root().descendants().enumerate() {
+        map.insert(n.id(), rank as u32);
+    }
+    map
+}
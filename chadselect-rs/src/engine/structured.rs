@@ -0,0 +1,253 @@
+//! Structured-data extraction engine.
+//!
+//! Collects the standard metadata embedded in an HTML page into one JSON
+//! document — built once and cached per content item — and queries it with
+//! JMESPath, so common fields need no per-site selectors:
+//!
+//! ```text
+//! structured:jsonld[?"@type"=='Product'] | [0].offers.price
+//! structured:microdata[0].name
+//! structured:opengraph.title
+//! ```
+//!
+//! The document has four keys:
+//!
+//! - `jsonld` — every `<script type="application/ld+json">` value, with
+//!   top-level arrays flattened.
+//! - `microdata` — top-level `itemscope` items.
+//! - `rdfa` — top-level RDFa (Lite) `typeof` items.
+//! - `opengraph` — `og:*` meta tags, keyed without the `og:` prefix.
+//!
+//! Microdata and RDFa items are objects with an `@type` and one key per
+//! property; schema.org URLs and `schema:` prefixes are shortened to the bare
+//! name. A repeated property becomes an array and a nested item a nested
+//! object.
+
+use jmespath::Expression;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::{json, Map, Value};
+
+use crate::content::ContentItem;
+use crate::engine::json;
+use crate::functions;
+use crate::warnings::warning;
+
+/// Maximum nesting of items inside items; deeper items are dropped.
+const MAX_ITEM_DEPTH: usize = 32;
+
+/// Prefixes shortened away from types and property names.
+const SCHEMA_PREFIXES: [&str; 3] = ["https://schema.org/", "http://schema.org/", "schema:"];
+
+/// The attributes one item vocabulary uses.
+struct Vocabulary {
+    /// Marks an element as an item.
+    scope: &'static str,
+    /// Names the property an element provides.
+    property: &'static str,
+    /// Holds the item's type.
+    item_type: &'static str,
+}
+
+const MICRODATA: Vocabulary = Vocabulary {
+    scope: "itemscope",
+    property: "itemprop",
+    item_type: "itemtype",
+};
+
+const RDFA: Vocabulary = Vocabulary {
+    scope: "typeof",
+    property: "property",
+    item_type: "typeof",
+};
+
+/// Process a JMESPath expression (potentially with a `>>` function chain)
+/// against a content item's structured-data document, returning matches.
+pub fn process(path_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
+    let (path, text_functions) = functions::split_functions(path_with_functions);
+
+    let Some(expression) = json::compiled(&path) else {
+        return vec![];
+    };
+
+    let results = evaluate(&expression, content_item);
+
+    if text_functions.is_empty() {
+        results
+    } else {
+        functions::apply_text_functions(results, &text_functions)
+    }
+}
+
+/// Evaluate an already-compiled expression against the item's cached
+/// structured-data document (no function chain), building it on first use.
+pub(crate) fn evaluate(
+    expression: &Expression<'static>,
+    content_item: &ContentItem,
+) -> Vec<String> {
+    let mut cached = content_item.structured_value.borrow_mut();
+
+    if cached.is_none() {
+        match jmespath::Variable::from_serializable(extract(&content_item.html())) {
+            Ok(var) => *cached = Some(jmespath::Rcvar::new(var)),
+            Err(e) => {
+                warning!(InvalidContent, "Failed to convert structured data: {}", e);
+                return vec![];
+            }
+        }
+    }
+
+    match cached.as_ref() {
+        Some(data) => json::search(expression, data),
+        None => vec![],
+    }
+}
+
+/// Build the structured-data document for a parsed page.
+pub fn extract(html: &Html) -> Value {
+    json!({
+        "jsonld": json_ld(html),
+        "microdata": items(html, &MICRODATA),
+        "rdfa": items(html, &RDFA),
+        "opengraph": open_graph(html),
+    })
+}
+
+// ─── JSON-LD ────────────────────────────────────────────────────────────────
+
+fn json_ld(html: &Html) -> Vec<Value> {
+    let selector = Selector::parse(r#"script[type="application/ld+json"]"#)
+        .expect("JSON-LD selector is valid");
+
+    let mut values = Vec::new();
+    for script in html.select(&selector) {
+        let text: String = script.text().collect();
+        match serde_json::from_str(text.trim()) {
+            Ok(Value::Array(array)) => values.extend(array),
+            Ok(value) => values.push(value),
+            Err(e) => warning!(InvalidContent, "Skipping invalid JSON-LD block: {}", e),
+        }
+    }
+    values
+}
+
+// ─── Microdata / RDFa ───────────────────────────────────────────────────────
+
+/// Every top-level item: a scope element that is not itself a property.
+fn items(html: &Html, vocabulary: &Vocabulary) -> Vec<Value> {
+    let selector =
+        Selector::parse(&format!("[{}]", vocabulary.scope)).expect("attribute selector is valid");
+
+    html.select(&selector)
+        .filter(|element| element.value().attr(vocabulary.property).is_none())
+        .map(|element| item(element, vocabulary, 0))
+        .collect()
+}
+
+/// Build one item object from its scope element.
+fn item(scope: ElementRef, vocabulary: &Vocabulary, depth: usize) -> Value {
+    let mut object = Map::new();
+    if let Some(item_type) = scope.value().attr(vocabulary.item_type) {
+        if let Some(first) = item_type.split_whitespace().next() {
+            object.insert(
+                "@type".to_string(),
+                Value::String(shorten(first).to_string()),
+            );
+        }
+    }
+
+    // Walk the scope's subtree in document order. A nested item's own
+    // subtree belongs to that item, so it is not descended into here.
+    let mut stack: Vec<_> = scope.children().filter_map(ElementRef::wrap).collect();
+    stack.reverse();
+    while let Some(element) = stack.pop() {
+        let attrs = element.value();
+        let is_scope = attrs.attr(vocabulary.scope).is_some();
+
+        if let Some(names) = attrs.attr(vocabulary.property) {
+            let value = if !is_scope {
+                Value::String(property_value(element))
+            } else if depth < MAX_ITEM_DEPTH {
+                item(element, vocabulary, depth + 1)
+            } else {
+                Value::Null
+            };
+            for name in names.split_whitespace() {
+                insert_property(&mut object, shorten(name), value.clone());
+            }
+        }
+
+        if !is_scope {
+            let start = stack.len();
+            stack.extend(element.children().filter_map(ElementRef::wrap));
+            stack[start..].reverse();
+        }
+    }
+
+    Value::Object(object)
+}
+
+/// The value an element contributes to its property: a `content` attribute,
+/// the element's URL/value attribute, or its whitespace-normalised text.
+fn property_value(element: ElementRef) -> String {
+    let attrs = element.value();
+    let from_attr = match attrs.name() {
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => "src",
+        "a" | "area" | "link" => "href",
+        "object" => "data",
+        "data" | "meter" => "value",
+        "time" => "datetime",
+        _ => "content",
+    };
+
+    attrs
+        .attr("content")
+        .or_else(|| attrs.attr(from_attr))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let text: String = element.text().collect();
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        })
+}
+
+// ─── OpenGraph ──────────────────────────────────────────────────────────────
+
+fn open_graph(html: &Html) -> Value {
+    let selector = Selector::parse("meta[content]").expect("meta selector is valid");
+
+    let mut object = Map::new();
+    for meta in html.select(&selector) {
+        let attrs = meta.value();
+        let key = attrs.attr("property").or_else(|| attrs.attr("name"));
+        if let (Some(name), Some(content)) = (
+            key.and_then(|k| k.strip_prefix("og:")),
+            attrs.attr("content"),
+        ) {
+            insert_property(&mut object, name, Value::String(content.to_string()));
+        }
+    }
+    Value::Object(object)
+}
+
+// ─── Helpers ────────────────────────────────────────────────────────────────
+
+/// Add `value` under `name`, turning a repeated property into an array.
+fn insert_property(object: &mut Map<String, Value>, name: &str, value: Value) {
+    match object.get_mut(name) {
+        None => {
+            object.insert(name.to_string(), value);
+        }
+        Some(Value::Array(values)) => values.push(value),
+        Some(existing) => {
+            let first = existing.take();
+            *existing = Value::Array(vec![first, value]);
+        }
+    }
+}
+
+/// Strip a schema.org URL or `schema:` prefix.
+fn shorten(name: &str) -> &str {
+    SCHEMA_PREFIXES
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(name)
+}
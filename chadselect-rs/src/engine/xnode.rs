@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
+use ego_tree::iter::Edge;
 use ego_tree::NodeId;
 use scraper::node::Node as SNode;
 use scraper::Html;
@@ -28,6 +29,9 @@ use chadpath::item::{Node, NodeType};
 use chadpath::output::OutputDefinition;
 use chadpath::validators::{Schema, ValidationError};
 use chadpath::value::Value;
+
+use crate::cancel;
+use crate::engine::NON_CONTENT_TAGS;
 use chadpath::xdmerror::{Error, ErrorKind};
 use chadpath::xmldecl::{XMLDecl, XMLDeclBuilder, DTD};
 
@@ -41,25 +45,7 @@ enum Loc {
     Attr { owner: NodeId, idx: usize },
 }
 
-/// Pre-order rank of every node, so document-order comparisons are O(1).
-/// Without this, `cmp_document_order` would re-walk to the root per call and
-/// chadpath's per-step nodeset sort would become O(n²).
-///
-/// This map depends only on the document, not the query, so it is built once
-/// per parsed document and cached on the [`ContentItem`](crate::content::ContentItem)
-/// alongside the `Html` — see [`ENode::root_with_order`]. (Rebuilding it per
-/// query — the fleet runs hundreds of selectors per page — was a dominant slice
-/// of the post-0.3.x CPU regression.)
-pub type OrderMap = HashMap<NodeId, u32>;
-
-/// Assign each node a pre-order (document-order) rank in a single pass.
-pub fn build_order(html: &Html) -> OrderMap {
-    let mut map = HashMap::new();
-    for (rank, n) in html.tree.root().descendants().enumerate() {
-        map.insert(n.id(), rank as u32);
-    }
-    map
-}
+pub use crate::engine::{build_order, OrderMap};
 
 /// An owned handle to a node in a `scraper`-parsed document. Cheap to clone
 /// (two `Rc` bumps + a small locator).
@@ -145,6 +131,44 @@ impl ENode {
         }
     }
 
+    /// String-value of an element/document node with the text of
+    /// [`NON_CONTENT_TAGS`] descendants left out. `None` for other nodes,
+    /// whose string-value is unaffected.
+    pub(crate) fn content_text(&self) -> Option<String> {
+        let Loc::Node(id) = self.loc else {
+            return None;
+        };
+        let nref = self.doc.tree.get(id)?;
+        if !matches!(nref.value(), SNode::Element(_) | SNode::Document | SNode::Fragment) {
+            return None;
+        }
+
+        let mut s = String::new();
+        let mut skipping = None;
+        for edge in nref.traverse() {
+            match edge {
+                Edge::Open(d) if skipping.is_none() => match d.value() {
+                    SNode::Text(t) => s.push_str(&t.text),
+                    SNode::Element(e) if d.id() != id && NON_CONTENT_TAGS.contains(&e.name()) => {
+                        skipping = Some(d.id());
+                    }
+                    _ => {}
+                },
+                Edge::Close(d) if skipping == Some(d.id()) => skipping = None,
+                _ => {}
+            }
+        }
+        Some(s)
+    }
+
+    /// The tree node this is, or `None` for an attribute.
+    pub(crate) fn node_id(&self) -> Option<NodeId> {
+        match self.loc {
+            Loc::Node(id) => Some(id),
+            Loc::Attr { .. } => None,
+        }
+    }
+
     /// Construct a sibling/parent-chain iterator that walks `id → step(id) → …`
     /// lazily, yielding owned `ENode`s without materialising a `Vec`. `step`
     /// returns the next node id in the chain (or `None` to stop).
@@ -464,6 +488,11 @@ impl Node for ENode {
         // Lazy: first child, then the next-sibling chain — no intermediate Vec.
         // `child_iter` is hit per element by `text()`/child-step predicates over
         // a `//*` node set, so avoiding a per-call allocation matters.
+        // An interrupted query sees no more children, so a runaway expression
+        // winds down instead of finishing its walk.
+        if cancel::interrupted() {
+            return ENodeIter::Empty;
+        }
         let first = match self.loc {
             Loc::Node(id) => match self.doc.tree.get(id).map(|n| n.value()) {
                 Some(SNode::Document) | Some(SNode::Fragment) | Some(SNode::Element(_)) => {
@@ -491,8 +520,8 @@ impl Node for ENode {
         // self). For `//` this is the whole-document walk, so not buffering all
         // N nodes into a Vec up front is the biggest single allocation saving.
         let root = match self.loc {
-            Loc::Node(id) => id,
-            Loc::Attr { .. } => return ENodeIter::Empty,
+            Loc::Node(id) if !cancel::interrupted() => id,
+            _ => return ENodeIter::Empty,
         };
         ENodeIter::Preorder {
             doc: self.doc.clone(),
@@ -544,7 +573,8 @@ impl Node for ENode {
         if let Loc::Node(id) = self.loc {
             if let Some(nref) = self.doc.tree.get(id) {
                 if let SNode::Element(el) = nref.value() {
-                    if let Some(val) = el.attr(&local) {
+                    // By local name, so `xlink:href` is `@href`.
+                    if let Some((_, val)) = el.attrs().find(|(k, _)| *k == local) {
                         return Rc::new(Value::from(val));
                     }
                 }
@@ -6,11 +6,17 @@
 //! backend was replaced because its tree-build was quadratic in memory and time
 //! on real-world and adversarial HTML.
 
-use log::warn;
+use std::rc::Rc;
 
+use ego_tree::NodeId;
+use scraper::ElementRef;
+
+use crate::config::TextOptions;
 use crate::content::ContentItem;
-use crate::engine::{xpath_eval, xpath_rewrite};
-use crate::functions;
+use crate::engine::xpath_eval::CompiledXPath;
+use crate::engine::{css, xpath_eval, xpath_rewrite, xpath_shims};
+use crate::functions::{self, TextFunction};
+use crate::warnings::warning;
 
 /// Maximum `(`/`[` nesting we hand to chadpath's parser. Beyond this we refuse
 /// the expression (warn + empty) rather than risk a stack overflow, because
@@ -39,30 +45,131 @@ const MAX_NESTING_DEPTH: usize = 16;
 pub fn process(xpath_with_functions: &str, content_item: &ContentItem) -> Vec<String> {
     let (raw_expr, text_functions) = functions::split_functions(xpath_with_functions);
 
+    let mut results = match compile(&raw_expr) {
+        Some(compiled) => {
+            evaluate(&compiled, content_item, &TextOptions::default(), false, usize::MAX)
+        }
+        None => vec![],
+    };
+
+    if !text_functions.is_empty() {
+        results = functions::apply_text_functions(results, &text_functions);
+    }
+
+    results
+}
+
+/// Compile `expr` (or fetch it from the per-thread cache). Returns `None` for
+/// an unparseable or over-nested expression.
+pub(crate) fn compile(expr: &str) -> Option<Rc<CompiledXPath>> {
     // Stack-safety gate: an iterative (non-recursive) depth scan decides whether
     // it's safe to hand the expression to chadpath's recursive-descent parser.
     // Pathologically nested expressions are refused here rather than risking a
-    // process-killing stack overflow inside the parser.
-    let depth = xpath_rewrite::nesting_depth(raw_expr);
+    // process-killing stack overflow inside the parser. Shim expansion nests
+    // further, so the expanded expression is checked again. SVG and MathML
+    // prefixes are dropped first; the adapter has no namespaces.
+    if !shallow_enough(expr) {
+        return None;
+    }
+    let expr = &*xpath_rewrite::strip_known_prefixes(expr);
+    match xpath_shims::rewrite(expr) {
+        Ok(expanded) if shallow_enough(&expanded) => xpath_eval::compile(&expanded),
+        Ok(_) => None,
+        Err(e) => {
+            warning!(InvalidQuery, "XPath expression '{expr}' not compiled: {e}");
+            None
+        }
+    }
+}
 
-    let mut results = if depth > MAX_NESTING_DEPTH {
-        warn!(
+/// Whether `expr` is within [`MAX_NESTING_DEPTH`], warning when not.
+fn shallow_enough(expr: &str) -> bool {
+    let depth = xpath_rewrite::nesting_depth(expr);
+    if depth > MAX_NESTING_DEPTH {
+        warning!(
+            InvalidQuery,
             "XPath expression nested {depth} levels deep (> {MAX_NESTING_DEPTH}); refusing to \
              avoid a stack overflow in chadpath's recursive parser"
         );
-        vec![]
+        return false;
+    }
+    true
+}
+
+/// Evaluate a compiled expression (no function chain) against the item,
+/// with the XPath 2.0 function shims when `shims` is set, keeping at most
+/// `limit` values.
+pub(crate) fn evaluate(
+    compiled: &CompiledXPath,
+    content_item: &ContentItem,
+    text_options: &TextOptions,
+    shims: bool,
+    limit: usize,
+) -> Vec<String> {
+    // Inline on the shared, cached parsed document + cached document-order
+    // map — no reparse, no per-query order rebuild. Positional predicates are
+    // evaluated correctly by the (forked) chadpath engine, so no rewriting
+    // is needed.
+    let (doc, order) = content_item.html_with_order();
+    xpath_eval::evaluate_compiled(
+        &doc,
+        order,
+        compiled,
+        text_options.exclude_script_text,
+        shims,
+        limit,
+    )
+}
+
+/// Like [`evaluate`], with the function chain applied and each value
+/// paired with the pre-order position and selector path of the element it
+/// came from. Text, attribute, and atomic results, and values a chain
+/// folded, have no element.
+pub(crate) fn run_located(
+    compiled: &CompiledXPath,
+    text_functions: &[TextFunction],
+    content_item: &ContentItem,
+    text_options: &TextOptions,
+    shims: bool,
+    limit: usize,
+) -> Vec<(Option<(usize, String)>, String)> {
+    let (doc, order) = content_item.html_with_order();
+    let entries = xpath_eval::evaluate_located(
+        &doc,
+        Rc::clone(&order),
+        compiled,
+        text_options.exclude_script_text,
+        shims,
+        limit,
+    );
+    let entries = if text_functions.is_empty() {
+        entries
     } else {
-        // Inline on the shared, cached parsed document + cached document-order
-        // map — no reparse, no per-query order rebuild. Positional predicates are
-        // evaluated correctly by the (forked) chadpath engine, so no rewriting
-        // is needed.
-        let (doc, order) = content_item.html_with_order();
-        xpath_eval::evaluate_with_order(&doc, order, raw_expr)
+        functions::apply_chain(entries, text_functions, &functions::Detached)
     };
 
-    if !text_functions.is_empty() {
-        results = functions::apply_text_functions(results, &text_functions);
-    }
+    entries
+        .into_iter()
+        .map(|(node_id, text)| {
+            let element = node_id
+                .and_then(|id| doc.tree.get(id))
+                .and_then(ElementRef::wrap);
+            let location = element.map(|element| {
+                let position = order.get(&element.id()).map_or(0, |rank| *rank as usize);
+                (position, css::css_path(&element))
+            });
+            (location, text)
+        })
+        .collect()
+}
 
-    results
+/// The tree nodes a compiled expression selects in the item's cached
+/// document, for DOM edits. Attribute and atomic results are skipped.
+pub(crate) fn matched_nodes(
+    compiled: &CompiledXPath,
+    content_item: &ContentItem,
+    shims: bool,
+) -> Vec<NodeId> {
+    let (doc, order) = content_item.html_with_order();
+    xpath_eval::evaluate_nodes(&doc, order, compiled, shims)
 }
@@ -6,14 +6,19 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use ego_tree::NodeId;
 use scraper::Html;
-use chadpath::item::{Item, Node};
+use chadpath::item::{Item, Node, Sequence};
+use chadpath::names::QName;
 use chadpath::parser::xpath::parse;
+use chadpath::transform::callable::Callable;
 use chadpath::transform::context::{ContextBuilder, StaticContextBuilder};
 use chadpath::transform::Transform;
 use chadpath::xdmerror::{Error, ErrorKind};
 
+use crate::cancel;
 use crate::engine::xnode::{ENode, OrderMap};
+use crate::engine::xpath_shims;
 
 thread_local! {
     /// Cache of compiled XPath expressions, keyed by the expression string.
@@ -23,19 +28,30 @@ thread_local! {
     /// Compiling once per distinct expression (per thread) turns that into a
     /// one-time cost. The compiled `Transform` is document-independent, so it is
     /// reused across documents.
-    static COMPILED: RefCell<HashMap<String, Rc<Transform<ENode>>>> =
+    static COMPILED: RefCell<HashMap<String, Rc<CompiledXPath>>> =
         RefCell::new(HashMap::new());
 }
 
-/// Compile `expr` to a (cached) `Transform`. Returns `None` on a parse error.
-fn compile(expr: &str) -> Option<Rc<Transform<ENode>>> {
+/// A compiled XPath expression.
+pub(crate) struct CompiledXPath {
+    pub(crate) transform: Transform<ENode>,
+    /// The shim callables the expression calls, registered when evaluating
+    /// with shims enabled.
+    shims: Vec<(QName, Callable<ENode>)>,
+}
+
+/// Compile `expr` (cached). Returns `None` on a parse error.
+pub(crate) fn compile(expr: &str) -> Option<Rc<CompiledXPath>> {
     COMPILED.with(|c| {
         if let Some(t) = c.borrow().get(expr) {
             return Some(t.clone());
         }
         match parse::<ENode>(expr, None, None) {
-            Ok(t) => {
-                let rc = Rc::new(t);
+            Ok(transform) => {
+                let rc = Rc::new(CompiledXPath {
+                    transform,
+                    shims: xpath_shims::callables_for(expr),
+                });
                 c.borrow_mut().insert(expr.to_string(), rc.clone());
                 Some(rc)
             }
@@ -58,27 +74,109 @@ pub fn evaluate(doc: &Rc<Html>, expr: &str) -> Vec<String> {
 /// Like [`evaluate`], but reuses a pre-built (cached) document-order map so the
 /// O(n) order pass is not repeated for every query against the same document.
 pub fn evaluate_with_order(doc: &Rc<Html>, order: Rc<OrderMap>, expr: &str) -> Vec<String> {
-    let Some(transform) = compile(expr) else {
+    let Some(compiled) = compile(expr) else {
         return vec![];
     };
+    evaluate_compiled(doc, order, &compiled, false, false, usize::MAX)
+}
+
+/// The tree nodes (elements, text, comments) an already-compiled expression
+/// selects, in result order. Attribute and atomic results are skipped.
+pub(crate) fn evaluate_nodes(
+    doc: &Rc<Html>,
+    order: Rc<OrderMap>,
+    compiled: &CompiledXPath,
+    shims: bool,
+) -> Vec<NodeId> {
+    match dispatch(doc, order, compiled, shims) {
+        Ok(seq) => seq
+            .iter()
+            .filter_map(|item| match item {
+                Item::Node(node) => node.node_id(),
+                _ => None,
+            })
+            .collect(),
+        Err(_) => vec![],
+    }
+}
 
+/// Like [`evaluate_with_order`], but runs an already-compiled expression.
+/// With `exclude_script_text`, element results leave out script/style text
+/// (see [`ENode::content_text`]). With `shims`, the XPath 2.0 function shims
+/// are available (see [`xpath_shims`](crate::engine::xpath_shims)). At most
+/// `limit` values are kept.
+pub(crate) fn evaluate_compiled(
+    doc: &Rc<Html>,
+    order: Rc<OrderMap>,
+    compiled: &CompiledXPath,
+    exclude_script_text: bool,
+    shims: bool,
+    limit: usize,
+) -> Vec<String> {
+    evaluate_located(doc, order, compiled, exclude_script_text, shims, limit)
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect()
+}
+
+/// Like [`evaluate_compiled`], pairing each value with the tree node it
+/// came from. Attribute and atomic results have none.
+pub(crate) fn evaluate_located(
+    doc: &Rc<Html>,
+    order: Rc<OrderMap>,
+    compiled: &CompiledXPath,
+    exclude_script_text: bool,
+    shims: bool,
+    limit: usize,
+) -> Vec<(Option<NodeId>, String)> {
+    match dispatch(doc, order, compiled, shims) {
+        Ok(seq) => seq
+            .iter()
+            .take_while(|_| !cancel::interrupted())
+            .map(|item| {
+                let (node_id, content_text) = match item {
+                    Item::Node(node) if exclude_script_text => {
+                        (node.node_id(), node.content_text())
+                    }
+                    Item::Node(node) => (node.node_id(), None),
+                    _ => (None, None),
+                };
+                let text = content_text
+                    .unwrap_or_else(|| item.to_string())
+                    .trim()
+                    .to_string();
+                (node_id, text)
+            })
+            .filter(|(_, text)| !text.is_empty())
+            .take(limit)
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+/// Run `compiled` with the document root as the context item.
+fn dispatch(
+    doc: &Rc<Html>,
+    order: Rc<OrderMap>,
+    compiled: &CompiledXPath,
+    shims: bool,
+) -> Result<Sequence<ENode>, Error> {
     let mut stctxt = StaticContextBuilder::new()
         .message(|_| Ok(()))
         .fetcher(|_| Err(Error::new(ErrorKind::NotImplemented, "no fetcher")))
         .parser(|_| Err(Error::new(ErrorKind::NotImplemented, "no parser")))
         .build();
 
-    let ctxt = ContextBuilder::new()
+    let mut builder = ContextBuilder::new()
         .context(vec![Item::Node(ENode::root_with_order(doc, order))])
-        .result_document(ENode::new_document())
-        .build();
-
-    match ctxt.dispatch(&mut stctxt, transform.as_ref()) {
-        Ok(seq) => seq
-            .iter()
-            .map(|item| item.to_string().trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect(),
-        Err(_) => vec![],
+        .result_document(ENode::new_document());
+    if shims && !compiled.shims.is_empty() {
+        builder = builder.maximum_depth(Some(xpath_shims::MAX_SHIM_DEPTH));
+        for (name, callable) in &compiled.shims {
+            builder = builder.callable(name.clone(), callable.clone());
+        }
     }
+    let ctxt = builder.build();
+
+    ctxt.dispatch(&mut stctxt, &compiled.transform)
 }
@@ -11,9 +11,21 @@
 //! lets [`crate::engine::xpath`] refuse an over-nested expression *before*
 //! invoking the recursive parser, rather than risk crashing the process.
 //!
+//! [`strip_known_prefixes`] drops the `svg:`, `math:`, `xlink:`, and
+//! `xhtml:` prefixes from name tests before parsing. The
+//! [`ENode`](crate::engine::xnode::ENode) adapter is namespace-free, so
+//! chadpath would otherwise refuse `//svg:text` for want of a namespace
+//! declaration.
+//!
 //! (Positional-predicate rewriting used to live here too, but the forked chadpath
 //! engine now evaluates positional predicates correctly, so it was removed.)
 
+use std::borrow::Cow;
+
+/// Namespace prefixes a selector may use for the namespaces html5ever puts
+/// embedded SVG and MathML (and their attributes) in.
+const KNOWN_PREFIXES: [&str; 4] = ["svg", "math", "xlink", "xhtml"];
+
 /// Maximum simultaneous nesting depth of `(` and `[` (outside string literals).
 pub fn nesting_depth(expr: &str) -> usize {
     let mut depth = 0usize;
@@ -40,6 +52,52 @@ pub fn nesting_depth(expr: &str) -> usize {
     max
 }
 
+/// `expr` with each [`KNOWN_PREFIXES`] prefix removed from element and
+/// attribute name tests (outside string literals): `//svg:g/svg:text` becomes
+/// `//g/text`, `@xlink:href` becomes `@href`.
+pub fn strip_known_prefixes(expr: &str) -> Cow<'_, str> {
+    if !KNOWN_PREFIXES.iter().any(|prefix| expr.contains(&format!("{prefix}:"))) {
+        return Cow::Borrowed(expr);
+    }
+    let mut out = String::with_capacity(expr.len());
+    let mut quote: Option<char> = None;
+    let mut rest = expr;
+    while let Some(c) = rest.chars().next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None => {
+                // Not inside a longer name, another prefix, or a `$variable`;
+                // after an axis (`child::`) is fine.
+                let at_name_start = match out.chars().last() {
+                    Some(p) if is_name_char(p) || p == '$' => false,
+                    Some(':') => out.ends_with("::"),
+                    _ => true,
+                };
+                if at_name_start {
+                    if let Some(prefix) = KNOWN_PREFIXES.iter().find(|prefix| {
+                        rest.strip_prefix(**prefix)
+                            .and_then(|after| after.strip_prefix(':'))
+                            .and_then(|after| after.chars().next())
+                            .is_some_and(|next| next == '*' || next.is_alphabetic() || next == '_')
+                    }) {
+                        rest = &rest[prefix.len() + 1..];
+                        continue;
+                    }
+                }
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    Cow::Owned(out)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +111,15 @@ mod tests {
         // Brackets inside a string literal don't count.
         assert_eq!(nesting_depth("//a[@x='[[[']"), 1);
     }
+
+    #[test]
+    fn known_prefixes_are_stripped() {
+        assert_eq!(strip_known_prefixes("//svg:g/svg:text"), "//g/text");
+        assert_eq!(strip_known_prefixes("//svg:use/@xlink:href"), "//use/@href");
+        assert_eq!(strip_known_prefixes("child::math:mi | //svg:*"), "child::mi | //*");
+        // Literals, longer names, and other prefixes are left alone.
+        assert_eq!(strip_known_prefixes("//p[.='svg:text']"), "//p[.='svg:text']");
+        assert_eq!(strip_known_prefixes("//mysvg:text"), "//mysvg:text");
+        assert_eq!(strip_known_prefixes("//fn:text"), "//fn:text");
+    }
 }
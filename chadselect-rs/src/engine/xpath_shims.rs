@@ -0,0 +1,561 @@
+//! XPath 2.0+ function shims — `matches`, `replace`, `tokenize`,
+//! `lower-case`, `upper-case`, and `ends-with` for XPath 1.0 selector
+//! libraries ported from lxml or Saxon.
+//!
+//! chadpath has no native function hook and evaluates registered XPath
+//! callables slowly (each context clone copies every callable body), so
+//! [`rewrite`] expands each shim call into equivalent XPath 1.0-plus-`let`/
+//! `for` code when the expression is compiled. Every expansion first calls
+//! the `xpath-shims()` marker function, which is only registered in the
+//! evaluation context when
+//! [`ChadSelectBuilder::xpath_shims`](crate::config::ChadSelectBuilder::xpath_shims)
+//! is on; without it the call fails and the query yields nothing, as the
+//! unknown functions would. The only other callable, `shim-split`, splits on
+//! separators whose occurrences can overlap (see [`callables_for`]).
+//!
+//! The regex functions cannot run a regex engine from inside XPath, so a
+//! pattern must be a string literal that denotes plain text — escaped
+//! metacharacters, an optional `^`/`$` anchor, and the `i` and `q` flags —
+//! and is expanded to a search for that text. `tokenize` also accepts
+//! `\s+`. Anything else is refused when the query is compiled, rather than
+//! silently matching the wrong thing.
+//!
+//! Expansions bind `$shim-*` variables, and respect two chadpath traits: it
+//! evaluates an `else` branch even when the condition holds, so costly work
+//! only ever sits in a `then` branch; and it rejects whitespace just inside
+//! parentheses.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use chadpath::names::{NcName, QName};
+use chadpath::parser::xpath::parse;
+use chadpath::transform::callable::{Callable, FormalParameters};
+
+use crate::engine::xnode::ENode;
+
+/// Deepest `shim-split` recursion (one level per token) before evaluation
+/// is abandoned, well before the stack is at risk.
+pub(crate) const MAX_SHIM_DEPTH: usize = 256;
+
+/// Letters `lower-case` and `upper-case` map: ASCII and Latin-1.
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZÀÁÂÃÄÅÆÇÈÉÊËÌÍÎÏÐÑÒÓÔÕÖØÙÚÛÜÝÞ";
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyzàáâãäåæçèéêëìíîïðñòóôõöøùúûüýþ";
+
+/// Whitespace as XPath regexes define `\s`.
+const WHITESPACE: &str = " \t\n\r";
+
+/// The marker function every expansion calls.
+const MARKER: &str = "xpath-shims";
+
+/// The callables expansions use: name, positional parameters, XPath body.
+/// `shim-split` returns the pieces of `$x` between the occurrences of `$t`
+/// in its case-folded copy `$h`, with `$r` between pieces, left to right.
+const CALLABLES: [(&str, &[&str], &str); 2] = [
+    (MARKER, &[], "true()"),
+    (
+        "shim-split",
+        &["x", "h", "t", "r"],
+        "if (contains($h, $t)) then \
+           (let $i := string-length(substring-before($h, $t)) return \
+            let $j := $i + string-length($t) + 1 return \
+            (substring($x, 1, $i), $r, \
+             shim-split(substring($x, $j), substring($h, $j), $t, $r))) \
+         else $x",
+    ),
+];
+
+thread_local! {
+    /// Callable bodies, compiled on first use per thread.
+    static COMPILED: RefCell<HashMap<&'static str, Callable<ENode>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// The callables the (rewritten) `expr` calls, for registering in an
+/// evaluation context. Only these are registered, because chadpath clones
+/// every registered body whenever it clones the context.
+pub(crate) fn callables_for(expr: &str) -> Vec<(QName, Callable<ENode>)> {
+    CALLABLES
+        .iter()
+        .filter(|(name, _, _)| expr.contains(&format!("{name}(")))
+        .map(|(name, _, _)| (qname(name), callable(name)))
+        .collect()
+}
+
+fn callable(name: &'static str) -> Callable<ENode> {
+    COMPILED.with(|compiled| {
+        compiled
+            .borrow_mut()
+            .entry(name)
+            .or_insert_with(|| {
+                let (_, params, body) = CALLABLES
+                    .iter()
+                    .find(|callable| callable.0 == name)
+                    .expect("known callable");
+                let body = parse::<ENode>(body, None, None).expect("valid callable body");
+                let params = params.iter().map(|p| qname(p)).collect();
+                Callable::new(body, FormalParameters::Positional(params))
+            })
+            .clone()
+    })
+}
+
+fn qname(name: &str) -> QName {
+    QName::from_local_name(NcName::try_from(name).expect("valid callable name"))
+}
+
+/// `text` case-folded the way `lower-case` folds it.
+fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| match UPPER.chars().position(|u| u == c) {
+            Some(i) => LOWER.chars().nth(i).unwrap_or(c),
+            None => c,
+        })
+        .collect()
+}
+
+/// The functions [`rewrite`] expands.
+const FUNCTIONS: [&str; 6] = [
+    "matches",
+    "replace",
+    "tokenize",
+    "lower-case",
+    "upper-case",
+    "ends-with",
+];
+
+/// Expand every shim function call in `expr`. Expressions without them are
+/// returned unchanged.
+///
+/// # Errors
+///
+/// When a call has the wrong number of arguments, or a pattern or flags
+/// argument that is not a supported string literal.
+pub(crate) fn rewrite(expr: &str) -> Result<Cow<'_, str>, String> {
+    if !FUNCTIONS.iter().any(|name| expr.contains(name)) {
+        return Ok(Cow::Borrowed(expr));
+    }
+    let chars: Vec<char> = expr.chars().collect();
+    let mut out = String::with_capacity(expr.len());
+    let mut i = 0;
+    let mut quote: Option<char> = None;
+    while i < chars.len() {
+        let c = chars[i];
+        if let Some(q) = quote {
+            out.push(c);
+            if c == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if c == '\'' || c == '"' {
+            quote = Some(c);
+            out.push(c);
+            i += 1;
+            continue;
+        }
+        if let Some((name, open)) = call_at(&chars, i) {
+            let (args, close) = split_args(&chars, open)?;
+            let args = args
+                .iter()
+                .map(|arg| rewrite(arg).map(Cow::into_owned))
+                .collect::<Result<Vec<_>, _>>()?;
+            out.push_str(&rewrite_call(name, &args)?);
+            i = close + 1;
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    Ok(Cow::Owned(out))
+}
+
+/// The shim function called at `i`, and the index of its `(`.
+fn call_at(chars: &[char], i: usize) -> Option<(&'static str, usize)> {
+    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':');
+    if i > 0 && (is_name_char(chars[i - 1]) || matches!(chars[i - 1], '$' | '@')) {
+        return None;
+    }
+    FUNCTIONS.iter().find_map(|name| {
+        let end = i + name.chars().count();
+        let matched = chars.get(i..end)?.iter().copied().eq(name.chars());
+        if !matched || chars.get(end).is_some_and(|c| is_name_char(*c)) {
+            return None;
+        }
+        let open = (end..chars.len()).find(|j| !chars[*j].is_whitespace())?;
+        (chars[open] == '(').then_some((*name, open))
+    })
+}
+
+/// The top-level arguments of the call whose `(` is at `open`, trimmed, and
+/// the index of its `)`.
+fn split_args(chars: &[char], open: usize) -> Result<(Vec<String>, usize), String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    for (j, &c) in chars.iter().enumerate().skip(open + 1) {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            current.push(c);
+            continue;
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '(' | '[' => depth += 1,
+            ')' if depth == 0 => {
+                if !current.trim().is_empty() || !args.is_empty() {
+                    args.push(current.trim().to_string());
+                }
+                return Ok((args, j));
+            }
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    Err("unclosed function call".to_string())
+}
+
+/// How a translated pattern is anchored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    None,
+    Start,
+    End,
+    Whole,
+}
+
+fn rewrite_call(name: &str, args: &[String]) -> Result<String, String> {
+    let arity = |range: std::ops::RangeInclusive<usize>| {
+        if range.contains(&args.len()) {
+            Ok(())
+        } else {
+            Err(format!("{name}() called with {} arguments", args.len()))
+        }
+    };
+    let body = match name {
+        "lower-case" => {
+            arity(1..=1)?;
+            format!("translate(string({}), '{UPPER}', '{LOWER}')", args[0])
+        }
+        "upper-case" => {
+            arity(1..=1)?;
+            format!("translate(string({}), '{LOWER}', '{UPPER}')", args[0])
+        }
+        "ends-with" => {
+            arity(2..=2)?;
+            format!(
+                "let $shim-s := string({}) return let $shim-t := string({}) return \
+                 substring($shim-s, string-length($shim-s) - string-length($shim-t) + 1) \
+                 = $shim-t",
+                args[0], args[1]
+            )
+        }
+        "matches" => {
+            arity(2..=3)?;
+            let flags = flags_arg(name, args.get(2))?;
+            let (text, anchor) = pattern(name, &args[1], &flags)?;
+            let (text, case_insensitive) = folded(text, &flags);
+            let q = quoted(&text)?;
+            let n = text.chars().count();
+            let test = match anchor {
+                Anchor::None => format!("contains($shim-x, {q})"),
+                Anchor::Start => format!("starts-with($shim-x, {q})"),
+                Anchor::End => {
+                    format!("substring($shim-x, string-length($shim-x) - {n} + 1) = {q}")
+                }
+                Anchor::Whole => format!("$shim-x = {q}"),
+            };
+            format!(
+                "let $shim-x := {} return {test}",
+                subject(&args[0], case_insensitive)
+            )
+        }
+        "replace" => {
+            arity(3..=4)?;
+            let flags = flags_arg(name, args.get(3))?;
+            let (text, anchor) = pattern(name, &args[1], &flags)?;
+            if text.is_empty() {
+                return Err("replace() pattern matches the empty string".to_string());
+            }
+            let replacement = match literal(&args[2]) {
+                Some(literal) if flags.contains('q') => quoted(&literal)?,
+                Some(literal) => quoted(&replacement(&literal)?)?,
+                None => format!("string({})", args[2]),
+            };
+            let (text, case_insensitive) = folded(text, &flags);
+            let q = quoted(&text)?;
+            let n = text.chars().count();
+            let result = match anchor {
+                Anchor::None => format!(
+                    "string({})",
+                    tokens("$shim-x", "$shim-h", &text, Some("$shim-r"))?
+                ),
+                Anchor::Start => format!(
+                    "if (starts-with($shim-h, {q})) \
+                     then concat($shim-r, substring($shim-x, {n} + 1)) else $shim-x"
+                ),
+                Anchor::End => format!(
+                    "if (substring($shim-h, string-length($shim-h) - {n} + 1) = {q}) \
+                     then concat(substring($shim-x, 1, string-length($shim-x) - {n}), $shim-r) \
+                     else $shim-x"
+                ),
+                Anchor::Whole => format!("if ($shim-h = {q}) then $shim-r else $shim-x"),
+            };
+            format!(
+                "let $shim-x := string({}) return let $shim-h := {} return \
+                 let $shim-r := {replacement} return {result}",
+                args[0],
+                subject("$shim-x", case_insensitive)
+            )
+        }
+        "tokenize" => {
+            arity(1..=3)?;
+            let Some(pattern_arg) = args.get(1) else {
+                return Ok(marked(&format!(
+                    "let $shim-w := normalize-space(string({})) return \
+                     if ($shim-w != '') then {} else ()",
+                    args[0],
+                    tokens("$shim-w", "$shim-w", " ", None)?
+                )));
+            };
+            let flags = flags_arg(name, args.get(2))?;
+            if !flags.contains('q') && literal(pattern_arg).as_deref() == Some(r"\s+") {
+                // Whitespace at either end yields an empty first or last token.
+                return Ok(marked(&format!(
+                    "let $shim-x := string({}) return \
+                     let $shim-w := normalize-space($shim-x) return \
+                     ((if ($shim-x != '' and \
+                           translate(substring($shim-x, 1, 1), '{WHITESPACE}', '') = '') \
+                       then '' else ()), \
+                      (if ($shim-w != '') then {} else ()), \
+                      (if ($shim-x != '' and translate(substring($shim-x, \
+                           string-length($shim-x)), '{WHITESPACE}', '') = '') \
+                       then '' else ()))",
+                    args[0],
+                    tokens("$shim-w", "$shim-w", " ", None)?
+                )));
+            }
+            let (text, anchor) = pattern(name, pattern_arg, &flags)?;
+            if text.is_empty() || anchor != Anchor::None {
+                return Err(format!(
+                    "tokenize() pattern {pattern_arg} is not a plain separator"
+                ));
+            }
+            let (text, case_insensitive) = folded(text, &flags);
+            format!(
+                "let $shim-x := string({}) return let $shim-h := {} return \
+                 if ($shim-x != '') then {} else ()",
+                args[0],
+                subject("$shim-x", case_insensitive),
+                tokens("$shim-x", "$shim-h", &text, None)?
+            )
+        }
+        _ => unreachable!("not a shim function"),
+    };
+    Ok(marked(&body))
+}
+
+/// `body` behind the marker call, which fails unless shims are enabled.
+fn marked(body: &str) -> String {
+    format!("(let $shim-on := {MARKER}() return {body})")
+}
+
+/// The string value of `arg`, case-folded when `case_insensitive`.
+fn subject(arg: &str, case_insensitive: bool) -> String {
+    if case_insensitive {
+        format!("translate(string({arg}), '{UPPER}', '{LOWER}')")
+    } else {
+        format!("string({arg})")
+    }
+}
+
+/// XPath for the pieces of string variable `x` between occurrences of
+/// `text` in its case-folded copy `h`, with `separator` between pieces.
+///
+/// When occurrences cannot overlap, a `for` over the positions of `h` finds
+/// them and cuts each piece from the occurrence before it; this avoids
+/// recursion, whose context clones make it slow. Otherwise occurrences are
+/// taken left to right by `shim-split`.
+fn tokens(x: &str, h: &str, text: &str, separator: Option<&str>) -> Result<String, String> {
+    let q = quoted(text)?;
+    let n = text.chars().count();
+    if overlaps(text) {
+        return Ok(format!(
+            "shim-split({x}, {h}, {q}, {})",
+            separator.unwrap_or("()")
+        ));
+    }
+    let separator = separator
+        .map(|separator| format!("(if ($shim-c > 0) then {separator} else ()), "))
+        .unwrap_or_default();
+    Ok(format!(
+        "(if (contains({h}, {q})) \
+         then (for $shim-c in (0, for $shim-i in 1 to string-length({h}) - {n} + 1 \
+                                  return (if (substring({h}, $shim-i, {n}) = {q}) \
+                                          then $shim-i else ())) \
+               return (let $shim-f := if ($shim-c = 0) then 1 else $shim-c + {n} return \
+                       let $shim-rest := concat(substring({h}, $shim-f), {q}) return \
+                       ({separator}substring({x}, $shim-f, \
+                         string-length(substring-before($shim-rest, {q})))))) \
+         else {x})"
+    ))
+}
+
+/// Whether occurrences of `text` can overlap (it has a proper prefix that
+/// is also a suffix).
+fn overlaps(text: &str) -> bool {
+    (1..text.len())
+        .filter(|i| text.is_char_boundary(*i))
+        .any(|i| text.ends_with(&text[..i]))
+}
+
+/// The pattern text, folded for the `i` flag, and whether it was.
+fn folded(text: String, flags: &str) -> (String, bool) {
+    if flags.contains('i') {
+        (fold(&text), true)
+    } else {
+        (text, false)
+    }
+}
+
+/// The flags argument, which must be a literal of supported flags.
+fn flags_arg(name: &str, arg: Option<&String>) -> Result<String, String> {
+    let Some(arg) = arg else {
+        return Ok(String::new());
+    };
+    let flags = literal(arg).ok_or_else(|| format!("{name}() flags must be a string literal"))?;
+    match flags.chars().find(|c| !matches!(c, 'i' | 's' | 'q')) {
+        Some(flag) => Err(format!("{name}() flag '{flag}' is not supported")),
+        None => Ok(flags),
+    }
+}
+
+/// The plain text and anchoring a literal regex `arg` denotes.
+fn pattern(name: &str, arg: &str, flags: &str) -> Result<(String, Anchor), String> {
+    let pattern =
+        literal(arg).ok_or_else(|| format!("{name}() pattern must be a string literal"))?;
+    if flags.contains('q') {
+        return Ok((pattern, Anchor::None));
+    }
+    let unsupported = || format!("{name}() pattern {arg} is not plain text (see the XPath shims)");
+
+    let (start, body) = match pattern.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, pattern.as_str()),
+    };
+    let mut text = String::new();
+    let mut end = false;
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => text.push('\n'),
+                Some('r') => text.push('\r'),
+                Some('t') => text.push('\t'),
+                Some(escaped) if escaped.is_ascii_punctuation() => text.push(escaped),
+                _ => return Err(unsupported()),
+            },
+            '$' if chars.peek().is_none() => end = true,
+            '.' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|' | '^' | '$' => {
+                return Err(unsupported())
+            }
+            _ => text.push(c),
+        }
+    }
+
+    let anchor = match (start, end) {
+        (true, true) => Anchor::Whole,
+        (true, false) => Anchor::Start,
+        (false, true) => Anchor::End,
+        (false, false) => Anchor::None,
+    };
+    Ok((text, anchor))
+}
+
+/// A `replace()` replacement with its `\\` and `\$` escapes resolved.
+fn replacement(literal: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('\\' | '$')) => out.push(escaped),
+                _ => return Err("replace() replacement has an invalid `\\` escape".to_string()),
+            },
+            '$' => return Err("replace() group references are not supported".to_string()),
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// The value of an XPath string literal, or `None` when `arg` is not one.
+fn literal(arg: &str) -> Option<String> {
+    let quote = arg.chars().next().filter(|c| matches!(c, '\'' | '"'))?;
+    let inner = arg.strip_prefix(quote)?.strip_suffix(quote)?;
+    let doubled = format!("{quote}{quote}");
+    let unescaped = inner.replace(&doubled, "");
+    (!unescaped.contains(quote)).then(|| inner.replace(&doubled, &quote.to_string()))
+}
+
+/// `text` as an XPath string literal.
+fn quoted(text: &str) -> Result<String, String> {
+    if !text.contains('\'') {
+        Ok(format!("'{text}'"))
+    } else if !text.contains('"') {
+        Ok(format!("\"{text}\""))
+    } else {
+        Err("patterns with both quote characters are not supported".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_shim_calls() {
+        assert_eq!(
+            rewrite("//a[ends-with(@href, '.pdf')]").unwrap(),
+            "//a[(let $shim-on := xpath-shims() return let $shim-s := string(@href) return \
+             let $shim-t := string('.pdf') return substring($shim-s, string-length($shim-s) \
+             - string-length($shim-t) + 1) = $shim-t)]"
+        );
+        assert_eq!(
+            rewrite(r"matches(@href, '\.PDF$')").unwrap(),
+            "(let $shim-on := xpath-shims() return let $shim-x := string(@href) return \
+             substring($shim-x, string-length($shim-x) - 4 + 1) = '.PDF')"
+        );
+        assert!(rewrite("replace(., 'aa', 'b')")
+            .unwrap()
+            .contains("shim-split("));
+        assert!(!rewrite("replace(., 'ab', 'b')")
+            .unwrap()
+            .contains("shim-split("));
+        // Names inside literals, steps, and longer names are left alone.
+        let untouched = "//matches[@replace = 'tokenize(x)']/my-lower-case(.)";
+        assert_eq!(rewrite(untouched).unwrap(), untouched);
+    }
+
+    #[test]
+    fn refuses_real_regexes() {
+        assert!(rewrite(r"matches(., '\d+')").is_err());
+        assert!(rewrite("matches(., 'a.c')").is_err());
+        assert!(rewrite("matches(., $pattern)").is_err());
+        assert!(rewrite("matches(., 'a', 'x')").is_err());
+        assert!(rewrite("replace(., 'a', '$1')").is_err());
+        assert!(rewrite("tokenize(., '^a')").is_err());
+        assert!(rewrite("lower-case(., 'x')").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Sitemap and RSS/Atom extraction over loaded XML content.
+//!
+//! These formats are parsed as XML (namespace-aware), not through the HTML
+//! parser — which would treat RSS `<link>` as a void element. Any loaded
+//! item whose content is well-formed XML is considered, whatever content
+//! type it was added as.
+
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::ChadSelect;
+
+const SITEMAP_NS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+const RSS1_NS: &str = "http://purl.org/rss/1.0/";
+const DUBLIN_CORE_NS: &str = "http://purl.org/dc/elements/1.1/";
+
+/// One `<url>` (or sitemap-index `<sitemap>`) entry of an XML sitemap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SitemapUrl {
+    /// The page URL (`<loc>`).
+    pub loc: String,
+    /// `<lastmod>`, as written.
+    pub lastmod: Option<String>,
+    /// `<changefreq>`, as written.
+    pub changefreq: Option<String>,
+    /// `<priority>`, as written.
+    pub priority: Option<String>,
+    /// `true` for the entries of a sitemap index, whose `loc` is another
+    /// sitemap rather than a page.
+    pub nested: bool,
+}
+
+/// One item of an RSS 2.0, RSS 1.0, or Atom feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedItem {
+    /// `<title>`.
+    pub title: Option<String>,
+    /// RSS `<link>`, or the `href` of Atom's alternate `<link>`.
+    pub link: Option<String>,
+    /// RSS `<pubDate>`, Atom `<published>` (else `<updated>`), or Dublin
+    /// Core `<dc:date>`, as written.
+    pub pub_date: Option<String>,
+    /// RSS `<guid>` or Atom `<id>`.
+    pub id: Option<String>,
+    /// RSS `<description>`, or Atom `<summary>` (else `<content>`).
+    pub summary: Option<String>,
+}
+
+impl ChadSelect {
+    /// Every `<url>` entry of the loaded XML sitemaps, in load order, plus
+    /// the `<sitemap>` entries of sitemap indexes (marked
+    /// [`nested`](SitemapUrl::nested)). Entries without a `<loc>` are
+    /// skipped.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_text(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    ///   <url><loc>https://example.com/a</loc><lastmod>2024-01-01</lastmod></url>
+    /// </urlset>"#.to_string());
+    ///
+    /// let urls = cs.extract_sitemap_urls();
+    /// assert_eq!(urls[0].loc, "https://example.com/a");
+    /// assert_eq!(urls[0].lastmod.as_deref(), Some("2024-01-01"));
+    /// ```
+    pub fn extract_sitemap_urls(&self) -> Vec<SitemapUrl> {
+        let mut urls = Vec::new();
+        for doc in self.xml_documents() {
+            for node in doc
+                .descendants()
+                .filter(|n| in_namespace(n, &[SITEMAP_NS, ""]))
+            {
+                let nested = match node.tag_name().name() {
+                    "url" => false,
+                    "sitemap" => true,
+                    _ => continue,
+                };
+                let field = |name: &str| child_text(&node, name, &[SITEMAP_NS, ""]);
+                if let Some(loc) = field("loc") {
+                    urls.push(SitemapUrl {
+                        loc,
+                        lastmod: field("lastmod"),
+                        changefreq: field("changefreq"),
+                        priority: field("priority"),
+                        nested,
+                    });
+                }
+            }
+        }
+        urls
+    }
+
+    /// Every item of the loaded RSS 2.0, RSS 1.0, and Atom feeds, in load
+    /// order.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_text(r#"<rss version="2.0"><channel>
+    ///   <item><title>Launch</title><link>https://example.com/launch</link></item>
+    /// </channel></rss>"#.to_string());
+    ///
+    /// let items = cs.extract_feed_items();
+    /// assert_eq!(items[0].title.as_deref(), Some("Launch"));
+    /// assert_eq!(items[0].link.as_deref(), Some("https://example.com/launch"));
+    /// ```
+    pub fn extract_feed_items(&self) -> Vec<FeedItem> {
+        let mut items = Vec::new();
+        for doc in self.xml_documents() {
+            for node in doc.descendants().filter(Node::is_element) {
+                match node.tag_name().name() {
+                    "item" if in_namespace(&node, &["", RSS1_NS]) => items.push(rss_item(&node)),
+                    "entry" if in_namespace(&node, &[ATOM_NS]) => items.push(atom_entry(&node)),
+                    _ => {}
+                }
+            }
+        }
+        items
+    }
+
+    /// Parse every loaded item that is well-formed XML; others are skipped.
+    fn xml_documents(&self) -> impl Iterator<Item = Document<'_>> {
+        let options = ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        };
+        self.content_list
+            .iter()
+            .map(|item| item.content.trim_start_matches('\u{feff}').trim_start())
+            .filter(|content| content.starts_with('<'))
+            .filter_map(move |content| Document::parse_with_options(content, options).ok())
+    }
+}
+
+fn rss_item(node: &Node) -> FeedItem {
+    let field = |name: &str| child_text(node, name, &["", RSS1_NS]);
+    FeedItem {
+        title: field("title"),
+        link: field("link"),
+        pub_date: field("pubDate").or_else(|| child_text(node, "date", &[DUBLIN_CORE_NS])),
+        id: field("guid"),
+        summary: field("description"),
+    }
+}
+
+fn atom_entry(node: &Node) -> FeedItem {
+    let field = |name: &str| child_text(node, name, &[ATOM_NS]);
+    let link = node
+        .children()
+        .filter(|n| n.tag_name().name() == "link" && in_namespace(n, &[ATOM_NS]))
+        .find(|n| matches!(n.attribute("rel"), None | Some("alternate")))
+        .and_then(|n| n.attribute("href"))
+        .map(str::to_string);
+    FeedItem {
+        title: field("title"),
+        link,
+        pub_date: field("published").or_else(|| field("updated")),
+        id: field("id"),
+        summary: field("summary").or_else(|| field("content")),
+    }
+}
+
+/// Whether element `node` is in one of `namespaces` (`""` for none).
+fn in_namespace(node: &Node, namespaces: &[&str]) -> bool {
+    node.is_element() && namespaces.contains(&node.tag_name().namespace().unwrap_or(""))
+}
+
+/// The trimmed text of the first child element `name` in one of
+/// `namespaces`, or `None` when it is missing or blank.
+fn child_text(node: &Node, name: &str, namespaces: &[&str]) -> Option<String> {
+    let child = node
+        .children()
+        .find(|n| n.tag_name().name() == name && in_namespace(n, namespaces))?;
+    let text: String = child
+        .descendants()
+        .filter(Node::is_text)
+        .filter_map(|n| n.text())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
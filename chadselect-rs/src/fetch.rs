@@ -0,0 +1,162 @@
+//! HTTP fetching (the `fetch` feature): load a page straight from its URL.
+
+use std::io::Read;
+
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::header::CONTENT_TYPE;
+
+use crate::config::LimitPolicy;
+use crate::content::{declared_charset, is_binary_mime, ContentType};
+use crate::ChadSelect;
+
+/// How far into an HTML body to look for a `<meta charset>` declaration.
+const META_SNIFF_BYTES: usize = 1024;
+
+impl ChadSelect {
+    /// Download `url` and add the body as content.
+    ///
+    /// The content type comes from the `Content-Type` header (HTML, JSON,
+    /// Markdown, TOML with the `toml` feature, otherwise text), or from the
+    /// body when the header is missing or generic. The body is decoded from
+    /// the header's charset, an HTML `<meta charset>`, or a byte-order mark,
+    /// defaulting to UTF-8. The URL is kept as the item's
+    /// [`content_source`](ChadSelect::content_source).
+    ///
+    /// Uses `reqwest`'s blocking client, so it must not be called from
+    /// inside an async runtime — use [`add_url_async`](ChadSelect::add_url_async)
+    /// there. Errors on a network failure, a non-2xx status, a binary
+    /// `Content-Type` (images, audio, video, fonts, archives), or a body
+    /// rejected by the size limits. A body over
+    /// [`max_item_bytes`](crate::ChadSelectBuilder::max_item_bytes) is not
+    /// downloaded past the limit.
+    pub fn add_url(&mut self, url: &str) -> Result<(), String> {
+        let response =
+            reqwest::blocking::get(url).map_err(|e| format!("GET {} failed: {}", url, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("GET {} returned {}", url, status));
+        }
+        let header = header_content_type(response.headers());
+        let limit = self.read_limit(url, header.as_deref(), response.content_length())?;
+        let mut body = Vec::new();
+        response
+            .take(limit)
+            .read_to_end(&mut body)
+            .map_err(|e| format!("GET {} failed: {}", url, e))?;
+        self.add_response(url, header.as_deref(), body)
+    }
+
+    /// Like [`add_url`](ChadSelect::add_url), with `reqwest`'s async client.
+    pub async fn add_url_async(&mut self, url: &str) -> Result<(), String> {
+        let mut response = reqwest::get(url)
+            .await
+            .map_err(|e| format!("GET {} failed: {}", url, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("GET {} returned {}", url, status));
+        }
+        let header = header_content_type(response.headers());
+        let limit = self.read_limit(url, header.as_deref(), response.content_length())?;
+        let mut body = Vec::new();
+        while (body.len() as u64) < limit {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) => break,
+                Err(e) => return Err(format!("GET {} failed: {}", url, e)),
+            }
+        }
+        self.add_response(url, header.as_deref(), body)
+    }
+
+    /// How many body bytes to read: one past
+    /// [`max_item_bytes`](crate::ChadSelectBuilder::max_item_bytes), so an
+    /// oversized body shows without being read whole. Errors up front on a
+    /// binary `Content-Type`, or a `Content-Length` over the limit that the
+    /// [`LimitPolicy`] would not truncate.
+    fn read_limit(
+        &self,
+        url: &str,
+        header: Option<&str>,
+        content_length: Option<u64>,
+    ) -> Result<u64, String> {
+        if let Some(mime) = header.filter(|mime| is_binary_mime(mime)) {
+            return Err(format!("GET {} returned binary content ({})", url, mime));
+        }
+        let Some(max) = self.config.max_item_bytes else {
+            return Ok(u64::MAX);
+        };
+        match content_length {
+            Some(length)
+                if length > max as u64 && self.config.limit_policy != LimitPolicy::Truncate =>
+            {
+                Err(format!(
+                    "GET {} body of {} bytes exceeds the {}-byte item limit",
+                    url, length, max
+                ))
+            }
+            _ => Ok(max as u64 + 1),
+        }
+    }
+
+    fn add_response(
+        &mut self,
+        url: &str,
+        header: Option<&str>,
+        mut body: Vec<u8>,
+    ) -> Result<(), String> {
+        if let Some(max) = self.config.max_item_bytes.filter(|&max| body.len() > max) {
+            if self.config.limit_policy != LimitPolicy::Truncate {
+                return Err(format!(
+                    "GET {} body exceeds the {}-byte item limit",
+                    url, max
+                ));
+            }
+            body.truncate(max);
+        }
+        let content_type = ContentType::from_mime(header, &body);
+        let (content, charset) = decode(header, &body, &content_type);
+        self.try_add(content, content_type)?;
+        let item = self.content_list.last_mut().expect("item was just added");
+        item.source = Some(url.to_string());
+        item.charset = Some(charset.to_string());
+        Ok(())
+    }
+}
+
+fn header_content_type(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers.get(CONTENT_TYPE)?.to_str().ok().map(str::to_string)
+}
+
+/// Decode `body` using the header's charset, then (for HTML) a `<meta
+/// charset>` near the top, then a byte-order mark, then UTF-8. Returns the
+/// text and the name of the encoding used.
+fn decode(
+    header: Option<&str>,
+    body: &[u8],
+    content_type: &ContentType,
+) -> (String, &'static str) {
+    let declared = header
+        .and_then(charset_param)
+        .or_else(|| match content_type {
+            ContentType::Html => {
+                let head = &body[..body.len().min(META_SNIFF_BYTES)];
+                declared_charset(&String::from_utf8_lossy(head))
+            }
+            _ => None,
+        });
+    let encoding = declared
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+    let (text, used, _) = encoding.decode(body);
+    (text.into_owned(), used.name())
+}
+
+/// The `charset` parameter of a `Content-Type` value.
+fn charset_param(header: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
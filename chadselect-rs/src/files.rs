@@ -0,0 +1,86 @@
+//! Loading saved documents from disk.
+
+use std::fs;
+use std::path::Path;
+
+use crate::content::ContentType;
+#[cfg(feature = "glob")]
+use crate::warnings::{warning, Capture};
+use crate::ChadSelect;
+
+impl ChadSelect {
+    /// Read a file and add it as content, with the type inferred from its
+    /// extension (see [`content_type_for_path`]). The path is kept as the
+    /// item's [`content_source`](ChadSelect::content_source).
+    ///
+    /// Invalid UTF-8 is replaced rather than rejected, and a leading
+    /// byte-order mark is dropped. Errors when the file cannot be read or is
+    /// rejected by the size limits.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let content = String::from_utf8_lossy(&bytes);
+        let content = content
+            .strip_prefix('\u{feff}')
+            .unwrap_or(&content)
+            .to_string();
+
+        self.try_add(content, content_type_for_path(path))?;
+        self.content_list
+            .last_mut()
+            .expect("item was just added")
+            .source = Some(path.display().to_string());
+        Ok(())
+    }
+
+    /// Add every file matching a glob pattern (`dumps/*.html`,
+    /// `pages/**/*.json`), in sorted path order, as [`add_file`] would.
+    /// Returns the number of files added.
+    ///
+    /// Files that cannot be read or are rejected by the size limits are
+    /// skipped with a warning; an invalid pattern is an error.
+    ///
+    /// [`add_file`]: ChadSelect::add_file
+    #[cfg(feature = "glob")]
+    pub fn add_glob(&mut self, pattern: &str) -> Result<usize, String> {
+        let paths =
+            glob::glob(pattern).map_err(|e| format!("invalid glob '{}': {}", pattern, e))?;
+
+        let capture = Capture::begin();
+        let mut added = 0;
+        for entry in paths {
+            match entry {
+                Ok(path) if path.is_file() => match self.add_file(&path) {
+                    Ok(()) => added += 1,
+                    Err(e) => warning!(ContentRejected, "File not added: {}", e),
+                },
+                Ok(_) => {}
+                Err(e) => warning!(ContentRejected, "File not added: {}", e),
+            }
+        }
+        self.keep_warnings(capture);
+        Ok(added)
+    }
+}
+
+/// The content type [`ChadSelect::add_file`] uses for a path, by extension
+/// (case-insensitive): `html`/`htm`/`xhtml` → HTML, `json` → JSON,
+/// `md`/`markdown` → Markdown, `toml` → TOML (with the `toml` feature),
+/// `ini`/`cfg`/`conf` → INI, anything else → text.
+pub fn content_type_for_path(path: &Path) -> ContentType {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" | "xhtml" => ContentType::Html,
+        "json" => ContentType::Json,
+        "md" | "markdown" => ContentType::Markdown,
+        #[cfg(feature = "toml")]
+        "toml" => ContentType::Toml,
+        "ini" | "cfg" | "conf" => ContentType::Ini,
+        _ => ContentType::Text,
+    }
+}
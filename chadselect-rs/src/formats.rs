@@ -0,0 +1,264 @@
+//! Config-file and binary formats normalised into JSON, so `json:` queries
+//! run against TOML, INI, and MessagePack payloads exactly as they do against
+//! JSON.
+
+#[cfg(feature = "msgpack")]
+use base64::engine::general_purpose::STANDARD;
+#[cfg(feature = "msgpack")]
+use base64::Engine as _;
+use serde_json::{Map, Value};
+
+/// Convert a TOML document into JSON. Datetimes become their RFC 3339 string.
+#[cfg(feature = "toml")]
+pub fn toml_to_json(source: &str) -> Result<Value, String> {
+    let table: toml::Table = source.parse().map_err(|e| format!("invalid TOML: {}", e))?;
+    Ok(toml_value(toml::Value::Table(table)))
+}
+
+#[cfg(feature = "toml")]
+fn toml_value(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_value).collect()),
+        toml::Value::Table(table) => {
+            Value::Object(table.into_iter().map(|(k, v)| (k, toml_value(v))).collect())
+        }
+    }
+}
+
+/// Convert an INI document into JSON.
+///
+/// Keys before the first `[section]` sit at the top level; each section
+/// becomes an object. Every value is a string (INI is untyped) with matching
+/// surrounding quotes removed. `;` and `#` start comment lines; `key=value`
+/// and `key: value` are both accepted, and a repeated key keeps its last
+/// value.
+pub fn ini_to_json(source: &str) -> Result<Value, String> {
+    let mut root = Map::new();
+    let mut section: Option<String> = None;
+
+    for (number, raw_line) in source.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with([';', '#']) {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| format!("invalid INI: unclosed section on line {}", number + 1))?;
+            let name = name.trim().to_string();
+            root.entry(name.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+            section = Some(name);
+            continue;
+        }
+
+        let Some(split) = line.find(['=', ':']) else {
+            return Err(format!("invalid INI: expected key=value on line {}", number + 1));
+        };
+        let key = line[..split].trim().to_string();
+        let value = Value::String(unquote(line[split + 1..].trim()).to_string());
+
+        let target = match &section {
+            Some(name) => match root.get_mut(name) {
+                Some(Value::Object(object)) => object,
+                _ => return Err(format!("invalid INI: section '{}' clashes with a key", name)),
+            },
+            None => &mut root,
+        };
+        target.insert(key, value);
+    }
+
+    Ok(Value::Object(root))
+}
+
+/// Strip one pair of matching surrounding quotes.
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Convert a MessagePack value into JSON.
+///
+/// Binary data becomes a base64 string and timestamps (extension type -1)
+/// become `{"seconds": s, "nanoseconds": n}`; other extension values become
+/// `{"ext": type, "data": base64}`. Map keys that are not strings are
+/// written as their JSON text (`1`, `true`). Non-finite floats become
+/// `null`. Trailing bytes after the value are an error.
+#[cfg(feature = "msgpack")]
+pub fn msgpack_to_json(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = MsgpackReader { bytes, pos: 0 };
+    let value = reader.value(0)?;
+    if reader.pos != bytes.len() {
+        return Err(format!(
+            "invalid MessagePack: {} trailing bytes after the value",
+            bytes.len() - reader.pos
+        ));
+    }
+    Ok(value)
+}
+
+/// Nesting deeper than this is rejected rather than risking the stack.
+#[cfg(feature = "msgpack")]
+const MSGPACK_MAX_DEPTH: usize = 512;
+
+#[cfg(feature = "msgpack")]
+struct MsgpackReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+#[cfg(feature = "msgpack")]
+impl<'a> MsgpackReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| format!("invalid MessagePack: truncated at byte {}", self.pos))?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    /// Read a big-endian length of `width` bytes.
+    fn len(&mut self, width: usize) -> Result<usize, String> {
+        let bytes = self.take(width)?;
+        Ok(bytes.iter().fold(0usize, |len, &b| (len << 8) | b as usize))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MSGPACK_MAX_DEPTH {
+            return Err("invalid MessagePack: nested too deeply".to_string());
+        }
+        let marker = self.take(1)?[0];
+        Ok(match marker {
+            0x00..=0x7f => Value::from(marker),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize, depth)?,
+            0x90..=0x9f => self.seq((marker & 0x0f) as usize, depth)?,
+            0xa0..=0xbf => self.str((marker & 0x1f) as usize)?,
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xc4..=0xc6 => {
+                let len = self.len(1 << (marker - 0xc4))?;
+                Value::String(STANDARD.encode(self.take(len)?))
+            }
+            0xc7..=0xc9 => {
+                let len = self.len(1 << (marker - 0xc7))?;
+                self.ext(len)?
+            }
+            0xca => float(f32::from_be_bytes(self.array()?) as f64),
+            0xcb => float(f64::from_be_bytes(self.array()?)),
+            0xcc => Value::from(self.take(1)?[0]),
+            0xcd => Value::from(u16::from_be_bytes(self.array()?)),
+            0xce => Value::from(u32::from_be_bytes(self.array()?)),
+            0xcf => Value::from(u64::from_be_bytes(self.array()?)),
+            0xd0 => Value::from(self.take(1)?[0] as i8),
+            0xd1 => Value::from(i16::from_be_bytes(self.array()?)),
+            0xd2 => Value::from(i32::from_be_bytes(self.array()?)),
+            0xd3 => Value::from(i64::from_be_bytes(self.array()?)),
+            0xd4..=0xd8 => self.ext(1 << (marker - 0xd4))?,
+            0xd9..=0xdb => {
+                let len = self.len(1 << (marker - 0xd9))?;
+                self.str(len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.len(2 << (marker - 0xdc))?;
+                self.seq(len, depth)?
+            }
+            0xde | 0xdf => {
+                let len = self.len(2 << (marker - 0xde))?;
+                self.map(len, depth)?
+            }
+            0xe0..=0xff => Value::from(marker as i8),
+            0xc1 => {
+                return Err(format!(
+                    "invalid MessagePack: reserved byte 0xc1 at byte {}",
+                    self.pos - 1
+                ))
+            }
+        })
+    }
+
+    fn str(&mut self, len: usize) -> Result<Value, String> {
+        let start = self.pos;
+        let bytes = self.take(len)?;
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| format!("invalid MessagePack: string at byte {} is not UTF-8", start))?;
+        Ok(Value::String(text.to_string()))
+    }
+
+    fn seq(&mut self, len: usize, depth: usize) -> Result<Value, String> {
+        // Every element takes at least one byte; don't trust the length for
+        // the allocation.
+        let mut items = Vec::with_capacity(len.min(self.bytes.len() - self.pos));
+        for _ in 0..len {
+            items.push(self.value(depth + 1)?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<Value, String> {
+        let mut map = Map::new();
+        for _ in 0..len {
+            let key = match self.value(depth + 1)? {
+                Value::String(key) => key,
+                other => other.to_string(),
+            };
+            let value = self.value(depth + 1)?;
+            map.insert(key, value);
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn ext(&mut self, len: usize) -> Result<Value, String> {
+        let kind = self.take(1)?[0] as i8;
+        let data = self.take(len)?;
+        let timestamp = match (kind, data.len()) {
+            (-1, 4) => Some((u32::from_be_bytes(data.try_into().unwrap()) as i64, 0)),
+            (-1, 8) => {
+                let packed = u64::from_be_bytes(data.try_into().unwrap());
+                Some(((packed & 0x3_ffff_ffff) as i64, (packed >> 34) as u32))
+            }
+            (-1, 12) => Some((
+                i64::from_be_bytes(data[4..].try_into().unwrap()),
+                u32::from_be_bytes(data[..4].try_into().unwrap()),
+            )),
+            _ => None,
+        };
+        let mut object = Map::new();
+        match timestamp {
+            Some((seconds, nanoseconds)) => {
+                object.insert("seconds".to_string(), Value::from(seconds));
+                object.insert("nanoseconds".to_string(), Value::from(nanoseconds));
+            }
+            None => {
+                object.insert("ext".to_string(), Value::from(kind));
+                object.insert("data".to_string(), Value::String(STANDARD.encode(data)));
+            }
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn float(f: f64) -> Value {
+    serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,208 @@
+//! Structured HTML form extraction — see
+//! [`ChadSelect::extract_forms`](crate::ChadSelect::extract_forms).
+
+use scraper::{ElementRef, Selector};
+
+use crate::ChadSelect;
+
+/// An HTML `<form>` and its named fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Form {
+    /// The `action` attribute as written (possibly relative); `None` posts
+    /// back to the page itself.
+    pub action: Option<String>,
+    /// The method, uppercased: `GET` (the default) or `POST`.
+    pub method: String,
+    /// The `enctype` attribute, if set.
+    pub enctype: Option<String>,
+    /// The form's `id`, if set.
+    pub id: Option<String>,
+    /// The form's `name`, if set.
+    pub name: Option<String>,
+    /// Named `<input>`, `<select>`, `<textarea>`, and `<button>` fields in
+    /// document order, including those outside the form that name it with
+    /// a `form` attribute.
+    pub fields: Vec<FormField>,
+}
+
+/// One named form control.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormField {
+    /// The `name` attribute.
+    pub name: String,
+    /// The input `type`, lowercased (`text` when unset), or `select`,
+    /// `textarea`, or `button-<type>` for the other controls.
+    pub kind: String,
+    /// The current value: the `value` attribute, a textarea's text, or the
+    /// first selected option of a select.
+    pub value: Option<String>,
+    /// Whether a checkbox or radio is `checked`.
+    pub checked: bool,
+    /// Whether the control is `required`.
+    pub required: bool,
+    /// Whether the control is `disabled` (and so never submitted).
+    pub disabled: bool,
+    /// A select's options; empty for other controls.
+    pub options: Vec<FormOption>,
+}
+
+/// One `<option>` of a select.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormOption {
+    /// The `value` attribute, or the option's text when unset.
+    pub value: String,
+    /// The option's text.
+    pub label: String,
+    /// Whether the option is `selected`.
+    pub selected: bool,
+}
+
+impl Form {
+    /// The name/value pairs a browser would submit without user input:
+    /// enabled fields only, checked checkboxes and radios, the selected
+    /// options of selects (the first option of a single select when none
+    /// is), and no buttons or file inputs.
+    pub fn submission(&self) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+        for field in self.fields.iter().filter(|f| !f.disabled) {
+            match field.kind.as_str() {
+                "checkbox" | "radio" if field.checked => {
+                    let value = field.value.clone().unwrap_or_else(|| "on".to_string());
+                    pairs.push((field.name.clone(), value));
+                }
+                "checkbox" | "radio" | "file" | "submit" | "image" | "reset" => {}
+                kind if kind.starts_with("button") => {}
+                "select" => {
+                    let selected: Vec<_> = field.options.iter().filter(|o| o.selected).collect();
+                    let values = if selected.is_empty() {
+                        field.value.iter().cloned().collect()
+                    } else {
+                        selected.iter().map(|o| o.value.clone()).collect::<Vec<_>>()
+                    };
+                    pairs.extend(values.into_iter().map(|value| (field.name.clone(), value)));
+                }
+                _ => pairs.push((field.name.clone(), field.value.clone().unwrap_or_default())),
+            }
+        }
+        pairs
+    }
+}
+
+impl ChadSelect {
+    /// Every `<form>` of the HTML item at content `index`, in document order.
+    /// Empty when `index` is out of range or not an HTML item.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<form action="/search" method="get">
+    ///   <input name="q" value="civic">
+    ///   <select name="sort"><option value="price">Price</option><option value="new" selected>Newest</option></select>
+    ///   <button type="submit">Go</button>
+    /// </form>"#.to_string());
+    ///
+    /// let form = &cs.extract_forms(0)[0];
+    /// assert_eq!(form.action.as_deref(), Some("/search"));
+    /// assert_eq!(form.method, "GET");
+    /// assert_eq!(
+    ///     form.submission(),
+    ///     vec![("q".to_string(), "civic".to_string()), ("sort".to_string(), "new".to_string())]
+    /// );
+    /// ```
+    pub fn extract_forms(&self, index: usize) -> Vec<Form> {
+        let Some(item) = self.html_item(index) else {
+            return vec![];
+        };
+        let html = item.html();
+        let forms = Selector::parse("form").expect("valid selector");
+        let controls = Selector::parse("input[name], select[name], textarea[name], button[name]")
+            .expect("valid selector");
+
+        html.select(&forms)
+            .map(|form| {
+                let attr = |name: &str| form.value().attr(name).map(str::to_string);
+                let id = attr("id");
+                // Controls inside the form (unless they name another form),
+                // plus controls elsewhere naming this form's id.
+                let fields = html
+                    .select(&controls)
+                    .filter(|control| match control.value().attr("form") {
+                        Some(owner) => id.as_deref() == Some(owner),
+                        None => control.ancestors().any(|a| a.id() == form.id()),
+                    })
+                    .map(|control| form_field(&control))
+                    .collect();
+
+                Form {
+                    action: attr("action").filter(|a| !a.trim().is_empty()),
+                    method: attr("method")
+                        .map(|m| m.trim().to_ascii_uppercase())
+                        .filter(|m| !m.is_empty())
+                        .unwrap_or_else(|| "GET".to_string()),
+                    enctype: attr("enctype"),
+                    name: attr("name"),
+                    id,
+                    fields,
+                }
+            })
+            .collect()
+    }
+}
+
+fn form_field(control: &ElementRef) -> FormField {
+    let value = control.value();
+    let attr = |name: &str| value.attr(name).map(str::to_string);
+    let mut field = FormField {
+        name: attr("name").unwrap_or_default(),
+        value: attr("value"),
+        checked: value.attr("checked").is_some(),
+        required: value.attr("required").is_some(),
+        disabled: value.attr("disabled").is_some(),
+        ..FormField::default()
+    };
+
+    match value.name() {
+        "select" => {
+            let options = Selector::parse("option").expect("valid selector");
+            field.kind = "select".to_string();
+            field.options = control
+                .select(&options)
+                .map(|option| {
+                    let label = option.text().collect::<String>().trim().to_string();
+                    FormOption {
+                        value: option
+                            .value()
+                            .attr("value")
+                            .map_or(label.clone(), str::to_string),
+                        label,
+                        selected: option.value().attr("selected").is_some(),
+                    }
+                })
+                .collect();
+            let multiple = value.attr("multiple").is_some();
+            field.value = field
+                .options
+                .iter()
+                .find(|o| o.selected)
+                .or_else(|| field.options.first().filter(|_| !multiple))
+                .map(|o| o.value.clone());
+        }
+        "textarea" => {
+            field.kind = "textarea".to_string();
+            field.value = Some(control.text().collect());
+        }
+        "button" => {
+            let kind = value.attr("type").unwrap_or("submit").to_ascii_lowercase();
+            field.kind = format!("button-{kind}");
+        }
+        _ => {
+            field.kind = value
+                .attr("type")
+                .unwrap_or("text")
+                .trim()
+                .to_ascii_lowercase();
+        }
+    }
+    field
+}
@@ -0,0 +1,108 @@
+//! HTTP archives (HAR): the responses of a browser-captured session.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde_json::Value;
+
+use crate::content::{is_binary_mime, ContentType};
+use crate::warnings::{warning, Capture};
+use crate::ChadSelect;
+
+impl ChadSelect {
+    /// Add the response bodies recorded in a HAR file (as exported by a
+    /// browser's developer tools), one content item per response, in entry
+    /// order. Returns the number of items added.
+    ///
+    /// Each item is typed by the response's `mimeType` as `add_url` would
+    /// type it (HTML, JSON, Markdown, otherwise text; the body is sniffed
+    /// when the type is missing or generic), and the request URL is kept as
+    /// its [`content_source`](ChadSelect::content_source). Base64-encoded
+    /// bodies are decoded. Responses without a body and binary ones (images, fonts,
+    /// media, archives) are skipped.
+    ///
+    /// Errors when `content` is not a HAR document. Bodies rejected by the
+    /// size limits are skipped with a warning.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let har = r#"{"log": {"entries": [
+    ///     {"request": {"url": "https://example.com/car/42"},
+    ///      "response": {"content": {"mimeType": "text/html",
+    ///                               "text": "<h1>Civic</h1>"}}},
+    ///     {"request": {"url": "https://example.com/api/price/42"},
+    ///      "response": {"content": {"mimeType": "application/json",
+    ///                               "text": "{\"price\": 28500}"}}}
+    /// ]}}"#;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// assert_eq!(cs.add_har(har), Ok(2));
+    /// assert_eq!(cs.select(0, "css:h1"), "Civic");
+    /// assert_eq!(cs.select(0, "json:price"), "28500");
+    /// assert_eq!(cs.content_source(1), Some("https://example.com/api/price/42"));
+    /// ```
+    pub fn add_har(&mut self, content: &str) -> Result<usize, String> {
+        let har: Value =
+            serde_json::from_str(content).map_err(|e| format!("invalid HAR JSON: {}", e))?;
+        let entries = har
+            .pointer("/log/entries")
+            .and_then(Value::as_array)
+            .ok_or("not a HAR document: no log.entries array")?;
+
+        let capture = Capture::begin();
+        let mut added = 0;
+        for entry in entries {
+            let url = entry.pointer("/request/url").and_then(Value::as_str);
+            let Some((mime, body)) = response_body(entry) else {
+                continue;
+            };
+            let content_type = ContentType::from_mime(mime, &body);
+            let body = String::from_utf8_lossy(&body).into_owned();
+            match self.try_add(body, content_type) {
+                Ok(()) => {
+                    added += 1;
+                    self.content_list
+                        .last_mut()
+                        .expect("item was just added")
+                        .source = url.map(str::to_string);
+                }
+                Err(e) => warning!(
+                    ContentRejected,
+                    "Response for {} not added: {}",
+                    url.unwrap_or("<no url>"),
+                    e
+                ),
+            }
+        }
+        self.keep_warnings(capture);
+        Ok(added)
+    }
+}
+
+/// The MIME type and decoded body of an entry's response, when it has a
+/// textual one.
+fn response_body(entry: &Value) -> Option<(Option<&str>, Vec<u8>)> {
+    let content = entry.pointer("/response/content")?;
+    let mime = content
+        .get("mimeType")
+        .and_then(Value::as_str)
+        .filter(|mime| !mime.trim().is_empty());
+    if mime.is_some_and(is_binary_mime) {
+        return None;
+    }
+    let text = content.get("text").and_then(Value::as_str)?;
+    if text.is_empty() {
+        return None;
+    }
+    let body = match content.get("encoding").and_then(Value::as_str) {
+        Some("base64") => match STANDARD.decode(text.trim()) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warning!(InvalidContent, "Invalid base64 response body in HAR: {}", e);
+                return None;
+            }
+        },
+        _ => text.as_bytes().to_vec(),
+    };
+    Some((mime, body))
+}
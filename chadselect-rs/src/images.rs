@@ -0,0 +1,232 @@
+//! Image metadata extraction — see
+//! [`ChadSelect::extract_images`](crate::ChadSelect::extract_images) and
+//! [`parse_srcset`].
+
+use scraper::{ElementRef, Selector};
+
+use crate::ChadSelect;
+
+/// Attributes lazy-loading scripts keep the real `src` in, most common first.
+const LAZY_SRC_ATTRIBUTES: [&str; 4] = ["data-src", "data-lazy-src", "data-original", "data-url"];
+
+/// Attributes lazy-loading scripts keep the real `srcset` in.
+const LAZY_SRCSET_ATTRIBUTES: [&str; 2] = ["data-srcset", "data-lazy-srcset"];
+
+/// One `<img>` of a page.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Image {
+    /// The image URL as written: `src`, or a lazy-load attribute
+    /// (`data-src`, …) when `src` is missing, empty, or a `data:`
+    /// placeholder.
+    pub src: Option<String>,
+    /// The `srcset` candidates (again falling back to `data-srcset`),
+    /// followed by those of any `<source>` in an enclosing `<picture>`.
+    pub srcset: Vec<SrcsetCandidate>,
+    /// The `alt` text; `Some("")` marks a decorative image.
+    pub alt: Option<String>,
+    /// The `title` attribute.
+    pub title: Option<String>,
+    /// The `width` attribute, in CSS pixels.
+    pub width: Option<u32>,
+    /// The `height` attribute, in CSS pixels.
+    pub height: Option<u32>,
+    /// Whether `src` or `srcset` came from a lazy-load attribute.
+    pub lazy: bool,
+}
+
+/// One candidate of a `srcset`: a URL and at most one descriptor. With
+/// neither descriptor the candidate is `1x`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SrcsetCandidate {
+    /// The candidate URL as written.
+    pub url: String,
+    /// A width descriptor (`800w`).
+    pub width: Option<u32>,
+    /// A pixel-density descriptor (`2x`).
+    pub density: Option<f32>,
+}
+
+/// Parse a `srcset` attribute per the HTML algorithm: URLs may contain
+/// commas (`/img,w_400.jpg 400w`), and candidates with invalid
+/// descriptors are dropped.
+///
+/// ```rust
+/// use chadselect::images::parse_srcset;
+///
+/// let candidates = parse_srcset("/a.jpg 480w, /a,large.jpg 1080w, /b.jpg 2x, /c.jpg");
+/// assert_eq!(candidates.len(), 4);
+/// assert_eq!(candidates[1].url, "/a,large.jpg");
+/// assert_eq!(candidates[1].width, Some(1080));
+/// assert_eq!(candidates[2].density, Some(2.0));
+/// assert_eq!((candidates[3].width, candidates[3].density), (None, None));
+/// ```
+pub fn parse_srcset(srcset: &str) -> Vec<SrcsetCandidate> {
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+        let url_end = rest
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+        let (url, after) = rest.split_at(url_end);
+
+        // A URL ending in commas ends the candidate; otherwise descriptors
+        // run to the next comma.
+        let (url, descriptors) = if url.ends_with(',') {
+            rest = after;
+            (url.trim_end_matches(','), "")
+        } else {
+            let descriptors_end = after.find(',').unwrap_or(after.len());
+            rest = &after[descriptors_end..];
+            (url, &after[..descriptors_end])
+        };
+
+        if let Some(candidate) = srcset_candidate(url, descriptors) {
+            candidates.push(candidate);
+        }
+    }
+}
+
+/// The candidate for `url` and its whitespace-separated `descriptors`, or
+/// `None` when the descriptors are invalid.
+fn srcset_candidate(url: &str, descriptors: &str) -> Option<SrcsetCandidate> {
+    let mut candidate = SrcsetCandidate {
+        url: url.to_string(),
+        ..SrcsetCandidate::default()
+    };
+    for descriptor in descriptors.split_ascii_whitespace() {
+        if candidate.width.is_some() || candidate.density.is_some() {
+            return None;
+        }
+        if let Some(width) = descriptor.strip_suffix('w') {
+            candidate.width = Some(width.parse().ok().filter(|w| *w > 0)?);
+        } else if let Some(density) = descriptor.strip_suffix('x') {
+            let density: f32 = density.parse().ok()?;
+            if !density.is_finite() || density < 0.0 {
+                return None;
+            }
+            candidate.density = Some(density);
+        } else {
+            // Height descriptors (`h`) are reserved; anything else is invalid.
+            return None;
+        }
+    }
+    (!candidate.url.is_empty()).then_some(candidate)
+}
+
+impl ChadSelect {
+    /// Every `<img>` of the HTML item at content `index`, in document order.
+    /// Empty when `index` is out of range or not an HTML item.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<img src="data:image/gif;base64,R0lGOD" data-src="/civic.jpg"
+    ///                     data-srcset="/civic-800.jpg 800w, /civic-1600.jpg 1600w"
+    ///                     alt="2021 Honda Civic" width="800" height="600">"#.to_string());
+    ///
+    /// let image = &cs.extract_images(0)[0];
+    /// assert_eq!(image.src.as_deref(), Some("/civic.jpg"));
+    /// assert!(image.lazy);
+    /// assert_eq!(image.srcset[1].width, Some(1600));
+    /// assert_eq!(image.alt.as_deref(), Some("2021 Honda Civic"));
+    /// assert_eq!((image.width, image.height), (Some(800), Some(600)));
+    /// ```
+    pub fn extract_images(&self, index: usize) -> Vec<Image> {
+        let Some(item) = self.html_item(index) else {
+            return vec![];
+        };
+        let html = item.html();
+        let images = Selector::parse("img").expect("valid selector");
+        html.select(&images).map(|img| image(&img)).collect()
+    }
+}
+
+fn image(img: &ElementRef) -> Image {
+    let value = img.value();
+    let attr = |name: &str| value.attr(name).map(str::to_string);
+
+    let mut lazy = false;
+    let mut src = attr("src").filter(|s| !is_placeholder(s));
+    if src.is_none() {
+        src = lazy_attribute(img, &LAZY_SRC_ATTRIBUTES);
+        lazy = src.is_some();
+    }
+
+    let mut srcset = value.attr("srcset").map(parse_srcset).unwrap_or_default();
+    if srcset.is_empty() {
+        if let Some(lazy_srcset) = lazy_attribute(img, &LAZY_SRCSET_ATTRIBUTES) {
+            srcset = parse_srcset(&lazy_srcset);
+            lazy |= !srcset.is_empty();
+        }
+    }
+    srcset.extend(picture_sources(img));
+
+    Image {
+        src,
+        srcset,
+        alt: attr("alt"),
+        title: attr("title"),
+        width: value.attr("width").and_then(dimension),
+        height: value.attr("height").and_then(dimension),
+        lazy,
+    }
+}
+
+/// Whether `src` is a stand-in for a lazily loaded image: empty, or an
+/// inline `data:` URI.
+fn is_placeholder(src: &str) -> bool {
+    let src = src.trim();
+    src.is_empty()
+        || src
+            .get(..5)
+            .is_some_and(|s| s.eq_ignore_ascii_case("data:"))
+}
+
+/// The first non-empty value among `attributes`.
+fn lazy_attribute(img: &ElementRef, attributes: &[&str]) -> Option<String> {
+    attributes
+        .iter()
+        .filter_map(|name| img.value().attr(name))
+        .map(str::trim)
+        .find(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// The `srcset` candidates of the `<source>` siblings when `img` sits in a
+/// `<picture>`.
+fn picture_sources(img: &ElementRef) -> Vec<SrcsetCandidate> {
+    let Some(picture) = img
+        .parent()
+        .and_then(ElementRef::wrap)
+        .filter(|parent| parent.value().name() == "picture")
+    else {
+        return vec![];
+    };
+    picture
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| child.value().name() == "source")
+        .filter_map(|source| {
+            let value = source.value();
+            value.attr("srcset").or_else(|| value.attr("data-srcset"))
+        })
+        .flat_map(parse_srcset)
+        .collect()
+}
+
+/// A `width`/`height` attribute in pixels (`"640"`, `"640px"`).
+fn dimension(value: &str) -> Option<u32> {
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .ok()
+}
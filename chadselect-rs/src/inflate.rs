@@ -0,0 +1,33 @@
+//! DEFLATE (RFC 1951) decompression, for PDF `FlateDecode` streams and zip
+//! archive members, via `miniz_oxide`.
+
+use miniz_oxide::inflate::{self, TINFLStatus};
+
+/// Refuse to inflate a single stream past this size (a decompression bomb
+/// guard).
+const MAX_OUTPUT: usize = 256 * 1024 * 1024;
+
+/// Decompress zlib-wrapped (or, failing the header check, raw) DEFLATE
+/// data. Input that ends early yields what was decoded so far — truncated
+/// streams are common in the wild — and the trailing checksum is not
+/// verified.
+#[cfg(feature = "pdf")]
+pub(crate) fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let zlib = data.len() >= 2
+        && data[0] & 0x0f == 8
+        && (u16::from(data[0]) << 8 | u16::from(data[1])) % 31 == 0;
+    inflate_raw(if zlib { &data[2..] } else { data })
+}
+
+/// Decompress raw DEFLATE data, as stored in zip archives. Input that ends
+/// early yields what was decoded so far.
+pub(crate) fn inflate_raw(data: &[u8]) -> Result<Vec<u8>, String> {
+    match inflate::decompress_to_vec_with_limit(data, MAX_OUTPUT) {
+        Ok(out) => Ok(out),
+        Err(e) if e.status == TINFLStatus::FailedCannotMakeProgress => Ok(e.output),
+        Err(e) if e.status == TINFLStatus::HasMoreOutput => {
+            Err("invalid deflate data: stream too large".to_string())
+        }
+        Err(e) => Err(format!("invalid deflate data: {}", e)),
+    }
+}
@@ -0,0 +1,163 @@
+//! Content item introspection — see
+//! [`ChadSelect::content_info`](crate::ChadSelect::content_info).
+
+#[cfg(feature = "css")]
+use scraper::{Html, Selector};
+
+#[cfg(feature = "css")]
+use crate::content::visible_text;
+use crate::content::{declared_charset, ContentItem, ContentType};
+use crate::ChadSelect;
+
+/// How much text language detection looks at; more adds cost, not accuracy.
+const LANGUAGE_SAMPLE_CHARS: usize = 4096;
+
+/// What is known about one loaded content item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentInfo {
+    /// The content type it was added as.
+    pub content_type: ContentType,
+    /// Where it came from (see
+    /// [`content_source`](ChadSelect::content_source)).
+    pub source: Option<String>,
+    /// Size of the raw content in bytes.
+    pub bytes: usize,
+    /// The detected language of its text, as an ISO 639-3 code (`eng`,
+    /// `deu`, `jpn`, …). For HTML and Markdown only the visible text is
+    /// considered; for JSON only the string values.
+    pub language: Option<String>,
+    /// Detection confidence, 0–1 (0 when no language was detected).
+    pub language_confidence: f64,
+    /// The language the page declares (`<html lang>`), as written.
+    pub declared_language: Option<String>,
+    /// The charset the content declares: a `<meta charset>` (or
+    /// `http-equiv` Content-Type) or an XML declaration's `encoding`,
+    /// lowercased.
+    pub declared_charset: Option<String>,
+    /// The charset the content was decoded from: the one `add_url` chose,
+    /// else `UTF-8`.
+    pub charset: String,
+    /// The HTML `<title>`, or a Markdown document's first `#` heading,
+    /// whitespace collapsed.
+    ///
+    /// This and `declared_language` need the `css` feature; without it
+    /// they are `None` and language detection samples the raw markup.
+    pub title: Option<String>,
+}
+
+impl ContentInfo {
+    /// Whether the detected language is `code` (ISO 639-3) with at least
+    /// `min_confidence`.
+    pub fn is_language(&self, code: &str, min_confidence: f64) -> bool {
+        self.language.as_deref() == Some(code) && self.language_confidence >= min_confidence
+    }
+}
+
+impl ChadSelect {
+    /// Language, charset, and title of the content item at `index`, for
+    /// routing pages to different selector sets. `None` when `index` is out
+    /// of range.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<html lang="de"><head><meta charset="iso-8859-1">
+    ///   <title>Gebrauchtwagen kaufen</title></head>
+    ///   <body><p>Der Wagen ist in sehr gutem Zustand und wurde regelmäßig in der Werkstatt gewartet.</p></body>
+    /// </html>"#.to_string());
+    ///
+    /// let info = cs.content_info(0).unwrap();
+    /// assert_eq!(info.language.as_deref(), Some("deu"));
+    /// assert_eq!(info.declared_language.as_deref(), Some("de"));
+    /// assert_eq!(info.declared_charset.as_deref(), Some("iso-8859-1"));
+    /// assert_eq!(info.charset, "UTF-8");
+    /// assert_eq!(info.title.as_deref(), Some("Gebrauchtwagen kaufen"));
+    /// ```
+    pub fn content_info(&self, index: usize) -> Option<ContentInfo> {
+        let item = self.content_list.get(index)?;
+        let (sample, declared_language, title) = match item.content_type {
+            #[cfg(feature = "css")]
+            ContentType::Html | ContentType::Markdown => {
+                let html = item.html();
+                (
+                    visible_text(&html, LANGUAGE_SAMPLE_CHARS),
+                    declared_language(&html),
+                    title(&html, &item.content_type),
+                )
+            }
+            ContentType::Json => (json_strings(&item.content), None, None),
+            _ => (
+                item.content.chars().take(LANGUAGE_SAMPLE_CHARS).collect(),
+                None,
+                None,
+            ),
+        };
+        let detected = whatlang::detect(&sample);
+
+        Some(ContentInfo {
+            content_type: item.content_type.clone(),
+            source: item.source.clone(),
+            bytes: item.size(),
+            language: detected.as_ref().map(|info| info.lang().code().to_string()),
+            language_confidence: detected.as_ref().map_or(0.0, |info| info.confidence()),
+            declared_language,
+            declared_charset: declared_charset(head(item)),
+            charset: item.charset.clone().unwrap_or_else(|| "UTF-8".to_string()),
+            title,
+        })
+    }
+}
+
+/// The start of the raw content, where charset declarations live.
+fn head(item: &ContentItem) -> &str {
+    let mut end = item.content.len().min(1024);
+    while !item.content.is_char_boundary(end) {
+        end -= 1;
+    }
+    &item.content[..end]
+}
+
+/// The string values of a JSON document, space-joined, up to
+/// [`LANGUAGE_SAMPLE_CHARS`].
+fn json_strings(json: &str) -> String {
+    fn collect(value: &serde_json::Value, out: &mut String) {
+        if out.len() >= LANGUAGE_SAMPLE_CHARS {
+            return;
+        }
+        match value {
+            serde_json::Value::String(s) => {
+                out.push_str(s);
+                out.push(' ');
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect(v, out)),
+            serde_json::Value::Object(map) => map.values().for_each(|v| collect(v, out)),
+            _ => {}
+        }
+    }
+    let mut out = String::new();
+    if let Ok(value) = serde_json::from_str(json) {
+        collect(&value, &mut out);
+    }
+    out
+}
+
+#[cfg(feature = "css")]
+fn declared_language(html: &Html) -> Option<String> {
+    let root = Selector::parse("html[lang]").expect("valid selector");
+    let lang = html.select(&root).next()?.value().attr("lang")?.trim();
+    (!lang.is_empty()).then(|| lang.to_string())
+}
+
+#[cfg(feature = "css")]
+fn title(html: &Html, content_type: &ContentType) -> Option<String> {
+    let selector = match content_type {
+        ContentType::Markdown => "h1",
+        _ => "title",
+    };
+    let selector = Selector::parse(selector).expect("valid selector");
+    let element = html.select(&selector).next()?;
+    let title = element.text().collect::<Vec<_>>().join(" ");
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
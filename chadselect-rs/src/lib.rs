@@ -18,11 +18,27 @@
 //! | Prefix   | Engine    | Content types |
 //! |----------|-----------|---------------|
 //! | `regex:` | Regex     | All           |
-//! | `xpath:` | XPath 1.0 | HTML, Text    |
-//! | `css:`   | CSS       | HTML          |
-//! | `json:`  | JMESPath  | JSON          |
+//! | `regexm:` | Regex, `^`/`$` per line | All |
+//! | `xpath:` | XPath 1.0 | HTML, Text, Markdown |
+//! | `css:`   | CSS       | HTML, Markdown |
+//! | `json:`  | JMESPath  | JSON, TOML, INI |
+//! | `auto:`  | Detected  | Per engine    |
+//! | `embedded-json:` | Source query, then JMESPath | Per source engine |
+//! | `structured:` | JMESPath over page metadata | HTML |
+//! | `text:`  | Full-text search | HTML, Text, Markdown |
+//! | `pat:`   | Built-in named regex | All |
+//! | `glob:`  | Whole-line glob match | All |
+//! | `lines:` | Line filter (`contains('ERROR')`) | All |
+//! | `bytes:` | Byte regex | All, Binary included |
+//! | `hex:`   | Hex byte signature (`de ad ?? ef`) | All, Binary included |
 //!
-//! If no prefix is provided, the query defaults to Regex.
+//! If no prefix is provided, the query defaults to Regex. Build with
+//! [`PrefixMode::Strict`] (see [`ChadSelectBuilder`]) to reject unprefixed
+//! queries instead.
+//!
+//! Each engine is a Cargo feature — `css`, `xpath`, `json`, `regex`, all on
+//! by default. A query for an engine the build leaves out is skipped with an
+//! [`UnsupportedEngine`](WarningKind::UnsupportedEngine) warning.
 //!
 //! ## Post-processing functions
 //!
@@ -31,20 +47,137 @@
 //! ```text
 //! css:.price >> normalize-space() >> uppercase()
 //! xpath://div/text() >> substring-after('VIN: ')
+//! json:products[].name >> uppercase()
 //! ```
 
+#[cfg(not(any(feature = "css", feature = "json", feature = "regex")))]
+compile_error!("chadselect needs at least one engine feature: `css`, `xpath`, `json`, or `regex`");
+
+#[cfg(feature = "readability")]
+pub mod article;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod classify;
+#[cfg(feature = "css")]
+pub mod clean;
+pub mod config;
+pub mod contacts;
 pub mod content;
+#[cfg(feature = "delta")]
+pub mod delta;
+#[cfg(feature = "css")]
+pub mod dom;
 pub mod engine;
-pub mod functions;
+#[cfg(feature = "feeds")]
+pub mod feeds;
+#[cfg(feature = "fetch")]
+mod fetch;
+pub mod files;
+pub mod formats;
+#[cfg(feature = "css")]
+pub mod forms;
+#[cfg(feature = "har")]
+pub mod har;
+#[cfg(feature = "css")]
+pub mod images;
+#[cfg(any(feature = "pdf", feature = "xlsx"))]
+mod inflate;
+#[cfg(feature = "info")]
+pub mod info;
+pub mod library;
+#[cfg(feature = "css")]
+pub mod links;
+#[cfg(feature = "mime")]
+pub mod mime;
+#[cfg(feature = "css")]
+pub mod normalize;
+#[cfg(feature = "css")]
+pub mod pagination;
+#[cfg(feature = "css")]
+mod pairs;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod prepared;
 pub mod query;
+pub mod result;
+pub mod rewrite;
+#[cfg(feature = "robots")]
+pub mod robots;
+pub mod rules;
+#[cfg(any(feature = "delta", feature = "arrow", feature = "sqlite"))]
+pub mod schema;
+#[cfg(feature = "similar")]
+pub mod similar;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod store;
+#[cfg(feature = "css")]
+mod suggest;
+mod template;
+#[cfg(all(feature = "css", feature = "xpath"))]
+pub mod translate;
+#[cfg(feature = "warc")]
+pub mod warc;
+pub mod watch;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
 
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::sync::Arc;
 
-use log::warn;
+use warnings::{warning, Capture};
+use watch::Change;
 
-pub use content::{ContentItem, ContentType};
+#[cfg(feature = "readability")]
+pub use article::Article;
+pub use cancel::{CancellationToken, QueryStatus};
+// Query parsing, the function pipeline, and the regex engine live in
+// `chadselect-core`; see `engine::regex` and `query` for the rest.
+pub use chadselect_core::{cancel, functions, patterns, warnings};
+pub use classify::{Classification, Classifier};
+pub use config::{ChadSelectBuilder, IndexMode, LimitPolicy, TextOptions, Translation};
+pub use contacts::Contacts;
+pub use content::{ContentItem, ContentType, MemoryUsage, ParseProgress};
+#[cfg(feature = "delta")]
+pub use delta::{ExtractionSnapshot, FieldChange, RecordChange, SnapshotDiff, SnapshotRecord};
+#[cfg(feature = "feeds")]
+pub use feeds::{FeedItem, SitemapUrl};
+pub use files::content_type_for_path;
+#[cfg(feature = "css")]
+pub use forms::{Form, FormField, FormOption};
 pub use functions::supported_text_functions;
-pub use query::{QueryType, FUNCTION_PIPE};
+#[cfg(feature = "css")]
+pub use images::{Image, SrcsetCandidate};
+#[cfg(feature = "info")]
+pub use info::ContentInfo;
+pub use library::QueryLibrary;
+#[cfg(feature = "css")]
+pub use links::{Link, LinkOptions};
+#[cfg(feature = "css")]
+pub use normalize::NormalizeOptions;
+#[cfg(feature = "css")]
+pub use pagination::{NextPage, NextPageSignal};
+pub use prepared::PreparedQuery;
+pub use query::{PrefixMode, QueryType, FALLBACK_OPERATOR, FUNCTION_PIPE};
+pub use result::{QueryCoverage, QueryResult, ResultLocation};
+pub use rewrite::RewriteRule;
+pub use rules::{FiredRule, RuleSet};
+#[cfg(any(feature = "delta", feature = "arrow", feature = "sqlite"))]
+pub use schema::{ColumnType, ExtractionSchema};
+#[cfg(feature = "snapshot")]
+pub use snapshot::Snapshot;
+#[cfg(feature = "warc")]
+pub use warc::WarcFilter;
+pub use warnings::{Warning, WarningKind};
+pub use watch::{WatchEvent, WatchId};
+
+/// Most warnings an instance keeps for [`ChadSelect::take_warnings`]; older
+/// ones are dropped first.
+const MAX_KEPT_WARNINGS: usize = 1024;
 
 /// Main entry point for data extraction.
 ///
@@ -52,36 +185,543 @@ pub use query::{QueryType, FUNCTION_PIPE};
 /// [`add_json`](ChadSelect::add_json), or [`add_text`](ChadSelect::add_text),
 /// then query with [`select`](ChadSelect::select) or
 /// [`query`](ChadSelect::query).
+///
+/// Cloning copies the content, named queries, and options; parsed caches are
+/// not copied and rebuild lazily in the clone, and watches (see
+/// [`watch`](ChadSelect::watch)) are not copied at all. See also
+/// [`snapshot`](ChadSelect::snapshot).
+#[derive(Clone)]
 pub struct ChadSelect {
     content_list: Vec<ContentItem>,
+    config: config::Config,
+    library: QueryLibrary,
+    /// The rules [`evaluate_rules`](ChadSelect::evaluate_rules) runs.
+    rules: RuleSet,
+    /// Query counter stamped onto items as they are used, for cache LRU.
+    clock: Cell<u64>,
+    /// Warnings recorded since the last [`take_warnings`](ChadSelect::take_warnings).
+    warnings: RefCell<Vec<Warning>>,
+    /// Queries run against content as it is added or appended.
+    watches: watch::Watches,
 }
 
 impl ChadSelect {
-    /// Create a new, empty `ChadSelect` instance.
+    /// Create a new, empty `ChadSelect` instance with the default options.
     pub fn new() -> Self {
-        Self {
-            content_list: Vec::new(),
-        }
+        ChadSelectBuilder::new().build()
+    }
+
+    /// Start building a `ChadSelect` with non-default options.
+    pub fn builder() -> ChadSelectBuilder {
+        ChadSelectBuilder::new()
     }
 
     // ── Content management ──────────────────────────────────────────────
 
     /// Add plain text content.
     pub fn add_text(&mut self, content: String) {
-        self.content_list
-            .push(ContentItem::new(content, ContentType::Text));
+        self.push(content, ContentType::Text);
     }
 
     /// Add HTML content (compatible with CSS, XPath, and Regex).
     pub fn add_html(&mut self, content: String) {
-        self.content_list
-            .push(ContentItem::new(content, ContentType::Html));
+        self.push(content, ContentType::Html);
+    }
+
+    /// Add an HTML snippet — a few elements returned by an API, say — parsed
+    /// as a fragment rather than a document. `add_html` wraps a snippet in
+    /// implied `<html><body>` elements, so absolute XPath such as `/div[1]`
+    /// finds nothing; here the snippet's top-level nodes are children of the
+    /// root. CSS and Regex queries behave as for `add_html`.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html_fragment("<div>Civic</div><div>Accord</div>".to_string());
+    /// assert_eq!(cs.select(0, "xpath:/div[2]/text()"), "Accord");
+    /// assert_eq!(cs.select(0, "css:div"), "Civic");
+    /// ```
+    pub fn add_html_fragment(&mut self, content: String) {
+        let capture = Capture::begin();
+        match self.fit_to_limits(content, &ContentType::Html) {
+            Ok(content) => {
+                let mut item = self.new_item(content, ContentType::Html);
+                item.fragment = true;
+                self.push_item(item);
+            }
+            Err(e) => warning!(ContentRejected, "Content not added: {}", e),
+        }
+        self.keep_warnings(capture);
     }
 
     /// Add JSON content (compatible with JMESPath and Regex).
     pub fn add_json(&mut self, content: String) {
-        self.content_list
-            .push(ContentItem::new(content, ContentType::Json));
+        self.push(content, ContentType::Json);
+    }
+
+    /// Add Markdown content. CSS and XPath query the rendered HTML (`css:h2`,
+    /// `xpath://pre/code`); Regex runs on the raw Markdown source.
+    pub fn add_markdown(&mut self, content: String) {
+        self.push(content, ContentType::Markdown);
+    }
+
+    /// Add TOML content (compatible with JMESPath and Regex). `json:` queries
+    /// run against the document converted to JSON; Regex sees the raw source.
+    #[cfg(feature = "toml")]
+    pub fn add_toml(&mut self, content: String) {
+        self.push(content, ContentType::Toml);
+    }
+
+    /// Add INI content (compatible with JMESPath and Regex). Sections become
+    /// objects (`json:database.host`); see [`formats::ini_to_json`].
+    pub fn add_ini(&mut self, content: String) {
+        self.push(content, ContentType::Ini);
+    }
+
+    /// Add a MessagePack payload — a binary API response captured from a
+    /// mobile app, say. It is converted to JSON (see
+    /// [`formats::msgpack_to_json`]) and added as JSON content, so `json:`
+    /// queries run against it and Regex sees the JSON text.
+    ///
+    /// Errors when `bytes` is not a single MessagePack value, or when the
+    /// size limits reject the converted JSON.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// // {"model": "Civic", "price": 28500}
+    /// let payload = b"\x82\xa5model\xa5Civic\xa5price\xcd\x6f\x54";
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_msgpack(payload).unwrap();
+    /// assert_eq!(cs.select(0, "json:model"), "Civic");
+    /// assert_eq!(cs.select(0, "json:price"), "28500");
+    /// ```
+    #[cfg(feature = "msgpack")]
+    pub fn add_msgpack(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let value = formats::msgpack_to_json(bytes)?;
+        self.try_add(value.to_string(), ContentType::Json)
+    }
+
+    /// Add raw bytes — a firmware dump or a captured protocol frame — as
+    /// [`ContentType::Binary`] content. `bytes:` and `hex:` queries match the
+    /// bytes themselves; `regex:` sees them one character per byte, so ASCII
+    /// strings in the data read as text. The size limits count the original
+    /// bytes.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_bytes(b"\x7fELF\x02\x01\x01\x00fw-1.4.2\x00\xde\xad\xbe\xef");
+    /// assert_eq!(cs.select(0, r"bytes:fw-([\d.]+)\x00"), "1.4.2");
+    /// assert_eq!(cs.select(0, "hex:de ad ?? ef"), "deadbeef");
+    /// assert_eq!(cs.select(0, "hex:7f 45 4c 46 (??)"), "02");
+    /// ```
+    pub fn add_bytes(&mut self, bytes: &[u8]) {
+        let content = bytes.iter().map(|&b| char::from(b)).collect();
+        self.push(content, ContentType::Binary);
+    }
+
+    /// Add content of an explicit type, applying the configured size limits
+    /// (see [`ChadSelectBuilder::max_item_bytes`]). The `add_*` methods call
+    /// this and log a warning on rejection; call it directly to handle one.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, ContentType};
+    ///
+    /// let mut cs = ChadSelect::builder().max_item_bytes(16).build();
+    /// assert!(cs.try_add("<p>small</p>".to_string(), ContentType::Html).is_ok());
+    /// assert!(cs.try_add("<p>far too large</p>".to_string(), ContentType::Html).is_err());
+    /// assert_eq!(cs.content_count(), 1);
+    /// ```
+    pub fn try_add(&mut self, content: String, content_type: ContentType) -> Result<(), String> {
+        let content = self.fit_to_limits(content, &content_type)?;
+        let item = self.new_item(content, content_type);
+        self.push_item(item);
+        Ok(())
+    }
+
+    /// Load `item`, then run the watches against it.
+    fn push_item(&mut self, item: ContentItem) {
+        self.content_list.push(item);
+        self.notify_watches(self.content_list.len() - 1, Change::Added);
+    }
+
+    /// A content item carrying the configured parse options.
+    fn new_item(&self, content: String, content_type: ContentType) -> ContentItem {
+        let mut item = ContentItem::new(content, content_type);
+        item.hoist_templates = self.config.hoist_templates;
+        item
+    }
+
+    /// Add several items at once, as the `add_*` methods would, returning the
+    /// number added. Items the size limits reject are skipped with a warning.
+    ///
+    /// With `warm`, each added item is parsed straight away (see
+    /// [`warm`](ChadSelect::warm)), so the first query against it does not
+    /// pay for parsing — load and warm off the request path, then serve.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, ContentType};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// let added = cs.add_many(
+    ///     vec![
+    ///         (ContentType::Html, "<h1>Civic</h1>".to_string()),
+    ///         (ContentType::Json, r#"{"price": 28500}"#.to_string()),
+    ///     ],
+    ///     true,
+    /// );
+    /// assert_eq!(added, 2);
+    /// assert_eq!(cs.select(0, "json:price"), "28500");
+    /// ```
+    pub fn add_many(&mut self, items: Vec<(ContentType, String)>, warm: bool) -> usize {
+        let capture = Capture::begin();
+        let mut added = 0;
+        for (content_type, content) in items {
+            match self.try_add(content, content_type) {
+                Ok(()) => added += 1,
+                Err(e) => warning!(ContentRejected, "Content not added: {}", e),
+            }
+        }
+        if warm {
+            // `EvictOldest` drains from the front: if it reached this batch,
+            // every remaining item is new.
+            let start = self.content_list.len().saturating_sub(added);
+            self.warm_items(start);
+        }
+        self.keep_warnings(capture);
+        added
+    }
+
+    /// Extend the content item at `index` with `more` — the new tail of a
+    /// followed log file, or the next chunk of a streamed response. Later
+    /// queries see the whole content. Only that item's parsed caches are
+    /// dropped; other items keep theirs.
+    ///
+    /// Errors when there is no item at `index`, when it holds binary
+    /// content, or when the grown item would break a size limit (appends are
+    /// never truncated and never evict items).
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_text("09:00 INFO started\n".to_string());
+    /// cs.append_text(0, "09:01 ERROR disk full\n").unwrap();
+    /// assert_eq!(cs.query(-1, r"regex:ERROR (.+)"), ["disk full"]);
+    /// ```
+    pub fn append_text(&mut self, index: usize, more: &str) -> Result<(), String> {
+        self.check_append(index, more.len())?;
+        if self.content_list[index].content_type == ContentType::Binary {
+            return Err("append_text cannot extend binary content".to_string());
+        }
+        let from = self.content_list[index].content.len();
+        let baseline = self.watch_baseline(index);
+        self.content_list[index].append(more);
+        self.notify_watches(index, Change::Text { from, baseline });
+        Ok(())
+    }
+
+    /// Append one line of a JSON Lines stream to the JSON array held by the
+    /// content item at `index`: `line` must be a single JSON value, and
+    /// becomes the array's last element. Start a stream with
+    /// `add_json("[]".to_string())`. A cached value tree is extended in
+    /// place, so `json:` queries do not re-parse the records already loaded.
+    ///
+    /// Errors when there is no item at `index`, when it is not a JSON array,
+    /// when `line` is not valid JSON, or when a size limit would be broken.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_json("[]".to_string());
+    /// cs.append_jsonl(0, r#"{"level": "info", "msg": "started"}"#).unwrap();
+    /// cs.append_jsonl(0, r#"{"level": "error", "msg": "disk full"}"#).unwrap();
+    /// assert_eq!(cs.query(-1, "json:[?level=='error'].msg"), ["disk full"]);
+    /// assert!(cs.append_jsonl(0, "{truncated").is_err());
+    /// ```
+    pub fn append_jsonl(&mut self, index: usize, line: &str) -> Result<(), String> {
+        // The separating comma grows the item by one more byte.
+        self.check_append(index, line.trim().len() + 1)?;
+        self.content_list[index].append_json_element(line)?;
+        self.notify_watches(index, Change::Element(line));
+        Ok(())
+    }
+
+    /// Check that item `index` exists and may grow by `added` bytes under the
+    /// size limits.
+    fn check_append(&self, index: usize, added: usize) -> Result<(), String> {
+        let item = self
+            .content_list
+            .get(index)
+            .ok_or_else(|| format!("invalid content index: {}", index))?;
+        if let Some(max) = self.config.max_item_bytes {
+            if item.size() + added > max {
+                return Err(format!(
+                    "appending {} bytes would exceed the {}-byte item limit",
+                    added, max
+                ));
+            }
+        }
+        if let Some(max) = self.config.max_total_bytes {
+            let used = self.content_bytes();
+            if used + added > max {
+                return Err(format!(
+                    "appending {} bytes would exceed the {}-byte total limit ({} in use)",
+                    added, max, used
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse every loaded item now rather than on its first query: the HTML
+    /// document (and XPath order map) for HTML and Markdown, the value tree
+    /// for JSON, TOML, and INI. Content that fails to parse is logged and
+    /// left for the queries to report. Honours the cache budget afterwards.
+    ///
+    /// Parsed documents are reference-counted and tied to this thread, so
+    /// parsing runs on the calling thread; to use several cores, give each
+    /// worker its own `ChadSelect`.
+    pub fn warm(&self) {
+        self.warm_items(0);
+    }
+
+    /// Add HTML content, parsing it now in chunks and calling `progress`
+    /// after each one, so an embedding UI can update a progress bar or pump
+    /// its events while a very large export is indexed. Return
+    /// `ControlFlow::Break(())` from `progress` to cancel: nothing is added
+    /// and the result is an error. Size limits apply as for
+    /// [`try_add`](ChadSelect::try_add). Needs the `css` feature.
+    ///
+    /// ```rust
+    /// use std::ops::ControlFlow;
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// let mut last = 0.0;
+    /// cs.add_html_with_progress("<p>big export</p>".to_string(), |p| {
+    ///     last = p.fraction();
+    ///     ControlFlow::Continue(())
+    /// })
+    /// .unwrap();
+    /// assert_eq!(last, 1.0);
+    /// assert_eq!(cs.select(0, "css:p"), "big export");
+    ///
+    /// let cancelled = cs.add_html_with_progress("<p>x</p>".to_string(), |_| ControlFlow::Break(()));
+    /// assert!(cancelled.is_err());
+    /// assert_eq!(cs.content_count(), 1);
+    /// ```
+    #[cfg(feature = "css")]
+    pub fn add_html_with_progress(
+        &mut self,
+        content: String,
+        mut progress: impl FnMut(ParseProgress) -> ControlFlow<()>,
+    ) -> Result<(), String> {
+        let content = self.fit_to_limits(content, &ContentType::Html)?;
+        let item = self.new_item(content, ContentType::Html);
+        let total_bytes = item.content.len();
+        let flow = item.parse_html_chunked(|bytes_parsed| {
+            progress(ParseProgress {
+                bytes_parsed,
+                total_bytes,
+            })
+        });
+        if flow.is_break() {
+            return Err("parsing cancelled".to_string());
+        }
+        item.html_with_order();
+        self.push_item(item);
+        self.enforce_cache_budget();
+        Ok(())
+    }
+
+    /// Like [`warm`](ChadSelect::warm), reporting progress across all items
+    /// not yet parsed — HTML and Markdown in chunks, other types one item at
+    /// a time. Return `ControlFlow::Break(())` from `progress` to stop: the
+    /// items finished so far stay parsed, and the result is an error.
+    ///
+    /// Parsing runs on the calling thread (see [`warm`](ChadSelect::warm));
+    /// `progress` is the place to keep a UI responsive or check a cancel
+    /// flag.
+    pub fn parse_all(
+        &self,
+        mut progress: impl FnMut(ParseProgress) -> ControlFlow<()>,
+    ) -> Result<(), String> {
+        let capture = Capture::begin();
+        let pending: Vec<&ContentItem> = self
+            .content_list
+            .iter()
+            .filter(|item| !item.is_parsed() && item.content_type != ContentType::Text)
+            .collect();
+        let total_bytes = pending.iter().map(|item| item.content.len()).sum();
+        let mut done = 0;
+        let mut report = |bytes_parsed| {
+            progress(ParseProgress {
+                bytes_parsed,
+                total_bytes,
+            })
+        };
+
+        let mut cancelled = false;
+        for item in pending {
+            #[cfg(feature = "css")]
+            let chunked = matches!(item.content_type, ContentType::Html | ContentType::Markdown);
+            #[cfg(not(feature = "css"))]
+            let chunked = false;
+            #[cfg(feature = "css")]
+            if chunked && item.parse_html_chunked(|bytes| report(done + bytes)).is_break() {
+                cancelled = true;
+                break;
+            }
+            if let Err(e) = item.warm() {
+                warning!(InvalidContent, "Failed to parse content while warming: {}", e);
+            }
+            done += item.content.len();
+            if !chunked && report(done).is_break() {
+                cancelled = true;
+                break;
+            }
+        }
+        self.enforce_cache_budget();
+        self.keep_warnings(capture);
+        if cancelled {
+            Err("parsing cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn warm_items(&self, start: usize) {
+        let capture = Capture::begin();
+        for item in &self.content_list[start..] {
+            if let Err(e) = item.warm() {
+                warning!(InvalidContent, "Failed to parse content while warming: {}", e);
+            }
+        }
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+    }
+
+    fn push(&mut self, content: String, content_type: ContentType) {
+        let capture = Capture::begin();
+        if let Err(e) = self.try_add(content, content_type) {
+            warning!(ContentRejected, "Content not added: {}", e);
+        }
+        self.keep_warnings(capture);
+    }
+
+    /// Apply the item and total size limits to incoming `content`, evicting
+    /// the oldest items under [`LimitPolicy::EvictOldest`].
+    fn fit_to_limits(
+        &mut self,
+        mut content: String,
+        content_type: &ContentType,
+    ) -> Result<String, String> {
+        let policy = self.config.limit_policy;
+        let mut size = content::size_of(&content, content_type);
+
+        if let Some(max) = self.config.max_item_bytes {
+            if size > max {
+                if policy != LimitPolicy::Truncate {
+                    return Err(format!(
+                        "item of {} bytes exceeds the {}-byte item limit",
+                        size, max
+                    ));
+                }
+                size = truncate_to(&mut content, content_type, max);
+            }
+        }
+
+        if let Some(max) = self.config.max_total_bytes {
+            let used = self.content_bytes();
+            if used + size > max {
+                match policy {
+                    LimitPolicy::Reject => {
+                        return Err(format!(
+                            "adding {} bytes would exceed the {}-byte total limit ({} in use)",
+                            size,
+                            max,
+                            used
+                        ))
+                    }
+                    LimitPolicy::Truncate if used < max => {
+                        truncate_to(&mut content, content_type, max - used);
+                    }
+                    LimitPolicy::Truncate => {
+                        return Err(format!("the {}-byte total limit is used up", max))
+                    }
+                    LimitPolicy::EvictOldest if size > max => {
+                        return Err(format!(
+                            "item of {} bytes exceeds the {}-byte total limit",
+                            size,
+                            max
+                        ))
+                    }
+                    LimitPolicy::EvictOldest => {
+                        let mut remaining = used;
+                        let evict = self
+                            .content_list
+                            .iter()
+                            .take_while(|item| {
+                                let over = remaining + size > max;
+                                remaining -= item.size();
+                                over
+                            })
+                            .count();
+                        self.content_list.drain(..evict);
+                    }
+                }
+            }
+        }
+
+        Ok(content)
+    }
+
+    /// Release every item's parsed caches (HTML documents, JSON value trees,
+    /// element text). Content stays loaded; the next query re-parses lazily.
+    pub fn drop_caches(&self) {
+        for item in &self.content_list {
+            item.drop_caches();
+        }
+    }
+
+    /// Release the parsed caches of the content item at `index` (a content
+    /// index, as counted by [`content_count`](ChadSelect::content_count)).
+    pub fn drop_caches_for(&self, index: usize) {
+        let capture = Capture::begin();
+        match self.content_list.get(index) {
+            Some(item) => item.drop_caches(),
+            None => warning!(IndexOutOfRange, "Invalid content index: {}", index),
+        }
+        self.keep_warnings(capture);
+    }
+
+    /// With a cache budget set, drop the least recently used items' caches
+    /// until the estimated cached bytes fit it.
+    fn enforce_cache_budget(&self) {
+        let Some(budget) = self.config.cache_budget_bytes else {
+            return;
+        };
+        let mut cached: usize = self.content_list.iter().map(ContentItem::cached_bytes).sum();
+        if cached <= budget {
+            return;
+        }
+
+        let mut parsed: Vec<&ContentItem> =
+            self.content_list.iter().filter(|item| item.is_parsed()).collect();
+        parsed.sort_by_key(|item| item.last_used.get());
+        for item in parsed {
+            if cached <= budget {
+                break;
+            }
+            cached -= item.cached_bytes();
+            item.drop_caches();
+        }
     }
 
     /// Return the number of loaded content items.
@@ -89,18 +729,132 @@ impl ChadSelect {
         self.content_list.len()
     }
 
+    /// Where the content item at `index` came from — the path for
+    /// [`add_file`](ChadSelect::add_file), the URL for `add_url` — or `None`
+    /// when unknown or out of range.
+    pub fn content_source(&self, index: usize) -> Option<&str> {
+        self.content_list.get(index)?.source.as_deref()
+    }
+
+    /// The 1-based page number of the content item at `index`, for items
+    /// holding one page of a paged document (see `add_pdf`), or `None`.
+    pub fn content_page(&self, index: usize) -> Option<usize> {
+        self.content_list.get(index)?.page
+    }
+
+    /// The sheet name of the content item at `index`, for items holding one
+    /// sheet of a workbook (see `add_xlsx`), or `None`.
+    pub fn content_sheet(&self, index: usize) -> Option<&str> {
+        self.content_list.get(index)?.sheet.as_deref()
+    }
+
+    /// The content item at `index` if it is HTML (or Markdown, rendered to
+    /// HTML) — the items the page-level extractors work on.
+    #[cfg(feature = "css")]
+    pub(crate) fn html_item(&self, index: usize) -> Option<&ContentItem> {
+        self.content_list
+            .get(index)
+            .filter(|item| matches!(item.content_type, ContentType::Html | ContentType::Markdown))
+    }
+
+    /// Total bytes of raw content held (the quantity the size limits count).
+    pub fn content_bytes(&self) -> usize {
+        self.content_list.iter().map(ContentItem::size).sum()
+    }
+
+    /// Current memory usage: raw content plus the items holding parsed
+    /// caches and their estimated size.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            items: self.content_list.len(),
+            content_bytes: self.content_bytes(),
+            parsed_items: self.content_list.iter().filter(|item| item.is_parsed()).count(),
+            cached_bytes: self.content_list.iter().map(ContentItem::cached_bytes).sum(),
+        }
+    }
+
     /// Remove all loaded content.
     pub fn clear(&mut self) {
         self.content_list.clear();
     }
 
+    // ── Named queries ───────────────────────────────────────────────────
+
+    /// Register `query` under `name`, so it can be run as `ref:name`.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.define("title", "css:h1 >> normalize-space()");
+    /// cs.add_html("<h1>  Civic </h1>".to_string());
+    /// assert_eq!(cs.select(0, "ref:title >> uppercase()"), "CIVIC");
+    /// ```
+    pub fn define(&mut self, name: &str, query: &str) {
+        self.library.define(name, query);
+    }
+
+    /// Register the regex `pattern` under `name`, so it can be run as
+    /// `pat:name` in queries, fallbacks, and schema columns.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.define_pattern("sku", r"SKU-\d{3}");
+    /// cs.add_html("<p>Part SKU-104</p>".to_string());
+    /// assert_eq!(cs.select(0, "css:.sku || pat:sku"), "SKU-104");
+    /// ```
+    pub fn define_pattern(&mut self, name: &str, pattern: &str) {
+        self.library.define_pattern(name, pattern);
+    }
+
+    /// The named queries and patterns available to `ref:` and `pat:`.
+    pub fn library(&self) -> &QueryLibrary {
+        &self.library
+    }
+
+    /// Mutable access to the named queries, e.g. to load a TOML file.
+    pub fn library_mut(&mut self) -> &mut QueryLibrary {
+        &mut self.library
+    }
+
+    // ── Diagnostics ────────────────────────────────────────────────────
+
+    /// Drain the warnings recorded since the last call — the same
+    /// diagnostics that are logged with `log::warn!`, as structured
+    /// [`Warning`]s. At most the 1024 most recent are kept.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, WarningKind};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_json("{not json".to_string());
+    /// cs.add_html("<p>hi</p>".to_string());
+    ///
+    /// assert!(cs.query(-1, "css:p[").is_empty());
+    /// assert!(cs.query(-1, "json:a").is_empty());
+    /// assert_eq!(cs.select(3, "css:p"), "");
+    ///
+    /// let kinds: Vec<_> = cs.take_warnings().iter().map(|w| w.kind).collect();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [WarningKind::InvalidQuery, WarningKind::InvalidContent, WarningKind::IndexOutOfRange]
+    /// );
+    /// assert!(cs.take_warnings().is_empty());
+    /// ```
+    pub fn take_warnings(&self) -> Vec<Warning> {
+        self.warnings.take()
+    }
+
     // ── Querying ────────────────────────────────────────────────────────
 
     /// Query all loaded content and return matching results.
     ///
     /// - `index = -1` returns **all** matches.
     /// - `index >= 0` returns the match at that position (or empty if out of
-    ///   bounds).
+    ///   bounds) — counted across all items, or within each item under
+    ///   [`IndexMode::PerContent`].
     ///
     /// **Never panics** — invalid queries or out-of-bounds indices return an
     /// empty vector.
@@ -109,36 +863,404 @@ impl ChadSelect {
         index: i32,
         query_str: &str,
     ) -> Vec<String> {
-        let query_type = match query::parse_query(query_str) {
-            Ok(qt) => qt,
-            Err(_) => {
-                warn!("Failed to parse query: {}", query_str);
-                return vec![];
+        match self.build_lenient(query_str) {
+            Some(prepared) => self.execute(index, &prepared, false).results,
+            None => vec![],
+        }
+    }
+
+    /// Like [`query`](ChadSelect::query), but also reports how the query was
+    /// routed — notably which engine an `auto:` query was classified as.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<span class="price">$49.99</span>"#.to_string());
+    ///
+    /// let r = cs.query_detailed(0, "auto:span.price");
+    /// assert_eq!(r.results, vec!["$49.99"]);
+    /// assert_eq!(r.engine(), Some("css"));
+    /// ```
+    pub fn query_detailed(&self, index: i32, query_str: &str) -> QueryResult {
+        let capture = Capture::begin();
+        let mut result = match self.build_lenient(query_str) {
+            Some(prepared) => self.execute(index, &prepared, true),
+            None => QueryResult::default(),
+        };
+        result.warnings = self.keep_warnings(capture);
+        result
+    }
+
+    /// Like [`query_detailed`](ChadSelect::query_detailed), stopping early
+    /// once `token` is cancelled or its deadline passes — a guard for
+    /// selectors that come from untrusted input.
+    ///
+    /// The engines check the token between content items and between
+    /// matches, and the XPath engine while walking the tree. A stopped query
+    /// returns the results it found so far with a
+    /// [`QueryStatus::TimedOut`] or [`QueryStatus::Cancelled`] status, and
+    /// does not fall back to later `||` alternatives. Parsing a document and
+    /// a single regex or JMESPath evaluation run to completion once started.
+    ///
+    /// ```rust
+    /// use chadselect::{CancellationToken, ChadSelect, QueryStatus};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html("<p>one</p><p>two</p>".to_string());
+    ///
+    /// let token = CancellationToken::new();
+    /// token.cancel();
+    /// let r = cs.query_cancellable(-1, "xpath://p", &token);
+    /// assert_eq!(r.status, QueryStatus::Cancelled);
+    /// assert!(r.results.is_empty());
+    /// ```
+    pub fn query_cancellable(
+        &self,
+        index: i32,
+        query_str: &str,
+        token: &CancellationToken,
+    ) -> QueryResult {
+        let scope = cancel::Scope::enter(token);
+        let mut result = self.query_detailed(index, query_str);
+        result.status = scope.status();
+        result
+    }
+
+    /// Run a query on the content item at `index` and return each value
+    /// with the byte range `(start, end)` of the raw content it came from —
+    /// for highlighting matches or cutting a context window around them.
+    ///
+    /// Regex matches, and `text:` sentences of plain content, carry their
+    /// exact range; a `>>` chain may change the value but not its range.
+    /// Values from HTML and Markdown (CSS and XPath results, `text:`
+    /// sentences) are located by finding their text verbatim in the raw
+    /// markup, in order and outside tags. A value that does not appear
+    /// verbatim — entities, collapsed whitespace, a transforming chain —
+    /// has no range and is left out, as are folds (`join()`, `count()`, …)
+    /// and the values of other engines. `||` fallbacks work as in
+    /// [`query`](ChadSelect::query); an out-of-range `index` yields nothing.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// let html = r#"<p class="vin" title="1HGCM82633A004352">1HGCM82633A004352</p>"#;
+    /// cs.add_text("VIN 1HGCM82633A004352, stock 4471".to_string());
+    /// cs.add_html(html.to_string());
+    ///
+    /// let spans = cs.query_spans(0, r"regex:\d{4}$");
+    /// assert_eq!(spans, vec![(29, 33, "4471".to_string())]);
+    ///
+    /// // Found in the text, not in the attribute.
+    /// let spans = cs.query_spans(1, "css:.vin");
+    /// assert_eq!(spans, vec![(41, 58, "1HGCM82633A004352".to_string())]);
+    /// assert_eq!(&html[41..58], "1HGCM82633A004352");
+    /// ```
+    pub fn query_spans(&self, index: usize, query_str: &str) -> Vec<(usize, usize, String)> {
+        let Some(item) = self.content_list.get(index) else {
+            return vec![];
+        };
+        let Some(prepared) = self.build_lenient(query_str) else {
+            return vec![];
+        };
+        let capture = Capture::begin();
+
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let alternatives = prepared.alternatives();
+        let located = alternatives
+            .iter()
+            .map(|alternative| alternative.run_located(item, index, &self.config))
+            .find(|located| {
+                alternatives.len() == 1
+                    || (!located.is_empty() && located.iter().all(|(_, r)| default_valid(r)))
+            })
+            .unwrap_or_default();
+        if item.is_parsed() {
+            item.last_used.set(tick);
+        }
+
+        let markup = matches!(item.content_type, ContentType::Html | ContentType::Markdown);
+        let mut from = 0;
+        let spans = located
+            .into_iter()
+            .filter_map(|(location, value)| {
+                let span = match location.span {
+                    Some(span) => span,
+                    None if markup => {
+                        let span = result::find_in_markup(&item.content, &value, from)?;
+                        from = span.end;
+                        span
+                    }
+                    None => return None,
+                };
+                Some((span.start, span.end, value))
+            })
+            .collect();
+
+        self.enforce_cache_budget();
+        self.keep_warnings(capture);
+        spans
+    }
+
+    /// Build a query the way `query` runs it: malformed text functions are
+    /// skipped, and an unparseable query is logged and yields `None`.
+    fn build_lenient(&self, query_str: &str) -> Option<PreparedQuery> {
+        let capture = Capture::begin();
+        let prepared = match PreparedQuery::build(
+            query_str,
+            &self.config,
+            Some(&self.library),
+            &self.content_list,
+            true,
+        ) {
+            Ok(prepared) => Some(prepared),
+            Err(e) => {
+                warning!(InvalidQuery, "Failed to parse query: {}", e);
+                None
             }
         };
+        self.keep_warnings(capture);
+        prepared
+    }
 
-        let mut all_results = Vec::new();
+    /// Parse and compile a query once for repeated use with
+    /// [`query_prepared`](ChadSelect::query_prepared), honouring this
+    /// instance's [`PrefixMode`] and named queries.
+    ///
+    /// Unlike [`query`](ChadSelect::query), this reports an unparseable query
+    /// or invalid engine expression as an error — useful for validating a
+    /// selector set at startup.
+    pub fn prepare(&self, query_str: &str) -> Result<PreparedQuery, String> {
+        let capture = Capture::begin();
+        let prepared = PreparedQuery::build(
+            query_str,
+            &self.config,
+            Some(&self.library),
+            &self.content_list,
+            false,
+        );
+        self.keep_warnings(capture);
+        prepared
+    }
 
-        for content_item in &self.content_list {
-            if !query::is_query_compatible(&query_type, &content_item.content_type) {
-                continue;
+    /// Run a [`PreparedQuery`] against all loaded content, with the same
+    /// `index` semantics as [`query`](ChadSelect::query). No per-call parsing
+    /// or compilation happens.
+    pub fn query_prepared(&self, index: i32, prepared: &PreparedQuery) -> Vec<String> {
+        self.execute(index, prepared, false).results
+    }
+
+    /// Like [`select`](ChadSelect::select), for a [`PreparedQuery`].
+    pub fn select_prepared(&self, index: i32, prepared: &PreparedQuery) -> String {
+        let result = self.query_prepared(index, prepared);
+        match result.into_iter().next() {
+            Some(first) if default_valid(&first) => first,
+            _ => String::new(),
+        }
+    }
+
+    /// Like [`query`](ChadSelect::query), but each regex match is borrowed
+    /// from the loaded content instead of copied. Results from other engines,
+    /// or rewritten by a `>>` chain, are owned.
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_text("SKU-1 SKU-2 SKU-3".to_string());
+    ///
+    /// let skus = cs.query_borrowed(-1, r"regex:SKU-\d");
+    /// assert_eq!(skus, vec!["SKU-1", "SKU-2", "SKU-3"]);
+    /// assert!(matches!(skus[0], Cow::Borrowed(_)));
+    /// ```
+    pub fn query_borrowed(&self, index: i32, query_str: &str) -> Vec<Cow<'_, str>> {
+        let Some(prepared) = self.build_lenient(query_str) else {
+            return vec![];
+        };
+        let capture = Capture::begin();
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let alternatives = prepared.alternatives();
+        let mut selected = vec![];
+        for alternative in alternatives {
+            let mut all_results = Vec::new();
+            for content_item in &self.content_list {
+                let item_results = alternative.run_borrowed(content_item, &self.config);
+                all_results.extend(self.pick_in_item(item_results, index));
+                if content_item.is_parsed() {
+                    content_item.last_used.set(tick);
+                }
+                if self.over_max_results(&mut all_results) {
+                    break;
+                }
+            }
+            let results = self.pick_across_items(all_results, index);
+            if alternatives.len() == 1
+                || (!results.is_empty() && results.iter().all(|r| default_valid(r)))
+            {
+                selected = results;
+                break;
             }
+        }
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        selected
+    }
+
+    /// Like [`query`](ChadSelect::query), returning shared `Arc<str>` values.
+    /// Equal results share one allocation, and regex matches are copied once,
+    /// straight from the content. Cloning a result to hand it to another
+    /// thread or cache is a reference-count bump.
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html("<b>new</b><b>used</b><b>new</b>".to_string());
+    ///
+    /// let conditions = cs.query_arc(-1, "css:b");
+    /// assert_eq!(conditions.len(), 3);
+    /// assert!(Arc::ptr_eq(&conditions[0], &conditions[2]));
+    /// ```
+    pub fn query_arc(&self, index: i32, query_str: &str) -> Vec<Arc<str>> {
+        let results = self.query_borrowed(index, query_str);
+        let mut interned: HashMap<&str, Arc<str>> = HashMap::new();
+        results
+            .iter()
+            .map(|value| {
+                interned
+                    .entry(value.as_ref())
+                    .or_insert_with(|| Arc::from(value.as_ref()))
+                    .clone()
+            })
+            .collect()
+    }
+
+    /// Run every alternative of `prepared` in order. A single query returns
+    /// its results as-is; a `||` chain returns the first alternative whose
+    /// results are non-empty and all valid, like
+    /// [`select_first`](ChadSelect::select_first).
+    ///
+    /// With `locate`, each result's [`ResultLocation`] is reported too.
+    fn execute(&self, index: i32, prepared: &PreparedQuery, locate: bool) -> QueryResult {
+        let capture = Capture::begin();
+        let result = self.run_alternatives(index, prepared, locate);
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        result
+    }
 
-            let results = match &query_type {
-                QueryType::Regex(pattern) => {
-                    engine::regex::process(pattern, &content_item.content, &content_item.content_type)
+    fn run_alternatives(&self, index: i32, prepared: &PreparedQuery, locate: bool) -> QueryResult {
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let alternatives = prepared.alternatives();
+        for alternative in alternatives {
+            let mut all_results = Vec::new();
+            let mut all_locations = Vec::new();
+            let mut truncated = false;
+            for (content_index, content_item) in self.content_list.iter().enumerate() {
+                if cancel::interrupted() {
+                    break;
+                }
+                if locate {
+                    let located =
+                        alternative.run_located(content_item, content_index, &self.config);
+                    for (location, result) in self.pick_in_item(located, index) {
+                        all_locations.push(location);
+                        all_results.push(result);
+                    }
+                } else {
+                    let item_results = alternative.run(content_item, &self.config);
+                    all_results.extend(self.pick_in_item(item_results, index));
                 }
-                QueryType::JsonPath(path) => engine::json::process(path, content_item),
-                QueryType::CssSelector(selector) => engine::css::process(selector, content_item),
-                QueryType::XPath(xpath) => {
-                    engine::xpath::process(xpath, content_item)
+                if content_item.is_parsed() {
+                    content_item.last_used.set(tick);
                 }
+                if self.over_max_results(&mut all_results) {
+                    all_locations.truncate(all_results.len());
+                    truncated = true;
+                    break;
+                }
+            }
+            let (results, locations) = if locate {
+                let located = all_results.into_iter().zip(all_locations).collect();
+                self.pick_across_items(located, index).into_iter().unzip()
+            } else {
+                (self.pick_across_items(all_results, index), vec![])
             };
 
-            all_results.extend(results);
+            let accepted = alternatives.len() == 1
+                || cancel::interrupted()
+                || (!results.is_empty() && results.iter().all(|r| default_valid(r)));
+            if accepted {
+                return QueryResult {
+                    results,
+                    locations,
+                    query_type: Some(alternative.query_type().clone()),
+                    truncated,
+                    ..Default::default()
+                };
+            }
         }
+        QueryResult {
+            results: vec![],
+            locations: vec![],
+            query_type: prepared.query_type().cloned(),
+            ..Default::default()
+        }
+    }
 
-        select_by_index(all_results, index)
+    /// Apply `index` to one content item's results: under
+    /// [`IndexMode::PerContent`] this is where a match is picked.
+    fn pick_in_item<T>(&self, results: Vec<T>, index: i32) -> Vec<T> {
+        match self.config.index_mode {
+            IndexMode::PerContent if index >= 0 => {
+                results.into_iter().nth(index as usize).into_iter().collect()
+            }
+            _ => results,
+        }
+    }
+
+    /// Apply `index` to the results gathered from every item: under
+    /// [`IndexMode::Global`] this is where a match is picked.
+    fn pick_across_items<T>(&self, results: Vec<T>, index: i32) -> Vec<T> {
+        match self.config.index_mode {
+            IndexMode::PerContent if index >= -1 => results,
+            _ => select_by_index(results, index),
+        }
+    }
+
+    /// Cut `results` to [`max_results`](ChadSelectBuilder::max_results),
+    /// reporting whether there were more.
+    fn over_max_results<T>(&self, results: &mut Vec<T>) -> bool {
+        match self.config.max_results {
+            Some(max) if results.len() > max => {
+                results.truncate(max);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Finish `capture`, keeping its warnings for
+    /// [`take_warnings`](ChadSelect::take_warnings) when no enclosing call
+    /// will, and return them.
+    fn keep_warnings(&self, capture: Capture) -> Vec<Warning> {
+        let (warnings, outermost) = capture.finish();
+        if outermost && !warnings.is_empty() {
+            let mut kept = self.warnings.borrow_mut();
+            kept.extend(warnings.iter().cloned());
+            let excess = kept.len().saturating_sub(MAX_KEPT_WARNINGS);
+            kept.drain(..excess);
+        }
+        warnings
     }
 
     /// Return a single result string, or an empty string.
@@ -219,13 +1341,46 @@ impl ChadSelect {
     where
         F: Fn(&str) -> bool,
     {
-        for (index, query_str) in queries {
+        self.select_first_tagged_where(queries, valid)
+            .map(|(_, result)| result)
+            .unwrap_or_default()
+    }
+
+    /// Like [`select_first`](ChadSelect::select_first), but also reports
+    /// which query won: `Some((position in queries, results))`, or `None`
+    /// when no query produced a valid result. Useful for telemetry on which
+    /// fallbacks actually fire.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<span class="price">$5</span>"#.to_string());
+    ///
+    /// let hit = cs.select_first_tagged(vec![(0, "css:.sale"), (0, "css:.price")]);
+    /// assert_eq!(hit, Some((1, vec!["$5".to_string()])));
+    /// ```
+    pub fn select_first_tagged(&self, queries: Vec<(i32, &str)>) -> Option<(usize, Vec<String>)> {
+        self.select_first_tagged_where(queries, default_valid)
+    }
+
+    /// Like [`select_first_tagged`](ChadSelect::select_first_tagged) but with
+    /// a custom validity check.
+    pub fn select_first_tagged_where<F>(
+        &self,
+        queries: Vec<(i32, &str)>,
+        valid: F,
+    ) -> Option<(usize, Vec<String>)>
+    where
+        F: Fn(&str) -> bool,
+    {
+        for (position, (index, query_str)) in queries.into_iter().enumerate() {
             let result = self.query(index, query_str);
             if !result.is_empty() && result.iter().all(|r| valid(r)) {
-                return result;
+                return Some((position, result));
             }
         }
-        vec![]
+        None
     }
 
     /// Run multiple queries and return the combined unique results.
@@ -256,17 +1411,377 @@ impl ChadSelect {
         all_results.into_iter().collect()
     }
 
+    /// Return the first valid match of `query_str` in each content item —
+    /// "the title of every loaded page".
+    ///
+    /// The result has one entry per content item, in load order, so it lines
+    /// up with content indices: an item where the query finds nothing valid,
+    /// or does not apply (a `css:` query on JSON), gets an empty string. With
+    /// `||` fallbacks, each item takes the first alternative with a valid
+    /// match on that item.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html("<title>Civic</title>".to_string());
+    /// cs.add_html("<p>no title</p>".to_string());
+    /// cs.add_html("<title>Accord</title><title>ignored</title>".to_string());
+    ///
+    /// assert_eq!(cs.select_each("css:title"), vec!["Civic", "", "Accord"]);
+    /// ```
+    pub fn select_each(&self, query_str: &str) -> Vec<String> {
+        self.select_each_where(query_str, default_valid)
+    }
+
+    /// Like [`select_each`](ChadSelect::select_each) but with a custom
+    /// validity check.
+    pub fn select_each_where<F>(&self, query_str: &str, valid: F) -> Vec<String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let Some(prepared) = self.build_lenient(query_str) else {
+            return vec![String::new(); self.content_list.len()];
+        };
+        let capture = Capture::begin();
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let values = self
+            .content_list
+            .iter()
+            .map(|content_item| {
+                let value = prepared
+                    .alternatives()
+                    .iter()
+                    .find_map(|alternative| {
+                        let results = alternative.run(content_item, &self.config);
+                        results.into_iter().find(|result| valid(result))
+                    })
+                    .unwrap_or_default();
+                if content_item.is_parsed() {
+                    content_item.last_used.set(tick);
+                }
+                value
+            })
+            .collect();
+
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        values
+    }
+
     /// Execute multiple queries in a single call and return each result set.
     ///
     /// This is the most efficient way to extract many fields from the same
-    /// content — one boundary crossing, all queries executed in Rust.
+    /// content. Every query is parsed once, and plain CSS selectors (no text
+    /// pseudo-selector or `||` fallback) are grouped so each HTML document is
+    /// walked once for the whole group rather than once per selector. Other
+    /// queries run as with [`query`](ChadSelect::query), sharing each
+    /// item's parsed document and JSON value.
     ///
-    /// Returns a `Vec<Vec<String>>` with one entry per input query, in order.
+    /// Returns a `Vec<Vec<String>>` with one entry per input query, in order;
+    /// each entry equals what `query` returns for that query.
     pub fn query_batch(&self, queries: &[(i32, &str)]) -> Vec<Vec<String>> {
-        queries
+        let capture = Capture::begin();
+        let prepared: Vec<Option<PreparedQuery>> = queries
             .iter()
-            .map(|(index, query_str)| self.query(*index, query_str))
-            .collect()
+            .map(|(_, query_str)| self.build_lenient(query_str))
+            .collect();
+
+        #[cfg(feature = "css")]
+        let (grouped, batched) = self.batch_plain_css(queries, &prepared);
+        #[cfg(not(feature = "css"))]
+        let (grouped, batched) = (Vec::<usize>::new(), Vec::<Vec<String>>::new());
+
+        let mut batched = grouped.into_iter().zip(batched).peekable();
+        let results = queries
+            .iter()
+            .zip(&prepared)
+            .enumerate()
+            .map(|(position, ((index, _), query))| {
+                if batched.peek().is_some_and(|(p, _)| *p == position) {
+                    let (_, mut results) = batched.next().expect("peeked");
+                    self.over_max_results(&mut results);
+                    return self.pick_across_items(results, *index);
+                }
+                match query {
+                    Some(query) => self.run_alternatives(*index, query, false).results,
+                    None => vec![],
+                }
+            })
+            .collect();
+
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        results
+    }
+
+    /// The positions in `queries` that are a single plain CSS selector, and
+    /// their results, from one walk of each HTML document for the whole
+    /// group — the fast path of [`query_batch`](ChadSelect::query_batch).
+    #[cfg(feature = "css")]
+    fn batch_plain_css(
+        &self,
+        queries: &[(i32, &str)],
+        prepared: &[Option<PreparedQuery>],
+    ) -> (Vec<usize>, Vec<Vec<String>>) {
+        // Positions of the queries that are a single plain CSS selector.
+        let mut grouped = Vec::new();
+        let mut selectors = Vec::new();
+        for (position, query) in prepared.iter().enumerate() {
+            if let Some([alternative]) = query.as_ref().map(PreparedQuery::alternatives) {
+                if let Some(css) = alternative.plain_css() {
+                    grouped.push(position);
+                    selectors.push(css);
+                }
+            }
+        }
+
+        let mut batched = vec![Vec::new(); grouped.len()];
+        if !grouped.is_empty() {
+            let tick = self.clock.get() + 1;
+            self.clock.set(tick);
+            for content_item in &self.content_list {
+                if !matches!(
+                    content_item.content_type,
+                    ContentType::Html | ContentType::Markdown
+                ) {
+                    continue;
+                }
+                let per_item = engine::css::run_batch(&selectors, content_item, &self.config.text);
+                for ((results, item_results), position) in
+                    batched.iter_mut().zip(per_item).zip(&grouped)
+                {
+                    results.extend(self.pick_in_item(item_results, queries[*position].0));
+                }
+                content_item.last_used.set(tick);
+            }
+        }
+
+        (grouped, batched)
+    }
+
+    /// Run a selector set against every loaded content item separately and
+    /// report, per query, how many results it returned on each item — a
+    /// coverage matrix for spotting selectors that silently stopped matching
+    /// after a site redesign.
+    ///
+    /// Each query is prepared like [`prepare`](ChadSelect::prepare), so a
+    /// malformed query is reported in [`QueryCoverage::error`] rather than
+    /// skipped. On each item, `||` alternatives are tried in order as in
+    /// [`query`](ChadSelect::query).
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<span class="price">$1</span>"#.to_string());
+    /// cs.add_html(r#"<span class="amount">$2</span>"#.to_string());
+    ///
+    /// let report = cs.check_queries(&["css:.price", "css:span"]);
+    /// assert_eq!(report[0].matches, vec![Some(1), Some(0)]);
+    /// assert!(report[0].is_partial());
+    /// assert!(!report[1].is_partial());
+    /// ```
+    pub fn check_queries(&self, queries: &[&str]) -> Vec<QueryCoverage> {
+        let capture = Capture::begin();
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let report = queries
+            .iter()
+            .map(|query_str| {
+                let prepared = match self.prepare(query_str) {
+                    Ok(prepared) => prepared,
+                    Err(e) => {
+                        return QueryCoverage {
+                            query: query_str.to_string(),
+                            error: Some(e),
+                            matches: vec![None; self.content_list.len()],
+                        }
+                    }
+                };
+                let matches = self
+                    .content_list
+                    .iter()
+                    .map(|content_item| {
+                        let count = self.count_on_item(&prepared, content_item);
+                        if content_item.is_parsed() {
+                            content_item.last_used.set(tick);
+                        }
+                        count
+                    })
+                    .collect();
+                QueryCoverage {
+                    query: query_str.to_string(),
+                    error: None,
+                    matches,
+                }
+            })
+            .collect();
+
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        report
+    }
+
+    /// The number of results `prepared` returns on one content item, or
+    /// `None` when none of its alternatives applies to the item.
+    fn count_on_item(
+        &self,
+        prepared: &PreparedQuery,
+        content_item: &ContentItem,
+    ) -> Option<usize> {
+        let alternatives = prepared.alternatives();
+        let mut applies = false;
+        for alternative in alternatives {
+            if !query::is_query_compatible(alternative.query_type(), &content_item.content_type) {
+                continue;
+            }
+            applies = true;
+            let results = alternative.run(content_item, &self.config);
+            let accepted = alternatives.len() == 1
+                || (!results.is_empty() && results.iter().all(|r| default_valid(r)));
+            if accepted {
+                return Some(results.len());
+            }
+        }
+        applies.then_some(0)
+    }
+
+    /// Propose queries that select `target`, for building a new site config
+    /// from a value seen on the page.
+    ///
+    /// Every HTML (and Markdown) item is scanned for the innermost elements
+    /// whose text contains `target` and for attributes equal to it. Each hit
+    /// yields CSS and XPath candidates — by id, by class chain, by a
+    /// neighbouring label (`tr:has-text('Price') td`), by full path, or with
+    /// `get-attr` for attributes. Only candidates for which
+    /// `query(index, candidate)` returns a value containing `target` are
+    /// kept, the ones matching fewest results across all content first.
+    /// Needs the `css` feature.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<div class="vehicle"><span class="vin">1HGCM82633A004352</span></div>"#.to_string());
+    ///
+    /// let suggestions = cs.suggest_selectors(0, "1HGCM82633A004352");
+    /// assert_eq!(suggestions[0], "css:div.vehicle > span.vin");
+    /// ```
+    #[cfg(feature = "css")]
+    pub fn suggest_selectors(&self, index: i32, target: &str) -> Vec<String> {
+        let target = target.trim();
+        if target.is_empty() {
+            return vec![];
+        }
+
+        let mut candidates = Vec::new();
+        for content_item in &self.content_list {
+            if matches!(content_item.content_type, ContentType::Html | ContentType::Markdown) {
+                candidates.extend(suggest::candidates(&content_item.html(), target));
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut verified: Vec<(usize, String)> = candidates
+            .into_iter()
+            .filter(|candidate| seen.insert(candidate.clone()))
+            .filter(|candidate| self.query(index, candidate).iter().any(|r| r.contains(target)))
+            .map(|candidate| (self.query(-1, &candidate).len(), candidate))
+            .collect();
+        verified.sort_by_key(|(matches, _)| *matches);
+        verified.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+
+    /// Return the full attribute map of every element a CSS query matches,
+    /// with the same `index` semantics as [`query`](ChadSelect::query) — one
+    /// pass instead of a `get-attr` query per attribute.
+    ///
+    /// Only `css:` queries (or `auto:`/`ref:` queries resolving to CSS) apply;
+    /// other engines are skipped with a warning. With `||` fallbacks, the
+    /// first alternative matching any element wins. A `>>` function chain
+    /// is ignored. Needs the `css` feature.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<img src="/a.png" alt="A"><img src="/b.png">"#.to_string());
+    ///
+    /// let images = cs.query_attrs(-1, "css:img");
+    /// assert_eq!(images.len(), 2);
+    /// assert_eq!(images[0]["alt"], "A");
+    /// assert_eq!(images[1]["src"], "/b.png");
+    /// ```
+    #[cfg(feature = "css")]
+    pub fn query_attrs(&self, index: i32, query_str: &str) -> Vec<HashMap<String, String>> {
+        let Some(prepared) = self.build_lenient(query_str) else {
+            return vec![];
+        };
+        let capture = Capture::begin();
+
+        let tick = self.clock.get() + 1;
+        self.clock.set(tick);
+
+        let mut maps = Vec::new();
+        for alternative in prepared.alternatives() {
+            let Some(css) = alternative.css() else {
+                warning!(
+                    InvalidQuery,
+                    "query_attrs needs a css: query, skipping {}: alternative",
+                    alternative.query_type().prefix()
+                );
+                continue;
+            };
+            for content_item in &self.content_list {
+                if query::is_query_compatible(alternative.query_type(), &content_item.content_type) {
+                    let item_maps = engine::css::attribute_maps(css, content_item);
+                    maps.extend(self.pick_in_item(item_maps, index));
+                    content_item.last_used.set(tick);
+                }
+            }
+            if !maps.is_empty() {
+                break;
+            }
+        }
+
+        self.enforce_cache_budget();
+        let maps = self.pick_across_items(maps, index);
+        self.keep_warnings(capture);
+        maps
+    }
+
+    /// Fill a template, replacing each `{query}` placeholder with that
+    /// query's first valid result (as [`select`](ChadSelect::select) with
+    /// index `0`), or an empty string when it has none.
+    ///
+    /// Any query works inside a placeholder, function chain included. `{{`
+    /// and `}}` are literal braces; braces nested in a query (a regex
+    /// `\d{17}`) or inside its quoted arguments stay part of the query.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<h1>Civic</h1><span class="price">$28,500</span>"#.to_string());
+    ///
+    /// let line = cs.render("Title: {css:h1} Price: {css:.price >> to-number()}");
+    /// assert_eq!(line, "Title: Civic Price: 28500");
+    /// ```
+    pub fn render(&self, template: &str) -> String {
+        let capture = Capture::begin();
+        let mut out = String::with_capacity(template.len());
+        for segment in template::parse(template) {
+            match segment {
+                template::Segment::Literal(text) => out.push_str(&text),
+                template::Segment::Query(query_str) => out.push_str(&self.select(0, query_str)),
+            }
+        }
+        self.keep_warnings(capture);
+        out
     }
 }
 
@@ -276,23 +1791,43 @@ impl Default for ChadSelect {
     }
 }
 
+/// Shorten `content` to at most `max` bytes, backing off to a char boundary,
+/// and return its new size. Binary content holds one character per byte, so
+/// it keeps its first `max` characters.
+fn truncate_to(content: &mut String, content_type: &ContentType, max: usize) -> usize {
+    if *content_type == ContentType::Binary {
+        let end = content.char_indices().nth(max).map_or(content.len(), |(i, _)| i);
+        content.truncate(end);
+        return content::size_of(content, content_type);
+    }
+    let mut end = max.min(content.len());
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    content.truncate(end);
+    end
+}
+
 /// Select results by index — `-1` means "all".
-fn select_by_index(results: Vec<String>, index: i32) -> Vec<String> {
+fn select_by_index<T>(mut results: Vec<T>, index: i32) -> Vec<T> {
     match index {
         -1 => results,
-        i if i >= 0 => match results.get(i as usize) {
-            Some(result) => vec![result.clone()],
-            None => {
-                warn!(
+        i if i >= 0 => {
+            let i = i as usize;
+            if i < results.len() {
+                vec![results.swap_remove(i)]
+            } else {
+                warning!(
+                    IndexOutOfRange,
                     "Index {} out of range (have {} results)",
                     i,
                     results.len()
                 );
                 vec![]
             }
-        },
+        }
         _ => {
-            warn!("Invalid index: {}", index);
+            warning!(IndexOutOfRange, "Invalid index: {}", index);
             vec![]
         }
     }
@@ -0,0 +1,282 @@
+//! Named query registry — define a query once, reference it as `ref:name`;
+//! define a regex once, run it as `pat:name`.
+//!
+//! ```text
+//! price      = css:.price >> normalize-space()
+//! ref:price >> uppercase()   →   css:.price >> normalize-space() >> uppercase()
+//! sku        = SKU-\d{3}
+//! pat:sku >> first()         →   regex:SKU-\d{3} >> first()
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::functions;
+use crate::patterns::PAT_PREFIX;
+use crate::query;
+
+/// The prefix that references a named query.
+pub const REF_PREFIX: &str = "ref:";
+
+/// Maximum `ref:` → `ref:` hops before resolution gives up (catches cycles).
+const MAX_REF_DEPTH: usize = 16;
+
+/// A set of queries registered under names.
+///
+/// Attach one to a [`ChadSelect`](crate::ChadSelect) with
+/// [`ChadSelectBuilder::library`](crate::ChadSelectBuilder::library), or
+/// register names directly with [`ChadSelect::define`](crate::ChadSelect::define).
+/// A query string `ref:name` (optionally followed by a `>>` chain, which is
+/// appended to the referenced query's own chain) then runs the named query.
+///
+/// ```rust
+/// use chadselect::{ChadSelect, QueryLibrary};
+///
+/// let mut lib = QueryLibrary::new();
+/// lib.define("price", "css:.price >> normalize-space()");
+///
+/// let mut cs = ChadSelect::builder().library(lib).build();
+/// cs.add_html(r#"<span class="price">  $49.99 </span>"#.to_string());
+/// assert_eq!(cs.select(0, "ref:price"), "$49.99");
+/// assert_eq!(cs.select(0, "ref:price >> substring-after('$')"), "49.99");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QueryLibrary {
+    queries: HashMap<String, String>,
+    patterns: HashMap<String, String>,
+}
+
+impl QueryLibrary {
+    /// Create an empty library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `query` under `name`, replacing any previous definition.
+    pub fn define(&mut self, name: &str, query: &str) {
+        self.queries.insert(name.to_string(), query.to_string());
+    }
+
+    /// The query registered under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.queries.get(name).map(String::as_str)
+    }
+
+    /// Register the regex `pattern` under `name`, so it can be run as
+    /// `pat:name`, replacing any previous definition. A user pattern
+    /// shadows a built-in one of the same name (see
+    /// [`patterns::BUILTIN_PATTERNS`](crate::patterns::BUILTIN_PATTERNS)).
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, QueryLibrary};
+    ///
+    /// let mut lib = QueryLibrary::new();
+    /// lib.define_pattern("sku", r"SKU-\d{3}");
+    ///
+    /// let mut cs = ChadSelect::builder().library(lib).build();
+    /// cs.add_text("Parts SKU-104 and SKU-221".to_string());
+    /// assert_eq!(cs.query(-1, "pat:sku"), vec!["SKU-104", "SKU-221"]);
+    /// ```
+    pub fn define_pattern(&mut self, name: &str, pattern: &str) {
+        self.patterns.insert(name.to_string(), pattern.to_string());
+    }
+
+    /// The regex registered under `name` with
+    /// [`define_pattern`](QueryLibrary::define_pattern), if any.
+    pub fn pattern(&self, name: &str) -> Option<&str> {
+        self.patterns.get(name).map(String::as_str)
+    }
+
+    /// Number of registered queries.
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// `true` when no queries are registered.
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Expand a `ref:name` query (recursively) into the query it names, and
+    /// a `pat:name` naming a user pattern into its `regex:` query. Other
+    /// queries (built-in `pat:` names included), and `||` fallback chains
+    /// (whose alternatives resolve separately), are returned unchanged.
+    ///
+    /// Returns an error for an unknown name or a reference cycle.
+    pub fn resolve<'a>(&self, query: &'a str) -> Result<Cow<'a, str>, String> {
+        let mut resolved = Cow::Borrowed(query);
+        for _ in 0..MAX_REF_DEPTH {
+            // A `||` chain is split by the caller before its parts resolve.
+            if query::split_alternatives(&resolved).len() > 1 {
+                return Ok(resolved);
+            }
+            if let Some(rest) = resolved.strip_prefix(PAT_PREFIX) {
+                return Ok(self.resolve_pattern(rest).map_or(resolved, Cow::Owned));
+            }
+            let Some(rest) = resolved.strip_prefix(REF_PREFIX) else {
+                return Ok(resolved);
+            };
+            let name_part = functions::strip_functions(rest);
+            let name = name_part.trim();
+            let chain = &rest[name_part.len()..];
+            let Some(definition) = self.get(name) else {
+                return Err(format!("unknown query reference '{}{}'", REF_PREFIX, name));
+            };
+            resolved = Cow::Owned(format!("{}{}", definition, chain));
+        }
+        Err(format!(
+            "query reference '{}' nests deeper than {} levels (cycle?)",
+            query, MAX_REF_DEPTH
+        ))
+    }
+
+    /// The `regex:` query for a `pat:` expression (without the prefix) naming
+    /// a user pattern; `None` for any other name, which is left for the
+    /// built-in patterns.
+    fn resolve_pattern(&self, expression: &str) -> Option<String> {
+        let name_part = functions::strip_functions(expression);
+        let chain = &expression[name_part.len()..];
+        let pattern = self.pattern(name_part.trim())?;
+        Some(format!("regex:{}{}", pattern, chain))
+    }
+
+    /// Load definitions from a TOML document of string values. Nested tables
+    /// produce dotted names, so a library can be versioned or grouped:
+    ///
+    /// ```toml
+    /// title = "css:h1"
+    ///
+    /// [v2]
+    /// price = "css:.price-now >> trim()"   # referenced as ref:v2.price
+    /// ```
+    ///
+    /// Returns the number of definitions loaded.
+    #[cfg(feature = "toml")]
+    pub fn load_toml(&mut self, source: &str) -> Result<usize, String> {
+        let table: toml::Table = source.parse().map_err(|e| format!("invalid TOML: {}", e))?;
+        let mut count = 0;
+        self.load_table("", &table, &mut count)?;
+        Ok(count)
+    }
+
+    #[cfg(feature = "toml")]
+    fn load_table(
+        &mut self,
+        prefix: &str,
+        table: &toml::Table,
+        count: &mut usize,
+    ) -> Result<(), String> {
+        for (key, value) in table {
+            let name = qualified(prefix, key);
+            match value {
+                toml::Value::String(query) => {
+                    self.define(&name, query);
+                    *count += 1;
+                }
+                toml::Value::Table(nested) => self.load_table(&name, nested, count)?,
+                other => {
+                    return Err(format!(
+                        "query '{}' must be a string, found {}",
+                        name,
+                        other.type_str()
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Load definitions from a YAML document, with the same layout as
+    /// [`load_toml`](QueryLibrary::load_toml): string values are queries and
+    /// nested mappings produce dotted names.
+    ///
+    /// ```yaml
+    /// title: "css:h1"
+    /// v2:
+    ///   price: "css:.price-now >> trim()"   # referenced as ref:v2.price
+    /// ```
+    ///
+    /// Returns the number of definitions loaded.
+    #[cfg(feature = "yaml")]
+    pub fn load_yaml(&mut self, source: &str) -> Result<usize, String> {
+        use yaml_rust2::{Yaml, YamlLoader};
+
+        let documents =
+            YamlLoader::load_from_str(source).map_err(|e| format!("invalid YAML: {}", e))?;
+        let mut count = 0;
+        match documents.first() {
+            Some(Yaml::Hash(mapping)) => self.load_mapping("", mapping, &mut count)?,
+            None | Some(Yaml::Null) => {}
+            Some(other) => {
+                return Err(format!(
+                    "a query library must be a mapping, found {}",
+                    yaml_type(other)
+                ))
+            }
+        }
+        Ok(count)
+    }
+
+    #[cfg(feature = "yaml")]
+    fn load_mapping(
+        &mut self,
+        prefix: &str,
+        mapping: &yaml_rust2::yaml::Hash,
+        count: &mut usize,
+    ) -> Result<(), String> {
+        use yaml_rust2::Yaml;
+
+        for (key, value) in mapping {
+            let Yaml::String(key) = key else {
+                return Err(format!(
+                    "query names must be strings, found {}",
+                    yaml_type(key)
+                ));
+            };
+            let name = qualified(prefix, key);
+            match value {
+                Yaml::String(query) => {
+                    self.define(&name, query);
+                    *count += 1;
+                }
+                Yaml::Hash(nested) => self.load_mapping(&name, nested, count)?,
+                other => {
+                    return Err(format!(
+                        "query '{}' must be a string, found {}",
+                        name,
+                        yaml_type(other)
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `key` under the table or mapping named `prefix`, dotted.
+#[cfg(any(feature = "toml", feature = "yaml"))]
+fn qualified(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// The name of a YAML value's type, for errors.
+#[cfg(feature = "yaml")]
+fn yaml_type(value: &yaml_rust2::Yaml) -> &'static str {
+    use yaml_rust2::Yaml;
+
+    match value {
+        Yaml::Real(_) => "float",
+        Yaml::Integer(_) => "integer",
+        Yaml::String(_) => "string",
+        Yaml::Boolean(_) => "boolean",
+        Yaml::Array(_) => "sequence",
+        Yaml::Hash(_) => "mapping",
+        Yaml::Alias(_) => "alias",
+        Yaml::Null => "null",
+        Yaml::BadValue => "invalid value",
+    }
+}
@@ -0,0 +1,259 @@
+//! Link and asset harvesting — see
+//! [`ChadSelect::extract_links`](crate::ChadSelect::extract_links) and
+//! [`ChadSelect::extract_assets`](crate::ChadSelect::extract_assets).
+
+use scraper::{ElementRef, Html, Selector};
+use url::Url;
+
+use crate::content::ContentItem;
+use crate::engine::css::match_text;
+use crate::images::parse_srcset;
+use crate::ChadSelect;
+
+/// Elements and attributes that reference page assets. `srcset` values are
+/// split into their candidate URLs.
+const ASSET_ATTRIBUTES: [(&str, &str); 12] = [
+    ("img", "src"),
+    ("img", "srcset"),
+    ("source", "src"),
+    ("source", "srcset"),
+    ("script", "src"),
+    ("link", "href"),
+    ("video", "src"),
+    ("video", "poster"),
+    ("audio", "src"),
+    ("iframe", "src"),
+    ("embed", "src"),
+    ("object", "data"),
+];
+
+/// `<link rel>` values naming an asset (other `<link>`s are metadata).
+const ASSET_RELS: [&str; 7] = [
+    "stylesheet",
+    "icon",
+    "apple-touch-icon",
+    "preload",
+    "prefetch",
+    "modulepreload",
+    "manifest",
+];
+
+/// How [`extract_links_with`](ChadSelect::extract_links_with) and
+/// [`extract_assets_with`](ChadSelect::extract_assets_with) resolve and
+/// filter URLs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkOptions {
+    /// Resolve URLs against this absolute URL. When `None`, the item's
+    /// [`content_source`](ChadSelect::content_source) is used if it is an
+    /// absolute URL. A `<base href>` in the page applies on top either way.
+    pub base_url: Option<String>,
+    /// Keep only URLs on the base URL's origin (scheme, host, and port).
+    /// Without any base, only relative URLs are kept.
+    pub same_origin: bool,
+}
+
+/// A URL referenced by a page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Link {
+    /// The URL, resolved against the base when there is one, else as
+    /// written.
+    pub url: String,
+    /// The anchor text of a link (whitespace collapsed), or the
+    /// `alt`/`title` of an asset.
+    pub text: String,
+    /// The `rel` attribute, if any.
+    pub rel: Option<String>,
+    /// The referencing element's tag (`a`, `img`, `script`, …).
+    pub tag: String,
+    /// The attribute the URL came from (`href`, `src`, `srcset`, …).
+    pub attribute: String,
+}
+
+impl ChadSelect {
+    /// The `<a href>` and `<area href>` links of the HTML item at content
+    /// `index`, in document order, with the default [`LinkOptions`].
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<a href="/cars/civic" rel="bookmark">Civic</a>"#.to_string());
+    ///
+    /// let links = cs.extract_links(0);
+    /// assert_eq!(links[0].url, "/cars/civic");
+    /// assert_eq!(links[0].text, "Civic");
+    /// assert_eq!(links[0].rel.as_deref(), Some("bookmark"));
+    /// ```
+    pub fn extract_links(&self, index: usize) -> Vec<Link> {
+        self.extract_links_with(index, &LinkOptions::default())
+    }
+
+    /// Like [`extract_links`](ChadSelect::extract_links), resolving and
+    /// filtering per `options`.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, LinkOptions};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<a href="/a">A</a><a href="https://other.example/b">B</a>"#.to_string());
+    ///
+    /// let options = LinkOptions {
+    ///     base_url: Some("https://example.com/cars/".to_string()),
+    ///     same_origin: true,
+    /// };
+    /// let links = cs.extract_links_with(0, &options);
+    /// assert_eq!(links.len(), 1);
+    /// assert_eq!(links[0].url, "https://example.com/a");
+    /// ```
+    pub fn extract_links_with(&self, index: usize, options: &LinkOptions) -> Vec<Link> {
+        let Some(item) = self.html_item(index) else {
+            return vec![];
+        };
+        let html = item.html();
+        let anchors = Selector::parse("a[href], area[href]").expect("valid selector");
+        let resolver = Resolver::new(item, &html, options);
+
+        html.select(&anchors)
+            .filter_map(|element| {
+                let href = element.value().attr("href")?;
+                if href.trim().starts_with('#') {
+                    return None;
+                }
+                let text = match element.value().name() {
+                    "area" => element.value().attr("alt").unwrap_or_default().to_string(),
+                    _ => match_text(&element)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                resolver.link(&element, "href", href, text)
+            })
+            .collect()
+    }
+
+    /// The assets (images, scripts, stylesheets, media, frames) referenced
+    /// by the HTML item at content `index`, in document order, with the
+    /// default [`LinkOptions`]. Each `srcset` candidate is its own record.
+    pub fn extract_assets(&self, index: usize) -> Vec<Link> {
+        self.extract_assets_with(index, &LinkOptions::default())
+    }
+
+    /// Like [`extract_assets`](ChadSelect::extract_assets), resolving and
+    /// filtering per `options`.
+    pub fn extract_assets_with(&self, index: usize, options: &LinkOptions) -> Vec<Link> {
+        let Some(item) = self.html_item(index) else {
+            return vec![];
+        };
+        let html = item.html();
+        let tags =
+            Selector::parse("img, source, script, link, video, audio, iframe, embed, object")
+                .expect("valid selector");
+        let resolver = Resolver::new(item, &html, options);
+
+        let mut assets = Vec::new();
+        for element in html.select(&tags) {
+            let value = element.value();
+            if value.name() == "link" && !is_asset_link(&element) {
+                continue;
+            }
+            let text = value
+                .attr("alt")
+                .or(value.attr("title"))
+                .unwrap_or_default();
+            for (_, attribute) in ASSET_ATTRIBUTES
+                .iter()
+                .filter(|(tag, _)| *tag == value.name())
+            {
+                let Some(raw) = value.attr(attribute) else {
+                    continue;
+                };
+                let urls: Vec<String> = if *attribute == "srcset" {
+                    parse_srcset(raw).into_iter().map(|c| c.url).collect()
+                } else {
+                    vec![raw.to_string()]
+                };
+                for url in urls {
+                    assets.extend(resolver.link(&element, attribute, &url, text.to_string()));
+                }
+            }
+        }
+        assets
+    }
+}
+
+fn is_asset_link(element: &ElementRef) -> bool {
+    let rel = element
+        .value()
+        .attr("rel")
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    rel.split_ascii_whitespace()
+        .any(|r| ASSET_RELS.contains(&r))
+}
+
+/// Resolves and filters one item's URLs.
+struct Resolver {
+    base: Option<Url>,
+    same_origin: bool,
+}
+
+impl Resolver {
+    fn new(item: &ContentItem, html: &Html, options: &LinkOptions) -> Self {
+        let given = options
+            .base_url
+            .as_deref()
+            .or(item.source.as_deref())
+            .and_then(|base| Url::parse(base).ok())
+            .filter(|base| !base.cannot_be_a_base());
+        let base_tag = Selector::parse("base[href]").expect("valid selector");
+        let page_base = html
+            .select(&base_tag)
+            .next()
+            .and_then(|b| b.value().attr("href"));
+
+        let base = match (given, page_base) {
+            (Some(given), Some(href)) => given.join(href.trim()).ok().or(Some(given)),
+            (None, Some(href)) => Url::parse(href.trim()).ok(),
+            (given, None) => given,
+        };
+        Resolver {
+            base,
+            same_origin: options.same_origin,
+        }
+    }
+
+    /// The record for `raw`, or `None` when it is empty, not navigable
+    /// (`javascript:`, `data:`), or filtered out as cross-origin.
+    fn link(&self, element: &ElementRef, attribute: &str, raw: &str, text: String) -> Option<Link> {
+        let raw = raw.trim();
+        let lowercase = raw.to_ascii_lowercase();
+        if raw.is_empty() || lowercase.starts_with("javascript:") || lowercase.starts_with("data:")
+        {
+            return None;
+        }
+
+        let url = match &self.base {
+            Some(base) => {
+                let resolved = base.join(raw).ok()?;
+                if self.same_origin && resolved.origin() != base.origin() {
+                    return None;
+                }
+                resolved.to_string()
+            }
+            None => {
+                if self.same_origin && Url::parse(raw).is_ok() {
+                    return None;
+                }
+                raw.to_string()
+            }
+        };
+
+        Some(Link {
+            url,
+            text,
+            rel: element.value().attr("rel").map(str::to_string),
+            tag: element.value().name().to_string(),
+            attribute: attribute.to_string(),
+        })
+    }
+}
@@ -0,0 +1,415 @@
+//! Email messages (RFC 5322 with MIME parts).
+//!
+//! [`ChadSelect::add_mime`] splits a raw message into content items: one JSON
+//! item describing the message (its headers and the list of its parts), then
+//! one item per inline HTML or plain-text part.
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine as _;
+use serde_json::{json, Map, Value};
+
+use crate::content::ContentType;
+use crate::warnings::{warning, Capture};
+use crate::ChadSelect;
+
+/// Deepest nesting of `multipart/*` parts followed; deeper parts are listed
+/// but not split.
+const MAX_MULTIPART_DEPTH: usize = 16;
+
+impl ChadSelect {
+    /// Add an email message in RFC 5322/MIME form (an `.eml` file, or what a
+    /// mail API returns as "raw"). Returns the number of content items
+    /// added.
+    ///
+    /// The first item is JSON for `json:` queries:
+    ///
+    /// - `headers` maps each lower-cased header name to its decoded value,
+    ///   or to an array of values when the header repeats (`received`).
+    /// - `parts` lists every leaf part in message order, with its
+    ///   `content_type`, `charset`, `filename`, decoded `size` in bytes, and
+    ///   `added` — whether the part was added as an item of its own.
+    ///
+    /// Each inline `text/html` part is then added as HTML and each inline
+    /// `text/plain` part as text, in message order. Base64 and
+    /// quoted-printable bodies are decoded, and so are `=?…?=` encoded words
+    /// in headers. Attachments and other types are listed but not added.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let message = "From: Dealer <sales@example.com>\r\n\
+    ///                Subject: =?utf-8?Q?Price_drop_=E2=80=94_Civic?=\r\n\
+    ///                Content-Type: text/html; charset=utf-8\r\n\
+    ///                \r\n\
+    ///                <p class=\"price\">$27,900</p>\r\n";
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// assert_eq!(cs.add_mime(message.to_string()), 2);
+    /// assert_eq!(cs.select(0, "json:headers.subject"), "Price drop — Civic");
+    /// assert_eq!(cs.select(0, "css:.price"), "$27,900");
+    /// ```
+    pub fn add_mime(&mut self, message: String) -> usize {
+        let capture = Capture::begin();
+        let root = Part::parse(&message);
+        let mut leaves = Vec::new();
+        root.collect_leaves(0, &mut leaves);
+
+        let parts: Vec<Value> = leaves
+            .iter()
+            .map(|leaf| {
+                json!({
+                    "content_type": leaf.content_type,
+                    "charset": leaf.charset,
+                    "filename": leaf.filename,
+                    "size": leaf.body.len(),
+                    "added": leaf.item_type().is_some(),
+                })
+            })
+            .collect();
+        let summary = json!({ "headers": headers_json(&root.headers), "parts": parts });
+
+        let mut added = 0;
+        match self.try_add(summary.to_string(), ContentType::Json) {
+            Ok(()) => added += 1,
+            Err(e) => warning!(ContentRejected, "Message summary not added: {}", e),
+        }
+        for leaf in &leaves {
+            let Some(content_type) = leaf.item_type() else {
+                continue;
+            };
+            match self.try_add(leaf.text(), content_type) {
+                Ok(()) => added += 1,
+                Err(e) => warning!(ContentRejected, "Message part not added: {}", e),
+            }
+        }
+        self.keep_warnings(capture);
+        added
+    }
+}
+
+/// One part of a message: its unfolded headers (names lower-cased) and raw
+/// body.
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a str,
+}
+
+/// A non-multipart part, decoded.
+struct Leaf {
+    content_type: String,
+    charset: Option<String>,
+    filename: Option<String>,
+    attachment: bool,
+    body: Vec<u8>,
+}
+
+impl<'a> Part<'a> {
+    /// Split `raw` at the first blank line into headers and body. Text
+    /// without a blank line is all headers; a part starting with a line
+    /// break has none.
+    fn parse(raw: &'a str) -> Self {
+        let (head, body) = match (raw.find("\r\n\r\n"), raw.find("\n\n")) {
+            _ if raw.starts_with("\r\n") => ("", &raw[2..]),
+            _ if raw.starts_with('\n') => ("", &raw[1..]),
+            (Some(crlf), Some(lf)) if lf < crlf => (&raw[..lf], &raw[lf + 2..]),
+            (Some(crlf), _) => (&raw[..crlf], &raw[crlf + 4..]),
+            (None, Some(lf)) => (&raw[..lf], &raw[lf + 2..]),
+            (None, None) => (raw, ""),
+        };
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in head.lines() {
+            if line.starts_with([' ', '\t']) {
+                // A folded continuation of the previous header.
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        Part { headers, body }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Append this part's leaves to `leaves`, descending into multiparts.
+    fn collect_leaves(&self, depth: usize, leaves: &mut Vec<Leaf>) {
+        let (content_type, params) = parse_parameters(self.header("content-type").unwrap_or(""));
+        let content_type = if content_type.is_empty() {
+            "text/plain".to_string()
+        } else {
+            content_type
+        };
+
+        if content_type.starts_with("multipart/") && depth < MAX_MULTIPART_DEPTH {
+            if let Some(boundary) = param(&params, "boundary") {
+                for body in split_multipart(self.body, boundary) {
+                    Part::parse(body).collect_leaves(depth + 1, leaves);
+                }
+                return;
+            }
+        }
+
+        let (disposition, disposition_params) =
+            parse_parameters(self.header("content-disposition").unwrap_or(""));
+        let filename = param(&disposition_params, "filename")
+            .or_else(|| param(&params, "name"))
+            .map(decode_words);
+        let encoding = self
+            .header("content-transfer-encoding")
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        let body = match encoding.as_str() {
+            "base64" => decode_base64(self.body),
+            "quoted-printable" => decode_quoted_printable(self.body),
+            _ => self.body.as_bytes().to_vec(),
+        };
+
+        leaves.push(Leaf {
+            charset: param(&params, "charset").map(str::to_ascii_lowercase),
+            filename,
+            attachment: disposition == "attachment",
+            content_type,
+            body,
+        });
+    }
+}
+
+impl Leaf {
+    /// The type the part is added as, if it is added.
+    fn item_type(&self) -> Option<ContentType> {
+        if self.attachment {
+            return None;
+        }
+        match self.content_type.as_str() {
+            "text/html" => Some(ContentType::Html),
+            "text/plain" => Some(ContentType::Text),
+            _ => None,
+        }
+    }
+
+    fn text(&self) -> String {
+        decode_charset(&self.body, self.charset.as_deref())
+    }
+}
+
+/// The headers as a JSON object; a repeated header becomes an array.
+fn headers_json(headers: &[(String, String)]) -> Value {
+    let mut object = Map::new();
+    for (name, value) in headers {
+        let value = Value::String(decode_words(value));
+        match object.get_mut(name) {
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            None => {
+                object.insert(name.clone(), value);
+            }
+        }
+    }
+    Value::Object(object)
+}
+
+/// Split a `type/subtype; key=value; key="quoted value"` header into its
+/// lower-cased value and its parameters (keys lower-cased).
+fn parse_parameters(header: &str) -> (String, Vec<(String, String)>) {
+    let mut pieces = split_unquoted(header, ';').into_iter();
+    let value = pieces.next().unwrap_or("").trim().to_ascii_lowercase();
+    let params = pieces
+        .filter_map(|piece| {
+            let (key, value) = piece.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((key.trim().to_ascii_lowercase(), value.replace("\\\"", "\"")))
+        })
+        .collect();
+    (value, params)
+}
+
+fn param<'p>(params: &'p [(String, String)], name: &str) -> Option<&'p str> {
+    params
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Split `text` on `separator` outside double quotes.
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c == separator && !in_quotes => {
+                pieces.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    pieces.push(&text[start..]);
+    pieces
+}
+
+/// The bodies between the `--boundary` delimiter lines of a multipart body.
+/// The line break before each delimiter belongs to the delimiter.
+fn split_multipart<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{boundary}");
+    let mut bodies = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if let Some(rest) = trimmed.strip_prefix(delimiter.as_str()) {
+            if rest.is_empty() || rest == "--" {
+                if let Some(start) = start {
+                    bodies.push(strip_line_break(&body[start..offset]));
+                }
+                if rest == "--" {
+                    return bodies;
+                }
+                start = Some(offset + line.len());
+            }
+        }
+        offset += line.len();
+    }
+    // An unterminated multipart keeps its last part.
+    if let Some(start) = start {
+        bodies.push(&body[start..]);
+    }
+    bodies
+}
+
+fn strip_line_break(text: &str) -> &str {
+    text.strip_suffix("\r\n")
+        .or_else(|| text.strip_suffix('\n'))
+        .unwrap_or(text)
+}
+
+fn decode_base64(body: &str) -> Vec<u8> {
+    let compact: String = body.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    decode_base64_text(&compact).unwrap_or_else(|e| {
+        warning!(InvalidContent, "Invalid base64 in message part: {}", e);
+        Vec::new()
+    })
+}
+
+/// Decode base64 with or without its `=` padding.
+fn decode_base64_text(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    STANDARD
+        .decode(text)
+        .or_else(|_| STANDARD_NO_PAD.decode(text.trim_end_matches('=')))
+}
+
+/// Decode quoted-printable: `=XX` escapes and `=` soft line breaks.
+fn decode_quoted_printable(body: &str) -> Vec<u8> {
+    let bytes = body.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            if let Some(rest) = bytes.get(i + 1..) {
+                if rest.starts_with(b"\r\n") {
+                    i += 3;
+                    continue;
+                }
+                if rest.starts_with(b"\n") {
+                    i += 2;
+                    continue;
+                }
+                if let Some(byte) = rest.get(..2).and_then(hex_byte) {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+fn hex_byte(pair: &[u8]) -> Option<u8> {
+    let pair = std::str::from_utf8(pair).ok()?;
+    u8::from_str_radix(pair, 16).ok()
+}
+
+/// Text from `bytes` in `charset`: UTF-8 (the default) and ASCII decode
+/// as UTF-8, ISO-8859-1 and Windows-1252 byte for byte; anything else as
+/// UTF-8 with invalid sequences replaced.
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    match charset.unwrap_or("utf-8") {
+        "iso-8859-1" | "latin1" | "latin-1" | "windows-1252" | "cp1252" => {
+            bytes.iter().map(|&b| char::from(b)).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Decode RFC 2047 encoded words (`=?utf-8?B?…?=`, `=?iso-8859-1?Q?…?=`) in
+/// a header value. Whitespace between two adjacent encoded words is
+/// dropped; anything that does not decode is kept as written.
+fn decode_words(value: &str) -> String {
+    if !value.contains("=?") {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, candidate) = rest.split_at(start);
+        match decode_word(candidate) {
+            Some((decoded, len)) => {
+                if !(after_word && before.trim().is_empty()) {
+                    out.push_str(before);
+                }
+                out.push_str(&decoded);
+                rest = &candidate[len..];
+                after_word = true;
+            }
+            None => {
+                out.push_str(before);
+                out.push_str("=?");
+                rest = &candidate[2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decode the encoded word at the start of `text`, returning it and the
+/// number of bytes it spanned.
+fn decode_word(text: &str) -> Option<(String, usize)> {
+    let inner = text.strip_prefix("=?")?;
+    let (charset, inner) = inner.split_once('?')?;
+    let (encoding, inner) = inner.split_once('?')?;
+    let end = inner.find("?=")?;
+    let encoded = &inner[..end];
+    let bytes = match encoding {
+        "B" | "b" => decode_base64_text(encoded).ok()?,
+        "Q" | "q" => decode_quoted_printable(&encoded.replace('_', " ")),
+        _ => return None,
+    };
+    // RFC 2231 allows a language suffix: `utf-8*en`.
+    let charset = charset
+        .split('*')
+        .next()
+        .unwrap_or(charset)
+        .to_ascii_lowercase();
+    let len = text.len() - inner.len() + end + 2;
+    Some((decode_charset(&bytes, Some(&charset)), len))
+}
@@ -0,0 +1,234 @@
+//! In-place HTML rewrites — see
+//! [`ChadSelect::normalize_html`](crate::ChadSelect::normalize_html) and
+//! [`ChadSelect::remove_nodes`](crate::ChadSelect::remove_nodes).
+
+use std::collections::HashSet;
+
+use ego_tree::NodeId;
+use scraper::{Html, Node};
+
+use crate::content::{ContentItem, ContentType};
+use crate::ChadSelect;
+
+/// Elements whose text keeps its whitespace.
+const PREFORMATTED_TAGS: [&str; 5] = ["pre", "textarea", "script", "style", "listing"];
+
+/// Optional rewrites applied by
+/// [`normalize_html`](ChadSelect::normalize_html) on top of the parser's own
+/// normalization.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// Attribute names to drop from every element, case-insensitively. A
+    /// trailing `*` matches a prefix: `data-*` drops all data attributes,
+    /// `on*` all inline event handlers.
+    pub remove_attributes: Vec<String>,
+    /// Write each element's attributes in alphabetical order.
+    pub sort_attributes: bool,
+    /// Drop HTML comments.
+    pub remove_comments: bool,
+    /// Collapse whitespace runs in text to a single space (except inside
+    /// `pre`, `textarea`, `script`, and `style`).
+    pub collapse_whitespace: bool,
+}
+
+impl ChadSelect {
+    /// Rewrite the HTML item at content `index` into normalized markup and
+    /// return it. `None` when `index` is out of range or not an HTML item.
+    ///
+    /// Reparsing alone lowercases tag and attribute names, closes and
+    /// re-nests sloppy markup the way browsers do, resolves entities, and
+    /// quotes attribute values; `options` adds attribute removal and
+    /// sorting, comment removal, and whitespace collapsing. The normalized
+    /// markup replaces the item's content, so Regex sees it too, and the
+    /// item's parsed caches are rebuilt from it on the next query.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, NormalizeOptions};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<DIV CLASS=price Data-Track=1 onclick="buy()">&euro;28&nbsp;500<P>VAT incl.</DIV>"#.to_string());
+    ///
+    /// let options = NormalizeOptions {
+    ///     remove_attributes: vec!["data-*".to_string(), "on*".to_string()],
+    ///     ..NormalizeOptions::default()
+    /// };
+    /// let html = cs.normalize_html(0, &options).unwrap();
+    /// assert!(html.contains(r#"<div class="price">€28&nbsp;500<p>VAT incl.</p></div>"#));
+    /// assert_eq!(cs.select(0, r#"regex:class="(\w+)""#), "price");
+    /// ```
+    pub fn normalize_html(&mut self, index: usize, options: &NormalizeOptions) -> Option<String> {
+        let item = self
+            .content_list
+            .get_mut(index)
+            .filter(|item| item.content_type == ContentType::Html)?;
+
+        let mut document = (*item.html()).clone();
+        normalize(&mut document, options);
+        Some(replace_document(item, &document))
+    }
+
+    /// Remove every element matching `query` from the HTML item at content
+    /// `index`, with its subtree, and return how many matched. Later queries
+    /// — Regex included — see the document without them, which makes
+    /// "everything except the ads" a two-step job instead of a selector
+    /// puzzle.
+    ///
+    /// `query` is a `css:` or `xpath:` query (bare queries follow the
+    /// configured prefix mode; `||` alternatives and `ref:` names work, and
+    /// every alternative's matches are removed). A `>>` function chain is
+    /// ignored. XPath may also select text and comment nodes; attribute
+    /// results are skipped. An out-of-range `index` or a non-HTML item
+    /// removes nothing.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<div class="listing">
+    ///     <p>Price: $28,500</p>
+    ///     <div class="ad">Finance from $199/mo</div>
+    ///     <script>track("$0")</script>
+    /// </div>"#.to_string());
+    ///
+    /// assert_eq!(cs.remove_nodes(0, "css:.ad, script"), Ok(2));
+    /// assert_eq!(cs.query(-1, r"regex:\$[\d,]+"), vec!["$28,500"]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// When `query` does not parse, or uses an engine other than CSS or
+    /// XPath.
+    pub fn remove_nodes(&mut self, index: usize, query: &str) -> Result<usize, String> {
+        let prepared = self.prepare(query)?;
+        let Some(item) = self
+            .content_list
+            .get_mut(index)
+            .filter(|item| item.content_type == ContentType::Html)
+        else {
+            return Ok(0);
+        };
+
+        let mut doomed = Vec::new();
+        for alternative in prepared.alternatives() {
+            let nodes = alternative.matched_nodes(item, &self.config).ok_or_else(|| {
+                format!(
+                    "remove_nodes needs a css: or xpath: query, got {}:",
+                    alternative.query_type().prefix()
+                )
+            })?;
+            doomed.extend(nodes);
+        }
+
+        let mut document = (*item.html()).clone();
+        let root = document.tree.root().id();
+        let mut seen = HashSet::new();
+        doomed.retain(|id| *id != root && seen.insert(*id));
+        for id in &doomed {
+            if let Some(mut node) = document.tree.get_mut(*id) {
+                node.detach();
+            }
+        }
+
+        if !doomed.is_empty() {
+            replace_document(item, &document);
+        }
+        Ok(doomed.len())
+    }
+}
+
+/// Make `document` the item's content, dropping the parsed caches built
+/// from the old markup, and return the new markup.
+fn replace_document(item: &mut ContentItem, document: &Html) -> String {
+    let markup = document.html();
+    item.drop_caches();
+    item.content = markup.clone();
+    markup
+}
+
+fn normalize(document: &mut Html, options: &NormalizeOptions) {
+    let removed: Vec<String> = options
+        .remove_attributes
+        .iter()
+        .map(|name| name.trim().to_ascii_lowercase())
+        .collect();
+
+    let ids: Vec<NodeId> = document
+        .tree
+        .root()
+        .descendants()
+        .map(|node| node.id())
+        .collect();
+    let mut comments = Vec::new();
+    for id in ids {
+        let preformatted = options.collapse_whitespace && in_preformatted(document, id);
+        let Some(mut node) = document.tree.get_mut(id) else {
+            continue;
+        };
+        match node.value() {
+            Node::Element(element) => {
+                if !removed.is_empty() {
+                    element
+                        .attrs
+                        .retain(|name, _| !matches_any(&name.local, &removed));
+                }
+                if options.sort_attributes {
+                    element.attrs.sort_by(|a, _, b, _| a.local.cmp(&b.local));
+                }
+            }
+            Node::Comment(_) if options.remove_comments => comments.push(id),
+            Node::Text(text) if options.collapse_whitespace && !preformatted => {
+                let collapsed = collapse(&text.text);
+                if collapsed != *text.text {
+                    text.text = collapsed.as_str().into();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for id in comments {
+        if let Some(mut node) = document.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+}
+
+/// Whether attribute `name` matches one of the lowercased `patterns`.
+fn matches_any(name: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        })
+}
+
+/// Whether node `id` sits inside an element that keeps its whitespace.
+fn in_preformatted(document: &Html, id: NodeId) -> bool {
+    document.tree.get(id).is_some_and(|node| {
+        node.ancestors().any(|a| {
+            a.value()
+                .as_element()
+                .is_some_and(|e| PREFORMATTED_TAGS.contains(&e.name()))
+        })
+    })
+}
+
+/// Collapse runs of ASCII whitespace to one space, keeping a leading and a
+/// trailing space when there was whitespace there.
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
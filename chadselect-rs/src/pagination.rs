@@ -0,0 +1,231 @@
+//! Next-page detection for crawlers — see
+//! [`ChadSelect::find_next_page`](crate::ChadSelect::find_next_page).
+
+use scraper::{ElementRef, Selector};
+
+use crate::engine::css::match_text;
+use crate::ChadSelect;
+
+/// Link texts that mean "next page" once lowercased and stripped of arrows.
+const NEXT_WORDS: [&str; 12] = [
+    "next",
+    "next page",
+    "more",
+    "older",
+    "older posts",
+    "suivant",
+    "weiter",
+    "nächste",
+    "siguiente",
+    "avanti",
+    "próxima",
+    "volgende",
+];
+
+/// Arrow-only link texts used for "next".
+const NEXT_ARROWS: [&str; 6] = ["›", "»", "→", ">", ">>", "❯"];
+
+/// Words that mark a link as something other than the next page.
+const NOT_NEXT: [&str; 4] = ["prev", "last", "first", "back"];
+
+/// Why a link was taken for the next page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextPageSignal {
+    /// A `<link rel="next">` or `<a rel="next">`.
+    RelNext,
+    /// Link text such as "Next", "Older posts", or "»".
+    NextText,
+    /// An `aria-label` or `title` mentioning "next".
+    NextLabel,
+    /// A class or id mentioning "next" (`pagination-next`).
+    NextClass,
+    /// The link numbered one past the current page of a numbered pagination.
+    PageNumber,
+}
+
+impl NextPageSignal {
+    /// How much this signal alone says the link is the next page, 0–1.
+    fn confidence(self) -> f32 {
+        match self {
+            NextPageSignal::RelNext => 0.95,
+            NextPageSignal::NextText => 0.8,
+            NextPageSignal::NextLabel => 0.75,
+            NextPageSignal::PageNumber => 0.7,
+            NextPageSignal::NextClass => 0.6,
+        }
+    }
+}
+
+/// A candidate next-page URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NextPage {
+    /// The link's `href`, as written in the page (possibly relative).
+    pub url: String,
+    /// Confidence 0–1: the strongest signal's, raised a little for each
+    /// further signal pointing at the same URL.
+    pub confidence: f32,
+    /// Every signal pointing at this URL, strongest first.
+    pub signals: Vec<NextPageSignal>,
+}
+
+impl ChadSelect {
+    /// Candidate next-page links of the HTML item at content `index`, most
+    /// confident first.
+    ///
+    /// Combines `rel="next"` links, "next"-style link text, labels and
+    /// classes (in several languages), and numbered paginations (the link
+    /// numbered one past the `aria-current` / `active` page). Links to `#`
+    /// or `javascript:` are ignored. Empty when `index` is out of range or
+    /// not an HTML item.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<nav class="pagination">
+    ///   <a href="?page=1">1</a><span aria-current="page">2</span><a href="?page=3">3</a>
+    ///   <a href="?page=3" class="next">Next »</a>
+    /// </nav>"#.to_string());
+    ///
+    /// let next = cs.find_next_page(0);
+    /// assert_eq!(next[0].url, "?page=3");
+    /// assert!(next[0].confidence > 0.8);
+    /// ```
+    pub fn find_next_page(&self, index: usize) -> Vec<NextPage> {
+        let Some(item) = self.html_item(index) else {
+            return vec![];
+        };
+        let html = item.html();
+
+        let mut found: Vec<(String, NextPageSignal)> = Vec::new();
+        let rel_next = Selector::parse("link[rel][href], a[rel][href]").expect("valid selector");
+        for element in html.select(&rel_next) {
+            let rel = element.value().attr("rel").unwrap_or_default();
+            if rel
+                .split_ascii_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("next"))
+            {
+                push_link(&mut found, &element, NextPageSignal::RelNext);
+            }
+        }
+
+        let anchors = Selector::parse("a[href]").expect("valid selector");
+        for anchor in html.select(&anchors) {
+            for signal in anchor_signals(&anchor) {
+                push_link(&mut found, &anchor, signal);
+            }
+        }
+
+        // The current page of a numbered pagination.
+        let current = Selector::parse("[aria-current=page], .active, .current, .selected")
+            .expect("valid selector");
+        for marker in html.select(&current) {
+            if let Some(next) = numbered_next(&marker, &anchors) {
+                push_link(&mut found, &next, NextPageSignal::PageNumber);
+            }
+        }
+
+        rank(found)
+    }
+}
+
+/// Record `element`'s `href` under `signal`, skipping non-navigating links.
+fn push_link(
+    found: &mut Vec<(String, NextPageSignal)>,
+    element: &ElementRef,
+    signal: NextPageSignal,
+) {
+    let href = element.value().attr("href").unwrap_or_default().trim();
+    let lowercase = href.to_ascii_lowercase();
+    if href.is_empty() || href.starts_with('#') || lowercase.starts_with("javascript:") {
+        return;
+    }
+    found.push((href.to_string(), signal));
+}
+
+/// The text, label, and class signals of one anchor.
+fn anchor_signals(anchor: &ElementRef) -> Vec<NextPageSignal> {
+    let value = anchor.value();
+    let text = match_text(anchor).to_lowercase();
+    let label = [value.attr("aria-label"), value.attr("title")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let class = [value.attr("class"), value.attr("id")]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    if NOT_NEXT
+        .iter()
+        .any(|word| text.contains(word) || label.contains(word))
+    {
+        return vec![];
+    }
+
+    let mut signals = Vec::new();
+    let words = text.trim_matches(|c: char| !c.is_alphanumeric()).trim();
+    if NEXT_WORDS.contains(&words) || NEXT_ARROWS.contains(&text.trim()) {
+        signals.push(NextPageSignal::NextText);
+    }
+    if label.contains("next") {
+        signals.push(NextPageSignal::NextLabel);
+    }
+    if class
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word == "next")
+    {
+        signals.push(NextPageSignal::NextClass);
+    }
+    signals
+}
+
+/// The anchor numbered one past `marker`, a current-page marker: when its
+/// text is a number `n`, the nearest anchor reading `n + 1` within the
+/// marker's parent or grandparent.
+fn numbered_next<'a>(marker: &ElementRef<'a>, anchors: &Selector) -> Option<ElementRef<'a>> {
+    let current: u32 = match_text(marker).parse().ok()?;
+    let wanted = (current + 1).to_string();
+
+    marker
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .take(2)
+        .find_map(|scope| {
+            scope
+                .select(anchors)
+                .find(|anchor| match_text(anchor) == wanted)
+        })
+}
+
+/// Merge signals per URL and order by confidence (then first appearance).
+fn rank(found: Vec<(String, NextPageSignal)>) -> Vec<NextPage> {
+    let mut pages: Vec<NextPage> = Vec::new();
+    for (url, signal) in found {
+        match pages.iter_mut().find(|page| page.url == url) {
+            Some(page) if !page.signals.contains(&signal) => page.signals.push(signal),
+            Some(_) => {}
+            None => pages.push(NextPage {
+                url,
+                confidence: 0.0,
+                signals: vec![signal],
+            }),
+        }
+    }
+
+    for page in &mut pages {
+        page.signals
+            .sort_by(|a, b| b.confidence().total_cmp(&a.confidence()));
+        let strongest = page.signals[0].confidence();
+        // Each corroborating signal closes a quarter of the remaining gap.
+        page.confidence = page.signals[1..].iter().fold(strongest, |confidence, _| {
+            confidence + (1.0 - confidence) * 0.25
+        });
+    }
+    pages.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    pages
+}
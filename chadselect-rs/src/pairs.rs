@@ -0,0 +1,150 @@
+//! Label/value pairing for spec tables and definition lists — see
+//! [`ChadSelect::extract_pairs`](crate::ChadSelect::extract_pairs).
+
+use std::collections::HashMap;
+
+use ego_tree::NodeId;
+use scraper::Html;
+
+use crate::content::ContentItem;
+use crate::functions;
+use crate::prepared::PreparedQuery;
+use crate::ChadSelect;
+
+impl ChadSelect {
+    /// Pair each element `label_query` matches in the content item at
+    /// `index` with the nearest `value_query` match that follows it in
+    /// document order, before the next label, and return the pairs as a
+    /// label → value map.
+    ///
+    /// Both queries are `css:` or `xpath:` queries (bare queries follow the
+    /// configured prefix mode; `||` alternatives and `ref:` names work, and
+    /// the first alternative matching anything is used). Each value is its
+    /// node's whitespace-normalized text, run through the query's `>>`
+    /// function chain; a label also loses a trailing `:`. A label with no
+    /// value before the next label is dropped, and a repeated label keeps
+    /// its first value. An out-of-range `index` or a non-HTML item yields
+    /// an empty map.
+    ///
+    /// Nothing ties a label to its value except order, so this works where
+    /// parallel queries drift apart: rows with a missing cell, values
+    /// nested beside labels in differently-classed wrappers, or
+    /// `<dt>`/`<dd>` lists with several `<dd>`s.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<table>
+    ///     <tr><th>Mileage:</th><td>12,000 mi</td></tr>
+    ///     <tr><th>Color</th></tr>
+    ///     <tr><th>Engine</th><td><b>2.0L</b> I4</td></tr>
+    /// </table>"#.to_string());
+    ///
+    /// let specs = cs.extract_pairs(0, "css:th", "css:td")?;
+    /// assert_eq!(specs.len(), 2);
+    /// assert_eq!(specs["Mileage"], "12,000 mi");
+    /// assert_eq!(specs["Engine"], "2.0L I4");
+    ///
+    /// let specs = cs.extract_pairs(0, "xpath://th", "xpath://td >> lowercase()")?;
+    /// assert_eq!(specs["Engine"], "2.0l i4");
+    /// # Ok::<(), String>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// When a query does not parse, or uses an engine other than CSS or
+    /// XPath.
+    pub fn extract_pairs(
+        &self,
+        index: usize,
+        label_query: &str,
+        value_query: &str,
+    ) -> Result<HashMap<String, String>, String> {
+        let labels = self.prepare(label_query)?;
+        let values = self.prepare(value_query)?;
+        let Some(item) = self.content_list.get(index) else {
+            return Ok(HashMap::new());
+        };
+
+        let labels = self.matched_texts(&labels, item)?;
+        let values = self.matched_texts(&values, item)?;
+        if labels.is_empty() || values.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Merge both lists in document order, labels first on a tie.
+        let (_, order) = item.html_with_order();
+        let rank = |id: &NodeId| order.get(id).copied().unwrap_or(u32::MAX);
+        let mut nodes: Vec<(u32, bool, String)> = labels
+            .into_iter()
+            .map(|(id, text)| (rank(&id), true, text))
+            .chain(
+                values
+                    .into_iter()
+                    .map(|(id, text)| (rank(&id), false, text)),
+            )
+            .collect();
+        nodes.sort_by_key(|(rank, is_label, _)| (*rank, !*is_label));
+
+        let mut pairs = HashMap::new();
+        let mut label = None;
+        for (_, is_label, text) in nodes {
+            if is_label {
+                label = Some(text.trim_end_matches(':').trim_end().to_string());
+            } else if let Some(label) = label.take() {
+                pairs.entry(label).or_insert(text);
+            }
+        }
+        pairs.remove("");
+        Ok(pairs)
+    }
+
+    /// The nodes the first matching alternative of `prepared` selects in
+    /// `item`, each with its text after the alternative's function chain.
+    /// Nodes whose text ends up empty are dropped.
+    fn matched_texts(
+        &self,
+        prepared: &PreparedQuery,
+        item: &ContentItem,
+    ) -> Result<Vec<(NodeId, String)>, String> {
+        for alternative in prepared.alternatives() {
+            let nodes = alternative
+                .matched_nodes(item, &self.config)
+                .ok_or_else(|| {
+                    format!(
+                        "extract_pairs needs a css: or xpath: query, got {}:",
+                        alternative.query_type().prefix()
+                    )
+                })?;
+            if nodes.is_empty() {
+                continue;
+            }
+
+            let html = item.html();
+            return Ok(nodes
+                .into_iter()
+                .filter_map(|id| {
+                    let text = node_text(&html, id);
+                    let text = functions::apply_text_functions(vec![text], alternative.functions())
+                        .into_iter()
+                        .next()?;
+                    Some((id, text))
+                })
+                .collect());
+        }
+        Ok(vec![])
+    }
+}
+
+/// The whitespace-normalized text of node `id` and its descendants.
+fn node_text(html: &Html, id: NodeId) -> String {
+    let Some(node) = html.tree.get(id) else {
+        return String::new();
+    };
+    let text: String = node
+        .descendants()
+        .filter_map(|node| node.value().as_text().map(|text| &**text))
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
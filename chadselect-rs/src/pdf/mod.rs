@@ -0,0 +1,480 @@
+//! PDF text layers (the `pdf` feature): the text of each page of an
+//! invoice or spec sheet, loaded as one text content item per page.
+//!
+//! A small reader — enough of the file format to find the pages, decode
+//! their content streams (`FlateDecode` through `miniz_oxide`,
+//! `ASCIIHexDecode`, `ASCII85Decode`), and map the shown strings to Unicode
+//! through the fonts' `ToUnicode` CMaps or simple encodings. Truncated or
+//! corrupted files are an error, never a panic. There is no layout
+//! analysis beyond line breaks and word gaps, and no OCR: scanned pages
+//! have no text layer.
+
+mod object;
+mod text;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::content::ContentType;
+use crate::inflate;
+use crate::warnings::{warning, Capture};
+use crate::ChadSelect;
+
+use object::{Dict, Lexer, Object};
+
+impl ChadSelect {
+    /// Add the text layer of a PDF, one text content item per page, in page
+    /// order. Returns the number of items added. Each item's
+    /// [`content_page`](ChadSelect::content_page) is its 1-based page number.
+    ///
+    /// Lines are separated by `\n` and words by spaces, as far as the text
+    /// positions show them. Pages without text (blank or scanned ones) add
+    /// no item; a content stream that cannot be decoded is skipped with a
+    /// warning, as is a page the size limits reject.
+    ///
+    /// Errors when `bytes` is not a PDF, is encrypted, ends inside an
+    /// object, or has no pages.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let page = "BT /F1 12 Tf 72 720 Td (Invoice INV-2041) Tj \
+    ///             0 -16 Td (Total: $1,250.00) Tj ET";
+    /// let pdf = format!(
+    ///     "%PDF-1.4\n\
+    ///      1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n\
+    ///      2 0 obj << /Type /Pages /Kids [3 0 R] /Count 1 >> endobj\n\
+    ///      3 0 obj << /Type /Page /Parent 2 0 R /Contents 4 0 R >> endobj\n\
+    ///      4 0 obj << /Length {} >> stream\n{}\nendstream endobj\n\
+    ///      trailer << /Root 1 0 R >>\n%%EOF",
+    ///     page.len(),
+    ///     page
+    /// );
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// assert_eq!(cs.add_pdf(pdf.as_bytes()), Ok(1));
+    /// assert_eq!(cs.select(0, r"regex:Total: \$([\d,.]+)"), "1,250.00");
+    /// assert_eq!(cs.content_page(0), Some(1));
+    /// ```
+    pub fn add_pdf(&mut self, bytes: &[u8]) -> Result<usize, String> {
+        let document = Document::parse(bytes)?;
+        let pages = document.pages();
+        if pages.is_empty() {
+            return Err("malformed PDF: no pages found".to_string());
+        }
+
+        let capture = Capture::begin();
+        let mut added = 0;
+        for (index, page) in pages.iter().enumerate() {
+            let number = index + 1;
+            let text = text::page_text(&document, page, number);
+            if text.is_empty() {
+                continue;
+            }
+            match self.try_add(text, ContentType::Text) {
+                Ok(()) => {
+                    added += 1;
+                    self.content_list
+                        .last_mut()
+                        .expect("item was just added")
+                        .page = Some(number);
+                }
+                Err(e) => warning!(ContentRejected, "PDF page {} not added: {}", number, e),
+            }
+        }
+        self.keep_warnings(capture);
+        Ok(added)
+    }
+}
+
+/// A page dictionary and the resources it uses (its own or inherited from
+/// the page tree).
+struct Page<'a> {
+    dict: &'a Dict,
+    resources: Option<&'a Dict>,
+}
+
+/// The objects of a PDF file, by object number.
+struct Document {
+    objects: HashMap<u32, Object>,
+    trailer: Dict,
+}
+
+/// Page trees nested deeper than this are treated as malformed.
+const MAX_TREE_DEPTH: usize = 64;
+
+impl Document {
+    /// Read every object in the file body and in object streams. The
+    /// cross-reference table is not consulted: objects are found by
+    /// scanning, later definitions replacing earlier ones (as incremental
+    /// updates do), which also copes with damaged tables.
+    fn parse(bytes: &[u8]) -> Result<Document, String> {
+        let header = &bytes[..bytes.len().min(1024)];
+        if find(header, b"%PDF-").is_none() {
+            return Err("not a PDF: missing %PDF- header".to_string());
+        }
+
+        let mut objects = HashMap::new();
+        let mut trailer = Dict::new();
+        let mut pos = 0;
+        while let Some((number, body)) = next_object(bytes, pos) {
+            let (object, end) = read_object(bytes, body)?;
+            objects.insert(number, object);
+            pos = end.max(body);
+        }
+        let mut pos = 0;
+        while let Some(i) = bytes.get(pos..).and_then(|rest| find(rest, b"trailer")) {
+            let mut lexer = Lexer::new(bytes);
+            lexer.pos = pos + i + b"trailer".len();
+            if let Some(Object::Dict(dict)) = lexer.object() {
+                trailer.extend(dict);
+            }
+            pos = lexer.pos.max(pos + i + 1);
+        }
+
+        let mut document = Document { objects, trailer };
+        // Cross-reference streams carry the trailer entries in PDF 1.5+.
+        let xref_entries: Vec<(String, Object)> = document
+            .objects
+            .values()
+            .filter_map(Object::as_dict)
+            .filter(|dict| dict.get("Type").and_then(Object::as_name) == Some("XRef"))
+            .flat_map(|dict| dict.iter().map(|(k, v)| (k.clone(), v.clone())))
+            .collect();
+        for (key, value) in xref_entries {
+            document.trailer.entry(key).or_insert(value);
+        }
+        if document.trailer.contains_key("Encrypt") {
+            return Err("encrypted PDFs are not supported".to_string());
+        }
+        document.unpack_object_streams();
+        Ok(document)
+    }
+
+    /// Add the objects compressed into `/Type /ObjStm` streams. Objects
+    /// also defined directly in the file body keep that definition.
+    fn unpack_object_streams(&mut self) {
+        let mut unpacked = Vec::new();
+        for object in self.objects.values() {
+            let Object::Stream(dict, _) = object else {
+                continue;
+            };
+            if dict.get("Type").and_then(Object::as_name) != Some("ObjStm") {
+                continue;
+            }
+            let Ok(data) = self.stream_data(object) else {
+                continue;
+            };
+            let count = self.number(dict.get("N")).unwrap_or(0.0) as usize;
+            let first = self.number(dict.get("First")).unwrap_or(0.0) as usize;
+            let mut header = Lexer::new(&data);
+            for _ in 0..count {
+                let (Some(Object::Number(number)), Some(Object::Number(offset))) =
+                    (header.object(), header.object())
+                else {
+                    break;
+                };
+                let mut lexer = Lexer::new(&data);
+                lexer.pos = first.saturating_add(offset as usize);
+                if let Some(object) = lexer.object() {
+                    unpacked.push((number as u32, object));
+                }
+            }
+        }
+        for (number, object) in unpacked {
+            self.objects.entry(number).or_insert(object);
+        }
+    }
+
+    /// Follow indirect references; a dangling one resolves to `null`.
+    fn resolve<'a>(&'a self, mut object: &'a Object) -> &'a Object {
+        for _ in 0..16 {
+            match object {
+                Object::Ref(number) => match self.objects.get(number) {
+                    Some(target) => object = target,
+                    None => return &Object::Null,
+                },
+                _ => return object,
+            }
+        }
+        &Object::Null
+    }
+
+    /// The resolved value of `key` in `dict`, if present and not `null`.
+    fn get<'a>(&'a self, dict: &'a Dict, key: &str) -> Option<&'a Object> {
+        match self.resolve(dict.get(key)?) {
+            Object::Null => None,
+            object => Some(object),
+        }
+    }
+
+    fn dict<'a>(&'a self, dict: &'a Dict, key: &str) -> Option<&'a Dict> {
+        self.get(dict, key)?.as_dict()
+    }
+
+    fn number(&self, object: Option<&Object>) -> Option<f64> {
+        self.resolve(object?).as_number()
+    }
+
+    /// The decoded data of a stream object.
+    fn stream_data(&self, object: &Object) -> Result<Vec<u8>, String> {
+        let Object::Stream(dict, raw) = self.resolve(object) else {
+            return Err("not a stream".to_string());
+        };
+        let filters = match self.get(dict, "Filter") {
+            None => Vec::new(),
+            Some(Object::Array(items)) => items.iter().map(|item| self.resolve(item)).collect(),
+            Some(filter) => vec![filter],
+        };
+        let params = self.get(dict, "DecodeParms");
+        let mut data = raw.clone();
+        for (i, filter) in filters.into_iter().enumerate() {
+            let name = filter.as_name().unwrap_or("");
+            data = match name {
+                "FlateDecode" | "Fl" => inflate::inflate(&data)?,
+                "ASCIIHexDecode" | "AHx" => ascii_hex(&data),
+                "ASCII85Decode" | "A85" => ascii85(&data)?,
+                _ => return Err(format!("unsupported stream filter /{}", name)),
+            };
+            let params = match params {
+                Some(Object::Array(items)) => items.get(i).map(|item| self.resolve(item)),
+                other => other,
+            };
+            let predictor = params
+                .and_then(Object::as_dict)
+                .and_then(|params| self.number(params.get("Predictor")))
+                .unwrap_or(1.0);
+            if predictor > 1.0 {
+                return Err(format!("unsupported stream predictor {}", predictor));
+            }
+        }
+        Ok(data)
+    }
+
+    /// The pages in order: the page tree under the catalog, or when that
+    /// is missing or broken, every `/Type /Page` object by object number.
+    fn pages(&self) -> Vec<Page<'_>> {
+        let mut pages = Vec::new();
+        let catalog = self.dict(&self.trailer, "Root").or_else(|| {
+            self.objects
+                .values()
+                .filter_map(Object::as_dict)
+                .find(|dict| dict.get("Type").and_then(Object::as_name) == Some("Catalog"))
+        });
+        if let Some(root) = catalog.and_then(|catalog| self.dict(catalog, "Pages")) {
+            self.collect_pages(root, None, 0, &mut HashSet::new(), &mut pages);
+        }
+        if pages.is_empty() {
+            let mut numbers: Vec<&u32> = self.objects.keys().collect();
+            numbers.sort();
+            for number in numbers {
+                let Some(dict) = self.objects[number].as_dict() else {
+                    continue;
+                };
+                if dict.get("Type").and_then(Object::as_name) == Some("Page") {
+                    pages.push(Page {
+                        dict,
+                        resources: self.dict(dict, "Resources"),
+                    });
+                }
+            }
+        }
+        pages
+    }
+
+    fn collect_pages<'a>(
+        &'a self,
+        node: &'a Dict,
+        inherited: Option<&'a Dict>,
+        depth: usize,
+        seen: &mut HashSet<u32>,
+        pages: &mut Vec<Page<'a>>,
+    ) {
+        let resources = self.dict(node, "Resources").or(inherited);
+        let Some(Object::Array(kids)) = self.get(node, "Kids") else {
+            pages.push(Page {
+                dict: node,
+                resources,
+            });
+            return;
+        };
+        if depth > MAX_TREE_DEPTH {
+            return;
+        }
+        for kid in kids {
+            if let Object::Ref(number) = kid {
+                if !seen.insert(*number) {
+                    continue;
+                }
+            }
+            if let Some(dict) = self.resolve(kid).as_dict() {
+                self.collect_pages(dict, resources, depth + 1, seen, pages);
+            }
+        }
+    }
+}
+
+/// The next `N G obj` header at or after `from`: the object number and the
+/// offset just past `obj`.
+fn next_object(bytes: &[u8], mut from: usize) -> Option<(u32, usize)> {
+    while let Some(i) = bytes.get(from..).and_then(|rest| find(rest, b"obj")) {
+        let at = from + i;
+        from = at + 3;
+        if bytes
+            .get(at + 3)
+            .is_some_and(|&b| b.is_ascii_alphanumeric())
+        {
+            continue;
+        }
+        // Walk back over `<number> <generation> `.
+        let mut j = at;
+        let skip_back = |j: &mut usize, pred: fn(u8) -> bool| {
+            let end = *j;
+            while *j > 0 && pred(bytes[*j - 1]) {
+                *j -= 1;
+            }
+            end - *j
+        };
+        if skip_back(&mut j, |b| b.is_ascii_whitespace()) == 0
+            || skip_back(&mut j, |b| b.is_ascii_digit()) == 0
+            || skip_back(&mut j, |b| b.is_ascii_whitespace()) == 0
+        {
+            continue;
+        }
+        let number_end = j;
+        if skip_back(&mut j, |b| b.is_ascii_digit()) == 0
+            || (j > 0 && bytes[j - 1].is_ascii_alphanumeric())
+        {
+            continue;
+        }
+        let Some(number) = std::str::from_utf8(&bytes[j..number_end])
+            .ok()
+            .and_then(|n| n.parse().ok())
+        else {
+            continue;
+        };
+        return Some((number, from));
+    }
+    None
+}
+
+/// Read the object starting at `pos` (just past `obj`), with its stream
+/// data if it has any. Returns the object and the offset past it; errors
+/// when the file ends inside it.
+fn read_object(bytes: &[u8], pos: usize) -> Result<(Object, usize), String> {
+    let past_end = || {
+        format!(
+            "malformed PDF: object at byte {} runs past the end of the file",
+            pos
+        )
+    };
+    let mut lexer = Lexer::new(bytes);
+    lexer.pos = pos;
+    let object = lexer.object().unwrap_or(Object::Null);
+    let Object::Dict(dict) = object else {
+        return Ok((object, lexer.pos));
+    };
+    lexer.skip_whitespace();
+    let rest = bytes.get(lexer.pos..).ok_or_else(past_end)?;
+    if !rest.starts_with(b"stream") {
+        return Ok((Object::Dict(dict), lexer.pos));
+    }
+    let mut start = lexer.pos + b"stream".len();
+    let rest = bytes.get(start..).ok_or_else(past_end)?;
+    if rest.starts_with(b"\r\n") {
+        start += 2;
+    } else if rest.starts_with(b"\n") || rest.starts_with(b"\r") {
+        start += 1;
+    }
+    let data = bytes.get(start..).ok_or_else(past_end)?;
+
+    // Trust a direct /Length only when `endstream` follows it.
+    let declared = dict
+        .get("Length")
+        .and_then(Object::as_number)
+        .and_then(|len| start.checked_add(len as usize))
+        .filter(|&end| {
+            end <= bytes.len() && {
+                let mut after = Lexer::new(bytes);
+                after.pos = end;
+                after.skip_whitespace();
+                bytes
+                    .get(after.pos..)
+                    .is_some_and(|rest| rest.starts_with(b"endstream"))
+            }
+        });
+    let (end, resume) = match declared {
+        Some(end) => (end, end),
+        None => match find(data, b"endstream") {
+            Some(i) => {
+                let mut end = start + i;
+                if bytes[..end].ends_with(b"\r\n") {
+                    end -= 2;
+                } else if bytes[..end].ends_with(b"\n") || bytes[..end].ends_with(b"\r") {
+                    end -= 1;
+                }
+                (end.max(start), start + i)
+            }
+            None => (bytes.len(), bytes.len()),
+        },
+    };
+    Ok((Object::Stream(dict, bytes[start..end].to_vec()), resume))
+}
+
+fn ascii_hex(data: &[u8]) -> Vec<u8> {
+    let mut digits = data
+        .iter()
+        .take_while(|&&b| b != b'>')
+        .filter_map(|&b| (b as char).to_digit(16).map(|d| d as u8))
+        .collect::<Vec<u8>>();
+    if digits.len() % 2 == 1 {
+        digits.push(0);
+    }
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect()
+}
+
+fn ascii85(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(data.len() * 4 / 5);
+    let mut group = [0u8; 5];
+    let mut len = 0;
+    for &b in data {
+        match b {
+            b'~' => break,
+            b'z' if len == 0 => out.extend_from_slice(&[0; 4]),
+            b'!'..=b'u' => {
+                group[len] = b - b'!';
+                len += 1;
+                if len == 5 {
+                    out.extend_from_slice(&ascii85_group(&group)?);
+                    len = 0;
+                }
+            }
+            b if b.is_ascii_whitespace() => {}
+            _ => return Err("invalid ASCII85 data".to_string()),
+        }
+    }
+    if len > 1 {
+        group[len..].fill(84);
+        let bytes = ascii85_group(&group)?;
+        out.extend_from_slice(&bytes[..len - 1]);
+    }
+    Ok(out)
+}
+
+fn ascii85_group(group: &[u8; 5]) -> Result<[u8; 4], String> {
+    let value = group
+        .iter()
+        .try_fold(0u32, |acc, &digit| {
+            acc.checked_mul(85)?.checked_add(u32::from(digit))
+        })
+        .ok_or("invalid ASCII85 data")?;
+    Ok(value.to_be_bytes())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,336 @@
+//! PDF objects and the tokenizer shared by file bodies, object streams,
+//! content streams, and CMaps.
+
+use std::collections::HashMap;
+
+pub(super) type Dict = HashMap<String, Object>;
+
+#[derive(Debug, Clone)]
+pub(super) enum Object {
+    Null,
+    /// `true` or `false` (nothing here needs the value).
+    Bool,
+    Number(f64),
+    String(Vec<u8>),
+    Name(String),
+    Array(Vec<Object>),
+    Dict(Dict),
+    /// An indirect reference, by object number.
+    Ref(u32),
+    /// A stream: its dictionary and raw (still encoded) data.
+    Stream(Dict, Vec<u8>),
+    /// A bare word — `obj`, `stream`, or a content-stream operator.
+    Keyword(String),
+}
+
+impl Object {
+    pub(super) fn as_dict(&self) -> Option<&Dict> {
+        match self {
+            Object::Dict(dict) | Object::Stream(dict, _) => Some(dict),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_name(&self) -> Option<&str> {
+        match self {
+            Object::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_number(&self) -> Option<f64> {
+        match self {
+            Object::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+/// Nesting deeper than this is treated as malformed.
+const MAX_DEPTH: usize = 64;
+
+pub(super) struct Lexer<'a> {
+    pub(super) data: &'a [u8],
+    pub(super) pos: usize,
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+fn is_regular(b: u8) -> bool {
+    !is_whitespace(b) && !is_delimiter(b)
+}
+
+impl<'a> Lexer<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Lexer { data, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    pub(super) fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.peek().is_some_and(|b| b != b'\r' && b != b'\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// The next object, or `None` at the end of the input or on a token
+    /// that cannot start one (which is skipped).
+    pub(super) fn object(&mut self) -> Option<Object> {
+        self.object_at_depth(0)
+    }
+
+    fn object_at_depth(&mut self, depth: usize) -> Option<Object> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        let b = self.peek()?;
+        match b {
+            b'/' => {
+                self.pos += 1;
+                Some(Object::Name(self.name()))
+            }
+            b'(' => {
+                self.pos += 1;
+                Some(Object::String(self.literal_string()))
+            }
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                let mut dict = Dict::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        None => break,
+                        Some(b'>') => {
+                            self.pos += 1;
+                            if self.peek() == Some(b'>') {
+                                self.pos += 1;
+                            }
+                            break;
+                        }
+                        _ => {}
+                    }
+                    match self.object_at_depth(depth + 1) {
+                        Some(Object::Name(key)) => {
+                            let value = self.object_at_depth(depth + 1).unwrap_or(Object::Null);
+                            dict.insert(key, value);
+                        }
+                        Some(_) => {}
+                        None => break,
+                    }
+                }
+                Some(Object::Dict(dict))
+            }
+            b'<' => {
+                self.pos += 1;
+                Some(Object::String(self.hex_string()))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        None => break,
+                        Some(b']') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        _ => {}
+                    }
+                    match self.object_at_depth(depth + 1) {
+                        Some(item) => items.push(item),
+                        None => break,
+                    }
+                }
+                Some(Object::Array(items))
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => {
+                let number = self.number();
+                if number.fract() == 0.0 && number >= 0.0 {
+                    if let Some(reference) = self.reference(number) {
+                        return Some(reference);
+                    }
+                }
+                Some(Object::Number(number))
+            }
+            b')' | b'>' | b']' | b'{' | b'}' => {
+                self.pos += 1;
+                self.object_at_depth(depth)
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(is_regular) {
+                    self.pos += 1;
+                }
+                let word = String::from_utf8_lossy(&self.data[start..self.pos]).into_owned();
+                Some(match word.as_str() {
+                    "true" | "false" => Object::Bool,
+                    "null" => Object::Null,
+                    _ => Object::Keyword(word),
+                })
+            }
+        }
+    }
+
+    /// After an integer, try `<generation> R`; on a miss the position is
+    /// left unchanged.
+    fn reference(&mut self, number: f64) -> Option<Object> {
+        let start = self.pos;
+        self.skip_whitespace();
+        let generation_start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos > generation_start {
+            self.skip_whitespace();
+            if self.peek() == Some(b'R')
+                && !self.data.get(self.pos + 1).is_some_and(|&b| is_regular(b))
+            {
+                self.pos += 1;
+                return Some(Object::Ref(number as u32));
+            }
+        }
+        self.pos = start;
+        None
+    }
+
+    fn number(&mut self) -> f64 {
+        let start = self.pos;
+        self.pos += 1;
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_digit() || b == b'.' || b == b'-')
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .unwrap_or(0.0)
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = Vec::new();
+        while let Some(b) = self.peek().filter(|&b| is_regular(b)) {
+            self.pos += 1;
+            if b == b'#' {
+                let hex = self
+                    .data
+                    .get(self.pos..self.pos + 2)
+                    .and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok());
+                if let Some(decoded) = hex {
+                    name.push(decoded);
+                    self.pos += 2;
+                    continue;
+                }
+            }
+            name.push(b);
+        }
+        String::from_utf8_lossy(&name).into_owned()
+    }
+
+    fn literal_string(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut depth = 1;
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'(' => {
+                    depth += 1;
+                    out.push(b);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    out.push(b);
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else { break };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'b' => out.push(8),
+                        b'f' => out.push(12),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(d - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(value as u8);
+                        }
+                        // A backslash before a line break continues the line.
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => out.push(other),
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+        out
+    }
+
+    fn hex_string(&mut self) -> Vec<u8> {
+        let mut digits = Vec::new();
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            if b == b'>' {
+                break;
+            }
+            if let Some(digit) = (b as char).to_digit(16) {
+                digits.push(digit as u8);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect()
+    }
+
+    /// Skip an inline image's data, from just after `ID` to past `EI`.
+    pub(super) fn skip_inline_image(&mut self) {
+        let data = self.data.get(self.pos..).unwrap_or_default();
+        let end = data
+            .windows(4)
+            .position(|w| is_whitespace(w[0]) && &w[1..3] == b"EI" && !is_regular(w[3]))
+            .map_or(data.len(), |i| i + 3);
+        self.pos += end;
+    }
+}
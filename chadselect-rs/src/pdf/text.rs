@@ -0,0 +1,612 @@
+//! Content-stream interpretation: the strings a page shows, decoded to
+//! Unicode and laid out as lines and words.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::object::{Dict, Lexer, Object};
+use super::{Document, Page};
+use crate::warnings::warning;
+
+/// Form XObjects nested deeper than this are not entered.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// The text of a page, lines separated by `\n`, trailing spaces trimmed.
+pub(super) fn page_text(document: &Document, page: &Page, number: usize) -> String {
+    let streams = match document.get(page.dict, "Contents") {
+        Some(Object::Array(items)) => items.iter().collect(),
+        Some(_) => vec![&page.dict["Contents"]],
+        None => Vec::new(),
+    };
+    let mut content = Vec::new();
+    for stream in streams {
+        match document.stream_data(stream) {
+            Ok(data) => {
+                content.extend_from_slice(&data);
+                content.push(b'\n');
+            }
+            Err(e) => warning!(
+                InvalidContent,
+                "PDF page {}: content skipped: {}",
+                number,
+                e
+            ),
+        }
+    }
+
+    let mut extractor = Extractor {
+        document,
+        out: String::new(),
+        fonts: HashMap::new(),
+        fallback: Rc::new(Font::simple()),
+        last: None,
+    };
+    extractor.run(&content, page.resources, IDENTITY, 0);
+
+    let lines: Vec<&str> = extractor.out.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+type Matrix = [f64; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// `a × b`, for row-vector affine matrices as PDF writes them.
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    [
+        a[0] * b[0] + a[1] * b[2],
+        a[0] * b[1] + a[1] * b[3],
+        a[2] * b[0] + a[3] * b[2],
+        a[2] * b[1] + a[3] * b[3],
+        a[4] * b[0] + a[5] * b[2] + b[4],
+        a[4] * b[1] + a[5] * b[3] + b[5],
+    ]
+}
+
+fn translate(tx: f64, ty: f64, m: &Matrix) -> Matrix {
+    multiply(&[1.0, 0.0, 0.0, 1.0, tx, ty], m)
+}
+
+struct Extractor<'a> {
+    document: &'a Document,
+    out: String,
+    /// Fonts built so far, by object number.
+    fonts: HashMap<u32, Rc<Font>>,
+    /// Used when a page names a font it does not define.
+    fallback: Rc<Font>,
+    /// Where the last shown string ended: device x and y, and font size.
+    last: Option<(f64, f64, f64)>,
+}
+
+/// Graphics and text state, as far as positioning text needs it.
+#[derive(Clone)]
+struct State {
+    ctm: Matrix,
+    tm: Matrix,
+    tlm: Matrix,
+    leading: f64,
+    font: Rc<Font>,
+    size: f64,
+}
+
+impl Extractor<'_> {
+    fn run(&mut self, content: &[u8], resources: Option<&Dict>, ctm: Matrix, depth: usize) {
+        let mut lexer = Lexer::new(content);
+        let mut operands: Vec<Object> = Vec::new();
+        let mut state = State {
+            ctm,
+            tm: IDENTITY,
+            tlm: IDENTITY,
+            leading: 0.0,
+            font: self.fallback.clone(),
+            size: 0.0,
+        };
+        let mut saved: Vec<State> = Vec::new();
+
+        while let Some(object) = lexer.object() {
+            let Object::Keyword(operator) = object else {
+                operands.push(object);
+                continue;
+            };
+            let number = |i: usize| operands.get(i).and_then(Object::as_number).unwrap_or(0.0);
+            match operator.as_str() {
+                "q" => saved.push(state.clone()),
+                "Q" => {
+                    if let Some(previous) = saved.pop() {
+                        state = previous;
+                    }
+                }
+                "cm" if operands.len() == 6 => {
+                    let m = [
+                        number(0),
+                        number(1),
+                        number(2),
+                        number(3),
+                        number(4),
+                        number(5),
+                    ];
+                    state.ctm = multiply(&m, &state.ctm);
+                }
+                "BT" => {
+                    state.tm = IDENTITY;
+                    state.tlm = IDENTITY;
+                }
+                "Tf" => {
+                    state.font = operands
+                        .first()
+                        .and_then(Object::as_name)
+                        .and_then(|name| self.font(resources, name))
+                        .unwrap_or_else(|| self.fallback.clone());
+                    state.size = number(1);
+                }
+                "TL" => state.leading = number(0),
+                "Td" => state.next_line(number(0), number(1)),
+                "TD" => {
+                    state.leading = -number(1);
+                    state.next_line(number(0), number(1));
+                }
+                "T*" => state.next_line(0.0, -state.leading),
+                "Tm" if operands.len() == 6 => {
+                    state.tlm = [
+                        number(0),
+                        number(1),
+                        number(2),
+                        number(3),
+                        number(4),
+                        number(5),
+                    ];
+                    state.tm = state.tlm;
+                }
+                "Tj" => {
+                    if let Some(Object::String(bytes)) = operands.first() {
+                        self.show(&mut state, bytes);
+                    }
+                }
+                "'" | "\"" => {
+                    state.next_line(0.0, -state.leading);
+                    if let Some(Object::String(bytes)) = operands.last() {
+                        self.show(&mut state, bytes);
+                    }
+                }
+                "TJ" => {
+                    if let Some(Object::Array(items)) = operands.first() {
+                        for item in items {
+                            match item {
+                                Object::String(bytes) => self.show(&mut state, bytes),
+                                Object::Number(adjust) => {
+                                    let shift = -adjust / 1000.0 * state.size;
+                                    state.tm = translate(shift, 0.0, &state.tm);
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                "Do" if depth < MAX_FORM_DEPTH => {
+                    if let Some(name) = operands.first().and_then(Object::as_name) {
+                        self.form(resources, name, &state.ctm, depth);
+                    }
+                }
+                "ID" => lexer.skip_inline_image(),
+                _ => {}
+            }
+            operands.clear();
+        }
+    }
+
+    /// Run a form XObject's content, if `name` is one.
+    fn form(&mut self, resources: Option<&Dict>, name: &str, ctm: &Matrix, depth: usize) {
+        let document = self.document;
+        let Some(form) = resources
+            .and_then(|resources| document.dict(resources, "XObject"))
+            .and_then(|xobjects| xobjects.get(name))
+        else {
+            return;
+        };
+        let Some(dict) = document.resolve(form).as_dict() else {
+            return;
+        };
+        if document.get(dict, "Subtype").and_then(Object::as_name) != Some("Form") {
+            return;
+        }
+        let Ok(content) = document.stream_data(form) else {
+            return;
+        };
+        let matrix = match document.get(dict, "Matrix") {
+            Some(Object::Array(items)) if items.len() == 6 => {
+                let mut m = IDENTITY;
+                for (slot, item) in m.iter_mut().zip(items) {
+                    *slot = document.resolve(item).as_number().unwrap_or(0.0);
+                }
+                m
+            }
+            _ => IDENTITY,
+        };
+        let form_resources = document.dict(dict, "Resources").or(resources);
+        self.run(&content, form_resources, multiply(&matrix, ctm), depth + 1);
+    }
+
+    fn font(&mut self, resources: Option<&Dict>, name: &str) -> Option<Rc<Font>> {
+        let document = self.document;
+        let entry = document.dict(resources?, "Font")?.get(name)?;
+        if let Object::Ref(number) = entry {
+            if let Some(font) = self.fonts.get(number) {
+                return Some(font.clone());
+            }
+            let font = Rc::new(Font::new(document, document.resolve(entry).as_dict()?));
+            self.fonts.insert(*number, font.clone());
+            return Some(font);
+        }
+        Some(Rc::new(Font::new(document, entry.as_dict()?)))
+    }
+
+    /// Append a shown string, preceded by a line break or space when its
+    /// position is far enough from where the last one ended.
+    fn show(&mut self, state: &mut State, bytes: &[u8]) {
+        let font = state.font.clone();
+        let start = multiply(&state.tm, &state.ctm);
+        let scale = (start[2] * start[2] + start[3] * start[3]).sqrt();
+        let size = (state.size * scale).abs().max(1.0);
+        if let Some((x, y, last_size)) = self.last {
+            let ends_in_space = self.out.ends_with([' ', '\n']);
+            if (start[5] - y).abs() > size.min(last_size) * 0.5 {
+                self.out.push('\n');
+            } else if !ends_in_space && (start[4] - x > size * 0.2 || start[4] < x - size) {
+                self.out.push(' ');
+            }
+        }
+
+        for (text, width) in font.decode(bytes) {
+            self.out.push_str(&text);
+            let advance = width / 1000.0 * state.size;
+            state.tm = translate(advance, 0.0, &state.tm);
+        }
+        let end = multiply(&state.tm, &state.ctm);
+        self.last = Some((end[4], end[5], size));
+    }
+}
+
+impl State {
+    fn next_line(&mut self, tx: f64, ty: f64) {
+        self.tlm = translate(tx, ty, &self.tlm);
+        self.tm = self.tlm;
+    }
+}
+
+/// What a font needs to turn shown bytes into text: its character codes'
+/// Unicode values and widths.
+struct Font {
+    /// Code lengths in bytes, shortest first.
+    code_lengths: Vec<usize>,
+    to_unicode: HashMap<u32, String>,
+    /// For simple fonts, the encoding's character for each byte.
+    encoding: Option<[char; 256]>,
+    first_char: u32,
+    widths: Vec<f64>,
+    default_width: f64,
+}
+
+impl Font {
+    /// A simple font in the standard encoding, with average glyph widths.
+    fn simple() -> Font {
+        Font {
+            code_lengths: vec![1],
+            to_unicode: HashMap::new(),
+            encoding: Some(WIN_ANSI),
+            first_char: 0,
+            widths: Vec::new(),
+            default_width: 500.0,
+        }
+    }
+
+    fn new(document: &Document, dict: &Dict) -> Font {
+        let composite = document.get(dict, "Subtype").and_then(Object::as_name) == Some("Type0");
+        let mut font = Font::simple();
+        if composite {
+            font.code_lengths = vec![2];
+            font.encoding = None;
+        }
+        if let Some(cmap) = dict.get("ToUnicode") {
+            if let Ok(data) = document.stream_data(cmap) {
+                font.read_cmap(&data);
+            }
+        }
+        if composite {
+            let descendant = match document.get(dict, "DescendantFonts") {
+                Some(Object::Array(items)) => items.first().map(|d| document.resolve(d)),
+                _ => None,
+            };
+            if let Some(dw) = descendant
+                .and_then(Object::as_dict)
+                .and_then(|d| document.number(d.get("DW")))
+            {
+                font.default_width = dw;
+            }
+            return font;
+        }
+
+        let mut encoding = WIN_ANSI;
+        if let Some(Object::Dict(enc)) = document.get(dict, "Encoding") {
+            if let Some(Object::Array(differences)) = document.get(enc, "Differences") {
+                let mut code = 0usize;
+                for item in differences {
+                    match document.resolve(item) {
+                        Object::Number(n) => code = *n as usize,
+                        Object::Name(glyph) => {
+                            if let (Some(slot), Some(c)) =
+                                (encoding.get_mut(code), glyph_char(glyph))
+                            {
+                                *slot = c;
+                            }
+                            code += 1;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        font.encoding = Some(encoding);
+        font.first_char = document.number(dict.get("FirstChar")).unwrap_or(0.0) as u32;
+        if let Some(Object::Array(widths)) = document.get(dict, "Widths") {
+            font.widths = widths
+                .iter()
+                .map(|w| document.resolve(w).as_number().unwrap_or(0.0))
+                .collect();
+        }
+        font
+    }
+
+    /// Read the code space and mappings of a `ToUnicode` CMap.
+    fn read_cmap(&mut self, data: &[u8]) {
+        let mut lexer = Lexer::new(data);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.object() {
+            tokens.push(token);
+        }
+        let mut lengths = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let Object::Keyword(keyword) = &tokens[i] else {
+                i += 1;
+                continue;
+            };
+            i += 1;
+            match keyword.as_str() {
+                "begincodespacerange" => {
+                    while let Some(Object::String(low)) = tokens.get(i) {
+                        lengths.push(low.len());
+                        i += 2;
+                    }
+                }
+                "beginbfchar" => {
+                    while let (Some(Object::String(code)), Some(Object::String(text))) =
+                        (tokens.get(i), tokens.get(i + 1))
+                    {
+                        self.to_unicode.insert(code_value(code), utf16(text));
+                        lengths.push(code.len());
+                        i += 2;
+                    }
+                }
+                "beginbfrange" => {
+                    while let (Some(Object::String(low)), Some(Object::String(high)), Some(dst)) =
+                        (tokens.get(i), tokens.get(i + 1), tokens.get(i + 2))
+                    {
+                        let (low_code, high_code) = (code_value(low), code_value(high));
+                        let span = high_code.saturating_sub(low_code).min(0xffff);
+                        for offset in 0..=span {
+                            let text = match dst {
+                                Object::String(first) => {
+                                    let mut units = utf16_units(first);
+                                    if let Some(last) = units.last_mut() {
+                                        *last = last.wrapping_add(offset as u16);
+                                    }
+                                    String::from_utf16_lossy(&units)
+                                }
+                                Object::Array(items) => match items.get(offset as usize) {
+                                    Some(Object::String(text)) => utf16(text),
+                                    _ => continue,
+                                },
+                                _ => continue,
+                            };
+                            self.to_unicode.insert(low_code + offset, text);
+                        }
+                        lengths.push(low.len());
+                        i += 3;
+                    }
+                }
+                _ => {}
+            }
+        }
+        lengths.retain(|&len| (1..=4).contains(&len));
+        if !lengths.is_empty() {
+            lengths.sort_unstable();
+            lengths.dedup();
+            self.code_lengths = lengths;
+        }
+    }
+
+    /// The text and width (in thousandths of the font size) of each
+    /// character code in `bytes`.
+    fn decode(&self, bytes: &[u8]) -> Vec<(String, f64)> {
+        let mut chars = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let remaining = bytes.len() - pos;
+            let len = self
+                .code_lengths
+                .iter()
+                .copied()
+                .filter(|&len| len <= remaining)
+                .find(|&len| {
+                    self.to_unicode
+                        .contains_key(&code_value(&bytes[pos..pos + len]))
+                })
+                .unwrap_or(self.code_lengths[0].min(remaining));
+            let code = code_value(&bytes[pos..pos + len]);
+            pos += len;
+
+            let text = match (self.to_unicode.get(&code), &self.encoding) {
+                (Some(text), _) => text.clone(),
+                (None, Some(encoding)) => encoding[code as usize & 0xff].to_string(),
+                (None, None) => String::new(),
+            };
+            let width = code
+                .checked_sub(self.first_char)
+                .and_then(|i| self.widths.get(i as usize))
+                .copied()
+                .filter(|&w| w > 0.0)
+                .unwrap_or(self.default_width);
+            chars.push((text, width));
+        }
+        chars
+    }
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |code, &b| (code << 8) | u32::from(b))
+}
+
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect()
+}
+
+fn utf16(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes))
+}
+
+/// Windows-1252 (the usual simple-font encoding; also used for the
+/// standard and Mac encodings, which agree with it on printable ASCII).
+const WIN_ANSI: [char; 256] = {
+    let mut table = ['\u{fffd}'; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = i as u8 as char;
+        i += 1;
+    }
+    let high = [
+        (0x80, '€'),
+        (0x82, '‚'),
+        (0x83, 'ƒ'),
+        (0x84, '„'),
+        (0x85, '…'),
+        (0x86, '†'),
+        (0x87, '‡'),
+        (0x88, 'ˆ'),
+        (0x89, '‰'),
+        (0x8a, 'Š'),
+        (0x8b, '‹'),
+        (0x8c, 'Œ'),
+        (0x8e, 'Ž'),
+        (0x91, '‘'),
+        (0x92, '’'),
+        (0x93, '“'),
+        (0x94, '”'),
+        (0x95, '•'),
+        (0x96, '–'),
+        (0x97, '—'),
+        (0x98, '˜'),
+        (0x99, '™'),
+        (0x9a, 'š'),
+        (0x9b, '›'),
+        (0x9c, 'œ'),
+        (0x9e, 'ž'),
+        (0x9f, 'Ÿ'),
+    ];
+    let mut j = 0;
+    while j < high.len() {
+        table[high[j].0] = high[j].1;
+        j += 1;
+    }
+    table
+};
+
+/// The character for a glyph name in an encoding's `Differences`.
+fn glyph_char(name: &str) -> Option<char> {
+    let name = name.split('.').next().unwrap_or(name);
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    let hex = match name.strip_prefix("uni") {
+        Some(hex) => hex.get(..4),
+        None => name
+            .strip_prefix('u')
+            .filter(|hex| (4..=6).contains(&hex.len())),
+    };
+    if let Some(c) = hex
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .and_then(char::from_u32)
+    {
+        return Some(c);
+    }
+    let c = match name {
+        "space" | "nbspace" | "nonbreakingspace" => ' ',
+        "exclam" => '!',
+        "quotedbl" => '"',
+        "numbersign" => '#',
+        "dollar" => '$',
+        "percent" => '%',
+        "ampersand" => '&',
+        "quotesingle" => '\'',
+        "parenleft" => '(',
+        "parenright" => ')',
+        "asterisk" => '*',
+        "plus" => '+',
+        "comma" => ',',
+        "hyphen" | "minus" => '-',
+        "period" => '.',
+        "slash" => '/',
+        "zero" => '0',
+        "one" => '1',
+        "two" => '2',
+        "three" => '3',
+        "four" => '4',
+        "five" => '5',
+        "six" => '6',
+        "seven" => '7',
+        "eight" => '8',
+        "nine" => '9',
+        "colon" => ':',
+        "semicolon" => ';',
+        "less" => '<',
+        "equal" => '=',
+        "greater" => '>',
+        "question" => '?',
+        "at" => '@',
+        "bracketleft" => '[',
+        "backslash" => '\\',
+        "bracketright" => ']',
+        "asciicircum" => '^',
+        "underscore" => '_',
+        "grave" => '`',
+        "braceleft" => '{',
+        "bar" => '|',
+        "braceright" => '}',
+        "asciitilde" => '~',
+        "quoteleft" => '‘',
+        "quoteright" => '’',
+        "quotedblleft" => '“',
+        "quotedblright" => '”',
+        "bullet" => '•',
+        "endash" => '–',
+        "emdash" => '—',
+        "ellipsis" => '…',
+        "trademark" => '™',
+        "copyright" => '©',
+        "registered" => '®',
+        "degree" => '°',
+        "Euro" => '€',
+        "sterling" => '£',
+        "yen" => '¥',
+        "section" => '§',
+        "paragraph" => '¶',
+        "dagger" => '†',
+        "multiply" => '×',
+        "divide" => '÷',
+        _ => return None,
+    };
+    Some(c)
+}
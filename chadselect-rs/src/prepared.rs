@@ -0,0 +1,751 @@
+//! Prepared queries — parse and compile a query once, run it many times.
+
+use std::borrow::Cow;
+use std::fmt;
+
+#[cfg(feature = "css")]
+use ego_tree::NodeId;
+#[cfg(feature = "json")]
+use jmespath::Expression;
+#[cfg(feature = "regex")]
+use regex::Regex;
+#[cfg(feature = "css")]
+use scraper::Selector;
+
+use crate::config::Config;
+use crate::content::ContentItem;
+#[cfg(feature = "regex")]
+use crate::content::ContentType;
+#[cfg(any(feature = "regex", feature = "css", feature = "json"))]
+use crate::engine;
+#[cfg(feature = "regex")]
+use crate::engine::bytes::Encoding;
+#[cfg(feature = "css")]
+use crate::engine::css::CompiledCss;
+#[cfg(feature = "css")]
+use crate::engine::fulltext::FullTextQuery;
+use crate::functions::{self, TextFunction};
+use crate::library::QueryLibrary;
+#[cfg(feature = "regex")]
+use crate::patterns;
+use crate::query::{self, PrefixMode, QueryType};
+use crate::result::ResultLocation;
+use crate::rewrite;
+#[cfg(all(feature = "css", feature = "xpath"))]
+use crate::translate;
+use crate::warnings::warning;
+
+/// Maximum nesting of `ref:` / `||` expansion before a query is rejected
+/// (catches a named query whose alternatives refer back to itself).
+const MAX_EXPANSION_DEPTH: usize = 16;
+
+/// A query whose prefix, engine expression, and `>>` function chain have been
+/// parsed and compiled up front.
+///
+/// Build one with [`ChadSelect::prepare`](crate::ChadSelect::prepare) or
+/// [`PreparedQuery::new`] and run it with
+/// [`ChadSelect::query_prepared`](crate::ChadSelect::query_prepared). A
+/// prepared query holds no reference to any content, so one instance can be
+/// run against any number of `ChadSelect`s — build a selector set once at
+/// startup and reuse it in the hot loop. It is `Send + Sync`, so worker
+/// threads can share one (in an `Arc`); an XPath alternative compiles once
+/// more on each thread that runs it.
+///
+/// A query with `||` fallbacks prepares every alternative.
+///
+/// ```rust
+/// use chadselect::{ChadSelect, PreparedQuery};
+///
+/// let price = PreparedQuery::new("css:.price >> trim()").unwrap();
+///
+/// for page in ["<b class='price'> $1 </b>", "<b class='price'> $2 </b>"] {
+///     let mut cs = ChadSelect::new();
+///     cs.add_html(page.to_string());
+///     assert!(cs.select_prepared(0, &price).starts_with('$'));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PreparedQuery {
+    alternatives: Vec<Alternative>,
+}
+
+/// One compiled `||` alternative.
+#[derive(Clone)]
+pub(crate) struct Alternative {
+    query_type: QueryType,
+    compiled: Compiled,
+    functions: Vec<TextFunction>,
+}
+
+/// The engine-specific compiled form of the expression. Only the engines
+/// this build includes have one.
+#[derive(Clone)]
+enum Compiled {
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+    #[cfg(feature = "json")]
+    Json(Expression<'static>),
+    #[cfg(feature = "css")]
+    Css(CompiledCss),
+    /// The expression, checked to compile. chadpath's compiled form is
+    /// neither `Send` nor `Sync`, so it is fetched from the engine's
+    /// per-thread cache when the query runs.
+    #[cfg(feature = "xpath")]
+    XPath(String),
+    #[cfg(all(feature = "css", feature = "json"))]
+    Structured(Expression<'static>),
+    #[cfg(feature = "css")]
+    FullText(FullTextQuery),
+    #[cfg(feature = "regex")]
+    Bytes {
+        regex: regex::bytes::Regex,
+        encoding: Encoding,
+    },
+    /// The source query producing JSON blobs, and the path run on each.
+    #[cfg(feature = "json")]
+    EmbeddedJson {
+        source: Box<Alternative>,
+        path: Expression<'static>,
+    },
+}
+
+impl PreparedQuery {
+    /// Parse and compile `query`, routing an unprefixed query to regex.
+    ///
+    /// Returns an error for an unparseable query or an expression its engine
+    /// rejects (invalid regex, CSS selector, XPath, or JMESPath).
+    pub fn new(query: &str) -> Result<Self, String> {
+        Self::with_mode(query, PrefixMode::Regex)
+    }
+
+    /// Like [`new`](PreparedQuery::new), routing an unprefixed query
+    /// according to `mode`.
+    pub fn with_mode(query: &str, mode: PrefixMode) -> Result<Self, String> {
+        let config = Config {
+            prefix_mode: mode,
+            ..Config::default()
+        };
+        Self::build(query, &config, None, &[], false)
+    }
+
+    /// Compile an already-parsed query.
+    pub fn from_query_type(query_type: QueryType) -> Result<Self, String> {
+        Ok(Self {
+            alternatives: vec![Alternative::compile(query_type, false)?],
+        })
+    }
+
+    /// Expand `ref:` references (when a library is given) and `||`
+    /// alternatives, then rewrite (per `config`, judged against `content`),
+    /// parse, translate, and compile each alternative.
+    ///
+    /// With `lenient`, an alternative that fails to parse or compile is
+    /// skipped with a warning instead of failing the whole query, and a
+    /// malformed text function is dropped from its chain — the behaviour of
+    /// [`ChadSelect::query`](crate::ChadSelect::query).
+    pub(crate) fn build(
+        query: &str,
+        config: &Config,
+        library: Option<&QueryLibrary>,
+        content: &[ContentItem],
+        lenient: bool,
+    ) -> Result<Self, String> {
+        let mut expanded = Vec::new();
+        expand(query, library, 0, &mut expanded)?;
+
+        let mut alternatives = Vec::with_capacity(expanded.len());
+        for alternative in &expanded {
+            let alternative = rewrite::apply(
+                alternative,
+                &config.rewrite_rules,
+                config.prefix_mode,
+                content,
+            );
+            let parsed = query::parse_query_with(&alternative, config.prefix_mode);
+            #[cfg(all(feature = "css", feature = "xpath"))]
+            let parsed =
+                parsed.map(|query_type| translate::rewrite(query_type, config.translation));
+            if let (true, Ok(query_type)) = (lenient, &parsed) {
+                if let Some(feature) = query_type.missing_feature() {
+                    let e = unsupported(query_type, feature);
+                    warning!(UnsupportedEngine, "Skipping query '{}': {}", alternative, e);
+                    continue;
+                }
+            }
+            match parsed.and_then(|query_type| Alternative::compile(query_type, lenient)) {
+                Ok(alt) => alternatives.push(alt),
+                Err(e) if lenient => {
+                    warning!(InvalidQuery, "Skipping query '{}': {}", alternative, e)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Self { alternatives })
+    }
+
+    /// The parsed query this was compiled from — the first alternative of a
+    /// `||` chain. `None` only when every alternative was skipped.
+    pub fn query_type(&self) -> Option<&QueryType> {
+        self.alternatives.first().map(|alt| &alt.query_type)
+    }
+
+    /// The compiled `||` alternatives, in fallback order.
+    pub(crate) fn alternatives(&self) -> &[Alternative] {
+        &self.alternatives
+    }
+}
+
+/// Flatten `query` into its `||` alternatives, resolving `ref:` names.
+fn expand(
+    query: &str,
+    library: Option<&QueryLibrary>,
+    depth: usize,
+    out: &mut Vec<String>,
+) -> Result<(), String> {
+    if depth > MAX_EXPANSION_DEPTH {
+        return Err(format!(
+            "query '{}' expands deeper than {} levels (cycle?)",
+            query, MAX_EXPANSION_DEPTH
+        ));
+    }
+
+    let alternatives = query::split_alternatives(query);
+    if alternatives.len() > 1 {
+        for alternative in alternatives {
+            expand(alternative, library, depth + 1, out)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(library) = library {
+        let resolved = library.resolve(query)?;
+        if resolved != query {
+            return expand(&resolved, Some(library), depth + 1, out);
+        }
+    }
+    out.push(query.to_string());
+    Ok(())
+}
+
+/// The selector of a navigation function (`closest('…')`, …) that does not
+/// compile.
+#[cfg(feature = "css")]
+fn invalid_navigation(function: &TextFunction) -> Option<&str> {
+    match function {
+        TextFunction::Navigate { step } => step
+            .selector()
+            .filter(|selector| engine::css::cached_selector(selector).is_none()),
+        _ => None,
+    }
+}
+
+impl Alternative {
+    /// Compile one parsed query. With `lenient`, a malformed text function
+    /// is skipped with a warning rather than failing the compile.
+    fn compile(query_type: QueryType, lenient: bool) -> Result<Self, String> {
+        if let Some(feature) = query_type.missing_feature() {
+            return Err(unsupported(&query_type, feature));
+        }
+        let invalid = |expr: &str| format!("invalid {} expression '{}'", query_type.prefix(), expr);
+        let split = |expr| {
+            if lenient {
+                Ok(functions::split_functions(expr))
+            } else {
+                functions::try_split_functions(expr)
+            }
+        };
+
+        #[allow(unused_mut)]
+        let (compiled, mut functions) = match &query_type {
+            #[cfg(feature = "regex")]
+            QueryType::Regex(p) => {
+                let (pattern, functions) = if lenient {
+                    functions::split_pattern_functions(p)
+                } else {
+                    functions::try_split_pattern_functions(p)?
+                };
+                let re = engine::regex::compiled(&pattern).ok_or_else(|| invalid(&pattern))?;
+                (Compiled::Regex(re), functions)
+            }
+            #[cfg(feature = "json")]
+            QueryType::JsonPath(p) => {
+                let (path, functions) = split(p)?;
+                let expr = engine::json::compiled(&path).ok_or_else(|| invalid(&path))?;
+                (Compiled::Json(expr), functions)
+            }
+            #[cfg(feature = "css")]
+            QueryType::CssSelector(s) => {
+                let (selector, functions) = split(s)?;
+                let css = engine::css::compile(&selector).ok_or_else(|| invalid(&selector))?;
+                (Compiled::Css(css), functions)
+            }
+            #[cfg(feature = "xpath")]
+            QueryType::XPath(x) => {
+                let (expr, functions) = split(x)?;
+                engine::xpath::compile(&expr).ok_or_else(|| invalid(&expr))?;
+                (Compiled::XPath(expr.into_owned()), functions)
+            }
+            #[cfg(all(feature = "css", feature = "json"))]
+            QueryType::Structured(p) => {
+                let (path, functions) = split(p)?;
+                let expr = engine::json::compiled(&path).ok_or_else(|| invalid(&path))?;
+                (Compiled::Structured(expr), functions)
+            }
+            #[cfg(feature = "css")]
+            QueryType::FullText(e) => {
+                let (expression, functions) = split(e)?;
+                let query = engine::fulltext::compile(&expression)
+                    .map_err(|e| format!("{}: {}", invalid(&expression), e))?;
+                (Compiled::FullText(query), functions)
+            }
+            #[cfg(feature = "regex")]
+            QueryType::Bytes(p) => {
+                let (pattern, functions) = if lenient {
+                    functions::split_pattern_functions(p)
+                } else {
+                    functions::try_split_pattern_functions(p)?
+                };
+                let regex = engine::bytes::compiled(&pattern).ok_or_else(|| invalid(&pattern))?;
+                let encoding = Encoding::Text;
+                (Compiled::Bytes { regex, encoding }, functions)
+            }
+            #[cfg(feature = "regex")]
+            QueryType::Hex(e) => {
+                let (signature, functions) = split(e)?;
+                let pattern = patterns::hex_to_regex(&signature)
+                    .map_err(|e| format!("{}: {}", invalid(&signature), e))?;
+                let regex = engine::bytes::compiled(&pattern).ok_or_else(|| invalid(&signature))?;
+                let encoding = Encoding::Hex;
+                (Compiled::Bytes { regex, encoding }, functions)
+            }
+            #[cfg(feature = "json")]
+            QueryType::EmbeddedJson(e) => {
+                let (source, stage) = engine::embedded::split(e);
+                let source = Alternative::compile(query::parse_query(source)?, lenient)?;
+                let (path, functions) = split(stage.unwrap_or("@"))?;
+                let path = engine::json::compiled(&path).ok_or_else(|| invalid(&path))?;
+                let source = Box::new(source);
+                (Compiled::EmbeddedJson { source, path }, functions)
+            }
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("missing_feature rejects engines this build leaves out"),
+        };
+        #[cfg(feature = "css")]
+        if let Some(selector) = functions.iter().find_map(invalid_navigation) {
+            let error = format!("invalid selector '{}'", selector);
+            if !lenient {
+                return Err(error);
+            }
+            warning!(InvalidQuery, "{}; skipping the navigation function", error);
+            functions.retain(|function| invalid_navigation(function).is_none());
+        }
+
+        Ok(Self {
+            query_type,
+            compiled,
+            functions,
+        })
+    }
+
+    /// The parsed query this alternative was compiled from.
+    pub(crate) fn query_type(&self) -> &QueryType {
+        &self.query_type
+    }
+
+    /// The `>>` function chain applied to this alternative's results.
+    #[cfg(feature = "css")]
+    pub(crate) fn functions(&self) -> &[TextFunction] {
+        &self.functions
+    }
+
+    /// The selector and function chain of a plain CSS alternative (no text
+    /// pseudo-selector) — the ones `query_batch` can match in a shared walk.
+    #[cfg(feature = "css")]
+    pub(crate) fn plain_css(&self) -> Option<(&Selector, &[TextFunction])> {
+        match &self.compiled {
+            Compiled::Css(CompiledCss::Standard(selector)) => Some((selector, &self.functions)),
+            _ => None,
+        }
+    }
+
+    /// The compiled selector of a CSS alternative (pseudo-selectors included).
+    #[cfg(feature = "css")]
+    pub(crate) fn css(&self) -> Option<&CompiledCss> {
+        match &self.compiled {
+            Compiled::Css(css) => Some(css),
+            _ => None,
+        }
+    }
+
+    /// The document nodes a CSS or XPath alternative matches in the item's
+    /// cached document, ignoring any function chain. `None` for other
+    /// engines, which do not select nodes.
+    #[cfg(feature = "css")]
+    #[cfg_attr(not(feature = "xpath"), allow(unused_variables))]
+    pub(crate) fn matched_nodes(
+        &self,
+        content_item: &ContentItem,
+        config: &Config,
+    ) -> Option<Vec<NodeId>> {
+        let compatible = query::is_query_compatible(&self.query_type, &content_item.content_type);
+        let nodes = match &self.compiled {
+            Compiled::Css(_) if !compatible => vec![],
+            Compiled::Css(css) => engine::css::matched_nodes(css, content_item),
+            #[cfg(feature = "xpath")]
+            Compiled::XPath(_) if !compatible => vec![],
+            #[cfg(feature = "xpath")]
+            Compiled::XPath(expr) => match engine::xpath::compile(expr) {
+                Some(transform) => {
+                    engine::xpath::matched_nodes(&transform, content_item, config.xpath_shims)
+                }
+                None => vec![],
+            },
+            _ => return None,
+        };
+        Some(nodes)
+    }
+
+    /// How many values the engine needs to collect from one item: one past
+    /// [`max_results`](crate::ChadSelectBuilder::max_results), so the caller
+    /// can tell the cap was hit. A function chain may fold or filter, so it
+    /// always gets every match.
+    fn match_limit(&self, config: &Config) -> usize {
+        match config.max_results {
+            Some(max) if self.functions.is_empty() => max.saturating_add(1),
+            _ => usize::MAX,
+        }
+    }
+
+    /// Run against one content item. Incompatible items yield no results.
+    pub(crate) fn run(&self, content_item: &ContentItem, config: &Config) -> Vec<String> {
+        self.run_limited(content_item, config, self.match_limit(config))
+    }
+
+    #[cfg_attr(
+        not(all(feature = "xpath", feature = "json", feature = "regex")),
+        allow(unused_variables, clippy::only_used_in_recursion)
+    )]
+    fn run_limited(
+        &self,
+        content_item: &ContentItem,
+        config: &Config,
+        limit: usize,
+    ) -> Vec<String> {
+        if !query::is_query_compatible(&self.query_type, &content_item.content_type) {
+            return vec![];
+        }
+
+        let results = match &self.compiled {
+            #[cfg(feature = "regex")]
+            Compiled::Regex(re) if self.functions.is_empty() => {
+                engine::regex::extract(re, &content_item.content, limit)
+            }
+            // A chain may widen matches (`context`), so it sees their ranges.
+            #[cfg(feature = "regex")]
+            Compiled::Regex(re) => {
+                let content = &content_item.content;
+                let entries = engine::regex::extract_located(re, content, limit)
+                    .into_iter()
+                    .map(|(span, text)| (Some((span.start, span.end)), text))
+                    .collect();
+                return functions::apply_chain(
+                    entries,
+                    &self.functions,
+                    &engine::regex::Spans(content),
+                )
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect();
+            }
+            #[cfg(feature = "json")]
+            Compiled::Json(expr) => engine::json::evaluate(expr, content_item),
+            #[cfg(feature = "xpath")]
+            Compiled::XPath(expr) => match engine::xpath::compile(expr) {
+                Some(transform) => engine::xpath::evaluate(
+                    &transform,
+                    content_item,
+                    &config.text,
+                    config.xpath_shims,
+                    limit,
+                ),
+                None => vec![],
+            },
+            // CSS applies its own chain (`get-attr` reads the element, not text).
+            #[cfg(all(feature = "css", feature = "json"))]
+            Compiled::Structured(expr) => engine::structured::evaluate(expr, content_item),
+            #[cfg(feature = "css")]
+            Compiled::FullText(query) => engine::fulltext::evaluate(query, content_item, limit),
+            #[cfg(feature = "regex")]
+            Compiled::Bytes { regex, encoding } => {
+                engine::bytes::evaluate(regex, *encoding, content_item, limit)
+            }
+            // Every blob can hold any number of values, so the source is uncapped.
+            #[cfg(feature = "json")]
+            Compiled::EmbeddedJson { source, path } => engine::embedded::evaluate(
+                path,
+                &source.run_limited(content_item, config, usize::MAX),
+            ),
+            #[cfg(feature = "css")]
+            Compiled::Css(css) => {
+                return engine::css::run(css, &self.functions, content_item, &config.text, limit)
+            }
+        };
+
+        if self.functions.is_empty() {
+            results
+        } else {
+            functions::apply_text_functions(results, &self.functions)
+        }
+    }
+
+    /// Like [`run`](Alternative::run), borrowing regex matches from the
+    /// content when there is no function chain to rewrite them.
+    pub(crate) fn run_borrowed<'a>(
+        &self,
+        content_item: &'a ContentItem,
+        config: &Config,
+    ) -> Vec<Cow<'a, str>> {
+        match &self.compiled {
+            #[cfg(feature = "regex")]
+            Compiled::Regex(re) if self.functions.is_empty() => {
+                if !query::is_query_compatible(&self.query_type, &content_item.content_type) {
+                    return vec![];
+                }
+                let limit = self.match_limit(config);
+                engine::regex::extract_borrowed(re, &content_item.content, limit)
+                    .into_iter()
+                    .map(Cow::Borrowed)
+                    .collect()
+            }
+            _ => self
+                .run(content_item, config)
+                .into_iter()
+                .map(Cow::Owned)
+                .collect(),
+        }
+    }
+
+    /// Like [`run`](Alternative::run), pairing each value with where it was
+    /// found. `content_index` is the item's position in load order.
+    #[cfg_attr(not(any(feature = "css", feature = "regex")), allow(unused_variables))]
+    pub(crate) fn run_located(
+        &self,
+        content_item: &ContentItem,
+        content_index: usize,
+        config: &Config,
+    ) -> Vec<(ResultLocation, String)> {
+        if !query::is_query_compatible(&self.query_type, &content_item.content_type) {
+            return vec![];
+        }
+        let unlocated = || ResultLocation {
+            content_index,
+            ..Default::default()
+        };
+        #[cfg(feature = "css")]
+        let at_element = |element: Option<(usize, String)>| match element {
+            Some((position, css_path)) => ResultLocation {
+                position: Some(position),
+                css_path: Some(css_path),
+                ..unlocated()
+            },
+            None => unlocated(),
+        };
+        let limit = self.match_limit(config);
+
+        match &self.compiled {
+            #[cfg(feature = "css")]
+            Compiled::Css(css) => {
+                engine::css::run_located(css, &self.functions, content_item, &config.text, limit)
+                    .into_iter()
+                    .map(|(element, text)| (at_element(element), text))
+                    .collect()
+            }
+            #[cfg(feature = "xpath")]
+            Compiled::XPath(expr) => match engine::xpath::compile(expr) {
+                Some(transform) => engine::xpath::run_located(
+                    &transform,
+                    &self.functions,
+                    content_item,
+                    &config.text,
+                    config.xpath_shims,
+                    limit,
+                )
+                .into_iter()
+                .map(|(element, text)| (at_element(element), text))
+                .collect(),
+                None => vec![],
+            },
+            #[cfg(feature = "regex")]
+            Compiled::Regex(re) => {
+                let content = &content_item.content;
+                let entries = engine::regex::extract_located(re, content, limit)
+                    .into_iter()
+                    .map(|(span, text)| (Some((span.start, span.end)), text))
+                    .collect();
+                let entries = if self.functions.is_empty() {
+                    entries
+                } else {
+                    functions::apply_chain(entries, &self.functions, &engine::regex::Spans(content))
+                };
+                entries
+                    .into_iter()
+                    .map(|(span, text)| {
+                        let location = match span {
+                            Some((start, end)) => {
+                                ResultLocation::at_span(content_index, content, start..end)
+                            }
+                            None => unlocated(),
+                        };
+                        (location, text)
+                    })
+                    .collect()
+            }
+            #[cfg(feature = "regex")]
+            Compiled::Bytes { regex, encoding } => {
+                let bytes = content_item.bytes();
+                let ranges = engine::bytes::extract_located(regex, &bytes, limit);
+                let entries = ranges
+                    .iter()
+                    .enumerate()
+                    .map(|(i, range)| (Some(i), encoding.encode(&bytes[range.clone()])))
+                    .collect();
+                let entries = if self.functions.is_empty() {
+                    entries
+                } else {
+                    functions::apply_chain(entries, &self.functions, &functions::Detached)
+                };
+                let content = &content_item.content;
+                entries
+                    .into_iter()
+                    .map(|(i, text)| {
+                        let location = match i.map(|i| ranges[i].clone()) {
+                            // Line and column only where the bytes are the
+                            // content's own, and the match starts a character.
+                            Some(span)
+                                if content_item.content_type != ContentType::Binary
+                                    && content.is_char_boundary(span.start) =>
+                            {
+                                ResultLocation::at_span(content_index, content, span)
+                            }
+                            Some(span) => ResultLocation {
+                                span: Some(span),
+                                ..unlocated()
+                            },
+                            None => unlocated(),
+                        };
+                        (location, text)
+                    })
+                    .collect()
+            }
+            #[cfg(feature = "css")]
+            Compiled::FullText(query) => {
+                let (locations, sentences): (Vec<_>, Vec<_>) =
+                    engine::fulltext::evaluate_located(query, content_item, content_index, limit)
+                        .into_iter()
+                        .unzip();
+                let entries = sentences
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, text)| (Some(i), text))
+                    .collect();
+                let entries = if self.functions.is_empty() {
+                    entries
+                } else {
+                    functions::apply_chain(entries, &self.functions, &functions::Detached)
+                };
+                entries
+                    .into_iter()
+                    .map(|(i, text)| {
+                        let location = i.map_or_else(unlocated, |i| locations[i].clone());
+                        (location, text)
+                    })
+                    .collect()
+            }
+            #[allow(unreachable_patterns)]
+            _ => self
+                .run(content_item, config)
+                .into_iter()
+                .map(|text| (unlocated(), text))
+                .collect(),
+        }
+    }
+
+    /// Whether [`run_appended`](Alternative::run_appended) can run this
+    /// alternative over appended text alone: regexes can, the other engines
+    /// need the whole document.
+    pub(crate) fn follows_appends(&self) -> bool {
+        #[cfg(feature = "regex")]
+        if let Compiled::Regex(_) = self.compiled {
+            return true;
+        }
+        false
+    }
+
+    /// Like [`run`](Alternative::run), over just the text appended to the
+    /// content at byte `from`: the search runs over the whole content,
+    /// starting at the line holding `from` (or at the top, for a pattern
+    /// that can match across lines), and keeps the matches that end past
+    /// it, so a match the append lengthened is returned whole. Empty unless the alternative
+    /// [`follows_appends`](Alternative::follows_appends).
+    #[cfg_attr(not(feature = "regex"), allow(unused_variables))]
+    pub(crate) fn run_appended(
+        &self,
+        content_item: &ContentItem,
+        config: &Config,
+        from: usize,
+    ) -> Vec<String> {
+        if !query::is_query_compatible(&self.query_type, &content_item.content_type) {
+            return vec![];
+        }
+        match &self.compiled {
+            #[cfg(feature = "regex")]
+            Compiled::Regex(re) => {
+                let content = &content_item.content;
+                // A match that can cross lines may start anywhere before the
+                // append; any other starts no earlier than its line.
+                let start = if engine::regex::spans_lines(re) {
+                    0
+                } else {
+                    content[..from].rfind('\n').map_or(0, |i| i + 1)
+                };
+                let entries = engine::regex::extract_located_from(re, content, start, usize::MAX)
+                    .into_iter()
+                    .filter(|(span, _)| span.end > from)
+                    .take(self.match_limit(config))
+                    .map(|(span, text)| (Some((span.start, span.end)), text))
+                    .collect();
+                let entries = if self.functions.is_empty() {
+                    entries
+                } else {
+                    functions::apply_chain(entries, &self.functions, &engine::regex::Spans(content))
+                };
+                entries.into_iter().map(|(_, text)| text).collect()
+            }
+            #[allow(unreachable_patterns)]
+            _ => vec![],
+        }
+    }
+}
+
+/// The error for a query whose engine needs `feature`, which this build
+/// leaves out.
+fn unsupported(query_type: &QueryType, feature: &str) -> String {
+    format!(
+        "the {}: engine is not built in (enable the `{}` feature)",
+        query_type.prefix(),
+        feature
+    )
+}
+
+impl fmt::Debug for PreparedQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let query_types: Vec<_> = self
+            .alternatives
+            .iter()
+            .map(|alt| &alt.query_type)
+            .collect();
+        f.debug_struct("PreparedQuery")
+            .field("alternatives", &query_types)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,154 @@
+//! Detailed query results.
+
+use std::ops::Range;
+
+use crate::cancel::QueryStatus;
+use crate::query::QueryType;
+use crate::warnings::Warning;
+
+/// The outcome of [`ChadSelect::query_detailed`](crate::ChadSelect::query_detailed):
+/// the same values [`query`](crate::ChadSelect::query) returns, plus how the
+/// query was routed and where each value came from.
+#[derive(Debug, Clone, Default)]
+pub struct QueryResult {
+    /// Matching values after index selection — identical to `query()`.
+    pub results: Vec<String>,
+    /// Where each value was found: one entry per entry of `results`, in the
+    /// same order.
+    pub locations: Vec<ResultLocation>,
+    /// The parsed query, including the engine actually chosen for `auto:` or
+    /// [`PrefixMode::Auto`](crate::PrefixMode::Auto) queries. For a `||`
+    /// chain this is the alternative that produced the results (the first
+    /// alternative when none did). `None` when the query failed to parse.
+    pub query_type: Option<QueryType>,
+    /// Whether the query ran to completion. Only
+    /// [`query_cancellable`](crate::ChadSelect::query_cancellable) can stop a
+    /// query early; its partial results are still in `results`.
+    pub status: QueryStatus,
+    /// Whether [`max_results`](crate::ChadSelectBuilder::max_results) cut
+    /// the results short; `results` holds the first `max_results` of them.
+    pub truncated: bool,
+    /// Warnings recorded while the query ran: an invalid alternative,
+    /// content that failed to parse, an index out of range.
+    pub warnings: Vec<Warning>,
+}
+
+impl QueryResult {
+    /// The prefix of the engine that ran the query (`"css"`, `"xpath"`, …),
+    /// or `None` when the query failed to parse.
+    pub fn engine(&self) -> Option<&'static str> {
+        self.query_type.as_ref().map(QueryType::prefix)
+    }
+}
+
+/// Where a result value was found in the loaded content.
+///
+/// Which fields are known depends on the engine: CSS and XPath element
+/// results carry the matched element's `position` and `css_path`; regex
+/// results (and `text:` results from plain content) carry the `line`,
+/// `column`, and byte `span` of the match. A value produced by folding
+/// several matches (`join()`, `count()`, …) or by `if-empty()` only has its
+/// `content_index`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultLocation {
+    /// Index of the content item in load order.
+    pub content_index: usize,
+    /// The matched element's pre-order rank among all nodes of its document,
+    /// so sorting by it gives document order.
+    pub position: Option<usize>,
+    /// A selector path from the document root (or the nearest ancestor with
+    /// an `id`) to the matched element, e.g.
+    /// `html > body > div.products > div:nth-child(3) > span.price`.
+    pub css_path: Option<String>,
+    /// 1-based line of the match in the raw content.
+    pub line: Option<usize>,
+    /// 1-based column (in characters) of the match in the raw content.
+    pub column: Option<usize>,
+    /// Byte range of the match in the raw content. A function chain may
+    /// change the value, but not the range it came from.
+    pub span: Option<Range<usize>>,
+}
+
+impl ResultLocation {
+    /// The location of the byte range `span` in `content`, with the 1-based
+    /// line and column of its start.
+    #[cfg(any(feature = "css", feature = "regex"))]
+    pub(crate) fn at_span(content_index: usize, content: &str, span: Range<usize>) -> Self {
+        let before = &content[..span.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ResultLocation {
+            content_index,
+            line: Some(before.matches('\n').count() + 1),
+            column: Some(before[line_start..].chars().count() + 1),
+            span: Some(span),
+            ..Default::default()
+        }
+    }
+}
+
+/// The byte range of the first occurrence of `text` in `markup` at or after
+/// byte `from` that lies in text content, not inside a tag or comment.
+pub(crate) fn find_in_markup(markup: &str, text: &str, from: usize) -> Option<Range<usize>> {
+    if text.is_empty() {
+        return None;
+    }
+    let mut start = from;
+    while let Some(offset) = markup.get(start..)?.find(text) {
+        let found = start + offset;
+        let before = &markup[..found];
+        let in_tag = before.rfind('<') > before.rfind('>');
+        let in_comment = before.rfind("<!--") > before.rfind("-->");
+        if !in_tag && !in_comment {
+            return Some(found..found + text.len());
+        }
+        start = found + text.chars().next()?.len_utf8();
+    }
+    None
+}
+
+/// How one query fared across the loaded content — a row of the coverage
+/// matrix from [`ChadSelect::check_queries`](crate::ChadSelect::check_queries).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryCoverage {
+    /// The query as given.
+    pub query: String,
+    /// Why the query could not be prepared, if it could not.
+    pub error: Option<String>,
+    /// Result count per content item, in load order. `None` where no
+    /// alternative of the query applies to the item's content type (a `css:`
+    /// query on JSON), so the item is neither a hit nor a miss.
+    pub matches: Vec<Option<usize>>,
+}
+
+impl QueryCoverage {
+    /// Indices of the content items the query returned results for.
+    pub fn matched_items(&self) -> Vec<usize> {
+        self.items_where(|count| count > 0)
+    }
+
+    /// Indices of the applicable content items the query returned nothing
+    /// for.
+    pub fn missed_items(&self) -> Vec<usize> {
+        self.items_where(|count| count == 0)
+    }
+
+    /// The query failed to prepare or matched no item at all.
+    pub fn is_dead(&self) -> bool {
+        self.matched_items().is_empty()
+    }
+
+    /// The query matched some items but missed others it applies to — the
+    /// usual sign of a selector broken by a redesign of part of a site.
+    pub fn is_partial(&self) -> bool {
+        !self.matched_items().is_empty() && !self.missed_items().is_empty()
+    }
+
+    fn items_where(&self, keep: impl Fn(usize) -> bool) -> Vec<usize> {
+        self.matches
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| count.is_some_and(&keep))
+            .map(|(item, _)| item)
+            .collect()
+    }
+}
@@ -0,0 +1,157 @@
+//! House-convention query rewrite rules, registered on the builder.
+
+use std::borrow::Cow;
+
+use crate::content::{ContentItem, ContentType};
+use crate::functions;
+use crate::query::{self, PrefixMode, QueryType, FUNCTION_PIPE};
+
+/// A rewrite applied to every query before it is parsed, so a convention can
+/// be enforced in one place instead of in every selector string. Register
+/// rules with [`ChadSelectBuilder::rewrite_rule`](crate::ChadSelectBuilder::rewrite_rule).
+///
+/// Rules see each `||` alternative (after `ref:` names are resolved) on its
+/// own. `DefaultPrefix` rules run first — the first whose content types fit
+/// wins — then every `AppendFunctions` rule, in the order registered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RewriteRule {
+    /// Append the `>>` chain `functions` (without the leading `>>`) to
+    /// queries run by the named engines (`"css"`, `"xpath"`, … — see
+    /// [`QueryType::prefix`]), unless their chain already ends with it.
+    AppendFunctions {
+        engines: Vec<String>,
+        functions: String,
+    },
+    /// Give queries with no engine prefix the prefix `prefix` (without the
+    /// colon) when every loaded content item is one of `content_types`. The
+    /// loaded content is judged when the query is built, so a
+    /// [`PreparedQuery`](crate::PreparedQuery) keeps the routing it was
+    /// prepared with.
+    DefaultPrefix {
+        prefix: String,
+        content_types: Vec<ContentType>,
+    },
+}
+
+impl RewriteRule {
+    /// An [`AppendFunctions`](RewriteRule::AppendFunctions) rule.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, RewriteRule};
+    ///
+    /// let rule = RewriteRule::append_functions(&["css", "xpath"], "normalize-space()");
+    /// let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    /// cs.add_html("<p>  Blue \n  Metallic </p>".to_string());
+    ///
+    /// assert_eq!(cs.select(0, "css:p"), "Blue Metallic");
+    /// assert_eq!(cs.select(0, "xpath://p"), "Blue Metallic");
+    /// ```
+    pub fn append_functions(engines: &[&str], functions: &str) -> Self {
+        RewriteRule::AppendFunctions {
+            engines: engines.iter().map(|engine| engine.to_string()).collect(),
+            functions: functions.to_string(),
+        }
+    }
+
+    /// A [`DefaultPrefix`](RewriteRule::DefaultPrefix) rule.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, ContentType, RewriteRule};
+    ///
+    /// let rule = RewriteRule::default_prefix("json", &[ContentType::Json]);
+    /// let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    /// cs.add_json(r#"{"user": {"name": "Ada"}}"#.to_string());
+    ///
+    /// assert_eq!(cs.select(0, "user.name"), "Ada");
+    /// ```
+    pub fn default_prefix(prefix: &str, content_types: &[ContentType]) -> Self {
+        RewriteRule::DefaultPrefix {
+            prefix: prefix.trim_end_matches(':').to_string(),
+            content_types: content_types.to_vec(),
+        }
+    }
+}
+
+/// Apply `rules` to one query alternative. `content` is the loaded content
+/// that `DefaultPrefix` rules are judged against.
+pub(crate) fn apply<'a>(
+    query: &'a str,
+    rules: &[RewriteRule],
+    mode: PrefixMode,
+    content: &[ContentItem],
+) -> Cow<'a, str> {
+    let mut query = Cow::Borrowed(query);
+    if rules.is_empty() {
+        return query;
+    }
+
+    if !query::has_prefix(&query) && !content.is_empty() {
+        let prefix = rules.iter().find_map(|rule| match rule {
+            RewriteRule::DefaultPrefix {
+                prefix,
+                content_types,
+            } if content
+                .iter()
+                .all(|item| content_types.contains(&item.content_type)) =>
+            {
+                Some(prefix)
+            }
+            _ => None,
+        });
+        if let Some(prefix) = prefix {
+            query = Cow::Owned(format!("{}:{}", prefix, query));
+        }
+    }
+
+    for rule in rules {
+        let RewriteRule::AppendFunctions { engines, functions } = rule else {
+            continue;
+        };
+        let Ok(query_type) = query::parse_query_with(&query, mode) else {
+            continue;
+        };
+        if engines.iter().any(|engine| engine == query_type.prefix())
+            && !ends_with_chain(&query_type, functions)
+        {
+            query = Cow::Owned(format!("{} {} {}", query, FUNCTION_PIPE, functions));
+        }
+    }
+    query
+}
+
+/// Whether the query's function chain already ends with the calls of
+/// `functions` (ignoring whitespace between tokens).
+fn ends_with_chain(query_type: &QueryType, functions: &str) -> bool {
+    let (expression, quote_aware) = match query_type {
+        QueryType::Regex(pattern) | QueryType::Bytes(pattern) => (pattern, false),
+        QueryType::XPath(e)
+        | QueryType::JsonPath(e)
+        | QueryType::CssSelector(e)
+        | QueryType::EmbeddedJson(e)
+        | QueryType::Structured(e)
+        | QueryType::FullText(e)
+        | QueryType::Hex(e) => (e, true),
+    };
+    let Some(chain) = functions::split_chain(expression, quote_aware).1 else {
+        return false;
+    };
+    let existing: Vec<String> = functions::function_segments(chain).map(compact).collect();
+    let wanted: Vec<String> = functions::function_segments(functions).map(compact).collect();
+    !wanted.is_empty() && existing.ends_with(&wanted)
+}
+
+/// `call` with the whitespace outside quoted arguments removed.
+fn compact(call: &str) -> String {
+    let mut out = String::with_capacity(call.len());
+    let mut quote = None;
+    for c in call.chars() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, c) if c.is_whitespace() => continue,
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
@@ -0,0 +1,241 @@
+//! robots.txt rules and `<meta name="robots">` directives.
+//!
+//! [`RobotsTxt`] follows RFC 9309: rules are grouped by `User-agent`, the
+//! groups naming the crawler's product token apply (else the `*` groups),
+//! and the longest matching `Allow`/`Disallow` pattern wins, `Allow` on a
+//! tie. Patterns support `*` and a trailing `$`.
+
+use regex::Regex;
+#[cfg(feature = "css")]
+use scraper::Selector;
+
+use crate::content::ContentType;
+use crate::ChadSelect;
+
+/// A parsed robots.txt file.
+///
+/// ```rust
+/// use chadselect::robots::RobotsTxt;
+///
+/// let robots = RobotsTxt::parse("User-agent: *\nDisallow: /admin\nAllow: /admin/public$\n");
+/// assert!(!robots.is_allowed("/admin/users", "MyCrawler/1.0"));
+/// assert!(robots.is_allowed("/admin/public", "MyCrawler/1.0"));
+/// assert!(robots.is_allowed("/cars", "MyCrawler/1.0"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RobotsTxt {
+    groups: Vec<Group>,
+    sitemaps: Vec<String>,
+}
+
+/// The rules following one run of `User-agent` lines.
+#[derive(Debug, Clone, Default)]
+struct Group {
+    /// Lowercased user-agent tokens (`*` for any).
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: bool,
+    /// Pattern length, the rule's precedence.
+    length: usize,
+    pattern: Regex,
+}
+
+impl RobotsTxt {
+    /// Parse robots.txt text. Unknown fields and malformed lines are ignored,
+    /// as crawlers are expected to.
+    pub fn parse(text: &str) -> Self {
+        let mut robots = RobotsTxt::default();
+        // Whether the current group has seen a rule yet; a `User-agent` line
+        // after rules starts a new group.
+        let mut in_rules = true;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match field.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules || robots.groups.is_empty() {
+                        robots.groups.push(Group::default());
+                        in_rules = false;
+                    }
+                    let group = robots.groups.last_mut().expect("group was just pushed");
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+                field @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    let Some(group) = robots.groups.last_mut() else {
+                        continue;
+                    };
+                    // An empty `Disallow:` allows everything — no rule needed.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some(pattern) = compile_pattern(value) {
+                        group.rules.push(Rule {
+                            allow: field == "allow",
+                            length: value.len(),
+                            pattern,
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules = true;
+                    if let Some(group) = robots.groups.last_mut() {
+                        group.crawl_delay = value.parse().ok();
+                    }
+                }
+                // `Sitemap:` lines stand outside the groups.
+                "sitemap" if !value.is_empty() => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+        robots
+    }
+
+    /// Whether `user_agent` may fetch `path` (the URL path plus any query,
+    /// e.g. `/search?q=civic`). `user_agent` may be a bare product token
+    /// (`Googlebot`) or a full User-Agent string (`Googlebot/2.1 (+…)`).
+    pub fn is_allowed(&self, path: &str, user_agent: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        let path = if path.is_empty() { "/" } else { path };
+
+        let mut best: Option<&Rule> = None;
+        for rule in self.groups_for(user_agent).flat_map(|group| &group.rules) {
+            if !rule.pattern.is_match(path) {
+                continue;
+            }
+            let better = match best {
+                None => true,
+                Some(best) => {
+                    rule.length > best.length || (rule.length == best.length && rule.allow)
+                }
+            };
+            if better {
+                best = Some(rule);
+            }
+        }
+        best.is_none_or(|rule| rule.allow)
+    }
+
+    /// The `Crawl-delay` (seconds) for `user_agent`, if its group sets one.
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<f64> {
+        self.groups_for(user_agent)
+            .find_map(|group| group.crawl_delay)
+    }
+
+    /// The `Sitemap:` URLs, in file order.
+    pub fn sitemaps(&self) -> &[String] {
+        &self.sitemaps
+    }
+
+    /// The groups naming `user_agent`'s product token, or else the `*`
+    /// groups.
+    fn groups_for<'a>(&'a self, user_agent: &str) -> impl Iterator<Item = &'a Group> {
+        let token = product_token(user_agent);
+        let named = self.groups.iter().any(|group| group.agents.contains(&token));
+        let wanted = if named { token } else { "*".to_string() };
+        self.groups
+            .iter()
+            .filter(move |group| group.agents.contains(&wanted))
+    }
+}
+
+/// The lowercased product token of a User-Agent string: `Googlebot/2.1
+/// (+…)` → `googlebot`.
+fn product_token(user_agent: &str) -> String {
+    user_agent
+        .trim()
+        .split(['/', ' ', ';'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Compile a robots.txt path pattern (`*` wildcard, `$` end anchor) into a
+/// regex anchored at the start of the path.
+fn compile_pattern(pattern: &str) -> Option<Regex> {
+    let (body, anchored) = match pattern.strip_suffix('$') {
+        Some(body) => (body, true),
+        None => (pattern, false),
+    };
+    let literal: Vec<String> = body.split('*').map(regex::escape).collect();
+    let end = if anchored { "$" } else { "" };
+    Regex::new(&format!("^{}{}", literal.join(".*"), end)).ok()
+}
+
+impl ChadSelect {
+    /// Parse the content item at `index` (a content index, as counted by
+    /// [`content_count`](ChadSelect::content_count)) as robots.txt. `None`
+    /// when the index is out of range or the item is not text.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_text("User-agent: *\nDisallow: /private/\n".to_string());
+    ///
+    /// let robots = cs.robots_txt(0).unwrap();
+    /// assert!(!robots.is_allowed("/private/report", "MyCrawler"));
+    /// ```
+    pub fn robots_txt(&self, index: usize) -> Option<RobotsTxt> {
+        let item = self.content_list.get(index)?;
+        (item.content_type == ContentType::Text).then(|| RobotsTxt::parse(&item.content))
+    }
+
+    /// The lowercased `<meta name="robots">` directives (`noindex`,
+    /// `nofollow`, `max-snippet:50`, …) of every HTML item, plus those of
+    /// `<meta>` tags naming `user_agent`'s product token (`googlebot`), in
+    /// document order without duplicates. Needs the `css` feature.
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<meta name="robots" content="noindex, follow">
+    ///                <meta name="googlebot" content="nosnippet">"#.to_string());
+    ///
+    /// assert_eq!(cs.meta_robots("MyCrawler"), vec!["noindex", "follow"]);
+    /// assert_eq!(cs.meta_robots("Googlebot/2.1"), vec!["noindex", "follow", "nosnippet"]);
+    /// ```
+    #[cfg(feature = "css")]
+    pub fn meta_robots(&self, user_agent: &str) -> Vec<String> {
+        let meta = Selector::parse("meta[name][content]").expect("valid selector");
+        let token = product_token(user_agent);
+        let mut directives: Vec<String> = Vec::new();
+
+        for item in &self.content_list {
+            if item.content_type != ContentType::Html {
+                continue;
+            }
+            for element in item.html().select(&meta) {
+                let name = element
+                    .value()
+                    .attr("name")
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                if name != "robots" && (token.is_empty() || name != token) {
+                    continue;
+                }
+                let content = element.value().attr("content").unwrap_or_default();
+                for directive in content.split(',') {
+                    let directive = directive.trim().to_ascii_lowercase();
+                    if !directive.is_empty() && !directives.contains(&directive) {
+                        directives.push(directive);
+                    }
+                }
+            }
+        }
+        directives
+    }
+}
@@ -0,0 +1,214 @@
+//! Rules: a query, a condition on its results, and the outcome they name —
+//! page classification (sold, available, error, captcha) as data instead
+//! of if/else around `select` calls.
+//!
+//! ```text
+//! RuleSet                                                 evaluate_rules
+//!   captcha   css:.captcha       any result          →    item 0: captcha
+//!   sold      css:.status        == "Sold"                item 1: sold
+//!   available css:.status        == "In stock"            item 2: available
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::warnings::Capture;
+use crate::ChadSelect;
+
+/// The condition of a [`rule_where`](RuleSet::rule_where).
+type Condition = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// One rule of a [`RuleSet`].
+#[derive(Clone)]
+pub(crate) struct Rule {
+    pub(crate) name: String,
+    pub(crate) query: String,
+    pub(crate) outcome: String,
+    /// The condition of a [`rule_where`](RuleSet::rule_where); `None` for a
+    /// [`rule`](RuleSet::rule), which fires on presence.
+    pub(crate) valid: Option<Condition>,
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rule")
+            .field("name", &self.name)
+            .field("query", &self.query)
+            .field("outcome", &self.outcome)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Named rules, each a query, a condition its results are checked against,
+/// and the outcome it stands for. Rules are kept in the order they were
+/// added, which is their priority: the first rule to fire on an item names
+/// its outcome.
+///
+/// A [`rule`](RuleSet::rule) fires on a content item when its query is
+/// present there, as a [`Classifier`](crate::Classifier) indicator is: it
+/// yields a result that is not empty or whitespace, or it is a `css:`
+/// selector without a `>>` chain that matches an element — so
+/// `css:.g-recaptcha` fires on an empty `<div class="g-recaptcha">`. A
+/// [`rule_where`](RuleSet::rule_where) fires when one of the results passes
+/// its condition. With `||` fallbacks, the first alternative holding a
+/// passing result counts, as for
+/// [`select_each_where`](ChadSelect::select_each_where).
+///
+/// ```rust
+/// use chadselect::RuleSet;
+///
+/// let rules = RuleSet::new()
+///     .rule("captcha", "css:.g-recaptcha, #challenge-form", "captcha")
+///     .rule_where("sold", "css:.status", "sold", |s| s.eq_ignore_ascii_case("sold"))
+///     .rule("available", "css:button.add-to-cart", "available");
+/// assert_eq!(rules.len(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pub(crate) rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// An empty rule set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add rule `name`: fires with `outcome` when `query` is present — it
+    /// has a non-empty result, or matches an element (see [`RuleSet`]). A
+    /// rule added under an existing name replaces it, keeping its place.
+    pub fn rule(self, name: &str, query: &str, outcome: &str) -> Self {
+        self.add(name, query, outcome, None)
+    }
+
+    /// Like [`rule`](RuleSet::rule), firing when a result passes `valid`.
+    pub fn rule_where<F>(self, name: &str, query: &str, outcome: &str, valid: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.add(name, query, outcome, Some(Arc::new(valid)))
+    }
+
+    fn add(
+        mut self,
+        name: &str,
+        query: &str,
+        outcome: &str,
+        valid: Option<Condition>,
+    ) -> Self {
+        let rule = Rule {
+            name: name.to_string(),
+            query: query.to_string(),
+            outcome: outcome.to_string(),
+            valid,
+        };
+        match self.rules.iter_mut().find(|r| r.name == name) {
+            Some(existing) => *existing = rule,
+            None => self.rules.push(rule),
+        }
+        self
+    }
+
+    /// Number of rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether the set has no rules.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// A rule that fired on a content item, from
+/// [`evaluate_rules`](ChadSelect::evaluate_rules).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiredRule {
+    /// The rule's name.
+    pub name: String,
+    /// The outcome the rule names.
+    pub outcome: String,
+    /// The result that passed the rule's condition — empty when a
+    /// [`rule`](RuleSet::rule) fired on an element without text.
+    pub value: String,
+}
+
+impl ChadSelect {
+    /// The rules [`evaluate_rules`](ChadSelect::evaluate_rules) runs.
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Replace the rules [`evaluate_rules`](ChadSelect::evaluate_rules)
+    /// runs (see also [`ChadSelectBuilder::rules`](crate::ChadSelectBuilder::rules)).
+    pub fn set_rules(&mut self, rules: RuleSet) {
+        self.rules = rules;
+    }
+
+    /// Run every rule against every content item. Returns one entry per
+    /// item, in content order, listing the rules that fired on it in rule
+    /// order — so an item's outcome is its first entry's, if any.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, RuleSet};
+    ///
+    /// let mut cs = ChadSelect::builder()
+    ///     .rules(
+    ///         RuleSet::new()
+    ///             .rule("captcha", "css:.captcha", "captcha")
+    ///             .rule_where("sold", "css:.status", "sold", |s| s == "Sold")
+    ///             .rule("price", "css:.price", "available"),
+    ///     )
+    ///     .build();
+    /// cs.add_html(r#"<div class="captcha">Verify you are human</div>"#.to_string());
+    /// cs.add_html(r#"<span class="status">Sold</span><span class="price">$28,500</span>"#.to_string());
+    /// cs.add_html(r#"<span class="status">In stock</span><span class="price">$31,000</span>"#.to_string());
+    ///
+    /// let fired = cs.evaluate_rules();
+    /// let outcomes: Vec<Option<&str>> = fired
+    ///     .iter()
+    ///     .map(|fired| fired.first().map(|rule| rule.outcome.as_str()))
+    ///     .collect();
+    /// assert_eq!(outcomes, [Some("captcha"), Some("sold"), Some("available")]);
+    /// ```
+    pub fn evaluate_rules(&self) -> Vec<Vec<FiredRule>> {
+        let mut fired = vec![Vec::new(); self.content_list.len()];
+        for rule in &self.rules.rules {
+            let values: Vec<Option<String>> = match &rule.valid {
+                Some(valid) => self
+                    .select_each_where(&rule.query, |value| valid(value))
+                    .into_iter()
+                    .map(|value| Some(value).filter(|value| !value.is_empty()))
+                    .collect(),
+                None => self.present_each(&rule.query),
+            };
+            for (item, value) in fired.iter_mut().zip(values) {
+                if let Some(value) = value {
+                    item.push(FiredRule {
+                        name: rule.name.clone(),
+                        outcome: rule.outcome.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+        fired
+    }
+
+    /// For each content item, the value showing `query` is present there
+    /// (see [`presence`](ChadSelect::presence)).
+    fn present_each(&self, query: &str) -> Vec<Option<String>> {
+        let Some(prepared) = self.build_lenient(query) else {
+            return vec![None; self.content_list.len()];
+        };
+        let capture = Capture::begin();
+        let present = self
+            .content_list
+            .iter()
+            .map(|item| self.presence(&prepared, item))
+            .collect();
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+        present
+    }
+}
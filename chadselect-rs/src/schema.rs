@@ -0,0 +1,126 @@
+//! Extraction schemas: named, typed columns filled by queries, one row per
+//! content item — the shape the `store` (SQLite) and `arrow` (Arrow and
+//! Parquet) outputs write and extraction snapshots capture.
+//!
+//! ```text
+//! ExtractionSchema                       rows
+//!   title   Text     css:h1         →    title   | price | miles
+//!   price   Integer  css:.price          Civic   | 28500 | 12000.5
+//!   miles   Real     css:.miles          Accord  | 31000 | (null)
+//! ```
+
+use crate::functions::parse_number;
+use crate::ChadSelect;
+
+/// The type of a schema column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// The value as extracted.
+    Text,
+    /// The first number in the value, truncated toward zero.
+    Integer,
+    /// The first number in the value.
+    Real,
+}
+
+impl ColumnType {
+    /// `text` as an integer; `None` when it has no number.
+    pub(crate) fn integer(text: &str) -> Option<i64> {
+        parse_number(text).map(|n| n.trunc() as i64)
+    }
+
+    /// `text` as a real; `None` when it has no number.
+    pub(crate) fn real(text: &str) -> Option<f64> {
+        parse_number(text)
+    }
+}
+
+/// Named, typed columns, each filled by a query.
+///
+/// Every content item becomes one row: each column takes the first valid
+/// match of its query in that item, as [`select_each`](ChadSelect::select_each)
+/// returns it. Items where every query comes up empty are skipped.
+///
+/// ```rust
+/// use chadselect::{ColumnType, ExtractionSchema};
+///
+/// let schema = ExtractionSchema::new()
+///     .column("title", ColumnType::Text, "css:h1")
+///     .column("price", ColumnType::Integer, "css:.price");
+/// assert_eq!(schema.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionSchema {
+    pub(crate) columns: Vec<Column>,
+    pub(crate) key: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Column {
+    pub(crate) name: String,
+    pub(crate) column_type: ColumnType,
+    pub(crate) query: String,
+}
+
+impl ExtractionSchema {
+    /// An empty schema.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a column named `name` of type `column_type`, filled by `query`.
+    /// A column added under an existing name replaces it.
+    pub fn column(mut self, name: &str, column_type: ColumnType, query: &str) -> Self {
+        let column = Column {
+            name: name.to_string(),
+            column_type,
+            query: query.to_string(),
+        };
+        match self.columns.iter_mut().find(|c| c.name == name) {
+            Some(existing) => *existing = column,
+            None => self.columns.push(column),
+        }
+        self
+    }
+
+    /// Identify rows by the value of column `name` when
+    /// [diffing snapshots](crate::ExtractionSnapshot::diff), instead of by
+    /// the content item's source.
+    pub fn key(mut self, name: &str) -> Self {
+        self.key = Some(name.to_string());
+        self
+    }
+
+    /// Number of columns.
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Whether the schema has no columns.
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+impl ChadSelect {
+    /// The rows of `schema`: one per content item with at least one
+    /// non-empty value, each holding the item's index and the columns' raw
+    /// values in schema order.
+    pub(crate) fn extract_rows(&self, schema: &ExtractionSchema) -> Vec<(usize, Vec<String>)> {
+        let mut columns: Vec<std::vec::IntoIter<String>> = schema
+            .columns
+            .iter()
+            .map(|column| self.select_each(&column.query).into_iter())
+            .collect();
+        (0..self.content_list.len())
+            .map(|index| {
+                let row = columns
+                    .iter_mut()
+                    .map(|values| values.next().unwrap_or_default())
+                    .collect::<Vec<_>>();
+                (index, row)
+            })
+            .filter(|(_, row)| row.iter().any(|value| !value.is_empty()))
+            .collect()
+    }
+}
@@ -0,0 +1,132 @@
+//! Near-duplicate detection: MinHash signatures over word shingles of each
+//! content item's text.
+//!
+//! ```text
+//! "used honda civic 2019 low miles"
+//!   shingles   {used honda civic} {honda civic 2019} {civic 2019 low} …
+//!   signature  [min h₁(s), min h₂(s), …, min h₁₂₈(s)]
+//! similarity   share of signature slots two items agree on ≈ Jaccard
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::content::ContentItem;
+#[cfg(feature = "css")]
+use crate::content::{visible_text, ContentType};
+use crate::ChadSelect;
+
+/// Number of hash functions in a signature; the similarity estimate's error
+/// shrinks with its square root.
+const SIGNATURE_LEN: usize = 128;
+
+/// Words per shingle.
+const SHINGLE_WORDS: usize = 3;
+
+/// SplitMix64 finalizer: a cheap, well-mixed 64-bit permutation.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// The text an item is compared on: the visible text of HTML and Markdown,
+/// the raw content of everything else (and of all content without the `css`
+/// feature).
+fn comparable_text(item: &ContentItem) -> String {
+    match item.content_type {
+        #[cfg(feature = "css")]
+        ContentType::Html | ContentType::Markdown => visible_text(&item.html(), usize::MAX),
+        _ => item.content.clone(),
+    }
+}
+
+/// The MinHash signature of `text`'s lowercase word shingles; empty when it
+/// has no words.
+fn signature(text: &str) -> Vec<u64> {
+    let lowercase = text.to_lowercase();
+    let words: Vec<&str> = lowercase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut minimums = vec![u64::MAX; SIGNATURE_LEN];
+    for shingle in words.windows(SHINGLE_WORDS.min(words.len())) {
+        let mut hasher = DefaultHasher::new();
+        shingle.hash(&mut hasher);
+        let hash = hasher.finish();
+        for (seed, minimum) in minimums.iter_mut().enumerate() {
+            *minimum = (*minimum).min(mix(hash ^ mix(seed as u64)));
+        }
+    }
+    minimums
+}
+
+/// Estimated Jaccard similarity of two signatures; 0 when either is empty.
+fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let equal = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    equal as f64 / SIGNATURE_LEN as f64
+}
+
+impl ChadSelect {
+    /// Content items whose text is near-identical to the item at `index`,
+    /// as `(index, similarity)` pairs with similarity at least `threshold`,
+    /// most similar first. The item itself is not included; an out-of-range
+    /// `index` gives an empty list.
+    ///
+    /// Similarity is the Jaccard similarity of the items' three-word
+    /// shingles, estimated with MinHash, from 0 (nothing shared) to 1. HTML
+    /// and Markdown are compared on their visible text, so pages that share
+    /// a template but show different listings are not alike. Other content
+    /// is compared on its raw text. Items that share no shingle, or have no
+    /// words, never match.
+    ///
+    /// Use it to group near-duplicate pages before extraction and process
+    /// one of each group:
+    ///
+    /// ```rust
+    /// use chadselect::ChadSelect;
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// let page = |id: &str, body: &str| {
+    ///     format!("<nav>Home | Search</nav><p>{}</p><footer>ref {}</footer>", body, id)
+    /// };
+    /// let civic = "2019 Honda Civic EX, one owner, 28,000 miles, new tires, full service history, clean title";
+    /// cs.add_html(page("a1", civic));
+    /// cs.add_html(page("a2", "2021 Toyota Prius, hybrid, 12,000 miles, factory warranty"));
+    /// cs.add_html(page("b7", civic));
+    ///
+    /// let similar = cs.find_similar(0, 0.8);
+    /// assert_eq!(similar.len(), 1);
+    /// assert_eq!(similar[0].0, 2);
+    /// ```
+    pub fn find_similar(&self, index: usize, threshold: f64) -> Vec<(usize, f64)> {
+        let Some(item) = self.content_list.get(index) else {
+            return Vec::new();
+        };
+        let target = signature(&comparable_text(item));
+
+        let mut similar: Vec<(usize, f64)> = self
+            .content_list
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(other, item)| {
+                (
+                    other,
+                    similarity(&target, &signature(&comparable_text(item))),
+                )
+            })
+            .filter(|(_, score)| *score > 0.0 && *score >= threshold)
+            .collect();
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1));
+        similar
+    }
+}
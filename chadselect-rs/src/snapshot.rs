@@ -0,0 +1,109 @@
+//! Checkpoints of a [`ChadSelect`] — its content, named queries, rules, and
+//! options.
+
+use crate::config::Config;
+use crate::content::{ContentItem, ContentType};
+use crate::library::QueryLibrary;
+use crate::rules::RuleSet;
+use crate::ChadSelect;
+
+/// A copy of everything loaded into a [`ChadSelect`], without any parsed
+/// caches.
+///
+/// Unlike `ChadSelect` (whose caches are `Rc`-based), a `Snapshot` is `Send`
+/// and `Sync`: take one after loading, hand it to worker threads, and rebuild
+/// an instance on each with [`ChadSelect::from_snapshot`]. It also serves as a
+/// checkpoint between pipeline stages via [`ChadSelect::restore`].
+///
+/// ```rust
+/// use chadselect::ChadSelect;
+///
+/// let mut cs = ChadSelect::new();
+/// cs.add_html("<h1>Civic</h1>".to_string());
+/// let snapshot = cs.snapshot();
+///
+/// let worker = std::thread::spawn(move || {
+///     let cs = ChadSelect::from_snapshot(&snapshot);
+///     cs.select(0, "css:h1")
+/// });
+/// assert_eq!(worker.join().unwrap(), "Civic");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    items: Vec<SavedItem>,
+    config: Config,
+    library: QueryLibrary,
+    rules: RuleSet,
+}
+
+/// One captured content item.
+#[derive(Debug, Clone)]
+struct SavedItem {
+    content: String,
+    content_type: ContentType,
+    source: Option<String>,
+    charset: Option<String>,
+    fragment: bool,
+    page: Option<usize>,
+    sheet: Option<String>,
+}
+
+impl Snapshot {
+    /// Number of content items captured.
+    pub fn content_count(&self) -> usize {
+        self.items.len()
+    }
+}
+
+impl ChadSelect {
+    /// Capture the loaded content, named queries, rules, and options.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            items: self
+                .content_list
+                .iter()
+                .map(|item| SavedItem {
+                    content: item.content.clone(),
+                    content_type: item.content_type.clone(),
+                    source: item.source.clone(),
+                    charset: item.charset.clone(),
+                    fragment: item.fragment,
+                    page: item.page,
+                    sheet: item.sheet.clone(),
+                })
+                .collect(),
+            config: self.config.clone(),
+            library: self.library.clone(),
+            rules: self.rules.clone(),
+        }
+    }
+
+    /// Replace this instance's content, named queries, rules, and options
+    /// with the snapshot's. Caches are rebuilt lazily.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.content_list = snapshot
+            .items
+            .iter()
+            .map(|saved| {
+                let mut item = ContentItem::new(saved.content.clone(), saved.content_type.clone());
+                item.source = saved.source.clone();
+                item.charset = saved.charset.clone();
+                item.fragment = saved.fragment;
+                item.page = saved.page;
+                item.sheet = saved.sheet.clone();
+                item.hoist_templates = snapshot.config.hoist_templates;
+                item
+            })
+            .collect();
+        self.config = snapshot.config.clone();
+        self.library = snapshot.library.clone();
+        self.rules = snapshot.rules.clone();
+    }
+
+    /// Build a new instance from a snapshot.
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        let mut cs = ChadSelect::new();
+        cs.restore(snapshot);
+        cs
+    }
+}
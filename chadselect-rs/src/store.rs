@@ -0,0 +1,145 @@
+//! Extract-and-store: append an [`ExtractionSchema`]'s rows to a SQLite
+//! table.
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+use crate::schema::{ColumnType, ExtractionSchema};
+use crate::ChadSelect;
+
+/// The `rusqlite` version `store` is built against, so callers can open a
+/// [`Connection`] without adding a matching dependency of their own.
+pub use rusqlite;
+
+impl ColumnType {
+    fn sql(self) -> &'static str {
+        match self {
+            ColumnType::Text => "TEXT",
+            ColumnType::Integer => "INTEGER",
+            ColumnType::Real => "REAL",
+        }
+    }
+
+    /// The SQL value for `text`; `NULL` when it is empty or has no number.
+    fn sql_value(self, text: String) -> Value {
+        if text.is_empty() {
+            return Value::Null;
+        }
+        let value = match self {
+            ColumnType::Text => Some(Value::Text(text)),
+            ColumnType::Integer => ColumnType::integer(&text).map(Value::Integer),
+            ColumnType::Real => ColumnType::real(&text).map(Value::Real),
+        };
+        value.unwrap_or(Value::Null)
+    }
+}
+
+/// `name` as a quoted SQL identifier.
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+impl ChadSelect {
+    /// Extract `schema` from every content item and append the rows to
+    /// `table`, creating it (with the schema's column types) when it does
+    /// not exist. Returns the number of rows written.
+    ///
+    /// Values are converted to the column's type; an empty value, or an
+    /// `INTEGER`/`REAL` one with no number in it, is stored as `NULL`.
+    /// All rows are written in one transaction.
+    ///
+    /// Errors when the schema is empty, when an existing `table` lacks one
+    /// of the schema's columns, or when SQLite fails. Queries that fail to
+    /// parse leave their column `NULL` and record a warning.
+    ///
+    /// ```rust
+    /// use chadselect::store::rusqlite::Connection;
+    /// use chadselect::{ChadSelect, ColumnType, ExtractionSchema};
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.add_html(r#"<h1>Civic</h1><span class="price">$28,500</span>"#.to_string());
+    /// cs.add_html(r#"<h1>Accord</h1>"#.to_string());
+    ///
+    /// let schema = ExtractionSchema::new()
+    ///     .column("title", ColumnType::Text, "css:h1")
+    ///     .column("price", ColumnType::Integer, "css:.price");
+    ///
+    /// let conn = Connection::open_in_memory().unwrap();
+    /// assert_eq!(cs.store(&conn, "cars", &schema), Ok(2));
+    ///
+    /// let price: Option<i64> = conn
+    ///     .query_row("SELECT price FROM cars WHERE title = 'Civic'", [], |row| row.get(0))
+    ///     .unwrap();
+    /// assert_eq!(price, Some(28500));
+    /// ```
+    pub fn store(
+        &self,
+        conn: &Connection,
+        table: &str,
+        schema: &ExtractionSchema,
+    ) -> Result<usize, String> {
+        if schema.is_empty() {
+            return Err("extraction schema has no columns".to_string());
+        }
+
+        let rows = self.extract_rows(schema);
+
+        let sql_error = |e: rusqlite::Error| format!("sqlite: {}", e);
+        let tx = conn.unchecked_transaction().map_err(sql_error)?;
+        ensure_table(&tx, table, schema)?;
+
+        let names: Vec<String> = schema.columns.iter().map(|c| quote(&c.name)).collect();
+        let placeholders = vec!["?"; names.len()].join(", ");
+        let insert = format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            quote(table),
+            names.join(", "),
+            placeholders
+        );
+        let mut statement = tx.prepare(&insert).map_err(sql_error)?;
+
+        for (_, row) in &rows {
+            let values = schema
+                .columns
+                .iter()
+                .zip(row)
+                .map(|(column, text)| column.column_type.sql_value(text.clone()));
+            statement
+                .execute(rusqlite::params_from_iter(values))
+                .map_err(sql_error)?;
+        }
+        drop(statement);
+        tx.commit().map_err(sql_error)?;
+        Ok(rows.len())
+    }
+}
+
+/// Create `table` for `schema`, or check that the existing one has every
+/// schema column.
+fn ensure_table(conn: &Connection, table: &str, schema: &ExtractionSchema) -> Result<(), String> {
+    let sql_error = |e: rusqlite::Error| format!("sqlite: {}", e);
+    let mut info = conn
+        .prepare(&format!("PRAGMA table_info({})", quote(table)))
+        .map_err(sql_error)?;
+    let existing: Vec<String> = info
+        .query_map([], |row| row.get(1))
+        .map_err(sql_error)?
+        .collect::<Result<_, _>>()
+        .map_err(sql_error)?;
+
+    if existing.is_empty() {
+        let definitions: Vec<String> = schema
+            .columns
+            .iter()
+            .map(|c| format!("{} {}", quote(&c.name), c.column_type.sql()))
+            .collect();
+        let create = format!("CREATE TABLE {} ({})", quote(table), definitions.join(", "));
+        conn.execute(&create, []).map_err(sql_error)?;
+        return Ok(());
+    }
+
+    match schema.columns.iter().find(|c| !existing.contains(&c.name)) {
+        Some(missing) => Err(format!("table {} has no column {}", table, missing.name)),
+        None => Ok(()),
+    }
+}
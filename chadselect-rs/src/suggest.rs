@@ -0,0 +1,141 @@
+//! Candidate queries for a known value — see
+//! [`ChadSelect::suggest_selectors`](crate::ChadSelect::suggest_selectors).
+
+use scraper::{ElementRef, Html};
+
+use crate::engine::css::{css_identifier, css_path, css_step, match_text};
+use crate::engine::NON_CONTENT_TAGS;
+
+/// Longest sibling text still treated as a label (`Price:`, `Mileage`).
+const MAX_LABEL_CHARS: usize = 40;
+
+/// Candidate CSS and XPath queries that may select `target` in `html`, most
+/// specific first. Candidates are unverified; the caller runs them.
+///
+/// Text matches come from the innermost elements whose text contains
+/// `target`; each yields queries by id, by class chain, by a neighbouring
+/// label, and by full path. Attribute values equal to `target` yield
+/// `get-attr` queries.
+pub(crate) fn candidates(html: &Html, target: &str) -> Vec<String> {
+    let mut candidates = Vec::new();
+
+    for element in html
+        .tree
+        .root()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+    {
+        let mut scope = element.ancestors().filter_map(ElementRef::wrap);
+        if is_non_content(element) || scope.any(is_non_content) {
+            continue;
+        }
+        for (name, value) in element.value().attrs() {
+            if value.trim() == target && name != "class" && name != "id" {
+                attribute_candidates(&element, name, &mut candidates);
+            }
+        }
+        let innermost = match_text(&element).contains(target)
+            && !element
+                .children()
+                .filter_map(ElementRef::wrap)
+                .any(|child| match_text(&child).contains(target));
+        if innermost {
+            text_candidates(&element, &mut candidates);
+        }
+    }
+
+    candidates
+}
+
+fn is_non_content(element: ElementRef) -> bool {
+    NON_CONTENT_TAGS.contains(&element.value().name())
+}
+
+/// Queries selecting the text of `element`.
+fn text_candidates(element: &ElementRef, out: &mut Vec<String>) {
+    let value = element.value();
+    let tag = value.name();
+    let step = css_step(element);
+    let parent = element.parent().and_then(ElementRef::wrap);
+
+    if let Some(id) = value.id() {
+        out.push(format!("css:#{}", css_identifier(id)));
+        if let Some(id) = xpath_literal(id) {
+            out.push(format!("xpath://{tag}[@id={id}]"));
+        }
+    }
+    if step != tag {
+        if let Some(parent) = parent.filter(|parent| css_step(parent) != parent.value().name()) {
+            out.push(format!("css:{} > {}", css_step(&parent), step));
+        }
+        out.push(format!("css:{step}"));
+        if let Some(class) = value.attr("class").and_then(xpath_literal) {
+            out.push(format!("xpath://{tag}[@class={class}]"));
+        }
+    }
+
+    // A short preceding sibling is usually the field's label.
+    let label = element
+        .prev_siblings()
+        .filter_map(ElementRef::wrap)
+        .next()
+        .map(|sibling| (sibling, match_text(&sibling)))
+        .filter(|(_, text)| !text.is_empty() && text.chars().count() <= MAX_LABEL_CHARS);
+    if let (Some((label_element, label)), Some(parent)) = (label, parent) {
+        out.push(format!(
+            "css:{}:has-text('{}') {}",
+            parent.value().name(),
+            css_string(&label),
+            tag
+        ));
+        if let Some(literal) = xpath_literal(&label) {
+            out.push(format!(
+                "xpath://{}[normalize-space()={}]/following-sibling::{}[1]",
+                label_element.value().name(),
+                literal,
+                tag
+            ));
+        }
+    }
+
+    out.push(format!("css:{}", css_path(element)));
+}
+
+/// Queries reading attribute `name` of `element`.
+fn attribute_candidates(element: &ElementRef, name: &str, out: &mut Vec<String>) {
+    let tag = element.value().name();
+    let step = css_step(element);
+    if step != tag {
+        out.push(format!(
+            "css:{step}[{name}] >> get-attr('{}')",
+            css_string(name)
+        ));
+    }
+    out.push(format!(
+        "css:{tag}[{name}] >> get-attr('{}')",
+        css_string(name)
+    ));
+    out.push(format!("xpath://{tag}/@{name}"));
+    out.push(format!(
+        "css:{} >> get-attr('{}')",
+        css_path(element),
+        css_string(name)
+    ));
+}
+
+/// `text` escaped for a single-quoted function or pseudo-selector argument.
+fn css_string(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// `text` as an XPath string literal, or `None` when it contains both quote
+/// characters (XPath 1.0 literals have no escapes).
+fn xpath_literal(text: &str) -> Option<String> {
+    if !text.contains('\'') {
+        Some(format!("'{text}'"))
+    } else if !text.contains('"') {
+        Some(format!("\"{text}\""))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,87 @@
+//! Templates interleaving literal text with query results — see
+//! [`ChadSelect::render`](crate::ChadSelect::render).
+
+
+use crate::functions;
+use crate::warnings::warning;
+
+/// One piece of a parsed template.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    /// Text copied to the output as-is.
+    Literal(String),
+    /// A `{query}` placeholder.
+    Query(&'a str),
+}
+
+/// Split `template` into literal text and `{query}` placeholders.
+///
+/// `{{` and `}}` are literal braces. A placeholder ends at the `}` that
+/// balances its opening brace, skipping quoted strings — so a regex
+/// quantifier (`{regex:\d{17}}`) or a quoted `}` stays inside the query. An
+/// unterminated placeholder is kept as literal text with a warning.
+pub(crate) fn parse(template: &str) -> Vec<Segment<'_>> {
+    let bytes = template.as_bytes();
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'}' if bytes.get(i + 1) == Some(&bytes[i]) => {
+                literal.push(bytes[i] as char);
+                i += 2;
+            }
+            b'{' => match placeholder_end(bytes, i) {
+                Some(end) => {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Query(template[i + 1..end].trim()));
+                    i = end + 1;
+                }
+                None => {
+                    warning!(InvalidQuery, "Unterminated placeholder in template '{}'", template);
+                    literal.push_str(&template[i..]);
+                    break;
+                }
+            },
+            _ => {
+                // Copy up to the next brace in one go (keeps UTF-8 intact); a
+                // lone `}` is literal.
+                let next = template[i..]
+                    .find(['{', '}'])
+                    .map_or(bytes.len(), |offset| i + offset.max(1));
+                literal.push_str(&template[i..next]);
+                i = next;
+            }
+        }
+    }
+
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    segments
+}
+
+/// Byte index of the `}` closing the placeholder opened at `open`.
+fn placeholder_end(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            // An unbalanced quote (an apostrophe in a regex) is a plain character.
+            b'\'' | b'"' => i = functions::closing_quote(bytes, i).unwrap_or(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
@@ -0,0 +1,824 @@
+//! Selector translation between CSS and XPath.
+//!
+//! [`css_to_xpath`] covers the CSS the `css:` engine accepts that has an
+//! XPath 1.0 equivalent: type, id, class, and attribute selectors, the four
+//! combinators, the structural pseudo-classes, `:not()`/`:is()`/`:where()`
+//! over compound selectors, `:has()`, and the text pseudo-selectors.
+//! [`xpath_to_css`] is best-effort: it handles location paths of the shapes
+//! `css_to_xpath` produces and the common hand-written ones, and refuses the
+//! rest.
+//!
+//! Element text can differ in whitespace between the engines — CSS joins an
+//! element's text nodes with a space, XPath concatenates them — and the text
+//! pseudo-selectors become whitespace-normalised comparisons.
+//!
+//! Built with the `css` and `xpath` features.
+
+use crate::engine;
+use crate::engine::css::{closing_paren, pseudo_argument, split_top_level};
+use crate::functions::{self, TextFunction};
+use crate::query::{QueryType, FUNCTION_PIPE};
+
+pub use crate::config::Translation;
+
+/// Translate a CSS selector (without a `>>` function chain) to an XPath 1.0
+/// location path selecting the same elements.
+///
+/// ```rust
+/// use chadselect::translate::css_to_xpath;
+///
+/// assert_eq!(css_to_xpath("ul > li#top").unwrap(), "//ul/li[@id='top']");
+/// assert_eq!(
+///     css_to_xpath("a[href^='/'], b:first-child").unwrap(),
+///     "//a[starts-with(@href, '/')] | //b[not(preceding-sibling::*)]"
+/// );
+/// assert!(css_to_xpath("p::first-line").is_err());
+/// ```
+pub fn css_to_xpath(selector: &str) -> Result<String, String> {
+    let paths = split_top_level(selector, |c| c == ',')
+        .into_iter()
+        .map(|complex| complex_to_xpath(complex, false))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("cannot translate CSS '{}' to XPath: {}", selector, e))?;
+    if paths.is_empty() {
+        return Err("cannot translate an empty CSS selector".to_string());
+    }
+    Ok(paths.join(" | "))
+}
+
+/// Translate an XPath location path selecting elements to an equivalent CSS
+/// selector, where one exists.
+///
+/// Handles absolute paths (`//`, `/`) of element steps on the child,
+/// descendant, `following-sibling`, and `self` axes, joined by `|`, with
+/// predicates testing attributes (`@a`, `@a='v'`, `contains`,
+/// `starts-with`, `not(@a)`, the class-token idiom), a leading position
+/// (`[2]`, `[last()]`), or text (`contains(., 'v')`,
+/// `normalize-space() = 'v'`, `starts-with(., 'v')`).
+///
+/// ```rust
+/// use chadselect::translate::xpath_to_css;
+///
+/// assert_eq!(xpath_to_css("//ul/li[2]").unwrap(), "ul > li:nth-of-type(2)");
+/// assert_eq!(
+///     xpath_to_css("//div[@id='main']//a[contains(@href, 'pdf')]").unwrap(),
+///     "div#main a[href*=\"pdf\"]"
+/// );
+/// assert!(xpath_to_css("//a/..").is_err());
+/// ```
+pub fn xpath_to_css(xpath: &str) -> Result<String, String> {
+    match xpath_query_parts(xpath)? {
+        (css, None) => Ok(css),
+        (_, Some(_)) => Err(format!(
+            "XPath '{}' selects attributes, not elements",
+            xpath
+        )),
+    }
+}
+
+/// Reroute a parsed query to the other engine per `translation`. A query
+/// that does not translate (or whose translation its new engine rejects)
+/// is returned unchanged.
+pub(crate) fn rewrite(query_type: QueryType, translation: Translation) -> QueryType {
+    let translated = match (&query_type, translation) {
+        (QueryType::CssSelector(query), Translation::CssToXPath) => {
+            css_query_to_xpath(query).map(QueryType::XPath)
+        }
+        (QueryType::XPath(query), Translation::XPathToCss) => {
+            xpath_query_to_css(query).map(QueryType::CssSelector)
+        }
+        _ => None,
+    };
+    translated.unwrap_or(query_type)
+}
+
+/// A `css:` query body as an `xpath:` one. A leading `get-attr` becomes an
+/// attribute step; any other `get-attr`/`get-attrs`, or a navigation step
+/// (`closest`, …), needs the matched element, so the query stays CSS.
+fn css_query_to_xpath(query: &str) -> Option<String> {
+    let (selector, chain) = functions::split_chain(query, true);
+    let mut segments: Vec<&str> = chain
+        .map(|chain| functions::function_segments(chain).collect())
+        .unwrap_or_default();
+    let mut xpath = css_to_xpath(&selector).ok()?;
+
+    let leading_attribute = segments
+        .first()
+        .and_then(|segment| functions::try_parse_text_functions(segment).ok())
+        .and_then(|parsed| match parsed.as_slice() {
+            [TextFunction::GetAttribute { attribute }] => Some(attribute.clone()),
+            _ => None,
+        });
+    if let Some(attribute) = leading_attribute {
+        if !is_ident(&attribute) {
+            return None;
+        }
+        xpath = if xpath.contains(" | ") {
+            format!("({})/@{}", xpath, attribute)
+        } else {
+            format!("{}/@{}", xpath, attribute)
+        };
+        segments.remove(0);
+    }
+    let reads_element = segments.iter().any(|segment| {
+        functions::try_parse_text_functions(segment).is_ok_and(|parsed| {
+            parsed.iter().any(|function| {
+                matches!(
+                    function,
+                    TextFunction::GetAttribute { .. }
+                        | TextFunction::GetAttributes { .. }
+                        | TextFunction::Navigate { .. }
+                )
+            })
+        })
+    });
+    if reads_element || engine::xpath::compile(&xpath).is_none() {
+        return None;
+    }
+    Some(with_chain(xpath, &segments))
+}
+
+/// An `xpath:` query body as a `css:` one; a final attribute step becomes a
+/// leading `get-attr`.
+fn xpath_query_to_css(query: &str) -> Option<String> {
+    let (xpath, chain) = functions::split_chain(query, true);
+    let mut segments: Vec<&str> = chain
+        .map(|chain| functions::function_segments(chain).collect())
+        .unwrap_or_default();
+    let (css, attribute) = xpath_query_parts(&xpath).ok()?;
+    engine::css::compile(&css)?;
+
+    let get_attr = attribute.map(|attribute| format!("get-attr('{}')", attribute));
+    if let Some(get_attr) = &get_attr {
+        segments.insert(0, get_attr.as_str());
+    }
+    Some(with_chain(css, &segments))
+}
+
+fn with_chain(expression: String, segments: &[&str]) -> String {
+    segments.iter().fold(expression, |query, segment| {
+        format!("{} {} {}", query, FUNCTION_PIPE, segment)
+    })
+}
+
+// ─── CSS → XPath ────────────────────────────────────────────────────────────
+
+/// One complex selector as a location path. With `relative` (a `:has()`
+/// argument) it may start with a combinator and is relative to the subject.
+fn complex_to_xpath(complex: &str, relative: bool) -> Result<String, String> {
+    let mut path = String::new();
+    let mut combinator = None;
+    let mut steps = 0;
+    for token in split_top_level(complex, |c| {
+        c.is_whitespace() || matches!(c, '>' | '+' | '~')
+    }) {
+        match token {
+            ">" | "+" | "~" => {
+                if combinator.is_some() || (steps == 0 && !relative) {
+                    return Err(format!("misplaced combinator in '{}'", complex));
+                }
+                combinator = token.chars().next();
+            }
+            _ => {
+                let (name, predicates) = compound_to_xpath(token)?;
+                let axis = match (steps, combinator.unwrap_or(' ')) {
+                    (0, ' ') if relative => "descendant::",
+                    (0, '>') => "",
+                    (0, ' ') => "//",
+                    (_, ' ') => "//",
+                    (_, '>') => "/",
+                    (0, '+') => "following-sibling::*[1]/self::",
+                    (0, _) => "following-sibling::",
+                    (_, '+') => "/following-sibling::*[1]/self::",
+                    _ => "/following-sibling::",
+                };
+                path.push_str(axis);
+                path.push_str(&name);
+                path.push_str(&predicates);
+                combinator = None;
+                steps += 1;
+            }
+        }
+    }
+    if combinator.is_some() || steps == 0 {
+        return Err(format!("incomplete selector '{}'", complex));
+    }
+    Ok(path)
+}
+
+/// A compound selector as an element name test and its predicates.
+fn compound_to_xpath(compound: &str) -> Result<(String, String), String> {
+    let bytes = compound.as_bytes();
+    let mut name = "*".to_string();
+    let mut tests: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    if bytes.first() == Some(&b'*') {
+        i = 1;
+    } else if bytes.first().is_some_and(|b| b.is_ascii_alphabetic()) {
+        let end = ident_end(compound, 0);
+        name = compound[..end].to_ascii_lowercase();
+        i = end;
+    }
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' | b'.' => {
+                let end = ident_end(compound, i + 1);
+                let value = &compound[i + 1..end];
+                if value.is_empty() {
+                    return Err(format!("expected a name after '{}'", bytes[i] as char));
+                }
+                tests.push(if bytes[i] == b'#' {
+                    format!("@id={}", literal(value))
+                } else {
+                    class_test(value)
+                });
+                i = end;
+            }
+            b'[' => {
+                let end = closing_bracket(bytes, i)
+                    .ok_or_else(|| format!("unclosed '[' in '{}'", compound))?;
+                tests.push(attribute_test(&compound[i + 1..end])?);
+                i = end + 1;
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => {
+                return Err(format!("pseudo-element in '{}'", compound));
+            }
+            b':' => {
+                let end = ident_end(compound, i + 1);
+                let pseudo = &compound[i + 1..end];
+                let argument = if bytes.get(end) == Some(&b'(') {
+                    let close = closing_paren(compound, end)
+                        .ok_or_else(|| format!("unclosed ':{}(' in '{}'", pseudo, compound))?;
+                    i = close + 1;
+                    Some(compound[end + 1..close].trim())
+                } else {
+                    i = end;
+                    None
+                };
+                tests.push(pseudo_test(pseudo, argument, &name)?);
+            }
+            _ => return Err(format!("unsupported syntax in '{}'", compound)),
+        }
+    }
+
+    let predicates = tests.iter().map(|test| format!("[{}]", test)).collect();
+    Ok((name, predicates))
+}
+
+/// The predicate for a pseudo-class of an element named `name`.
+fn pseudo_test(pseudo: &str, argument: Option<&str>, name: &str) -> Result<String, String> {
+    let of_type = || {
+        if name == "*" {
+            Err(format!(":{} needs an element name", pseudo))
+        } else {
+            Ok(name)
+        }
+    };
+    Ok(match (pseudo, argument) {
+        ("first-child", None) => "not(preceding-sibling::*)".to_string(),
+        ("last-child", None) => "not(following-sibling::*)".to_string(),
+        ("only-child", None) => {
+            "not(preceding-sibling::*) and not(following-sibling::*)".to_string()
+        }
+        ("first-of-type", None) => format!("not(preceding-sibling::{})", of_type()?),
+        ("last-of-type", None) => format!("not(following-sibling::{})", of_type()?),
+        ("only-of-type", None) => {
+            let name = of_type()?;
+            format!(
+                "not(preceding-sibling::{0}) and not(following-sibling::{0})",
+                name
+            )
+        }
+        ("nth-child", Some(an_b)) => nth_test("preceding-sibling::*", an_b)?,
+        ("nth-last-child", Some(an_b)) => nth_test("following-sibling::*", an_b)?,
+        ("nth-of-type", Some(an_b)) => {
+            nth_test(&format!("preceding-sibling::{}", of_type()?), an_b)?
+        }
+        ("nth-last-of-type", Some(an_b)) => {
+            nth_test(&format!("following-sibling::{}", of_type()?), an_b)?
+        }
+        ("empty", None) => "not(*) and not(text())".to_string(),
+        ("root", None) => "not(parent::*)".to_string(),
+        ("is" | "where", Some(list)) => self_test(list)?,
+        ("not", Some(list)) => format!("not({})", self_test(list)?),
+        ("has", Some(list)) => split_top_level(list, |c| c == ',')
+            .into_iter()
+            .map(|relative| complex_to_xpath(relative, true))
+            .collect::<Result<Vec<_>, _>>()?
+            .join(" or "),
+        ("has-text", Some(text)) => format!("contains(., {})", literal(&pseudo_argument(text))),
+        ("contains-text", Some(text)) => {
+            format!("text()[contains(., {})]", literal(&pseudo_argument(text)))
+        }
+        ("text-equals", Some(text)) => {
+            format!("normalize-space() = {}", literal(&pseudo_argument(text)))
+        }
+        ("text-starts", Some(text)) => {
+            format!(
+                "starts-with(normalize-space(), {})",
+                literal(&pseudo_argument(text))
+            )
+        }
+        ("text-ends", Some(text)) => {
+            let text = literal(&pseudo_argument(text));
+            format!(
+                "substring(normalize-space(), string-length(normalize-space()) - \
+                 string-length({0}) + 1) = {0}",
+                text
+            )
+        }
+        _ => return Err(format!("unsupported pseudo-class ':{}'", pseudo)),
+    })
+}
+
+/// A predicate testing the context element against a list of compound
+/// selectors, for `:is()`, `:where()`, and `:not()`.
+fn self_test(list: &str) -> Result<String, String> {
+    let tests = split_top_level(list, |c| c == ',')
+        .into_iter()
+        .map(|compound| {
+            if split_top_level(compound, |c| {
+                c.is_whitespace() || matches!(c, '>' | '+' | '~')
+            })
+            .len()
+                > 1
+            {
+                return Err(format!("'{}' is not a compound selector", compound));
+            }
+            let (name, predicates) = compound_to_xpath(compound)?;
+            Ok(format!("self::{}{}", name, predicates))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if tests.is_empty() {
+        return Err("empty selector list".to_string());
+    }
+    Ok(tests.join(" or "))
+}
+
+/// The `an+b` predicate, counting the siblings on `axis` before the element.
+fn nth_test(axis: &str, an_b: &str) -> Result<String, String> {
+    let (a, b) = parse_nth(an_b).ok_or_else(|| format!("unsupported an+b '{}'", an_b))?;
+    let position = format!("count({}) + 1", axis);
+    Ok(match a {
+        0 => format!("count({}) = {}", axis, b - 1),
+        a if a > 0 => format!("{0} >= {1} and ({0} - {1}) mod {2} = 0", position, b, a),
+        a => format!("{0} <= {1} and ({1} - ({0})) mod {2} = 0", position, b, -a),
+    })
+}
+
+/// Parse an `an+b` argument (`odd`, `even`, `3`, `2n+1`, `-n+3`).
+fn parse_nth(an_b: &str) -> Option<(i64, i64)> {
+    let compact: String = an_b.chars().filter(|c| !c.is_whitespace()).collect();
+    match compact.to_ascii_lowercase().as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+    let Some((a, b)) = compact.split_once(['n', 'N']) else {
+        return Some((0, compact.parse().ok()?));
+    };
+    let a = match a {
+        "" | "+" => 1,
+        "-" => -1,
+        a => a.parse().ok()?,
+    };
+    let b = match b {
+        "" => 0,
+        b if b.starts_with(['+', '-']) => b.trim_start_matches('+').parse().ok()?,
+        _ => return None,
+    };
+    Some((a, b))
+}
+
+/// The predicate for an attribute selector's bracket contents.
+fn attribute_test(inner: &str) -> Result<String, String> {
+    let inner = inner.trim();
+    let name_end = ident_end(inner, 0);
+    let name = &inner[..name_end];
+    if name.is_empty() {
+        return Err(format!("unsupported attribute selector '[{}]'", inner));
+    }
+    let rest = inner[name_end..].trim_start();
+    if rest.is_empty() {
+        return Ok(format!("@{}", name));
+    }
+    let (operator, value) = match rest.find('=') {
+        Some(eq) if eq <= 1 => (&rest[..eq], rest[eq + 1..].trim()),
+        _ => return Err(format!("unsupported attribute selector '[{}]'", inner)),
+    };
+    let value = match value.as_bytes().first() {
+        Some(b'\'' | b'"') => {
+            let close = functions::closing_quote(value.as_bytes(), 0)
+                .filter(|&close| close == value.len() - 1)
+                .ok_or_else(|| format!("unsupported attribute value in '[{}]'", inner))?;
+            functions::unescape_argument(&value[1..close])
+        }
+        _ if ident_end(value, 0) == value.len() && !value.is_empty() => value.to_string(),
+        _ => return Err(format!("unsupported attribute value in '[{}]'", inner)),
+    };
+    let attribute = format!("@{}", name);
+    let lit = literal(&value);
+    Ok(match operator {
+        "" => format!("{} = {}", attribute, lit),
+        "~" => format!(
+            "contains(concat(' ', normalize-space({}), ' '), {})",
+            attribute,
+            literal(&format!(" {} ", value))
+        ),
+        "^" => format!("starts-with({}, {})", attribute, lit),
+        "$" => format!(
+            "substring({0}, string-length({0}) - string-length({1}) + 1) = {1}",
+            attribute, lit
+        ),
+        "*" => format!("contains({}, {})", attribute, lit),
+        "|" => format!(
+            "{0} = {1} or starts-with({0}, {2})",
+            attribute,
+            lit,
+            literal(&format!("{}-", value))
+        ),
+        _ => return Err(format!("unsupported attribute operator '{}='", operator)),
+    })
+}
+
+fn class_test(class: &str) -> String {
+    format!(
+        "contains(concat(' ', normalize-space(@class), ' '), {})",
+        literal(&format!(" {} ", class))
+    )
+}
+
+/// `text` as an XPath string literal (XPath 1.0 has no escapes, so text
+/// holding both quote kinds becomes a `concat`).
+fn literal(text: &str) -> String {
+    if !text.contains('\'') {
+        format!("'{}'", text)
+    } else if !text.contains('"') {
+        format!("\"{}\"", text)
+    } else {
+        let parts: Vec<String> = text.split('\'').map(|part| format!("'{}'", part)).collect();
+        format!("concat({})", parts.join(", \"'\", "))
+    }
+}
+
+/// The end of the CSS identifier starting at `start`.
+fn ident_end(input: &str, start: usize) -> usize {
+    input[start..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .map_or(input.len(), |offset| start + offset)
+}
+
+fn is_ident(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_alphabetic() || c == '_') && ident_end(text, 0) == text.len()
+}
+
+/// The index of the `]` closing the `[` at `open`, skipping quoted text.
+fn closing_bracket(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut i = open + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' => i = functions::closing_quote(bytes, i)?,
+            b']' => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// ─── XPath → CSS ────────────────────────────────────────────────────────────
+
+/// A compound selector being built from XPath steps.
+struct Compound {
+    name: String,
+    rest: String,
+}
+
+/// Translate an XPath union to CSS, with the attribute every branch ends
+/// on, if any.
+fn xpath_query_parts(xpath: &str) -> Result<(String, Option<String>), String> {
+    let unsupported =
+        |reason: String| format!("cannot translate XPath '{}' to CSS: {}", xpath, reason);
+    let mut selectors = Vec::new();
+    let mut attribute = None;
+    let paths = split_top_level(xpath, |c| c == '|')
+        .into_iter()
+        .filter(|path| *path != "|");
+    for (n, path) in paths.enumerate() {
+        let (css, attr) = path_to_css(path).map_err(unsupported)?;
+        if n > 0 && attr != attribute {
+            return Err(unsupported("branches select different nodes".to_string()));
+        }
+        selectors.push(css);
+        attribute = attr;
+    }
+    if selectors.is_empty() {
+        return Err(unsupported("empty expression".to_string()));
+    }
+    Ok((selectors.join(", "), attribute))
+}
+
+fn path_to_css(path: &str) -> Result<(String, Option<String>), String> {
+    let path = path.trim();
+    let path = path.strip_prefix('.').unwrap_or(path);
+    if !path.starts_with('/') {
+        return Err(format!("'{}' is not an absolute path", path));
+    }
+
+    let mut css = String::new();
+    let mut current: Option<Compound> = None;
+    let mut attribute = None;
+    let mut slashes = 0;
+    for token in split_top_level(path, |c| c == '/') {
+        if token == "/" {
+            slashes += 1;
+            continue;
+        }
+        if attribute.is_some() {
+            return Err("steps after an attribute".to_string());
+        }
+        if let Some(name) = token.strip_prefix('@') {
+            if slashes != 1 || current.is_none() || !is_ident(name) {
+                return Err(format!("unsupported attribute step '{}'", token));
+            }
+            attribute = Some(name.to_string());
+            slashes = 0;
+            continue;
+        }
+
+        let (axis, test) = match token.split_once("::") {
+            Some((axis, test)) if !axis.contains('[') => (axis.trim(), test.trim()),
+            _ => ("child", token),
+        };
+        let combinator = match (axis, slashes) {
+            ("child", 1) if current.is_none() => "",
+            ("child", 1) => " > ",
+            ("child", 2) | ("descendant", 1) => " ",
+            ("following-sibling", 1) => " ~ ",
+            ("self", 1) => "self",
+            _ => return Err(format!("unsupported step '{}'", token)),
+        };
+        slashes = 0;
+
+        let (name, predicates) = step_parts(test)?;
+        let mut compound = Compound {
+            name,
+            rest: String::new(),
+        };
+        let mut combinator = combinator;
+        for (n, predicate) in predicates.iter().enumerate() {
+            let positional = match predicate.as_str() {
+                "last()" => Some(None),
+                p => p
+                    .strip_prefix("position()=")
+                    .unwrap_or(p)
+                    .parse::<u32>()
+                    .ok()
+                    .map(Some),
+            };
+            match positional {
+                Some(_) if n > 0 => return Err("position after another predicate".to_string()),
+                Some(Some(1)) if combinator == " ~ " && compound.name == "*" => combinator = " + ",
+                Some(_) if combinator == " ~ " || axis != "child" => {
+                    return Err(format!("unsupported position on axis '{}'", axis))
+                }
+                Some(position) => {
+                    let kind = if compound.name == "*" {
+                        "child"
+                    } else {
+                        "of-type"
+                    };
+                    compound.rest.push_str(&match position {
+                        Some(position) => format!(":nth-{}({})", kind, position),
+                        None => format!(":last-{}", kind),
+                    });
+                }
+                None => compound
+                    .rest
+                    .push_str(&predicate_to_css(predicate, &compound.name)?),
+            }
+        }
+
+        match (combinator, current.take()) {
+            ("self", Some(mut previous)) => {
+                if previous.name == "*" {
+                    previous.name = compound.name;
+                } else if compound.name != "*" && compound.name != previous.name {
+                    return Err(format!("'{}' cannot match the previous step", token));
+                }
+                previous.rest.push_str(&compound.rest);
+                current = Some(previous);
+            }
+            ("self", None) => return Err("'self::' as the first step".to_string()),
+            (combinator, Some(previous)) => {
+                css.push_str(&compound_css(previous));
+                css.push_str(combinator);
+                current = Some(compound);
+            }
+            ("", None) => {
+                compound.rest.push_str(":root");
+                current = Some(compound);
+            }
+            (" ", None) => current = Some(compound),
+            (_, None) => return Err(format!("unsupported first step '{}'", token)),
+        }
+    }
+    if slashes > 0 {
+        return Err("trailing '/'".to_string());
+    }
+    let last = current.ok_or_else(|| "no element steps".to_string())?;
+    css.push_str(&compound_css(last));
+    Ok((css, attribute))
+}
+
+fn compound_css(compound: Compound) -> String {
+    if compound.name == "*" && !compound.rest.is_empty() {
+        compound.rest
+    } else {
+        compound.name + &compound.rest
+    }
+}
+
+/// Split a step's node test from its predicates, each returned with the
+/// whitespace outside string literals removed.
+fn step_parts(step: &str) -> Result<(String, Vec<String>), String> {
+    let bytes = step.as_bytes();
+    let test_end = step.find('[').unwrap_or(step.len());
+    let name = step[..test_end].trim();
+    if name != "*" && !is_ident(name) {
+        return Err(format!("unsupported node test '{}'", name));
+    }
+
+    let mut predicates = Vec::new();
+    let mut i = test_end;
+    while i < bytes.len() {
+        if bytes[i] != b'[' {
+            return Err(format!("unexpected text in step '{}'", step));
+        }
+        let close =
+            closing_bracket(bytes, i).ok_or_else(|| format!("unclosed '[' in '{}'", step))?;
+        predicates.extend(split_and(&step[i + 1..close]));
+        i = close + 1;
+    }
+    Ok((name.to_ascii_lowercase(), predicates))
+}
+
+/// The `and`-joined conditions of a predicate, with whitespace outside
+/// string literals removed.
+fn split_and(predicate: &str) -> Vec<String> {
+    let mut conditions = vec![String::new()];
+    for token in split_top_level(predicate, char::is_whitespace) {
+        if token == "and" {
+            conditions.push(String::new());
+        } else if let Some(last) = conditions.last_mut() {
+            last.push_str(token);
+        }
+    }
+    conditions
+        .iter()
+        .map(|condition| strip_spaces(condition))
+        .collect()
+}
+
+fn strip_spaces(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut quote = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, c) if c.is_whitespace() => continue,
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// One whitespace-free predicate condition of a step named `name` as CSS.
+fn predicate_to_css(predicate: &str, name: &str) -> Result<String, String> {
+    const TEXT: [&str; 5] = [
+        ".",
+        "string()",
+        "string(.)",
+        "normalize-space()",
+        "normalize-space(.)",
+    ];
+    let unsupported = || format!("unsupported predicate '{}'", predicate);
+
+    if let Some(name) = predicate.strip_prefix('@').filter(|name| is_ident(name)) {
+        return Ok(format!("[{}]", name));
+    }
+    // The sibling tests `css_to_xpath` writes for the structural
+    // pseudo-classes.
+    let kind = |siblings: &str| match siblings {
+        "*" => Some("child"),
+        siblings if siblings == name => Some("of-type"),
+        _ => None,
+    };
+    for (axis, end) in [
+        ("preceding-sibling::", "first"),
+        ("following-sibling::", "last"),
+    ] {
+        let siblings = predicate
+            .strip_prefix("not(")
+            .and_then(|rest| rest.strip_prefix(axis))
+            .and_then(|rest| rest.strip_suffix(')'));
+        if let Some(kind) = siblings.and_then(kind) {
+            return Ok(format!(":{}-{}", end, kind));
+        }
+        let counted = predicate
+            .strip_prefix("count(")
+            .and_then(|rest| rest.strip_prefix(axis))
+            .and_then(|rest| rest.split_once(")="));
+        if let Some((siblings, count)) = counted {
+            let (kind, count) = kind(siblings)
+                .zip(count.parse::<u32>().ok())
+                .ok_or_else(unsupported)?;
+            let last = if end == "last" { "last-" } else { "" };
+            return Ok(format!(":nth-{}{}({})", last, kind, count + 1));
+        }
+    }
+    if let Some(name) = predicate
+        .strip_prefix("not(@")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .filter(|name| is_ident(name))
+    {
+        return Ok(format!(":not([{}])", name));
+    }
+    if let Some(class) = call_args(predicate, "contains").and_then(|args| match args.as_slice() {
+        [concat, token] if *concat == "concat(' ',normalize-space(@class),' ')" => {
+            string_literal(token)
+        }
+        _ => None,
+    }) {
+        let class = class
+            .strip_prefix(' ')
+            .and_then(|class| class.strip_suffix(' '))
+            .ok_or_else(unsupported)?;
+        return Ok(if is_ident(class) {
+            format!(".{}", class)
+        } else {
+            format!("[class~={}]", css_string(class))
+        });
+    }
+    for (function, operator) in [("contains", "*="), ("starts-with", "^=")] {
+        if let Some([subject, value]) = call_args(predicate, function).as_deref() {
+            let value = string_literal(value).ok_or_else(unsupported)?;
+            return match subject.strip_prefix('@') {
+                Some(name) if is_ident(name) => {
+                    Ok(format!("[{}{}{}]", name, operator, css_string(&value)))
+                }
+                _ if TEXT.contains(subject) && function == "contains" => {
+                    Ok(format!(":has-text({})", pseudo_string(&value)))
+                }
+                _ if *subject == "text()" && function == "contains" => {
+                    Ok(format!(":contains-text({})", pseudo_string(&value)))
+                }
+                _ if TEXT.contains(subject) => {
+                    Ok(format!(":text-starts({})", pseudo_string(&value)))
+                }
+                _ => Err(unsupported()),
+            };
+        }
+    }
+    if let Some((subject, value)) = predicate.split_once('=') {
+        let value = string_literal(value).ok_or_else(unsupported)?;
+        return match subject.strip_prefix('@') {
+            Some("id") if is_ident(&value) => Ok(format!("#{}", value)),
+            Some(name) if is_ident(name) => Ok(format!("[{}={}]", name, css_string(&value))),
+            _ if TEXT.contains(&subject) || subject == "text()" => {
+                Ok(format!(":text-equals({})", pseudo_string(&value)))
+            }
+            _ => Err(unsupported()),
+        };
+    }
+    Err(unsupported())
+}
+
+/// The top-level arguments of `function(…)` when `text` is exactly that call.
+fn call_args<'a>(text: &'a str, function: &str) -> Option<Vec<&'a str>> {
+    let open = function.len();
+    let inner = text.strip_prefix(function)?.strip_prefix('(')?;
+    (closing_paren(text, open)? == text.len() - 1)
+        .then(|| split_top_level(&inner[..inner.len() - 1], |c| c == ','))
+}
+
+/// The value of an XPath string literal.
+fn string_literal(text: &str) -> Option<String> {
+    let quote = text.chars().next().filter(|c| matches!(c, '\'' | '"'))?;
+    let body = text.strip_prefix(quote)?.strip_suffix(quote)?;
+    (!body.contains(quote)).then(|| body.to_string())
+}
+
+/// `text` as a double-quoted CSS string.
+fn css_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `text` as a quoted text pseudo-selector argument.
+fn pseudo_string(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -0,0 +1,343 @@
+//! WARC archives (the `warc` feature): the response records of a crawl, such
+//! as a Common Crawl segment, loaded as content items.
+
+use std::borrow::Cow;
+use std::io::{self, BufRead, Read};
+
+use glob::Pattern;
+
+use crate::config::LimitPolicy;
+use crate::content::{is_binary_mime, ContentType};
+use crate::warnings::{warning, Capture};
+use crate::ChadSelect;
+
+/// Which records [`add_warc_with`](ChadSelect::add_warc_with) loads.
+///
+/// The default loads every successful response with a textual body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarcFilter {
+    /// Keep only bodies whose MIME type (without parameters, compared
+    /// case-insensitively) is one of these, e.g. `text/html`. Empty keeps
+    /// every textual type.
+    pub mime_types: Vec<String>,
+    /// Keep only records whose target URL matches one of these glob patterns
+    /// (`https://example.com/cars/*`; `*` also matches `/`). Empty keeps
+    /// every URL.
+    pub url_patterns: Vec<String>,
+    /// Stop reading after this many items have been added.
+    pub max_items: Option<usize>,
+}
+
+impl ChadSelect {
+    /// Add the response bodies of a WARC archive, with the default
+    /// [`WarcFilter`]. See [`add_warc_with`](ChadSelect::add_warc_with).
+    pub fn add_warc(&mut self, reader: impl BufRead) -> Result<usize, String> {
+        self.add_warc_with(reader, &WarcFilter::default())
+    }
+
+    /// Add the bodies of the WARC `response` and `resource` records read
+    /// from `reader` that `filter` keeps, one content item per record, in
+    /// archive order. Returns the number of items added.
+    ///
+    /// Each item is typed by its MIME type as `add_url` would type it, and
+    /// keeps the record's `WARC-Target-URI` as its
+    /// [`content_source`](ChadSelect::content_source). Only 2xx responses
+    /// are loaded; chunked bodies are reassembled and bodies are decoded as
+    /// UTF-8. Empty bodies, binary types, and bodies with a
+    /// `Content-Encoding` (which are not decompressed) are skipped, the
+    /// latter with a warning.
+    ///
+    /// `reader` must yield uncompressed WARC. Common Crawl segments
+    /// (`.warc.gz`) are a series of gzip members: wrap the file in a
+    /// multi-member gzip decoder such as `flate2::read::MultiGzDecoder`.
+    ///
+    /// Errors on an I/O failure, a malformed or truncated record, or an
+    /// invalid URL pattern; items added before the error stay loaded. Bodies
+    /// rejected by the size limits are skipped with a warning. A record
+    /// whose content block (HTTP headers included) is larger than
+    /// [`max_item_bytes`](crate::ChadSelectBuilder::max_item_bytes) is not
+    /// read into memory: it is skipped, or under
+    /// [`LimitPolicy::Truncate`] cut to its first `max_item_bytes` bytes.
+    ///
+    /// ```rust
+    /// use chadselect::{ChadSelect, WarcFilter};
+    ///
+    /// let http = "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\n<h1>Civic</h1>";
+    /// let warc = format!(
+    ///     "WARC/1.0\r\nWARC-Type: response\r\n\
+    ///      WARC-Target-URI: https://example.com/cars/42\r\n\
+    ///      Content-Type: application/http; msgtype=response\r\n\
+    ///      Content-Length: {}\r\n\r\n{}\r\n\r\n",
+    ///     http.len(),
+    ///     http
+    /// );
+    ///
+    /// let filter = WarcFilter {
+    ///     url_patterns: vec!["https://example.com/cars/*".to_string()],
+    ///     ..WarcFilter::default()
+    /// };
+    /// let mut cs = ChadSelect::new();
+    /// assert_eq!(cs.add_warc_with(warc.as_bytes(), &filter), Ok(1));
+    /// assert_eq!(cs.select(0, "css:h1"), "Civic");
+    /// assert_eq!(cs.content_source(0), Some("https://example.com/cars/42"));
+    /// ```
+    pub fn add_warc_with(
+        &mut self,
+        mut reader: impl BufRead,
+        filter: &WarcFilter,
+    ) -> Result<usize, String> {
+        let patterns = filter
+            .url_patterns
+            .iter()
+            .map(|p| Pattern::new(p).map_err(|e| format!("invalid URL pattern '{}': {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let capture = Capture::begin();
+        let mut added = 0;
+        let outcome = loop {
+            if filter.max_items.is_some_and(|max| added >= max) {
+                break Ok(());
+            }
+            let record = match read_record(&mut reader, self.config.max_item_bytes) {
+                Ok(Some(record)) => record,
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            };
+            let Some(url) = record.header("warc-target-uri") else {
+                continue;
+            };
+            let url = url.trim_start_matches('<').trim_end_matches('>');
+            if !patterns.is_empty() && !patterns.iter().any(|p| p.matches(url)) {
+                continue;
+            }
+            if record.is_cut() && self.config.limit_policy != LimitPolicy::Truncate {
+                warning!(
+                    ContentRejected,
+                    "Record for {} not added: record of {} bytes exceeds the {}-byte item limit",
+                    url,
+                    record.length,
+                    record.block.len()
+                );
+                continue;
+            }
+            let Some((mime, body)) = record.payload(url) else {
+                continue;
+            };
+            let mime = mime.as_deref();
+            if body.is_empty()
+                || mime.is_some_and(is_binary_mime)
+                || !kept_mime(mime, &filter.mime_types)
+            {
+                continue;
+            }
+
+            let content_type = ContentType::from_mime(mime, &body);
+            let body = String::from_utf8_lossy(&body).into_owned();
+            match self.try_add(body, content_type) {
+                Ok(()) => {
+                    added += 1;
+                    self.content_list
+                        .last_mut()
+                        .expect("item was just added")
+                        .source = Some(url.to_string());
+                }
+                Err(e) => warning!(ContentRejected, "Record for {} not added: {}", url, e),
+            }
+        };
+        self.keep_warnings(capture);
+        outcome.map(|()| added)
+    }
+}
+
+/// One WARC record: its named fields (names lower-cased) and content block.
+struct Record {
+    headers: Headers,
+    /// The content block, or its first bytes when it exceeds the item limit.
+    block: Vec<u8>,
+    /// The declared length of the whole block.
+    length: u64,
+}
+
+impl Record {
+    /// Whether the block was cut to the item limit.
+    fn is_cut(&self) -> bool {
+        (self.block.len() as u64) < self.length
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+
+    /// The MIME type and body of a `response` or `resource` record, when it
+    /// carries a loadable one.
+    fn payload(&self, url: &str) -> Option<(Option<String>, Cow<'_, [u8]>)> {
+        match self.header("warc-type")? {
+            "resource" => Some((
+                self.header("content-type").map(str::to_string),
+                Cow::Borrowed(&self.block),
+            )),
+            "response" => {
+                let (status, headers, body) = split_http_response(&self.block)?;
+                if !(200..300).contains(&status) {
+                    return None;
+                }
+                if header(&headers, "content-encoding")
+                    .is_some_and(|encoding| !encoding.eq_ignore_ascii_case("identity"))
+                {
+                    warning!(
+                        InvalidContent,
+                        "Response for {} skipped: its body is content-encoded",
+                        url
+                    );
+                    return None;
+                }
+                let body = match header(&headers, "transfer-encoding") {
+                    Some(encoding) if encoding.eq_ignore_ascii_case("chunked") => {
+                        Cow::Owned(dechunk(body))
+                    }
+                    _ => Cow::Borrowed(body),
+                };
+                Some((header(&headers, "content-type").map(str::to_string), body))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn header<'h>(headers: &'h [(String, String)], name: &str) -> Option<&'h str> {
+    headers
+        .iter()
+        .find(|(header, _)| header == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Read the next record, or `None` at the end of the input. At most
+/// `max_block` bytes of the content block are kept; the rest is read past.
+fn read_record(
+    reader: &mut impl BufRead,
+    max_block: Option<usize>,
+) -> Result<Option<Record>, String> {
+    let mut line = String::new();
+    // Records are separated by blank lines.
+    loop {
+        line.clear();
+        if read_line(reader, &mut line)? == 0 {
+            return Ok(None);
+        }
+        if !line.trim().is_empty() {
+            break;
+        }
+    }
+    if !line.starts_with("WARC/") {
+        return Err(format!(
+            "malformed WARC record: expected a version line, found '{}'",
+            line.trim()
+        ));
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if read_line(reader, &mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length: u64 = header(&headers, "content-length")
+        .and_then(|length| length.parse().ok())
+        .ok_or("malformed WARC record: missing or invalid Content-Length")?;
+    let kept = max_block.map_or(length, |max| length.min(max as u64));
+    let truncated = || format!("truncated WARC record: expected {} bytes", length);
+
+    // The declared length is untrusted: memory grows only as bytes arrive.
+    let mut block = Vec::new();
+    reader
+        .take(kept)
+        .read_to_end(&mut block)
+        .map_err(|e| format!("cannot read WARC input: {}", e))?;
+    if (block.len() as u64) < kept {
+        return Err(truncated());
+    }
+    let skipped = io::copy(&mut reader.take(length - kept), &mut io::sink())
+        .map_err(|e| format!("cannot read WARC input: {}", e))?;
+    if skipped < length - kept {
+        return Err(truncated());
+    }
+    Ok(Some(Record {
+        headers,
+        block,
+        length,
+    }))
+}
+
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<usize, String> {
+    let mut bytes = Vec::new();
+    let read = reader
+        .read_until(b'\n', &mut bytes)
+        .map_err(|e| format!("cannot read WARC input: {}", e))?;
+    line.push_str(&String::from_utf8_lossy(&bytes));
+    Ok(read)
+}
+
+/// Header fields with lower-cased names.
+type Headers = Vec<(String, String)>;
+
+/// Split an HTTP response message into its status code, headers (names
+/// lower-cased), and body.
+fn split_http_response(message: &[u8]) -> Option<(u16, Headers, &[u8])> {
+    let (head_end, body_start) = match find(message, b"\r\n\r\n") {
+        Some(i) => (i, i + 4),
+        None => find(message, b"\n\n").map(|i| (i, i + 2))?,
+    };
+    let head = String::from_utf8_lossy(&message[..head_end]);
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let headers: Headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    Some((status, headers, &message[body_start..]))
+}
+
+/// Reassemble a `Transfer-Encoding: chunked` body. A malformed chunk
+/// ends the body there.
+fn dechunk(mut body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len());
+    while let Some(line_end) = find(body, b"\n") {
+        let size_line = String::from_utf8_lossy(&body[..line_end]);
+        let size_field = size_line.split(';').next().unwrap_or("").trim();
+        let Ok(size) = usize::from_str_radix(size_field, 16) else {
+            break;
+        };
+        let start = line_end + 1;
+        if size == 0 || start + size > body.len() {
+            out.extend_from_slice(&body[start..(start + size).min(body.len())]);
+            break;
+        }
+        out.extend_from_slice(&body[start..start + size]);
+        body = &body[start + size..];
+        body = body
+            .strip_prefix(b"\r\n")
+            .or_else(|| body.strip_prefix(b"\n"))
+            .unwrap_or(body);
+    }
+    out
+}
+
+/// Whether the filter's MIME types keep a body of type `mime`.
+fn kept_mime(mime: Option<&str>, kept: &[String]) -> bool {
+    if kept.is_empty() {
+        return true;
+    }
+    let essence = mime.and_then(|m| m.split(';').next()).unwrap_or("").trim();
+    kept.iter().any(|m| m.trim().eq_ignore_ascii_case(essence))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,243 @@
+//! Watch mode: queries registered with [`ChadSelect::watch`] run against
+//! content as it arrives, and their callbacks receive the new matches.
+//!
+//! ```text
+//! watch("regex:ERROR (.+)")    add_text("… ERROR disk full …")    → callback(["disk full"])
+//!                              append_text(0, "… ERROR retry …")  → callback(["retry"])
+//! ```
+
+use crate::content::{ContentItem, ContentType};
+use crate::prepared::{Alternative, PreparedQuery};
+use crate::warnings::Capture;
+use crate::{default_valid, ChadSelect};
+
+/// Identifies a watch registered with [`ChadSelect::watch`], for
+/// [`unwatch`](ChadSelect::unwatch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WatchId(u64);
+
+/// New matches of a watched query, passed to its callback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchEvent {
+    /// The watch that matched.
+    pub id: WatchId,
+    /// The content index of the item that was added or appended to.
+    pub content_index: usize,
+    /// The new matches, in document order; never empty.
+    pub results: Vec<String>,
+}
+
+struct Watch {
+    id: WatchId,
+    prepared: PreparedQuery,
+    callback: Box<dyn FnMut(&WatchEvent)>,
+}
+
+/// How a content item changed, for [`ChadSelect::notify_watches`].
+pub(crate) enum Change<'a> {
+    /// The item was added.
+    Added,
+    /// Text was appended at byte `from`; `baseline` is the
+    /// [`watch_baseline`](ChadSelect::watch_baseline) taken before.
+    Text {
+        from: usize,
+        baseline: Vec<Option<usize>>,
+    },
+    /// `line` was appended to the JSON array as its last element.
+    Element(&'a str),
+}
+
+/// The registered watches of a [`ChadSelect`]. Callbacks cannot be cloned,
+/// so a clone starts with none.
+#[derive(Default)]
+pub(crate) struct Watches {
+    next_id: u64,
+    entries: Vec<Watch>,
+}
+
+impl Clone for Watches {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl ChadSelect {
+    /// Run `query` against every item added or appended to from now on,
+    /// calling `callback` with the matches each change brings — an alert on
+    /// every new `ERROR` line of a followed log, say.
+    ///
+    /// An added item delivers all of its matches. An append delivers only
+    /// what it brought, without rerunning the query over the whole item:
+    ///
+    /// - after [`append_text`](ChadSelect::append_text), a `regex:` query
+    ///   (and `lines:`, `glob:`, `pat:`) searches from the start of the line
+    ///   the append continued — from the top, if the pattern can match a
+    ///   newline — keeping the matches that end in the new text: those the
+    ///   query over the whole item gains. A match the append lengthened is
+    ///   delivered again, whole;
+    /// - after [`append_jsonl`](ChadSelect::append_jsonl), the query runs
+    ///   over a one-element array holding just the new line, so
+    ///   `json:[?level=='error'].msg` reports the new record's message.
+    ///
+    /// Other engines need the whole document: after `append_text` they rerun
+    /// over the grown item and deliver the results past the count it had
+    /// before, which suits queries whose results grow at the end.
+    ///
+    /// Content already loaded is not run. With `||` fallbacks, each item
+    /// uses the first alternative whose results are all non-empty, as
+    /// [`select_each`](ChadSelect::select_each) does.
+    ///
+    /// Errors when `query` does not parse. Clones of this instance start
+    /// without watches.
+    ///
+    /// ```rust
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use chadselect::ChadSelect;
+    ///
+    /// let alerts = Rc::new(RefCell::new(Vec::new()));
+    /// let sink = Rc::clone(&alerts);
+    ///
+    /// let mut cs = ChadSelect::new();
+    /// cs.watch("regex:ERROR (.+)", move |event| {
+    ///     sink.borrow_mut().extend(event.results.clone());
+    /// })
+    /// .unwrap();
+    ///
+    /// cs.add_text("09:00 INFO started\n09:01 ERROR disk full\n".to_string());
+    /// cs.append_text(0, "09:02 ERROR retrying\n").unwrap();
+    /// assert_eq!(*alerts.borrow(), ["disk full", "retrying"]);
+    /// ```
+    pub fn watch(
+        &mut self,
+        query: &str,
+        callback: impl FnMut(&WatchEvent) + 'static,
+    ) -> Result<WatchId, String> {
+        let prepared = self.prepare(query)?;
+        let watches = &mut self.watches;
+        let id = WatchId(watches.next_id);
+        watches.next_id += 1;
+        watches.entries.push(Watch {
+            id,
+            prepared,
+            callback: Box::new(callback),
+        });
+        Ok(id)
+    }
+
+    /// Remove the watch `id`. Returns whether it was registered.
+    pub fn unwatch(&mut self, id: WatchId) -> bool {
+        let entries = &mut self.watches.entries;
+        let before = entries.len();
+        entries.retain(|watch| watch.id != id);
+        entries.len() != before
+    }
+
+    /// For each watch, how many results it has for item `index` before the
+    /// item is appended to — or `None` for a watch that
+    /// [follows appends](crate::prepared::Alternative::follows_appends) and
+    /// so needs no count. Empty when nothing is watched.
+    pub(crate) fn watch_baseline(&self, index: usize) -> Vec<Option<usize>> {
+        let Some(content_item) = self.content_list.get(index) else {
+            return vec![];
+        };
+        self.watches
+            .entries
+            .iter()
+            .map(|watch| {
+                let alternatives = watch.prepared.alternatives();
+                if alternatives.iter().all(Alternative::follows_appends) {
+                    None
+                } else {
+                    Some(self.watched_results(&watch.prepared, content_item).len())
+                }
+            })
+            .collect()
+    }
+
+    /// Call each watch back with the new results `change` brought to item
+    /// `index`.
+    pub(crate) fn notify_watches(&mut self, index: usize, change: Change<'_>) {
+        if self.watches.entries.is_empty() {
+            return;
+        }
+        let Some(content_item) = self.content_list.get(index) else {
+            return;
+        };
+        let capture = Capture::begin();
+        let element = match change {
+            Change::Element(line) => Some(ContentItem::new(
+                format!("[{}]", line.trim()),
+                ContentType::Json,
+            )),
+            _ => None,
+        };
+        // Callbacks need `&mut` while the queries borrow `self`.
+        let mut watches = std::mem::take(&mut self.watches);
+        for (i, watch) in watches.entries.iter_mut().enumerate() {
+            let prepared = &watch.prepared;
+            let results = match &change {
+                Change::Added => self.watched_results(prepared, content_item),
+                Change::Text { from, baseline } => match baseline.get(i).copied().flatten() {
+                    Some(skip) => {
+                        let mut results = self.watched_results(prepared, content_item);
+                        results.drain(..skip.min(results.len()));
+                        results
+                    }
+                    None => self.appended_results(prepared, content_item, *from),
+                },
+                Change::Element(_) => element
+                    .as_ref()
+                    .map_or_else(Vec::new, |element| self.watched_results(prepared, element)),
+            };
+            if !results.is_empty() {
+                (watch.callback)(&WatchEvent {
+                    id: watch.id,
+                    content_index: index,
+                    results,
+                });
+            }
+        }
+        self.watches = watches;
+        self.keep_warnings(capture);
+        self.enforce_cache_budget();
+    }
+
+    /// The results of `prepared` on `content_item` alone.
+    fn watched_results(&self, prepared: &PreparedQuery, content_item: &ContentItem) -> Vec<String> {
+        first_accepted(prepared, |alternative| {
+            alternative.run(content_item, &self.config)
+        })
+    }
+
+    /// The results of `prepared` on the text appended to `content_item` at
+    /// byte `from`.
+    fn appended_results(
+        &self,
+        prepared: &PreparedQuery,
+        content_item: &ContentItem,
+        from: usize,
+    ) -> Vec<String> {
+        first_accepted(prepared, |alternative| {
+            alternative.run_appended(content_item, &self.config, from)
+        })
+    }
+}
+
+/// The results of the first alternative of `prepared` that `run` gives
+/// an accepted answer: the only one, or all non-empty.
+fn first_accepted(
+    prepared: &PreparedQuery,
+    run: impl Fn(&Alternative) -> Vec<String>,
+) -> Vec<String> {
+    let alternatives = prepared.alternatives();
+    for alternative in alternatives {
+        let results = run(alternative);
+        if alternatives.len() == 1
+            || (!results.is_empty() && results.iter().all(|r| default_valid(r)))
+        {
+            return results;
+        }
+    }
+    vec![]
+}
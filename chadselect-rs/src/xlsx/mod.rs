@@ -0,0 +1,403 @@
+//! Spreadsheets (the `xlsx` feature): the sheets of an XLSX or ODS
+//! workbook, such as a dealer price list, loaded as one JSON content item
+//! per sheet.
+
+mod zip;
+
+use roxmltree::{Document, Node};
+use serde_json::{Map, Value};
+
+use crate::content::ContentType;
+use crate::warnings::{warning, Capture};
+use crate::ChadSelect;
+
+use zip::Archive;
+
+/// Excel's sheet size limits; cell references beyond them are ignored.
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+
+impl ChadSelect {
+    /// Add the sheets of an XLSX (Excel) or ODS (OpenDocument) workbook,
+    /// one JSON content item per sheet, in workbook order. Returns the
+    /// number of items added. Each item's
+    /// [`content_sheet`](ChadSelect::content_sheet) is the sheet's name.
+    ///
+    /// A sheet becomes
+    /// `{"sheet": "Prices", "rows": [["SKU", "Price"], ["A-1", 12.5]],
+    /// "records": [{"SKU": "A-1", "Price": 12.5}]}`: `rows` holds every
+    /// row as an array of cells (`null` for blanks, trailing blanks
+    /// dropped), and `records` each row after the first as an object keyed
+    /// by the first row's values (a column's letter when its header is
+    /// blank). Numbers and booleans keep their type; formulas give their
+    /// cached result. Dates are Excel serial numbers in XLSX and ISO 8601
+    /// strings in ODS, as stored.
+    ///
+    /// Errors when `bytes` is not an XLSX or ODS workbook. Sheets that
+    /// cannot be read, or that the size limits reject, are skipped with a
+    /// warning.
+    pub fn add_xlsx(&mut self, bytes: &[u8]) -> Result<usize, String> {
+        let archive = Archive::parse(bytes)?;
+        let sheets = if archive.contains("xl/workbook.xml") {
+            xlsx_sheets(&archive)?
+        } else if archive.contains("content.xml") {
+            ods_sheets(&archive)?
+        } else {
+            return Err("not an XLSX or ODS workbook".to_string());
+        };
+
+        let capture = Capture::begin();
+        let mut added = 0;
+        for sheet in sheets {
+            let (name, rows) = match sheet {
+                Ok(sheet) => sheet,
+                Err(e) => {
+                    warning!(InvalidContent, "Sheet skipped: {}", e);
+                    continue;
+                }
+            };
+            let json = sheet_json(&name, rows);
+            match self.try_add(json.to_string(), ContentType::Json) {
+                Ok(()) => {
+                    added += 1;
+                    self.content_list
+                        .last_mut()
+                        .expect("item was just added")
+                        .sheet = Some(name);
+                }
+                Err(e) => warning!(ContentRejected, "Sheet '{}' not added: {}", name, e),
+            }
+        }
+        self.keep_warnings(capture);
+        Ok(added)
+    }
+}
+
+/// A sheet's name and rows, or why it could not be read.
+type Sheet = Result<(String, Vec<Vec<Value>>), String>;
+
+fn sheet_json(name: &str, mut rows: Vec<Vec<Value>>) -> Value {
+    for row in &mut rows {
+        while row.last().is_some_and(Value::is_null) {
+            row.pop();
+        }
+    }
+    while rows.last().is_some_and(Vec::is_empty) {
+        rows.pop();
+    }
+
+    let header: Vec<String> = rows.first().map_or_else(Vec::new, |first| {
+        first
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| match cell {
+                Value::Null => column_name(i),
+                Value::String(text) if text.trim().is_empty() => column_name(i),
+                Value::String(text) => text.trim().to_string(),
+                other => other.to_string(),
+            })
+            .collect()
+    });
+    let records: Vec<Value> = rows
+        .iter()
+        .skip(1)
+        .filter(|row| !row.is_empty())
+        .map(|row| {
+            let record: Map<String, Value> = row
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| !cell.is_null())
+                .map(|(i, cell)| {
+                    let key = header.get(i).cloned().unwrap_or_else(|| column_name(i));
+                    (key, cell.clone())
+                })
+                .collect();
+            Value::Object(record)
+        })
+        .collect();
+
+    let mut sheet = Map::new();
+    sheet.insert("sheet".to_string(), Value::String(name.to_string()));
+    sheet.insert(
+        "rows".to_string(),
+        Value::Array(rows.into_iter().map(Value::Array).collect()),
+    );
+    sheet.insert("records".to_string(), Value::Array(records));
+    Value::Object(sheet)
+}
+
+/// The spreadsheet name of the 0-based column `index`: `A`, …, `Z`, `AA`.
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("ASCII letters")
+}
+
+/// The 0-based column of a cell reference such as `AB12`.
+fn column_index(reference: &str) -> Option<usize> {
+    let letters = reference.bytes().take_while(u8::is_ascii_alphabetic);
+    let mut index = 0usize;
+    let mut any = false;
+    for letter in letters {
+        index = index * 26 + (letter.to_ascii_uppercase() - b'A') as usize + 1;
+        if index > MAX_COLUMNS {
+            return None;
+        }
+        any = true;
+    }
+    any.then(|| index - 1)
+}
+
+/// A number cell's JSON value: an integer when it is whole.
+fn number(text: &str) -> Value {
+    match text.trim().parse::<f64>() {
+        Ok(n) if n.fract() == 0.0 && n.abs() < 1e15 => Value::from(n as i64),
+        Ok(n) => serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number),
+        Err(_) => Value::String(text.to_string()),
+    }
+}
+
+fn member_text(archive: &Archive, name: &str) -> Result<Option<String>, String> {
+    match archive.read(name) {
+        None => Ok(None),
+        Some(bytes) => String::from_utf8(bytes?)
+            .map(Some)
+            .map_err(|_| format!("{} is not UTF-8", name)),
+    }
+}
+
+fn parse_xml<'a>(name: &str, text: &'a str) -> Result<Document<'a>, String> {
+    Document::parse(text).map_err(|e| format!("invalid XML in {}: {}", name, e))
+}
+
+/// An attribute by local name, whatever its namespace.
+fn attribute<'a>(node: &Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+fn is(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+/// The text of the `<t>` elements under `node`, skipping phonetic runs.
+fn run_text(node: &Node) -> String {
+    node.descendants()
+        .filter(|n| is(n, "t") && !n.ancestors().any(|a| is(&a, "rPh")))
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+// ─── XLSX ───────────────────────────────────────────────────────────────────
+
+fn xlsx_sheets(archive: &Archive) -> Result<Vec<Sheet>, String> {
+    let workbook = member_text(archive, "xl/workbook.xml")?.unwrap_or_default();
+    let workbook = parse_xml("xl/workbook.xml", &workbook)?;
+    let rels = member_text(archive, "xl/_rels/workbook.xml.rels")?.unwrap_or_default();
+    let rels = parse_xml("xl/_rels/workbook.xml.rels", &rels)?;
+    let targets: Vec<(&str, &str)> = rels
+        .descendants()
+        .filter(|n| is(n, "Relationship"))
+        .filter_map(|n| Some((attribute(&n, "Id")?, attribute(&n, "Target")?)))
+        .collect();
+
+    let shared = match member_text(archive, "xl/sharedStrings.xml")? {
+        Some(text) => {
+            let doc = parse_xml("xl/sharedStrings.xml", &text)?;
+            doc.root_element()
+                .children()
+                .filter(|n| is(n, "si"))
+                .map(|n| run_text(&n))
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    Ok(workbook
+        .descendants()
+        .filter(|n| is(n, "sheet"))
+        .map(|sheet| {
+            let name = attribute(&sheet, "name").unwrap_or_default().to_string();
+            let target = attribute(&sheet, "id")
+                .and_then(|id| targets.iter().find(|(rel, _)| *rel == id))
+                .map(|(_, target)| match target.strip_prefix('/') {
+                    Some(absolute) => absolute.to_string(),
+                    None => format!("xl/{}", target),
+                })
+                .ok_or_else(|| format!("sheet '{}' has no part", name))?;
+            let xml = member_text(archive, &target)?
+                .ok_or_else(|| format!("sheet '{}': {} is missing", name, target))?;
+            let rows = xlsx_rows(&parse_xml(&target, &xml)?, &shared);
+            Ok((name, rows))
+        })
+        .collect())
+}
+
+fn xlsx_rows(sheet: &Document, shared: &[String]) -> Vec<Vec<Value>> {
+    let mut rows: Vec<Vec<Value>> = Vec::new();
+    for row in sheet.descendants().filter(|n| is(n, "row")) {
+        let index = attribute(&row, "r")
+            .and_then(|r| r.parse::<usize>().ok())
+            .map_or(rows.len(), |r| r.saturating_sub(1));
+        if index >= MAX_ROWS {
+            continue;
+        }
+        if rows.len() <= index {
+            rows.resize(index + 1, Vec::new());
+        }
+        let cells = &mut rows[index];
+        for cell in row.children().filter(|n| is(n, "c")) {
+            let column = match attribute(&cell, "r") {
+                Some(reference) => match column_index(reference) {
+                    Some(column) => column,
+                    None => continue,
+                },
+                None => cells.len(),
+            };
+            let raw = cell.children().find(|n| is(n, "v")).and_then(|v| v.text());
+            let value = match (attribute(&cell, "t"), raw) {
+                (Some("inlineStr"), _) => cell
+                    .children()
+                    .find(|n| is(n, "is"))
+                    .map_or(Value::Null, |is| Value::String(run_text(&is))),
+                (_, None) => Value::Null,
+                (Some("s"), Some(raw)) => raw
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| shared.get(i))
+                    .map_or(Value::Null, |text| Value::String(text.clone())),
+                (Some("b"), Some(raw)) => Value::Bool(raw.trim() == "1"),
+                (Some("str" | "e" | "d"), Some(raw)) => Value::String(raw.to_string()),
+                (_, Some(raw)) => number(raw),
+            };
+            if cells.len() <= column {
+                cells.resize(column + 1, Value::Null);
+            }
+            cells[column] = value;
+        }
+    }
+    rows
+}
+
+// ─── ODS ────────────────────────────────────────────────────────────────────
+
+fn ods_sheets(archive: &Archive) -> Result<Vec<Sheet>, String> {
+    let content = member_text(archive, "content.xml")?.unwrap_or_default();
+    let doc = parse_xml("content.xml", &content)?;
+    Ok(doc
+        .descendants()
+        .filter(|n| is(n, "table") && n.ancestors().any(|a| is(&a, "spreadsheet")))
+        .map(|table| {
+            let name = attribute(&table, "name").unwrap_or_default().to_string();
+            Ok((name, ods_rows(&table)))
+        })
+        .collect())
+}
+
+/// A repeat count attribute, at least 1.
+fn repeated(node: &Node, name: &str) -> usize {
+    attribute(node, name)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+fn ods_rows(table: &Node) -> Vec<Vec<Value>> {
+    let mut rows: Vec<Vec<Value>> = Vec::new();
+    // Runs of empty rows are only materialized when a later row has cells,
+    // so a trailing `number-rows-repeated="1048000"` costs nothing.
+    let mut pending_rows = 0;
+    let table_rows = table
+        .descendants()
+        .filter(|n| is(n, "table-row") && n.ancestors().find(|a| is(a, "table")) == Some(*table));
+    for row in table_rows {
+        let cells = ods_cells(&row);
+        let count = repeated(&row, "number-rows-repeated");
+        if cells.is_empty() {
+            pending_rows += count;
+            continue;
+        }
+        let room = MAX_ROWS.saturating_sub(rows.len());
+        rows.extend(std::iter::repeat_n(Vec::new(), pending_rows.min(room)));
+        pending_rows = 0;
+        let room = MAX_ROWS.saturating_sub(rows.len());
+        rows.extend(std::iter::repeat_n(cells, count.min(room)));
+    }
+    rows
+}
+
+fn ods_cells(row: &Node) -> Vec<Value> {
+    let mut cells = Vec::new();
+    let mut pending = 0;
+    for cell in row
+        .children()
+        .filter(|n| is(n, "table-cell") || is(n, "covered-table-cell"))
+    {
+        let value = ods_value(&cell);
+        let count = repeated(&cell, "number-columns-repeated");
+        if value.is_null() {
+            pending += count;
+            continue;
+        }
+        let room = MAX_COLUMNS.saturating_sub(cells.len());
+        cells.extend(std::iter::repeat_n(Value::Null, pending.min(room)));
+        pending = 0;
+        let room = MAX_COLUMNS.saturating_sub(cells.len());
+        cells.extend(std::iter::repeat_n(value, count.min(room)));
+    }
+    cells
+}
+
+fn ods_value(cell: &Node) -> Value {
+    let typed = |name| attribute(cell, name).map(str::to_string);
+    match attribute(cell, "value-type") {
+        Some("float" | "percentage" | "currency") => {
+            typed("value").map_or(Value::Null, |v| number(&v))
+        }
+        Some("boolean") => Value::Bool(attribute(cell, "boolean-value") == Some("true")),
+        Some("date") => typed("date-value").map_or(Value::Null, Value::String),
+        Some("time") => typed("time-value").map_or(Value::Null, Value::String),
+        _ => {
+            let paragraphs: Vec<String> = cell
+                .children()
+                .filter(|n| is(n, "p"))
+                .map(|p| paragraph_text(&p))
+                .collect();
+            if paragraphs.is_empty() {
+                Value::Null
+            } else {
+                Value::String(paragraphs.join("\n"))
+            }
+        }
+    }
+}
+
+/// The text of an ODS paragraph, expanding `<text:s/>` spaces, tabs, and
+/// line breaks.
+fn paragraph_text(paragraph: &Node) -> String {
+    let mut text = String::new();
+    for node in paragraph.descendants().skip(1) {
+        if node.is_text() {
+            text.push_str(node.text().unwrap_or_default());
+        } else if is(&node, "s") {
+            text.extend(std::iter::repeat_n(
+                ' ',
+                repeated(&node, "c").min(MAX_COLUMNS),
+            ));
+        } else if is(&node, "tab") {
+            text.push('\t');
+        } else if is(&node, "line-break") {
+            text.push('\n');
+        }
+    }
+    text
+}
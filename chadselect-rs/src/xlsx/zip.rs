@@ -0,0 +1,107 @@
+//! Just enough of the zip format to read the members of a workbook: the
+//! central directory, and stored or deflated entries.
+
+use crate::inflate::inflate_raw;
+
+const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+const LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+
+pub(super) struct Archive<'a> {
+    data: &'a [u8],
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    name: String,
+    method: u16,
+    encrypted: bool,
+    compressed_size: usize,
+    header_offset: usize,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?) as usize)
+}
+
+impl<'a> Archive<'a> {
+    /// Read the central directory. Errors when `data` is not a zip archive
+    /// (or is a ZIP64 one, which no workbook needs).
+    pub(super) fn parse(data: &'a [u8]) -> Result<Archive<'a>, String> {
+        // The end record sits in the last 64 KiB (its comment is at most
+        // that long).
+        let search_from = data.len().saturating_sub(22 + 0xffff);
+        let end = data[search_from..]
+            .windows(4)
+            .rposition(|w| w == END_OF_DIRECTORY)
+            .map(|i| search_from + i)
+            .ok_or("not a zip archive")?;
+        let malformed = || "malformed zip archive".to_string();
+        let count = u16_at(data, end + 10).ok_or_else(malformed)?;
+        let mut at = u32_at(data, end + 16).ok_or_else(malformed)?;
+        if at == 0xffff_ffff || count == 0xffff {
+            return Err("ZIP64 archives are not supported".to_string());
+        }
+
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            if data.get(at..at + 4) != Some(DIRECTORY_ENTRY) {
+                return Err(malformed());
+            }
+            let field = |offset| u16_at(data, at + offset).ok_or_else(malformed);
+            let (name_len, extra_len, comment_len) = (field(28)?, field(30)?, field(32)?);
+            let name = data
+                .get(at + 46..at + 46 + name_len as usize)
+                .ok_or_else(malformed)?;
+            entries.push(Entry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                method: field(10)?,
+                encrypted: field(8)? & 1 == 1,
+                compressed_size: u32_at(data, at + 20).ok_or_else(malformed)?,
+                header_offset: u32_at(data, at + 42).ok_or_else(malformed)?,
+            });
+            at += 46 + name_len as usize + extra_len as usize + comment_len as usize;
+        }
+        Ok(Archive { data, entries })
+    }
+
+    pub(super) fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.name == name)
+    }
+
+    /// The contents of member `name`, `None` when there is no such member.
+    pub(super) fn read(&self, name: &str) -> Option<Result<Vec<u8>, String>> {
+        let entry = self.entries.iter().find(|entry| entry.name == name)?;
+        Some(self.read_entry(entry))
+    }
+
+    fn read_entry(&self, entry: &Entry) -> Result<Vec<u8>, String> {
+        let malformed = || format!("malformed zip member {}", entry.name);
+        if entry.encrypted {
+            return Err(format!("zip member {} is encrypted", entry.name));
+        }
+        let at = entry.header_offset;
+        if self.data.get(at..at + 4) != Some(LOCAL_HEADER) {
+            return Err(malformed());
+        }
+        let name_len = u16_at(self.data, at + 26).ok_or_else(malformed)? as usize;
+        let extra_len = u16_at(self.data, at + 28).ok_or_else(malformed)? as usize;
+        let start = at + 30 + name_len + extra_len;
+        let data = self
+            .data
+            .get(start..start + entry.compressed_size)
+            .ok_or_else(malformed)?;
+        match entry.method {
+            0 => Ok(data.to_vec()),
+            8 => inflate_raw(data).map_err(|e| format!("zip member {}: {}", entry.name, e)),
+            method => Err(format!(
+                "zip member {} uses unsupported compression method {}",
+                entry.name, method
+            )),
+        }
+    }
+}
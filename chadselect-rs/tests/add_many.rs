@@ -0,0 +1,111 @@
+//! Tests for bulk ingestion with `add_many` and eager parsing with `warm`.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, ContentType, LimitPolicy};
+
+fn items() -> Vec<(ContentType, String)> {
+    vec![
+        (
+            ContentType::Html,
+            "<h1>Civic</h1><p class='vin'>VIN 1HG</p>".to_string(),
+        ),
+        (ContentType::Json, r#"{"price": 28500}"#.to_string()),
+        (ContentType::Markdown, "## Changelog".to_string()),
+        (ContentType::Ini, "[db]\nhost = example".to_string()),
+        (ContentType::Text, "Order #42".to_string()),
+    ]
+}
+
+#[test]
+fn add_many_adds_every_item() {
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_many(items(), false), 5);
+    assert_eq!(cs.content_count(), 5);
+    assert_eq!(cs.select(0, "css:h1"), "Civic");
+    assert_eq!(cs.select(0, "json:price"), "28500");
+    assert_eq!(cs.select(0, "xpath://h2"), "Changelog");
+    assert_eq!(cs.select(0, "json:db.host"), "example");
+    assert_eq!(cs.select(0, r"regex:#(\d+)"), "42");
+}
+
+#[test]
+fn warmed_items_query_the_same() {
+    let mut cold = ChadSelect::new();
+    cold.add_many(items(), false);
+    let mut warm = ChadSelect::new();
+    warm.add_many(items(), true);
+
+    for query in [
+        "css:.vin",
+        "xpath://h1",
+        "json:price",
+        "json:db.host",
+        "css:h2",
+    ] {
+        assert_eq!(warm.query(-1, query), cold.query(-1, query), "{}", query);
+    }
+}
+
+#[test]
+fn add_many_skips_rejected_items() {
+    let mut cs = ChadSelect::builder().max_item_bytes(20).build();
+    let added = cs.add_many(
+        vec![
+            (ContentType::Html, "<p>ok</p>".to_string()),
+            (
+                ContentType::Html,
+                "<p>this one is far too long</p>".to_string(),
+            ),
+            (ContentType::Json, "[1]".to_string()),
+        ],
+        true,
+    );
+    assert_eq!(added, 2);
+    assert_eq!(cs.query(-1, "css:p"), vec!["ok"]);
+}
+
+#[test]
+fn warm_with_eviction_only_touches_remaining_items() {
+    let mut cs = ChadSelect::builder()
+        .max_total_bytes(20)
+        .limit_policy(LimitPolicy::EvictOldest)
+        .build();
+    let added = cs.add_many(
+        vec![
+            (ContentType::Html, "<p>first</p>".to_string()),
+            (ContentType::Html, "<p>second</p>".to_string()),
+        ],
+        true,
+    );
+    assert_eq!(added, 2);
+    assert_eq!(cs.content_count(), 1);
+    assert_eq!(cs.query(-1, "css:p"), vec!["second"]);
+}
+
+#[test]
+fn warm_tolerates_invalid_content() {
+    let mut cs = ChadSelect::new();
+    cs.add_json("{not json".to_string());
+    cs.add_html("<b>fine</b>".to_string());
+    cs.warm();
+    assert!(cs.query(-1, "json:a").is_empty());
+    assert_eq!(cs.select(0, "css:b"), "fine");
+}
+
+#[test]
+fn warm_respects_cache_budget() {
+    let mut cs = ChadSelect::builder().cache_budget_bytes(1).build();
+    cs.add_many(items(), true);
+    assert!(cs.memory_usage().cached_bytes <= 1);
+    assert_eq!(cs.select(0, "css:h1"), "Civic");
+}
+
+#[test]
+fn warm_parses_loaded_items() {
+    let mut cs = ChadSelect::new();
+    cs.add_many(items(), false);
+    assert_eq!(cs.memory_usage().parsed_items, 0);
+    cs.warm();
+    // HTML, JSON, Markdown, and INI are parsed; plain text has nothing to parse.
+    assert_eq!(cs.memory_usage().parsed_items, 4);
+}
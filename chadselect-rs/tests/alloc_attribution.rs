@@ -23,6 +23,7 @@
 //!   * always: a **guard** asserting XPath stays under a per-row alloc ceiling;
 //!   * `--nocapture`: prints a per-engine attribution table and a size-scaling
 //!     table (run `-- --nocapture --test-threads=1`).
+#![cfg(all(feature = "xpath", feature = "regex"))]
 
 use std::alloc::{GlobalAlloc, Layout, System};
 use std::sync::atomic::{AtomicU64, Ordering};
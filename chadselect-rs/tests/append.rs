@@ -0,0 +1,77 @@
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, ContentType};
+
+#[test]
+fn appended_text_is_queried() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("09:00 INFO started\n".to_string());
+    assert!(cs.query(-1, "regex:ERROR (.+)").is_empty());
+
+    cs.append_text(0, "09:01 ERROR disk ").unwrap();
+    cs.append_text(0, "full\n09:02 ERROR retrying\n").unwrap();
+    assert_eq!(cs.query(-1, "regex:ERROR (.+)"), ["disk full", "retrying"]);
+    assert_eq!(cs.content_count(), 1);
+}
+
+#[test]
+fn appending_html_reparses_only_that_item() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<ul><li>Civic</li>".to_string());
+    cs.add_html("<p>other</p>".to_string());
+    cs.warm();
+    assert_eq!(cs.memory_usage().parsed_items, 2);
+
+    cs.append_text(0, "<li>Accord</li></ul>").unwrap();
+    assert_eq!(cs.memory_usage().parsed_items, 1);
+    assert_eq!(cs.query(-1, "css:li"), ["Civic", "Accord"]);
+    assert_eq!(cs.query(-1, "xpath://li[2]/text()"), ["Accord"]);
+}
+
+#[test]
+fn jsonl_lines_extend_the_array() {
+    let mut cs = ChadSelect::new();
+    cs.add_json("[]".to_string());
+    cs.append_jsonl(0, r#"{"id": 1, "status": "ok"}"#).unwrap();
+    assert_eq!(cs.query(-1, "json:[*].id"), ["1"]);
+
+    // The cached value tree is extended rather than dropped.
+    cs.append_jsonl(0, " {\"id\": 2, \"status\": \"failed\"}\n")
+        .unwrap();
+    assert_eq!(cs.memory_usage().parsed_items, 1);
+    assert_eq!(cs.query(-1, "json:[?status=='failed'].id"), ["2"]);
+    assert_eq!(
+        cs.select(0, r"regex:(?s)^.*$"),
+        r#"[{"id": 1, "status": "ok"},{"id": 2, "status": "failed"}]"#
+    );
+    assert_eq!(cs.query(-1, r#"regex:"id": (\d)"#), ["1", "2"]);
+}
+
+#[test]
+fn rejected_appends_leave_the_item_unchanged() {
+    let mut cs = ChadSelect::builder().max_item_bytes(32).build();
+    cs.add_json(r#"{"not": "an array"}"#.to_string());
+    cs.add_json("[1]".to_string());
+    cs.add_bytes(b"\x00\x01");
+
+    let bytes = cs.content_bytes();
+    assert!(cs.append_jsonl(0, "2").is_err());
+    assert!(cs.append_jsonl(1, "{broken").is_err());
+    assert!(cs.append_jsonl(1, &"9".repeat(40)).is_err());
+    assert!(cs.append_text(2, "text").is_err());
+    assert!(cs.append_text(7, "text").is_err());
+    assert_eq!(cs.content_bytes(), bytes);
+
+    cs.append_jsonl(1, "2").unwrap();
+    assert_eq!(cs.query(-1, "json:[1]"), ["2"]);
+}
+
+#[test]
+fn appending_markdown_rerenders() {
+    let mut cs = ChadSelect::new();
+    cs.try_add("# Changelog\n".to_string(), ContentType::Markdown)
+        .unwrap();
+    assert!(cs.query(-1, "css:h2").is_empty());
+    cs.append_text(0, "\n## 0.4.5\n").unwrap();
+    assert_eq!(cs.query(-1, "css:h2"), ["0.4.5"]);
+}
@@ -0,0 +1,109 @@
+//! Tests for `record_batch` and `write_parquet` (the `arrow` and `parquet`
+//! features).
+#![cfg(all(feature = "arrow", feature = "css", feature = "json"))]
+
+use chadselect::arrow::arrow_array::{Array, Float64Array, Int64Array, StringArray};
+use chadselect::arrow::arrow_schema::DataType;
+use chadselect::{ChadSelect, ColumnType, ExtractionSchema};
+
+fn listings() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<h1>Civic</h1><span class="price">$28,500</span><span class="miles">12,000.5 mi</span>"#
+            .to_string(),
+    );
+    cs.add_html(r#"<h1>Accord</h1><span class="price">call us</span>"#.to_string());
+    cs.add_html("<p>no listing here</p>".to_string());
+    cs.add_json(r#"{"title": "Model 3", "price": 39990}"#.to_string());
+    cs
+}
+
+fn schema() -> ExtractionSchema {
+    ExtractionSchema::new()
+        .column("title", ColumnType::Text, "css:h1 || json:title")
+        .column("price", ColumnType::Integer, "css:.price || json:price")
+        .column("miles", ColumnType::Real, "css:.miles")
+}
+
+#[test]
+fn batch_has_typed_nullable_columns() {
+    let batch = listings().record_batch(&schema()).unwrap();
+    assert_eq!(batch.num_rows(), 3);
+
+    let fields: Vec<_> = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| (f.name().clone(), f.data_type().clone(), f.is_nullable()))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            ("title".to_string(), DataType::Utf8, true),
+            ("price".to_string(), DataType::Int64, true),
+            ("miles".to_string(), DataType::Float64, true),
+        ]
+    );
+}
+
+#[test]
+fn values_are_converted_per_column() {
+    let batch = listings().record_batch(&schema()).unwrap();
+    let titles = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    let prices = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    let miles = batch
+        .column(2)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+
+    let titles: Vec<_> = titles.iter().collect();
+    assert_eq!(titles, vec![Some("Civic"), Some("Accord"), Some("Model 3")]);
+    let prices: Vec<_> = prices.iter().collect();
+    assert_eq!(prices, vec![Some(28500), None, Some(39990)]);
+    let miles: Vec<_> = miles.iter().collect();
+    assert_eq!(miles, vec![Some(12000.5), None, None]);
+}
+
+#[test]
+fn empty_schema_is_an_error() {
+    assert!(listings().record_batch(&ExtractionSchema::new()).is_err());
+}
+
+#[test]
+fn no_content_gives_an_empty_batch() {
+    let batch = ChadSelect::new().record_batch(&schema()).unwrap();
+    assert_eq!(batch.num_rows(), 0);
+    assert_eq!(batch.num_columns(), 3);
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_round_trips() {
+    use chadselect::arrow::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    let path = std::env::temp_dir().join(format!("chadselect-{}.parquet", std::process::id()));
+    let cs = listings();
+    assert_eq!(
+        cs.write_parquet(File::create(&path).unwrap(), &schema()),
+        Ok(3)
+    );
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap();
+    let batches: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0], cs.record_batch(&schema()).unwrap());
+}
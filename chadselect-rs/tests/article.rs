@@ -0,0 +1,127 @@
+//! Tests for `extract_article` (the `readability` feature).
+#![cfg(feature = "readability")]
+
+use chadselect::ChadSelect;
+
+fn page(html: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(html.to_string());
+    cs
+}
+
+const STORY: &str = r#"<html><head>
+  <title>Hybrid SUVs compared | Motor Weekly</title>
+  <meta property="og:title" content="Hybrid SUVs compared">
+  <meta property="article:published_time" content="2024-05-02T09:00:00Z">
+</head><body>
+  <header class="masthead"><p>Motor Weekly, the magazine for people who love cars, since 1962.</p></header>
+  <nav class="menu"><a href="/">Home</a><a href="/reviews">Reviews</a><a href="/news">News</a></nav>
+  <div id="main">
+    <div class="post-body">
+      <h1>Hybrid SUVs compared</h1>
+      <p class="byline">By Sam Rivera</p>
+      <p>We drove five hybrid SUVs for a month each, logging fuel use, cabin noise, and cargo space.</p>
+      <h2>Fuel economy</h2>
+      <p>The RAV4 led on the highway, while the CR-V was best in town, by a small but steady margin.</p>
+      <ul><li>RAV4: 41 mpg combined, the best of the group by a clear margin</li>
+          <li>CR-V: 40 mpg combined, close behind</li></ul>
+      <p>All five beat their EPA estimates in mixed driving, which surprised us, given the cold weather.</p>
+    </div>
+    <div class="related-links">
+      <p><a href="/a">Best hybrids of the year, ranked by our editors</a></p>
+      <p><a href="/b">Why hybrid batteries last longer than you think</a></p>
+    </div>
+    <div class="comments">
+      <p>Great article, but you forgot the Tucson hybrid, which is better than all of these, honestly.</p>
+    </div>
+  </div>
+  <footer><p>Copyright Motor Weekly. All rights reserved, everywhere, forever and always.</p></footer>
+</body></html>"#;
+
+#[test]
+fn metadata() {
+    let article = page(STORY).extract_article(0).unwrap();
+    assert_eq!(article.title.as_deref(), Some("Hybrid SUVs compared"));
+    assert_eq!(article.byline.as_deref(), Some("Sam Rivera"));
+    assert_eq!(article.published.as_deref(), Some("2024-05-02T09:00:00Z"));
+}
+
+#[test]
+fn body_text_skips_boilerplate() {
+    let article = page(STORY).extract_article(0).unwrap();
+    let paragraphs: Vec<&str> = article.text.split("\n\n").collect();
+    assert_eq!(paragraphs[0], "By Sam Rivera");
+    assert!(paragraphs[1].starts_with("We drove five hybrid SUVs"));
+    assert!(paragraphs.contains(&"Fuel economy"));
+    assert!(paragraphs.contains(&"CR-V: 40 mpg combined, close behind"));
+    assert!(paragraphs.last().unwrap().starts_with("All five beat"));
+
+    for boilerplate in [
+        "Motor Weekly, the magazine",
+        "Reviews",
+        "Best hybrids",
+        "Tucson",
+        "Copyright",
+    ] {
+        assert!(
+            !article.text.contains(boilerplate),
+            "{boilerplate} leaked into the body"
+        );
+    }
+    assert!(article.html.starts_with(r#"<div class="post-body">"#));
+}
+
+#[test]
+fn split_stories_keep_qualifying_siblings() {
+    let paragraph = "This sentence, with a few commas, is long enough to count as body text. ";
+    let html = format!(
+        r#"<body><div class="story">
+             <div class="part"><p>{p}</p><p>{p}</p><p>{p}</p></div>
+             <div class="part"><p>{p}</p><p>{p}</p></div>
+             <div class="sidebar"><p>{p}</p></div>
+           </div></body>"#,
+        p = paragraph.repeat(2)
+    );
+    let article = page(&html).extract_article(0).unwrap();
+    assert_eq!(article.text.split("\n\n").count(), 5);
+}
+
+#[test]
+fn title_falls_back_to_h1_then_title() {
+    let body = "<p>Plenty of body text here, long enough, with commas, to score as content.</p>";
+    let cs = page(&format!(
+        "<title>Page title</title><h1>Heading</h1><div>{body}</div>"
+    ));
+    assert_eq!(
+        cs.extract_article(0).unwrap().title.as_deref(),
+        Some("Heading")
+    );
+
+    let cs = page(&format!("<title> Page title </title><div>{body}</div>"));
+    let article = cs.extract_article(0).unwrap();
+    assert_eq!(article.title.as_deref(), Some("Page title"));
+    assert_eq!(article.byline, None);
+    assert_eq!(article.published, None);
+}
+
+#[test]
+fn time_element_gives_the_date() {
+    let cs = page(
+        r#"<article><time datetime="2023-11-20">Nov 20</time>
+           <p>Plenty of body text here, long enough, with commas, to score as content.</p></article>"#,
+    );
+    assert_eq!(
+        cs.extract_article(0).unwrap().published.as_deref(),
+        Some("2023-11-20")
+    );
+}
+
+#[test]
+fn no_article_without_body_text() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<nav><a href='/'>Home</a></nav><p>Short.</p>".to_string());
+    cs.add_text(STORY.to_string());
+    assert_eq!(cs.extract_article(0), None);
+    assert_eq!(cs.extract_article(1), None);
+    assert_eq!(cs.extract_article(2), None);
+}
@@ -0,0 +1,85 @@
+//! Tests for the `auto:` prefix and engine detection.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::query::{detect_query_type, QueryType};
+use chadselect::{ChadSelect, PrefixMode};
+
+fn engine(expr: &str) -> &'static str {
+    detect_query_type(expr).prefix()
+}
+
+#[test]
+fn detects_xpath() {
+    assert_eq!(engine("//div[@class='price']/text()"), "xpath");
+    assert_eq!(engine("(//li)[2]"), "xpath");
+    assert_eq!(engine("./span"), "xpath");
+}
+
+#[test]
+fn detects_css() {
+    assert_eq!(engine(".price"), "css");
+    assert_eq!(engine("#vin"), "css");
+    assert_eq!(engine("div.product > span"), "css");
+    assert_eq!(engine("ul li"), "css");
+    assert_eq!(engine("[data-id]"), "css");
+    assert_eq!(engine("a[href^='http'] >> get-attr('href')"), "css");
+}
+
+#[test]
+fn detects_jmespath() {
+    assert_eq!(engine("store.inventory[0].name"), "json");
+    assert_eq!(engine("products[].name >> uppercase()"), "json");
+    assert_eq!(engine("items[?price > `10`].name"), "json");
+}
+
+#[test]
+fn falls_back_to_regex() {
+    assert_eq!(engine(r"VIN:\s*(\w+)"), "regex");
+    assert_eq!(engine(r"\d+"), "regex");
+    assert_eq!(engine("hello world"), "regex");
+}
+
+#[test]
+fn detection_keeps_function_chain() {
+    match detect_query_type(".price >> trim()") {
+        QueryType::CssSelector(s) => assert_eq!(s, ".price >> trim()"),
+        other => panic!("expected css, got {:?}", other),
+    }
+}
+
+#[test]
+fn auto_prefix_reports_engine() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<span class="price">$49.99</span>"#.to_string());
+    cs.add_json(r#"{"store": {"name": "Widget World"}}"#.to_string());
+
+    let r = cs.query_detailed(0, "auto://span/text()");
+    assert_eq!(r.results, vec!["$49.99"]);
+    assert_eq!(r.engine(), Some("xpath"));
+
+    let r = cs.query_detailed(0, "auto:store.name >> uppercase()");
+    assert_eq!(r.results, vec!["WIDGET WORLD"]);
+    assert_eq!(r.engine(), Some("json"));
+
+    let r = cs.query_detailed(0, "regex:(\\d+)\\.");
+    assert_eq!(r.results, vec!["49"]);
+    assert_eq!(r.engine(), Some("regex"));
+}
+
+#[test]
+fn auto_prefix_mode_classifies_unprefixed_queries() {
+    let mut cs = ChadSelect::builder().prefix_mode(PrefixMode::Auto).build();
+    cs.add_html(r#"<span class="price">$49.99</span>"#.to_string());
+
+    assert_eq!(cs.select(0, ".price"), "$49.99");
+    assert_eq!(cs.query_detailed(0, "span.price").engine(), Some("css"));
+    assert_eq!(cs.select(0, r"\$([\d.]+)"), "49.99");
+}
+
+#[test]
+fn unparseable_query_has_no_engine() {
+    let cs = ChadSelect::builder().prefix_mode(PrefixMode::Strict).build();
+    let r = cs.query_detailed(-1, "nope");
+    assert!(r.results.is_empty());
+    assert_eq!(r.engine(), None);
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "xpath")]
+
 use chadselect::ChadSelect;
 
 fn cs() -> ChadSelect {
@@ -0,0 +1,53 @@
+//! Tests for `ChadSelectBuilder` options.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use chadselect::query::{parse_query_with, QueryType};
+use chadselect::{ChadSelect, PrefixMode};
+
+// ─── Prefix mode ────────────────────────────────────────────────────────────
+
+#[test]
+fn default_mode_routes_unprefixed_to_regex() {
+    let mut cs = ChadSelect::builder().build();
+    cs.add_text("id: 42".to_string());
+    assert_eq!(cs.select(0, r"id: (\d+)"), "42");
+}
+
+#[test]
+fn strict_mode_rejects_unprefixed_queries() {
+    let mut cs = ChadSelect::builder().prefix_mode(PrefixMode::Strict).build();
+    cs.add_text("id: 42".to_string());
+    assert!(cs.query(-1, r"id: (\d+)").is_empty());
+    assert_eq!(cs.select(0, r"regex:id: (\d+)"), "42");
+}
+
+#[test]
+fn strict_mode_rejects_typoed_prefix() {
+    let error = parse_query_with("xpat://div", PrefixMode::Strict).unwrap_err();
+    let engines = [
+        QueryType::Regex(String::new()),
+        QueryType::XPath(String::new()),
+        QueryType::JsonPath(String::new()),
+        QueryType::CssSelector(String::new()),
+        QueryType::EmbeddedJson(String::new()),
+        QueryType::Structured(String::new()),
+        QueryType::FullText(String::new()),
+        QueryType::Bytes(String::new()),
+        QueryType::Hex(String::new()),
+    ];
+    for engine in engines {
+        let prefix = format!("{}:", engine.prefix());
+        assert!(error.contains(&prefix), "{} missing from: {}", prefix, error);
+    }
+    for alias in ["regexm:", "pat:", "glob:", "lines:", "auto:", "ref:"] {
+        assert!(error.contains(alias), "{} missing from: {}", alias, error);
+    }
+    assert!(matches!(
+        parse_query_with("xpat://div", PrefixMode::Regex),
+        Ok(QueryType::Regex(_))
+    ));
+    assert!(matches!(
+        parse_query_with("xpath://div", PrefixMode::Strict),
+        Ok(QueryType::XPath(_))
+    ));
+}
@@ -0,0 +1,107 @@
+//! Tests for binary content and the `bytes:` / `hex:` prefixes.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use chadselect::patterns::hex_to_regex;
+use chadselect::{ChadSelect, PreparedQuery};
+
+const DUMP: &[u8] = b"\x7fELF\x02\x01\x01\x00fw-1.4.2\x00\xde\xad\xbe\xef\n\xff\xfeOK\x00";
+
+fn dump() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_bytes(DUMP);
+    cs
+}
+
+#[test]
+fn binary_content_keeps_every_byte() {
+    let cs = dump();
+    assert_eq!(cs.content_count(), 1);
+    #[cfg(feature = "info")]
+    {
+        let info = cs.content_info(0).unwrap();
+        assert_eq!(info.content_type, chadselect::ContentType::Binary);
+        assert_eq!(info.bytes, DUMP.len());
+    }
+
+    // `.` matches any byte, `\n` included.
+    assert_eq!(cs.query(-1, "bytes:.").len(), DUMP.len());
+    assert_eq!(cs.select(0, r"hex:ef (??) ff"), "0a");
+    // The last byte is reachable, so nothing was lost.
+    assert_eq!(cs.query(-1, "hex:4f 4b 00"), vec!["4f4b00"]);
+}
+
+#[test]
+fn bytes_queries_return_lossy_text() {
+    let cs = dump();
+    assert_eq!(cs.select(0, r"bytes:fw-([\d.]+)\x00"), "1.4.2");
+    assert_eq!(cs.select(0, r"bytes:\xff\xfe(..)"), "OK");
+    assert_eq!(cs.select(0, r"bytes:\xff\xfe"), "\u{fffd}\u{fffd}");
+    assert_eq!(
+        cs.query(-1, r"bytes:[[:alpha:]]{2,} >> lowercase()"),
+        vec!["elf", "fw", "ok"]
+    );
+    // Regex sees one character per byte, so ASCII reads as text.
+    assert_eq!(cs.select(0, r"regex:fw-([\d.]+)"), "1.4.2");
+    // Engines that need text skip binary content.
+    assert!(cs.query(-1, "xpath://text()").is_empty());
+    assert!(cs.query(-1, "text:firmware").is_empty());
+}
+
+#[test]
+fn hex_queries_return_hex() {
+    let cs = dump();
+    assert_eq!(cs.select(0, "hex:DE AD ?? EF"), "deadbeef");
+    assert_eq!(cs.select(0, "hex:7f454c46 (??)"), "02");
+    assert_eq!(cs.query(-1, "hex:00"), vec!["00", "00", "00"]);
+    assert_eq!(cs.select(0, "hex:de ad >> uppercase()"), "DEAD");
+    assert!(cs.query(-1, "hex:ca fe").is_empty());
+}
+
+#[test]
+fn byte_queries_run_on_text_content() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("héllo wörld".to_string());
+    assert_eq!(cs.select(0, "hex:68 (c3 a9)"), "c3a9");
+    assert_eq!(cs.select(0, r"bytes:w(..)r"), "ö");
+    assert_eq!(cs.select(0, "css:p || hex:6c 6c"), "6c6c");
+}
+
+#[test]
+fn locations_carry_byte_spans() {
+    let cs = dump();
+    let spans = cs.query_spans(0, "hex:de ad be ef");
+    assert_eq!(spans, vec![(17, 21, "deadbeef".to_string())]);
+
+    let mut cs = ChadSelect::new();
+    cs.add_text("a\nbé x".to_string());
+    let result = cs.query_detailed(0, "hex:78");
+    let location = &result.locations[0];
+    assert_eq!(location.span, Some(6..7));
+    assert_eq!((location.line, location.column), (Some(2), Some(4)));
+    // A match starting inside a character has a span but no line.
+    let result = cs.query_detailed(0, r"bytes:\xa9");
+    assert_eq!(result.locations[0].span, Some(4..5));
+    assert_eq!(result.locations[0].line, None);
+}
+
+#[test]
+fn signatures_translate_and_validate() {
+    assert_eq!(hex_to_regex("DE AD ?? ef").unwrap(), r"\xde\xad.\xef");
+    assert_eq!(hex_to_regex("(7f45)(??)").unwrap(), r"(\x7f\x45)(.)");
+    assert!(hex_to_regex("de a").is_err());
+    assert!(hex_to_regex("de ?a").is_err());
+    assert!(hex_to_regex("(de").is_err());
+    assert!(hex_to_regex("de)").is_err());
+    assert!(hex_to_regex("zz").is_err());
+
+    assert!(PreparedQuery::new("hex:de ad ?").is_err());
+    assert!(PreparedQuery::new(r"bytes:(unclosed").is_err());
+    assert_eq!(
+        PreparedQuery::new("hex:de ad")
+            .unwrap()
+            .query_type()
+            .unwrap()
+            .prefix(),
+        "hex"
+    );
+}
@@ -0,0 +1,91 @@
+//! Tests for manual cache control and the LRU cache budget.
+#![cfg(all(feature = "css", feature = "json"))]
+
+use chadselect::ChadSelect;
+
+fn page(label: &str) -> String {
+    format!("<html><body><h1>{}</h1><p>{}</p></body></html>", label, "x".repeat(1000))
+}
+
+#[test]
+fn drop_caches_releases_and_reparses() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page("one"));
+    cs.add_json(r#"{"a": 1}"#.to_string());
+
+    assert_eq!(cs.select(0, "css:h1"), "one");
+    assert_eq!(cs.select(0, "json:a"), "1");
+    assert_eq!(cs.memory_usage().parsed_items, 2);
+    assert!(cs.memory_usage().cached_bytes > 0);
+
+    cs.drop_caches();
+    let usage = cs.memory_usage();
+    assert_eq!(usage.parsed_items, 0);
+    assert_eq!(usage.cached_bytes, 0);
+
+    // Queries still work, re-parsing lazily.
+    assert_eq!(cs.select(0, "css:h1"), "one");
+}
+
+#[test]
+fn drop_caches_for_one_item() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page("one"));
+    cs.add_html(page("two"));
+    cs.query(-1, "css:h1");
+    assert_eq!(cs.memory_usage().parsed_items, 2);
+
+    cs.drop_caches_for(0);
+    assert_eq!(cs.memory_usage().parsed_items, 1);
+
+    // Out of range is ignored.
+    cs.drop_caches_for(10);
+    assert_eq!(cs.memory_usage().parsed_items, 1);
+}
+
+#[test]
+fn text_pseudo_cache_is_released() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page("one"));
+    cs.query(-1, "css:h1:has-text('one')");
+    cs.drop_caches();
+    assert_eq!(cs.memory_usage().cached_bytes, 0);
+    assert_eq!(cs.select(0, "css:h1:has-text('one')"), "one");
+}
+
+#[test]
+fn budget_keeps_recently_used_items() {
+    let one_doc = {
+        let mut probe = ChadSelect::new();
+        probe.add_html(page("probe"));
+        probe.query(-1, "css:h1");
+        probe.memory_usage().cached_bytes
+    };
+
+    let mut cs = ChadSelect::builder().cache_budget_bytes(one_doc * 2).build();
+    cs.add_html(page("one"));
+    cs.add_html(page("two"));
+    cs.add_html(page("three"));
+    cs.add_json(r#"{"a": 1}"#.to_string());
+
+    // Touching all three documents overflows the budget; only two stay cached.
+    cs.query(-1, "css:h1");
+    assert!(cs.memory_usage().cached_bytes <= one_doc * 2);
+    assert_eq!(cs.memory_usage().parsed_items, 2);
+
+    // A JSON query stamps only the JSON item, so it is kept over older ones.
+    cs.query(-1, "json:a");
+    assert_eq!(cs.select(0, "json:a"), "1");
+    assert!(cs.memory_usage().cached_bytes <= one_doc * 2);
+    assert_eq!(cs.query(-1, "css:h1"), vec!["one", "two", "three"]);
+}
+
+#[test]
+fn no_budget_keeps_everything() {
+    let mut cs = ChadSelect::new();
+    for label in ["a", "b", "c"] {
+        cs.add_html(page(label));
+    }
+    cs.query(-1, "css:h1");
+    assert_eq!(cs.memory_usage().parsed_items, 3);
+}
@@ -0,0 +1,53 @@
+//! Tests for the `check_queries` coverage matrix.
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+fn pages() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<h1>Civic</h1><span class="price">$1</span>"#.to_string());
+    cs.add_html(r#"<h1>Accord</h1><span class="amount">$2</span>"#.to_string());
+    cs.add_json(r#"{"price": "$3"}"#.to_string());
+    cs
+}
+
+#[test]
+fn reports_result_counts_per_item() {
+    let cs = pages();
+    let report = cs.check_queries(&["css:h1", "css:.price", "json:price", r"regex:\$\d"]);
+
+    assert_eq!(report[0].query, "css:h1");
+    assert_eq!(report[0].matches, vec![Some(1), Some(1), None]);
+    assert!(!report[0].is_partial() && !report[0].is_dead());
+
+    assert_eq!(report[1].matches, vec![Some(1), Some(0), None]);
+    assert!(report[1].is_partial());
+    assert_eq!(report[1].matched_items(), vec![0]);
+    assert_eq!(report[1].missed_items(), vec![1]);
+
+    assert_eq!(report[2].matches, vec![None, None, Some(1)]);
+    assert_eq!(report[3].matches, vec![Some(1), Some(1), Some(1)]);
+}
+
+#[test]
+fn dead_and_malformed_queries() {
+    let cs = pages();
+    let report = cs.check_queries(&["css:.gone", "css:][", "css:h1 >> nope()"]);
+    assert!(report[0].is_dead() && report[0].error.is_none());
+    assert_eq!(report[0].matches, vec![Some(0), Some(0), None]);
+    for malformed in &report[1..] {
+        assert!(
+            malformed.error.is_some() && malformed.is_dead(),
+            "{malformed:?}"
+        );
+        assert_eq!(malformed.matches, vec![None; 3]);
+    }
+}
+
+#[test]
+fn fallbacks_cover_each_item() {
+    let cs = pages();
+    let report = cs.check_queries(&["css:.price || css:.amount || json:price"]);
+    assert_eq!(report[0].matches, vec![Some(1), Some(1), Some(1)]);
+    assert!(cs.check_queries(&[]).is_empty());
+}
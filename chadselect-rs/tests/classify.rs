@@ -0,0 +1,70 @@
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, Classifier};
+
+fn routing() -> Classifier {
+    Classifier::new()
+        .indicator("captcha", "css:.g-recaptcha", 0.9)
+        .indicator("captcha", "regex:(?i)verify you are human", 0.6)
+        .indicator("error", "regex:Access Denied|503 Service Unavailable", 0.8)
+        .indicator("error", "json:error.message", 0.9)
+        .indicator("error", "css:.error >> lowercase()", 0.5)
+        .indicator("product", "css:.price >> to-number()", 0.7)
+}
+
+#[test]
+fn picks_the_label_with_the_most_evidence() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<div class="g-recaptcha"></div><p>Verify you are human</p>"#.to_string());
+    cs.add_json(r#"{"error": {"message": "rate limited"}}"#.to_string());
+    cs.add_html(r#"<span class="price">$28,500</span>"#.to_string());
+    cs.add_html(
+        r#"<h1>Access Denied</h1><p class="error">Blocked</p><span class="price">$1</span>"#
+            .to_string(),
+    );
+
+    let captcha = cs.classify(0, &routing()).unwrap();
+    assert_eq!(captcha.label, "captcha");
+    assert!((captcha.confidence - 0.96).abs() < 1e-9);
+    assert_eq!(
+        captcha.indicators,
+        ["css:.g-recaptcha", "regex:(?i)verify you are human"]
+    );
+
+    let api = cs.classify(1, &routing()).unwrap();
+    assert_eq!((api.label.as_str(), api.indicators.len()), ("error", 1));
+
+    assert_eq!(cs.classify(2, &routing()).unwrap().label, "product");
+
+    // 1 − 0.2 × 0.5 = 0.9 for error beats 0.7 for product.
+    let blocked = cs.classify(3, &routing()).unwrap();
+    assert_eq!(blocked.label, "error");
+    assert!((blocked.confidence - 0.9).abs() < 1e-9);
+}
+
+#[test]
+fn nothing_present_or_below_threshold_is_none() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>Welcome</p>".to_string());
+    cs.add_html(r#"<p class="error">Try again</p>"#.to_string());
+
+    assert!(cs.classify(0, &routing()).is_none());
+    assert!(cs.classify(9, &routing()).is_none());
+    assert_eq!(cs.classify(1, &routing()).unwrap().label, "error");
+    assert!(cs.classify(1, &routing().min_confidence(0.6)).is_none());
+}
+
+#[test]
+fn invalid_indicators_are_skipped_and_weights_clamped() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("Access Denied".to_string());
+
+    let classifier = Classifier::new()
+        .indicator("broken", "css:p[", 1.0)
+        .indicator("error", "regex:Denied", 7.0);
+    assert_eq!(classifier.labels(), ["broken", "error"]);
+
+    let error = cs.classify(0, &classifier).unwrap();
+    assert_eq!((error.label.as_str(), error.confidence), ("error", 1.0));
+    assert!(!cs.take_warnings().is_empty());
+}
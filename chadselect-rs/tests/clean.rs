@@ -0,0 +1,105 @@
+//! Tests for `clean_html` and `add_html_cleaned`.
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::clean::clean_html;
+use chadselect::ChadSelect;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head>
+  <title>Civic review</title>
+  <style>.x { color: red }</style>
+  <script>track("pageview");</script>
+  <script type="application/ld+json">{"@type": "Product", "name": "Civic"}</script>
+</head>
+<body class="has-sidebar">
+  <header role="banner"><a href="/">Motor Weekly</a></header>
+  <div class="breadcrumbs">Home › Reviews</div>
+  <main>
+    <article class="post">
+      <h1>2021 Civic review</h1>
+      <p>Price as tested: $28,500.</p>
+      <div class="post-share">Share on Facebook</div>
+      <div id="comments"><p>First! Mine cost $19,999.</p></div>
+    </article>
+  </main>
+  <div class="ad-slot">Financing from $199/mo</div>
+  <div class="cookie-consent">We use cookies</div>
+  <section class="related-posts">Best hybrids</section>
+  <div role="complementary">Popular now</div>
+  <noscript>Enable JavaScript</noscript>
+  <!-- build 1234 -->
+  <footer>© 2024 Motor Weekly</footer>
+</body></html>"#;
+
+#[test]
+fn boilerplate_is_removed() {
+    let cleaned = clean_html(PAGE);
+    for gone in [
+        "color: red",
+        "pageview",
+        "Motor Weekly",
+        "Home › Reviews",
+        "Share on Facebook",
+        "First!",
+        "$199/mo",
+        "cookies",
+        "Best hybrids",
+        "Popular now",
+        "Enable JavaScript",
+        "build 1234",
+        "© 2024",
+    ] {
+        assert!(!cleaned.contains(gone), "{gone:?} survived cleaning");
+    }
+}
+
+#[test]
+fn content_and_structure_are_kept() {
+    let cleaned = clean_html(PAGE);
+    assert!(cleaned.contains("<title>Civic review</title>"));
+    assert!(cleaned.contains(r#"<body class="has-sidebar">"#));
+    assert!(cleaned.contains("<h1>2021 Civic review</h1>"));
+    assert!(cleaned.contains("Price as tested: $28,500."));
+    // JSON data scripts stay for structured-data queries.
+    assert!(cleaned.contains(r#""name": "Civic""#));
+}
+
+#[test]
+fn class_words_match_whole_words_only() {
+    let cleaned = clean_html(
+        r#"<div class="download-area">Get the brochure</div>
+           <div class="navigator">Route planner</div>
+           <div id="main_nav">Menu</div>"#,
+    );
+    assert!(cleaned.contains("Get the brochure"));
+    assert!(cleaned.contains("Route planner"));
+    assert!(!cleaned.contains("Menu"));
+}
+
+#[test]
+fn cleaned_content_is_queryable() {
+    let mut cleaned = ChadSelect::new();
+    cleaned.add_html_cleaned(PAGE.to_string());
+    let mut raw = ChadSelect::new();
+    raw.add_html(PAGE.to_string());
+
+    assert_eq!(cleaned.query(-1, r"regex:\$[\d,]+"), vec!["$28,500"]);
+    assert_eq!(
+        raw.query(-1, r"regex:\$[\d,]+"),
+        vec!["$28,500", "$19,999", "$199"]
+    );
+    assert_eq!(cleaned.select(0, "css:h1"), "2021 Civic review");
+    assert_eq!(cleaned.select(0, "structured:jsonld[0].name"), "Civic");
+    assert!(cleaned.query(-1, "css:footer").is_empty());
+}
+
+#[test]
+fn size_limits_apply_to_the_cleaned_content() {
+    let limit = clean_html(PAGE).len();
+    assert!(limit < PAGE.len());
+
+    let mut cs = ChadSelect::builder().max_item_bytes(limit).build();
+    cs.add_html_cleaned(PAGE.to_string());
+    cs.add_html(PAGE.to_string());
+    assert_eq!(cs.content_count(), 1);
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "css")]
+
+use chadselect::ChadSelect;
+
+fn c() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<html><body>
+        <span class="status">Sold Out</span>
+        <span class="badge">New</span>
+        <span class="badge">Certified</span>
+    </body></html>"#.to_string());
+    cs
+}
+
+#[test]
+fn if_empty_supplies_a_default() {
+    let cs = c();
+    assert_eq!(cs.select(0, "css:.color >> if-empty('unknown')"), "unknown");
+    assert_eq!(cs.query(-1, "css:.badge >> if-empty('none')"), vec!["New", "Certified"]);
+    // After a filtering step that drops everything.
+    assert_eq!(cs.select(0, "css:.badge >> substring-after('X') >> if-empty('n/a')"), "n/a");
+}
+
+#[test]
+fn if_contains_branches() {
+    let cs = c();
+    assert_eq!(cs.select(0, "css:.status >> if-contains('Sold', 'SOLD', 'AVAILABLE')"), "SOLD");
+    assert_eq!(
+        cs.query(-1, "css:.badge >> if-contains('New', 'NEW', 'USED')"),
+        vec!["NEW", "USED"]
+    );
+    // No else-branch: non-matching values pass through.
+    assert_eq!(
+        cs.query(-1, "css:.badge >> if-contains('New', 'NEW')"),
+        vec!["NEW", "Certified"]
+    );
+}
@@ -0,0 +1,107 @@
+//! Tests for TOML and INI content (`add_toml`, `add_ini`).
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::formats::ini_to_json;
+use chadselect::ChadSelect;
+
+const INI: &str = r#"
+; global settings
+name = demo
+debug: "true"
+
+[database]
+host = db.example.com
+port = 5432
+# comment
+password = 'p@ss=word'
+
+[server]
+host = 0.0.0.0
+"#;
+
+// ─── INI ────────────────────────────────────────────────────────────────────
+
+#[test]
+fn ini_sections_and_top_level_keys() {
+    let mut cs = ChadSelect::new();
+    cs.add_ini(INI.to_string());
+
+    assert_eq!(cs.select(0, "json:name"), "demo");
+    assert_eq!(cs.select(0, "json:debug"), "true");
+    assert_eq!(cs.select(0, "json:database.host"), "db.example.com");
+    assert_eq!(cs.select(0, "json:database.port"), "5432");
+    assert_eq!(cs.select(0, "json:database.password"), "p@ss=word");
+    assert_eq!(cs.query(-1, "json:*.host"), vec!["db.example.com", "0.0.0.0"]);
+}
+
+#[test]
+fn ini_regex_runs_on_raw_source() {
+    let mut cs = ChadSelect::new();
+    cs.add_ini(INI.to_string());
+    assert_eq!(cs.select(0, r"regex:port = (\d+)"), "5432");
+}
+
+#[test]
+fn ini_is_not_html() {
+    let mut cs = ChadSelect::new();
+    cs.add_ini(INI.to_string());
+    assert!(cs.query(-1, "css:*").is_empty());
+}
+
+#[test]
+fn ini_parse_errors() {
+    assert!(ini_to_json("[unclosed").is_err());
+    assert!(ini_to_json("just words").is_err());
+    assert!(ini_to_json("").unwrap().as_object().unwrap().is_empty());
+}
+
+// ─── TOML ───────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "toml")]
+mod toml_content {
+    use chadselect::ChadSelect;
+
+    const CARGO: &str = r#"
+[package]
+name = "chadselect"
+version = "0.4.4"
+keywords = ["scraping", "xpath"]
+published = 2024-05-01T10:00:00Z
+
+[dependencies]
+regex = "1"
+scraper = { version = "0.21", default-features = false }
+
+[[bench]]
+name = "engine_bench"
+harness = false
+"#;
+
+    fn make_cs() -> ChadSelect {
+        let mut cs = ChadSelect::new();
+        cs.add_toml(CARGO.to_string());
+        cs
+    }
+
+    #[test]
+    fn toml_tables_and_arrays() {
+        let cs = make_cs();
+        assert_eq!(cs.select(0, "json:package.version"), "0.4.4");
+        assert_eq!(cs.query(-1, "json:package.keywords"), vec!["scraping", "xpath"]);
+        assert_eq!(cs.select(0, "json:dependencies.scraper.version"), "0.21");
+        assert_eq!(cs.select(0, "json:bench[0].harness"), "false");
+    }
+
+    #[test]
+    fn toml_datetime_as_string() {
+        let cs = make_cs();
+        assert_eq!(cs.select(0, "json:package.published"), "2024-05-01T10:00:00Z");
+    }
+
+    #[test]
+    fn invalid_toml_returns_empty() {
+        let mut cs = ChadSelect::new();
+        cs.add_toml("not = = toml".to_string());
+        assert!(cs.query(-1, "json:not").is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! Tests for `extract_contacts` and the `extract-emails()` /
+//! `extract-phones()` pipe functions (phones need the `phone` feature).
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::functions::try_parse_text_functions;
+use chadselect::ChadSelect;
+
+const PAGE: &str = r#"<html><head><script>var support = "script@example.com";</script></head>
+<body><p>Sales: Sales@Example.com, or support@help.example.co.uk.</p>
+<p>Call +1 (212) 555-0100 or +44 20 7946 0958. Local: (415) 555-0132.</p>
+<a href="mailto:sales@example.com?subject=Civic">Email us</a>
+<a href="tel:+1-646-555-0142">Call</a>
+<a href="/contact">Contact</a></body></html>"#;
+
+fn page() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(PAGE.to_string());
+    cs
+}
+
+#[test]
+fn extract_contacts_reads_visible_text_and_mailto_links() {
+    let contacts = page().extract_contacts(0);
+    assert_eq!(
+        contacts.emails,
+        ["Sales@Example.com", "support@help.example.co.uk"]
+    );
+}
+
+#[test]
+fn extract_contacts_reads_raw_text_of_other_content() {
+    let mut cs = ChadSelect::new();
+    cs.add_json(r#"{"dealer": {"email": "info@civicmotors.com"}}"#.to_string());
+    assert_eq!(cs.extract_contacts(0).emails, ["info@civicmotors.com"]);
+    assert_eq!(cs.extract_contacts(1), Default::default());
+}
+
+#[test]
+fn extract_emails_yields_one_result_per_address() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("Write to a@example.com or B@example.org; not @example.com or a@b.".to_string());
+    assert_eq!(
+        cs.query(-1, "regex:.+ >> extract-emails()"),
+        vec!["a@example.com", "B@example.org"]
+    );
+    assert!(cs.query(-1, "regex:not.+ >> extract-emails()").is_empty());
+}
+
+#[test]
+fn extract_functions_check_their_arguments() {
+    assert!(try_parse_text_functions("extract-emails()").is_ok());
+    assert!(try_parse_text_functions("extract-emails('x')").is_err());
+    assert!(try_parse_text_functions("extract-phones()").is_err());
+    #[cfg(not(feature = "phone"))]
+    assert!(try_parse_text_functions("extract-phones('US')").is_err());
+}
+
+#[cfg(feature = "phone")]
+mod phone {
+    use super::*;
+
+    #[test]
+    fn extract_contacts_finds_international_numbers() {
+        assert_eq!(
+            page().extract_contacts(0).phones,
+            ["+12125550100", "+442079460958", "+16465550142"]
+        );
+    }
+
+    #[test]
+    fn extract_phones_reads_national_numbers_of_the_region() {
+        assert_eq!(
+            page().query(-1, "css:p >> extract-phones('us')"),
+            vec!["+12125550100", "+442079460958", "+14155550132"]
+        );
+    }
+
+    #[test]
+    fn invalid_numbers_and_regions_are_rejected() {
+        let mut cs = ChadSelect::new();
+        cs.add_text("Order 1234567, ref 000-000-0000".to_string());
+        assert!(cs.query(-1, "regex:.+ >> extract-phones('US')").is_empty());
+        assert!(try_parse_text_functions("extract-phones('XX')").is_err());
+    }
+}
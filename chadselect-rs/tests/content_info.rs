@@ -0,0 +1,84 @@
+//! Tests for `content_info`.
+#![cfg(all(feature = "info", feature = "css", feature = "json"))]
+
+use chadselect::{ChadSelect, ContentType};
+
+#[test]
+fn html_language_title_and_charset() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<!DOCTYPE html><html lang="fr-CA"><head>
+             <meta http-equiv="Content-Type" content="text/html; charset=ISO-8859-15">
+             <title>
+               Voitures   d'occasion
+             </title>
+             <script>var message = "this English text inside a script is not counted at all";</script>
+           </head><body>
+             <p>Cette voiture est en excellent état et a toujours été entretenue chez le concessionnaire.</p>
+           </body></html>"#
+            .to_string(),
+    );
+    let info = cs.content_info(0).unwrap();
+    assert_eq!(info.content_type, ContentType::Html);
+    assert_eq!(info.language.as_deref(), Some("fra"));
+    assert!(info.language_confidence > 0.5);
+    assert!(info.is_language("fra", 0.5));
+    assert!(!info.is_language("eng", 0.0));
+    assert_eq!(info.declared_language.as_deref(), Some("fr-CA"));
+    assert_eq!(info.declared_charset.as_deref(), Some("iso-8859-15"));
+    assert_eq!(info.charset, "UTF-8");
+    assert_eq!(info.title.as_deref(), Some("Voitures d'occasion"));
+    assert_eq!(info.source, None);
+    assert_eq!(info.bytes, cs.content_bytes());
+}
+
+#[test]
+fn markdown_title_is_the_first_heading() {
+    let mut cs = ChadSelect::new();
+    cs.add_markdown("Intro line\n\n# Kaufberatung\n\nDieses Auto ist sparsam, zuverlässig und günstig im Unterhalt.\n".to_string());
+    let info = cs.content_info(0).unwrap();
+    assert_eq!(info.title.as_deref(), Some("Kaufberatung"));
+    assert_eq!(info.language.as_deref(), Some("deu"));
+    assert_eq!(info.declared_language, None);
+}
+
+#[test]
+fn json_language_uses_string_values() {
+    let mut cs = ChadSelect::new();
+    cs.add_json(
+        r#"{"description": "El coche está en muy buen estado y siempre ha dormido en garaje.",
+            "price": 12500, "tags": ["económico", "fiable"]}"#
+            .to_string(),
+    );
+    let info = cs.content_info(0).unwrap();
+    assert_eq!(info.language.as_deref(), Some("spa"));
+    assert_eq!(info.title, None);
+    assert_eq!(info.declared_charset, None);
+}
+
+#[test]
+fn xml_declaration_charset() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(r#"<?xml version="1.0" encoding="Shift_JIS"?><feed/>"#.to_string());
+    let info = cs.content_info(0).unwrap();
+    assert_eq!(info.declared_charset.as_deref(), Some("shift_jis"));
+}
+
+#[test]
+fn undetectable_and_out_of_range() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("12345 67890".to_string());
+    let info = cs.content_info(0).unwrap();
+    assert_eq!(info.language, None);
+    assert_eq!(info.language_confidence, 0.0);
+    assert!(cs.content_info(1).is_none());
+}
+
+#[test]
+#[cfg(feature = "snapshot")]
+fn charset_survives_snapshots() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<title>Kept</title>".to_string());
+    let restored = ChadSelect::from_snapshot(&cs.snapshot());
+    assert_eq!(restored.content_info(0), cs.content_info(0));
+}
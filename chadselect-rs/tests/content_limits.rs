@@ -0,0 +1,142 @@
+//! Tests for content size limits and memory-usage inspection.
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, ContentType, LimitPolicy};
+
+fn page(n: usize) -> String {
+    format!("<p>{}</p>", "x".repeat(n))
+}
+
+// ─── Per-item limit ─────────────────────────────────────────────────────────
+
+#[test]
+fn no_limits_by_default() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page(100_000));
+    assert_eq!(cs.content_count(), 1);
+}
+
+#[test]
+fn item_limit_rejects() {
+    let mut cs = ChadSelect::builder().max_item_bytes(50).build();
+    cs.add_html(page(10));
+    cs.add_html(page(100));
+    assert_eq!(cs.content_count(), 1);
+
+    let err = cs.try_add(page(100), ContentType::Html).unwrap_err();
+    assert!(err.contains("item limit"), "{}", err);
+}
+
+#[test]
+fn item_limit_truncates() {
+    let mut cs = ChadSelect::builder()
+        .max_item_bytes(10)
+        .limit_policy(LimitPolicy::Truncate)
+        .build();
+    cs.add_text("0123456789abcdef".to_string());
+    assert_eq!(cs.content_bytes(), 10);
+    assert_eq!(cs.select(0, r"regex:(\w+)"), "0123456789");
+}
+
+#[test]
+fn truncation_respects_char_boundaries() {
+    let mut cs = ChadSelect::builder()
+        .max_item_bytes(5)
+        .limit_policy(LimitPolicy::Truncate)
+        .build();
+    cs.add_text("ééé".to_string()); // 6 bytes
+    assert_eq!(cs.select(0, "regex:(.+)"), "éé");
+}
+
+#[test]
+fn binary_items_count_their_own_bytes() {
+    // Every byte is above 0x7f, so each would take two bytes as UTF-8.
+    let blob = [0xde, 0xad, 0xbe, 0xef].repeat(4);
+    let mut cs = ChadSelect::builder().max_item_bytes(16).build();
+    cs.add_bytes(&blob);
+    assert_eq!(cs.content_count(), 1);
+    assert_eq!(cs.content_bytes(), 16);
+    assert_eq!(cs.memory_usage().content_bytes, 16);
+
+    cs.add_bytes(&[0xff; 17]);
+    assert_eq!(cs.content_count(), 1);
+
+    let mut cs = ChadSelect::builder()
+        .max_item_bytes(3)
+        .limit_policy(LimitPolicy::Truncate)
+        .build();
+    cs.add_bytes(&blob);
+    assert_eq!(cs.content_bytes(), 3);
+    assert_eq!(cs.select(0, "hex:de ad be"), "deadbe");
+    assert!(cs.query(-1, "hex:ef").is_empty());
+}
+
+#[test]
+fn evict_oldest_still_refuses_oversized_item() {
+    let mut cs = ChadSelect::builder()
+        .max_item_bytes(20)
+        .limit_policy(LimitPolicy::EvictOldest)
+        .build();
+    assert!(cs.try_add(page(50), ContentType::Html).is_err());
+}
+
+// ─── Total limit ────────────────────────────────────────────────────────────
+
+#[test]
+fn total_limit_rejects() {
+    let mut cs = ChadSelect::builder().max_total_bytes(30).build();
+    cs.add_text("a".repeat(20));
+    let err = cs.try_add("b".repeat(20), ContentType::Text).unwrap_err();
+    assert!(err.contains("total limit"), "{}", err);
+    assert_eq!(cs.content_bytes(), 20);
+}
+
+#[test]
+fn total_limit_truncates_to_remaining_budget() {
+    let mut cs = ChadSelect::builder()
+        .max_total_bytes(30)
+        .limit_policy(LimitPolicy::Truncate)
+        .build();
+    cs.add_text("a".repeat(20));
+    cs.add_text("b".repeat(20));
+    assert_eq!(cs.content_bytes(), 30);
+    assert_eq!(cs.select(0, "regex:(b+)"), "b".repeat(10));
+
+    // Budget exhausted: further items are refused.
+    assert!(cs.try_add("c".to_string(), ContentType::Text).is_err());
+}
+
+#[test]
+fn total_limit_evicts_oldest() {
+    let mut cs = ChadSelect::builder()
+        .max_total_bytes(30)
+        .limit_policy(LimitPolicy::EvictOldest)
+        .build();
+    cs.add_text("a".repeat(10));
+    cs.add_text("b".repeat(10));
+    cs.add_text("c".repeat(10));
+    cs.add_text("d".repeat(15));
+
+    assert_eq!(cs.content_count(), 2);
+    assert_eq!(cs.content_bytes(), 25);
+    assert_eq!(cs.query(-1, "regex:^(\\w)"), vec!["c", "d"]);
+}
+
+// ─── Usage inspection ───────────────────────────────────────────────────────
+
+#[test]
+fn memory_usage_reports_parsed_items() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page(5));
+    cs.add_json(r#"{"a": 1}"#.to_string());
+    cs.add_text("plain".to_string());
+
+    let before = cs.memory_usage();
+    assert_eq!(before.items, 3);
+    assert_eq!(before.content_bytes, cs.content_bytes());
+    assert_eq!(before.parsed_items, 0);
+
+    cs.query(-1, "css:p");
+    cs.query(-1, "json:a");
+    assert_eq!(cs.memory_usage().parsed_items, 2);
+}
@@ -0,0 +1,91 @@
+//! Tests for the `context()` pipe function.
+#![cfg(all(feature = "css", feature = "regex"))]
+
+use chadselect::engine::regex::process;
+use chadselect::functions::try_parse_text_functions;
+use chadselect::{ChadSelect, ContentType};
+
+const LOG: &str = "12:00 ok\r\n12:01 upstream timeout after 30s\r\n12:02 ok — done\r\n";
+
+fn log() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(LOG.to_string());
+    cs
+}
+
+#[test]
+fn character_windows_clip_at_the_content_edges() {
+    let cs = log();
+    assert_eq!(
+        cs.query(-1, "regex:timeout >> context(9)"),
+        vec!["upstream timeout after 30"]
+    );
+    assert_eq!(cs.query(-1, "regex:^12:00 >> context(3)"), vec!["12:00 ok"]);
+    // Characters, not bytes: the em dash counts once.
+    assert_eq!(
+        cs.query(-1, "regex:done >> context(3)"),
+        vec![" — done\r\n"]
+    );
+    assert_eq!(
+        cs.query(-1, "regex:upstream >> context(0)"),
+        vec!["upstream"]
+    );
+}
+
+#[test]
+fn line_windows_cover_the_whole_line() {
+    let cs = log();
+    assert_eq!(
+        cs.query(-1, "regex:timeout >> context('line')"),
+        vec!["12:01 upstream timeout after 30s"]
+    );
+    assert_eq!(
+        cs.query(-1, r"regex:\bok\b >> context()"),
+        vec!["12:00 ok", "12:02 ok — done"]
+    );
+}
+
+#[test]
+fn context_reads_the_match_not_the_chain_value() {
+    let cs = log();
+    assert_eq!(
+        cs.query(
+            -1,
+            r"regex:(\d+)s >> uppercase() >> context('line') >> uppercase()"
+        ),
+        vec!["12:01 UPSTREAM TIMEOUT AFTER 30S"]
+    );
+    assert_eq!(
+        cs.query_detailed(-1, r"regex:timeout >> context(4)")
+            .locations[0]
+            .span,
+        Some(25..32)
+    );
+    assert_eq!(
+        process("timeout >> context(5)", LOG, &ContentType::Text),
+        vec!["ream timeout afte"]
+    );
+}
+
+#[test]
+fn other_engines_and_folds_are_unchanged() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<p class="status">upstream timeout</p>"#.to_string());
+    assert_eq!(
+        cs.query(-1, "css:.status >> context(3)"),
+        vec!["upstream timeout"]
+    );
+    assert_eq!(
+        log().query(-1, r"regex:\d\d:\d\d >> join(',') >> context(2)"),
+        vec!["12:00,12:01,12:02"]
+    );
+}
+
+#[test]
+fn arguments_are_checked() {
+    assert!(try_parse_text_functions("context(80)").is_ok());
+    assert!(try_parse_text_functions("context('line')").is_ok());
+    assert!(try_parse_text_functions("context('80')").is_err());
+    assert!(try_parse_text_functions("context(-1)").is_err());
+    assert!(try_parse_text_functions("context(1, 2)").is_err());
+}
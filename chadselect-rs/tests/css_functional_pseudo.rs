@@ -0,0 +1,107 @@
+//! Tests for the `:has()` / `:is()` / `:where()` / `:not()` fallback layer.
+#![cfg(feature = "css")]
+
+use chadselect::ChadSelect;
+
+const HTML: &str = r#"
+<div class="specs">
+  <div class="row"><span class="label">Price</span><b>$1</b></div>
+  <div class="row"><span class="label">Mileage</span><b>42,000</b></div>
+  <div class="row"><i>note</i><b>n/a</b></div>
+  <section><p><span>deep</span></p><em>x</em></section>
+  <section><p>shallow</p><em>y</em></section>
+</div>
+"#;
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+#[test]
+fn has_with_text_pseudo() {
+    let cs = make_cs();
+    assert_eq!(
+        cs.query(-1, "css:div.row:has(span:text-equals('Price')) b"),
+        vec!["$1"]
+    );
+}
+
+#[test]
+fn nested_has() {
+    let cs = make_cs();
+    assert_eq!(cs.query(-1, "css:section:has(p:has(span)) em"), vec!["x"]);
+}
+
+#[test]
+fn has_relative_combinators() {
+    let cs = make_cs();
+    assert_eq!(cs.query(-1, "css:.row:has(> i):has(> b) b"), vec!["n/a"]);
+    assert_eq!(
+        cs.query(-1, "css:span:has(+ b:text-starts('4')):has(~ b)"),
+        vec!["Mileage"]
+    );
+}
+
+#[test]
+fn is_and_where_with_text_pseudo() {
+    let cs = make_cs();
+    assert_eq!(
+        cs.query(-1, "css:.row > :is(span:text-equals('Price'), i)"),
+        vec!["Price", "note"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:.row > :where(span:text-ends('age'))"),
+        vec!["Mileage"]
+    );
+}
+
+#[test]
+fn not_with_nested_has() {
+    let cs = make_cs();
+    assert_eq!(
+        cs.query(-1, "css:.row:not(:has(span:text-equals('Price'))) b"),
+        vec!["42,000", "n/a"]
+    );
+}
+
+#[test]
+fn selector_list_with_fallback() {
+    let cs = make_cs();
+    assert_eq!(
+        cs.query(-1, "css:section:has(p:has(span)) em, .row:has(i) b"),
+        vec!["n/a", "x"]
+    );
+}
+
+#[test]
+fn functions_and_attributes_still_apply() {
+    let cs = make_cs();
+    assert_eq!(
+        cs.select(0, "css:.row:has(span:has-text('Mile')) b >> uppercase()"),
+        "42,000"
+    );
+    assert_eq!(
+        cs.query(
+            -1,
+            "css:.row:has(span:text-equals('Price')) >> get-attr('class')"
+        ),
+        vec!["row"]
+    );
+}
+
+#[test]
+fn native_selectors_unchanged() {
+    let cs = make_cs();
+    assert_eq!(cs.query(-1, "css:.row:has(i) b"), vec!["n/a"]);
+    assert_eq!(cs.query(-1, "css:.row:has-text('Price') b"), vec!["$1"]);
+}
+
+#[test]
+fn invalid_fallback_selector_is_empty() {
+    let cs = make_cs();
+    assert!(cs.query(-1, "css:div:has(p:has(span)) > > b").is_empty());
+    assert!(cs.query(-1, "css:div:has(p:has(span) b").is_empty());
+    assert!(cs.query(-1, "css:div:has(p:has([[)) b").is_empty());
+}
@@ -1,4 +1,5 @@
 //! Tests for the CSS selector extraction engine.
+#![cfg(feature = "css")]
 
 use chadselect::ChadSelect;
 
@@ -97,6 +98,61 @@ fn css_get_attr_missing() {
     assert!(results.is_empty());
 }
 
+fn make_links_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<nav>
+            <a href="/p/1" title="First">1</a>
+            <a href="/p/2">2</a>
+            <a href="/p/3" title="Next page" rel="next">Next</a>
+            <a href="/cart"><img src="/cart.png" alt="Cart"></a>
+        </nav>"#
+            .to_string(),
+    );
+    cs
+}
+
+#[test]
+fn get_attr_after_filters_reads_the_kept_elements() {
+    let cs = make_links_cs();
+    assert_eq!(cs.query(-1, "css:a >> matches('Next') >> get-attr('href')"), vec!["/p/3"]);
+    assert_eq!(cs.query(-1, "css:a >> matches('^\\d$') >> last() >> get-attr('href')"), vec!["/p/2"]);
+    assert_eq!(
+        cs.query(-1, "css:a >> uppercase() >> sort() >> get-attr('href')"),
+        vec!["/p/1", "/p/2", "/p/3"]
+    );
+    // Functions after get-attr act on the attribute value.
+    assert_eq!(
+        cs.query(-1, "css:a >> get-attr('href') >> substring-after-last('/')"),
+        vec!["1", "2", "3", "cart"]
+    );
+    // An element without text still yields its attributes.
+    assert_eq!(cs.query(-1, "css:img >> get-attr('alt') >> uppercase()"), vec!["CART"]);
+    // After a fold there is no element; the value is kept unchanged.
+    assert_eq!(cs.query(-1, "css:a >> join(',') >> get-attr('href')"), vec!["1,2,Next"]);
+}
+
+#[test]
+fn get_attrs_pairs_values() {
+    let cs = make_links_cs();
+    assert_eq!(
+        cs.query(-1, "css:a >> get-attrs('href', 'title')"),
+        vec!["/p/1|First", "/p/2|", "/p/3|Next page", "/cart|"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:a[rel] >> get-attrs('rel', 'href') >> uppercase()"),
+        vec!["NEXT|/P/3"]
+    );
+    // Elements with none of the attributes are dropped.
+    assert!(cs.query(-1, "css:a >> get-attrs('data-x', 'data-y')").is_empty());
+}
+
+#[test]
+fn get_attr_after_text_pseudo_selector() {
+    let cs = make_links_cs();
+    assert_eq!(cs.query(-1, "css:a:text-equals('Next') >> get-attr('rel')"), vec!["next"]);
+}
+
 // ─── Text pseudo-selectors ──────────────────────────────────────────────────
 
 const PSEUDO_HTML: &str = r#"
@@ -167,6 +223,34 @@ fn pseudo_with_postprocess_function() {
     assert_eq!(results, vec!["V6 TURBO"]);
 }
 
+#[test]
+fn pseudo_argument_escapes_and_mixed_quotes() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<ul>
+            <li><b>O'Brien</b> <i>owner</i></li>
+            <li><b>Say "hi" (loud)</b> <i>greeting</i></li>
+            <li><b>Back\slash</b> <i>path</i></li>
+        </ul>"#
+            .to_string(),
+    );
+
+    assert_eq!(cs.query(-1, r"css:li:has-text('O\'Brien') i"), vec!["owner"]);
+    assert_eq!(cs.query(-1, r#"css:li:has-text("O'Brien") i"#), vec!["owner"]);
+    // Quoted text may hold parentheses and the other quote.
+    assert_eq!(
+        cs.query(-1, r#"css:b:text-equals('Say "hi" (loud)') + i"#),
+        vec!["greeting"]
+    );
+    assert_eq!(cs.query(-1, r#"css:b:text-ends("\"hi\" (loud)") + i"#), vec!["greeting"]);
+    assert_eq!(cs.query(-1, r"css:b:text-equals('Back\\slash') + i"), vec!["path"]);
+    // Escaped quotes also work in function arguments after the selector.
+    assert_eq!(
+        cs.query(-1, r"css:li:has-text('O\'Brien') b >> replace('\'', '')"),
+        vec!["OBrien"]
+    );
+}
+
 #[test]
 fn pseudo_with_trim() {
     let cs = make_pseudo_cs();
@@ -0,0 +1,77 @@
+//! Tests for the `parse-currency()` and `convert-unit()` pipe functions.
+#![cfg(feature = "css")]
+
+use chadselect::functions::try_parse_text_functions;
+use chadselect::ChadSelect;
+
+fn values(values: &[&str], query: &str) -> Vec<String> {
+    let mut cs = ChadSelect::new();
+    let items: String = values.iter().map(|v| format!("<li>{}</li>", v)).collect();
+    cs.add_html(format!("<ul>{}</ul>", items));
+    cs.query(-1, query)
+}
+
+#[test]
+fn parse_currency_reads_symbols_and_separators() {
+    assert_eq!(
+        values(
+            &["$1,299.00", "1.299,00 €", "£49.9", "US$ 1 299", "¥1500"],
+            "css:li >> parse-currency()"
+        ),
+        vec![
+            "1299.00|USD",
+            "1299.00|EUR",
+            "49.90|GBP",
+            "1299.00|USD",
+            "1500|JPY"
+        ]
+    );
+}
+
+#[test]
+fn parse_currency_reads_iso_codes() {
+    assert_eq!(
+        values(&["CHF 12.5", "49,99 EUR"], "css:li >> parse-currency()"),
+        vec!["12.50|CHF", "49.99|EUR"]
+    );
+}
+
+#[test]
+fn values_without_currency_or_amount_are_dropped() {
+    assert_eq!(
+        values(
+            &["1299", "$", "Call for price", "€5"],
+            "css:li >> parse-currency()"
+        ),
+        vec!["5.00|EUR"]
+    );
+}
+
+#[test]
+fn convert_unit_converts_within_a_kind() {
+    assert_eq!(
+        values(&["12,000 mi"], "css:li >> convert-unit('mi', 'km')"),
+        vec!["19312.128"]
+    );
+    assert_eq!(
+        values(&["212 °F"], "css:li >> convert-unit('°F', 'C')"),
+        vec!["100"]
+    );
+    assert_eq!(
+        values(&["2 lbs", "n/a"], "css:li >> convert-unit('lb', 'kg')"),
+        vec!["0.907185"]
+    );
+}
+
+#[test]
+fn unknown_or_mismatched_units_fail_to_parse() {
+    assert!(try_parse_text_functions("convert-unit('mi', 'km')").is_ok());
+    for chain in [
+        "convert-unit('mi', 'parsec')",
+        "convert-unit('mi', 'kg')",
+        "convert-unit('mi')",
+        "parse-currency('USD')",
+    ] {
+        assert!(try_parse_text_functions(chain).is_err(), "{}", chain);
+    }
+}
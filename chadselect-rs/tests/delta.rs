@@ -0,0 +1,129 @@
+//! Tests for `extraction_snapshot` and `ExtractionSnapshot::diff`.
+#![cfg(all(feature = "delta", feature = "css"))]
+
+use chadselect::{ChadSelect, ColumnType, ExtractionSchema, FieldChange};
+
+fn listing(model: &str, price: &str) -> String {
+    format!(r#"<h1>{}</h1><span class="price">{}</span>"#, model, price)
+}
+
+fn schema() -> ExtractionSchema {
+    ExtractionSchema::new()
+        .column("model", ColumnType::Text, "css:h1")
+        .column("price", ColumnType::Integer, "css:.price")
+        .key("model")
+}
+
+fn load(pages: &[(&str, &str)]) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    for (model, price) in pages {
+        cs.add_html(listing(model, price));
+    }
+    cs
+}
+
+#[test]
+fn snapshot_keys_by_key_column_and_canonicalizes_numbers() {
+    let snapshot =
+        load(&[("Civic", "$28,500"), ("Accord", "call us")]).extraction_snapshot(&schema());
+    assert_eq!(snapshot.columns(), ["model", "price"]);
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot.value("Civic", "price"), Some("28500"));
+    assert_eq!(snapshot.value("Accord", "price"), None);
+    assert_eq!(snapshot.get("Accord").unwrap().content_hash.len(), 64);
+}
+
+#[test]
+fn keys_fall_back_to_source_then_index() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(listing("Civic", "$28,500"));
+    let path = std::env::temp_dir().join(format!("chadselect-delta-{}.html", std::process::id()));
+    std::fs::write(&path, listing("Accord", "$31,000")).unwrap();
+    cs.add_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    cs.add_html("<p>no listing here</p>".to_string());
+
+    let schema = ExtractionSchema::new().column("price", ColumnType::Integer, "css:.price");
+    let snapshot = cs.extraction_snapshot(&schema);
+    let keys: Vec<_> = snapshot.records().iter().map(|r| r.key.as_str()).collect();
+    assert_eq!(keys, ["0", path.display().to_string().as_str()]);
+}
+
+#[test]
+fn duplicate_keys_keep_the_first_record() {
+    let snapshot = load(&[("Civic", "$28,500"), ("Civic", "$1")]).extraction_snapshot(&schema());
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot.value("Civic", "price"), Some("28500"));
+}
+
+#[test]
+fn diff_reports_added_removed_and_changed() {
+    let old = load(&[
+        ("Civic", "$28,500"),
+        ("Accord", "$31,000"),
+        ("Golf", "$24,000"),
+    ])
+    .extraction_snapshot(&schema());
+    let new = load(&[
+        ("Civic", "$27,900"),
+        ("Golf", "24000"),
+        ("Prius", "$30,500"),
+    ])
+    .extraction_snapshot(&schema());
+
+    let diff = old.diff(&new);
+    assert!(!diff.is_empty());
+    let added: Vec<_> = diff.added.iter().map(|r| r.key.as_str()).collect();
+    assert_eq!(added, ["Prius"]);
+    let removed: Vec<_> = diff.removed.iter().map(|r| r.key.as_str()).collect();
+    assert_eq!(removed, ["Accord"]);
+
+    // "$24,000" → "24000" is the same number, so Golf only changed content.
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].key, "Civic");
+    assert_eq!(
+        diff.changed[0].fields,
+        vec![FieldChange {
+            column: "price".to_string(),
+            old: Some("28500".to_string()),
+            new: Some("27900".to_string()),
+        }]
+    );
+    assert_eq!(diff.content_changed, ["Golf"]);
+}
+
+#[test]
+fn identical_runs_have_an_empty_diff() {
+    let pages = [("Civic", "$28,500"), ("Accord", "$31,000")];
+    let old = load(&pages).extraction_snapshot(&schema());
+    let new = load(&pages).extraction_snapshot(&schema());
+    let diff = old.diff(&new);
+    assert!(diff.is_empty());
+    assert!(diff.content_changed.is_empty());
+}
+
+#[test]
+fn columns_are_matched_by_name() {
+    let cs = load(&[("Civic", "$28,500")]);
+    let old = cs.extraction_snapshot(&schema());
+    let new = cs.extraction_snapshot(
+        &ExtractionSchema::new()
+            .column("model", ColumnType::Text, "css:h1")
+            .column("title", ColumnType::Text, "css:h1")
+            .key("model"),
+    );
+
+    let diff = old.diff(&new);
+    let fields: Vec<_> = diff.changed[0]
+        .fields
+        .iter()
+        .map(|f| (f.column.as_str(), f.old.as_deref(), f.new.as_deref()))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("price", Some("28500"), None),
+            ("title", None, Some("Civic"))
+        ]
+    );
+}
@@ -0,0 +1,141 @@
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use chadselect::dom::{DomBackend, Scraper};
+use chadselect::ChadSelect;
+
+fn listing() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<ul id="results">
+            <li class="car" data-vin="1HGCM"><a href="/civic">Civic &amp; Co</a> <b>$28,500</b></li>
+            <li class="car" data-vin="2HGFA"><a href="/accord">Accord</a> <b>$31,000</b></li>
+        </ul>"#
+            .to_string(),
+    );
+    cs.add_json(r#"{"car": "not html"}"#.to_string());
+    cs.add_html(r#"<div><span class="car" data-vin="3FADP">Focus</span></div>"#.to_string());
+    cs
+}
+
+/// The same checks on every backend: results must match `query` for plain
+/// selectors.
+fn agrees_with_query<B: DomBackend>() {
+    let cs = listing();
+    for query in [
+        "css:li.car a",
+        "css:#results b",
+        "css:#results > li > a, span.car",
+        "css:.car",
+        "css:.car >> get-attr('data-vin')",
+        "css:li a >> get-attr('href') >> uppercase()",
+        "css:.car b >> to-number() >> sum()",
+    ] {
+        assert_eq!(
+            cs.query_dom::<B>(-1, query),
+            cs.query(-1, query),
+            "{}",
+            query
+        );
+    }
+    assert_eq!(cs.query_dom::<B>(1, "css:.car a"), ["Accord"]);
+    assert!(cs.query_dom::<B>(5, "css:.car a").is_empty());
+    assert_eq!(
+        cs.query_dom::<B>(-1, "css:video || css:span.car"),
+        ["Focus"]
+    );
+}
+
+#[test]
+fn scraper_backend() {
+    agrees_with_query::<Scraper>();
+}
+
+#[cfg(feature = "tl")]
+#[test]
+fn tl_backend() {
+    agrees_with_query::<chadselect::dom::Tl>();
+}
+
+#[cfg(feature = "lol-html")]
+#[test]
+fn lol_html_backend() {
+    agrees_with_query::<chadselect::dom::LolHtml>();
+}
+
+#[test]
+fn non_css_alternatives_are_skipped_with_a_warning() {
+    let cs = listing();
+    let result = cs.query_dom::<Scraper>(-1, "xpath://li/a || css:span.car");
+    assert_eq!(result, ["Focus"]);
+    assert!(cs
+        .take_warnings()
+        .iter()
+        .any(|w| w.message.contains("query_dom runs plain css:")));
+
+    assert!(cs
+        .query_dom::<Scraper>(-1, "css:li:has-text('Civic')")
+        .is_empty());
+}
+
+#[test]
+fn markdown_is_rendered_first() {
+    let mut cs = ChadSelect::new();
+    cs.add_markdown("# Inventory\n\n- [Civic](/civic)\n".to_string());
+    assert_eq!(
+        cs.query_dom::<Scraper>(-1, "css:li a >> get-attr('href')"),
+        ["/civic"]
+    );
+}
+
+thread_local! {
+    static PARSES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// `Scraper`, counting the documents it parses.
+struct Counting;
+
+impl DomBackend for Counting {
+    type Document<'a> = scraper::Html;
+
+    fn parse(html: &str) -> Result<scraper::Html, String> {
+        PARSES.with(|parses| parses.set(parses.get() + 1));
+        Scraper::parse(html)
+    }
+
+    fn select(
+        document: &scraper::Html,
+        selector: &str,
+    ) -> Result<Vec<chadselect::dom::DomElement>, String> {
+        Scraper::select(document, selector)
+    }
+}
+
+#[test]
+fn selections_are_cached_per_item() {
+    let cs = listing();
+    let parses = || PARSES.with(|parses| parses.get());
+    assert_eq!(
+        cs.query_dom::<Counting>(-1, "css:.car"),
+        cs.query(-1, "css:.car")
+    );
+    assert_eq!(parses(), 2, "one parse per HTML item");
+    cs.query_dom::<Counting>(0, "css:.car >> get-attr('data-vin')");
+    assert_eq!(parses(), 2, "same selector, no parse");
+    cs.query_dom::<Counting>(-1, "css:b");
+    assert_eq!(parses(), 4);
+
+    cs.drop_caches();
+    cs.query_dom::<Counting>(-1, "css:.car");
+    assert_eq!(parses(), 6);
+}
+
+#[test]
+fn navigation_warns() {
+    let cs = listing();
+    assert!(cs
+        .query_dom::<Scraper>(-1, "css:a >> closest('li')")
+        .is_empty());
+    assert!(cs.take_warnings().iter().any(|w| w
+        .message
+        .contains("closest() in 'a >> closest('li')' finds nothing")));
+}
@@ -0,0 +1,100 @@
+//! Tests for the `embedded-json:` script-tag bridge.
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use chadselect::{ChadSelect, PreparedQuery};
+
+const HTML: &str = r#"<html><head>
+<script type="application/ld+json">
+{"@type": "Product", "name": "Widget", "offers": {"price": "19.99", "priceCurrency": "USD"}}
+</script>
+<script type="application/ld+json">{"@type": "BreadcrumbList", "itemListElement": []}</script>
+<script>window.__INITIAL_STATE__ = {"cart": {"count": 3}, "user": null};</script>
+<script>console.log("no json here");</script>
+</head><body>
+<div class="product" data-json='{"sku": "W-1", "tags": ["a", "b"]}'>Widget</div>
+</body></html>"#;
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+#[test]
+fn ld_json_path() {
+    let cs = make_cs();
+    let price = cs.select(
+        0,
+        "embedded-json:css:script[type='application/ld+json'] >> json:offers.price",
+    );
+    assert_eq!(price, "19.99");
+}
+
+#[test]
+fn every_blob_is_queried() {
+    let cs = make_cs();
+    let types = cs.query(-1, r#"embedded-json:css:script[type='application/ld+json'] >> json:"@type""#);
+    assert_eq!(types, vec!["Product", "BreadcrumbList"]);
+}
+
+#[test]
+fn script_assignment() {
+    let cs = make_cs();
+    let count = cs.select(0, "embedded-json:css:script >> json:cart.count");
+    assert_eq!(count, "3");
+}
+
+#[test]
+fn attribute_source_with_its_own_functions() {
+    let cs = make_cs();
+    let sku = cs.select(0, "embedded-json:css:div.product >> get-attr('data-json') >> json:sku");
+    assert_eq!(sku, "W-1");
+}
+
+#[test]
+fn functions_after_json_stage() {
+    let cs = make_cs();
+    let tags = cs.select(
+        0,
+        "embedded-json:css:div.product >> get-attr('data-json') >> json:tags >> uppercase() >> join('|')",
+    );
+    assert_eq!(tags, "A|B");
+}
+
+#[test]
+fn xpath_source() {
+    let cs = make_cs();
+    let name = cs.select(
+        0,
+        "embedded-json:xpath://script[@type='application/ld+json'] >> json:name",
+    );
+    assert_eq!(name, "Widget");
+}
+
+#[test]
+fn without_json_stage_returns_blob() {
+    let cs = make_cs();
+    let blob = cs.select(0, "embedded-json:css:div.product >> get-attr('data-json')");
+    assert!(blob.contains("W-1"));
+}
+
+#[test]
+fn works_as_fallback_alternative() {
+    let cs = make_cs();
+    let price = cs.select(0, "css:.price || embedded-json:css:script >> json:offers.price");
+    assert_eq!(price, "19.99");
+}
+
+#[test]
+fn invalid_path_is_rejected_by_prepare() {
+    assert!(PreparedQuery::new("embedded-json:css:script >> json:`bad").is_err());
+    let prepared = PreparedQuery::new("embedded-json:css:script >> json:cart").unwrap();
+    assert_eq!(prepared.query_type().unwrap().prefix(), "embedded-json");
+}
+
+#[test]
+fn engine_reported_in_detailed_result() {
+    let cs = make_cs();
+    let detailed = cs.query_detailed(0, "embedded-json:css:script >> json:cart.count");
+    assert_eq!(detailed.engine(), Some("embedded-json"));
+}
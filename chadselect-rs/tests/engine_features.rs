@@ -0,0 +1,57 @@
+//! Tests for the per-engine Cargo features. Most run under any feature set;
+//! the rest only in builds that leave their engine out, e.g.
+//! `cargo test --no-default-features --features regex --test engine_features`.
+
+use chadselect::query::parse_query;
+use chadselect::PreparedQuery;
+
+#[test]
+fn missing_feature_follows_the_build() {
+    let cases = [
+        ("regex:\\d+", "regex", cfg!(feature = "regex")),
+        ("bytes:\\x00", "regex", cfg!(feature = "regex")),
+        ("css:p", "css", cfg!(feature = "css")),
+        ("text:price", "css", cfg!(feature = "css")),
+        ("xpath://p", "xpath", cfg!(feature = "xpath")),
+        ("json:a.b", "json", cfg!(feature = "json")),
+    ];
+    for (query, feature, built) in cases {
+        let expected = (!built).then_some(feature);
+        let query_type = parse_query(query).unwrap();
+        assert_eq!(query_type.missing_feature(), expected, "{}", query);
+    }
+}
+
+#[cfg(all(feature = "json", not(feature = "xpath")))]
+#[test]
+fn disabled_engine_is_skipped_with_a_warning() {
+    use chadselect::{ChadSelect, WarningKind};
+
+    let mut cs = ChadSelect::new();
+    cs.add_json(r#"{"name": "Civic"}"#.to_string());
+
+    let r = cs.query_detailed(-1, "xpath://name || json:name");
+    assert_eq!(r.results, vec!["Civic"]);
+    assert_eq!(r.warnings.len(), 1);
+    assert_eq!(r.warnings[0].kind, WarningKind::UnsupportedEngine);
+    assert!(r.warnings[0].message.contains("`xpath` feature"));
+}
+
+#[cfg(not(feature = "css"))]
+#[test]
+fn prepare_rejects_a_disabled_engine() {
+    let err = PreparedQuery::new("css:p").unwrap_err();
+    assert!(err.contains("enable the `css` feature"), "{}", err);
+}
+
+#[cfg(feature = "css")]
+#[test]
+fn built_engines_prepare() {
+    use chadselect::ChadSelect;
+
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>a</p>".to_string());
+
+    let query = PreparedQuery::new("css:p").unwrap();
+    assert_eq!(cs.query_prepared(-1, &query), vec!["a"]);
+}
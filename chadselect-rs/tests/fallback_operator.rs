@@ -0,0 +1,75 @@
+//! Tests for the `||` fallback operator inside a single query string.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::query::split_alternatives;
+use chadselect::ChadSelect;
+
+fn cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<span class="price">$28,500</span><span class="blank"> </span>"#.to_string());
+    cs.add_json(r#"{"a": null, "b": "fallback"}"#.to_string());
+    cs
+}
+
+#[test]
+fn splits_only_before_prefixed_alternatives() {
+    assert_eq!(
+        split_alternatives(r"css:.sale || css:.price || regex:\$(\d+)"),
+        vec!["css:.sale", "css:.price", r"regex:\$(\d+)"]
+    );
+    // JMESPath's own `||` operator is not a fallback.
+    assert_eq!(split_alternatives("json:a || b"), vec!["json:a || b"]);
+    // Nor is `||` inside a string literal.
+    assert_eq!(
+        split_alternatives("xpath://p[.='x || css:y']"),
+        vec!["xpath://p[.='x || css:y']"]
+    );
+}
+
+#[test]
+fn first_non_empty_alternative_wins() {
+    let cs = cs();
+    assert_eq!(cs.select(0, "css:.sale-price || css:.price || regex:(\\d+)"), "$28,500");
+    assert_eq!(cs.select(0, r"css:.sale-price || regex:\$(\d+)"), "28");
+}
+
+#[test]
+fn whitespace_only_results_fall_through() {
+    let cs = cs();
+    let r = cs.query_detailed(0, "xpath://span[@class='blank']/text() || css:.price");
+    assert_eq!(r.results, vec!["$28,500"]);
+    assert_eq!(r.engine(), Some("css"));
+}
+
+#[test]
+fn jmespath_or_still_works() {
+    let cs = cs();
+    assert_eq!(cs.select(0, "json:a || b"), "fallback");
+}
+
+#[test]
+fn invalid_alternatives_are_skipped_but_rejected_by_prepare() {
+    let cs = cs();
+    assert_eq!(cs.select(0, "css:][bad || css:.price"), "$28,500");
+    assert!(cs.prepare("css:][bad || css:.price").is_err());
+
+    let prepared = cs.prepare("css:.missing || css:.price >> translate('$,','')").unwrap();
+    assert_eq!(cs.select_prepared(0, &prepared), "28500");
+}
+
+#[test]
+fn refs_can_be_alternatives() {
+    let mut cs = cs();
+    cs.define("sale", "css:.sale-price");
+    cs.define("price", "ref:sale || css:.price");
+    assert_eq!(cs.select(0, "ref:price"), "$28,500");
+
+    cs.define("loop", "ref:loop || css:.price");
+    assert!(cs.query(0, "ref:loop").is_empty());
+}
+
+#[test]
+fn all_alternatives_missing_returns_empty() {
+    let cs = cs();
+    assert!(cs.query(-1, "css:.nope || json:nope").is_empty());
+}
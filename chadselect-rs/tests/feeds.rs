@@ -0,0 +1,126 @@
+//! Tests for the sitemap and RSS/Atom extractors.
+#![cfg(all(feature = "feeds", feature = "css", feature = "json"))]
+
+use chadselect::{ChadSelect, FeedItem, SitemapUrl};
+
+const SITEMAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"
+        xmlns:image="http://www.google.com/schemas/sitemap-image/1.1">
+  <url>
+    <loc> https://example.com/cars/civic </loc>
+    <lastmod>2024-05-01</lastmod>
+    <changefreq>daily</changefreq>
+    <priority>0.8</priority>
+    <image:image><image:loc>https://example.com/civic.jpg</image:loc></image:image>
+  </url>
+  <url><loc>https://example.com/cars/accord</loc></url>
+  <url><lastmod>2024-01-01</lastmod></url>
+</urlset>"#;
+
+const SITEMAP_INDEX: &str = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/sitemap-cars.xml</loc><lastmod>2024-05-02</lastmod></sitemap>
+</sitemapindex>"#;
+
+const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel>
+    <title>Dealer news</title>
+    <link>https://example.com/</link>
+    <item>
+      <title>Spring sale</title>
+      <link>https://example.com/news/spring</link>
+      <pubDate>Mon, 01 Apr 2024 09:00:00 GMT</pubDate>
+      <guid isPermaLink="false">news-1</guid>
+      <description><![CDATA[<p>Up to <b>20%</b> off</p>]]></description>
+    </item>
+    <item><title>Undated</title><dc:date>2024-04-02</dc:date></item>
+  </channel>
+</rss>"#;
+
+const ATOM: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Changelog</title>
+  <link href="https://example.com/atom.xml" rel="self"/>
+  <entry>
+    <title>v2 released</title>
+    <link rel="self" href="https://example.com/entries/2.xml"/>
+    <link href="https://example.com/entries/2"/>
+    <id>urn:uuid:2</id>
+    <updated>2024-03-02T00:00:00Z</updated>
+    <summary>Faster.</summary>
+  </entry>
+</feed>"#;
+
+#[test]
+fn sitemap_urls_and_index_entries() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(SITEMAP.to_string());
+    cs.add_html(SITEMAP_INDEX.to_string());
+    cs.add_html("<html><body><url><loc>not xml".to_string());
+
+    assert_eq!(
+        cs.extract_sitemap_urls(),
+        vec![
+            SitemapUrl {
+                loc: "https://example.com/cars/civic".to_string(),
+                lastmod: Some("2024-05-01".to_string()),
+                changefreq: Some("daily".to_string()),
+                priority: Some("0.8".to_string()),
+                nested: false,
+            },
+            SitemapUrl {
+                loc: "https://example.com/cars/accord".to_string(),
+                ..Default::default()
+            },
+            SitemapUrl {
+                loc: "https://example.com/sitemap-cars.xml".to_string(),
+                lastmod: Some("2024-05-02".to_string()),
+                nested: true,
+                ..Default::default()
+            },
+        ]
+    );
+}
+
+#[test]
+fn rss_and_atom_items() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(RSS.to_string());
+    cs.add_text(ATOM.to_string());
+    cs.add_json(r#"{"item": 1}"#.to_string());
+
+    assert_eq!(
+        cs.extract_feed_items(),
+        vec![
+            FeedItem {
+                title: Some("Spring sale".to_string()),
+                link: Some("https://example.com/news/spring".to_string()),
+                pub_date: Some("Mon, 01 Apr 2024 09:00:00 GMT".to_string()),
+                id: Some("news-1".to_string()),
+                summary: Some("<p>Up to <b>20%</b> off</p>".to_string()),
+            },
+            FeedItem {
+                title: Some("Undated".to_string()),
+                pub_date: Some("2024-04-02".to_string()),
+                ..Default::default()
+            },
+            FeedItem {
+                title: Some("v2 released".to_string()),
+                link: Some("https://example.com/entries/2".to_string()),
+                pub_date: Some("2024-03-02T00:00:00Z".to_string()),
+                id: Some("urn:uuid:2".to_string()),
+                summary: Some("Faster.".to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn foreign_namespaces_are_ignored() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(
+        r#"<doc xmlns="urn:other"><url><loc>x</loc></url><item><title>t</title></item></doc>"#
+            .to_string(),
+    );
+    assert!(cs.extract_sitemap_urls().is_empty());
+    assert!(cs.extract_feed_items().is_empty());
+}
@@ -0,0 +1,131 @@
+//! Tests for `add_url` (the `fetch` feature), against a local one-shot server.
+#![cfg(all(feature = "fetch", feature = "css", feature = "json", feature = "regex"))]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+use chadselect::{ChadSelect, LimitPolicy};
+
+/// Serve one response with the given status line, `Content-Type` (if any),
+/// and body; return the URL to fetch it from.
+fn serve(status: &'static str, content_type: Option<&'static str>, body: &'static [u8]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/page", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4096];
+        let _ = stream.read(&mut request);
+        let mut head = format!("HTTP/1.1 {status}\r\nContent-Length: {}\r\n", body.len());
+        if let Some(content_type) = content_type {
+            head.push_str(&format!("Content-Type: {content_type}\r\n"));
+        }
+        head.push_str("Connection: close\r\n\r\n");
+        stream.write_all(head.as_bytes()).unwrap();
+        stream.write_all(body).unwrap();
+    });
+    url
+}
+
+#[test]
+fn html_by_header_with_source_recorded() {
+    let url = serve(
+        "200 OK",
+        Some("text/html; charset=utf-8"),
+        b"<h1>Civic</h1>",
+    );
+    let mut cs = ChadSelect::new();
+    cs.add_url(&url).unwrap();
+    assert_eq!(cs.select(0, "css:h1"), "Civic");
+    assert_eq!(cs.content_source(0), Some(url.as_str()));
+    #[cfg(feature = "snapshot")]
+    {
+        assert_eq!(cs.snapshot().content_count(), 1);
+        assert_eq!(
+            ChadSelect::from_snapshot(&cs.snapshot()).content_source(0),
+            Some(url.as_str())
+        );
+    }
+}
+
+#[test]
+fn json_and_sniffed_types() {
+    let mut cs = ChadSelect::new();
+    cs.add_url(&serve(
+        "200 OK",
+        Some("application/ld+json"),
+        br#"{"price": 5}"#,
+    ))
+    .unwrap();
+    cs.add_url(&serve("200 OK", None, b"  <p class='x'>sniffed</p>"))
+        .unwrap();
+    assert_eq!(cs.select(0, "json:price"), "5");
+    assert_eq!(cs.select(0, "css:p.x"), "sniffed");
+}
+
+#[test]
+#[cfg(feature = "info")]
+fn charsets_from_header_and_meta() {
+    let mut cs = ChadSelect::new();
+    cs.add_url(&serve(
+        "200 OK",
+        Some("text/plain; charset=ISO-8859-1"),
+        b"caf\xe9",
+    ))
+    .unwrap();
+    cs.add_url(&serve(
+        "200 OK",
+        Some("text/html"),
+        b"<html><head><meta charset=\"windows-1252\"></head><body><b>\x93hi\x94</b></body></html>",
+    ))
+    .unwrap();
+    assert_eq!(cs.select(0, r"regex:caf."), "caf\u{e9}");
+    assert_eq!(cs.select(0, "css:b"), "\u{201c}hi\u{201d}");
+
+    // ISO-8859-1 is decoded as its WHATWG superset, windows-1252.
+    assert_eq!(cs.content_info(0).unwrap().charset, "windows-1252");
+    let info = cs.content_info(1).unwrap();
+    assert_eq!(info.charset, "windows-1252");
+    assert_eq!(info.declared_charset.as_deref(), Some("windows-1252"));
+}
+
+#[test]
+fn http_errors_are_reported() {
+    let mut cs = ChadSelect::new();
+    let err = cs
+        .add_url(&serve("404 Not Found", Some("text/html"), b"gone"))
+        .unwrap_err();
+    assert!(err.contains("404"), "{err}");
+    assert!(cs.add_url("http://127.0.0.1:1/unreachable").is_err());
+    assert_eq!(cs.content_count(), 0);
+}
+
+#[test]
+fn size_limits_apply_before_the_download() {
+    let body = b"VIN 1HGFE2F59PA000001 and a long description";
+
+    let mut cs = ChadSelect::builder().max_item_bytes(16).build();
+    let err = cs
+        .add_url(&serve("200 OK", Some("text/plain"), body))
+        .unwrap_err();
+    assert!(err.contains("16-byte item limit"), "{}", err);
+    assert_eq!(cs.content_count(), 0);
+
+    let mut cs = ChadSelect::builder()
+        .max_item_bytes(16)
+        .limit_policy(LimitPolicy::Truncate)
+        .build();
+    cs.add_url(&serve("200 OK", Some("text/plain"), body))
+        .unwrap();
+    assert_eq!(cs.select(0, "regex:.+"), "VIN 1HGFE2F59PA0");
+}
+
+#[test]
+fn binary_content_types_are_refused() {
+    let mut cs = ChadSelect::new();
+    let err = cs
+        .add_url(&serve("200 OK", Some("image/png"), b"\x89PNG\r\n"))
+        .unwrap_err();
+    assert!(err.contains("binary content (image/png)"), "{}", err);
+    assert_eq!(cs.content_count(), 0);
+}
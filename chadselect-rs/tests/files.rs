@@ -0,0 +1,74 @@
+//! Tests for `add_file` and `add_glob`.
+#![cfg(all(feature = "css", feature = "json"))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chadselect::{content_type_for_path, ChadSelect, ContentType};
+
+/// A fresh scratch directory for one test.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("chadselect-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn add_file_infers_type_and_records_path() {
+    let dir = scratch("add-file");
+    let html = dir.join("page.HTML");
+    let json = dir.join("data.json");
+    fs::write(&html, "\u{feff}<h1>Civic</h1>").unwrap();
+    fs::write(&json, r#"{"price": 5}"#).unwrap();
+
+    let mut cs = ChadSelect::new();
+    cs.add_file(&html).unwrap();
+    cs.add_file(&json).unwrap();
+    assert_eq!(cs.select(0, "css:h1"), "Civic");
+    assert_eq!(cs.select(0, "json:price"), "5");
+    assert_eq!(
+        cs.content_source(0),
+        Some(html.display().to_string().as_str())
+    );
+
+    let err = cs.add_file(dir.join("missing.html")).unwrap_err();
+    assert!(err.contains("missing.html"), "{err}");
+    assert_eq!(cs.content_count(), 2);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[cfg(feature = "glob")]
+fn add_glob_loads_matches_in_order() {
+    let dir = scratch("add-glob");
+    for (name, title) in [("b.html", "B"), ("a.html", "A"), ("c.txt", "C")] {
+        fs::write(dir.join(name), format!("<h1>{title}</h1>")).unwrap();
+    }
+    fs::create_dir(dir.join("sub.html")).unwrap();
+
+    let mut cs = ChadSelect::new();
+    let pattern = format!("{}/*.html", dir.display());
+    assert_eq!(cs.add_glob(&pattern), Ok(2));
+    assert_eq!(cs.query(-1, "css:h1"), vec!["A", "B"]);
+    assert!(cs.content_source(1).unwrap().ends_with("b.html"));
+
+    assert_eq!(cs.add_glob(&format!("{}/*.none", dir.display())), Ok(0));
+    assert!(cs.add_glob("[").is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn extensions_map_to_content_types() {
+    for (path, expected) in [
+        ("a.htm", ContentType::Html),
+        ("a.xhtml", ContentType::Html),
+        ("a.json", ContentType::Json),
+        ("notes.md", ContentType::Markdown),
+        ("settings.ini", ContentType::Ini),
+        ("dump.txt", ContentType::Text),
+        ("no_extension", ContentType::Text),
+    ] {
+        assert_eq!(content_type_for_path(Path::new(path)), expected, "{path}");
+    }
+}
@@ -0,0 +1,125 @@
+//! Tests for `extract_forms`.
+#![cfg(all(feature = "css", feature = "json"))]
+
+use chadselect::{ChadSelect, FormOption};
+
+const LOGIN: &str = r#"
+<form id="login" action="/session" method="post" enctype="multipart/form-data">
+  <input type="hidden" name="csrf" value="tok-123">
+  <input name="user" required>
+  <input type="PASSWORD" name="pass">
+  <input type="checkbox" name="remember" checked>
+  <input type="checkbox" name="newsletter" value="yes">
+  <input type="radio" name="plan" value="free">
+  <input type="radio" name="plan" value="pro" checked>
+  <input name="legacy" value="x" disabled>
+  <input type="file" name="avatar">
+  <input type="submit" value="Sign in">
+  <button name="action" value="login">Sign in</button>
+</form>
+<textarea name="note" form="login">hello</textarea>
+<form action=" ">
+  <select name="colors" multiple>
+    <option>Red</option>
+    <option value="b" selected>Blue</option>
+    <option value="g" selected>Green</option>
+  </select>
+  <select name="size"><option value="s">Small</option><option value="m">Medium</option></select>
+  <input name="other" form="elsewhere">
+</form>
+"#;
+
+#[test]
+fn form_attributes_and_fields() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(LOGIN.to_string());
+    let forms = cs.extract_forms(0);
+    assert_eq!(forms.len(), 2);
+
+    let login = &forms[0];
+    assert_eq!(login.action.as_deref(), Some("/session"));
+    assert_eq!(login.method, "POST");
+    assert_eq!(login.enctype.as_deref(), Some("multipart/form-data"));
+    assert_eq!(login.id.as_deref(), Some("login"));
+
+    let kinds: Vec<_> = login
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.kind.as_str()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("csrf", "hidden"),
+            ("user", "text"),
+            ("pass", "password"),
+            ("remember", "checkbox"),
+            ("newsletter", "checkbox"),
+            ("plan", "radio"),
+            ("plan", "radio"),
+            ("legacy", "text"),
+            ("avatar", "file"),
+            ("action", "button-submit"),
+            ("note", "textarea"),
+        ]
+    );
+    assert!(login.fields[1].required);
+    assert!(login.fields[7].disabled);
+    assert_eq!(login.fields[10].value.as_deref(), Some("hello"));
+}
+
+#[test]
+fn submission_pairs() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(LOGIN.to_string());
+    let forms = cs.extract_forms(0);
+
+    let pairs = |i: usize| -> Vec<(String, String)> { forms[i].submission() };
+    let expected: Vec<(String, String)> = [
+        ("csrf", "tok-123"),
+        ("user", ""),
+        ("pass", ""),
+        ("remember", "on"),
+        ("plan", "pro"),
+        ("note", "hello"),
+    ]
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    assert_eq!(pairs(0), expected);
+
+    let search = &forms[1];
+    assert_eq!(search.action, None);
+    assert_eq!(search.method, "GET");
+    assert_eq!(
+        search.fields[0].options[0],
+        FormOption {
+            value: "Red".to_string(),
+            label: "Red".to_string(),
+            selected: false
+        }
+    );
+    assert_eq!(
+        search.fields.len(),
+        2,
+        "`form=elsewhere` belongs to another form"
+    );
+    assert_eq!(
+        pairs(1),
+        vec![
+            ("colors".to_string(), "b".to_string()),
+            ("colors".to_string(), "g".to_string()),
+            ("size".to_string(), "s".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn no_forms() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>none</p>".to_string());
+    cs.add_json("{}".to_string());
+    assert!(cs.extract_forms(0).is_empty());
+    assert!(cs.extract_forms(1).is_empty());
+    assert!(cs.extract_forms(2).is_empty());
+}
@@ -0,0 +1,155 @@
+//! Tests for the `text:` full-text search engine.
+#![cfg(all(feature = "css", feature = "json"))]
+
+use chadselect::{ChadSelect, PreparedQuery};
+
+const HTML: &str = r#"<html><head><title>Honda Civic for sale</title>
+<script>var civic = "low miles";</script></head><body>
+<h1>2019 Honda Civic EX</h1>
+<p>One owner, garage kept. Low miles for the year! Full service
+   history is available on request.</p>
+<ul><li>Leather seats and a sunroof.</li><li>Factory warranty until 2026.</li></ul>
+<div id="dealer">Call <b>Civic Motors</b> today. Financing available.</div>
+</body></html>"#;
+
+const TEXT: &str =
+    "Clean title. Civic has 28,000 miles.\n\nSecond paragraph mentions\nthe warranty";
+
+fn html() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+#[test]
+fn word_matches_sentences_case_insensitively() {
+    assert_eq!(
+        html().query(-1, "text:WARRANTY"),
+        vec!["Factory warranty until 2026."]
+    );
+}
+
+#[test]
+fn sentences_are_split_and_whitespace_collapsed() {
+    assert_eq!(
+        html().query(-1, "text:history"),
+        vec!["Full service history is available on request."]
+    );
+}
+
+#[test]
+fn phrase_requires_adjacent_words() {
+    let cs = html();
+    assert_eq!(
+        cs.query(-1, r#"text:"low miles""#),
+        vec!["Low miles for the year!"]
+    );
+    assert!(cs.query(-1, r#"text:"miles low""#).is_empty());
+}
+
+#[test]
+fn hidden_text_is_not_searched() {
+    // The <title> and <script> both mention the words; only body text counts.
+    assert_eq!(html().query(-1, "text:civic").len(), 2);
+}
+
+#[test]
+fn and_is_implied_and_or_binds_looser() {
+    let cs = html();
+    assert_eq!(
+        cs.query(-1, "text:leather sunroof"),
+        vec!["Leather seats and a sunroof."]
+    );
+    assert_eq!(
+        cs.query(-1, "text:leather AND sunroof"),
+        cs.query(-1, "text:leather sunroof")
+    );
+    assert!(cs.query(-1, "text:leather warranty").is_empty());
+    assert_eq!(
+        cs.query(-1, "text:leather sunroof OR warranty"),
+        vec![
+            "Leather seats and a sunroof.",
+            "Factory warranty until 2026."
+        ]
+    );
+}
+
+#[test]
+fn near_limits_the_words_between_terms() {
+    let cs = html();
+    // "Full service history": one word between "full" and "history".
+    assert_eq!(cs.query(-1, "text:full NEAR/1 history").len(), 1);
+    assert!(cs.query(-1, "text:full NEAR/0 history").is_empty());
+    // Either order.
+    assert_eq!(cs.query(-1, "text:history NEAR/1 full").len(), 1);
+    assert_eq!(cs.query(-1, "text:owner NEAR kept").len(), 1);
+}
+
+#[test]
+fn inline_elements_stay_in_their_block() {
+    assert_eq!(
+        html().query(-1, r#"text:"civic motors""#),
+        vec!["Call Civic Motors today."]
+    );
+}
+
+#[test]
+fn plain_text_splits_at_blank_lines() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(TEXT.to_string());
+    assert_eq!(
+        cs.query(-1, "text:civic NEAR/3 miles"),
+        vec!["Civic has 28,000 miles."]
+    );
+    assert_eq!(
+        cs.query(-1, "text:warranty"),
+        vec!["Second paragraph mentions the warranty"]
+    );
+}
+
+#[test]
+fn locations_point_at_blocks_and_lines() {
+    let result = html().query_detailed(-1, "text:financing");
+    assert_eq!(result.engine(), Some("text"));
+    assert_eq!(result.locations[0].css_path.as_deref(), Some("div#dealer"));
+    assert!(result.locations[0].position.is_some());
+
+    let mut cs = ChadSelect::new();
+    cs.add_text(TEXT.to_string());
+    let result = cs.query_detailed(-1, "text:warranty");
+    assert_eq!(result.locations[0].line, Some(3));
+    assert_eq!(result.locations[0].column, Some(1));
+}
+
+#[test]
+fn function_chain_applies_to_sentences() {
+    assert_eq!(
+        html().query(-1, "text:warranty >> uppercase()"),
+        vec!["FACTORY WARRANTY UNTIL 2026."]
+    );
+}
+
+#[test]
+fn json_content_is_skipped() {
+    let mut cs = ChadSelect::new();
+    cs.add_json(r#"{"note": "warranty"}"#.to_string());
+    cs.add_text("Has warranty.".to_string());
+    assert_eq!(cs.query(-1, "text:warranty"), vec!["Has warranty."]);
+}
+
+#[test]
+fn malformed_queries_fail_to_prepare() {
+    for query in [
+        "text:",
+        "text:OR civic",
+        "text:civic AND",
+        "text:civic NEAR",
+        "text:civic NEAR/x miles",
+        r#"text:"civic"#,
+        "text:civic OR OR miles",
+        "text:--",
+    ] {
+        assert!(PreparedQuery::new(query).is_err(), "{}", query);
+    }
+    assert!(html().query(-1, "text:OR").is_empty());
+}
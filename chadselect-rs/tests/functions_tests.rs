@@ -1,8 +1,11 @@
 //! Tests for post-processing text functions.
+#![cfg(all(feature = "xpath", feature = "regex"))]
 
 use chadselect::functions::{
-    apply_single_text_function, apply_text_functions, parse_text_functions, TextFunction,
+    apply_single_text_function, apply_text_functions, parse_text_functions,
+    split_functions, split_pattern_functions, try_parse_text_functions, TextFunction,
 };
+use chadselect::ChadSelect;
 
 // ─── Parsing ────────────────────────────────────────────────────────────────
 
@@ -34,7 +37,7 @@ fn parse_substring() {
     assert_eq!(functions.len(), 1);
     if let TextFunction::Substring { start, length } = &functions[0] {
         assert_eq!(*start, 1);
-        assert_eq!(*length, 3);
+        assert_eq!(*length, Some(3));
     } else {
         panic!("Expected Substring function");
     }
@@ -82,10 +85,130 @@ fn parse_unknown_function_is_skipped() {
 
 #[test]
 fn parse_incomplete_args_are_skipped() {
-    let functions = parse_text_functions(r#"substring(1) >> replace("only-one-arg")"#);
+    // `substring(1)` is a valid open-ended form; `substring()` is not.
+    let functions = parse_text_functions(r#"substring() >> replace("only-one-arg")"#);
     assert_eq!(functions.len(), 0);
 }
 
+// ─── Argument tokenizer ─────────────────────────────────────────────────────
+
+#[test]
+fn quoted_arguments_keep_commas_and_parentheses() {
+    let apply = |chain: &str, input: &str| {
+        apply_text_functions(vec![input.to_string()], &parse_text_functions(chain))
+    };
+    assert_eq!(apply(r#"replace(",", ";")"#, "a,b,c"), vec!["a;b;c"]);
+    assert_eq!(apply("replace('(', '[') >> replace(')', ']')", "f(x)"), vec!["f[x]"]);
+    assert_eq!(apply("replace(') >> (', '|')", "a) >> (b"), vec!["a|b"]);
+    assert_eq!(apply("if-contains('a, b', 'yes', 'no')", "x a, b y"), vec!["yes"]);
+}
+
+#[test]
+fn quoted_argument_escapes() {
+    let apply = |chain: &str, input: &str| {
+        apply_text_functions(vec![input.to_string()], &parse_text_functions(chain))
+    };
+    assert_eq!(apply(r"replace('\'', '')", "O'Brien"), vec!["OBrien"]);
+    assert_eq!(apply(r#"replace("\"", "'")"#, r#"say "hi""#), vec!["say 'hi'"]);
+    assert_eq!(apply(r"replace('\\', '/')", r"a\b"), vec!["a/b"]);
+    assert_eq!(apply(r"replace('\t', ' ')", "a\tb"), vec!["a b"]);
+    // Other backslashes are kept, so regex classes need no doubling.
+    assert_eq!(apply(r"regex-replace('\d+', '#')", "a12b3"), vec!["a#b#"]);
+}
+
+#[test]
+fn bare_arguments_balance_brackets() {
+    let functions = parse_text_functions(r"regex-extract((\d{1,3})) >> substring(0, 2)");
+    assert_eq!(functions.len(), 2);
+    assert_eq!(
+        apply_text_functions(vec!["ab 1234".to_string()], &functions),
+        vec!["12"]
+    );
+}
+
+#[test]
+fn try_parse_reports_malformed_functions() {
+    for (chain, expected) in [
+        ("nope()", "unknown text function 'nope'"),
+        ("trim", "missing '('"),
+        ("replace('a')", "replace(): expected 2 argument(s), got 1"),
+        ("replace('a', 'b'", "missing ')'"),
+        ("replace('a, 'b')", "expected ',' or ')'"),
+        ("replace('a', 'b) >> trim()", "unterminated string"),
+        ("substring('1')", "substring(): '1' is not a valid number"),
+        ("trim(1)", "trim(): expected 0 argument(s), got 1"),
+        ("regex-extract('(')", "regex-extract(): invalid regex '('"),
+        ("get-attr('')", "get-attr(): argument must not be empty"),
+        ("replace('a',, 'b')", "empty argument 2"),
+        ("trim() x", "unexpected 'x' after 'trim(…)'"),
+    ] {
+        let err = try_parse_text_functions(chain).unwrap_err();
+        assert!(err.contains(expected), "{}: {}", chain, err);
+    }
+    // The lenient parser drops only the bad function.
+    assert_eq!(parse_text_functions("trim() >> nope() >> uppercase()").len(), 2);
+}
+
+// ─── Pipe splitting ─────────────────────────────────────────────────────────
+
+#[test]
+fn split_ignores_pipe_inside_string_literal() {
+    let (expr, functions) = split_functions("//p[contains(., 'a >> b')] >> uppercase()");
+    assert_eq!(expr, "//p[contains(., 'a >> b')]");
+    assert_eq!(functions.len(), 1);
+}
+
+#[test]
+fn split_unescapes_escaped_pipe() {
+    let (expr, functions) = split_functions(r"a \>> b >> trim()");
+    assert_eq!(expr, "a >> b");
+    assert_eq!(functions.len(), 1);
+
+    let (expr, functions) = split_functions(r"a \>> b");
+    assert_eq!(expr, "a >> b");
+    assert!(functions.is_empty());
+}
+
+#[test]
+fn split_pattern_does_not_track_quotes() {
+    // An apostrophe in a regex is a plain character, not a string literal.
+    let (expr, functions) = split_pattern_functions("it's (\\d+) >> trim()");
+    assert_eq!(expr, "it's (\\d+)");
+    assert_eq!(functions.len(), 1);
+}
+
+#[test]
+fn split_unterminated_quote_is_literal() {
+    let (expr, functions) = split_functions("//p[.=\"it's\"] >> trim()");
+    assert_eq!(expr, "//p[.=\"it's\"]");
+    assert_eq!(functions.len(), 1);
+}
+
+#[test]
+fn parse_pipe_inside_quoted_argument() {
+    let functions = parse_text_functions("replace('>>', '/') >> trim()");
+    assert_eq!(functions.len(), 2);
+    if let TextFunction::Replace { find, replace } = &functions[0] {
+        assert_eq!(find, ">>");
+        assert_eq!(replace, "/");
+    } else {
+        panic!("Expected Replace function");
+    }
+}
+
+#[test]
+fn escaped_pipe_end_to_end() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<p>Home >> Cars</p><p>Other</p>"#.to_string());
+    cs.add_text("path: a>>b".to_string());
+
+    assert_eq!(
+        cs.select(0, "xpath://p[contains(., '>>')]/text() >> substring-after('>> ')"),
+        "Cars"
+    );
+    assert_eq!(cs.select(-1, r"regex:path: (a\>>b) >> uppercase()"), "A>>B");
+}
+
 // ─── Apply single function ──────────────────────────────────────────────────
 
 #[test]
@@ -131,7 +254,7 @@ fn apply_substring() {
             "Hello World",
             &TextFunction::Substring {
                 start: 0,
-                length: 5,
+                length: Some(5),
             }
         ),
         "Hello"
@@ -141,7 +264,7 @@ fn apply_substring() {
             "Hello World",
             &TextFunction::Substring {
                 start: 6,
-                length: 5,
+                length: Some(5),
             }
         ),
         "World"
@@ -155,7 +278,7 @@ fn apply_substring_out_of_bounds() {
             "Hello",
             &TextFunction::Substring {
                 start: 10,
-                length: 5,
+                length: Some(5),
             }
         ),
         ""
@@ -165,13 +288,46 @@ fn apply_substring_out_of_bounds() {
             "Hello",
             &TextFunction::Substring {
                 start: 3,
-                length: 10,
+                length: Some(10),
             }
         ),
         "lo"
     );
 }
 
+#[test]
+fn parse_substring_open_ended_and_negative() {
+    let cases = [
+        ("substring(2)", 2, None),
+        ("substring(-4)", -4, None),
+        ("substring(-4, 2)", -4, Some(2)),
+        ("substring(1, -1)", 1, None),
+    ];
+    for (input, expected_start, expected_length) in cases {
+        let functions = parse_text_functions(input);
+        match &functions[..] {
+            [TextFunction::Substring { start, length }] => {
+                assert_eq!((*start, *length), (expected_start, expected_length), "{}", input);
+            }
+            other => panic!("Expected Substring for {}, got {:?}", input, other),
+        }
+    }
+    assert!(parse_text_functions("substring(x)").is_empty());
+    assert!(parse_text_functions("substring(1, -2)").is_empty());
+}
+
+#[test]
+fn apply_substring_negative_and_open_ended() {
+    let apply = |start, length| {
+        apply_single_text_function("1HGCM82633A004352", &TextFunction::Substring { start, length })
+    };
+    assert_eq!(apply(-4, None), "4352");
+    assert_eq!(apply(-6, Some(2)), "00");
+    assert_eq!(apply(11, None), "004352");
+    // A negative start past the beginning clamps to it.
+    assert_eq!(apply(-40, Some(3)), "1HG");
+}
+
 #[test]
 fn apply_substring_after() {
     assert_eq!(
@@ -290,7 +446,7 @@ fn chain_substring_after_then_substring_then_lowercase() {
         },
         TextFunction::Substring {
             start: 0,
-            length: 3,
+            length: Some(3),
         },
         TextFunction::Lowercase,
     ];
@@ -336,7 +492,7 @@ fn unicode_substring() {
             "Hello 🌍 World",
             &TextFunction::Substring {
                 start: 6,
-                length: 1,
+                length: Some(1),
             }
         ),
         "🌍"
@@ -0,0 +1,67 @@
+//! Tests for the `glob:` prefix.
+#![cfg(all(feature = "css", feature = "regex"))]
+
+use chadselect::patterns::glob_to_regex;
+use chadselect::ChadSelect;
+
+const LOG: &str = "INFO start\r\nERROR disk full\nWARN error rate high\nERROR: net down\nversion 2.1.0\nversion 2.10\ncost *5*\n";
+
+fn log() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(LOG.to_string());
+    cs
+}
+
+#[test]
+fn star_matches_whole_lines() {
+    let cs = log();
+    assert_eq!(cs.query(-1, "glob:*error*"), vec!["WARN error rate high"]);
+    assert_eq!(
+        cs.query(-1, "glob:ERROR*"),
+        vec!["ERROR disk full", "ERROR: net down"]
+    );
+    // Anchored: the glob must cover the whole line.
+    assert!(cs.query(-1, "glob:ERROR").is_empty());
+    // CRLF line endings don't leak into results.
+    assert_eq!(cs.query(-1, "glob:INFO *"), vec!["INFO start"]);
+}
+
+#[test]
+fn question_mark_and_classes() {
+    let cs = log();
+    assert_eq!(cs.query(-1, "glob:version 2.?.*"), vec!["version 2.1.0"]);
+    assert_eq!(cs.query(-1, "glob:version 2.1[0-9]"), vec!["version 2.10"]);
+    assert_eq!(
+        cs.query(-1, "glob:[!EW]*"),
+        vec!["INFO start", "version 2.1.0", "version 2.10", "cost *5*"]
+    );
+}
+
+#[test]
+fn escapes_and_regex_characters_are_literal() {
+    let cs = log();
+    assert_eq!(cs.query(-1, r"glob:cost \*5\*"), vec!["cost *5*"]);
+    assert!(cs.query(-1, "glob:version 2.1.0|ERROR*").is_empty());
+    assert!(cs.query(-1, "glob:version 2(1)0").is_empty());
+}
+
+#[test]
+fn chains_and_fallbacks_apply() {
+    let cs = log();
+    assert_eq!(
+        cs.query(-1, "glob:ERROR* >> substring-after('ERROR') >> trim()"),
+        vec!["disk full", ": net down"]
+    );
+    assert_eq!(
+        cs.select(0, "css:.missing || glob:WARN*"),
+        "WARN error rate high"
+    );
+}
+
+#[test]
+fn translation() {
+    assert_eq!(glob_to_regex("*error*"), "(?mR)^.*error.*$");
+    assert_eq!(glob_to_regex("a[!]x-]b"), r"(?mR)^a[^\]x\-]b$");
+    assert_eq!(glob_to_regex("[oops"), r"(?mR)^\[oops$");
+    assert_eq!(glob_to_regex("a>>b"), r"(?mR)^a\x3E\x3Eb$");
+}
@@ -0,0 +1,147 @@
+//! Tests for `add_har`.
+#![cfg(all(
+    feature = "har",
+    feature = "info",
+    feature = "css",
+    feature = "json",
+    feature = "regex"
+))]
+
+use chadselect::{ChadSelect, ContentType};
+
+fn entry(url: &str, mime: &str, text: &str, encoding: Option<&str>) -> serde_json::Value {
+    let mut content = serde_json::json!({ "mimeType": mime, "text": text, "size": text.len() });
+    if let Some(encoding) = encoding {
+        content["encoding"] = encoding.into();
+    }
+    serde_json::json!({
+        "request": { "method": "GET", "url": url },
+        "response": { "status": 200, "content": content }
+    })
+}
+
+fn har(entries: Vec<serde_json::Value>) -> String {
+    serde_json::json!({ "log": { "version": "1.2", "entries": entries } }).to_string()
+}
+
+#[test]
+fn responses_become_typed_items_with_their_urls() {
+    let mut cs = ChadSelect::new();
+    let added = cs.add_har(&har(vec![
+        entry(
+            "https://example.com/car/42",
+            "text/html; charset=utf-8",
+            "<h1>Civic</h1>",
+            None,
+        ),
+        entry(
+            "https://example.com/api/42",
+            "application/json",
+            r#"{"price": 28500}"#,
+            None,
+        ),
+        entry(
+            "https://example.com/notes.txt",
+            "text/plain",
+            "VIN 1HGFE2F59PA000001",
+            None,
+        ),
+    ]));
+
+    assert_eq!(added, Ok(3));
+    let types: Vec<ContentType> = (0..3)
+        .map(|i| cs.content_info(i).unwrap().content_type)
+        .collect();
+    assert_eq!(
+        types,
+        vec![ContentType::Html, ContentType::Json, ContentType::Text]
+    );
+    assert_eq!(cs.content_source(0), Some("https://example.com/car/42"));
+    assert_eq!(cs.select(0, "css:h1"), "Civic");
+    assert_eq!(cs.select(0, "json:price"), "28500");
+    assert_eq!(cs.select(0, r"regex:VIN (\w+)"), "1HGFE2F59PA000001");
+}
+
+#[test]
+fn base64_bodies_are_decoded() {
+    let mut cs = ChadSelect::new();
+    // `{"model": "Accord"}`
+    let body = "eyJtb2RlbCI6ICJBY2NvcmQifQ==";
+    cs.add_har(&har(vec![entry(
+        "https://example.com/a",
+        "application/json",
+        body,
+        Some("base64"),
+    )]))
+    .unwrap();
+
+    assert_eq!(cs.select(0, "json:model"), "Accord");
+}
+
+#[test]
+fn binary_and_empty_responses_are_skipped() {
+    let mut cs = ChadSelect::new();
+    let added = cs.add_har(&har(vec![
+        entry(
+            "https://example.com/logo.png",
+            "image/png",
+            "iVBORw0KGgo=",
+            Some("base64"),
+        ),
+        entry(
+            "https://example.com/font.woff2",
+            "font/woff2",
+            "d09GMgABAAAA",
+            Some("base64"),
+        ),
+        entry("https://example.com/204", "text/html", "", None),
+        entry(
+            "https://example.com/icon.svg",
+            "image/svg+xml",
+            "<svg><title>i</title></svg>",
+            None,
+        ),
+    ]));
+
+    assert_eq!(added, Ok(1));
+    assert_eq!(cs.content_source(0), Some("https://example.com/icon.svg"));
+}
+
+#[test]
+fn missing_mime_types_are_sniffed() {
+    let mut cs = ChadSelect::new();
+    cs.add_har(&har(vec![entry(
+        "https://example.com/x",
+        "",
+        r#"[{"id": 1}]"#,
+        None,
+    )]))
+    .unwrap();
+
+    assert_eq!(cs.content_info(0).unwrap().content_type, ContentType::Json);
+}
+
+#[test]
+fn invalid_archives_are_errors() {
+    let mut cs = ChadSelect::new();
+
+    assert!(cs.add_har("not json").is_err());
+    assert!(cs.add_har(r#"{"log": {}}"#).is_err());
+    assert_eq!(cs.content_count(), 0);
+}
+
+#[test]
+fn rejected_bodies_are_skipped_with_a_warning() {
+    let mut cs = ChadSelect::builder().max_item_bytes(32).build();
+    let big = format!("<p>{}</p>", "x".repeat(64));
+    let added = cs.add_har(&har(vec![
+        entry("https://example.com/big", "text/html", &big, None),
+        entry("https://example.com/small", "text/html", "<p>ok</p>", None),
+    ]));
+
+    assert_eq!(added, Ok(1));
+    let warnings = cs.take_warnings();
+    assert!(warnings
+        .iter()
+        .any(|w| w.message.contains("https://example.com/big")));
+}
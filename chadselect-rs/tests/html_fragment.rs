@@ -0,0 +1,70 @@
+//! Tests for `add_html_fragment`.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+const SNIPPET: &str = r#"<div class="car">Civic</div><div class="car">Accord</div><span>CR-V</span>"#;
+
+#[test]
+fn absolute_xpath_addresses_top_level_nodes() {
+    let mut cs = ChadSelect::new();
+    cs.add_html_fragment(SNIPPET.to_string());
+
+    assert_eq!(cs.select(0, "xpath:/div[1]/text()"), "Civic");
+    assert_eq!(cs.select(0, "xpath:/div[2]/text()"), "Accord");
+    assert_eq!(cs.select(0, "xpath:/span/text()"), "CR-V");
+    assert_eq!(cs.select(0, "xpath:count(/*)"), "3");
+}
+
+#[test]
+fn document_parsing_wraps_the_same_snippet() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(SNIPPET.to_string());
+
+    assert_eq!(cs.select(0, "xpath:/div[1]/text()"), "");
+    assert_eq!(cs.select(0, "xpath:/html/body/div[1]/text()"), "Civic");
+}
+
+#[test]
+fn no_implied_html_or_body_elements() {
+    let mut cs = ChadSelect::new();
+    cs.add_html_fragment(SNIPPET.to_string());
+
+    assert_eq!(cs.select(0, "xpath:count(//html)"), "0");
+    assert_eq!(cs.select(0, "xpath:count(//body)"), "0");
+    assert!(cs.query(-1, "css:body").is_empty());
+}
+
+#[test]
+fn css_and_regex_work_as_for_documents() {
+    let mut cs = ChadSelect::new();
+    cs.add_html_fragment(SNIPPET.to_string());
+
+    assert_eq!(cs.query(-1, "css:div.car"), vec!["Civic", "Accord"]);
+    assert_eq!(cs.query(-1, "css:div.car, span"), vec!["Civic", "Accord", "CR-V"]);
+    assert_eq!(cs.select(0, r"regex:<span>(.+?)</span>"), "CR-V");
+}
+
+#[test]
+fn top_level_text_nodes_are_children_of_the_root() {
+    let mut cs = ChadSelect::new();
+    cs.add_html_fragment("<p>one</p>text<p>two</p>".to_string());
+
+    assert_eq!(cs.query(-1, "xpath:/p/text()"), vec!["one", "two"]);
+    assert_eq!(cs.select(0, "xpath:/text()"), "text");
+}
+
+#[test]
+fn fragment_mode_survives_snapshots_and_clones() {
+    let mut cs = ChadSelect::new();
+    cs.add_html_fragment(SNIPPET.to_string());
+
+    #[cfg(feature = "snapshot")]
+    {
+        let restored = ChadSelect::from_snapshot(&cs.snapshot());
+        assert_eq!(restored.select(0, "xpath:/div[2]/text()"), "Accord");
+    }
+
+    let cloned = cs.clone();
+    assert_eq!(cloned.select(0, "xpath:/span/text()"), "CR-V");
+}
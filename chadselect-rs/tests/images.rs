@@ -0,0 +1,172 @@
+//! Tests for `extract_images` and `parse_srcset`.
+#![cfg(feature = "css")]
+
+use chadselect::images::parse_srcset;
+use chadselect::{ChadSelect, SrcsetCandidate};
+
+fn page(html: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(html.to_string());
+    cs
+}
+
+fn candidate(url: &str, width: Option<u32>, density: Option<f32>) -> SrcsetCandidate {
+    SrcsetCandidate {
+        url: url.to_string(),
+        width,
+        density,
+    }
+}
+
+#[test]
+fn srcset_widths_and_densities() {
+    assert_eq!(
+        parse_srcset("  /s.jpg 320w,\n /m.jpg 640w , /l.jpg   1280w "),
+        vec![
+            candidate("/s.jpg", Some(320), None),
+            candidate("/m.jpg", Some(640), None),
+            candidate("/l.jpg", Some(1280), None),
+        ]
+    );
+    assert_eq!(
+        parse_srcset("/a.jpg, /a@1.5.jpg 1.5x,/a@2.jpg 2x"),
+        vec![
+            candidate("/a.jpg", None, None),
+            candidate("/a@1.5.jpg", None, Some(1.5)),
+            candidate("/a@2.jpg", None, Some(2.0)),
+        ]
+    );
+}
+
+#[test]
+fn srcset_urls_may_contain_commas() {
+    assert_eq!(
+        parse_srcset(concat!(
+            "https://cdn.example/c_fill,w_400/car.jpg 400w, ",
+            "https://cdn.example/c_fill,w_800/car.jpg 800w",
+        )),
+        vec![
+            candidate("https://cdn.example/c_fill,w_400/car.jpg", Some(400), None),
+            candidate("https://cdn.example/c_fill,w_800/car.jpg", Some(800), None),
+        ]
+    );
+    // A comma directly after a URL ends its candidate.
+    assert_eq!(
+        parse_srcset("/a.jpg,/b.jpg 2x"),
+        vec![candidate("/a.jpg,/b.jpg", None, Some(2.0))]
+    );
+    assert_eq!(
+        parse_srcset("/a.jpg, /b.jpg 2x"),
+        vec![
+            candidate("/a.jpg", None, None),
+            candidate("/b.jpg", None, Some(2.0))
+        ]
+    );
+}
+
+#[test]
+fn invalid_srcset_candidates_are_dropped() {
+    assert_eq!(
+        parse_srcset("/a.jpg 0w, /b.jpg 2x 400w, /c.jpg 10q, /d.jpg -1x, /e.jpg 3x"),
+        vec![candidate("/e.jpg", None, Some(3.0))]
+    );
+    assert!(parse_srcset("").is_empty());
+    assert!(parse_srcset(" , ,").is_empty());
+}
+
+#[test]
+fn image_attributes() {
+    let cs = page(
+        r#"<img src="/front.jpg" srcset="/front-2x.jpg 2x" alt="Front" title="Front view"
+                width="640px" height="480">
+           <img src="/spacer.gif" alt="">
+           <img src="/broken.jpg" width="auto">"#,
+    );
+    let images = cs.extract_images(0);
+    assert_eq!(images.len(), 3);
+
+    assert_eq!(images[0].src.as_deref(), Some("/front.jpg"));
+    assert_eq!(
+        images[0].srcset,
+        vec![candidate("/front-2x.jpg", None, Some(2.0))]
+    );
+    assert_eq!(images[0].alt.as_deref(), Some("Front"));
+    assert_eq!(images[0].title.as_deref(), Some("Front view"));
+    assert_eq!((images[0].width, images[0].height), (Some(640), Some(480)));
+    assert!(!images[0].lazy);
+
+    assert_eq!(images[1].alt.as_deref(), Some(""));
+    assert_eq!(images[2].alt, None);
+    assert_eq!(images[2].width, None);
+}
+
+#[test]
+fn lazy_load_fallbacks() {
+    let cs = page(
+        r#"<img data-src="/a.jpg">
+           <img src="data:image/gif;base64,R0lGOD" data-lazy-src="/b.jpg" data-srcset="/b-2x.jpg 2x">
+           <img src="/c.jpg" data-src="/c-large.jpg">
+           <img src="" data-original="/d.jpg">
+           <img src="/e.jpg" data-srcset="/e-800.jpg 800w">
+           <img>"#,
+    );
+    let images = cs.extract_images(0);
+    let srcs: Vec<Option<&str>> = images.iter().map(|i| i.src.as_deref()).collect();
+    assert_eq!(
+        srcs,
+        vec![
+            Some("/a.jpg"),
+            Some("/b.jpg"),
+            Some("/c.jpg"),
+            Some("/d.jpg"),
+            Some("/e.jpg"),
+            None
+        ]
+    );
+    let lazy: Vec<bool> = images.iter().map(|i| i.lazy).collect();
+    assert_eq!(lazy, vec![true, true, false, true, true, false]);
+    assert_eq!(
+        images[1].srcset,
+        vec![candidate("/b-2x.jpg", None, Some(2.0))]
+    );
+    assert_eq!(
+        images[4].srcset,
+        vec![candidate("/e-800.jpg", Some(800), None)]
+    );
+}
+
+#[test]
+fn picture_sources_are_included() {
+    let cs = page(
+        r#"<picture>
+             <source type="image/avif" srcset="/car.avif 1x, /car@2.avif 2x">
+             <source media="(max-width: 600px)" srcset="/car-small.webp">
+             <img src="/car.jpg" alt="Car">
+           </picture>"#,
+    );
+    let images = cs.extract_images(0);
+    assert_eq!(images.len(), 1);
+    assert_eq!(
+        images[0].srcset,
+        vec![
+            candidate("/car.avif", None, Some(1.0)),
+            candidate("/car@2.avif", None, Some(2.0)),
+            candidate("/car-small.webp", None, None),
+        ]
+    );
+}
+
+#[test]
+fn links_assets_use_the_srcset_parser() {
+    let cs = page(r#"<img srcset="https://cdn.example/c_fill,w_400/car.jpg 400w">"#);
+    let assets = cs.extract_assets(0);
+    assert_eq!(assets[0].url, "https://cdn.example/c_fill,w_400/car.jpg");
+}
+
+#[test]
+fn non_html_and_out_of_range_are_empty() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(r#"<img src="/x.jpg">"#.to_string());
+    assert!(cs.extract_images(0).is_empty());
+    assert!(cs.extract_images(3).is_empty());
+}
@@ -0,0 +1,84 @@
+//! Tests for `IndexMode::PerContent`.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, IndexMode};
+
+fn per_content() -> ChadSelect {
+    let mut cs = ChadSelect::builder()
+        .index_mode(IndexMode::PerContent)
+        .build();
+    cs.add_html(
+        "<h1>Civic</h1><span class='price'>$1</span><span class='price'>$2</span>".to_string(),
+    );
+    cs.add_html("<h1>Accord</h1>".to_string());
+    cs.add_html("<h1>CR-V</h1><span class='price'>$3</span>".to_string());
+    cs
+}
+
+#[test]
+fn index_picks_within_each_item() {
+    let cs = per_content();
+    assert_eq!(cs.query(0, "css:h1"), vec!["Civic", "Accord", "CR-V"]);
+    assert_eq!(cs.query(0, "css:.price"), vec!["$1", "$3"]);
+    assert_eq!(cs.query(1, "css:.price"), vec!["$2"]);
+    assert!(cs.query(5, "css:.price").is_empty());
+    assert_eq!(cs.query(-1, "css:.price"), vec!["$1", "$2", "$3"]);
+}
+
+#[test]
+fn global_mode_is_unchanged() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<h1>Civic</h1>".to_string());
+    cs.add_html("<h1>Accord</h1>".to_string());
+    assert_eq!(cs.query(0, "css:h1"), vec!["Civic"]);
+    assert_eq!(cs.query(1, "css:h1"), vec!["Accord"]);
+}
+
+#[test]
+fn every_engine_and_method_honours_the_mode() {
+    let mut cs = ChadSelect::builder()
+        .index_mode(IndexMode::PerContent)
+        .build();
+    cs.add_text("id=1 id=2".to_string());
+    cs.add_text("id=3".to_string());
+    cs.add_json(r#"{"ids": [4, 5]}"#.to_string());
+    cs.add_json(r#"{"ids": [6]}"#.to_string());
+
+    assert_eq!(cs.query(0, r"regex:id=(\d)"), vec!["1", "3"]);
+    assert_eq!(cs.query(0, "json:ids[]"), vec!["4", "6"]);
+    assert_eq!(cs.query_borrowed(1, r"regex:id=(\d)"), vec!["2"]);
+
+    let r = cs.query_detailed(0, r"regex:id=(\d)");
+    assert_eq!(r.results, vec!["1", "3"]);
+    let items: Vec<_> = r.locations.iter().map(|l| l.content_index).collect();
+    assert_eq!(items, vec![0, 1]);
+}
+
+#[test]
+fn batch_and_attribute_queries() {
+    let cs = per_content();
+    let results = cs.query_batch(&[(0, "css:h1"), (1, "css:.price"), (0, "xpath://h1")]);
+    assert_eq!(results[0], vec!["Civic", "Accord", "CR-V"]);
+    assert_eq!(results[1], vec!["$2"]);
+    assert_eq!(results[2], vec!["Civic", "Accord", "CR-V"]);
+
+    let maps = cs.query_attrs(0, "css:.price");
+    assert_eq!(maps.len(), 2);
+}
+
+#[test]
+fn fallback_chains_and_select() {
+    let cs = per_content();
+    assert_eq!(
+        cs.query(0, "css:.missing || css:h1"),
+        vec!["Civic", "Accord", "CR-V"]
+    );
+    assert_eq!(cs.select(0, "css:.price"), "$1");
+    assert_eq!(cs.select(1, "css:.price"), "$2");
+}
+
+#[test]
+fn invalid_index_is_empty() {
+    let cs = per_content();
+    assert!(cs.query(-2, "css:h1").is_empty());
+}
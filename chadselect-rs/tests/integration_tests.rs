@@ -1,4 +1,5 @@
 //! Integration tests — cross-engine and multi-content scenarios.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
 
 use chadselect::ChadSelect;
 
@@ -29,6 +30,30 @@ fn select_first_returns_empty_when_all_miss() {
     assert!(result.is_empty());
 }
 
+#[test]
+fn select_first_tagged_reports_winner() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<span id="vin">ABC123</span>"#.to_string());
+
+    let hit = cs.select_first_tagged(vec![
+        (0, "css:#nonexistent"),
+        (0, "xpath://span[@id='vin']/text()"),
+        (0, "css:#vin"),
+    ]);
+    assert_eq!(hit, Some((1, vec!["ABC123".to_string()])));
+
+    assert_eq!(cs.select_first_tagged(vec![(0, "css:.nope")]), None);
+}
+
+#[test]
+fn select_first_tagged_where_skips_rejected() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("a: 0\nb: 42".to_string());
+
+    let hit = cs.select_first_tagged_where(vec![(0, r"a: (\d+)"), (0, r"b: (\d+)")], |s| s != "0");
+    assert_eq!(hit, Some((1, vec!["42".to_string()])));
+}
+
 // ─── select_many combines results ───────────────────────────────────────────
 
 #[test]
@@ -218,7 +243,7 @@ fn select_first_where_all_rejected_returns_empty() {
 
     let r = cs.select_first_where(
         vec![(0, r"(\d+)")],
-        |s| s.parse::<f64>().map_or(false, |n| n > 100.0),
+        |s| s.parse::<f64>().is_ok_and(|n| n > 100.0),
     );
     assert!(r.is_empty());
 }
@@ -261,14 +286,59 @@ fn select_where_numeric_range_validator() {
 
     // Accept only prices > 10
     let r = cs.select_where(0, "json:price", |s| {
-        s.parse::<f64>().map_or(false, |n| n > 10.0)
+        s.parse::<f64>().is_ok_and(|n| n > 10.0)
     });
     assert_eq!(r, "");
 
     cs.clear();
     cs.add_json(r#"{"price": 49.99}"#.to_string());
     let r = cs.select_where(0, "json:price", |s| {
-        s.parse::<f64>().map_or(false, |n| n > 10.0)
+        s.parse::<f64>().is_ok_and(|n| n > 10.0)
     });
     assert_eq!(r, "49.99");
 }
+
+// ─── query_batch ────────────────────────────────────────────────────────────
+
+#[test]
+fn query_batch_matches_individual_queries() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<div class="car"><h1>Civic</h1><a class="vin" href="/v/1">VIN 1HGCM</a>
+           <span class="price">$28,500</span><span class="price">$0</span>
+           <span>Miles:</span><span>12,000</span></div>"#
+            .to_string(),
+    );
+    cs.add_json(r#"{"dealer": "Metro Honda"}"#.to_string());
+    cs.add_html(r#"<h1>Accord</h1><span class="price">$31,000</span>"#.to_string());
+
+    let queries = [
+        (-1, "css:h1"),
+        (0, "css:.price"),
+        (-1, "css:.price >> normalize-space()"),
+        (0, "css:a.vin >> get-attr('href')"),
+        (0, "css:span:text-equals(Miles:) + span"),
+        (0, "css:.missing || css:h1"),
+        (0, "json:dealer"),
+        (-1, "xpath://h1/text()"),
+        (0, r"VIN (\w+)"),
+        (5, "css:h1"),
+        (0, "css:[[invalid"),
+    ];
+
+    let batch = cs.query_batch(&queries);
+    assert_eq!(batch.len(), queries.len());
+    for ((index, query), results) in queries.iter().zip(&batch) {
+        assert_eq!(results, &cs.query(*index, query), "query {}", query);
+    }
+    assert_eq!(batch[0], vec!["Civic", "Accord"]);
+    assert_eq!(batch[3], vec!["/v/1"]);
+    assert!(batch[10].is_empty());
+}
+
+#[test]
+fn query_batch_on_empty_content() {
+    let cs = ChadSelect::new();
+    let batch = cs.query_batch(&[(0, "css:h1"), (-1, "json:a")]);
+    assert_eq!(batch, vec![Vec::<String>::new(), vec![]]);
+}
@@ -19,7 +19,9 @@
 //! `json:` selectors against one already-parsed document, in two dimensions:
 //!   * **allocations** (count + bytes) via a counting `#[global_allocator]`;
 //!   * **CPU** (wall-clock over many iterations).
+//!
 //! Run: `cargo test --release --test jmespath_alloc report -- --nocapture --test-threads=1`
+#![cfg(feature = "json")]
 
 use std::alloc::{GlobalAlloc, Layout, System};
 use std::sync::atomic::{AtomicU64, Ordering};
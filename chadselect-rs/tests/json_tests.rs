@@ -1,4 +1,5 @@
 //! Tests for the JMESPath extraction engine.
+#![cfg(all(feature = "css", feature = "json"))]
 
 use chadselect::ChadSelect;
 
@@ -132,3 +133,19 @@ fn json_query_skips_text_content() {
     let results = cs.query(-1, "json:whatever");
     assert!(results.is_empty());
 }
+
+// ─── Function piping ────────────────────────────────────────────────────────
+
+#[test]
+fn json_function_pipe() {
+    let cs = make_cs();
+    let results = cs.query(-1, "json:store.inventory[].name >> uppercase() >> trim()");
+    assert_eq!(results, vec!["WIDGET", "GADGET", "DOOHICKEY"]);
+}
+
+#[test]
+fn json_function_pipe_fold() {
+    let cs = make_cs();
+    let result = cs.select(0, "json:store.inventory[?in_stock].name >> join(', ')");
+    assert_eq!(result, "Widget, Doohickey");
+}
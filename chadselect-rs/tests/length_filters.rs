@@ -0,0 +1,63 @@
+//! Tests for the `min-length()`, `max-length()`, and `word-count()` pipe
+//! functions.
+#![cfg(feature = "css")]
+
+use chadselect::functions::{apply_text_functions, try_parse_text_functions};
+use chadselect::ChadSelect;
+
+fn apply(values: &[&str], chain: &str) -> Vec<String> {
+    let values = values.iter().map(|v| v.to_string()).collect();
+    apply_text_functions(values, &try_parse_text_functions(chain).unwrap())
+}
+
+#[test]
+fn length_filters_count_characters() {
+    let values = &["ok", "héllo", "a longer value"];
+    assert_eq!(
+        apply(values, "min-length(5)"),
+        vec!["héllo", "a longer value"]
+    );
+    assert_eq!(apply(values, "max-length(5)"), vec!["ok", "héllo"]);
+    assert_eq!(
+        apply(values, "min-length(3) >> max-length(5)"),
+        vec!["héllo"]
+    );
+    assert_eq!(apply(values, "min-length(0)"), values.to_vec());
+}
+
+#[test]
+fn word_count_follows_unicode_word_boundaries() {
+    assert_eq!(
+        apply(
+            &["Low miles, one owner!", "2019 Honda Civic EX-L", "  "],
+            "word-count()"
+        ),
+        vec!["4", "5", "0"]
+    );
+    assert_eq!(apply(&["can't stop"], "word-count()"), vec!["2"]);
+}
+
+#[test]
+fn filters_gate_query_results() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        "<p>Hi</p><p>This paragraph has enough text to keep.</p><p>Short one.</p>".to_string(),
+    );
+    assert_eq!(
+        cs.query(-1, "css:p >> min-length(12)"),
+        vec!["This paragraph has enough text to keep."]
+    );
+    assert_eq!(cs.query(-1, "css:p >> word-count() >> sum()"), vec!["10"]);
+}
+
+#[test]
+fn lengths_must_be_bare_numbers() {
+    for chain in [
+        "min-length()",
+        "min-length('5')",
+        "max-length(-1)",
+        "word-count(3)",
+    ] {
+        assert!(try_parse_text_functions(chain).is_err(), "{}", chain);
+    }
+}
@@ -0,0 +1,94 @@
+//! Tests for named queries (`QueryLibrary` and `ref:`).
+#![cfg(feature = "xpath")]
+
+use chadselect::{ChadSelect, QueryLibrary};
+
+fn cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<h1> Civic </h1><span class="price">$28,500</span>"#.to_string());
+    cs
+}
+
+#[test]
+fn ref_runs_named_query() {
+    let mut cs = cs();
+    cs.define("title", "css:h1 >> trim()");
+    assert_eq!(cs.select(0, "ref:title"), "Civic");
+    assert_eq!(cs.select(0, "ref:title >> uppercase()"), "CIVIC");
+}
+
+#[test]
+fn refs_resolve_recursively() {
+    let mut cs = cs();
+    cs.define("price", "css:.price");
+    cs.define("price_digits", "ref:price >> translate('$,','')");
+    assert_eq!(cs.select(0, "ref:price_digits"), "28500");
+}
+
+#[test]
+fn unknown_and_cyclic_refs_return_nothing() {
+    let mut cs = cs();
+    assert!(cs.query(-1, "ref:missing").is_empty());
+
+    cs.define("a", "ref:b");
+    cs.define("b", "ref:a");
+    assert!(cs.query(-1, "ref:a").is_empty());
+    assert!(cs.library().resolve("ref:a").is_err());
+}
+
+#[test]
+fn library_is_shared_via_builder_and_prepare() {
+    let mut lib = QueryLibrary::new();
+    lib.define("price", "css:.price");
+
+    let mut cs = ChadSelect::builder().library(lib.clone()).build();
+    cs.add_html(r#"<span class="price">$1</span>"#.to_string());
+    let prepared = cs.prepare("ref:price").unwrap();
+    assert_eq!(cs.select_prepared(0, &prepared), "$1");
+    assert!(cs.prepare("ref:nope").is_err());
+    assert_eq!(lib.len(), 1);
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn library_loads_from_toml() {
+    let mut cs = cs();
+    let loaded = cs
+        .library_mut()
+        .load_toml(
+            r#"
+            title = "css:h1 >> trim()"
+
+            [v2]
+            price = "css:.price >> substring-after('$')"
+            "#,
+        )
+        .unwrap();
+    assert_eq!(loaded, 2);
+    assert_eq!(cs.select(0, "ref:title"), "Civic");
+    assert_eq!(cs.select(0, "ref:v2.price"), "28,500");
+    assert!(cs.library_mut().load_toml("n = 1").is_err());
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn library_loads_from_yaml() {
+    let mut cs = cs();
+    let loaded = cs
+        .library_mut()
+        .load_yaml(
+            r#"
+title: "css:h1 >> trim()"
+v2:
+  price: "css:.price >> substring-after('$')"
+"#,
+        )
+        .unwrap();
+    assert_eq!(loaded, 2);
+    assert_eq!(cs.select(0, "ref:title"), "Civic");
+    assert_eq!(cs.select(0, "ref:v2.price"), "28,500");
+    assert_eq!(cs.library_mut().load_yaml(""), Ok(0));
+    assert!(cs.library_mut().load_yaml("n: 1").is_err());
+    assert!(cs.library_mut().load_yaml("- css:h1").is_err());
+    assert!(cs.library_mut().load_yaml("title: [").is_err());
+}
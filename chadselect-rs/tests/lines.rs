@@ -0,0 +1,93 @@
+//! Tests for the `lines:` prefix.
+#![cfg(all(feature = "css", feature = "regex"))]
+
+use chadselect::{ChadSelect, PreparedQuery};
+
+const LOG: &str =
+    "INFO: started\r\nERROR: disk full\n\nWARN: error rate > 5%\nERROR: net down (eth0)\nDONE\n";
+
+fn log() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(LOG.to_string());
+    cs
+}
+
+#[test]
+fn literal_filters_pick_whole_lines() {
+    let cs = log();
+    assert_eq!(
+        cs.query(-1, "lines:contains('ERROR')"),
+        vec!["ERROR: disk full", "ERROR: net down (eth0)"]
+    );
+    assert_eq!(
+        cs.query(-1, "lines:starts-with('INFO')"),
+        vec!["INFO: started"]
+    );
+    assert_eq!(
+        cs.query(-1, "lines:ends-with('(eth0)')"),
+        vec!["ERROR: net down (eth0)"]
+    );
+    assert_eq!(cs.query(-1, "lines:equals('DONE')"), vec!["DONE"]);
+    assert!(cs.query(-1, "lines:equals('ERROR')").is_empty());
+    // Regex characters and `>` are literal.
+    assert_eq!(
+        cs.query(-1, "lines:contains('> 5%')"),
+        vec!["WARN: error rate > 5%"]
+    );
+}
+
+#[test]
+fn regex_filters_and_chains() {
+    let cs = log();
+    assert_eq!(
+        cs.query(
+            -1,
+            "lines:matches('(?i)^(warn|error)') >> substring-after(': ')"
+        ),
+        vec!["disk full", "error rate > 5%", "net down (eth0)"]
+    );
+    assert_eq!(
+        cs.query(-1, "lines:not-matches(':') >> lowercase()"),
+        vec!["done"]
+    );
+    assert_eq!(
+        cs.query(
+            -1,
+            "lines:contains('ERROR') >> substring-after(': ') >> last()"
+        ),
+        vec!["net down (eth0)"]
+    );
+}
+
+#[test]
+fn no_filter_keeps_every_non_empty_line() {
+    let cs = log();
+    assert_eq!(cs.query(-1, "lines: >> count()"), vec!["5"]);
+    assert_eq!(
+        cs.query(-1, "lines:"),
+        LOG.lines()
+            .map(str::trim_end)
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn fallbacks_spans_and_errors() {
+    let cs = log();
+    assert_eq!(
+        cs.select(0, "css:.log || lines:contains('WARN')"),
+        "WARN: error rate > 5%"
+    );
+    assert_eq!(
+        cs.query_detailed(0, "lines:equals('DONE')").engine(),
+        Some("regex")
+    );
+    let spans = cs.query_spans(0, "lines:starts-with('ERROR')");
+    assert_eq!(&LOG[spans[0].0..spans[0].1], "ERROR: disk full");
+
+    assert!(PreparedQuery::new("lines:grep('x')").is_err());
+    assert!(PreparedQuery::new("lines:contains()").is_err());
+    assert!(PreparedQuery::new("lines:contains('a', 'b')").is_err());
+    assert!(PreparedQuery::new("lines:matches('(')").is_err());
+}
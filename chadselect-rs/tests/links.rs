@@ -0,0 +1,191 @@
+//! Tests for `extract_links` and `extract_assets`.
+#![cfg(all(feature = "css", feature = "json"))]
+
+use chadselect::{ChadSelect, LinkOptions};
+
+fn page(html: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(html.to_string());
+    cs
+}
+
+fn based(base: &str, same_origin: bool) -> LinkOptions {
+    LinkOptions {
+        base_url: Some(base.to_string()),
+        same_origin,
+    }
+}
+
+#[test]
+fn links_in_document_order() {
+    let cs = page(
+        r##"<nav>
+          <a href="/cars">  All <b>cars</b> </a>
+          <a href="#top">Top</a>
+          <a href="javascript:void(0)">Menu</a>
+          <a>No href</a>
+          <a href="https://other.example/x" rel="nofollow noopener">Elsewhere</a>
+        </nav>
+        <map><area href="/zone" alt="Zone"></map>"##,
+    );
+    let links = cs.extract_links(0);
+    let urls: Vec<&str> = links.iter().map(|l| l.url.as_str()).collect();
+    assert_eq!(urls, vec!["/cars", "https://other.example/x", "/zone"]);
+
+    assert_eq!(links[0].text, "All cars");
+    assert_eq!(links[0].tag, "a");
+    assert_eq!(links[0].attribute, "href");
+    assert_eq!(links[0].rel, None);
+    assert_eq!(links[1].rel.as_deref(), Some("nofollow noopener"));
+    assert_eq!(links[2].tag, "area");
+    assert_eq!(links[2].text, "Zone");
+}
+
+#[test]
+fn base_url_resolves_relative_links() {
+    let cs = page(
+        r#"<a href="civic">Civic</a><a href="../about">About</a><a href="//cdn.example/a">CDN</a>"#,
+    );
+    let urls: Vec<String> = cs
+        .extract_links_with(0, &based("https://example.com/cars/", false))
+        .into_iter()
+        .map(|l| l.url)
+        .collect();
+    assert_eq!(
+        urls,
+        vec![
+            "https://example.com/cars/civic",
+            "https://example.com/about",
+            "https://cdn.example/a",
+        ]
+    );
+}
+
+#[test]
+fn base_element_applies_on_top_of_the_base_url() {
+    let cs = page(r#"<head><base href="/inventory/"></head><a href="civic">Civic</a>"#);
+    let links = cs.extract_links_with(0, &based("https://example.com/cars/list", false));
+    assert_eq!(links[0].url, "https://example.com/inventory/civic");
+
+    // An absolute <base> alone is enough to resolve.
+    let cs = page(r#"<head><base href="https://example.org/a/"></head><a href="b">B</a>"#);
+    assert_eq!(cs.extract_links(0)[0].url, "https://example.org/a/b");
+}
+
+#[test]
+fn same_origin_filter() {
+    let cs = page(
+        r#"<a href="/a">A</a>
+           <a href="https://example.com/b">B</a>
+           <a href="https://example.com:8443/c">C</a>
+           <a href="http://example.com/d">D</a>
+           <a href="https://other.example/e">E</a>"#,
+    );
+    let urls: Vec<String> = cs
+        .extract_links_with(0, &based("https://example.com/", true))
+        .into_iter()
+        .map(|l| l.url)
+        .collect();
+    assert_eq!(urls, vec!["https://example.com/a", "https://example.com/b"]);
+
+    // Without any base, only relative links are kept.
+    let options = LinkOptions {
+        base_url: None,
+        same_origin: true,
+    };
+    let urls: Vec<String> = cs
+        .extract_links_with(0, &options)
+        .into_iter()
+        .map(|l| l.url)
+        .collect();
+    assert_eq!(urls, vec!["/a"]);
+}
+
+#[test]
+fn assets_cover_media_scripts_and_stylesheets() {
+    let cs = page(
+        r#"<head>
+          <link rel="stylesheet" href="/site.css">
+          <link rel="canonical" href="/page">
+          <link rel="Icon" href="/favicon.ico">
+          <script src="/app.js"></script>
+          <script>inline()</script>
+        </head>
+        <body>
+          <img src="/a.jpg" alt="Front view" srcset="/a-2x.jpg 2x, /a-3x.jpg 3x">
+          <img src="data:image/png;base64,AAAA">
+          <video src="/clip.mp4" poster="/poster.jpg"></video>
+          <iframe src="https://maps.example/embed" title="Map"></iframe>
+          <object data="/doc.pdf"></object>
+        </body>"#,
+    );
+    let assets = cs.extract_assets(0);
+    let found: Vec<(&str, &str, &str)> = assets
+        .iter()
+        .map(|a| (a.tag.as_str(), a.attribute.as_str(), a.url.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("link", "href", "/site.css"),
+            ("link", "href", "/favicon.ico"),
+            ("script", "src", "/app.js"),
+            ("img", "src", "/a.jpg"),
+            ("img", "srcset", "/a-2x.jpg"),
+            ("img", "srcset", "/a-3x.jpg"),
+            ("video", "src", "/clip.mp4"),
+            ("video", "poster", "/poster.jpg"),
+            ("iframe", "src", "https://maps.example/embed"),
+            ("object", "data", "/doc.pdf"),
+        ]
+    );
+    assert_eq!(assets[3].text, "Front view");
+    assert_eq!(assets[8].text, "Map");
+    assert_eq!(assets[0].rel.as_deref(), Some("stylesheet"));
+}
+
+#[test]
+fn assets_resolve_and_filter_like_links() {
+    let cs = page(r#"<img src="img/a.jpg"><script src="https://cdn.example/lib.js"></script>"#);
+    let urls: Vec<String> = cs
+        .extract_assets_with(0, &based("https://example.com/cars/", true))
+        .into_iter()
+        .map(|a| a.url)
+        .collect();
+    assert_eq!(urls, vec!["https://example.com/cars/img/a.jpg"]);
+}
+
+#[test]
+fn file_sources_are_not_used_as_a_base() {
+    let dir = std::env::temp_dir().join(format!("chadselect-links-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("page.html");
+    std::fs::write(&path, r#"<a href="next.html">Next</a>"#).unwrap();
+
+    let mut cs = ChadSelect::new();
+    cs.add_file(&path).unwrap();
+    assert_eq!(cs.extract_links(0)[0].url, "next.html");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn markdown_links_are_extracted() {
+    let mut cs = ChadSelect::new();
+    cs.add_markdown("See [the docs](https://example.com/docs) and ![logo](/logo.png).".to_string());
+    assert_eq!(cs.extract_links(0)[0].url, "https://example.com/docs");
+    assert_eq!(cs.extract_links(0)[0].text, "the docs");
+    assert_eq!(cs.extract_assets(0)[0].url, "/logo.png");
+    assert_eq!(cs.extract_assets(0)[0].text, "logo");
+}
+
+#[test]
+fn non_html_and_out_of_range_are_empty() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(r#"<a href="/x">X</a>"#.to_string());
+    cs.add_json(r#"{"href": "/x"}"#.to_string());
+    assert!(cs.extract_links(0).is_empty());
+    assert!(cs.extract_links(1).is_empty());
+    assert!(cs.extract_assets(0).is_empty());
+    assert!(cs.extract_links(7).is_empty());
+    assert!(cs.extract_assets(7).is_empty());
+}
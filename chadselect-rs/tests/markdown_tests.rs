@@ -0,0 +1,76 @@
+//! Tests for Markdown content (`add_markdown`).
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+const README: &str = r#"# chadselect
+
+Unified **data extraction** for [Rust](https://www.rust-lang.org).
+
+## Install
+
+```toml
+[dependencies]
+chadselect = "0.4"
+```
+
+## Features
+
+- Regex
+- XPath
+- ~~sxd~~ chadpath
+
+| Engine | Prefix |
+|--------|--------|
+| CSS    | css:   |
+| JSON   | json:  |
+"#;
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_markdown(README.to_string());
+    cs
+}
+
+#[test]
+fn css_on_rendered_headings() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, "css:h1"), "chadselect");
+    assert_eq!(cs.query(-1, "css:h2"), vec!["Install", "Features"]);
+}
+
+#[test]
+fn css_links_and_emphasis() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, "css:a >> get-attr('href')"), "https://www.rust-lang.org");
+    assert_eq!(cs.select(0, "css:strong"), "data extraction");
+}
+
+#[test]
+fn xpath_code_block() {
+    let cs = make_cs();
+    let code = cs.select(0, "xpath://pre/code");
+    assert!(code.contains(r#"chadselect = "0.4""#));
+    assert_eq!(cs.select(0, "xpath://pre/code/@class"), "language-toml");
+}
+
+#[test]
+fn gfm_tables_and_lists() {
+    let cs = make_cs();
+    assert_eq!(cs.query(-1, "css:td:nth-child(2)"), vec!["css:", "json:"]);
+    assert_eq!(cs.query(-1, "css:li").len(), 3);
+    assert_eq!(cs.select(0, "css:del"), "sxd");
+}
+
+#[test]
+fn regex_runs_on_raw_source() {
+    let cs = make_cs();
+    assert_eq!(cs.query(-1, r"regex:(?m)^## (.+)$"), vec!["Install", "Features"]);
+    assert_eq!(cs.select(0, r"regex:\*\*(.+?)\*\*"), "data extraction");
+}
+
+#[test]
+fn json_skips_markdown() {
+    let cs = make_cs();
+    assert!(cs.query(-1, "json:anything").is_empty());
+}
@@ -0,0 +1,96 @@
+//! Tests for the `max_results` guardrail.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+fn capped(max: usize) -> ChadSelect {
+    ChadSelect::builder().max_results(max).build()
+}
+
+#[test]
+fn runaway_regex_is_truncated() {
+    let mut cs = capped(100);
+    cs.add_text("x".repeat(1_000_000));
+    let r = cs.query_detailed(-1, "regex:.");
+    assert_eq!(r.results.len(), 100);
+    assert!(r.truncated);
+    assert_eq!(cs.query(-1, "regex:.").len(), 100);
+    assert_eq!(cs.query_borrowed(-1, "regex:.").len(), 100);
+}
+
+#[test]
+fn under_the_cap_is_not_truncated() {
+    let mut cs = capped(3);
+    cs.add_html("<p>a</p><p>b</p><p>c</p>".to_string());
+    let r = cs.query_detailed(-1, "css:p");
+    assert_eq!(r.results, vec!["a", "b", "c"]);
+    assert!(!r.truncated);
+    assert_eq!(r.locations.len(), 3);
+}
+
+#[test]
+fn css_and_xpath_keep_the_first_results() {
+    let mut cs = capped(2);
+    cs.add_html("<p>a</p><p></p><p>b</p><p>c</p>".to_string());
+
+    // The empty paragraph does not count toward the cap.
+    let r = cs.query_detailed(-1, "css:p");
+    assert_eq!(r.results, vec!["a", "b"]);
+    assert!(r.truncated);
+    assert_eq!(r.locations.len(), 2);
+
+    let r = cs.query_detailed(-1, "xpath://p");
+    assert_eq!(r.results, vec!["a", "b"]);
+    assert!(r.truncated);
+}
+
+#[test]
+fn cap_spans_content_items() {
+    let mut cs = capped(3);
+    cs.add_html("<b>1</b><b>2</b>".to_string());
+    cs.add_html("<b>3</b><b>4</b>".to_string());
+    cs.add_html("<b>5</b><b>6</b>".to_string());
+    let r = cs.query_detailed(-1, "css:b");
+    assert_eq!(r.results, vec!["1", "2", "3"]);
+    assert!(r.truncated);
+    // The item past the cap is never visited, so never parsed.
+    assert_eq!(cs.memory_usage().parsed_items, 2);
+}
+
+#[test]
+fn function_chains_see_every_match() {
+    let mut cs = capped(2);
+    cs.add_text("a1 a2 a3 a4".to_string());
+    assert_eq!(cs.select(0, r"regex:a\d >> count()"), "4");
+    assert_eq!(cs.select(0, r"regex:a(\d) >> sum()"), "10");
+
+    let r = cs.query_detailed(-1, r"regex:a\d >> uppercase()");
+    assert_eq!(r.results, vec!["A1", "A2"]);
+    assert!(r.truncated);
+}
+
+#[test]
+fn index_selects_within_the_cap() {
+    let mut cs = capped(2);
+    cs.add_text("a b c".to_string());
+    assert_eq!(cs.select(1, "regex:[a-z]"), "b");
+    assert!(cs.query(2, "regex:[a-z]").is_empty());
+}
+
+#[test]
+fn batch_queries_are_capped() {
+    let mut cs = capped(2);
+    cs.add_html("<i>1</i><i>2</i><i>3</i>".to_string());
+    let results = cs.query_batch(&[(-1, "css:i"), (-1, r"regex:\d")]);
+    assert_eq!(results[0], vec!["1", "2"]);
+    assert_eq!(results[1].len(), 2);
+}
+
+#[test]
+fn uncapped_by_default() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("x".repeat(5_000));
+    let r = cs.query_detailed(-1, "regex:.");
+    assert_eq!(r.results.len(), 5_000);
+    assert!(!r.truncated);
+}
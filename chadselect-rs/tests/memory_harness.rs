@@ -24,6 +24,7 @@
 //! `--test-threads=1` matters: the allocator counter is process-global, so
 //! concurrent tests would corrupt each other's measurements. A serializing
 //! mutex is also held during every measurement as a backstop.
+#![cfg(all(feature = "xpath", feature = "regex"))]
 
 use std::alloc::{GlobalAlloc, Layout, System};
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -0,0 +1,132 @@
+//! Tests for `add_mime`.
+#![cfg(all(feature = "mime", feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+const NOTIFICATION: &str = "Received: from mx1.example.net\r\n\
+Received: from relay.example.com\r\n\
+From: =?utf-8?B?TcO8bGxlciBBdXRv?= <alerts@example.com>\r\n\
+To: buyer@example.org\r\n\
+Subject: =?iso-8859-1?Q?Preis=E4nderung?= =?utf-8?Q?_f=C3=BCr_Civic?=\r\n\
+Message-ID: <42@example.com>\r\n\
+MIME-Version: 1.0\r\n\
+Content-Type: multipart/mixed; boundary=\"outer\"\r\n\
+\r\n\
+This is a multi-part message in MIME format.\r\n\
+--outer\r\n\
+Content-Type: multipart/alternative;\r\n\
+\tboundary=\"inner\"\r\n\
+\r\n\
+--inner\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+New price: =E2=82=AC27.900 for the Civic, down from =\r\n\
+=E2=82=AC28.500.\r\n\
+--inner\r\n\
+Content-Type: text/html; charset=utf-8\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+PHAgY2xhc3M9InByaWNlIj7igqwyNy45MDA8L3A+PGEgaHJlZj0iL2xpc3RpbmcvNDIi\r\n\
+PlZpZXc8L2E+\r\n\
+--inner--\r\n\
+--outer\r\n\
+Content-Type: application/pdf; name=\"brochure.pdf\"\r\n\
+Content-Disposition: attachment; filename=\"brochure.pdf\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+JVBERi0xLjQK\r\n\
+--outer--\r\n";
+
+#[test]
+fn summary_then_inline_text_parts() {
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_mime(NOTIFICATION.to_string()), 3);
+    assert_eq!(cs.content_count(), 3);
+
+    assert_eq!(
+        cs.select(0, "json:headers.\"message-id\""),
+        "<42@example.com>"
+    );
+    assert_eq!(
+        cs.select(0, "regex:New price: (.+) for"),
+        "€27.900",
+        "quoted-printable text part"
+    );
+    assert_eq!(cs.select(0, "css:p.price"), "€27.900");
+    assert_eq!(cs.select(0, "css:a >> get-attr('href')"), "/listing/42");
+}
+
+#[test]
+fn headers_are_decoded_and_repeats_become_arrays() {
+    let mut cs = ChadSelect::new();
+    cs.add_mime(NOTIFICATION.to_string());
+
+    assert_eq!(
+        cs.select(0, "json:headers.from"),
+        "Müller Auto <alerts@example.com>"
+    );
+    assert_eq!(
+        cs.select(0, "json:headers.subject"),
+        "Preisänderung für Civic"
+    );
+    assert_eq!(
+        cs.query(-1, "json:headers.received[]"),
+        vec!["from mx1.example.net", "from relay.example.com"]
+    );
+    assert_eq!(cs.select(0, "json:headers.\"mime-version\""), "1.0");
+}
+
+#[test]
+fn parts_are_listed_with_attachments() {
+    let mut cs = ChadSelect::new();
+    cs.add_mime(NOTIFICATION.to_string());
+
+    assert_eq!(
+        cs.query(-1, "json:parts[].content_type"),
+        vec!["text/plain", "text/html", "application/pdf"]
+    );
+    assert_eq!(cs.query(-1, "json:parts[?added].content_type").len(), 2);
+    assert_eq!(cs.select(0, "json:parts[2].filename"), "brochure.pdf");
+    assert_eq!(cs.select(0, "json:parts[2].size"), "9");
+}
+
+#[test]
+fn single_part_message_defaults_to_plain_text() {
+    let mut cs = ChadSelect::new();
+    let added =
+        cs.add_mime("Subject: Hello\nFrom: a@example.com\n\nOrder #12345 shipped.\n".to_string());
+
+    assert_eq!(added, 2);
+    assert_eq!(cs.select(0, "json:parts[0].content_type"), "text/plain");
+    assert_eq!(cs.select(0, r"regex:Order #(\d+)"), "12345");
+}
+
+#[test]
+fn latin1_parts_are_transcoded() {
+    let mut cs = ChadSelect::new();
+    let message = "Content-Type: text/plain; charset=ISO-8859-1\n\
+                   Content-Transfer-Encoding: quoted-printable\n\
+                   \n\
+                   Gr=FC=DFe aus M=FCnchen\n";
+    cs.add_mime(message.to_string());
+
+    assert_eq!(cs.select(0, r"regex:Gr\S+ aus \S+"), "Grüße aus München");
+}
+
+#[test]
+fn rejected_parts_are_not_counted() {
+    let mut cs = ChadSelect::builder().max_item_bytes(300).build();
+    let big_html = format!("<p>{}</p>", "x".repeat(500));
+    let message = format!(
+        "Content-Type: multipart/alternative; boundary=b\n\n--b\n\
+         Content-Type: text/plain\n\nshort\n--b\n\
+         Content-Type: text/html\n\n{big_html}\n--b--\n"
+    );
+
+    assert_eq!(cs.add_mime(message), 2);
+    assert!(cs
+        .take_warnings()
+        .iter()
+        .any(|w| w.message.contains("part not added")));
+}
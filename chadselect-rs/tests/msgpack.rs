@@ -0,0 +1,68 @@
+//! Tests for MessagePack content (`add_msgpack`, `formats::msgpack_to_json`).
+#![cfg(all(feature = "msgpack", feature = "json", feature = "regex"))]
+
+use chadselect::formats::msgpack_to_json;
+use chadselect::ChadSelect;
+use serde_json::json;
+
+/// `{"cars": [{"id": 1, "model": "Civic", "price": 28500.5},
+///            {"id": 2, "model": "CR-V", "price": null}], "ok": true}`
+const PAYLOAD: &[u8] = b"\x82\xa4cars\x92\
+    \x83\xa2id\x01\xa5model\xa5Civic\xa5price\xcb\x40\xdb\xd5\x20\x00\x00\x00\x00\
+    \x83\xa2id\x02\xa5model\xa4CR-V\xa5price\xc0\
+    \xa2ok\xc3";
+
+#[test]
+fn json_queries_run_against_msgpack() {
+    let mut cs = ChadSelect::new();
+    cs.add_msgpack(PAYLOAD).unwrap();
+
+    assert_eq!(cs.query(-1, "json:cars[*].model"), vec!["Civic", "CR-V"]);
+    assert_eq!(cs.select(0, "json:cars[0].price"), "28500.5");
+    assert_eq!(cs.select(0, "json:ok"), "true");
+    assert_eq!(cs.select(0, r#"regex:"model":"(C[^"]+)""#), "Civic");
+}
+
+#[test]
+fn integer_widths_and_negative_numbers() {
+    // [-1, -128, 255, 65535, 4294967296, -32768]
+    let bytes = b"\x96\xff\xd0\x80\xcc\xff\xcd\xff\xff\xcf\x00\x00\x00\x01\x00\x00\x00\x00\xd1\x80\x00";
+    assert_eq!(
+        msgpack_to_json(bytes),
+        Ok(json!([-1, -128, 255, 65535, 4294967296u64, -32768]))
+    );
+}
+
+#[test]
+fn binary_extensions_and_non_string_keys() {
+    // {1: bin [0xde, 0xad], "t": timestamp32(1700000000), "x": fixext1(5, 0x2a)}
+    let bytes = b"\x83\x01\xc4\x02\xde\xad\xa1t\xd6\xff\x65\x53\xf1\x00\xa1x\xd4\x05\x2a";
+    assert_eq!(
+        msgpack_to_json(bytes),
+        Ok(json!({
+            "1": "3q0=",
+            "t": {"seconds": 1700000000, "nanoseconds": 0},
+            "x": {"ext": 5, "data": "Kg=="}
+        }))
+    );
+}
+
+#[test]
+fn malformed_payloads_are_errors() {
+    let mut cs = ChadSelect::new();
+    // Truncated string, trailing bytes, reserved marker, bad UTF-8.
+    for bytes in [&b"\xa5Civ"[..], b"\x01\x02", b"\xc1", b"\xa2\xff\xfe"] {
+        assert!(cs.add_msgpack(bytes).is_err(), "{:?}", bytes);
+    }
+    // An array claiming far more elements than the input holds.
+    assert!(msgpack_to_json(b"\xdd\xff\xff\xff\xff\x01").is_err());
+    assert_eq!(cs.content_count(), 0);
+}
+
+#[test]
+fn size_limits_apply_to_the_converted_json() {
+    let mut cs = ChadSelect::builder().max_item_bytes(8).build();
+    assert!(cs.add_msgpack(PAYLOAD).is_err());
+    assert!(cs.add_msgpack(b"\x91\x01").is_ok());
+    assert_eq!(cs.select(0, "json:[0]"), "1");
+}
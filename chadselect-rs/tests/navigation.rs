@@ -0,0 +1,128 @@
+//! Tests for the DOM navigation pipe functions (`closest`, `parent`,
+//! `following`, `preceding`, and the sibling steps).
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use chadselect::functions::try_parse_text_functions;
+use chadselect::{ChadSelect, PreparedQuery, Translation};
+
+const PAGE: &str = r#"<html><body>
+<h2>New</h2>
+<div class="product" data-sku="C-1">
+  <h3>Civic</h3><img src="/civic.jpg"><span class="badge">Sale</span>
+  <div class="meta"><span class="price">$28,500</span></div>
+</div>
+<div class="product" data-sku="A-2">
+  <h3>Accord</h3><span class="price">$31,000</span>
+</div>
+<h2>Used</h2>
+<dl><dt>VIN</dt><dd>1HGCM82633A004352</dd><dt>Trim</dt><dd>EX</dd></dl>
+</body></html>"#;
+
+fn page() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(PAGE.to_string());
+    cs
+}
+
+#[test]
+fn closest_and_parent_move_up() {
+    let cs = page();
+    assert_eq!(
+        cs.query(
+            -1,
+            "css:.price >> closest('.product') >> get-attr('data-sku')"
+        ),
+        vec!["C-1", "A-2"]
+    );
+    assert_eq!(
+        cs.query(
+            -1,
+            "css:.product >> closest('.product') >> get-attr('data-sku')"
+        ),
+        vec!["C-1", "A-2"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:.price >> parent() >> get-attr('class')"),
+        vec!["meta", "product"]
+    );
+    assert!(cs.query(-1, "css:.price >> closest('table')").is_empty());
+}
+
+#[test]
+fn siblings_skip_text_and_filter_by_selector() {
+    let cs = page();
+    assert_eq!(cs.select(0, "css:.badge >> preceding-sibling()"), "");
+    assert_eq!(
+        cs.query(-1, "css:.badge >> preceding-sibling() >> get-attr('src')"),
+        vec!["/civic.jpg"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:.badge >> preceding-sibling('h3')"),
+        vec!["Civic"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:dt >> following-sibling('dd')"),
+        vec!["1HGCM82633A004352", "EX"]
+    );
+    assert!(cs.query(-1, "css:.meta >> following-sibling()").is_empty());
+}
+
+#[test]
+fn following_and_preceding_walk_document_order() {
+    let cs = page();
+    // Outside the element's own subtree, into later siblings' subtrees.
+    assert_eq!(
+        cs.query(-1, "css:h3 >> following('.price')"),
+        vec!["$28,500", "$31,000"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:.price >> preceding('h2')"),
+        vec!["New", "New"]
+    );
+    // Ancestors are not preceding; the nearest earlier element wins.
+    assert_eq!(
+        cs.query(-1, "css:dl >> preceding('.price, h2')"),
+        vec!["Used"]
+    );
+    assert_eq!(
+        cs.select(0, "css:dt:text-equals('Trim') >> following()"),
+        "EX"
+    );
+}
+
+#[test]
+fn text_functions_run_on_the_new_element() {
+    let cs = page();
+    assert_eq!(
+        cs.query(
+            -1,
+            "css:.price >> closest('.product') >> normalize-space() >> first()"
+        ),
+        vec!["Civic Sale $28,500"]
+    );
+    // Values of other engines have no element to move from.
+    assert_eq!(cs.query(-1, "regex:Civic >> closest('div')"), vec!["Civic"]);
+}
+
+#[test]
+fn arguments_are_checked_and_queries_stay_css() {
+    assert!(try_parse_text_functions("closest('.a')").is_ok());
+    assert!(try_parse_text_functions("following-sibling()").is_ok());
+    assert!(try_parse_text_functions("closest()").is_err());
+    assert!(try_parse_text_functions("parent('div')").is_err());
+    // Selectors are compiled when the query is prepared.
+    assert!(try_parse_text_functions("following('[')").is_ok());
+    assert!(PreparedQuery::new("css:h3 >> following('[')").is_err());
+    assert_eq!(page().query(-1, "css:h3 >> following('[')"), vec!["Civic", "Accord"]);
+
+    let mut cs = ChadSelect::builder()
+        .translation(Translation::CssToXPath)
+        .build();
+    cs.add_html(PAGE.to_string());
+    let r = cs.query_detailed(
+        -1,
+        "css:.price >> closest('.product') >> get-attr('data-sku')",
+    );
+    assert_eq!(r.engine(), Some("css"));
+    assert_eq!(r.results, vec!["C-1", "A-2"]);
+}
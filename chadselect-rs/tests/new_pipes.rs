@@ -1,3 +1,5 @@
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
 use chadselect::ChadSelect;
 
 fn c() -> ChadSelect {
@@ -30,3 +32,201 @@ fn new_pipe_functions() {
     // replace with a comma arg now works (was buggy)
     assert_eq!(cs.select(0, "css:.price >> replace(',','')"), "$1299.00");
 }
+
+#[test]
+fn matches_and_not_matches_filter_results() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<ul>
+        <li class="spec">12,345 mi</li>
+        <li class="spec">Automatic</li>
+        <li class="spec">Call for price</li>
+        <li class="spec">4 doors</li>
+    </ul>"#.to_string());
+
+    assert_eq!(cs.query(-1, r"css:.spec >> matches('^[\d,]+ mi$')"), vec!["12,345 mi"]);
+    assert_eq!(
+        cs.query(-1, "css:.spec >> not-matches('(?i)call|doors')"),
+        vec!["12,345 mi", "Automatic"]
+    );
+    // Filters compose with transforms and keep values unchanged.
+    assert_eq!(
+        cs.query(-1, r"css:.spec >> matches('\d') >> regex-replace('[^0-9]','')"),
+        vec!["12345", "4"]
+    );
+    // A comma inside the quoted pattern is part of the regex.
+    assert_eq!(cs.query(-1, "css:.spec >> matches('\\d,\\d')"), vec!["12,345 mi"]);
+    // An invalid pattern skips the function, leaving the chain intact.
+    assert_eq!(cs.query(-1, "css:.spec >> matches('(') >> uppercase()").len(), 4);
+}
+
+fn prices() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<ul>
+        <li class="p">$1,299.00</li>
+        <li class="p">$99.50</li>
+        <li class="p">$15,000</li>
+        <li class="p">$99.50</li>
+        <li class="p">Call</li>
+    </ul>"#.to_string());
+    cs
+}
+
+#[test]
+fn to_number_extracts_first_number() {
+    let cs = prices();
+    assert_eq!(cs.query(-1, "css:.p >> to-number()"), vec!["1299", "99.5", "15000", "99.5"]);
+
+    let mut cs = ChadSelect::new();
+    cs.add_text("Balance: -1,024.75 USD, due 3.".to_string());
+    assert_eq!(cs.select(0, r"(Balance: \S+) >> to-number()"), "-1024.75");
+    assert_eq!(cs.select(0, r"(due \d\.) >> to-number()"), "3");
+}
+
+#[test]
+fn result_set_functions() {
+    let cs = prices();
+    // Numeric sort once every value is a number.
+    assert_eq!(
+        cs.query(-1, "css:.p >> to-number() >> sort()"),
+        vec!["99.5", "99.5", "1299", "15000"]
+    );
+    assert_eq!(cs.select(0, "css:.p >> to-number() >> sort() >> last()"), "15000");
+    assert_eq!(cs.query(-1, "css:.p >> to-number() >> sort() >> first()"), vec!["99.5"]);
+    // String sort when any value is not a number.
+    assert_eq!(
+        cs.query(-1, "css:.p >> sort()"),
+        vec!["$1,299.00", "$15,000", "$99.50", "$99.50", "Call"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:.p >> unique()"),
+        vec!["$1,299.00", "$99.50", "$15,000", "Call"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:.p >> unique() >> reverse()"),
+        vec!["Call", "$15,000", "$99.50", "$1,299.00"]
+    );
+    // Each acts on an empty list without producing a value.
+    assert!(cs.query(-1, "css:.missing >> sort() >> last()").is_empty());
+}
+
+#[test]
+fn numeric_aggregates() {
+    let cs = prices();
+    assert_eq!(cs.select(0, "css:.p >> sum()"), "16498");
+    assert_eq!(cs.select(0, "css:.p >> min()"), "99.5");
+    assert_eq!(cs.select(0, "css:.p >> max()"), "15000");
+    assert_eq!(cs.select(0, "css:.p >> avg()"), "4124.5");
+    // `count` counts every value; the others skip the non-numeric "Call".
+    assert_eq!(cs.select(0, "css:.p >> count()"), "5");
+    assert_eq!(cs.select(0, "css:.p >> unique() >> count()"), "4");
+
+    assert_eq!(cs.query(-1, "css:.missing >> count()"), vec!["0"]);
+    assert!(cs.query(-1, "css:.missing >> sum()").is_empty());
+    assert!(cs.query(-1, "css:.p >> not-matches('\\d') >> avg()").is_empty());
+}
+
+#[test]
+fn format_fills_template() {
+    let cs = c();
+    assert_eq!(cs.select(0, "css:.file >> format('file={}')"), "file=archive.tar.gz");
+    assert_eq!(
+        cs.select(0, "css:.dl >> get-attr('href') >> substring-after-last('/') >> format('{{id: {}}}')"),
+        "{id: 42}"
+    );
+    assert_eq!(
+        cs.query(-1, "css:.price, .mileage >> to-number() >> format('<{}>')"),
+        vec!["<1299>", "<12345>"]
+    );
+}
+
+#[test]
+#[cfg(feature = "digest")]
+fn digest_and_base64_functions() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<p class="w">hello</p>
+        <p class="b">eyJza3UiOiAiQUItMSJ9</p>
+        <p class="u">aGk_Pz8</p>
+        <p class="bad">not base64!</p>"#.to_string());
+
+    assert_eq!(
+        cs.select(0, "css:.w >> sha256()"),
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+    assert_eq!(cs.select(0, "css:.w >> md5()"), "5d41402abc4b2a76b9719d911017c592");
+    assert_eq!(cs.select(0, "css:.w >> base64-encode()"), "aGVsbG8=");
+    assert_eq!(cs.select(0, "css:.w >> base64-encode() >> base64-decode()"), "hello");
+
+    assert_eq!(cs.select(0, r#"css:.b >> base64-decode()"#), r#"{"sku": "AB-1"}"#);
+    // URL-safe alphabet without padding.
+    assert_eq!(cs.select(0, "css:.u >> base64-decode()"), "hi???");
+    // Undecodable input is dropped.
+    assert!(cs.query(-1, "css:.bad >> base64-decode()").is_empty());
+}
+
+#[test]
+fn strip_tags_removes_markup() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(
+        r#"desc: <p>Low <b>miles</b> &amp; <i>one</i> owner<!-- note --></p><script>track()</script>
+note: 1 < 2 and plain
+row: <td>Civic</td><td>2021</td>"#
+            .to_string(),
+    );
+
+    assert_eq!(cs.select(0, r"desc: (.*) >> strip-tags()"), "Low miles & one owner");
+    assert_eq!(cs.select(0, r"note: (.*) >> strip-tags()"), "1 < 2 and plain");
+    assert_eq!(cs.select(0, r"row: (.*) >> strip-tags()"), "Civic2021");
+    // Markup-only values become empty and are dropped.
+    assert!(cs.query(-1, r"(<script>.*</script>) >> strip-tags()").is_empty());
+}
+
+#[test]
+fn presentation_functions() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<p class="t">the QUICK brown-fox's den</p>
+        <p class="n">42</p>
+        <p class="d">A spacious three-bedroom home</p>"#.to_string());
+
+    assert_eq!(cs.select(0, "css:.t >> title-case()"), "The Quick Brown-Fox's Den");
+    assert_eq!(cs.select(0, "css:.t >> capitalize()"), "The quick brown-fox's den");
+
+    assert_eq!(cs.select(0, "css:.n >> pad-left(6, '0')"), "000042");
+    assert_eq!(cs.select(0, "css:.n >> pad-right(4, '.')"), "42..");
+    assert_eq!(cs.select(0, "css:.n >> pad-left(4) >> format('[{}]')"), "[  42]");
+    assert_eq!(cs.select(0, "css:.n >> pad-left(1, '0')"), "42");
+
+    assert_eq!(cs.select(0, "css:.d >> truncate(12, '…')"), "A spacious …");
+    assert_eq!(cs.select(0, "css:.d >> truncate(8)"), "A spacio");
+    assert_eq!(cs.select(0, "css:.n >> truncate(8, '…')"), "42");
+    // Malformed arguments skip the function.
+    assert_eq!(cs.select(0, "css:.n >> pad-left('x', '0')"), "42");
+}
+
+#[test]
+fn case_insensitive_and_regex_replace() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<p class="t">NEW 2024 Civic - new arrival</p>
+        <p class="s">  lots   of
+            space </p>"#.to_string());
+
+    assert_eq!(cs.select(0, "css:.t >> replace-i('new ', '')"), "2024 Civic - arrival");
+    // The replacement is literal: `$` is not a group reference.
+    assert_eq!(cs.select(0, "css:.t >> replace-i('civic', '$1 Accord')"), "NEW 2024 $1 Accord - new arrival");
+    // Regex metacharacters in `find` are literal.
+    assert_eq!(cs.select(0, "css:.t >> replace-i(' - ', '|')"), "NEW 2024 Civic|new arrival");
+
+    assert_eq!(cs.select(0, r"css:.s >> replace-regex('\s+', ' ') >> trim()"), "lots of space");
+    assert_eq!(cs.select(0, r"css:.t >> replace-regex('(\d+) (\w+)', '$2 $1')"), "NEW Civic 2024 - new arrival");
+}
+
+#[test]
+fn translate_and_remove_chars() {
+    let cs = c();
+    assert_eq!(cs.select(0, "css:.price >> remove-chars('$,')"), "1299.00");
+    assert_eq!(cs.select(0, "css:.mileage >> remove-chars(', mi')"), "12345");
+    assert_eq!(cs.select(0, "css:.file >> translate('.', '_')"), "archive_tar_gz");
+    // `translate` maps by position and deletes characters with no counterpart.
+    assert_eq!(cs.select(0, "css:.file >> translate('ar.', 'AR')"), "ARchivetARgz");
+    // No argument: the function is skipped.
+    assert_eq!(cs.select(0, "css:.file >> remove-chars()"), "archive.tar.gz");
+}
@@ -0,0 +1,105 @@
+//! Tests for `normalize_html`.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use chadselect::{ChadSelect, NormalizeOptions};
+
+fn normalized(html: &str, options: &NormalizeOptions) -> (ChadSelect, String) {
+    let mut cs = ChadSelect::new();
+    cs.add_html(html.to_string());
+    let out = cs.normalize_html(0, options).unwrap();
+    (cs, out)
+}
+
+#[test]
+fn parser_normalization() {
+    let (cs, html) = normalized(
+        r#"<TABLE><TR><TD Class='cell'>a &amp; b &lt;c&gt; &copy;<TD>2</TABLE><UL><LI>one<LI>two</UL>"#,
+        &NormalizeOptions::default(),
+    );
+    assert!(html.contains(
+        r#"<table><tbody><tr><td class="cell">a &amp; b &lt;c&gt; ©</td><td>2</td></tr></tbody></table>"#
+    ));
+    assert!(html.contains("<ul><li>one</li><li>two</li></ul>"));
+    assert!(html.starts_with("<html><head></head><body>"));
+
+    // Regex now sees the normalized markup.
+    assert_eq!(
+        cs.query(-1, r"regex:<td[^>]*>"),
+        vec![r#"<td class="cell">"#, "<td>"]
+    );
+    assert_eq!(cs.select(0, "css:td.cell"), "a & b <c> ©");
+}
+
+#[test]
+fn attribute_removal_with_prefixes() {
+    let (_, html) = normalized(
+        r#"<a HREF="/x" data-id="7" DATA-Track="y" onClick="go()" onmouseover="h()" style="color:red">x</a>"#,
+        &NormalizeOptions {
+            remove_attributes: vec!["data-*".into(), "ON*".into(), "Style".into()],
+            ..NormalizeOptions::default()
+        },
+    );
+    assert!(html.contains(r#"<a href="/x">x</a>"#), "{html}");
+}
+
+#[test]
+fn attributes_keep_source_order_or_sort() {
+    let source = r#"<img src="/a.jpg" alt="A" width="10" class="hero">"#;
+    let (_, html) = normalized(source, &NormalizeOptions::default());
+    assert!(html.contains(r#"<img src="/a.jpg" alt="A" width="10" class="hero">"#));
+
+    let (_, html) = normalized(
+        source,
+        &NormalizeOptions {
+            sort_attributes: true,
+            ..NormalizeOptions::default()
+        },
+    );
+    assert!(html.contains(r#"<img alt="A" class="hero" src="/a.jpg" width="10">"#));
+}
+
+#[test]
+fn comments_and_whitespace() {
+    let options = NormalizeOptions {
+        remove_comments: true,
+        collapse_whitespace: true,
+        ..NormalizeOptions::default()
+    };
+    let (cs, html) = normalized(
+        "<p>  Price:\n\t $28,500 <!-- promo --> </p><pre>  keep\n  this </pre>",
+        &options,
+    );
+    assert!(html.contains("<p> Price: $28,500  </p>"), "{html}");
+    assert!(html.contains("<pre>  keep\n  this </pre>"));
+    assert!(!html.contains("promo"));
+    assert_eq!(cs.query(-1, r"regex:Price: \S+"), vec!["Price: $28,500"]);
+}
+
+#[test]
+fn queries_see_the_rewritten_dom() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<div data-x="1" class="a">A</div>"#.to_string());
+    // Parse and cache the original first.
+    assert_eq!(cs.select(0, "css:[data-x]"), "A");
+
+    cs.normalize_html(
+        0,
+        &NormalizeOptions {
+            remove_attributes: vec!["data-x".into()],
+            ..NormalizeOptions::default()
+        },
+    );
+    assert!(cs.query(-1, "css:[data-x]").is_empty());
+    assert_eq!(cs.select(0, "xpath://div[@class='a']/text()"), "A");
+}
+
+#[test]
+fn only_html_items() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("<B>x</B>".to_string());
+    cs.add_markdown("# Title".to_string());
+    assert_eq!(cs.normalize_html(0, &NormalizeOptions::default()), None);
+    assert_eq!(cs.normalize_html(1, &NormalizeOptions::default()), None);
+    assert_eq!(cs.normalize_html(2, &NormalizeOptions::default()), None);
+    assert_eq!(cs.select(0, "regex:<B>"), "<B>");
+}
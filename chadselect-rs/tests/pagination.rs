@@ -0,0 +1,69 @@
+//! Tests for `find_next_page`.
+#![cfg(all(feature = "css", feature = "json"))]
+
+use chadselect::{ChadSelect, NextPageSignal};
+
+fn page(html: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(html.to_string());
+    cs
+}
+
+#[test]
+fn rel_next_is_most_confident() {
+    let cs = page(
+        r#"<head><link rel="next" href="/cars?page=2"></head>
+        <body><a href="/cars?page=2">Next</a><a href="/about">More info</a></body>"#,
+    );
+    let next = cs.find_next_page(0);
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0].url, "/cars?page=2");
+    assert_eq!(
+        next[0].signals,
+        vec![NextPageSignal::RelNext, NextPageSignal::NextText]
+    );
+    assert!(next[0].confidence > 0.95 && next[0].confidence <= 1.0);
+}
+
+#[test]
+fn text_label_and_class_signals() {
+    let cs = page(
+        r##"<a href="/p/1" class="prev">« Previous</a>
+        <a href="/p/3">Siguiente ›</a>
+        <a href="/p/3b" aria-label="Next page"><svg></svg></a>
+        <a href="/p/3c" class="pager-next"><i></i></a>
+        <a href="#" class="next">Next</a>
+        <a href="javascript:void(0)">Next</a>
+        <a href="/last">Last »</a>"##,
+    );
+    let next = cs.find_next_page(0);
+    let urls: Vec<_> = next.iter().map(|n| n.url.as_str()).collect();
+    assert_eq!(urls, vec!["/p/3", "/p/3b", "/p/3c"]);
+    assert_eq!(next[1].signals, vec![NextPageSignal::NextLabel]);
+    assert_eq!(next[2].signals, vec![NextPageSignal::NextClass]);
+}
+
+#[test]
+fn numbered_pagination() {
+    let cs = page(
+        r#"<ul class="pages">
+            <li><a href="/list/1">1</a></li>
+            <li class="active"><a href="/list/2">2</a></li>
+            <li><a href="/list/3">3</a></li>
+            <li><a href="/list/4">4</a></li>
+        </ul>"#,
+    );
+    let next = cs.find_next_page(0);
+    assert_eq!(next.len(), 1);
+    assert_eq!(next[0].url, "/list/3");
+    assert_eq!(next[0].signals, vec![NextPageSignal::PageNumber]);
+}
+
+#[test]
+fn non_html_and_missing_items() {
+    let mut cs = page("<p>no links</p>");
+    cs.add_json(r#"{"next": "/p/2"}"#.to_string());
+    assert!(cs.find_next_page(0).is_empty());
+    assert!(cs.find_next_page(1).is_empty());
+    assert!(cs.find_next_page(9).is_empty());
+}
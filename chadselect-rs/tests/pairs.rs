@@ -0,0 +1,62 @@
+//! Tests for `extract_pairs`.
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use chadselect::ChadSelect;
+
+const SPECS: &str = r#"<div class="specs">
+  <div class="row"><span class="k-91x">Mileage:</span><span class="v-2ab">12,000 mi</span></div>
+  <div class="row"><span class="k-91x">Color</span></div>
+  <div class="row"><span class="k-91x">Engine</span>
+    <div class="wrap"><span class="v-7cd"> 2.0L <b>I4</b> </span></div></div>
+  <div class="row"><span class="k-91x">Engine</span><span class="v-2ab">V6</span></div>
+  <div class="row"><span class="k-91x">Drive</span><span class="v-2ab">FWD</span>
+    <span class="v-2ab">AWD</span></div>
+</div>"#;
+
+fn page() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(SPECS.to_string());
+    cs
+}
+
+#[test]
+fn labels_pair_with_the_nearest_following_value() {
+    let pairs = page()
+        .extract_pairs(0, "css:.row > span:first-child", "css:[class^=v-]")
+        .unwrap();
+    assert_eq!(pairs.len(), 3);
+    assert_eq!(pairs["Mileage"], "12,000 mi");
+    assert_eq!(pairs["Engine"], "2.0L I4");
+    assert_eq!(pairs["Drive"], "FWD");
+    assert!(!pairs.contains_key("Color"));
+}
+
+#[test]
+fn xpath_queries_fallbacks_and_chains_work() {
+    let cs = page();
+    let pairs = cs
+        .extract_pairs(
+            0,
+            "xpath://span[starts-with(@class, 'k-')] >> lowercase()",
+            "css:.value || xpath://span[starts-with(@class, 'v-')] >> to-number()",
+        )
+        .unwrap();
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(pairs["mileage"], "12000");
+    assert_eq!(pairs["engine"], "2");
+
+    let text = cs
+        .extract_pairs(0, "xpath://span[@class='k-91x']/text()", "xpath://b/text()")
+        .unwrap();
+    assert_eq!(text["Engine"], "I4");
+}
+
+#[test]
+fn other_engines_fail_and_missing_content_is_empty() {
+    let mut cs = page();
+    cs.add_json(r#"{"a": 1}"#.to_string());
+    assert!(cs.extract_pairs(0, "json:a", "css:span").is_err());
+    assert!(cs.extract_pairs(0, "css:span[", "css:span").is_err());
+    assert!(cs.extract_pairs(1, "css:dt", "css:dd").unwrap().is_empty());
+    assert!(cs.extract_pairs(5, "css:dt", "css:dd").unwrap().is_empty());
+}
@@ -0,0 +1,148 @@
+//! Tests for chunked parsing with progress reporting and cancellation.
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use std::ops::ControlFlow;
+
+use chadselect::{ChadSelect, ParseProgress};
+
+/// An HTML page of about `rows` × 40 bytes.
+fn big_html(rows: usize) -> String {
+    let mut html = String::from("<table>");
+    for i in 0..rows {
+        html.push_str(&format!("<tr><td class='n'>row {:07}</td></tr>", i));
+    }
+    html.push_str("</table>");
+    html
+}
+
+#[test]
+fn progress_is_monotonic_and_completes() {
+    let html = big_html(100_000);
+    let total = html.len();
+    let mut reports: Vec<ParseProgress> = Vec::new();
+
+    let mut cs = ChadSelect::new();
+    cs.add_html_with_progress(html, |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+
+    assert!(
+        reports.len() > 1,
+        "a multi-megabyte page reports more than once"
+    );
+    assert!(reports
+        .windows(2)
+        .all(|w| w[0].bytes_parsed < w[1].bytes_parsed));
+    assert!(reports.iter().all(|p| p.total_bytes == total));
+    assert_eq!(reports.last().unwrap().bytes_parsed, total);
+    assert_eq!(cs.query(-1, "css:td.n").len(), 100_000);
+    assert_eq!(cs.select(0, "xpath://tr[last()]/td"), "row 0099999");
+}
+
+#[test]
+fn chunked_parse_matches_one_shot_parse() {
+    // Multi-byte characters straddle the chunk boundaries.
+    let html = format!("<p>{}</p><p>end</p>", "é€😀".repeat(400_000));
+    let mut chunked = ChadSelect::new();
+    chunked
+        .add_html_with_progress(html.clone(), |_| ControlFlow::Continue(()))
+        .unwrap();
+    let mut one_shot = ChadSelect::new();
+    one_shot.add_html(html);
+
+    assert_eq!(chunked.query(-1, "css:p"), one_shot.query(-1, "css:p"));
+}
+
+#[test]
+fn cancelling_adds_nothing() {
+    let mut cs = ChadSelect::new();
+    let mut calls = 0;
+    let result = cs.add_html_with_progress(big_html(100_000), |_| {
+        calls += 1;
+        if calls == 2 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert!(result.is_err());
+    assert_eq!(calls, 2);
+    assert_eq!(cs.content_count(), 0);
+}
+
+#[test]
+fn empty_content_reports_once() {
+    let mut cs = ChadSelect::new();
+    let mut reports = Vec::new();
+    cs.add_html_with_progress(String::new(), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].fraction(), 1.0);
+}
+
+#[test]
+fn size_limits_apply() {
+    let mut cs = ChadSelect::builder().max_item_bytes(8).build();
+    let result =
+        cs.add_html_with_progress("<p>too long</p>".to_string(), |_| ControlFlow::Continue(()));
+    assert!(result.is_err());
+    assert_eq!(cs.content_count(), 0);
+}
+
+#[test]
+fn parse_all_reports_across_items() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(big_html(50_000));
+    cs.add_json(r#"{"a": 1}"#.to_string());
+    cs.add_markdown("# Title".to_string());
+    cs.add_text("plain".to_string());
+    let total = cs.memory_usage().content_bytes - "plain".len();
+
+    let mut reports = Vec::new();
+    cs.parse_all(|p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+
+    assert!(reports.iter().all(|p| p.total_bytes == total));
+    assert!(reports
+        .windows(2)
+        .all(|w| w[0].bytes_parsed <= w[1].bytes_parsed));
+    assert_eq!(reports.last().unwrap().bytes_parsed, total);
+    assert_eq!(cs.memory_usage().parsed_items, 3);
+
+    // Everything is parsed now, so a second pass has nothing to do.
+    let mut again = 0;
+    cs.parse_all(|_| {
+        again += 1;
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(again, 0);
+}
+
+#[test]
+fn parse_all_cancel_keeps_finished_items() {
+    let mut cs = ChadSelect::new();
+    cs.add_json(r#"{"a": 1}"#.to_string());
+    cs.add_html(big_html(50_000));
+
+    let result = cs.parse_all(|p| {
+        if p.bytes_parsed > 10 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    assert!(result.is_err());
+    // The JSON item finished before the break; the HTML page did not.
+    assert_eq!(cs.memory_usage().parsed_items, 1);
+    assert_eq!(cs.select(0, "json:a"), "1");
+    assert_eq!(cs.query(-1, "css:td").len(), 50_000);
+}
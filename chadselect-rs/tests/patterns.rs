@@ -0,0 +1,124 @@
+//! Tests for the `pat:` built-in named patterns.
+#![cfg(all(feature = "css", feature = "regex"))]
+
+use chadselect::patterns::{builtin, BUILTIN_PATTERNS};
+use chadselect::{ChadSelect, PreparedQuery};
+#[cfg(feature = "delta")]
+use chadselect::{ColumnType, ExtractionSchema};
+
+fn text(content: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(content.to_string());
+    cs
+}
+
+#[test]
+fn email_and_url() {
+    let cs = text(
+        "Mail Sales@Example.co.uk or a.b+cars@dealer.com (see https://example.com/cars?id=7).\n\
+         Docs: http://docs.example.org/a_b, not ftp://x.org or @example.com.",
+    );
+    assert_eq!(
+        cs.query(-1, "pat:email"),
+        vec!["Sales@Example.co.uk", "a.b+cars@dealer.com"]
+    );
+    assert_eq!(
+        cs.query(-1, "pat:url"),
+        vec![
+            "https://example.com/cars?id=7",
+            "http://docs.example.org/a_b"
+        ]
+    );
+}
+
+#[test]
+fn ipv4_and_uuid() {
+    let cs = text(
+        "hosts 10.0.0.1, 192.168.001.1, 256.1.1.1, 8.8.8.8; id 123E4567-e89b-12d3-a456-426614174000 \
+         and 123e4567-e89b-12d3-a456-42661417400",
+    );
+    assert_eq!(cs.query(-1, "pat:ipv4"), vec!["10.0.0.1", "8.8.8.8"]);
+    assert_eq!(
+        cs.query(-1, "pat:uuid"),
+        vec!["123E4567-e89b-12d3-a456-426614174000"]
+    );
+}
+
+#[test]
+fn iso_date_with_optional_time() {
+    let cs = text(
+        "Listed 2024-03-09, updated 2024-03-10T14:05:00Z and 2024-03-11 08:30+01:00; \
+         not 2024-13-01 or 2024-02-32.",
+    );
+    assert_eq!(
+        cs.query(-1, "pat:iso-date"),
+        vec![
+            "2024-03-09",
+            "2024-03-10T14:05:00Z",
+            "2024-03-11 08:30+01:00"
+        ]
+    );
+}
+
+#[test]
+fn vin_excludes_i_o_and_q() {
+    let cs = text("VIN: 1HGCM82633A004352. Bad: 1HGCM82633A00435Q, 1HGCM82633A0043521.");
+    assert_eq!(cs.query(-1, "pat:vin"), vec!["1HGCM82633A004352"]);
+}
+
+#[test]
+fn chains_and_fallbacks_apply() {
+    let cs = text("Contact SALES@example.com or sales@example.com");
+    assert_eq!(
+        cs.query(-1, "pat:email >> lowercase() >> unique()"),
+        vec!["sales@example.com"]
+    );
+    assert_eq!(cs.select(0, "css:.email || pat:email"), "SALES@example.com");
+    assert_eq!(cs.query_detailed(0, "pat:email").engine(), Some("regex"));
+}
+
+#[test]
+fn unknown_names_fail_and_builtins_are_listed() {
+    let error = PreparedQuery::new("pat:phone").err().unwrap();
+    assert!(error.contains("pat:phone"), "{}", error);
+    assert!(error.contains("iso-date"), "{}", error);
+    assert!(text("x").query(-1, "pat:phone").is_empty());
+
+    assert_eq!(BUILTIN_PATTERNS.len(), 6);
+    assert_eq!(builtin("vin"), Some(r"\b[A-HJ-NPR-Z0-9]{17}\b"));
+    assert_eq!(builtin("nope"), None);
+}
+
+#[test]
+fn user_patterns_run_in_queries_fallbacks_and_refs() {
+    let mut cs = ChadSelect::new();
+    cs.define_pattern("sku", r"SKU-\d{3}");
+    cs.define("part", "pat:sku >> first()");
+    cs.add_text("Parts SKU-104 and SKU-221".to_string());
+
+    assert_eq!(cs.query(-1, "pat:sku"), vec!["SKU-104", "SKU-221"]);
+    assert_eq!(cs.select(0, "css:.sku || pat:sku >> last()"), "SKU-221");
+    assert_eq!(cs.select(0, "ref:part"), "SKU-104");
+    assert_eq!(cs.library().pattern("sku"), Some(r"SKU-\d{3}"));
+}
+
+#[test]
+#[cfg(feature = "delta")]
+fn user_patterns_shadow_builtins_and_fill_schema_columns() {
+    let mut cs = ChadSelect::builder()
+        .pattern("sku", r"SKU-\d{3}")
+        .pattern("email", r"[a-z]+@dealer\.com")
+        .build();
+    cs.add_text("SKU-104 by a@dealer.com, b@example.com".to_string());
+
+    assert_eq!(cs.query(-1, "pat:email"), vec!["a@dealer.com"]);
+    let schema = ExtractionSchema::new()
+        .column("sku", ColumnType::Text, "pat:sku")
+        .column("contact", ColumnType::Text, "pat:email");
+    let snapshot = cs.extraction_snapshot(&schema);
+    assert_eq!(snapshot.value("0", "sku"), Some("SKU-104"));
+    assert_eq!(snapshot.value("0", "contact"), Some("a@dealer.com"));
+
+    // Patterns registered on one instance are unknown elsewhere.
+    assert!(PreparedQuery::new("pat:sku").is_err());
+}
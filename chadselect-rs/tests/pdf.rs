@@ -0,0 +1,370 @@
+//! Tests for PDF text extraction (`add_pdf`, the `pdf` feature).
+#![cfg(all(feature = "pdf", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+/// A PDF with `objects` numbered from 1, followed by `trailer`. There is
+/// no cross-reference table: the reader scans for objects.
+fn pdf(objects: &[Vec<u8>], trailer: &str) -> Vec<u8> {
+    let mut out = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    for (i, object) in objects.iter().enumerate() {
+        out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        out.extend_from_slice(object);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    out.extend_from_slice(trailer.as_bytes());
+    out.extend_from_slice(b"\n%%EOF\n");
+    out
+}
+
+fn dict(text: &str) -> Vec<u8> {
+    text.as_bytes().to_vec()
+}
+
+fn stream(entries: &str, data: &[u8]) -> Vec<u8> {
+    let mut out = format!("<< /Length {} {} >>\nstream\n", data.len(), entries).into_bytes();
+    out.extend_from_slice(data);
+    out.extend_from_slice(b"\nendstream");
+    out
+}
+
+const TRAILER: &str = "trailer\n<< /Root 1 0 R /Size 9 >>";
+
+const HELVETICA: &str = "<< /Font << /F1 5 0 R >> >>";
+
+fn helvetica() -> Vec<u8> {
+    dict("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>")
+}
+
+/// A one-page document: its content stream is object 4, its resources
+/// `resources`, and `extra` objects are numbered from 5.
+fn one_page(content: Vec<u8>, resources: &str, extra: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut objects = vec![
+        dict("<< /Type /Catalog /Pages 2 0 R >>"),
+        dict("<< /Type /Pages /Kids [3 0 R] /Count 1 >>"),
+        dict(&format!(
+            "<< /Type /Page /Parent 2 0 R /Contents 4 0 R /Resources {} >>",
+            resources
+        )),
+        content,
+    ];
+    objects.extend(extra);
+    pdf(&objects, TRAILER)
+}
+
+/// zlib data using fixed Huffman codes: a page showing a spec sheet.
+const FIXED: &[u8] =
+    b"\x78\xda\x73\x0a\x51\xd0\x77\x33\x54\x30\x34\x54\x08\x49\x53\x30\x37\x52\x30\x37\
+    \x30\x50\x08\x49\x51\xd0\x08\x2e\x48\x4d\x56\x28\xce\x48\x4d\x2d\xb1\x52\xc8\xcd\
+    \x4f\x49\xcd\x51\x88\xd0\x35\x32\x30\xd0\x54\x08\xc9\x52\x30\x50\xd0\x35\x34\x01\
+    \xab\x0a\x4f\xcd\x4c\xcf\x00\xaa\x30\x34\xd2\x33\x55\xc8\x4e\x07\xcb\xba\x86\x00\
+    \x00\x8f\xc8\x15\xe2";
+
+/// A one-page document whose catalog, page tree, and page (objects 1-3)
+/// live in an object stream, with the trailer entries in a cross-reference
+/// stream.
+fn object_stream_pdf() -> Vec<u8> {
+    let objstm: &[u8] =
+        b"\x78\x9c\x33\x54\x30\x50\x30\x52\x30\x36\x51\x30\x56\x30\x37\x53\xb0\xb1\x51\xd0\
+    \x0f\xa9\x2c\x48\x55\xd0\x77\x4e\x2c\x49\xcc\xc9\x4f\x57\xd0\x0f\x48\x4c\x4f\x2d\
+    \x06\x2a\x31\x50\x08\x52\xb0\xb3\x43\x52\x01\x91\xd0\xf7\xce\x4c\x29\x56\x88\x36\
+    \x06\xc9\xc7\x02\xb5\xe5\x97\xe6\x95\x28\x18\x62\xaa\x04\x91\x45\xa9\x40\x39\x88\
+    \x49\x40\x85\x79\x25\x40\x6e\xb1\x82\x09\xcc\x64\x00\xc7\xf9\x25\x02";
+    let mut bytes = b"%PDF-1.7\n".to_vec();
+    for object in [
+        stream("", b"BT 72 700 Td (From an object stream) Tj ET"),
+        stream("/Type /ObjStm /N 3 /First 14 /Filter /FlateDecode", objstm),
+        stream("/Type /XRef /Root 1 0 R /Size 7", b""),
+    ]
+    .iter()
+    .enumerate()
+    {
+        bytes.extend_from_slice(format!("{} 0 obj\n", object.0 + 4).as_bytes());
+        bytes.extend_from_slice(object.1);
+        bytes.extend_from_slice(b"\nendobj\n");
+    }
+    bytes.extend_from_slice(b"startxref\n0\n%%EOF\n");
+    bytes
+}
+
+/// A one-page document in a two-byte font with a `ToUnicode` CMap.
+fn cmap_pdf() -> Vec<u8> {
+    let cmap = b"/CIDInit /ProcSet findresource begin 12 dict begin begincmap \
+        1 begincodespacerange <0000> <FFFF> endcodespacerange \
+        2 beginbfchar <0001> <0050> <0002> <00E9> endbfchar \
+        2 beginbfrange <0010> <0019> <0030> <0020> <0021> [<0020> <20AC>] endbfrange \
+        endcmap CMapName currentdict /CMap defineresource pop end end";
+    let content = b"BT /F2 12 Tf 72 700 Td <00010002000100200011001300140020 0021> Tj ET";
+    one_page(
+        stream("", content),
+        "<< /Font << /F2 5 0 R >> >>",
+        vec![
+            dict(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /ABCDEF+Frutiger \
+                  /Encoding /Identity-H /ToUnicode 6 0 R >>",
+            ),
+            stream("", cmap),
+        ],
+    )
+}
+
+// ─── Pages, lines, and words ────────────────────────────────────────────────
+
+#[test]
+fn one_item_per_page_in_page_tree_order() {
+    let first = b"BT /F1 12 Tf 72 720 Td (Invoice INV-2041) Tj 0 -16 Td (Due: 2024-07-01) Tj ET";
+    let second = b"BT /F1 12 Tf 72 720 Td (Total: $1,250.00) Tj ET";
+    let bytes = pdf(
+        &[
+            dict("<< /Type /Catalog /Pages 2 0 R >>"),
+            // Page order comes from /Kids, and resources are inherited.
+            dict(
+                "<< /Type /Pages /Kids [6 0 R 4 0 R 3 0 R] /Count 3 \
+                  /Resources << /Font << /F1 9 0 R >> >> >>",
+            ),
+            dict("<< /Type /Page /Parent 2 0 R /Contents 5 0 R >>"),
+            dict("<< /Type /Page /Parent 2 0 R /Contents 8 0 R >>"),
+            stream("", second),
+            dict("<< /Type /Page /Parent 2 0 R /Contents 7 0 R >>"),
+            stream("", first),
+            stream("", b"0 0 1 rg 10 10 100 100 re f"),
+            helvetica(),
+        ],
+        TRAILER,
+    );
+
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(2));
+    assert_eq!(
+        cs.query(-1, "regex:.+"),
+        vec!["Invoice INV-2041", "Due: 2024-07-01", "Total: $1,250.00"]
+    );
+    // The blank middle page adds no item but keeps its number.
+    assert_eq!(cs.content_page(0), Some(1));
+    assert_eq!(cs.content_page(1), Some(3));
+
+    #[cfg(feature = "snapshot")]
+    {
+        let restored = ChadSelect::from_snapshot(&cs.snapshot());
+        assert_eq!(restored.content_page(1), Some(3));
+    }
+}
+
+#[test]
+fn word_gaps_and_line_breaks_follow_text_positions() {
+    let content = b"BT /F1 10 Tf 14 TL 72 700 Td \
+        [(Qty)-3000(Unit price)] TJ [(Wid)20(get)] TJ \
+        T* (Next \\(line\\)) Tj \
+        1 0 0 1 72 600 Tm (Far below) Tj ET \
+        q 1 0 0 1 0 -200 cm BT 72 700 Td (Moved by the CTM) Tj ET Q";
+    let bytes = one_page(stream("", content), HELVETICA, vec![helvetica()]);
+
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(
+        cs.query(-1, "regex:.+"),
+        vec![
+            "Qty Unit priceWidget",
+            "Next (line)",
+            "Far below",
+            "Moved by the CTM"
+        ]
+    );
+}
+
+// ─── Stream filters ─────────────────────────────────────────────────────────
+
+#[test]
+fn compressed_content_streams_are_decoded() {
+    // zlib data using fixed Huffman codes (`FIXED`), dynamic codes, and a
+    // stored block.
+    let dynamic: &[u8] =
+        b"\x78\xda\x65\xd3\xbd\x4a\x05\x31\x18\x84\xe1\xde\xab\xf8\x3a\xb5\x10\x33\xf3\x25\
+    \x9b\xc4\x52\xd0\xca\x42\x64\x6f\x40\x10\x5b\x7f\x38\x20\xde\xbd\x87\x15\x96\x61\
+    \x36\x55\xe0\x6d\x86\x87\xe4\x7e\x8d\xdb\x47\x04\x4a\xac\xef\xd1\x19\xbd\x9c\x6f\
+    \x6f\x01\xc6\xfa\x74\x71\xf5\xf2\xf1\x13\xe5\x2e\x3e\x5f\xbf\x4f\xf1\x7c\x53\xce\
+    \x27\xbe\x4e\xbf\x81\xeb\xb8\xfc\x8f\xd0\xd8\xb7\xc8\x3d\x52\x22\xea\x16\x73\x8f\
+    \x29\x91\xd8\x62\xdd\x63\xd5\x38\xb6\xd8\xf6\xd8\x24\x66\xb3\x41\x8b\xc4\x4a\x1b\
+    \xd4\x35\x4e\x1b\x34\x24\xb6\xc5\x06\x4d\x89\x4b\xda\x20\x28\x51\x3f\x10\xa9\x51\
+    \x77\x23\x28\xd2\x70\x24\xa8\xd2\x74\x25\x28\xd3\x74\x26\x88\x13\x8a\x3b\x41\xa0\
+    \x00\x87\x42\xd7\xea\x52\x10\x2a\xd0\xa9\x20\x56\x48\xb7\xa2\x58\xa1\xba\x15\xa1\
+    \xf5\xf0\x9e\xc4\x0a\xcd\xad\x28\x56\x58\xdc\x8a\x55\xab\x5b\x51\xad\xba\x5b\x51\
+    \xad\x86\x5b\x51\xad\x86\x5b\x51\xad\xa6\x5b\x51\xac\x58\xdc\x2a\xc5\x8a\x70\xab\
+    \x84\x56\xb7\x4a\xb1\x22\x0f\x9f\x4f\xac\x98\x6e\x95\x55\xab\x5b\xa5\x58\xb1\xba\
+    \x55\x8a\x15\x9b\x5b\x65\xd7\xea\x56\x29\x56\x5c\xdc\x2a\xd5\xaa\x8b\xd5\xc3\xfa\
+    \x07\x89\xd0\x37\x3d";
+    let stored: &[u8] =
+        b"\x78\x01\x01\x54\x00\xab\xff\x42\x54\x20\x2f\x46\x31\x20\x31\x31\x20\x54\x66\x20\
+    \x37\x32\x20\x37\x30\x30\x20\x54\x64\x20\x28\x53\x70\x65\x63\x20\x73\x68\x65\x65\
+    \x74\x3a\x20\x6d\x6f\x64\x65\x6c\x20\x58\x2d\x32\x30\x30\x29\x20\x54\x6a\x20\x30\
+    \x20\x2d\x31\x34\x20\x54\x64\x20\x28\x57\x65\x69\x67\x68\x74\x3a\x20\x31\x32\x2e\
+    \x35\x20\x6b\x67\x29\x20\x54\x6a\x20\x45\x54\x8f\xc8\x15\xe2";
+
+    for data in [FIXED, stored] {
+        let bytes = one_page(
+            stream("/Filter /FlateDecode", data),
+            HELVETICA,
+            vec![helvetica()],
+        );
+        let mut cs = ChadSelect::new();
+        assert_eq!(cs.add_pdf(&bytes), Ok(1));
+        assert_eq!(cs.select(0, r"regex:Weight: ([\d.]+) kg"), "12.5");
+    }
+
+    let bytes = one_page(
+        stream("/Filter [/FlateDecode]", dynamic),
+        HELVETICA,
+        vec![helvetica()],
+    );
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(cs.query(-1, r"regex:Row \d+:").len(), 40);
+    assert_eq!(cs.select(0, r"regex:Row 39: part (P-\d+)"), "P-0273");
+}
+
+#[test]
+fn ascii_filters_and_undecodable_streams() {
+    let hex = b"42542037322037303020 \n546420286865782920546a204554>";
+    let bytes = one_page(
+        stream("/Filter /ASCIIHexDecode", hex),
+        HELVETICA,
+        vec![helvetica()],
+    );
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(cs.select(0, "regex:.+"), "hex");
+
+    let a85 = b"6<#'d1*AM00Ha>*+=LZ>@qfX:2(9YIC*5rE~>";
+    let bytes = one_page(stream("/Filter /A85", a85), HELVETICA, vec![helvetica()]);
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(cs.select(0, "regex:.+"), "ascii85");
+
+    // An unsupported filter skips the stream with a warning.
+    let bytes = one_page(
+        stream("/Filter /LZWDecode", b"\x80\x0b\x60"),
+        HELVETICA,
+        vec![],
+    );
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(0));
+    assert!(cs
+        .take_warnings()
+        .iter()
+        .any(|w| w.message.contains("LZWDecode")));
+}
+
+// ─── Fonts ──────────────────────────────────────────────────────────────────
+
+#[test]
+fn to_unicode_cmaps_map_two_byte_codes() {
+    let bytes = cmap_pdf();
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(cs.select(0, "regex:.+"), "PéP 134 €");
+}
+
+#[test]
+fn simple_font_encodings_and_differences() {
+    // 0x93/0x94 are curly quotes in WinAnsi; /Differences remaps 1-3.
+    let content = b"BT /F1 12 Tf 72 700 Td (\x93Caf\xe9\x94 \x01\x02\x03) Tj ET";
+    let bytes = one_page(
+        stream("", content),
+        HELVETICA,
+        vec![dict(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica \
+                   /Encoding << /BaseEncoding /WinAnsiEncoding \
+                   /Differences [1 /uni2713 /bullet /Euro] >> >>",
+        )],
+    );
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(cs.select(0, "regex:.+"), "“Café” ✓•€");
+}
+
+// ─── File structure ─────────────────────────────────────────────────────────
+
+#[test]
+fn objects_in_compressed_object_streams() {
+    let bytes = object_stream_pdf();
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_pdf(&bytes), Ok(1));
+    assert_eq!(cs.select(0, "regex:.+"), "From an object stream");
+}
+
+#[test]
+fn rejects_non_pdf_encrypted_and_pageless_input() {
+    let mut cs = ChadSelect::new();
+    assert!(cs
+        .add_pdf(b"<html>not a pdf</html>")
+        .unwrap_err()
+        .contains("not a PDF"));
+
+    let encrypted = pdf(
+        &[dict("<< /Type /Catalog >>")],
+        "trailer\n<< /Root 1 0 R /Encrypt 2 0 R >>",
+    );
+    assert!(cs.add_pdf(&encrypted).unwrap_err().contains("encrypted"));
+
+    let pageless = pdf(&[dict("<< /Type /Catalog >>")], TRAILER);
+    assert!(cs.add_pdf(&pageless).unwrap_err().contains("no pages"));
+    assert_eq!(cs.content_count(), 0);
+}
+
+// ─── Malformed input ────────────────────────────────────────────────────────
+
+/// Every prefix, and thousands of byte-level mutations, of documents that
+/// exercise each part of the reader: `add_pdf` returns a result for all of
+/// them and never panics.
+#[test]
+fn truncated_and_corrupted_files_never_panic() {
+    let documents = [
+        one_page(
+            stream("/Filter /FlateDecode", FIXED),
+            HELVETICA,
+            vec![helvetica()],
+        ),
+        object_stream_pdf(),
+        cmap_pdf(),
+    ];
+    // Bytes that steer the lexer: delimiters, digits, keywords' letters.
+    const INTERESTING: &[u8] = b"<>[]()/%\\ \n0129RobjstreamTjBTEI\xff\x00";
+
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % bound as u64) as usize
+    };
+
+    let mut cs = ChadSelect::new();
+    for document in &documents {
+        for len in 0..document.len() {
+            let _ = cs.add_pdf(&document[..len]);
+        }
+        for _ in 0..5_000 {
+            let mut bytes = document.clone();
+            for _ in 0..1 + next(4) {
+                let at = next(bytes.len());
+                match next(4) {
+                    0 => bytes[at] = INTERESTING[next(INTERESTING.len())],
+                    1 => bytes[at] = next(256) as u8,
+                    2 => {
+                        let end = (at + 1 + next(16)).min(bytes.len());
+                        bytes.drain(at..end);
+                    }
+                    _ => bytes.insert(at, INTERESTING[next(INTERESTING.len())]),
+                }
+                if bytes.is_empty() {
+                    break;
+                }
+            }
+            if next(2) == 0 {
+                bytes.truncate(next(bytes.len() + 1));
+            }
+            let _ = cs.add_pdf(&bytes);
+        }
+        cs.clear();
+    }
+}
@@ -0,0 +1,84 @@
+//! Tests for prepared (pre-compiled) queries.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, PrefixMode, PreparedQuery};
+
+fn page(price: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(format!(
+        r#"<div class="p"><a href="/item/1">Item</a><span class="price"> {price} </span></div>"#
+    ));
+    cs.add_json(format!(r#"{{"price": "{price}"}}"#));
+    cs.add_text(format!("price: {price}"));
+    cs
+}
+
+#[test]
+fn prepared_matches_unprepared_for_every_engine() {
+    let cs = page("$10");
+    for q in [
+        "css:.price >> trim()",
+        "css:a >> get-attr('href')",
+        "css:div:has-text('Item') .price",
+        "xpath://span[@class='price']/text() >> normalize-space()",
+        "json:price >> translate('$','')",
+        r"regex:price: \$(\d+)",
+        r"\$(\d+)",
+    ] {
+        let prepared = cs.prepare(q).unwrap();
+        assert_eq!(cs.query_prepared(-1, &prepared), cs.query(-1, q), "query {q}");
+    }
+}
+
+#[test]
+fn prepared_query_is_shared_across_instances() {
+    let prepared = PreparedQuery::new("css:.price >> trim()").unwrap();
+    assert_eq!(page("$1").select_prepared(0, &prepared), "$1");
+    assert_eq!(page("$2").select_prepared(0, &prepared), "$2");
+    assert_eq!(page("$3").query_prepared(5, &prepared), Vec::<String>::new());
+}
+
+#[test]
+fn prepare_reports_invalid_expressions() {
+    let cs = ChadSelect::new();
+    assert!(cs.prepare("regex:(unclosed").is_err());
+    assert!(cs.prepare("css:][invalid").is_err());
+    assert!(cs.prepare("xpath://div[").is_err());
+    assert!(cs.prepare("json:foo[").is_err());
+    // A malformed text function fails `prepare`; `query` just skips it.
+    let err = cs.prepare("css:.price >> replace('$') >> trim()").unwrap_err();
+    assert!(err.contains("replace()"), "{}", err);
+    assert!(cs.prepare("regex:(\\d+) >> nope()").is_err());
+    assert!(PreparedQuery::new("css:.price >> trim(").is_err());
+    assert_eq!(page("$10").query(0, "css:.price >> replace('$') >> trim()"), vec!["$10"]);
+}
+
+#[test]
+fn prepare_honours_prefix_mode() {
+    let strict = ChadSelect::builder().prefix_mode(PrefixMode::Strict).build();
+    assert!(strict.prepare("xpat://div").is_err());
+    assert_eq!(
+        PreparedQuery::with_mode(".price", PrefixMode::Auto).unwrap().query_type().unwrap().prefix(),
+        "css"
+    );
+}
+
+#[test]
+fn prepared_queries_are_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PreparedQuery>();
+
+    // One instance shared by worker threads; XPath recompiles once per thread.
+    let query = std::sync::Arc::new(
+        PreparedQuery::new("xpath://span[@class='price'] || json:price").unwrap(),
+    );
+    let handles: Vec<_> = ["$1", "$2"]
+        .into_iter()
+        .map(|price| {
+            let query = std::sync::Arc::clone(&query);
+            std::thread::spawn(move || page(price).select_prepared(0, &query))
+        })
+        .collect();
+    let prices: Vec<String> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(prices, ["$1", "$2"]);
+}
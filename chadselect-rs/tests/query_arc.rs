@@ -0,0 +1,82 @@
+//! Tests for `query_borrowed` and `query_arc`.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use chadselect::ChadSelect;
+
+const HTML: &str = r#"
+<ul>
+  <li class="price">$10.00</li>
+  <li class="price">$25.50</li>
+  <li class="price">$10.00</li>
+</ul>
+"#;
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(HTML.to_string());
+    cs.add_text("total $45.50".to_string());
+    cs
+}
+
+#[test]
+fn borrowed_matches_query() {
+    let cs = make_cs();
+    for query in [
+        r"regex:\$[\d.]+",
+        r"regex:\$(\d+)",
+        r"regex:\$[\d.]+ >> uppercase()",
+        "css:.price",
+        "xpath://li[2]",
+        "css:.missing || regex:total (\\S+)",
+    ] {
+        for index in [-1, 0, 2, 9] {
+            assert_eq!(
+                cs.query_borrowed(index, query),
+                cs.query(index, query),
+                "{}",
+                query
+            );
+        }
+    }
+}
+
+#[test]
+fn regex_matches_borrow_from_content() {
+    let cs = make_cs();
+    let results = cs.query_borrowed(-1, r"regex:\$([\d.]+)");
+    assert_eq!(results.len(), 4);
+    assert!(results.iter().all(|r| matches!(r, Cow::Borrowed(_))));
+
+    // A chain rewrites the values, so they are owned.
+    let results = cs.query_borrowed(-1, r"regex:\$([\d.]+) >> uppercase()");
+    assert!(results.iter().all(|r| matches!(r, Cow::Owned(_))));
+}
+
+#[test]
+fn arc_interns_equal_values() {
+    let cs = make_cs();
+    let results = cs.query_arc(-1, "css:.price");
+    assert_eq!(
+        results,
+        cs.query(-1, "css:.price")
+            .iter()
+            .map(|r| Arc::from(r.as_str()))
+            .collect::<Vec<Arc<str>>>()
+    );
+    assert!(Arc::ptr_eq(&results[0], &results[2]));
+    assert!(!Arc::ptr_eq(&results[0], &results[1]));
+
+    let regex = cs.query_arc(-1, r"regex:\$10\.00");
+    assert_eq!(regex.len(), 2);
+    assert!(Arc::ptr_eq(&regex[0], &regex[1]));
+}
+
+#[test]
+fn invalid_query_is_empty() {
+    let cs = make_cs();
+    assert!(cs.query_borrowed(-1, "regex:(").is_empty());
+    assert!(cs.query_arc(-1, "css:[[").is_empty());
+}
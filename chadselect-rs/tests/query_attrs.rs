@@ -0,0 +1,75 @@
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use std::collections::HashMap;
+
+use chadselect::ChadSelect;
+
+fn gallery() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<div class="gallery">
+            <figure><img src="/1.jpg" alt="Front" width="640"><figcaption>Front</figcaption></figure>
+            <figure><img src="/2.jpg" alt="Side"><figcaption>Side view</figcaption></figure>
+            <img class="logo" src="/logo.svg">
+        </div>"#
+            .to_string(),
+    );
+    cs.add_json(r#"{"img": "not html"}"#.to_string());
+    cs.add_html(r#"<img src="/3.jpg" alt="Rear">"#.to_string());
+    cs
+}
+
+fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn returns_every_attribute_of_every_match() {
+    let cs = gallery();
+    let images = cs.query_attrs(-1, "css:img");
+    assert_eq!(
+        images,
+        vec![
+            map(&[("src", "/1.jpg"), ("alt", "Front"), ("width", "640")]),
+            map(&[("src", "/2.jpg"), ("alt", "Side")]),
+            map(&[("class", "logo"), ("src", "/logo.svg")]),
+            map(&[("src", "/3.jpg"), ("alt", "Rear")]),
+        ]
+    );
+}
+
+#[test]
+fn index_selects_one_map() {
+    let cs = gallery();
+    assert_eq!(
+        cs.query_attrs(1, "css:img"),
+        vec![map(&[("src", "/2.jpg"), ("alt", "Side")])]
+    );
+    assert!(cs.query_attrs(10, "css:img").is_empty());
+}
+
+#[test]
+fn text_pseudo_selectors_and_fallbacks() {
+    let cs = gallery();
+    let side = cs.query_attrs(-1, "css:figure:has-text('Side') img");
+    assert_eq!(side, vec![map(&[("src", "/2.jpg"), ("alt", "Side")])]);
+
+    let fallback = cs.query_attrs(-1, "css:video || css:img.logo");
+    assert_eq!(
+        fallback,
+        vec![map(&[("class", "logo"), ("src", "/logo.svg")])]
+    );
+}
+
+#[test]
+fn non_css_queries_return_nothing() {
+    let cs = gallery();
+    assert!(cs.query_attrs(-1, "json:img").is_empty());
+    assert!(cs.query_attrs(-1, "xpath://img").is_empty());
+    assert!(cs.query_attrs(-1, "css:][").is_empty());
+    // Only the CSS alternative contributes.
+    assert_eq!(cs.query_attrs(-1, "json:img || css:img.logo").len(), 1);
+}
@@ -0,0 +1,128 @@
+//! Tests for query deadlines and cancellation tokens.
+#![cfg(all(feature = "xpath", feature = "regex"))]
+
+use std::time::{Duration, Instant};
+
+use chadselect::{CancellationToken, ChadSelect, QueryStatus};
+
+fn rows(count: usize) -> String {
+    let mut html = String::from("<ul>");
+    for i in 0..count {
+        html.push_str(&format!("<li class='row'>item {}</li>", i));
+    }
+    html.push_str("</ul>");
+    html
+}
+
+#[test]
+fn unhindered_query_is_complete() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(rows(3));
+    let token = CancellationToken::with_timeout(Duration::from_secs(60));
+    let r = cs.query_cancellable(-1, "css:li.row", &token);
+    assert_eq!(r.status, QueryStatus::Complete);
+    assert_eq!(r.results, cs.query(-1, "css:li.row"));
+    assert_eq!(r.locations.len(), 3);
+}
+
+#[test]
+fn cancelled_token_stops_every_engine() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(rows(3));
+    cs.add_text("a1 a2 a3".to_string());
+    let token = CancellationToken::new();
+    token.cancel();
+
+    for query in [
+        "css:li",
+        "xpath://li",
+        r"regex:a\d",
+        "css:li:has-text('item')",
+    ] {
+        let r = cs.query_cancellable(-1, query, &token);
+        assert_eq!(r.status, QueryStatus::Cancelled, "{}", query);
+        assert!(r.results.is_empty(), "{}", query);
+    }
+}
+
+#[test]
+fn expired_deadline_times_out() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(rows(3));
+    let token = CancellationToken::with_deadline(Instant::now());
+    assert!(token.is_expired());
+    let r = cs.query_cancellable(-1, "css:li", &token);
+    assert_eq!(r.status, QueryStatus::TimedOut);
+}
+
+#[test]
+fn runaway_xpath_returns_partial_results() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(rows(2_000));
+    // Quadratic: every element counts every element.
+    let token = CancellationToken::with_timeout(Duration::from_millis(50));
+    let started = Instant::now();
+    let r = cs.query_cancellable(-1, "xpath://*[count(//*) > 0]/li", &token);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(r.status, QueryStatus::TimedOut);
+    assert!(r.results.len() < 2_000);
+}
+
+#[test]
+fn stops_between_content_items() {
+    let mut cs = ChadSelect::new();
+    for _ in 0..50 {
+        cs.add_html(rows(1_000));
+    }
+    let token = CancellationToken::with_timeout(Duration::from_millis(1));
+    let r = cs.query_cancellable(-1, "css:li", &token);
+    assert_eq!(r.status, QueryStatus::TimedOut);
+    assert!(r.results.len() < 50_000);
+    // Items the query never reached stay unparsed.
+    assert!(cs.memory_usage().parsed_items < 50);
+}
+
+#[test]
+fn cancel_from_another_thread() {
+    let mut cs = ChadSelect::new();
+    for _ in 0..200 {
+        cs.add_html(rows(1_000));
+    }
+    let token = CancellationToken::new();
+    let remote = token.clone();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        remote.cancel();
+    });
+    let r = cs.query_cancellable(-1, "css:li", &token);
+    canceller.join().unwrap();
+    assert_eq!(r.status, QueryStatus::Cancelled);
+    assert!(token.is_cancelled());
+}
+
+#[test]
+fn interrupted_chain_does_not_fall_back() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(rows(3));
+    let token = CancellationToken::new();
+    token.cancel();
+    let r = cs.query_cancellable(-1, "css:.missing || css:li", &token);
+    assert_eq!(r.status, QueryStatus::Cancelled);
+    assert_eq!(r.engine(), Some("css"));
+    assert!(r.results.is_empty());
+}
+
+#[test]
+fn plain_queries_are_unaffected() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(rows(3));
+    let token = CancellationToken::new();
+    token.cancel();
+    let _ = cs.query_cancellable(-1, "css:li", &token);
+    // The token only applies inside `query_cancellable`.
+    assert_eq!(cs.query(-1, "css:li").len(), 3);
+    assert_eq!(
+        cs.query_detailed(-1, "xpath://li").status,
+        QueryStatus::Complete
+    );
+}
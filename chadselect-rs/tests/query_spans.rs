@@ -0,0 +1,79 @@
+//! Tests for `query_spans` and `ResultLocation::span`.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+const TEXT: &str = "Civic 2021\nprice: $28,500\nAccord 2022\nprice: $31,000\n";
+
+const HTML: &str = r#"<div class="car" data-name="Civic">
+  <h3>Civic</h3><span class="price">$28,500</span>
+  <!-- <h3>Old</h3> -->
+  <h3>Accord</h3><span class="price">$31,000</span>
+  <h3>A &amp; B</h3>
+</div>"#;
+
+fn loaded() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(TEXT.to_string());
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+fn texts<'a>(content: &'a str, spans: &[(usize, usize, String)]) -> Vec<&'a str> {
+    spans
+        .iter()
+        .map(|(start, end, _)| &content[*start..*end])
+        .collect()
+}
+
+#[test]
+fn regex_spans_are_exact_and_survive_chains() {
+    let cs = loaded();
+    let spans = cs.query_spans(0, r"regex:\$([\d,]+)");
+    assert_eq!(texts(TEXT, &spans), vec!["28,500", "31,000"]);
+    assert_eq!(spans[0].2, "28,500");
+
+    let spans = cs.query_spans(0, r"regex:\$[\d,]+ >> to-number()");
+    assert_eq!(texts(TEXT, &spans), vec!["$28,500", "$31,000"]);
+    assert_eq!(spans[1].2, "31000");
+
+    // Folds have no span.
+    assert!(cs.query_spans(0, r"regex:\d{4} >> join(',')").is_empty());
+
+    let r = cs.query_detailed(-1, r"regex:Accord");
+    assert_eq!(r.locations[0].span, Some(26..32));
+}
+
+#[test]
+fn markup_values_are_found_in_text_in_order() {
+    let cs = loaded();
+    let spans = cs.query_spans(1, "css:h3");
+    // Not the attribute or the comment; `A &amp; B` does not appear verbatim.
+    assert_eq!(texts(HTML, &spans), vec!["Civic", "Accord"]);
+    assert!(HTML[..spans[0].0].ends_with("<h3>"));
+
+    let spans = cs.query_spans(1, "xpath://span[@class='price']");
+    assert_eq!(texts(HTML, &spans), vec!["$28,500", "$31,000"]);
+    assert_eq!(spans[1].2, "$31,000");
+}
+
+#[test]
+fn full_text_spans_cover_plain_sentences() {
+    let mut cs = ChadSelect::new();
+    let text = "The Civic is red. The Accord is blue.";
+    cs.add_text(text.to_string());
+    let spans = cs.query_spans(0, "text:accord");
+    assert_eq!(texts(text, &spans), vec!["The Accord is blue."]);
+}
+
+#[test]
+fn fallbacks_other_engines_and_bad_indexes() {
+    let mut cs = loaded();
+    let spans = cs.query_spans(1, "css:.missing || css:.price");
+    assert_eq!(texts(HTML, &spans), vec!["$28,500", "$31,000"]);
+
+    cs.add_json(r#"{"name": "Civic"}"#.to_string());
+    assert!(cs.query_spans(2, "json:name").is_empty());
+    assert!(cs.query_spans(9, "regex:Civic").is_empty());
+    assert!(cs.query_spans(0, "css:h3").is_empty());
+}
@@ -1,4 +1,5 @@
 //! Tests for the Regex extraction engine.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
 
 use chadselect::ChadSelect;
 
@@ -141,3 +142,23 @@ fn select_returns_empty_string_on_no_match() {
     let result = cs.select(0, r"regex:(\d+)");
     assert_eq!(result, "");
 }
+
+// ─── Function piping ────────────────────────────────────────────────────────
+
+#[test]
+fn regex_function_pipe() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("VIN: 1HGFE2F59PA000001".to_string());
+
+    let result = cs.select(0, r"regex:VIN:\s*(\w+) >> lowercase() >> substring(0,3)");
+    assert_eq!(result, "1hg");
+}
+
+#[test]
+fn unprefixed_regex_function_pipe() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("price: $1,299".to_string());
+
+    let result = cs.select(0, r"price:\s*(\S+) >> translate('$,','')");
+    assert_eq!(result, "1299");
+}
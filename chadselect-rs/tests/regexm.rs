@@ -0,0 +1,53 @@
+//! Tests for the `regexm:` prefix.
+#![cfg(all(feature = "css", feature = "regex"))]
+
+use chadselect::query::parse_query;
+use chadselect::{ChadSelect, QueryType};
+
+const LOG: &str = "INFO: up\r\nERROR: disk full\r\nBEGIN\nalpha\nbeta\nEND\nERROR: net down\n";
+
+fn log() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(LOG.to_string());
+    cs
+}
+
+#[test]
+fn anchors_match_per_line() {
+    let cs = log();
+    assert!(cs.query(-1, r"regex:^ERROR: (.*)$").is_empty());
+    assert_eq!(
+        cs.query(-1, r"regexm:^ERROR: (.*)$"),
+        vec!["disk full", "net down"]
+    );
+    // `\r\n` is one line break, so `$` leaves no `\r` behind.
+    assert_eq!(cs.query(-1, r"regexm:^INFO: (\w+)$"), vec!["up"]);
+}
+
+#[test]
+fn dot_stays_within_a_line_unless_asked() {
+    let cs = log();
+    assert!(cs.query(-1, r"regexm:^BEGIN\n(.*?)\nEND$").is_empty());
+    // Inline flags apply on top of the defaults.
+    assert_eq!(
+        cs.select(0, r"regexm:(?s)^BEGIN\n(.*?)\nEND$"),
+        "alpha\nbeta"
+    );
+    assert_eq!(
+        cs.query(-1, r"regexm:(?i)^error: (.*)$ >> uppercase()"),
+        vec!["DISK FULL", "NET DOWN"]
+    );
+}
+
+#[test]
+fn parses_to_a_flagged_regex() {
+    match parse_query(r"regexm:^a.b$ >> trim()").unwrap() {
+        QueryType::Regex(pattern) => assert_eq!(pattern, r"(?mR)^a.b$ >> trim()"),
+        other => panic!("expected a regex query, got {:?}", other),
+    }
+    let cs = log();
+    assert_eq!(
+        cs.select(0, r"css:.missing || regexm:^ERROR: (.*)$"),
+        "disk full"
+    );
+}
@@ -0,0 +1,81 @@
+//! Tests for `remove_nodes`.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+const LISTING: &str = r#"<div class="listing">
+  <h1>2021 Honda Civic</h1>
+  <p class="price">$28,500</p>
+  <div class="ad"><p class="price">$199/mo</p><script>track()</script></div>
+  <!-- sponsored -->
+  <p class="note">Clean title</p>
+  <script>window.price = "$1";</script>
+</div>"#;
+
+fn listing() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(LISTING.to_string());
+    cs
+}
+
+#[test]
+fn css_removal_affects_every_engine() {
+    let mut cs = listing();
+    assert_eq!(cs.query(-1, "css:.price").len(), 2);
+
+    // The nested script matches too, and is counted once.
+    assert_eq!(cs.remove_nodes(0, "css:.ad, script"), Ok(3));
+    assert_eq!(cs.query(-1, "css:.price"), vec!["$28,500"]);
+    assert_eq!(
+        cs.query(-1, "xpath://p/text()"),
+        vec!["$28,500", "Clean title"]
+    );
+    assert_eq!(cs.query(-1, r"regex:\$[\d,]+"), vec!["$28,500"]);
+    assert_eq!(cs.remove_nodes(0, "css:.ad"), Ok(0));
+}
+
+#[test]
+fn xpath_removes_comments_and_skips_attributes() {
+    let mut cs = listing();
+    assert_eq!(cs.remove_nodes(0, "xpath://comment()"), Ok(1));
+    assert!(cs.query(-1, "regex:sponsored").is_empty());
+
+    assert_eq!(cs.remove_nodes(0, "xpath://p/@class"), Ok(0));
+    assert_eq!(cs.query(-1, "css:p").len(), 3);
+    assert_eq!(cs.remove_nodes(0, "xpath:/"), Ok(0));
+    assert_eq!(cs.select(0, "css:h1"), "2021 Honda Civic");
+}
+
+#[test]
+fn alternatives_and_named_queries() {
+    let mut cs = listing();
+    cs.define("ads", "css:.ad");
+    assert_eq!(
+        cs.remove_nodes(0, "ref:ads || xpath://p[@class='note']"),
+        Ok(2)
+    );
+    assert_eq!(cs.query(-1, "css:p"), vec!["$28,500"]);
+}
+
+#[test]
+fn only_the_indexed_html_item_changes() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p class='ad'>one</p>".to_string());
+    cs.add_html("<p class='ad'>two</p>".to_string());
+    cs.add_text("<p class='ad'>three</p>".to_string());
+
+    assert_eq!(cs.remove_nodes(1, "css:.ad"), Ok(1));
+    assert_eq!(cs.remove_nodes(2, "css:.ad"), Ok(0));
+    assert_eq!(cs.remove_nodes(9, "css:.ad"), Ok(0));
+    assert_eq!(cs.query(-1, "css:.ad"), vec!["one"]);
+    assert_eq!(cs.query(-1, "regex:three"), vec!["three"]);
+}
+
+#[test]
+fn rejects_bad_queries() {
+    let mut cs = listing();
+    assert!(cs.remove_nodes(0, r"regex:\d+").is_err());
+    assert!(cs.remove_nodes(0, "json:price").is_err());
+    assert!(cs.remove_nodes(0, "css:[[").is_err());
+    assert_eq!(cs.query(-1, "css:.price").len(), 2);
+}
@@ -0,0 +1,147 @@
+//! Tests for the result locations reported by `query_detailed`.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, ResultLocation};
+
+fn shop() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<html><body><div class="products">
+            <div class="product"><span class="price">$1</span></div>
+            <div class="product"><span class="price">$2</span><span class="price old">$3</span></div>
+            <div class="product" id="p-3"><span class="price">$4</span></div>
+        </div></body></html>"#
+            .to_string(),
+    );
+    cs.add_text("line one\nprice: $5\n  price: $6".to_string());
+    cs
+}
+
+#[test]
+fn css_results_carry_position_and_path() {
+    let cs = shop();
+    let r = cs.query_detailed(-1, "css:.price");
+    assert_eq!(r.results, vec!["$1", "$2", "$3", "$4"]);
+    assert_eq!(r.locations.len(), r.results.len());
+
+    let paths: Vec<_> = r
+        .locations
+        .iter()
+        .map(|l| l.css_path.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            "html > body > div.products > div:nth-child(1) > span.price",
+            "html > body > div.products > div:nth-child(2) > span:nth-child(1)",
+            "html > body > div.products > div:nth-child(2) > span.price.old",
+            "div#p-3 > span.price",
+        ]
+    );
+
+    // Every path selects exactly the element it was built from.
+    for (path, value) in paths.iter().zip(&r.results) {
+        assert_eq!(
+            &cs.query(-1, &format!("css:{path}")),
+            &vec![value.clone()],
+            "{path}"
+        );
+    }
+
+    let positions: Vec<_> = r.locations.iter().map(|l| l.position.unwrap()).collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]), "{positions:?}");
+    assert!(r
+        .locations
+        .iter()
+        .all(|l| l.content_index == 0 && l.line.is_none()));
+}
+
+#[test]
+fn locations_follow_index_and_functions() {
+    let cs = shop();
+    let r = cs.query_detailed(1, "css:.price >> translate('$', '')");
+    assert_eq!(r.results, vec!["2"]);
+    assert_eq!(
+        r.locations[0].css_path.as_deref(),
+        Some("html > body > div.products > div:nth-child(2) > span:nth-child(1)")
+    );
+
+    // Filters keep each value's element; a fold has none.
+    let kept = cs.query_detailed(-1, "css:span:text-ends('4') >> matches('\\d')");
+    assert_eq!(
+        kept.locations[0].css_path.as_deref(),
+        Some("div#p-3 > span.price")
+    );
+    let joined = cs.query_detailed(-1, "css:.price >> join(',')");
+    assert_eq!(joined.results, vec!["$1,$2,$3,$4"]);
+    assert_eq!(joined.locations, vec![ResultLocation::default()]);
+}
+
+#[test]
+fn xpath_element_results_carry_position_and_path() {
+    let cs = shop();
+    let css = cs.query_detailed(-1, "css:.price");
+    let r = cs.query_detailed(-1, "xpath://span[contains(@class, 'price')]");
+    assert_eq!(r.results, css.results);
+    assert_eq!(r.locations, css.locations);
+
+    // Only elements have a path; text and attribute results do not.
+    let text = cs.query_detailed(0, "xpath://div[@id='p-3']/span/text()");
+    assert_eq!(text.results, vec!["$4"]);
+    assert_eq!(text.locations[0].css_path, None);
+    let attr = cs.query_detailed(0, "xpath://div[@id='p-3']/@class");
+    assert_eq!(attr.results, vec!["product"]);
+    assert_eq!(attr.locations[0].position, None);
+
+    // A chain keeps each value's element, as for CSS.
+    let chained = cs.query_detailed(-1, "xpath://span[@class='price'] >> translate('$', '')");
+    assert_eq!(chained.results, vec!["1", "2", "4"]);
+    assert_eq!(
+        chained.locations[2].css_path.as_deref(),
+        Some("div#p-3 > span.price")
+    );
+}
+
+#[test]
+fn regex_results_carry_line_and_column() {
+    let cs = shop();
+    let r = cs.query_detailed(-1, r"regex:price: (\$\d)");
+    assert_eq!(r.results, vec!["$5", "$6"]);
+    let spots: Vec<_> = r
+        .locations
+        .iter()
+        .map(|l| (l.content_index, l.line.unwrap(), l.column.unwrap()))
+        .collect();
+    assert_eq!(spots, vec![(1, 2, 8), (1, 3, 10)]);
+    assert!(r.locations[0].css_path.is_none());
+}
+
+#[test]
+fn other_engines_report_the_content_item() {
+    let mut cs = shop();
+    cs.add_json(r#"{"price": "$7"}"#.to_string());
+    let r = cs.query_detailed(0, "json:price");
+    assert_eq!(r.results, vec!["$7"]);
+    assert_eq!(
+        r.locations,
+        vec![ResultLocation {
+            content_index: 2,
+            ..Default::default()
+        }]
+    );
+
+    let missing = cs.query_detailed(-1, "css:.nothing");
+    assert!(missing.results.is_empty() && missing.locations.is_empty());
+}
+
+#[test]
+fn paths_escape_awkward_names() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<div id="a.b"><p class="2col x:y">Hi</p><p>There</p></div>"#.to_string());
+    let r = cs.query_detailed(-1, "css:p");
+    let paths: Vec<_> = r.locations.iter().map(|l| l.css_path.clone().unwrap()).collect();
+    assert_eq!(paths, vec![r"div#a\.b > p.\32 col.x\:y", "div#a\\.b > p:nth-child(2)"]);
+    for (path, value) in paths.iter().zip(&r.results) {
+        assert_eq!(&cs.query(-1, &format!("css:{path}")), &vec![value.clone()], "{path}");
+    }
+}
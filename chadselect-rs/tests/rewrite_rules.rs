@@ -0,0 +1,105 @@
+//! Tests for builder-level query rewrite rules.
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, ContentType, PrefixMode, RewriteRule};
+
+const HTML: &str = "<ul><li> Blue \n Metallic </li><li>Black</li></ul>";
+
+#[test]
+fn append_functions_to_named_engines() {
+    let rule = RewriteRule::append_functions(&["css", "xpath"], "normalize-space() >> lowercase()");
+    let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    cs.add_html(HTML.to_string());
+
+    assert_eq!(cs.query(-1, "css:li"), vec!["blue metallic", "black"]);
+    assert_eq!(cs.query(-1, "xpath://li"), vec!["blue metallic", "black"]);
+    // Regex is not listed, so its matches are untouched.
+    assert_eq!(cs.query(-1, r"regex:B\w+"), vec!["Blue", "Black"]);
+}
+
+#[test]
+fn append_functions_appends_after_existing_chain() {
+    let rule = RewriteRule::append_functions(&["css"], "uppercase()");
+    let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    cs.add_html(HTML.to_string());
+
+    assert_eq!(cs.query(-1, "css:li >> last()"), vec!["BLACK"]);
+    assert_eq!(
+        cs.query(-1, "css:li >> join('|')"),
+        vec!["BLUE \n METALLIC|BLACK"]
+    );
+}
+
+#[test]
+fn append_functions_skips_queries_already_ending_with_chain() {
+    let rule = RewriteRule::append_functions(&["css"], "count()");
+    let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    cs.add_html(HTML.to_string());
+
+    assert_eq!(cs.query(-1, "css:li"), vec!["2"]);
+    assert_eq!(cs.query(-1, "css:li >> count( )"), vec!["2"]);
+}
+
+#[test]
+fn append_functions_applies_to_each_alternative() {
+    let rule = RewriteRule::append_functions(&["css"], "uppercase()");
+    let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    cs.add_html(HTML.to_string());
+
+    assert_eq!(
+        cs.query(-1, "css:.missing || css:li:last-child"),
+        vec!["BLACK"]
+    );
+}
+
+#[test]
+fn append_functions_uses_detected_engine() {
+    let rule = RewriteRule::append_functions(&["css"], "uppercase()");
+    let mut cs = ChadSelect::builder()
+        .prefix_mode(PrefixMode::Auto)
+        .rewrite_rule(rule)
+        .build();
+    cs.add_html(HTML.to_string());
+
+    assert_eq!(cs.query(-1, "li:last-child"), vec!["BLACK"]);
+    assert_eq!(cs.query(-1, "auto:li:last-child"), vec!["BLACK"]);
+}
+
+#[test]
+fn default_prefix_for_json_only_instances() {
+    let rule = RewriteRule::default_prefix("json", &[ContentType::Json]);
+    let mut cs = ChadSelect::builder().rewrite_rule(rule).build();
+    cs.add_json(r#"{"items": [{"name": "a"}, {"name": "b"}]}"#.to_string());
+
+    assert_eq!(cs.query(-1, "items[].name"), vec!["a", "b"]);
+    // Explicit prefixes are left alone.
+    assert_eq!(cs.query(-1, r#"regex:"name": "(\w)""#), vec!["a", "b"]);
+
+    // Once HTML is loaded too, unprefixed queries fall back to regex.
+    cs.add_html("<p>items[].name</p>".to_string());
+    assert_eq!(cs.query(-1, r"items\[\]"), vec!["items[]"]);
+}
+
+#[test]
+fn default_prefix_needs_content() {
+    let rule = RewriteRule::default_prefix("json:", &[ContentType::Json]);
+    let cs = ChadSelect::builder()
+        .prefix_mode(PrefixMode::Strict)
+        .rewrite_rule(rule)
+        .build();
+
+    assert!(cs.prepare("items[].name").is_err());
+}
+
+#[test]
+fn default_prefix_then_append_functions() {
+    let mut cs = ChadSelect::builder()
+        .rewrite_rule(RewriteRule::append_functions(&["json"], "uppercase()"))
+        .rewrite_rule(RewriteRule::default_prefix("json", &[ContentType::Json]))
+        .build();
+    cs.add_json(r#"{"name": "ada"}"#.to_string());
+
+    let r = cs.query_detailed(0, "name");
+    assert_eq!(r.results, vec!["ADA"]);
+    assert_eq!(r.engine(), Some("json"));
+}
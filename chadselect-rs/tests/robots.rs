@@ -0,0 +1,96 @@
+//! Tests for robots.txt rules and meta-robots directives.
+#![cfg(all(feature = "robots", feature = "css"))]
+
+use chadselect::robots::RobotsTxt;
+use chadselect::ChadSelect;
+
+const ROBOTS: &str = "\
+# Example robots.txt
+User-agent: *
+Disallow: /admin/
+Disallow: /*.pdf$
+Allow: /admin/help
+Crawl-delay: 2
+
+User-agent: Googlebot
+User-agent: bingbot
+Disallow: /search   # no search pages
+Allow: /search/about
+
+User-agent: BadBot
+Disallow: /
+
+Sitemap: https://example.com/sitemap.xml
+";
+
+#[test]
+fn wildcard_group_rules() {
+    let robots = RobotsTxt::parse(ROBOTS);
+    let ua = "MyCrawler/1.0 (+https://example.com/bot)";
+    assert!(robots.is_allowed("/cars/civic", ua));
+    assert!(!robots.is_allowed("/admin/users", ua));
+    assert!(
+        robots.is_allowed("/admin/help/faq", ua),
+        "longer allow wins"
+    );
+    assert!(!robots.is_allowed("/docs/manual.pdf", ua));
+    assert!(
+        robots.is_allowed("/docs/manual.pdf?download=1", ua),
+        "`$` anchors the end"
+    );
+    assert!(robots.is_allowed("", ua));
+    assert_eq!(robots.crawl_delay(ua), Some(2.0));
+}
+
+#[test]
+fn named_groups_replace_the_wildcard_group() {
+    let robots = RobotsTxt::parse(ROBOTS);
+    assert!(robots.is_allowed("/admin/users", "Googlebot/2.1"));
+    assert!(!robots.is_allowed("/search?q=civic", "googlebot"));
+    assert!(robots.is_allowed("/search/about", "BINGBOT"));
+    assert_eq!(robots.crawl_delay("Googlebot"), None);
+    assert!(!robots.is_allowed("/anything", "BadBot"));
+    assert!(robots.is_allowed("/robots.txt", "BadBot"));
+    assert_eq!(robots.sitemaps(), ["https://example.com/sitemap.xml"]);
+}
+
+#[test]
+fn equal_length_ties_go_to_allow() {
+    let robots = RobotsTxt::parse("User-agent: *\nDisallow: /page\nAllow: /page\nDisallow:\n");
+    assert!(robots.is_allowed("/page", "x"));
+    assert!(RobotsTxt::parse("").is_allowed("/anything", "x"));
+    assert!(RobotsTxt::parse("Disallow: /orphan-rule\n").is_allowed("/orphan-rule", "x"));
+}
+
+#[test]
+fn robots_txt_from_loaded_content() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(ROBOTS.to_string());
+    cs.add_html("<p>page</p>".to_string());
+    assert!(!cs.robots_txt(0).unwrap().is_allowed("/admin/", "x"));
+    assert!(cs.robots_txt(1).is_none());
+    assert!(cs.robots_txt(2).is_none());
+}
+
+#[test]
+fn meta_robots_directives() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<head>
+            <meta name="ROBOTS" content="NoIndex, max-snippet:50">
+            <meta name="googlebot" content="noindex, nosnippet">
+            <meta name="description" content="not a directive">
+        </head>"#
+            .to_string(),
+    );
+    cs.add_html(r#"<meta name="robots" content="nofollow">"#.to_string());
+    assert_eq!(
+        cs.meta_robots("MyCrawler"),
+        vec!["noindex", "max-snippet:50", "nofollow"]
+    );
+    assert_eq!(
+        cs.meta_robots("Googlebot/2.1"),
+        vec!["noindex", "max-snippet:50", "nosnippet", "nofollow"]
+    );
+    assert!(ChadSelect::new().meta_robots("x").is_empty());
+}
@@ -0,0 +1,105 @@
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, FiredRule, RuleSet};
+
+fn listing_rules() -> RuleSet {
+    RuleSet::new()
+        .rule("captcha", "css:#challenge", "captcha")
+        .rule("error", "regex:Access Denied|503 Service", "error")
+        .rule("api-error", "json:error.message", "error")
+        .rule_where("sold", "css:.status", "sold", |s| {
+            s.eq_ignore_ascii_case("sold")
+        })
+        .rule("available", "css:.price", "available")
+}
+
+fn pages() -> ChadSelect {
+    let mut cs = ChadSelect::builder().rules(listing_rules()).build();
+    cs.add_html(r#"<form id="challenge"></form>"#.to_string());
+    cs.add_html(
+        r#"<span class="status">SOLD</span><span class="price">$28,500</span>"#.to_string(),
+    );
+    cs.add_html(
+        r#"<span class="status">In stock</span><span class="price">$31,000</span>"#.to_string(),
+    );
+    cs.add_json(r#"{"error": {"message": "rate limited"}}"#.to_string());
+    cs.add_text("<h1>Access Denied</h1>".to_string());
+    cs.add_html("<p>nothing to see</p>".to_string());
+    cs
+}
+
+#[test]
+fn reports_fired_rules_per_item_in_rule_order() {
+    let fired = pages().evaluate_rules();
+    let names: Vec<Vec<&str>> = fired
+        .iter()
+        .map(|item| item.iter().map(|rule| rule.name.as_str()).collect())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            vec!["captcha"],
+            vec!["sold", "available"],
+            vec!["available"],
+            vec!["api-error"],
+            vec!["error"],
+            vec![],
+        ]
+    );
+    assert_eq!(
+        fired[1][0],
+        FiredRule {
+            name: "sold".to_string(),
+            outcome: "sold".to_string(),
+            value: "SOLD".to_string(),
+        }
+    );
+    assert_eq!(fired[3][0].value, "rate limited");
+    // The empty form fires on presence alone.
+    assert_eq!(fired[0][0].value, "");
+}
+
+#[test]
+fn rules_and_classify_agree_on_presence() {
+    use chadselect::Classifier;
+
+    let mut cs = ChadSelect::builder()
+        .rules(
+            RuleSet::new()
+                .rule("captcha", "css:.g-recaptcha", "captcha")
+                .rule_where("marker", "css:.g-recaptcha", "captcha", |_| true),
+        )
+        .build();
+    cs.add_html(r#"<div class="g-recaptcha"></div>"#.to_string());
+    cs.add_html(r#"<div class="g-recaptcha">Verify</div>"#.to_string());
+    cs.add_html("<p>Verify</p>".to_string());
+
+    let classifier = Classifier::new().indicator("captcha", "css:.g-recaptcha", 1.0);
+    let fired = cs.evaluate_rules();
+    for (index, rules) in fired.iter().enumerate() {
+        let present = rules.iter().any(|rule| rule.name == "captcha");
+        assert_eq!(present, cs.classify(index, &classifier).is_some());
+    }
+    assert_eq!(fired[0].len(), 1, "a condition still needs a value");
+    assert_eq!(fired[1][0].value, "Verify");
+    assert!(fired[2].is_empty());
+}
+
+#[test]
+fn rules_can_be_replaced() {
+    let mut cs = pages();
+    assert_eq!(cs.rules().len(), 5);
+
+    cs.set_rules(
+        listing_rules()
+            .rule("captcha", "regex:verify you are human", "captcha")
+            .rule("broken", "css:p[", "never"),
+    );
+    assert_eq!(cs.rules().len(), 6);
+    let fired = cs.evaluate_rules();
+    assert!(fired[0].is_empty());
+    assert!(fired.iter().flatten().all(|rule| rule.name != "broken"));
+
+    cs.set_rules(RuleSet::new());
+    assert!(cs.evaluate_rules().iter().all(Vec::is_empty));
+}
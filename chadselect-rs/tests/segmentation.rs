@@ -0,0 +1,78 @@
+//! Tests for the `sentences()` and `paragraphs()` pipe functions.
+#![cfg(feature = "css")]
+
+use chadselect::functions::{apply_text_functions, parse_text_functions};
+use chadselect::ChadSelect;
+
+fn apply(values: &[&str], chain: &str) -> Vec<String> {
+    let values = values.iter().map(|v| v.to_string()).collect();
+    apply_text_functions(values, &parse_text_functions(chain))
+}
+
+#[test]
+fn sentences_split_on_unicode_boundaries() {
+    assert_eq!(
+        apply(
+            &["Mr. Smith bought it. It runs well!  Any questions? Call us"],
+            "sentences()"
+        ),
+        vec![
+            "Mr.",
+            "Smith bought it.",
+            "It runs well!",
+            "Any questions?",
+            "Call us"
+        ]
+    );
+    // Full stops that end no sentence stay put.
+    assert_eq!(
+        apply(&["Price: 1.5 million. Done. e.g. not here."], "sentences()"),
+        vec!["Price: 1.5 million.", "Done. e.g. not here."]
+    );
+    assert_eq!(
+        apply(&["車は新しいです。走行距離は少ないです。"], "sentences()"),
+        vec!["車は新しいです。", "走行距離は少ないです。"]
+    );
+}
+
+#[test]
+fn paragraphs_split_on_blank_lines() {
+    let body = "First line\nstill first.\n\n  \nSecond.\r\n\r\nThird\u{2029}Fourth\n";
+    assert_eq!(
+        apply(&[body], "paragraphs()"),
+        vec!["First line\nstill first.", "Second.", "Third", "Fourth"]
+    );
+    assert!(apply(&["\n\n  \n"], "paragraphs()").is_empty());
+}
+
+#[test]
+fn split_values_flow_through_the_rest_of_the_chain() {
+    let values = &["One. Two.", "Three."];
+    assert_eq!(apply(values, "sentences() >> count()"), vec!["3"]);
+    assert_eq!(
+        apply(values, "sentences() >> matches('T') >> uppercase()"),
+        vec!["TWO.", "THREE."]
+    );
+    assert_eq!(
+        apply(&["A\n\nB. C."], "paragraphs() >> sentences()"),
+        vec!["A", "B.", "C."]
+    );
+}
+
+#[test]
+fn splitting_works_on_query_results() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        "<div class='desc'>Clean title. One owner.</div><a class='desc' href='/x'>See more.</a>"
+            .to_string(),
+    );
+    assert_eq!(
+        cs.query(-1, "css:.desc >> sentences()"),
+        vec!["Clean title.", "One owner.", "See more."]
+    );
+    // Each part keeps its element, so `get-attr` still reads it.
+    assert_eq!(
+        cs.query(-1, "css:a.desc >> sentences() >> get-attr('href')"),
+        vec!["/x"]
+    );
+}
@@ -0,0 +1,54 @@
+//! Tests for `select_each`.
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+#[test]
+fn one_entry_per_item_in_load_order() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<h1>Civic</h1><h1>second</h1>".to_string());
+    cs.add_json(r#"{"title": "Accord"}"#.to_string());
+    cs.add_html("<p>no heading</p>".to_string());
+    cs.add_html("<h1>  </h1><h1>CR-V</h1>".to_string());
+
+    // JSON is not searched by CSS; whitespace-only matches are not valid.
+    assert_eq!(cs.select_each("css:h1"), vec!["Civic", "", "", "CR-V"]);
+}
+
+#[test]
+fn fallbacks_are_tried_per_item() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<h1>Civic</h1>".to_string());
+    cs.add_json(r#"{"title": "Accord"}"#.to_string());
+    cs.add_html("<title>CR-V</title>".to_string());
+
+    assert_eq!(
+        cs.select_each("css:h1 || json:title || css:title"),
+        vec!["Civic", "Accord", "CR-V"]
+    );
+}
+
+#[test]
+fn functions_and_custom_validators() {
+    let mut cs = ChadSelect::new();
+    cs.add_text("price: 0, then 12".to_string());
+    cs.add_text("price: 7".to_string());
+
+    assert_eq!(
+        cs.select_each(r"regex:\d+ >> format('${}')"),
+        vec!["$0", "$7"]
+    );
+    assert_eq!(
+        cs.select_each_where(r"regex:\d+", |v| v != "0"),
+        vec!["12", "7"]
+    );
+}
+
+#[test]
+fn invalid_query_keeps_alignment() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>a</p>".to_string());
+    cs.add_html("<p>b</p>".to_string());
+    assert_eq!(cs.select_each("css:p["), vec!["", ""]);
+    assert!(ChadSelect::new().select_each("css:p").is_empty());
+}
@@ -0,0 +1,72 @@
+//! Tests for `find_similar`.
+#![cfg(all(feature = "similar", feature = "css", feature = "json"))]
+
+use chadselect::ChadSelect;
+
+const CIVIC: &str = "2019 Honda Civic EX in excellent condition, one owner, 28,000 miles, \
+    new tires, full service history, clean title, financing available";
+
+fn page(body: &str) -> String {
+    format!(
+        "<html><head><style>p {{ color: red }}</style></head>\
+         <body><nav>Home | Search | Sell</nav><p>{}</p></body></html>",
+        body
+    )
+}
+
+#[test]
+fn identical_text_scores_one() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page(CIVIC));
+    cs.add_html(page(CIVIC));
+    assert_eq!(cs.find_similar(0, 0.5), vec![(1, 1.0)]);
+}
+
+#[test]
+fn shared_template_with_different_text_does_not_match() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page(CIVIC));
+    cs.add_html(page(
+        "2021 Toyota Prius hybrid with factory warranty, 12,000 miles, heated seats",
+    ));
+    assert!(cs.find_similar(0, 0.5).is_empty());
+}
+
+#[test]
+fn near_duplicates_rank_above_looser_matches() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(page(CIVIC));
+    cs.add_html(page(&CIVIC.replace("financing available", "call today")));
+    cs.add_html(page(&format!(
+        "{} Also listed: 2018 Honda Accord, 41,000 miles, sunroof, leather seats, \
+         backup camera, one owner, dealer maintained, certified pre-owned",
+        CIVIC
+    )));
+    cs.add_html(page("Unrelated page about lawn mowers"));
+
+    let similar = cs.find_similar(0, 0.3);
+    let order: Vec<usize> = similar.iter().map(|(index, _)| *index).collect();
+    assert_eq!(order, vec![1, 2]);
+    assert!(similar[0].1 > similar[1].1);
+    assert!(similar.iter().all(|(_, score)| *score < 1.0));
+}
+
+#[test]
+fn non_html_content_uses_raw_text() {
+    let mut cs = ChadSelect::new();
+    cs.add_text(CIVIC.to_string());
+    cs.add_html(page(CIVIC));
+    cs.add_json(r#"{"id": 1}"#.to_string());
+    let similar = cs.find_similar(0, 0.5);
+    assert_eq!(similar.len(), 1);
+    assert_eq!(similar[0].0, 1);
+}
+
+#[test]
+fn empty_and_out_of_range_items_match_nothing() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p></p>".to_string());
+    cs.add_html("<p> </p>".to_string());
+    assert!(cs.find_similar(0, 0.0).is_empty());
+    assert!(cs.find_similar(5, 0.0).is_empty());
+}
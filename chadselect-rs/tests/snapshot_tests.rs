@@ -0,0 +1,82 @@
+//! Tests for `Clone`, `snapshot`, and `restore`.
+#![cfg(all(feature = "snapshot", feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, PrefixMode, RuleSet, Snapshot};
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::builder().prefix_mode(PrefixMode::Strict).build();
+    cs.define("title", "css:h1");
+    cs.add_html("<h1>Civic</h1>".to_string());
+    cs.add_json(r#"{"price": 28500}"#.to_string());
+    cs
+}
+
+#[test]
+fn clone_copies_content_library_and_options() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, "ref:title"), "Civic");
+
+    let mut copy = cs.clone();
+    assert_eq!(copy.content_count(), 2);
+    assert_eq!(copy.select(0, "ref:title"), "Civic");
+    assert_eq!(copy.select(0, "json:price"), "28500");
+    // Strict mode carried over: unprefixed queries are rejected.
+    assert!(copy.query(-1, "Civic").is_empty());
+    // Caches are not copied.
+    assert_eq!(cs.clone().memory_usage().parsed_items, 0);
+
+    // The clone is independent.
+    copy.clear();
+    assert_eq!(cs.content_count(), 2);
+}
+
+#[test]
+fn restore_replaces_state() {
+    let mut cs = make_cs();
+    let checkpoint = cs.snapshot();
+    assert_eq!(checkpoint.content_count(), 2);
+
+    cs.clear();
+    cs.add_text("stage two".to_string());
+    cs.define("title", "regex:(stage)");
+    assert_eq!(cs.select(0, "ref:title"), "stage");
+
+    cs.restore(&checkpoint);
+    assert_eq!(cs.content_count(), 2);
+    assert_eq!(cs.select(0, "ref:title"), "Civic");
+}
+
+#[test]
+fn snapshot_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Snapshot>();
+}
+
+#[test]
+fn workers_rebuild_from_snapshot() {
+    let snapshot = make_cs().snapshot();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let snapshot = snapshot.clone();
+            std::thread::spawn(move || ChadSelect::from_snapshot(&snapshot).select(0, "json:price"))
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), "28500");
+    }
+}
+
+#[test]
+fn snapshot_carries_rules() {
+    let mut cs = make_cs();
+    cs.set_rules(RuleSet::new().rule("car", "regex:Civic", "car"));
+    let snapshot = cs.snapshot();
+
+    let rebuilt = ChadSelect::from_snapshot(&snapshot);
+    assert_eq!(rebuilt.rules().len(), 1);
+    assert_eq!(rebuilt.evaluate_rules()[0][0].outcome, "car");
+
+    cs.set_rules(RuleSet::new());
+    cs.restore(&snapshot);
+    assert_eq!(cs.rules().len(), 1);
+}
@@ -0,0 +1,129 @@
+//! Tests for `ChadSelect::store` (the `sqlite` feature).
+#![cfg(all(feature = "sqlite", feature = "css"))]
+
+use chadselect::store::rusqlite::Connection;
+use chadselect::{ChadSelect, ColumnType, ExtractionSchema, WarningKind};
+
+fn listings() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<h1>Civic</h1><span class="price">$28,500</span><span class="miles">12,000.5 mi</span>"#
+            .to_string(),
+    );
+    cs.add_html(r#"<h1>Accord</h1><span class="price">call us</span>"#.to_string());
+    cs.add_html("<p>no listing here</p>".to_string());
+    cs
+}
+
+fn schema() -> ExtractionSchema {
+    ExtractionSchema::new()
+        .column("title", ColumnType::Text, "css:h1")
+        .column("price", ColumnType::Integer, "css:.price")
+        .column("miles", ColumnType::Real, "css:.miles")
+}
+
+type Row = (String, Option<i64>, Option<f64>);
+
+fn rows(conn: &Connection, table: &str) -> Vec<Row> {
+    let mut statement = conn
+        .prepare(&format!(
+            "SELECT title, price, miles FROM {table} ORDER BY rowid"
+        ))
+        .unwrap();
+    statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn creates_typed_table_and_skips_empty_items() {
+    let conn = Connection::open_in_memory().unwrap();
+    assert_eq!(listings().store(&conn, "cars", &schema()), Ok(2));
+
+    assert_eq!(
+        rows(&conn, "cars"),
+        vec![
+            ("Civic".to_string(), Some(28500), Some(12000.5)),
+            ("Accord".to_string(), None, None),
+        ]
+    );
+
+    let types: Vec<String> = conn
+        .prepare("SELECT type FROM pragma_table_info('cars')")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(types, vec!["TEXT", "INTEGER", "REAL"]);
+}
+
+#[test]
+fn appends_to_existing_table() {
+    let conn = Connection::open_in_memory().unwrap();
+    let cs = listings();
+    cs.store(&conn, "cars", &schema()).unwrap();
+    cs.store(&conn, "cars", &schema()).unwrap();
+    assert_eq!(rows(&conn, "cars").len(), 4);
+}
+
+#[test]
+fn existing_table_may_have_extra_columns() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(
+        "CREATE TABLE cars (id INTEGER PRIMARY KEY, title TEXT, price INTEGER, miles REAL)",
+        [],
+    )
+    .unwrap();
+    assert_eq!(listings().store(&conn, "cars", &schema()), Ok(2));
+}
+
+#[test]
+fn existing_table_missing_a_column_is_an_error() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute("CREATE TABLE cars (title TEXT)", []).unwrap();
+    let err = listings().store(&conn, "cars", &schema()).unwrap_err();
+    assert!(err.contains("no column price"), "{err}");
+    let count: i64 = conn
+        .query_row("SELECT count(*) FROM cars", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn quotes_identifiers() {
+    let conn = Connection::open_in_memory().unwrap();
+    let schema = ExtractionSchema::new().column("select \"title\"", ColumnType::Text, "css:h1");
+    assert_eq!(listings().store(&conn, "car listings", &schema), Ok(2));
+    let title: String = conn
+        .query_row(
+            r#"SELECT "select ""title""" FROM "car listings" LIMIT 1"#,
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(title, "Civic");
+}
+
+#[test]
+fn empty_schema_is_an_error() {
+    let conn = Connection::open_in_memory().unwrap();
+    assert!(listings()
+        .store(&conn, "cars", &ExtractionSchema::new())
+        .is_err());
+}
+
+#[test]
+fn invalid_query_leaves_column_null_with_warning() {
+    let conn = Connection::open_in_memory().unwrap();
+    let cs = listings();
+    let schema = schema().column("price", ColumnType::Integer, "css:[");
+    assert_eq!(cs.store(&conn, "cars", &schema), Ok(2));
+    assert!(rows(&conn, "cars").iter().all(|row| row.1.is_none()));
+    assert!(cs
+        .take_warnings()
+        .iter()
+        .any(|w| w.kind == WarningKind::InvalidQuery));
+}
@@ -0,0 +1,123 @@
+//! Tests for the `structured:` engine (JSON-LD, microdata, RDFa, OpenGraph).
+#![cfg(all(feature = "css", feature = "json"))]
+
+use chadselect::ChadSelect;
+
+const HTML: &str = r#"<html><head>
+<meta property="og:title" content="Widget Deluxe">
+<meta property="og:type" content="product">
+<meta property="og:image" content="https://example.com/a.jpg">
+<meta property="og:image" content="https://example.com/b.jpg">
+<meta name="description" content="not opengraph">
+<script type="application/ld+json">
+{"@context": "https://schema.org", "@type": "Product", "name": "Widget", "offers": {"@type": "Offer", "price": "19.99"}}
+</script>
+<script type="application/ld+json">
+[{"@type": "BreadcrumbList"}, {"@type": "Organization", "name": "Acme"}]
+</script>
+<script type="application/ld+json">{ not json }</script>
+</head><body>
+<div itemscope itemtype="https://schema.org/Product">
+  <h1 itemprop="name">  Microdata
+     Widget </h1>
+  <img itemprop="image" src="/w.jpg">
+  <span itemprop="color">red</span><span itemprop="color">blue</span>
+  <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+    <meta itemprop="priceCurrency" content="USD">
+    <span itemprop="price">24.50</span>
+  </div>
+  <a itemprop="url" href="https://example.com/widget">link</a>
+</div>
+<div itemscope itemtype="https://schema.org/Person"><span itemprop="name">Ann</span></div>
+<div vocab="https://schema.org/" typeof="Event">
+  <span property="name">Launch</span>
+  <time property="startDate" datetime="2024-05-01">May 1</time>
+  <div property="location" typeof="Place"><span property="name">Hall A</span></div>
+</div>
+</body></html>"#;
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+// ─── JSON-LD ────────────────────────────────────────────────────────────────
+
+#[test]
+fn json_ld_blocks() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, "structured:jsonld[0].offers.price"), "19.99");
+    let types = cs.query(-1, r#"structured:jsonld[]."@type""#);
+    assert_eq!(types, vec!["Product", "BreadcrumbList", "Organization"]);
+}
+
+#[test]
+fn json_ld_filter_by_type() {
+    let cs = make_cs();
+    let name = cs.select(0, r#"structured:jsonld[?"@type"=='Organization'].name"#);
+    assert_eq!(name, "Acme");
+}
+
+// ─── Microdata ──────────────────────────────────────────────────────────────
+
+#[test]
+fn microdata_properties() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, r#"structured:microdata[0]."@type""#), "Product");
+    assert_eq!(cs.select(0, "structured:microdata[0].name"), "Microdata Widget");
+    assert_eq!(cs.select(0, "structured:microdata[0].image"), "/w.jpg");
+    assert_eq!(cs.select(0, "structured:microdata[0].url"), "https://example.com/widget");
+}
+
+#[test]
+fn microdata_repeated_and_nested() {
+    let cs = make_cs();
+    assert_eq!(cs.query(-1, "structured:microdata[0].color"), vec!["red", "blue"]);
+    assert_eq!(cs.select(0, "structured:microdata[0].offers.price"), "24.50");
+    assert_eq!(cs.select(0, "structured:microdata[0].offers.priceCurrency"), "USD");
+}
+
+#[test]
+fn microdata_top_level_items_only() {
+    let cs = make_cs();
+    let types = cs.query(-1, r#"structured:microdata[]."@type""#);
+    assert_eq!(types, vec!["Product", "Person"]);
+}
+
+// ─── RDFa ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn rdfa_items() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, r#"structured:rdfa[0]."@type""#), "Event");
+    assert_eq!(cs.select(0, "structured:rdfa[0].startDate"), "2024-05-01");
+    assert_eq!(cs.select(0, "structured:rdfa[0].location.name"), "Hall A");
+}
+
+// ─── OpenGraph ──────────────────────────────────────────────────────────────
+
+#[test]
+fn open_graph_tags() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, "structured:opengraph.title"), "Widget Deluxe");
+    assert_eq!(cs.query(-1, "structured:opengraph.image").len(), 2);
+    assert!(cs.query(-1, "structured:opengraph.description").is_empty());
+}
+
+// ─── Integration ────────────────────────────────────────────────────────────
+
+#[test]
+fn function_pipe_and_fallback() {
+    let cs = make_cs();
+    assert_eq!(cs.select(0, "structured:opengraph.title >> uppercase()"), "WIDGET DELUXE");
+    let price = cs.select(0, "css:.price || structured:microdata[0].offers.price");
+    assert_eq!(price, "24.50");
+}
+
+#[test]
+fn structured_skips_non_html_content() {
+    let mut cs = ChadSelect::new();
+    cs.add_json(r#"{"opengraph": {"title": "x"}}"#.to_string());
+    assert!(cs.query(-1, "structured:opengraph.title").is_empty());
+}
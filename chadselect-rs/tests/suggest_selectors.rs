@@ -0,0 +1,79 @@
+//! Tests for `suggest_selectors`.
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use chadselect::ChadSelect;
+
+fn listing() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<html><body>
+        <h1 id="title">2021 Honda Civic</h1>
+        <table class="specs">
+            <tr><th>Mileage</th><td>12,345 mi</td></tr>
+            <tr><th>VIN</th><td>1HGCM82633A004352</td></tr>
+        </table>
+        <div class="price-box"><span class="price">$28,500</span></div>
+        <a class="dealer" href="/dealers/metro-honda" data-dealer-id="D-778">Metro Honda</a>
+        <script>var vin = "1HGCM82633A004352";</script>
+        </body></html>"#
+            .to_string(),
+    );
+    cs
+}
+
+#[test]
+fn suggestions_select_the_value() {
+    let cs = listing();
+    for target in [
+        "2021 Honda Civic",
+        "12,345 mi",
+        "$28,500",
+        "1HGCM82633A004352",
+        "D-778",
+    ] {
+        let suggestions = cs.suggest_selectors(0, target);
+        assert!(!suggestions.is_empty(), "no suggestion for {target}");
+        for query in &suggestions {
+            assert!(cs.select(0, query).contains(target), "{query} for {target}");
+        }
+    }
+}
+
+#[test]
+fn most_specific_suggestions_come_first() {
+    let cs = listing();
+    assert_eq!(cs.suggest_selectors(0, "2021 Honda Civic")[0], "css:#title");
+    assert_eq!(
+        cs.suggest_selectors(0, "$28,500")[0],
+        "css:div.price-box > span.price"
+    );
+    let vin = cs.suggest_selectors(0, "1HGCM82633A004352");
+    assert!(
+        vin.contains(&"css:tr:has-text('VIN') td".to_string()),
+        "{vin:?}"
+    );
+    assert!(
+        vin.contains(&"xpath://th[normalize-space()='VIN']/following-sibling::td[1]".to_string()),
+        "{vin:?}"
+    );
+    assert_eq!(
+        cs.suggest_selectors(0, "D-778")[0],
+        "css:a.dealer[data-dealer-id] >> get-attr('data-dealer-id')"
+    );
+}
+
+#[test]
+fn index_and_missing_values() {
+    let cs = listing();
+    // Suggestions honour `index` like `query` does.
+    let at_one = cs.suggest_selectors(1, "1HGCM82633A004352");
+    assert!(at_one
+        .iter()
+        .all(|q| cs.query(1, q).concat().contains("1HGCM82633A004352")));
+    assert!(cs.suggest_selectors(0, "not on the page").is_empty());
+    assert!(cs.suggest_selectors(0, "   ").is_empty());
+
+    let mut json = ChadSelect::new();
+    json.add_json(r#"{"vin": "1HGCM82633A004352"}"#.to_string());
+    assert!(json.suggest_selectors(-1, "1HGCM82633A004352").is_empty());
+}
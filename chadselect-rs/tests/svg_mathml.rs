@@ -0,0 +1,88 @@
+//! Tests for queries over embedded SVG and MathML.
+#![cfg(feature = "xpath")]
+
+use chadselect::ChadSelect;
+
+const CHART: &str = r##"<html><body>
+<figure>
+  <svg class="chart" viewBox="0 0 100 50">
+    <defs><linearGradient id="fill"/></defs>
+    <g class="series">
+      <path class="line" d="M0 40 L50 20 L100 10"/>
+      <text x="10" y="45"><tspan>Q1</tspan> <tspan>42</tspan></text>
+      <text x="60" y="25">Q2 57</text>
+    </g>
+    <use xlink:href="#marker"/>
+  </svg>
+  <math><mi>x</mi><mo>=</mo><mn>2</mn></math>
+</figure>
+</body></html>"##;
+
+fn chart() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(CHART.to_string());
+    cs
+}
+
+#[test]
+fn css_reaches_svg_elements_and_attributes() {
+    let cs = chart();
+
+    assert_eq!(
+        cs.query(-1, "css:svg text >> normalize-space()"),
+        vec!["Q1 42", "Q2 57"]
+    );
+    assert_eq!(
+        cs.select(0, "css:svg path.line >> get-attr('d')"),
+        "M0 40 L50 20 L100 10"
+    );
+    assert_eq!(
+        cs.select(0, "css:defs > linearGradient >> get-attr('id')"),
+        "fill"
+    );
+}
+
+#[test]
+fn get_attr_handles_camel_case_and_xlink_names() {
+    let cs = chart();
+
+    assert_eq!(cs.select(0, "css:svg >> get-attr('viewBox')"), "0 0 100 50");
+    assert_eq!(cs.select(0, "css:svg >> get-attr('viewbox')"), "0 0 100 50");
+    assert_eq!(cs.select(0, "css:use >> get-attr('xlink:href')"), "#marker");
+    assert_eq!(cs.select(0, "css:use >> get-attr('href')"), "#marker");
+}
+
+#[test]
+fn xpath_accepts_svg_and_xlink_prefixes() {
+    let cs = chart();
+
+    assert_eq!(
+        cs.query(-1, "xpath://svg:g/svg:text[2]/text()"),
+        vec!["Q2 57"]
+    );
+    assert_eq!(cs.select(0, "xpath://svg:path/@d"), "M0 40 L50 20 L100 10");
+    assert_eq!(cs.select(0, "xpath://svg:use/@xlink:href"), "#marker");
+    assert_eq!(cs.select(0, "xpath:count(//svg:g/svg:*)"), "3");
+    // The same queries without prefixes.
+    assert_eq!(cs.select(0, "xpath://svg/@viewBox"), "0 0 100 50");
+    assert_eq!(
+        cs.select(0, "xpath://use[@href='#marker']/@href"),
+        "#marker"
+    );
+}
+
+#[test]
+fn mathml_is_queryable() {
+    let cs = chart();
+
+    assert_eq!(cs.query(-1, "css:math mi, math mn"), vec!["x", "2"]);
+    assert_eq!(cs.select(0, "xpath:string(//math:math)"), "x=2");
+}
+
+#[test]
+fn prefixes_inside_string_literals_are_kept() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<p title="svg:text">label</p>"#.to_string());
+
+    assert_eq!(cs.select(0, "xpath://p[@title='svg:text']/text()"), "label");
+}
@@ -0,0 +1,81 @@
+//! Tests for `hoist_templates`.
+#![cfg(feature = "xpath")]
+
+use chadselect::ChadSelect;
+
+const PAGE: &str = r#"<html><body>
+<product-card id="card">
+  <template id="data"><div class="price">$28,500</div><span class="model">Civic</span></template>
+</product-card>
+</body></html>"#;
+
+fn hoisting(enabled: bool) -> ChadSelect {
+    let mut cs = ChadSelect::builder().hoist_templates(enabled).build();
+    cs.add_html(PAGE.to_string());
+    cs
+}
+
+#[test]
+fn selectors_cross_the_template_boundary_when_hoisted() {
+    let cs = hoisting(true);
+
+    assert_eq!(cs.select(0, "css:#data .price"), "$28,500");
+    assert_eq!(cs.select(0, "css:product-card > template > span"), "Civic");
+    assert_eq!(
+        cs.select(0, "xpath://template[@id='data']/div/text()"),
+        "$28,500"
+    );
+    assert_eq!(cs.select(0, "xpath:count(//product-card//span)"), "1");
+}
+
+#[test]
+fn template_contents_stay_separate_by_default() {
+    let cs = hoisting(false);
+
+    assert!(cs.query(-1, "css:#data .price").is_empty());
+    assert_eq!(cs.select(0, "xpath:count(//template/*)"), "0");
+}
+
+#[test]
+fn nested_templates_are_hoisted() {
+    let mut cs = ChadSelect::builder().hoist_templates(true).build();
+    cs.add_html(
+        r#"<template id="outer"><section><template id="inner"><b>deep</b></template></section></template>"#
+            .to_string(),
+    );
+
+    assert_eq!(cs.select(0, "css:#outer section #inner b"), "deep");
+    assert_eq!(
+        cs.select(0, "xpath://template[@id='outer']//template/b/text()"),
+        "deep"
+    );
+}
+
+#[test]
+fn declarative_shadow_roots_are_parsed_into_the_host() {
+    let page = r#"<product-card><template shadowrootmode="open"><div class="price">$9</div></template></product-card>"#;
+    for enabled in [false, true] {
+        let mut cs = ChadSelect::builder().hoist_templates(enabled).build();
+        cs.add_html(page.to_string());
+
+        assert_eq!(cs.select(0, "css:product-card .price"), "$9");
+        assert_eq!(cs.select(0, "xpath://product-card/div/text()"), "$9");
+    }
+}
+
+#[test]
+fn applies_to_fragments_snapshots_and_progress_parsing() {
+    let mut cs = ChadSelect::builder().hoist_templates(true).build();
+    cs.add_html_fragment(r#"<template id="t"><i>fragment</i></template>"#.to_string());
+    cs.add_html_with_progress(PAGE.to_string(), |_| std::ops::ControlFlow::Continue(()))
+        .unwrap();
+
+    assert_eq!(cs.select(0, "xpath:/template/i/text()"), "fragment");
+    assert_eq!(cs.query(-1, "css:#data .price"), vec!["$28,500"]);
+
+    #[cfg(feature = "snapshot")]
+    {
+        let restored = ChadSelect::from_snapshot(&cs.snapshot());
+        assert_eq!(restored.query(-1, "css:template .model"), vec!["Civic"]);
+    }
+}
@@ -0,0 +1,61 @@
+#![cfg(all(feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::ChadSelect;
+
+fn page() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(
+        r#"<h1>Civic</h1>
+        <span class="price">$28,500</span>
+        <span class="vin">VIN: 1HGCM82633A004352</span>"#
+            .to_string(),
+    );
+    cs.add_json(r#"{"dealer": {"name": "Metro Honda"}}"#.to_string());
+    cs
+}
+
+#[test]
+fn render_interleaves_literals_and_results() {
+    let cs = page();
+    assert_eq!(
+        cs.render("Title: {css:h1} Price: {css:.price} Dealer: {json:dealer.name}"),
+        "Title: Civic Price: $28,500 Dealer: Metro Honda"
+    );
+}
+
+#[test]
+fn render_runs_function_chains() {
+    let cs = page();
+    assert_eq!(
+        cs.render("{css:h1 >> uppercase()} at {css:.price >> to-number() >> format('{} USD')}"),
+        "CIVIC at 28500 USD"
+    );
+}
+
+#[test]
+fn render_keeps_nested_braces_in_queries() {
+    let cs = page();
+    assert_eq!(
+        cs.render(r"vin={regex:([A-Z0-9]{17})}"),
+        "vin=1HGCM82633A004352"
+    );
+    assert_eq!(cs.render("{css:h1 >> format('}{}')}"), "}Civic");
+}
+
+#[test]
+fn render_escapes_and_misses() {
+    let cs = page();
+    assert_eq!(cs.render("{{literal}} {css:h1}"), "{literal} Civic");
+    assert_eq!(cs.render("missing=[{css:.nope}]"), "missing=[]");
+    assert_eq!(cs.render("no placeholders"), "no placeholders");
+    assert_eq!(cs.render("lone } brace"), "lone } brace");
+    assert_eq!(cs.render("open {css:h1"), "open {css:h1");
+    assert_eq!(cs.render("naïve → {css:h1} ✓"), "naïve → Civic ✓");
+}
+
+#[test]
+fn render_resolves_named_queries() {
+    let mut cs = page();
+    cs.define("title", "css:h1");
+    assert_eq!(cs.render("<{ref:title}>"), "<Civic>");
+}
@@ -0,0 +1,79 @@
+//! Tests for the `:text-fuzzy()` CSS pseudo-selector.
+#![cfg(feature = "xpath")]
+
+use chadselect::translate::css_to_xpath;
+use chadselect::{ChadSelect, PreparedQuery};
+
+const SPECS: &str = r#"<table>
+<tr><th>Continental-GT</th><td>Bentley</td></tr>
+<tr><th>continental  gt.</th><td>Bentley (UK)</td></tr>
+<tr><th>Continental GTC Speed</th><td>Convertible</td></tr>
+<tr><th>Flying Spur</th><td>Sedan</td></tr>
+</table>"#;
+
+fn specs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(SPECS.to_string());
+    cs
+}
+
+#[test]
+fn matches_despite_case_punctuation_and_spacing() {
+    assert_eq!(
+        specs().query(-1, "css:th:text-fuzzy('Continental GT', 0.8)"),
+        vec!["Continental-GT", "continental  gt."]
+    );
+}
+
+#[test]
+fn threshold_defaults_to_point_eight_and_bounds_matches() {
+    let cs = specs();
+    assert_eq!(
+        cs.query(-1, "css:th:text-fuzzy('Continental GT')"),
+        cs.query(-1, "css:th:text-fuzzy('Continental GT', 0.8)")
+    );
+    // "continental gtc speed" is 7 edits from "continental gt" (21 chars).
+    assert_eq!(
+        cs.query(-1, "css:th:text-fuzzy('Continental GT', 0.6)")
+            .len(),
+        3
+    );
+    assert_eq!(
+        cs.query(-1, "css:th:text-fuzzy('Continental GT', 0)").len(),
+        4
+    );
+    // Typos within the threshold still match.
+    assert_eq!(
+        cs.query(-1, "css:th:text-fuzzy(\"Flyng Spur\", 0.9)"),
+        vec!["Flying Spur"]
+    );
+    assert!(cs
+        .query(-1, "css:th:text-fuzzy('Flyng Spur', 1)")
+        .is_empty());
+}
+
+#[test]
+fn combinators_and_functional_pseudo_classes() {
+    let cs = specs();
+    assert_eq!(
+        cs.query(-1, "css:th:text-fuzzy('continental gt') + td"),
+        vec!["Bentley", "Bentley (UK)"]
+    );
+    assert_eq!(
+        cs.query(-1, "css:tr:has(th:text-fuzzy('flying spur')) td"),
+        vec!["Sedan"]
+    );
+}
+
+#[test]
+fn malformed_arguments_are_invalid() {
+    for query in [
+        "css:th:text-fuzzy('GT', 1.5)",
+        "css:th:text-fuzzy('GT', high)",
+        "css:th:text-fuzzy('GT', 0.8, 2)",
+        "css:tr:has(th:text-fuzzy('GT', -1))",
+    ] {
+        assert!(PreparedQuery::new(query).is_err(), "{}", query);
+    }
+    assert!(css_to_xpath("th:text-fuzzy('GT')").is_err());
+}
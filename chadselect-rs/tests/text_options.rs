@@ -0,0 +1,127 @@
+//! Tests for configurable CSS text flattening (`TextOptions`).
+#![cfg(feature = "xpath")]
+
+use chadselect::{ChadSelect, TextOptions};
+
+const HTML: &str = r#"<html><body>
+<pre class="code">  fn main() {
+    <span>println!("hi");</span>
+  }
+</pre>
+<p class="intro">Hello <b>big</b> world</p>
+<a class="logo" href="/"><img src="logo.png" alt="Acme Corp"> Home</a>
+<span class="icon"><img src="x.png" title="Close"></span>
+</body></html>"#;
+
+fn make_cs(options: TextOptions) -> ChadSelect {
+    let mut cs = ChadSelect::builder().text_options(options).build();
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+#[test]
+fn default_joins_with_space_and_trims() {
+    let cs = make_cs(TextOptions::default());
+    assert_eq!(cs.select(0, "css:p.intro"), "Hello  big  world");
+    assert_eq!(cs.select(0, "css:a.logo"), "Home");
+}
+
+#[test]
+fn raw_text_preserves_pre_whitespace() {
+    let cs = make_cs(TextOptions {
+        separator: String::new(),
+        trim: false,
+        ..TextOptions::default()
+    });
+    assert_eq!(
+        cs.select(0, "css:pre.code"),
+        "  fn main() {\n    println!(\"hi\");\n  }\n"
+    );
+    assert_eq!(cs.select(0, "css:p.intro"), "Hello big world");
+}
+
+#[test]
+fn custom_separator() {
+    let cs = make_cs(TextOptions {
+        separator: "|".to_string(),
+        ..TextOptions::default()
+    });
+    assert_eq!(cs.select(0, "css:p.intro"), "Hello |big| world");
+}
+
+#[test]
+fn alt_and_title_text_included() {
+    let cs = make_cs(TextOptions {
+        include_alt_text: true,
+        ..TextOptions::default()
+    });
+    assert_eq!(cs.select(0, "css:a.logo"), "Acme Corp  Home");
+    assert_eq!(cs.select(0, "css:span.icon"), "Close");
+}
+
+#[test]
+fn pseudo_selectors_match_default_text() {
+    let cs = make_cs(TextOptions {
+        separator: String::new(),
+        trim: false,
+        ..TextOptions::default()
+    });
+    // Matching ignores the options; the extracted text honours them.
+    assert_eq!(cs.select(0, "css:p:has-text('Hello  big')"), "Hello big world");
+}
+
+#[test]
+fn options_apply_to_prepared_queries() {
+    let cs = make_cs(TextOptions {
+        separator: "/".to_string(),
+        ..TextOptions::default()
+    });
+    let prepared = cs.prepare("css:p.intro").unwrap();
+    assert_eq!(cs.select_prepared(0, &prepared), "Hello /big/ world");
+}
+
+// ─── Script/style exclusion ─────────────────────────────────────────────────
+
+const PAGE: &str = r#"<html><head><style>body { color: red }</style></head><body>
+<div id="main">Price: <script>var tracking = 1;</script><b>$10</b><noscript>Enable JS</noscript><template><p>tpl</p></template></div>
+<script type="application/ld+json">{"a": 1}</script>
+</body></html>"#;
+
+fn make_page(exclude: bool) -> ChadSelect {
+    let mut cs = ChadSelect::builder()
+        .text_options(TextOptions {
+            exclude_script_text: exclude,
+            ..TextOptions::default()
+        })
+        .build();
+    cs.add_html(PAGE.to_string());
+    cs
+}
+
+#[test]
+fn script_text_included_by_default() {
+    let cs = make_page(false);
+    assert!(cs.select(0, "css:#main").contains("tracking"));
+    assert!(cs.select(0, "xpath://div[@id='main']").contains("tracking"));
+}
+
+#[test]
+fn css_excludes_script_style_noscript_template() {
+    let cs = make_page(true);
+    assert_eq!(cs.select(0, "css:#main"), "Price:  $10");
+    assert!(!cs.select(0, "css:html").contains("color: red"));
+}
+
+#[test]
+fn xpath_excludes_script_text() {
+    let cs = make_page(true);
+    assert_eq!(cs.select(0, "xpath://div[@id='main']"), "Price: $10");
+    assert!(!cs.select(0, "xpath://body").contains("tracking"));
+}
+
+#[test]
+fn selecting_a_script_still_returns_its_text() {
+    let cs = make_page(true);
+    assert_eq!(cs.select(0, "css:script[type='application/ld+json']"), r#"{"a": 1}"#);
+    assert_eq!(cs.select(0, "xpath://script[@type='application/ld+json']"), r#"{"a": 1}"#);
+}
@@ -0,0 +1,196 @@
+//! Tests for CSS ↔ XPath translation and the `translation` option.
+#![cfg(feature = "xpath")]
+
+use chadselect::translate::{css_to_xpath, xpath_to_css};
+use chadselect::{ChadSelect, Translation};
+
+const HTML: &str = r#"
+<ul id="menu">
+  <li class="item first">One</li>
+  <li class="item">Two</li>
+  <li class="item sale" data-sku="B-7">Three</li>
+  <li>Four</li>
+</ul>
+<div class="row"><span class="label">Price</span><b>$1</b></div>
+<div class="row"><span class="label">Mileage</span><b>42,000</b></div>
+<p lang="en-US"><a href="/a.pdf">A</a><a href="https://x.test/b">B</a></p>
+"#;
+
+fn make_cs() -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_html(HTML.to_string());
+    cs
+}
+
+/// The CSS selector and its XPath translation select the same elements.
+fn assert_same(css: &str) {
+    let cs = make_cs();
+    let xpath = css_to_xpath(css).unwrap();
+    assert_eq!(
+        cs.query(-1, &format!("xpath:{}", xpath)),
+        cs.query(-1, &format!("css:{}", css)),
+        "{} => {}",
+        css,
+        xpath
+    );
+}
+
+#[test]
+fn css_to_xpath_matches_css_engine() {
+    for css in [
+        "li",
+        "#menu > li.item",
+        "ul li.sale",
+        "li[data-sku]",
+        "li[data-sku='B-7']",
+        "li[class~=sale]",
+        "a[href^='/']",
+        "a[href$='.pdf']",
+        "a[href*='x.test']",
+        "p[lang|=en] a",
+        "li:first-child",
+        "li:last-child",
+        "li:nth-child(2n+1)",
+        "li:nth-child(-n+2)",
+        "li:nth-last-child(1)",
+        "li:nth-of-type(3)",
+        "li:not(.item)",
+        "li:is(.first, .sale)",
+        "li.first + li",
+        "li.first ~ li:not(.sale)",
+        "div:has(> span.label) b",
+        "ul:has(li.sale) li.first",
+        "span:has(+ b)",
+        "div.row:has-text('Price') b",
+        "span:text-equals('Mileage') + b",
+        "span:text-starts('Mil')",
+        "span:text-ends('age')",
+        "li.first, a",
+    ] {
+        assert_same(css);
+    }
+}
+
+#[test]
+fn css_to_xpath_quotes_literals() {
+    assert_eq!(
+        css_to_xpath("[title=\"it's\"]").unwrap(),
+        "//*[@title = \"it's\"]"
+    );
+    assert_eq!(
+        css_to_xpath(r#"[title='say "it\'s"']"#).unwrap(),
+        r#"//*[@title = concat('say "it', "'", 's"')]"#
+    );
+}
+
+#[test]
+fn css_to_xpath_rejects_untranslatable() {
+    assert!(css_to_xpath("a::before").is_err());
+    assert!(css_to_xpath(":first-of-type").is_err());
+    assert!(css_to_xpath("li:hover").is_err());
+    assert!(css_to_xpath("> li").is_err());
+    assert!(css_to_xpath("").is_err());
+}
+
+#[test]
+fn xpath_to_css_round_trips() {
+    let cs = make_cs();
+    for css in [
+        "#menu > li.item",
+        "li.first + li",
+        "a[href^='/']",
+        "li:nth-of-type(3)",
+        "li:last-child",
+        "li:nth-last-child(2)",
+    ] {
+        let xpath = css_to_xpath(css).unwrap();
+        let back = xpath_to_css(&xpath).unwrap();
+        assert_eq!(
+            cs.query(-1, &format!("css:{}", back)),
+            cs.query(-1, &format!("css:{}", css)),
+            "{} => {} => {}",
+            css,
+            xpath,
+            back
+        );
+    }
+}
+
+#[test]
+fn xpath_to_css_hand_written() {
+    assert_eq!(xpath_to_css("/html/body").unwrap(), "html:root > body");
+    assert_eq!(
+        xpath_to_css("//ul/li[last()]").unwrap(),
+        "ul > li:last-of-type"
+    );
+    assert_eq!(xpath_to_css("//*[@data-sku]").unwrap(), "[data-sku]");
+    assert_eq!(
+        xpath_to_css("//li[@class='item' and not(@data-sku)] | //a").unwrap(),
+        "li[class=\"item\"]:not([data-sku]), a"
+    );
+    assert_eq!(
+        xpath_to_css("//span[normalize-space() = 'Price']/following-sibling::b").unwrap(),
+        "span:text-equals('Price') ~ b"
+    );
+}
+
+#[test]
+fn xpath_to_css_rejects_untranslatable() {
+    assert!(xpath_to_css("//li/text()").is_err());
+    assert!(xpath_to_css("//a/@href").is_err());
+    assert!(xpath_to_css("//li[@class='a'][2]").is_err());
+    assert!(xpath_to_css("count(//li)").is_err());
+    assert!(xpath_to_css("//li/ancestor::ul").is_err());
+}
+
+#[test]
+fn css_queries_run_on_xpath() {
+    let mut cs = ChadSelect::builder()
+        .translation(Translation::CssToXPath)
+        .build();
+    cs.add_html(HTML.to_string());
+
+    let r = cs.query_detailed(-1, "css:li.item >> uppercase()");
+    assert_eq!(r.results, vec!["ONE", "TWO", "THREE"]);
+    assert_eq!(r.engine(), Some("xpath"));
+
+    let r = cs.query_detailed(-1, "css:p a >> get-attr('href') >> first()");
+    assert_eq!(r.results, vec!["/a.pdf"]);
+    assert_eq!(r.engine(), Some("xpath"));
+}
+
+#[test]
+fn xpath_queries_run_on_css() {
+    let mut cs = ChadSelect::builder()
+        .translation(Translation::XPathToCss)
+        .build();
+    cs.add_html(HTML.to_string());
+
+    let r = cs.query_detailed(-1, "xpath://ul/li[2]");
+    assert_eq!(r.results, vec!["Two"]);
+    assert_eq!(r.engine(), Some("css"));
+
+    let r = cs.query_detailed(-1, "xpath://p/a/@href >> last()");
+    assert_eq!(r.results, vec!["https://x.test/b"]);
+    assert_eq!(r.engine(), Some("css"));
+}
+
+#[test]
+fn untranslatable_queries_keep_their_engine() {
+    let mut cs = ChadSelect::builder()
+        .translation(Translation::CssToXPath)
+        .build();
+    cs.add_html(HTML.to_string());
+
+    let r = cs.query_detailed(-1, "css:li.sale >> get-attrs('class', 'data-sku')");
+    assert_eq!(r.engine(), Some("css"));
+    assert_eq!(r.results.len(), 1);
+
+    let mut cs = ChadSelect::builder()
+        .translation(Translation::XPathToCss)
+        .build();
+    cs.add_html(HTML.to_string());
+    let r = cs.query_detailed(-1, "xpath:count(//li)");
+    assert_eq!(r.results, vec!["4"]);
+    assert_eq!(r.engine(), Some("xpath"));
+}
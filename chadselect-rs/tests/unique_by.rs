@@ -0,0 +1,54 @@
+//! Tests for the `unique-by(key)` pipe function.
+#![cfg(feature = "regex")]
+
+use chadselect::functions::try_parse_text_functions;
+use chadselect::ChadSelect;
+
+fn text(content: &str) -> ChadSelect {
+    let mut cs = ChadSelect::new();
+    cs.add_text(content.to_string());
+    cs
+}
+
+#[test]
+fn function_keys_keep_the_first_value_as_written() {
+    let cs = text("Red\nred\n RED \nBlue\nblue");
+    assert_eq!(
+        cs.query(-1, "regex:(?m)^.+$ >> unique-by(lowercase)"),
+        vec!["Red", " RED ", "Blue"]
+    );
+    assert_eq!(
+        cs.query(-1, "regex:(?m)^.+$ >> trim() >> unique-by('lowercase()')"),
+        vec!["Red", "Blue"]
+    );
+    assert_eq!(
+        cs.query(-1, "regex:(?m)^.+$ >> unique-by(substring(0, 1))"),
+        vec!["Red", "red", " RED ", "Blue", "blue"]
+    );
+}
+
+#[test]
+fn regex_keys_use_the_first_capture_group() {
+    let cs = text("Stock #1042 (new)\nstock no. 1042\nStock #77, used\nNo stock number");
+    assert_eq!(
+        cs.query(-1, r"regex:(?m)^.+$ >> unique-by(regex:'(\d+)')"),
+        vec!["Stock #1042 (new)", "Stock #77, used", "No stock number"]
+    );
+
+    // Values the regex misses are keyed by themselves.
+    let cs = text("#7 new\n#7 used\nno number\nno number");
+    assert_eq!(
+        cs.query(-1, r"regex:(?m)^.+$ >> unique-by(regex:'#\d+')"),
+        vec!["#7 new", "no number"]
+    );
+}
+
+#[test]
+fn keys_must_map_values() {
+    assert!(try_parse_text_functions("unique-by(trim)").is_ok());
+    assert!(try_parse_text_functions(r"unique-by(regex:'\d{1,3}, ')").is_ok());
+    assert!(try_parse_text_functions("unique-by()").is_err());
+    assert!(try_parse_text_functions("unique-by(first)").is_err());
+    assert!(try_parse_text_functions("unique-by(nope)").is_err());
+    assert!(try_parse_text_functions("unique-by(regex:'(')").is_err());
+}
@@ -0,0 +1,220 @@
+//! Tests for `add_warc` (the `warc` feature).
+#![cfg(all(feature = "warc", feature = "css", feature = "json", feature = "regex"))]
+
+use chadselect::{ChadSelect, LimitPolicy, WarcFilter};
+
+fn record(warc_type: &str, url: &str, content_type: &str, block: &str) -> String {
+    format!(
+        "WARC/1.0\r\nWARC-Type: {warc_type}\r\nWARC-Target-URI: {url}\r\n\
+         WARC-Record-ID: <urn:uuid:0>\r\nContent-Type: {content_type}\r\n\
+         Content-Length: {}\r\n\r\n{block}\r\n\r\n",
+        block.len()
+    )
+}
+
+fn response(url: &str, status: &str, headers: &str, body: &str) -> String {
+    let http = format!("HTTP/1.1 {status}\r\n{headers}\r\n{body}");
+    record("response", url, "application/http; msgtype=response", &http)
+}
+
+fn crawl() -> String {
+    [
+        record(
+            "warcinfo",
+            "",
+            "application/warc-fields",
+            "software: test\r\n",
+        ),
+        record(
+            "request",
+            "https://example.com/cars/1",
+            "application/http",
+            "GET / HTTP/1.1\r\n\r\n",
+        ),
+        response(
+            "https://example.com/cars/1",
+            "200 OK",
+            "Content-Type: text/html\r\n",
+            "<h1>Civic</h1>",
+        ),
+        response(
+            "https://example.com/api/2",
+            "200 OK",
+            "Content-Type: application/json\r\n",
+            r#"{"price": 28500}"#,
+        ),
+        response(
+            "https://example.com/gone",
+            "404 Not Found",
+            "Content-Type: text/html\r\n",
+            "<h1>404</h1>",
+        ),
+        response(
+            "https://example.com/logo.png",
+            "200 OK",
+            "Content-Type: image/png\r\n",
+            "PNG",
+        ),
+        response(
+            "https://example.com/moved",
+            "301 Moved",
+            "Location: /cars/1\r\n",
+            "",
+        ),
+        response(
+            "https://other.org/cars/3",
+            "200 OK",
+            "Content-Type: text/html\r\n",
+            "<h1>Accord</h1>",
+        ),
+    ]
+    .concat()
+}
+
+#[test]
+fn successful_textual_responses_are_loaded() {
+    let mut cs = ChadSelect::new();
+
+    assert_eq!(cs.add_warc(crawl().as_bytes()), Ok(3));
+    assert_eq!(cs.content_source(0), Some("https://example.com/cars/1"));
+    #[cfg(feature = "info")]
+    assert_eq!(
+        cs.content_info(1).unwrap().content_type,
+        chadselect::ContentType::Json
+    );
+    assert_eq!(cs.query(-1, "css:h1"), vec!["Civic", "Accord"]);
+    assert_eq!(cs.select(0, "json:price"), "28500");
+}
+
+#[test]
+fn filters_by_url_pattern_and_mime_type() {
+    let mut cs = ChadSelect::new();
+    let filter = WarcFilter {
+        url_patterns: vec!["https://example.com/*".to_string()],
+        mime_types: vec!["TEXT/HTML".to_string()],
+        ..WarcFilter::default()
+    };
+
+    assert_eq!(cs.add_warc_with(crawl().as_bytes(), &filter), Ok(1));
+    assert_eq!(cs.select(0, "css:h1"), "Civic");
+}
+
+#[test]
+fn max_items_stops_reading() {
+    let mut cs = ChadSelect::new();
+    let filter = WarcFilter {
+        max_items: Some(2),
+        ..WarcFilter::default()
+    };
+
+    assert_eq!(cs.add_warc_with(crawl().as_bytes(), &filter), Ok(2));
+    assert_eq!(cs.content_count(), 2);
+}
+
+#[test]
+fn chunked_bodies_are_reassembled_and_resources_loaded() {
+    let mut cs = ChadSelect::new();
+    let warc = [
+        response(
+            "https://example.com/chunked",
+            "200 OK",
+            "Content-Type: text/html\r\nTransfer-Encoding: chunked\r\n",
+            "7\r\n<h1>CR-\r\n6\r\nV</h1>\r\n0\r\n\r\n",
+        ),
+        record(
+            "resource",
+            "https://example.com/notes.txt",
+            "text/plain",
+            "VIN 1HGFE2F59PA000001",
+        ),
+    ]
+    .concat();
+
+    assert_eq!(cs.add_warc(warc.as_bytes()), Ok(2));
+    assert_eq!(cs.select(0, "css:h1"), "CR-V");
+    assert_eq!(cs.select(0, r"regex:VIN (\w+)"), "1HGFE2F59PA000001");
+}
+
+#[test]
+fn content_encoded_bodies_are_skipped_with_a_warning() {
+    let mut cs = ChadSelect::new();
+    let warc = response(
+        "https://example.com/gz",
+        "200 OK",
+        "Content-Type: text/html\r\nContent-Encoding: gzip\r\n",
+        "\u{1f}binary",
+    );
+
+    assert_eq!(cs.add_warc(warc.as_bytes()), Ok(0));
+    assert!(cs
+        .take_warnings()
+        .iter()
+        .any(|w| w.message.contains("content-encoded")));
+}
+
+#[test]
+fn malformed_input_is_an_error_after_the_good_records() {
+    let mut cs = ChadSelect::new();
+    let mut warc = response(
+        "https://example.com/a",
+        "200 OK",
+        "Content-Type: text/html\r\n",
+        "<p>a</p>",
+    );
+    warc.push_str("garbage that is not a record\r\n");
+
+    assert!(cs.add_warc(warc.as_bytes()).is_err());
+    assert_eq!(cs.content_count(), 1);
+
+    let filter = WarcFilter {
+        url_patterns: vec!["[".to_string()],
+        ..WarcFilter::default()
+    };
+    assert!(cs.add_warc_with("".as_bytes(), &filter).is_err());
+}
+
+#[test]
+fn declared_lengths_are_not_trusted() {
+    let mut cs = ChadSelect::new();
+    let warc = "WARC/1.0\r\nWARC-Type: resource\r\nWARC-Target-URI: https://example.com/\r\n\
+                Content-Type: text/plain\r\nContent-Length: 99999999999999\r\n\r\nshort";
+    let err = cs.add_warc(warc.as_bytes()).unwrap_err();
+    assert!(err.contains("truncated"), "{}", err);
+    assert_eq!(cs.content_count(), 0);
+}
+
+#[test]
+fn records_over_the_item_limit_are_not_read() {
+    let big = "x".repeat(500);
+    let warc = [
+        response(
+            "https://example.com/big",
+            "200 OK",
+            "Content-Type: text/plain\r\n",
+            &big,
+        ),
+        response(
+            "https://example.com/small",
+            "200 OK",
+            "Content-Type: text/plain\r\n",
+            "small",
+        ),
+    ]
+    .concat();
+
+    let mut cs = ChadSelect::builder().max_item_bytes(100).build();
+    assert_eq!(cs.add_warc(warc.as_bytes()), Ok(1));
+    assert_eq!(cs.content_source(0), Some("https://example.com/small"));
+    assert!(cs
+        .take_warnings()
+        .iter()
+        .any(|w| w.message.contains("https://example.com/big")));
+
+    let mut cs = ChadSelect::builder()
+        .max_item_bytes(100)
+        .limit_policy(LimitPolicy::Truncate)
+        .build();
+    assert_eq!(cs.add_warc(warc.as_bytes()), Ok(2));
+    assert!(cs.content_bytes() <= 100 + "small".len());
+    assert!(cs.select(0, "regex:x+").starts_with("xxx"));
+}
@@ -0,0 +1,119 @@
+//! Tests for the structured warning channel.
+#![cfg(all(feature = "xpath", feature = "json"))]
+
+use chadselect::{ChadSelect, ContentType, WarningKind};
+
+fn kinds(cs: &ChadSelect) -> Vec<WarningKind> {
+    cs.take_warnings().iter().map(|w| w.kind).collect()
+}
+
+#[test]
+fn detailed_result_carries_its_warnings() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>a</p>".to_string());
+
+    let r = cs.query_detailed(-1, "css:p[ || css:p");
+    assert_eq!(r.results, vec!["a"]);
+    assert_eq!(r.warnings.len(), 1);
+    assert_eq!(r.warnings[0].kind, WarningKind::InvalidQuery);
+    assert!(r.warnings[0].message.contains("p["));
+
+    let r = cs.query_detailed(-1, "css:p");
+    assert!(r.warnings.is_empty());
+}
+
+#[test]
+fn buffer_collects_across_calls_and_drains() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>a</p>".to_string());
+
+    cs.query(-1, "css:p[");
+    cs.select(5, "css:p");
+    cs.query_detailed(-1, "xpath://p[");
+    assert_eq!(
+        kinds(&cs),
+        [
+            WarningKind::InvalidQuery,
+            WarningKind::IndexOutOfRange,
+            WarningKind::InvalidQuery,
+        ]
+    );
+    assert!(cs.take_warnings().is_empty());
+}
+
+#[test]
+fn invalid_content_is_reported() {
+    let mut cs = ChadSelect::new();
+    cs.add_json("{broken".to_string());
+    let r = cs.query_detailed(-1, "json:a");
+    assert!(r.results.is_empty());
+    assert_eq!(r.warnings[0].kind, WarningKind::InvalidContent);
+}
+
+#[test]
+fn rejected_content_is_reported() {
+    let mut cs = ChadSelect::builder().max_item_bytes(4).build();
+    cs.add_text("too long".to_string());
+    let added = cs.add_many(
+        vec![(ContentType::Text, "also too long".to_string())],
+        false,
+    );
+    assert_eq!(added, 0);
+    assert_eq!(
+        kinds(&cs),
+        [WarningKind::ContentRejected, WarningKind::ContentRejected]
+    );
+
+    // `try_add` returns the error instead.
+    assert!(cs
+        .try_add("still too long".to_string(), ContentType::Text)
+        .is_err());
+    assert!(cs.take_warnings().is_empty());
+}
+
+#[test]
+fn warming_and_cache_control_report() {
+    let mut cs = ChadSelect::new();
+    cs.add_json("[1,".to_string());
+    cs.warm();
+    cs.drop_caches_for(7);
+    assert_eq!(
+        kinds(&cs),
+        [WarningKind::InvalidContent, WarningKind::IndexOutOfRange]
+    );
+}
+
+#[test]
+fn nested_calls_record_once() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<h1>t</h1>".to_string());
+    // `render` runs `select` per placeholder.
+    assert_eq!(cs.render("{css:h1[} {css:h1}"), " t");
+    assert_eq!(kinds(&cs), [WarningKind::InvalidQuery]);
+}
+
+#[test]
+fn instances_keep_their_own_warnings() {
+    let mut a = ChadSelect::new();
+    let mut b = ChadSelect::new();
+    a.add_html("<p>a</p>".to_string());
+    b.add_html("<p>b</p>".to_string());
+
+    a.query(-1, "css:p[");
+    b.query(-1, "css:p");
+    assert!(b.take_warnings().is_empty());
+    assert_eq!(a.take_warnings().len(), 1);
+}
+
+#[test]
+fn buffer_is_bounded() {
+    let mut cs = ChadSelect::new();
+    cs.add_html("<p>a</p>".to_string());
+    for i in 0..1_500 {
+        cs.select(10 + i, "css:p");
+    }
+    let warnings = cs.take_warnings();
+    assert_eq!(warnings.len(), 1024);
+    // The oldest were dropped.
+    assert!(warnings.last().unwrap().message.contains("1509"));
+}
@@ -0,0 +1,132 @@
+#![cfg(all(feature = "xpath", feature = "json", feature = "regex"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use chadselect::{ChadSelect, WatchEvent};
+
+/// A callback that records every event, and the events it recorded.
+fn recorder() -> (Rc<RefCell<Vec<WatchEvent>>>, impl FnMut(&WatchEvent)) {
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&events);
+    (events, move |event: &WatchEvent| {
+        sink.borrow_mut().push(event.clone())
+    })
+}
+
+#[test]
+fn added_items_deliver_their_matches() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(r#"<span class="price">$1</span>"#.to_string());
+
+    let (events, callback) = recorder();
+    let id = cs.watch("css:.price", callback).unwrap();
+    assert!(events.borrow().is_empty(), "existing content is not run");
+
+    cs.add_json(r#"{"price": 5}"#.to_string());
+    cs.add_html(r#"<span class="price">$2</span><span class="price">$3</span>"#.to_string());
+
+    let events = events.borrow();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].id, id);
+    assert_eq!(events[0].content_index, 2);
+    assert_eq!(events[0].results, ["$2", "$3"]);
+}
+
+#[test]
+fn appends_deliver_only_new_matches() {
+    let mut cs = ChadSelect::new();
+    let (errors, on_error) = recorder();
+    let (failed, on_failed) = recorder();
+    cs.watch("regex:ERROR (.+)", on_error).unwrap();
+    cs.watch("json:[?status=='failed'].id", on_failed).unwrap();
+
+    cs.add_text("INFO boot\nERROR disk full\n".to_string());
+    cs.append_text(0, "INFO ok\n").unwrap();
+    cs.append_text(0, "ERROR retry\nERROR gave up\n").unwrap();
+
+    cs.add_json("[]".to_string());
+    cs.append_jsonl(1, r#"{"id": 1, "status": "ok"}"#).unwrap();
+    cs.append_jsonl(1, r#"{"id": 2, "status": "failed"}"#)
+        .unwrap();
+
+    let errors: Vec<_> = errors.borrow().iter().map(|e| e.results.clone()).collect();
+    assert_eq!(errors, [vec!["disk full"], vec!["retry", "gave up"]]);
+    let failed = failed.borrow();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].content_index, 1);
+    assert_eq!(failed[0].results, ["2"]);
+}
+
+#[test]
+fn fragments_and_fallbacks() {
+    let mut cs = ChadSelect::new();
+    let (events, callback) = recorder();
+    cs.watch("xpath:/li/text() || css:.none", callback).unwrap();
+
+    cs.add_html_fragment("<li>Civic</li><li>Accord</li>".to_string());
+    assert_eq!(events.borrow()[0].results, ["Civic", "Accord"]);
+}
+
+#[test]
+fn unwatch_and_clone_stop_callbacks() {
+    let mut cs = ChadSelect::new();
+    let (events, callback) = recorder();
+    let id = cs.watch("regex:\\d+", callback).unwrap();
+    assert!(cs.watch("css:p[", |_| {}).is_err());
+
+    let mut copy = cs.clone();
+    copy.add_text("42".to_string());
+    assert!(events.borrow().is_empty());
+
+    assert!(cs.unwatch(id));
+    assert!(!cs.unwatch(id));
+    cs.add_text("42".to_string());
+    assert!(events.borrow().is_empty());
+}
+
+#[test]
+fn appends_search_only_the_new_text() {
+    let mut cs = ChadSelect::new();
+    let (events, callback) = recorder();
+    cs.watch("regex:ERROR [a-z ]+", callback).unwrap();
+
+    cs.add_text("INFO boot\nERROR disk".to_string());
+    cs.append_text(0, " full\nINFO ok\n").unwrap();
+    cs.append_text(0, "INFO still ok\n").unwrap();
+
+    let results: Vec<_> = events.borrow().iter().map(|e| e.results.clone()).collect();
+    assert_eq!(results, [vec!["ERROR disk"], vec!["ERROR disk full"]]);
+}
+
+#[test]
+fn appends_deliver_what_the_query_gains() {
+    let cases = [
+        // `^` is the start of the item, not of the appended text.
+        ("regex:^(\\w+ \\w+)", "Header v1\nfoo", " bar\n"),
+        ("regexm:^(\\w+ \\w+)", "Header v1\nfoo", " bar\n"),
+        ("regex:\\bbar\\w*", "foobar", "bar baz\n"),
+        // A match that can span lines starts before the appended line.
+        ("regex:(?s)BEGIN(.*?)END", "BEGIN one\ntwo", "\nEND\n"),
+        ("regex:ERROR\\s+(\\w+)", "ERROR\n", "disk\n"),
+    ];
+
+    for (query, text, appended) in cases {
+        let mut cs = ChadSelect::new();
+        let (events, callback) = recorder();
+        cs.watch(query, callback).unwrap();
+        cs.add_text(text.to_string());
+        let mut gained = cs.query(-1, query);
+        let seen = events.borrow().len();
+        cs.append_text(0, appended).unwrap();
+        for before in std::mem::replace(&mut gained, cs.query(-1, query)) {
+            if let Some(i) = gained.iter().position(|r| *r == before) {
+                gained.remove(i);
+            }
+        }
+
+        let events = events.borrow();
+        let delivered = events.get(seen).map_or(vec![], |e| e.results.clone());
+        assert_eq!(delivered, gained, "{}", query);
+    }
+}
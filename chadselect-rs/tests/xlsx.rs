@@ -0,0 +1,288 @@
+//! Tests for spreadsheet loading (`add_xlsx`, the `xlsx` feature).
+#![cfg(all(feature = "xlsx", feature = "json"))]
+
+use chadselect::ChadSelect;
+
+/// A zip archive of `members`, each stored (method 0) or, when given as
+/// already-deflated data, deflated (method 8). CRCs are left zero: the
+/// reader does not check them.
+fn zip(members: &[(&str, &[u8], u16)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data, method) in members {
+        let offset = out.len() as u32;
+        let sizes = |buf: &mut Vec<u8>| {
+            buf.extend_from_slice(&0u32.to_le_bytes()); // crc
+            buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(data.len() as u32 * 4).to_le_bytes());
+            buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&0u16.to_le_bytes()); // extra
+        };
+        out.extend_from_slice(b"PK\x03\x04\x14\x00\x00\x00");
+        out.extend_from_slice(&method.to_le_bytes());
+        out.extend_from_slice(&[0; 4]); // time, date
+        sizes(&mut out);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        directory.extend_from_slice(b"PK\x01\x02\x14\x00\x14\x00\x00\x00");
+        directory.extend_from_slice(&method.to_le_bytes());
+        directory.extend_from_slice(&[0; 4]);
+        sizes(&mut directory);
+        directory.extend_from_slice(&[0; 10]); // comment, disk, attributes
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let directory_offset = out.len() as u32;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(b"PK\x05\x06\x00\x00\x00\x00");
+    out.extend_from_slice(&(members.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(members.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&[0; 2]);
+    out
+}
+
+const WORKBOOK: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+  xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <sheets>
+    <sheet name="Prices" sheetId="1" r:id="rId2"/>
+    <sheet name="Notes" sheetId="2" r:id="rId1"/>
+  </sheets>
+</workbook>"#;
+
+const RELS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet2.xml"/>
+  <Relationship Id="rId2" Type="worksheet" Target="/xl/worksheets/sheet1.xml"/>
+  <Relationship Id="rId3" Type="sharedStrings" Target="sharedStrings.xml"/>
+</Relationships>"#;
+
+const SHARED: &str = r#"<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <si><t>SKU</t></si>
+  <si><t>Price</t></si>
+  <si><r><t>Civic </t></r><r><rPr><b/></rPr><t>LX</t></r></si>
+  <si><t>In stock</t></si>
+</sst>"#;
+
+const PRICES: &str = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <sheetData>
+    <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c><c r="D1" t="s"><v>3</v></c></row>
+    <row r="2"><c r="A2" t="s"><v>2</v></c><c r="B2"><v>28500</v></c><c r="C2"><v>0.5</v></c><c r="D2" t="b"><v>1</v></c></row>
+    <row r="4"><c r="A4" t="inlineStr"><is><t>Accord</t></is></c><c r="B4"><f>B2*1.2</f><v>34200</v></c><c r="D4" t="b"><v>0</v></c></row>
+    <row r="5"><c r="A5" t="str"><f>"CR-"&amp;"V"</f><v>CR-V</v></c><c r="B5"/></row>
+  </sheetData>
+</worksheet>"#;
+
+const NOTES: &str = r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">
+  <sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>Prices exclude tax</t></is></c></row></sheetData>
+</worksheet>"#;
+
+fn workbook() -> Vec<u8> {
+    zip(&[
+        ("[Content_Types].xml", b"<Types/>", 0),
+        ("xl/workbook.xml", WORKBOOK.as_bytes(), 0),
+        ("xl/_rels/workbook.xml.rels", RELS.as_bytes(), 0),
+        ("xl/sharedStrings.xml", SHARED.as_bytes(), 0),
+        ("xl/worksheets/sheet1.xml", PRICES.as_bytes(), 0),
+        ("xl/worksheets/sheet2.xml", NOTES.as_bytes(), 0),
+    ])
+}
+
+// ─── XLSX ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn one_json_item_per_sheet_in_workbook_order() {
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_xlsx(&workbook()), Ok(2));
+    assert_eq!(cs.content_sheet(0), Some("Prices"));
+    assert_eq!(cs.content_sheet(1), Some("Notes"));
+    assert_eq!(cs.query(-1, "json:sheet"), vec!["Prices", "Notes"]);
+
+    #[cfg(feature = "snapshot")]
+    {
+        let restored = ChadSelect::from_snapshot(&cs.snapshot());
+        assert_eq!(restored.content_sheet(1), Some("Notes"));
+    }
+}
+
+#[test]
+fn rows_keep_positions_and_cell_types() {
+    let mut cs = ChadSelect::new();
+    cs.add_xlsx(&workbook()).unwrap();
+
+    // Row 3 is missing, so it is empty; C1 is blank, so null.
+    assert_eq!(cs.select(0, "json:length(rows)"), "5");
+    assert_eq!(
+        cs.select(0, "json:to_string(rows[0])"),
+        r#"["SKU","Price",null,"In stock"]"#
+    );
+    assert_eq!(
+        cs.select(0, "json:to_string(rows[1])"),
+        r#"["Civic LX",28500,0.5,true]"#
+    );
+    assert_eq!(cs.select(0, "json:to_string(rows[2])"), "[]");
+    // A formula gives its cached value; an empty cell is dropped.
+    assert_eq!(cs.select(0, "json:rows[3][1]"), "34200");
+    assert_eq!(cs.select(0, "json:to_string(rows[4])"), r#"["CR-V"]"#);
+}
+
+#[test]
+fn records_are_keyed_by_the_header_row() {
+    let mut cs = ChadSelect::new();
+    cs.add_xlsx(&workbook()).unwrap();
+
+    assert_eq!(cs.select(0, "json:records[0].Price"), "28500");
+    // A blank header falls back to the column letter.
+    assert_eq!(cs.select(0, "json:records[0].C"), "0.5");
+    assert_eq!(
+        cs.query(-1, "json:records[?Price > `30000`].SKU"),
+        vec!["Accord"]
+    );
+    assert_eq!(
+        cs.query(-1, r#"json:records[?"In stock"].SKU"#),
+        vec!["Civic LX"]
+    );
+    // Empty rows make no record.
+    assert_eq!(cs.select(0, "json:length(records)"), "3");
+}
+
+#[test]
+fn deflated_members_are_decompressed() {
+    let sheet: &[u8] =
+        b"\x7d\xd6\xc1\xaa\x9b\x50\x10\xc6\xf1\x7d\x9f\x42\xdc\xdf\x1c\xbf\xcf\x68\x92\x62\
+    \xbc\xb4\x94\xae\x0b\x6d\x1f\x40\x12\x7b\x23\x4d\x34\xa8\xe4\xb6\x6f\x5f\xe3\x09\
+    \x9e\x40\x67\x66\xa7\x23\xe7\x8f\x9b\x1f\x73\x8a\xd7\x3f\x97\x73\x74\xab\xfb\xa1\
+    \xe9\xda\x7d\x8c\x55\x12\x47\x75\x7b\xe8\x8e\x4d\xfb\xb6\x8f\x7f\xfe\xf8\xfa\xb2\
+    \x8d\xa3\x61\xac\xda\x63\x75\xee\xda\x7a\x1f\xff\xad\x87\xf8\xb5\xfc\x50\xbc\x77\
+    \xfd\xef\xe1\x54\xd7\x63\x34\x05\xda\x61\x1f\x9f\xc6\xf1\xfa\xd1\xb9\xe1\x70\xaa\
+    \x2f\xd5\xb0\xea\xae\x75\x3b\x7d\xf9\xd5\xf5\x97\x6a\x9c\x5e\xfb\x37\x37\x5c\xfb\
+    \xba\x3a\xce\x87\x2e\x67\xc7\x24\xc9\xdd\xa5\x6a\xda\xb8\x2c\xe6\xd9\x97\x6a\xac\
+    \xca\xa2\xef\xde\xa3\x7e\xfa\x91\x69\x7a\xb8\x3f\x7c\x42\x1c\x8d\xfb\xb8\x69\xcf\
+    \x4d\x5b\x7f\x1f\xfb\x69\xde\x0c\x65\x31\x96\xdf\x5e\x92\x04\x85\x1b\xcb\xc2\xdd\
+    \x07\xee\xf0\x38\xf0\xf9\x7e\xf4\x56\x62\x95\x15\xee\xe6\xe7\x6e\x8a\x2e\x65\x2e\
+    \x65\x1a\x65\x4a\x65\xce\x65\x6a\xe5\x74\x29\xa7\x46\x39\x95\xca\xe9\x5c\x4e\xb5\
+    \xf2\x7a\x29\xaf\x8d\xf2\x5a\x2a\xaf\xe7\xf2\x5a\x2b\x67\x4b\x39\x33\xca\x99\x54\
+    \xce\xe6\x72\xa6\x95\xf3\xa5\x9c\x1b\xe5\x5c\x2a\xe7\x73\x39\xd7\xca\x9b\xa5\xbc\
+    \x31\xca\x1b\xa9\xbc\x99\xcb\x1b\xad\xbc\x5d\xca\x5b\xa3\xbc\x95\xca\xdb\xb9\xbc\
+    \xd5\xca\xbb\xa5\xbc\x33\xca\x3b\xa9\xbc\x9b\xcb\x3b\xad\x8c\x24\x50\x49\xf4\x36\
+    \x12\xd1\x4a\xe2\xb1\x24\x6a\xfd\x09\xa2\x21\x11\xb2\xc4\x07\x45\xd5\x22\x02\x46\
+    \x18\x1a\x21\x6a\x84\xe7\x08\xd5\x23\x02\x48\x18\x22\x21\x8a\x84\x27\x09\xd5\x24\
+    \x02\x4a\x18\x2a\x21\xaa\x84\x67\x09\xd5\x25\x02\x4c\x18\x32\x21\xca\x84\xa7\x09\
+    \xd5\x26\x02\x4e\x18\x3a\x21\xea\x84\xe7\x09\xd5\x27\x02\x50\x18\x42\x21\x0a\x85\
+    \x27\x0a\xd5\x28\x02\x52\x18\x4a\x21\x2a\x85\x67\x0a\xd5\x29\x02\x54\x18\x52\x21\
+    \x4a\x85\xa7\x0a\xd5\x2a\x83\x55\x1a\x56\x29\x5a\xa5\xb7\x4a\xd5\x2a\x83\x55\x1a\
+    \x56\x29\x5a\xa5\xb7\x4a\x7d\x6f\x3e\x2d\x4e\xc3\x2a\xe5\xcd\xf9\x58\x9d\xaa\x55\
+    \x06\xab\x34\xac\x52\xb4\x4a\x6f\x95\xaa\x55\x06\xab\x34\xac\x52\xb4\x4a\x6f\x95\
+    \xaa\x55\x06\xab\x34\xac\x52\xb4\x4a\x6f\x95\xaa\x55\x06\xab\x34\xac\x52\xb4\x4a\
+    \x6f\x95\xaa\x55\x06\xab\x34\xac\x52\xb4\x4a\x6f\x95\xaa\x55\x06\xab\x34\xac\x52\
+    \xb4\x4a\x6f\x95\xaa\x55\x06\xab\x34\xac\x52\xb4\x4a\x6f\x95\xaa\xd5\x34\x58\x4d\
+    \x0d\xab\xa9\x68\x35\xf5\x56\xd3\xff\xad\xba\xa7\xbb\xae\x5b\x2e\xd1\xe5\x3f";
+    let bytes = zip(&[
+        ("xl/workbook.xml", WORKBOOK.as_bytes(), 0),
+        ("xl/_rels/workbook.xml.rels", RELS.as_bytes(), 0),
+        ("xl/worksheets/sheet1.xml", sheet, 8),
+        ("xl/worksheets/sheet2.xml", NOTES.as_bytes(), 0),
+    ]);
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_xlsx(&bytes), Ok(2));
+    assert_eq!(cs.select(0, "json:length(rows)"), "30");
+    assert_eq!(cs.select(0, "json:to_string(rows[29])"), r#"["P-030",30.5]"#);
+}
+
+#[test]
+fn unreadable_sheets_are_skipped_with_a_warning() {
+    let bytes = zip(&[
+        ("xl/workbook.xml", WORKBOOK.as_bytes(), 0),
+        ("xl/_rels/workbook.xml.rels", RELS.as_bytes(), 0),
+        ("xl/worksheets/sheet2.xml", NOTES.as_bytes(), 0),
+    ]);
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_xlsx(&bytes), Ok(1));
+    assert_eq!(cs.content_sheet(0), Some("Notes"));
+    let warnings = cs.take_warnings();
+    assert!(warnings
+        .iter()
+        .any(|w| w.message.contains("sheet1.xml is missing")));
+}
+
+// ─── ODS ────────────────────────────────────────────────────────────────────
+
+const ODS_CONTENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content
+  xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0"
+  xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0"
+  xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+ <office:body><office:spreadsheet>
+  <table:table table:name="Stock">
+   <table:table-column table:number-columns-repeated="4"/>
+   <table:table-row>
+    <table:table-cell office:value-type="string"><text:p>Model</text:p></table:table-cell>
+    <table:table-cell office:value-type="string"><text:p>Price</text:p></table:table-cell>
+    <table:table-cell office:value-type="string"><text:p>Since</text:p></table:table-cell>
+   </table:table-row>
+   <table:table-row>
+    <table:table-cell office:value-type="string"><text:p>Civic<text:s text:c="2"/>LX</text:p></table:table-cell>
+    <table:table-cell office:value-type="currency" office:value="28500"><text:p>$28,500</text:p></table:table-cell>
+    <table:table-cell office:value-type="date" office:date-value="2024-03-01"><text:p>03/01/24</text:p></table:table-cell>
+    <table:table-cell table:number-columns-repeated="1020"/>
+   </table:table-row>
+   <table:table-row table:number-rows-repeated="2">
+    <table:table-cell/>
+    <table:table-cell office:value-type="float" office:value="0.25" table:number-columns-repeated="2"><text:p>0.25</text:p></table:table-cell>
+   </table:table-row>
+   <table:table-row table:number-rows-repeated="1048570"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+  </table:table>
+  <table:table table:name="Empty"/>
+ </office:spreadsheet></office:body>
+</office:document-content>"#;
+
+#[test]
+fn ods_sheets_expand_repeats_and_drop_trailing_blanks() {
+    let bytes = zip(&[
+        (
+            "mimetype",
+            b"application/vnd.oasis.opendocument.spreadsheet",
+            0,
+        ),
+        ("content.xml", ODS_CONTENT.as_bytes(), 0),
+    ]);
+    let mut cs = ChadSelect::new();
+    assert_eq!(cs.add_xlsx(&bytes), Ok(2));
+    assert_eq!(cs.content_sheet(0), Some("Stock"));
+
+    assert_eq!(cs.select(0, "json:length(rows)"), "4");
+    assert_eq!(
+        cs.select(0, "json:to_string(rows[1])"),
+        r#"["Civic  LX",28500,"2024-03-01"]"#
+    );
+    assert_eq!(cs.select(0, "json:to_string(rows[3])"), "[null,0.25,0.25]");
+    assert_eq!(cs.select(0, "json:records[0].Since"), "2024-03-01");
+    assert_eq!(
+        cs.query(-1, "json:records[*].Price"),
+        vec!["28500", "0.25", "0.25"]
+    );
+    assert_eq!(cs.select(1, "json:to_string(rows)"), "[]");
+}
+
+#[test]
+fn rejects_what_is_not_a_workbook() {
+    let mut cs = ChadSelect::new();
+    assert_eq!(
+        cs.add_xlsx(b"SKU,Price\nA-1,12.5\n").unwrap_err(),
+        "not a zip archive"
+    );
+    let other = zip(&[("readme.txt", b"hello", 0)]);
+    assert_eq!(
+        cs.add_xlsx(&other).unwrap_err(),
+        "not an XLSX or ODS workbook"
+    );
+    let unsupported = zip(&[("xl/workbook.xml", WORKBOOK.as_bytes(), 12)]);
+    assert!(cs
+        .add_xlsx(&unsupported)
+        .unwrap_err()
+        .contains("compression method 12"));
+    assert_eq!(cs.content_count(), 0);
+}
@@ -3,6 +3,7 @@
 //! pseudo + exact-label→sibling (fixed). C: xrust per-step positional on
 //! non-child axes is still broken upstream; the parenthesized `(...)[N]`
 //! workaround is asserted so we notice if either changes.
+#![cfg(feature = "xpath")]
 use chadselect::ChadSelect;
 
 fn doc() -> ChadSelect {
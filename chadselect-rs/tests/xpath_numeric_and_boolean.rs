@@ -10,6 +10,7 @@
 //!   * top-level boolean results serialize as "true"/"false"
 //!     (`ValueData` `Display` gained its missing `Boolean` arm).
 //!   * `string-length()` implemented (`Transform::StringLength`).
+#![cfg(feature = "xpath")]
 
 use chadselect::ChadSelect;
 
@@ -20,6 +20,7 @@
 //! ```text
 //! cargo test --release --test xpath_perf_guard -- --ignored --nocapture
 //! ```
+#![cfg(feature = "xpath")]
 
 use std::time::{Duration, Instant};
 
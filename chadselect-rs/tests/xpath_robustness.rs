@@ -9,6 +9,7 @@
 //! Companion suites:
 //!   * `xpath_perf_guard.rs`  — asserts evaluation stays ~linear (no O(n²)).
 //!   * `xpath_known_gaps.rs`  — documents known conformance gaps (#[ignore]d).
+#![cfg(feature = "xpath")]
 
 use chadselect::ChadSelect;
 
@@ -0,0 +1,151 @@
+//! Tests for the XPath 2.0 function shims (`ChadSelectBuilder::xpath_shims`).
+#![cfg(feature = "xpath")]
+
+use chadselect::ChadSelect;
+
+const PAGE: &str = r#"<ul>
+  <li><a href="/docs/Spec.PDF">Spec</a></li>
+  <li><a href="/docs/faq.html">FAQ</a></li>
+  <li><a href="https://cdn.example.com/brochure.pdf?v=2">Brochure</a></li>
+</ul>
+<p class=" price  sale ">Price: $28,500 (was $31,000)</p>
+<p class="tags">red,blue,,green</p>
+<h1>Ÿes Ünïcode ÀÉÎ</h1>"#;
+
+fn shimmed() -> ChadSelect {
+    let mut cs = ChadSelect::builder().xpath_shims(true).build();
+    cs.add_html(PAGE.to_string());
+    cs
+}
+
+#[test]
+fn matches_with_anchors_and_flags() {
+    let cs = shimmed();
+    assert_eq!(
+        cs.query(-1, r"xpath://a[matches(@href, '\.pdf$', 'i')]"),
+        vec!["Spec"]
+    );
+    assert_eq!(
+        cs.query(-1, r"xpath://a[matches(@href, '\.pdf')]"),
+        vec!["Brochure"]
+    );
+    assert_eq!(
+        cs.query(-1, "xpath://a[matches(@href, '^https://')]"),
+        vec!["Brochure"]
+    );
+    assert_eq!(
+        cs.query(-1, "xpath://a[matches(., '^faq$', 'i')]"),
+        vec!["FAQ"]
+    );
+    assert_eq!(
+        cs.query(-1, "xpath://a[matches(@href, '?v=', 'q')]"),
+        vec!["Brochure"]
+    );
+    assert_eq!(cs.select(0, "xpath:matches('abc', '')"), "true");
+}
+
+#[test]
+fn replace_every_occurrence() {
+    let cs = shimmed();
+    assert_eq!(
+        cs.select(0, r"xpath:replace(//p[1], '\$', 'USD ')"),
+        "Price: USD 28,500 (was USD 31,000)"
+    );
+    assert_eq!(
+        cs.select(0, "xpath:replace(//p[1], '^price: ', '', 'i')"),
+        "$28,500 (was $31,000)"
+    );
+    assert_eq!(
+        cs.select(0, r"xpath:replace(//p[1], '\)$', ']')"),
+        "Price: $28,500 (was $31,000]"
+    );
+    assert_eq!(cs.select(0, "xpath:replace('aaa', 'A', 'b', 'i')"), "bbb");
+    assert_eq!(
+        cs.select(0, r"xpath:replace('1.5', '.', '\$', 'q')"),
+        r"1\$5"
+    );
+    assert_eq!(cs.select(0, r"xpath:replace('1.5', '\.', '\$')"), "1$5");
+}
+
+#[test]
+fn tokenize_forms() {
+    let cs = shimmed();
+    assert_eq!(
+        cs.query(-1, "xpath:tokenize(//p[2], ',')"),
+        vec!["red", "blue", "green"] // empty tokens are dropped from results
+    );
+    assert_eq!(cs.select(0, "xpath:count(tokenize(//p[2], ','))"), "4");
+    assert_eq!(
+        cs.query(-1, "xpath:tokenize(//p[1]/@class)"),
+        vec!["price", "sale"]
+    );
+    // `\s+` keeps the empty tokens around leading/trailing whitespace.
+    assert_eq!(
+        cs.select(0, r"xpath:count(tokenize(//p[1]/@class, '\s+'))"),
+        "4"
+    );
+    assert_eq!(
+        cs.query(-1, r"xpath://p[tokenize(@class, '\s+') = 'sale']/@class"),
+        vec!["price  sale"]
+    );
+    assert_eq!(cs.select(0, "xpath:count(tokenize('', ','))"), "0");
+}
+
+#[test]
+fn case_mapping_and_ends_with() {
+    let cs = shimmed();
+    assert_eq!(cs.select(0, "xpath:lower-case(//h1)"), "Ÿes ünïcode àéî");
+    assert_eq!(cs.select(0, "xpath:upper-case('straße')"), "STRAßE");
+    assert_eq!(
+        cs.query(-1, "xpath://a[ends-with(@href, '.html')]"),
+        vec!["FAQ"]
+    );
+    assert_eq!(
+        cs.query(-1, "xpath://a[ends-with(lower-case(@href), '.pdf')]"),
+        vec!["Spec"]
+    );
+    assert_eq!(cs.select(0, "xpath:ends-with('a', 'abc')"), "false");
+}
+
+#[test]
+fn shims_are_off_by_default() {
+    let mut cs = ChadSelect::new();
+    cs.add_html(PAGE.to_string());
+    assert!(cs
+        .query(-1, r"xpath://a[matches(@href, '\.pdf$', 'i')]")
+        .is_empty());
+    assert!(cs.query(-1, "xpath:lower-case(//h1)").is_empty());
+    assert_eq!(cs.query(-1, "xpath://a").len(), 3);
+}
+
+#[test]
+fn real_regexes_are_refused() {
+    let cs = shimmed();
+    assert!(cs.query(-1, r"xpath://a[matches(@href, '\d+')]").is_empty());
+    assert!(cs
+        .prepare(r"xpath://a[matches(@href, '[a-z]+\.pdf')]")
+        .is_err());
+    assert!(cs.prepare("xpath:replace(//p, 'a', '$1')").is_err());
+    assert!(cs
+        .prepare("xpath://a[matches(@href, @data-pattern)]")
+        .is_err());
+    assert!(cs.prepare(r"xpath://a[matches(@href, '\.pdf')]").is_ok());
+}
+
+#[test]
+fn long_inputs() {
+    let mut cs = ChadSelect::builder().xpath_shims(true).build();
+    cs.add_html(format!(
+        "<p>{}</p><i>{}</i>",
+        "a-".repeat(200),
+        "a-".repeat(1000)
+    ));
+    assert_eq!(cs.select(0, "xpath:count(tokenize(//i, '-'))"), "1001");
+    // A separator whose occurrences can overlap is matched left to right
+    // recursively, and evaluation gives up past the depth limit.
+    assert_eq!(
+        cs.select(0, "xpath:string-length(replace(//p, 'a-a', 'X'))"),
+        "200"
+    );
+    assert!(cs.query(-1, "xpath:replace(//i, 'a-a', 'X')").is_empty());
+}
@@ -1,4 +1,5 @@
 //! Tests for the XPath 1.0 extraction engine.
+#![cfg(feature = "xpath")]
 
 use chadselect::ChadSelect;
 